@@ -0,0 +1,152 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::Error;
+
+/// Generates [`Language`] from its codes, and whether they can be translated from or to.
+macro_rules! languages {
+    ($($variant:ident => $code:literal, source: $source:literal, target: $target:literal;)*) => {
+        /// A language supported by DeepL.
+        ///
+        /// Use [`Language::Custom`] for languages DeepL has added since, which aren't validated.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Language {
+            $(
+                #[doc = concat!("`", $code, "`")]
+                $variant,
+            )*
+            /// Any other language code, sent to DeepL as-is.
+            Custom(String),
+        }
+
+        impl Language {
+            /// The code DeepL uses for the language, ex. "EN-US".
+            #[must_use]
+            pub fn code(&self) -> &str {
+                match self {
+                    $(Self::$variant => $code,)*
+                    Self::Custom(code) => code,
+                }
+            }
+
+            /// Whether texts in the language can be translated.
+            #[must_use]
+            pub fn is_source(&self) -> bool {
+                match self {
+                    $(Self::$variant => $source,)*
+                    Self::Custom(_) => true,
+                }
+            }
+
+            /// Whether texts can be translated to the language.
+            #[must_use]
+            pub fn is_target(&self) -> bool {
+                match self {
+                    $(Self::$variant => $target,)*
+                    Self::Custom(_) => true,
+                }
+            }
+
+            /// Private method that finds the known language with the given code, ignoring case.
+            fn from_code(code: &str) -> Option<Self> {
+                $(
+                    if code.eq_ignore_ascii_case($code) {
+                        return Some(Self::$variant);
+                    }
+                )*
+
+                None
+            }
+        }
+    };
+}
+
+// EN and PT can only be translated to as one of their variants.
+languages! {
+    Arabic => "AR", source: true, target: true;
+    Bulgarian => "BG", source: true, target: true;
+    Czech => "CS", source: true, target: true;
+    Danish => "DA", source: true, target: true;
+    German => "DE", source: true, target: true;
+    Greek => "EL", source: true, target: true;
+    English => "EN", source: true, target: false;
+    EnglishBritish => "EN-GB", source: false, target: true;
+    EnglishAmerican => "EN-US", source: false, target: true;
+    Spanish => "ES", source: true, target: true;
+    Estonian => "ET", source: true, target: true;
+    Finnish => "FI", source: true, target: true;
+    French => "FR", source: true, target: true;
+    Hungarian => "HU", source: true, target: true;
+    Indonesian => "ID", source: true, target: true;
+    Italian => "IT", source: true, target: true;
+    Japanese => "JA", source: true, target: true;
+    Korean => "KO", source: true, target: true;
+    Lithuanian => "LT", source: true, target: true;
+    Latvian => "LV", source: true, target: true;
+    Norwegian => "NB", source: true, target: true;
+    Dutch => "NL", source: true, target: true;
+    Polish => "PL", source: true, target: true;
+    Portuguese => "PT", source: true, target: false;
+    PortugueseBrazilian => "PT-BR", source: false, target: true;
+    PortugueseEuropean => "PT-PT", source: false, target: true;
+    Romanian => "RO", source: true, target: true;
+    Russian => "RU", source: true, target: true;
+    Slovak => "SK", source: true, target: true;
+    Slovenian => "SL", source: true, target: true;
+    Swedish => "SV", source: true, target: true;
+    Turkish => "TR", source: true, target: true;
+    Ukrainian => "UK", source: true, target: true;
+    Chinese => "ZH", source: true, target: true;
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Parses a known language code, ignoring case. Unknown codes are an error, use
+/// [`Language::Custom`] to send them anyway.
+impl FromStr for Language {
+    type Err = Error;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::from_code(code).ok_or_else(|| Error::UnknownLanguage(code.to_string()))
+    }
+}
+
+/// Codes DeepL has added since are kept as [`Language::Custom`].
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(de)?;
+        Ok(Self::from_code(&code).unwrap_or(Self::Custom(code)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        assert_eq!(
+            "en-us".parse::<Language>().unwrap(),
+            Language::EnglishAmerican
+        );
+        assert_eq!(Language::Japanese.to_string(), "JA");
+        assert_eq!(Language::Custom("XX".to_string()).code(), "XX");
+
+        assert!(matches!(
+            "XX".parse::<Language>(),
+            Err(Error::UnknownLanguage(code)) if code == "XX"
+        ));
+    }
+
+    #[test]
+    fn variants_are_only_targets() {
+        assert!(Language::English.is_source() && !Language::English.is_target());
+        assert!(!Language::EnglishAmerican.is_source() && Language::EnglishAmerican.is_target());
+        assert!(Language::Custom("XX".to_string()).is_target());
+    }
+}
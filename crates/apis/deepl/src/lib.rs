@@ -20,8 +20,8 @@
 //!
 //! // Translate Text
 //! let texts = TranslatableTextList {
-//!     source_language: Some("DE".to_string()),
-//!     target_language: "EN-US".to_string(),
+//!     source_language: Some(Language::German),
+//!     target_language: Language::EnglishAmerican,
 //!     texts: vec!("ja".to_string()),
 //! };
 //! let translated = deepl.translate(None, texts).unwrap();
@@ -36,9 +36,12 @@
 //!
 //! The main API functions are documented in the [DeepL] struct.
 
+mod language;
 mod serde_impls;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Range};
+
+pub use language::Language;
 
 /// Information about API usage & limits for this account.
 #[derive(Debug)]
@@ -63,6 +66,7 @@ pub struct LanguageInformation {
 }
 
 /// Translation option that controls the splitting of sentences before the translation.
+#[derive(Clone, Copy)]
 pub enum SplitSentences {
     /// Don't split sentences.
     None,
@@ -73,6 +77,7 @@ pub enum SplitSentences {
 }
 
 /// Translation option that controls the desired translation formality.
+#[derive(Clone, Copy)]
 pub enum Formality {
     /// Default formality.
     Default,
@@ -82,7 +87,28 @@ pub enum Formality {
     Less,
 }
 
+/// Translation option that sets which kind of markup the texts contain.
+#[derive(Clone, Copy)]
+pub enum TagHandling {
+    /// Keep XML tags intact.
+    Xml,
+    /// Keep HTML tags intact.
+    Html,
+}
+
+/// Translation option that selects which of DeepL's models translates the texts.
+#[derive(Clone, Copy)]
+pub enum ModelType {
+    /// Use the next-gen model, failing if it doesn't support the language pair.
+    QualityOptimized,
+    /// Use the next-gen model if it supports the language pair, otherwise the classic one.
+    PreferQualityOptimized,
+    /// Use the classic model, which is faster.
+    LatencyOptimized,
+}
+
 /// Custom [flags for the translation request](https://www.deepl.com/docs-api/translating-text/request/).
+#[derive(Clone, Default)]
 pub struct TranslationOptions {
     /// Sets whether the translation engine should first split the input into sentences. This is enabled by default.
     pub split_sentences: Option<SplitSentences>,
@@ -90,6 +116,21 @@ pub struct TranslationOptions {
     pub preserve_formatting: Option<bool>,
     /// Sets whether the translated text should lean towards formal or informal language.
     pub formality: Option<Formality>,
+    /// Sets which kind of markup the texts contain, so that it's kept intact in the translation.
+    pub tag_handling: Option<TagHandling>,
+    /// XML tags whose content is never translated.
+    pub ignore_tags: Option<Vec<String>>,
+    /// XML tags that never split sentences, ex. ones used for emphasis.
+    pub non_splitting_tags: Option<Vec<String>>,
+    /// XML tags that always split sentences, ex. ones used for paragraphs.
+    pub splitting_tags: Option<Vec<String>>,
+    /// Sets whether the structure of XML texts is detected automatically. This is enabled by default,
+    /// disable it to only split sentences at `splitting_tags`.
+    pub outline_detection: Option<bool>,
+    /// Text that isn't translated, but is used to improve the translation, ex. what the texts are about.
+    pub context: Option<String>,
+    /// Sets which model translates the texts.
+    pub model_type: Option<ModelType>,
 }
 
 /// Holds a list of strings to be translated.
@@ -97,9 +138,9 @@ pub struct TranslationOptions {
 pub struct TranslatableTextList {
     /// Source language, if known. Will be auto-detected by the DeepL API
     /// if not provided.
-    pub source_language: Option<String>,
+    pub source_language: Option<Language>,
     /// Target language (required).
-    pub target_language: String,
+    pub target_language: Language,
     /// List of texts that are supposed to be translated.
     pub texts: Vec<String>,
 }
@@ -119,6 +160,73 @@ struct TranslatedTextList {
     translations: Vec<TranslatedText>,
 }
 
+/// The outcome of translating one chunk of a [batch](DeepL::translate_batch).
+#[derive(Debug)]
+pub struct TranslatedChunk {
+    /// Indices of the texts in the chunk, in the list that was passed in.
+    pub texts: Range<usize>,
+    /// The translations of the texts in the chunk, in the same order, or why they failed.
+    pub result: Result<Vec<TranslatedText>, Error>,
+}
+
+/// Rephrasing option that controls the writing style of the improved text.
+pub enum WritingStyle {
+    /// Default writing style.
+    Default,
+    /// Write in plain, easy to understand language.
+    Simple,
+    /// Write in a professional, business-like language.
+    Business,
+    /// Write in a scientific, academic language.
+    Academic,
+    /// Write in a relaxed, conversational language.
+    Casual,
+}
+
+/// Rephrasing option that controls the tone of the improved text.
+pub enum Tone {
+    /// Default tone.
+    Default,
+    /// Sound excited and positive.
+    Enthusiastic,
+    /// Sound warm and approachable.
+    Friendly,
+    /// Sound self-assured and decisive.
+    Confident,
+    /// Sound tactful and considerate.
+    Diplomatic,
+}
+
+/// Custom [flags for the rephrasing request](https://developers.deepl.com/docs/api-reference/improve-text).
+///
+/// Only one of `writing_style` and `tone` can be set per request.
+#[derive(Default)]
+pub struct RephraseOptions {
+    /// Language to write the improved text in. Defaults to the language of the text.
+    pub target_language: Option<Language>,
+    /// Sets the writing style of the improved text.
+    pub writing_style: Option<WritingStyle>,
+    /// Sets the tone of the improved text.
+    pub tone: Option<Tone>,
+}
+
+/// Holds one unit of improved text.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImprovedText {
+    /// Language that DeepL auto-detected for the original text.
+    pub detected_source_language: String,
+    /// Language the improved text is written in.
+    pub target_language: String,
+    /// Improved text.
+    pub text: String,
+}
+
+// Only needed for JSON deserialization.
+#[derive(Debug)]
+struct ImprovedTextList {
+    improvements: Vec<ImprovedText>,
+}
+
 // Only needed for JSON deserialization.
 #[derive(Debug)]
 struct ServerErrorMessage {
@@ -139,6 +247,8 @@ struct ServerErrorMessage {
 /// If you get an [AuthorizationError](ErrorKind::AuthorizationError), then something was wrong with your API key, for example.
 pub struct DeepL {
     api_key: String,
+    agent: ureq::Agent,
+    endpoint: Option<String>,
 }
 
 /// Implements the actual REST API. See also the [online documentation](https://www.deepl.com/docs-api/).
@@ -149,41 +259,76 @@ impl DeepL {
     /// Should you ever need to use more than one DeepL account in our program, then you can create one
     /// instance for each account / API key.
     pub fn new(api_key: String) -> DeepL {
-        DeepL { api_key }
+        Self::with_agent(api_key, ureq::agent())
+    }
+
+    /// Same as [`DeepL::new`], but sends all requests through the given agent, which
+    /// allows sharing connections, timeouts and proxy settings with the rest of the program.
+    pub fn with_agent(api_key: String, agent: ureq::Agent) -> DeepL {
+        DeepL {
+            api_key,
+            agent,
+            endpoint: None,
+        }
+    }
+
+    /// Sends all requests to the given base URL instead of DeepL's own servers,
+    /// ex. a local server replaying recorded responses.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> DeepL {
+        self.endpoint = Some(endpoint.into());
+        self
     }
 
+    /// Most texts DeepL accepts in a single request.
+    pub const MAX_TEXTS_PER_REQUEST: usize = 50;
+
+    /// Most bytes of encoded text sent in a single request, leaving room for the other
+    /// parameters within DeepL's limit of 128 KiB per request.
+    pub const MAX_TEXT_BYTES_PER_REQUEST: usize = 120 * 1024;
+
     /// Private method that performs the HTTP calls.
+    ///
+    /// The parameters are sent form-encoded in the body, so that texts aren't limited by the
+    /// length of the URL or written to the logs of proxies along the way.
     fn http_request(
         &self,
         url: &'static str,
-        query: &[(&'static str, Cow<str>)],
+        params: &[(&'static str, Cow<str>)],
     ) -> Result<ureq::Response, Error> {
-        let url = match self.api_key.ends_with(":fx") {
-            true => format!("https://api-free.deepl.com/v2{url}"),
-            false => format!("https://api.deepl.com/v2{url}"),
+        let url = match (&self.endpoint, self.api_key.ends_with(":fx")) {
+            (Some(endpoint), _) => format!("{endpoint}/v2{url}"),
+            (None, true) => format!("https://api-free.deepl.com/v2{url}"),
+            (None, false) => format!("https://api.deepl.com/v2{url}"),
         };
 
-        let mut request = ureq::post(&url).query("auth_key", &self.api_key);
+        let form = params
+            .iter()
+            .map(|(key, value)| (*key, value.as_ref()))
+            .collect::<Vec<_>>();
 
-        for (key, value) in query {
-            request = request.query(key, value);
-        }
+        let request = self
+            .agent
+            .post(&url)
+            .set("Authorization", &format!("DeepL-Auth-Key {}", self.api_key));
 
-        match request.call() {
-            Ok(response) => match response.status() {
-                200..=299 => Ok(response),
-                401 | 403 => Err(Error::AuthorizationError),
-                status => {
-                    // DeepL sends back error messages in the response body.
-                    // Try to fetch them to construct more helpful exceptions.
-                    match response.into_json::<ServerErrorMessage>() {
-                        Ok(server_error) => Err(Error::ServerError(server_error.message)),
-                        _ => Err(Error::ServerError(status.to_string())),
-                    }
-                }
-            },
+        // ureq treats error statuses as errors, but their bodies are still needed below.
+        let response = match request.send_form(&form) {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(Error::ServerError(e.to_string())),
+        };
 
-            Err(e) => Err(Error::ServerError(e.to_string())),
+        match response.status() {
+            200..=299 => Ok(response),
+            401 | 403 => Err(Error::AuthorizationError),
+            456 => Err(Error::QuotaExceeded),
+            status => {
+                // DeepL sends back error messages in the response body.
+                // Try to fetch them to construct more helpful exceptions.
+                match response.into_json::<ServerErrorMessage>() {
+                    Ok(server_error) => Err(Error::ServerError(server_error.message)),
+                    _ => Err(Error::ServerError(status.to_string())),
+                }
+            }
         }
     }
 
@@ -228,13 +373,23 @@ impl DeepL {
         options: Option<TranslationOptions>,
         text_list: TranslatableTextList,
     ) -> Result<Vec<TranslatedText>, Error> {
-        let mut query = vec![("target_lang", text_list.target_language.into())];
+        if let Some(source_language) = text_list.source_language.as_ref() {
+            if !source_language.is_source() {
+                return Err(Error::UnsupportedSourceLanguage(source_language.clone()));
+            }
+        }
+
+        if !text_list.target_language.is_target() {
+            return Err(Error::UnsupportedTargetLanguage(text_list.target_language));
+        }
+
+        let mut params = vec![("target_lang", text_list.target_language.to_string().into())];
 
         if let Some(source_language_content) = text_list.source_language {
-            query.push(("source_lang", source_language_content.into()));
+            params.push(("source_lang", source_language_content.to_string().into()));
         }
 
-        query.extend(
+        params.extend(
             text_list
                 .texts
                 .into_iter()
@@ -243,7 +398,7 @@ impl DeepL {
 
         if let Some(opt) = options {
             if let Some(split_sentences) = opt.split_sentences {
-                query.push((
+                params.push((
                     "split_sentences",
                     match split_sentences {
                         SplitSentences::None => "0",
@@ -254,7 +409,7 @@ impl DeepL {
                 ));
             }
             if let Some(preserve_formatting) = opt.preserve_formatting {
-                query.push((
+                params.push((
                     "preserve_formatting",
                     match preserve_formatting {
                         false => "0",
@@ -264,7 +419,7 @@ impl DeepL {
                 ));
             }
             if let Some(formality) = opt.formality {
-                query.push((
+                params.push((
                     "formality",
                     match formality {
                         Formality::Default => "default",
@@ -274,20 +429,201 @@ impl DeepL {
                     .into(),
                 ));
             }
+            if let Some(tag_handling) = opt.tag_handling {
+                params.push((
+                    "tag_handling",
+                    match tag_handling {
+                        TagHandling::Xml => "xml",
+                        TagHandling::Html => "html",
+                    }
+                    .into(),
+                ));
+            }
+            if let Some(ignore_tags) = opt.ignore_tags {
+                params.push(("ignore_tags", ignore_tags.join(",").into()));
+            }
+            if let Some(non_splitting_tags) = opt.non_splitting_tags {
+                params.push(("non_splitting_tags", non_splitting_tags.join(",").into()));
+            }
+            if let Some(splitting_tags) = opt.splitting_tags {
+                params.push(("splitting_tags", splitting_tags.join(",").into()));
+            }
+            if let Some(outline_detection) = opt.outline_detection {
+                params.push((
+                    "outline_detection",
+                    match outline_detection {
+                        false => "0",
+                        true => "1",
+                    }
+                    .into(),
+                ));
+            }
+            if let Some(context) = opt.context {
+                params.push(("context", context.into()));
+            }
+            if let Some(model_type) = opt.model_type {
+                params.push((
+                    "model_type",
+                    match model_type {
+                        ModelType::QualityOptimized => "quality_optimized",
+                        ModelType::PreferQualityOptimized => "prefer_quality_optimized",
+                        ModelType::LatencyOptimized => "latency_optimized",
+                    }
+                    .into(),
+                ));
+            }
         }
 
-        self.http_request("/translate", &query)?
+        self.http_request("/translate", &params)?
             .into_json::<TranslatedTextList>()
             .map(|c| c.translations)
             .map_err(|_| Error::DeserializationError)
     }
+
+    /// Translate any number of [text chunks](TranslatableTextList), split into as many requests
+    /// as needed to stay within [`DeepL::MAX_TEXTS_PER_REQUEST`] and
+    /// [`DeepL::MAX_TEXT_BYTES_PER_REQUEST`]. Texts larger than the size limit are sent on their own.
+    ///
+    /// Every chunk is translated even if earlier ones failed, and the chunks are returned in the
+    /// order of the texts they contain.
+    pub fn translate_batch(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Vec<TranslatedChunk> {
+        let TranslatableTextList {
+            source_language,
+            target_language,
+            mut texts,
+        } = text_list;
+
+        let ranges = Self::chunk_ranges(&texts);
+        let mut chunks = Vec::with_capacity(ranges.len());
+
+        // Taken from the back, so that the texts don't have to be cloned or shifted.
+        for range in ranges.into_iter().rev() {
+            let chunk = TranslatableTextList {
+                source_language: source_language.clone(),
+                target_language: target_language.clone(),
+                texts: texts.split_off(range.start),
+            };
+
+            chunks.push(TranslatedChunk {
+                result: self.translate(options.clone(), chunk),
+                texts: range,
+            });
+        }
+
+        chunks.reverse();
+        chunks
+    }
+
+    /// Private method that splits texts into the ranges sent in each request of a batch.
+    fn chunk_ranges(texts: &[String]) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        let mut size = 0;
+
+        for (i, text) in texts.iter().enumerate() {
+            // Each text is sent as `&text=...`, form-encoded.
+            let text_size = "&text=".len()
+                + text
+                    .bytes()
+                    .map(|b| match b {
+                        _ if b.is_ascii_alphanumeric() => 1,
+                        b'*' | b'-' | b'.' | b'_' | b' ' => 1,
+                        _ => 3,
+                    })
+                    .sum::<usize>();
+
+            if i > start
+                && (i - start == Self::MAX_TEXTS_PER_REQUEST
+                    || size + text_size > Self::MAX_TEXT_BYTES_PER_REQUEST)
+            {
+                ranges.push(start..i);
+                start = i;
+                size = 0;
+            }
+
+            size += text_size;
+        }
+
+        if start < texts.len() {
+            ranges.push(start..texts.len());
+        }
+
+        ranges
+    }
+
+    /// Improve the spelling, grammar and wording of a text. You can pass in optional
+    /// [rephrasing flags](RephraseOptions) to change its style or tone.
+    ///
+    /// This uses DeepL Write, which is only available to Pro accounts.
+    /// See also the [vendor documentation](https://developers.deepl.com/docs/api-reference/improve-text).
+    pub fn rephrase(
+        &self,
+        text: String,
+        options: Option<RephraseOptions>,
+    ) -> Result<ImprovedText, Error> {
+        let mut params = vec![("text", text.into())];
+
+        if let Some(opt) = options {
+            if let Some(target_language) = opt.target_language {
+                if !target_language.is_target() {
+                    return Err(Error::UnsupportedTargetLanguage(target_language));
+                }
+
+                params.push(("target_lang", target_language.to_string().into()));
+            }
+            if let Some(writing_style) = opt.writing_style {
+                params.push((
+                    "writing_style",
+                    match writing_style {
+                        WritingStyle::Default => "default",
+                        WritingStyle::Simple => "simple",
+                        WritingStyle::Business => "business",
+                        WritingStyle::Academic => "academic",
+                        WritingStyle::Casual => "casual",
+                    }
+                    .into(),
+                ));
+            }
+            if let Some(tone) = opt.tone {
+                params.push((
+                    "tone",
+                    match tone {
+                        Tone::Default => "default",
+                        Tone::Enthusiastic => "enthusiastic",
+                        Tone::Friendly => "friendly",
+                        Tone::Confident => "confident",
+                        Tone::Diplomatic => "diplomatic",
+                    }
+                    .into(),
+                ));
+            }
+        }
+
+        self.http_request("/write/rephrase", &params)?
+            .into_json::<ImprovedTextList>()
+            .map_err(|_| Error::DeserializationError)?
+            .improvements
+            .into_iter()
+            .next()
+            .ok_or(Error::DeserializationError)
+    }
 }
 
 #[derive(Debug)]
 pub enum Error {
     AuthorizationError,
+    /// The character limit of the account has been reached.
+    QuotaExceeded,
     ServerError(String),
     DeserializationError,
+    /// The code isn't one of the known [languages](Language).
+    UnknownLanguage(String),
+    UnsupportedSourceLanguage(Language),
+    UnsupportedTargetLanguage(Language),
 }
 
 impl std::fmt::Display for Error {
@@ -296,6 +632,7 @@ impl std::fmt::Display for Error {
             Error::AuthorizationError => {
                 write!(f, "Authorization failed, is your API key correct?")
             }
+            Error::QuotaExceeded => write!(f, "The translation quota has been exceeded."),
             Error::ServerError(message) => write!(
                 f,
                 "An error occurred while communicating with the DeepL server: '{message}'"
@@ -304,6 +641,13 @@ impl std::fmt::Display for Error {
                 f,
                 "An error occurred while deserializing the response data."
             ),
+            Error::UnknownLanguage(code) => write!(f, "'{code}' is not a known language code."),
+            Error::UnsupportedSourceLanguage(language) => {
+                write!(f, "'{language}' can't be translated from.")
+            }
+            Error::UnsupportedTargetLanguage(language) => {
+                write!(f, "'{language}' can't be translated to.")
+            }
         }
     }
 }
@@ -312,6 +656,278 @@ impl std::error::Error for Error {}
 
 #[cfg(test)]
 mod tests {
+    use httpmock::{Method::POST, MockServer};
+
+    use super::*;
+
+    const KEY: &str = "test-key";
+
+    fn replay(path: &str, status: u16, fixture: &str) -> (MockServer, DeepL) {
+        let server = MockServer::start();
+
+        server.mock(|when, then| {
+            when.method(POST)
+                .path(path)
+                .header("authorization", format!("DeepL-Auth-Key {KEY}"));
+            then.status(status)
+                .header("content-type", "application/json")
+                .body(fixture);
+        });
+
+        let deepl = DeepL::new(KEY.to_string()).with_endpoint(server.base_url());
+        (server, deepl)
+    }
+
+    #[test]
+    fn usage_information() {
+        let (_server, deepl) = replay("/v2/usage", 200, include_str!("../fixtures/usage.json"));
+
+        let usage_information = deepl.usage_information().unwrap();
+        assert_eq!(usage_information.character_limit, 500_000);
+        assert_eq!(usage_information.character_count, 180_118);
+    }
+
+    #[test]
+    fn target_languages() {
+        let (_server, deepl) = replay(
+            "/v2/languages",
+            200,
+            include_str!("../fixtures/target_languages.json"),
+        );
+
+        let target_languages = deepl.target_languages().unwrap();
+        assert_eq!(target_languages.len(), 3);
+        assert_eq!(target_languages[1].language, "EN-US");
+        assert_eq!(target_languages.last().unwrap().name, "Chinese");
+    }
+
+    #[test]
+    fn translate() {
+        let (_server, deepl) = replay(
+            "/v2/translate",
+            200,
+            include_str!("../fixtures/translate.json"),
+        );
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::EnglishAmerican,
+            texts: vec!["ja".to_string(), "nein".to_string()],
+        };
+
+        assert_eq!(
+            deepl.translate(None, texts).unwrap(),
+            vec![
+                TranslatedText {
+                    detected_source_language: "DE".to_string(),
+                    text: "yes".to_string(),
+                },
+                TranslatedText {
+                    detected_source_language: "DE".to_string(),
+                    text: "no".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn batches_are_chunked_by_count_and_size() {
+        let short = vec!["ja".to_string(); 120];
+        assert_eq!(DeepL::chunk_ranges(&short), vec![0..50, 50..100, 100..120]);
+
+        // Every character is encoded as three bytes per byte of UTF-8.
+        let long = vec!["あ".repeat(6_000); 5];
+        assert_eq!(DeepL::chunk_ranges(&long), vec![0..2, 2..4, 4..5]);
+
+        let huge = vec!["ja".to_string(), "x".repeat(200_000), "nein".to_string()];
+        assert_eq!(DeepL::chunk_ranges(&huge), vec![0..1, 1..2, 2..3]);
+
+        assert!(DeepL::chunk_ranges(&[]).is_empty());
+    }
+
+    #[test]
+    fn translate_batch() {
+        let (_server, deepl) = replay(
+            "/v2/translate",
+            200,
+            include_str!("../fixtures/translate.json"),
+        );
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::EnglishAmerican,
+            texts: vec!["ja".to_string(); 60],
+        };
+
+        let chunks = deepl.translate_batch(None, texts);
+
+        assert_eq!(
+            chunks.iter().map(|c| c.texts.clone()).collect::<Vec<_>>(),
+            vec![0..50, 50..60]
+        );
+        assert!(chunks.iter().all(|c| c.result.is_ok()));
+    }
+
+    #[test]
+    fn rephrase() {
+        let (_server, deepl) = replay(
+            "/v2/write/rephrase",
+            200,
+            include_str!("../fixtures/rephrase.json"),
+        );
+
+        let options = RephraseOptions {
+            tone: Some(Tone::Friendly),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            deepl
+                .rephrase("stream is at 8 dont be late".to_string(), Some(options))
+                .unwrap(),
+            ImprovedText {
+                detected_source_language: "en".to_string(),
+                target_language: "en-US".to_string(),
+                text: "The stream starts at 8, don't be late!".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parameters_are_sent_in_the_body() {
+        let server = MockServer::start();
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v2/translate")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body_contains("target_lang=EN-US")
+                .body_contains("text=ja+nein");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(include_str!("../fixtures/translate.json"));
+        });
+
+        let deepl = DeepL::new(KEY.to_string()).with_endpoint(server.base_url());
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::EnglishAmerican,
+            texts: vec!["ja nein".to_string()],
+        };
+
+        deepl.translate(None, texts).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn translation_options() {
+        let server = MockServer::start();
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v2/translate")
+                .body_contains("tag_handling=html")
+                .body_contains("ignore_tags=code%2Cpre")
+                .body_contains("outline_detection=0")
+                .body_contains("context=A+schedule+post")
+                .body_contains("model_type=prefer_quality_optimized");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(include_str!("../fixtures/translate.json"));
+        });
+
+        let deepl = DeepL::new(KEY.to_string()).with_endpoint(server.base_url());
+
+        let options = TranslationOptions {
+            tag_handling: Some(TagHandling::Html),
+            ignore_tags: Some(vec!["code".to_string(), "pre".to_string()]),
+            outline_detection: Some(false),
+            context: Some("A schedule post".to_string()),
+            model_type: Some(ModelType::PreferQualityOptimized),
+            ..Default::default()
+        };
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::EnglishAmerican,
+            texts: vec!["<p>ja</p>".to_string()],
+        };
+
+        deepl.translate(Some(options), texts).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn languages_are_validated_before_sending() {
+        let deepl = DeepL::new(KEY.to_string()).with_endpoint("http://127.0.0.1:9");
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::English,
+            texts: vec!["ja".to_string()],
+        };
+
+        match deepl.translate(None, texts) {
+            Err(e @ Error::UnsupportedTargetLanguage(Language::English)) => {
+                assert_eq!(e.to_string(), "'EN' can't be translated to.")
+            }
+            res => panic!("expected an unsupported language, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn server_error_message() {
+        let (_server, deepl) = replay(
+            "/v2/translate",
+            400,
+            include_str!("../fixtures/wrong_language.json"),
+        );
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::Custom("NONEXISTING".to_string()),
+            texts: vec!["ja".to_string()],
+        };
+
+        match deepl.translate(None, texts) {
+            Err(Error::ServerError(message)) => {
+                assert_eq!(message, "Value for 'target_lang' not supported.")
+            }
+            res => panic!("expected a server error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn quota_exceeded() {
+        let (_server, deepl) = replay("/v2/translate", 456, r#"{"message": "Quota Exceeded"}"#);
+
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: Language::EnglishAmerican,
+            texts: vec!["ja".to_string()],
+        };
+
+        match deepl.translate(None, texts) {
+            Err(Error::QuotaExceeded) => {}
+            res => panic!("expected the quota to be exceeded, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn unauthorized() {
+        let (_server, deepl) = replay("/v2/usage", 403, "");
+
+        assert!(matches!(
+            deepl.usage_information(),
+            Err(Error::AuthorizationError)
+        ));
+    }
+}
+
+/// Tests against the real API, which need `DEEPL_API_KEY` to be set and use up quota.
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
     use super::*;
 
     #[test]
@@ -343,8 +959,8 @@ mod tests {
             (
                 None,
                 TranslatableTextList {
-                    source_language: Some("DE".to_string()),
-                    target_language: "EN-US".to_string(),
+                    source_language: Some(Language::German),
+                    target_language: Language::EnglishAmerican,
                     texts: vec!["ja".to_string()],
                 },
                 vec![TranslatedText {
@@ -357,10 +973,11 @@ mod tests {
                     split_sentences: None,
                     preserve_formatting: Some(true),
                     formality: None,
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("DE".to_string()),
-                    target_language: "EN-US".to_string(),
+                    source_language: Some(Language::German),
+                    target_language: Language::EnglishAmerican,
                     texts: vec!["ja\n nein".to_string()],
                 },
                 vec![TranslatedText {
@@ -373,10 +990,11 @@ mod tests {
                     split_sentences: Some(SplitSentences::None),
                     preserve_formatting: None,
                     formality: None,
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("DE".to_string()),
-                    target_language: "EN-US".to_string(),
+                    source_language: Some(Language::German),
+                    target_language: Language::EnglishAmerican,
                     texts: vec!["Ja. Nein.".to_string()],
                 },
                 vec![TranslatedText {
@@ -389,10 +1007,11 @@ mod tests {
                     split_sentences: None,
                     preserve_formatting: None,
                     formality: Some(Formality::More),
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("EN".to_string()),
-                    target_language: "DE".to_string(),
+                    source_language: Some(Language::English),
+                    target_language: Language::German,
                     texts: vec!["Please go home.".to_string()],
                 },
                 vec![TranslatedText {
@@ -405,10 +1024,11 @@ mod tests {
                     split_sentences: None,
                     preserve_formatting: None,
                     formality: Some(Formality::Less),
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("EN".to_string()),
-                    target_language: "DE".to_string(),
+                    source_language: Some(Language::English),
+                    target_language: Language::German,
                     texts: vec!["Please go home.".to_string()],
                 },
                 vec![TranslatedText {
@@ -427,8 +1047,8 @@ mod tests {
     fn translate_empty() {
         let key = std::env::var("DEEPL_API_KEY").unwrap();
         let texts = TranslatableTextList {
-            source_language: Some("DE".to_string()),
-            target_language: "EN-US".to_string(),
+            source_language: Some(Language::German),
+            target_language: Language::EnglishAmerican,
             texts: vec![],
         };
         DeepL::new(key).translate(None, texts).unwrap();
@@ -440,7 +1060,7 @@ mod tests {
         let key = std::env::var("DEEPL_API_KEY").unwrap();
         let texts = TranslatableTextList {
             source_language: None,
-            target_language: "NONEXISTING".to_string(),
+            target_language: Language::Custom("NONEXISTING".to_string()),
             texts: vec!["ja".to_string()],
         };
         DeepL::new(key).translate(None, texts).unwrap();
@@ -451,8 +1071,8 @@ mod tests {
     fn translate_unauthorized() {
         let key = "wrong_key".to_string();
         let texts = TranslatableTextList {
-            source_language: Some("DE".to_string()),
-            target_language: "EN-US".to_string(),
+            source_language: Some(Language::German),
+            target_language: Language::EnglishAmerican,
             texts: vec!["ja".to_string()],
         };
         DeepL::new(key).translate(None, texts).unwrap();
@@ -7,8 +7,8 @@ use serde::{
 };
 
 use crate::{
-    LanguageInformation, ServerErrorMessage, TranslatableTextList, TranslatedText,
-    TranslatedTextList, UsageInformation,
+    ImprovedText, ImprovedTextList, Language, LanguageInformation, ServerErrorMessage,
+    TranslatableTextList, TranslatedText, TranslatedTextList, UsageInformation,
 };
 
 #[automatically_derived]
@@ -325,7 +325,7 @@ impl<'de> serde::Deserialize<'de> for TranslatableTextList {
             }
             #[inline]
             fn visit_seq<A: SeqAccess<'de>>(self, mut s: A) -> Result<Self::Value, A::Error> {
-                let f0 = match SeqAccess::next_element::<Option<String>>(&mut s)? {
+                let f0 = match SeqAccess::next_element::<Option<Language>>(&mut s)? {
                     Some(v) => v,
                     None => {
                         return Err(Error::invalid_length(
@@ -334,7 +334,7 @@ impl<'de> serde::Deserialize<'de> for TranslatableTextList {
                         ));
                     }
                 };
-                let f1 = match SeqAccess::next_element::<String>(&mut s)? {
+                let f1 = match SeqAccess::next_element::<Language>(&mut s)? {
                     Some(v) => v,
                     None => {
                         return Err(Error::invalid_length(
@@ -360,8 +360,8 @@ impl<'de> serde::Deserialize<'de> for TranslatableTextList {
             }
             #[inline]
             fn visit_map<A: MapAccess<'de>>(self, mut m: A) -> Result<Self::Value, A::Error> {
-                let mut f0: Option<Option<String>> = None;
-                let mut f1: Option<String> = None;
+                let mut f0: Option<Option<Language>> = None;
+                let mut f1: Option<Language> = None;
                 let mut f2: Option<Vec<String>> = None;
 
                 while let Some(key) = MapAccess::next_key::<Field>(&mut m)? {
@@ -372,7 +372,7 @@ impl<'de> serde::Deserialize<'de> for TranslatableTextList {
                                     "source_language",
                                 ));
                             }
-                            f0 = Some(MapAccess::next_value::<Option<String>>(&mut m)?);
+                            f0 = Some(MapAccess::next_value::<Option<Language>>(&mut m)?);
                         }
                         Field::Field1 => {
                             if Option::is_some(&f1) {
@@ -380,7 +380,7 @@ impl<'de> serde::Deserialize<'de> for TranslatableTextList {
                                     "target_language",
                                 ));
                             }
-                            f1 = Some(MapAccess::next_value::<String>(&mut m)?);
+                            f1 = Some(MapAccess::next_value::<Language>(&mut m)?);
                         }
                         Field::Field2 => {
                             if Option::is_some(&f2) {
@@ -649,6 +649,258 @@ impl<'de> serde::Deserialize<'de> for TranslatedTextList {
     }
 }
 
+impl<'de> serde::Deserialize<'de> for ImprovedText {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        enum Field {
+            Field0,
+            Field1,
+            Field2,
+            Ignore,
+        }
+        struct FieldVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FieldVisitor {
+            type Value = Field;
+            fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+                Formatter::write_str(fmt, "field identifier")
+            }
+            fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+                match val {
+                    0 => Ok(Field::Field0),
+                    1 => Ok(Field::Field1),
+                    2 => Ok(Field::Field2),
+                    _ => Ok(Field::Ignore),
+                }
+            }
+            fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+                match val {
+                    "detected_source_language" => Ok(Field::Field0),
+                    "target_language" => Ok(Field::Field1),
+                    "text" => Ok(Field::Field2),
+                    _ => Ok(Field::Ignore),
+                }
+            }
+            fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+                match val {
+                    b"detected_source_language" => Ok(Field::Field0),
+                    b"target_language" => Ok(Field::Field1),
+                    b"text" => Ok(Field::Field2),
+                    _ => Ok(Field::Ignore),
+                }
+            }
+        }
+        impl<'de> serde::Deserialize<'de> for Field {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                Deserializer::deserialize_identifier(de, FieldVisitor)
+            }
+        }
+        struct Visitor<'de> {
+            marker: PhantomData<ImprovedText>,
+            lifetime: PhantomData<&'de ()>,
+        }
+        impl<'de> serde::de::Visitor<'de> for Visitor<'de> {
+            type Value = ImprovedText;
+            fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+                Formatter::write_str(fmt, "struct ImprovedText")
+            }
+            #[inline]
+            fn visit_seq<A: SeqAccess<'de>>(self, mut s: A) -> Result<Self::Value, A::Error> {
+                let f0 = match SeqAccess::next_element::<String>(&mut s)? {
+                    Some(v) => v,
+                    None => {
+                        return Err(Error::invalid_length(
+                            0,
+                            &"struct ImprovedText with 3 elements",
+                        ));
+                    }
+                };
+                let f1 = match SeqAccess::next_element::<String>(&mut s)? {
+                    Some(v) => v,
+                    None => {
+                        return Err(Error::invalid_length(
+                            1,
+                            &"struct ImprovedText with 3 elements",
+                        ));
+                    }
+                };
+                let f2 = match SeqAccess::next_element::<String>(&mut s)? {
+                    Some(v) => v,
+                    None => {
+                        return Err(Error::invalid_length(
+                            2,
+                            &"struct ImprovedText with 3 elements",
+                        ));
+                    }
+                };
+                Ok(ImprovedText {
+                    detected_source_language: f0,
+                    target_language: f1,
+                    text: f2,
+                })
+            }
+            #[inline]
+            fn visit_map<A: MapAccess<'de>>(self, mut m: A) -> Result<Self::Value, A::Error> {
+                let mut f0: Option<String> = None;
+                let mut f1: Option<String> = None;
+                let mut f2: Option<String> = None;
+                while let Some(key) = MapAccess::next_key::<Field>(&mut m)? {
+                    match key {
+                        Field::Field0 => {
+                            if Option::is_some(&f0) {
+                                return Err(<A::Error as Error>::duplicate_field(
+                                    "detected_source_language",
+                                ));
+                            }
+                            f0 = Some(MapAccess::next_value::<String>(&mut m)?);
+                        }
+                        Field::Field1 => {
+                            if Option::is_some(&f1) {
+                                return Err(<A::Error as Error>::duplicate_field(
+                                    "target_language",
+                                ));
+                            }
+                            f1 = Some(MapAccess::next_value::<String>(&mut m)?);
+                        }
+                        Field::Field2 => {
+                            if Option::is_some(&f2) {
+                                return Err(<A::Error as Error>::duplicate_field("text"));
+                            }
+                            f2 = Some(MapAccess::next_value::<String>(&mut m)?);
+                        }
+                        _ => {
+                            let _ = MapAccess::next_value::<IgnoredAny>(&mut m)?;
+                        }
+                    }
+                }
+                let f0 = match f0 {
+                    Some(f) => f,
+                    None => missing_field("detected_source_language")?,
+                };
+                let f1 = match f1 {
+                    Some(f) => f,
+                    None => missing_field("target_language")?,
+                };
+                let f2 = match f2 {
+                    Some(f) => f,
+                    None => missing_field("text")?,
+                };
+                Ok(ImprovedText {
+                    detected_source_language: f0,
+                    target_language: f1,
+                    text: f2,
+                })
+            }
+        }
+        const FIELDS: &[&str] = &["detected_source_language", "target_language", "text"];
+        Deserializer::deserialize_struct(
+            de,
+            "ImprovedText",
+            FIELDS,
+            Visitor {
+                marker: PhantomData::<ImprovedText>,
+                lifetime: PhantomData,
+            },
+        )
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ImprovedTextList {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        enum Field {
+            Field0,
+            Ignore,
+        }
+        struct FieldVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FieldVisitor {
+            type Value = Field;
+            fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+                Formatter::write_str(fmt, "field identifier")
+            }
+            fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+                match val {
+                    0 => Ok(Field::Field0),
+                    _ => Ok(Field::Ignore),
+                }
+            }
+            fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+                match val {
+                    "improvements" => Ok(Field::Field0),
+                    _ => Ok(Field::Ignore),
+                }
+            }
+            fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+                match val {
+                    b"improvements" => Ok(Field::Field0),
+                    _ => Ok(Field::Ignore),
+                }
+            }
+        }
+        impl<'de> serde::Deserialize<'de> for Field {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                Deserializer::deserialize_identifier(de, FieldVisitor)
+            }
+        }
+        struct Visitor<'de> {
+            marker: PhantomData<ImprovedTextList>,
+            lifetime: PhantomData<&'de ()>,
+        }
+        impl<'de> serde::de::Visitor<'de> for Visitor<'de> {
+            type Value = ImprovedTextList;
+            fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+                Formatter::write_str(fmt, "struct ImprovedTextList")
+            }
+            #[inline]
+            fn visit_seq<A: SeqAccess<'de>>(self, mut s: A) -> Result<Self::Value, A::Error> {
+                let f0 = match SeqAccess::next_element::<Vec<ImprovedText>>(&mut s)? {
+                    Some(v) => v,
+                    None => {
+                        return Err(Error::invalid_length(
+                            0,
+                            &"struct ImprovedTextList with 1 element",
+                        ));
+                    }
+                };
+                Ok(ImprovedTextList { improvements: f0 })
+            }
+            #[inline]
+            fn visit_map<A: MapAccess<'de>>(self, mut m: A) -> Result<Self::Value, A::Error> {
+                let mut f0: Option<Vec<ImprovedText>> = None;
+                while let Some(key) = MapAccess::next_key::<Field>(&mut m)? {
+                    match key {
+                        Field::Field0 => {
+                            if Option::is_some(&f0) {
+                                return Err(<A::Error as Error>::duplicate_field("improvements"));
+                            }
+                            f0 = Some(MapAccess::next_value::<Vec<ImprovedText>>(&mut m)?);
+                        }
+                        _ => {
+                            let _ = MapAccess::next_value::<IgnoredAny>(&mut m)?;
+                        }
+                    }
+                }
+                let f0 = match f0 {
+                    Some(f) => f,
+                    None => missing_field("improvements")?,
+                };
+                Ok(ImprovedTextList { improvements: f0 })
+            }
+        }
+        const FIELDS: &[&str] = &["improvements"];
+        Deserializer::deserialize_struct(
+            de,
+            "ImprovedTextList",
+            FIELDS,
+            Visitor {
+                marker: PhantomData::<ImprovedTextList>,
+                lifetime: PhantomData,
+            },
+        )
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for ServerErrorMessage {
     fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
         enum Field {
@@ -217,8 +217,9 @@ impl Default for Client {
     }
 }
 
-#[cfg(test)]
-mod tests {
+/// Tests against the real server, which needs network access.
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
     use super::*;
     use tracing_test::traced_test;
 
@@ -99,3 +99,55 @@ where
     .await
     .context(here!())?)
 } */
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{Method::GET, MockServer};
+
+    use super::*;
+    use crate::types::Room;
+
+    fn replay(status: u16, fixture: &str) -> Result<Vec<Room>, ValidationError> {
+        let server = MockServer::start();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/Room");
+            then.status(status)
+                .header("content-type", "application/json")
+                .body(fixture);
+        });
+
+        validate_response(ureq::get(&server.url("/Room")).call())
+    }
+
+    #[test]
+    fn rooms_from_fixture() {
+        let rooms = replay(200, include_str!("../fixtures/rooms.json")).unwrap();
+
+        assert_eq!(rooms.len(), 2);
+
+        assert_eq!(rooms[0].name, "Kiara TL");
+        assert!(!rooms[0].needs_password);
+        assert!(rooms[0].allows_external_sharing);
+        assert!(rooms[0].stream.is_some());
+        assert_eq!(rooms[0].tags, ["EN", "Karaoke"]);
+
+        assert!(rooms[1].needs_password);
+        assert!(!rooms[1].allows_external_sharing);
+        assert!(rooms[1].is_empty);
+        assert_eq!(rooms[1].stream, None);
+        assert!(rooms[1].tags.is_empty());
+    }
+
+    #[test]
+    fn error_status_keeps_body() {
+        let res = replay(404, include_str!("../fixtures/not_found.json"));
+
+        assert!(matches!(
+            res,
+            Err(ValidationError::ServerError(
+                ServerError::ErrorCodeWithValueParseError(404, ParseError::ResponseParseError(..))
+            ))
+        ));
+    }
+}
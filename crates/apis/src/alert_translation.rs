@@ -0,0 +1,149 @@
+//! Translates the titles of streams for their alerts, with the DeepL account set up for
+//! translating tweets.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use deepl::{DeepL, Language, TranslatableTextList};
+use futures::future::join_all;
+use tokio::time::timeout;
+use tracing::{error, instrument, warn};
+
+use utility::{
+    config::{Config, Database},
+    here, http,
+    streams::Livestream,
+};
+
+use crate::{
+    discord_api::TranslatedTitle,
+    translation_usage::{AccountedDeepL, UsageFeature},
+};
+
+/// How long the alert waits for its translations, those that aren't done by then are left out.
+const TRANSLATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The title of the stream in every language set up for alerts, skipping the languages
+/// it's already in, and the ones that couldn't be translated to in time.
+#[instrument(skip(config, live), fields(stream = %live.title))]
+pub async fn translate_title(config: &Config, live: &Livestream) -> Vec<TranslatedTitle> {
+    let languages = &config.stream_tracking.alerts.translations;
+
+    if languages.is_empty() {
+        return Vec::new();
+    }
+
+    let token = match config.deepl_token() {
+        Some(token) => token.to_owned(),
+        None => {
+            error!("Alert translations are set up, but DeepL isn't.");
+            return Vec::new();
+        }
+    };
+
+    let translations = languages.iter().map(|language| {
+        let title = live.title.clone();
+        let database = config.database.clone();
+        let token = token.clone();
+        let code = language.language.clone();
+
+        async move {
+            // The DeepL client is blocking, so it's kept off the async runtime.
+            let result =
+                tokio::task::spawn_blocking(move || translate(token, database, title, &code))
+                    .await
+                    .context(here!())
+                    .and_then(|r| r);
+
+            match result {
+                Ok(title) => title.map(|title| TranslatedTitle {
+                    language: language.language.to_uppercase(),
+                    channel: language.channel,
+                    title,
+                }),
+                Err(e) => {
+                    error!(?e, language = %language.language, "Failed to translate title!");
+                    None
+                }
+            }
+        }
+    });
+
+    match timeout(TRANSLATION_TIMEOUT, join_all(translations)).await {
+        Ok(translations) => translations.into_iter().flatten().collect(),
+        Err(_) => {
+            warn!("Translating the title took too long, posting the alert without it.");
+            Vec::new()
+        }
+    }
+}
+
+/// The languages set up for alerts that DeepL doesn't know.
+pub fn unknown_languages(config: &Config) -> Vec<&str> {
+    config
+        .stream_tracking
+        .alerts
+        .translations
+        .iter()
+        .map(|l| l.language.as_str())
+        .filter(|code| code.parse::<Language>().is_err())
+        .collect()
+}
+
+fn translate(
+    token: String,
+    database: Database,
+    title: String,
+    language: &str,
+) -> anyhow::Result<Option<String>> {
+    let target = language.parse::<Language>().context(here!())?;
+    // Targets can be regional, such as `EN-US`, while detected languages never are.
+    let target_code = target.code().to_ascii_uppercase();
+
+    let client = DeepL::with_agent(token, http::client().agent().clone());
+    let client = AccountedDeepL::new(client, database, UsageFeature::AlertTranslation);
+
+    let translated = client
+        .translate(
+            None,
+            TranslatableTextList {
+                source_language: None,
+                target_language: target,
+                texts: vec![title],
+            },
+        )
+        .map_err(|e| anyhow!("{e}"))
+        .context(here!())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Translated text wasn't found.").context(here!()))?;
+
+    let source_code = translated.detected_source_language.to_ascii_uppercase();
+
+    if target_code.split('-').next() == Some(source_code.as_str()) {
+        return Ok(None);
+    }
+
+    Ok(Some(translated.text))
+}
+
+#[cfg(test)]
+mod tests {
+    use utility::config::AlertLanguage;
+
+    use super::*;
+
+    #[test]
+    fn unknown_alert_languages_are_found() {
+        let mut config = Config::default();
+        config.stream_tracking.alerts.translations = ["JA", "en-us", "Klingon"]
+            .into_iter()
+            .map(|language| AlertLanguage {
+                language: language.to_owned(),
+                channel: None,
+            })
+            .collect();
+
+        assert_eq!(unknown_languages(&config), vec!["Klingon"]);
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::Context;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::Row;
+use serenity::model::id::{ChannelId, GuildId};
+
+use utility::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+/// A message from an archived stream chat, as it's kept for searching.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedMessage {
+    pub guild: Option<GuildId>,
+    pub channel: ChannelId,
+    pub talent: Option<String>,
+    pub video_id: Option<String>,
+    pub stream_title: Option<String>,
+    pub author_name: String,
+    pub content: String,
+    pub sent_at: DateTime<Utc>,
+    /// Seconds since the stream started, negative for messages sent before it.
+    pub offset: i64,
+}
+
+impl IndexedMessage {
+    /// The time since the stream started, as `[-][HH:]MM:SS`.
+    #[must_use]
+    pub fn clock_time(&self) -> String {
+        clock_time(self.offset)
+    }
+
+    /// A link to the moment in the stream the message was sent, if it was sent while live.
+    #[must_use]
+    pub fn video_link(&self) -> Option<String> {
+        self.video_id
+            .as_ref()
+            .filter(|_| self.offset >= 0)
+            .map(|id| format!("https://youtu.be/{id}?t={}", self.offset))
+    }
+}
+
+/// Formats seconds since the start of a stream as `[-][HH:]MM:SS`.
+#[must_use]
+pub fn clock_time(offset: i64) -> String {
+    let sign = if offset < 0 { "-" } else { "" };
+    let offset = offset.abs();
+
+    match (offset / 3600, offset / 60 % 60, offset % 60) {
+        (0, minutes, seconds) => format!("{sign}{minutes:02}:{seconds:02}"),
+        (hours, minutes, seconds) => format!("{sign}{hours:02}:{minutes:02}:{seconds:02}"),
+    }
+}
+
+/// What to look for in the archived chats.
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveQuery {
+    /// Words that all have to be in the message, matched on whole words.
+    pub text: String,
+    /// Only messages archived in this guild are searched, or every guild if it isn't set.
+    pub guild: Option<GuildId>,
+    pub talent: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Full-text index over the messages of archived stream chats.
+pub struct ArchiveIndex {
+    handle: DatabaseHandle,
+}
+
+impl ArchiveIndex {
+    const TABLE_NAME: &'static str = "ArchivedMessages";
+    const COLUMNS: &'static str =
+        "channel_id, talent, video_id, stream_title, author_name, content, sent_at, stream_offset, guild_id";
+    pub const MAX_RESULTS: usize = 100;
+
+    pub fn open(database: &Database) -> anyhow::Result<Self> {
+        let handle = database.get_handle().context(here!())?;
+
+        // Only the text is indexed, the other columns are only filtered on or shown.
+        match &handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING fts5(content, author_name, channel_id UNINDEXED, talent UNINDEXED, video_id UNINDEXED, stream_title UNINDEXED, sent_at UNINDEXED, stream_offset UNINDEXED, guild_id UNINDEXED)",
+                        Self::TABLE_NAME
+                    ),
+                    [],
+                )
+                .context(here!())?,
+        };
+
+        Ok(Self { handle })
+    }
+
+    pub fn insert(&self, messages: &[IndexedMessage]) -> anyhow::Result<()> {
+        match &self.handle {
+            DatabaseHandle::SQLite(h) => {
+                let tx = h.unchecked_transaction().context(here!())?;
+
+                {
+                    let mut stmt = tx
+                        .prepare(&format!(
+                            "INSERT INTO {} ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                            Self::TABLE_NAME,
+                            Self::COLUMNS
+                        ))
+                        .context(here!())?;
+
+                    for message in messages {
+                        stmt.execute(rusqlite::params![
+                            message.channel.0,
+                            message.talent,
+                            message.video_id,
+                            message.stream_title,
+                            message.author_name,
+                            message.content,
+                            message.sent_at.timestamp(),
+                            message.offset,
+                            message.guild.map(|g| g.0),
+                        ])
+                        .context(here!())?;
+                    }
+                }
+
+                tx.commit().context(here!())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The best matches for the query, at most [`Self::MAX_RESULTS`] of them.
+    pub fn search(&self, query: &ArchiveQuery) -> anyhow::Result<Vec<IndexedMessage>> {
+        let text = match Self::match_expression(&query.text) {
+            Some(text) => text,
+            None => return Ok(Vec::new()),
+        };
+
+        match &self.handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT {} FROM {} WHERE {1} MATCH ?1 AND (?2 IS NULL OR talent = ?2) AND (?3 IS NULL OR sent_at >= ?3) AND (?4 IS NULL OR sent_at <= ?4) AND (?6 IS NULL OR guild_id = ?6) ORDER BY rank LIMIT ?5",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let messages = stmt
+                    .query_map(
+                        rusqlite::params![
+                            text,
+                            query.talent,
+                            query.from.map(|t| t.timestamp()),
+                            query.to.map(|t| t.timestamp()),
+                            Self::MAX_RESULTS,
+                            query.guild.map(|g| g.0),
+                        ],
+                        Self::from_row,
+                    )
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                Ok(messages)
+            }
+        }
+    }
+
+    /// Deletes the messages sent before `before`, returning how many were deleted.
+    pub fn prune(&self, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        match &self.handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!("DELETE FROM {} WHERE sent_at < ?", Self::TABLE_NAME),
+                    [before.timestamp()],
+                )
+                .context(here!()),
+        }
+    }
+
+    /// Quotes every word, so that the text is never read as FTS5 query syntax.
+    fn match_expression(text: &str) -> Option<String> {
+        let words = text
+            .split_whitespace()
+            .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+            .collect::<Vec<_>>();
+
+        (!words.is_empty()).then(|| words.join(" "))
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<IndexedMessage> {
+        Ok(IndexedMessage {
+            guild: row.get::<_, Option<u64>>(8)?.map(GuildId),
+            channel: ChannelId(row.get(0)?),
+            talent: row.get(1)?,
+            video_id: row.get(2)?,
+            stream_title: row.get(3)?,
+            author_name: row.get(4)?,
+            content: row.get(5)?,
+            sent_at: Utc
+                .timestamp_opt(row.get(6)?, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            offset: row.get(7)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn index() -> ArchiveIndex {
+        ArchiveIndex::open(&Database::SQLite {
+            path: PathBuf::from(":memory:"),
+        })
+        .unwrap()
+    }
+
+    fn message(talent: &str, content: &str, sent_at: i64, offset: i64) -> IndexedMessage {
+        IndexedMessage {
+            guild: Some(GuildId(1)),
+            channel: ChannelId(1),
+            talent: Some(talent.to_owned()),
+            video_id: Some("dQw4w9WgXcQ".to_owned()),
+            stream_title: Some("Karaoke!".to_owned()),
+            author_name: "Viewer".to_owned(),
+            content: content.to_owned(),
+            sent_at: Utc.timestamp_opt(sent_at, 0).unwrap(),
+            offset,
+        }
+    }
+
+    #[test]
+    fn search_filters_by_talent_and_date() {
+        let index = index();
+
+        index
+            .insert(&[
+                message("Calli", "that song was great", 1_000, 60),
+                message("Kiara", "great stream", 2_000, 3_725),
+                message("Calli", "nothing to see here", 3_000, 120),
+            ])
+            .unwrap();
+
+        let search = |talent: Option<&str>, from: Option<i64>| {
+            index
+                .search(&ArchiveQuery {
+                    text: "great".to_owned(),
+                    guild: None,
+                    talent: talent.map(ToOwned::to_owned),
+                    from: from.map(|t| Utc.timestamp_opt(t, 0).unwrap()),
+                    to: None,
+                })
+                .unwrap()
+        };
+
+        assert_eq!(search(None, None).len(), 2);
+        assert_eq!(
+            search(Some("Calli"), None)[0].content,
+            "that song was great"
+        );
+        assert_eq!(
+            search(None, Some(1_500))[0].talent.as_deref(),
+            Some("Kiara")
+        );
+
+        let found = &search(Some("Kiara"), None)[0];
+        assert_eq!(found.clock_time(), "01:02:05");
+        assert_eq!(
+            found.video_link().as_deref(),
+            Some("https://youtu.be/dQw4w9WgXcQ?t=3725")
+        );
+    }
+
+    #[test]
+    fn search_is_limited_to_the_guild() {
+        let index = index();
+
+        let mut other_guild = message("Kiara", "great stream", 2_000, 0);
+        other_guild.guild = Some(GuildId(2));
+
+        index
+            .insert(&[message("Calli", "great song", 1_000, 0), other_guild])
+            .unwrap();
+
+        let search = |guild: Option<u64>| {
+            index
+                .search(&ArchiveQuery {
+                    text: "great".to_owned(),
+                    guild: guild.map(GuildId),
+                    ..Default::default()
+                })
+                .unwrap()
+        };
+
+        assert_eq!(search(None).len(), 2);
+
+        let found = search(Some(2));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].talent.as_deref(), Some("Kiara"));
+        assert_eq!(found[0].guild, Some(GuildId(2)));
+    }
+
+    #[test]
+    fn query_syntax_is_searched_as_text() {
+        let index = index();
+
+        index
+            .insert(&[message("Calli", "is it \"NEAR\" or not", 0, -30)])
+            .unwrap();
+
+        let found = index
+            .search(&ArchiveQuery {
+                text: "\"near\" OR".to_owned(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].clock_time(), "-00:30");
+        assert_eq!(found[0].video_link(), None);
+    }
+
+    #[test]
+    fn prune_deletes_older_messages() {
+        let index = index();
+
+        index
+            .insert(&[
+                message("Calli", "great start", 1_000, 0),
+                message("Calli", "great ending", 5_000, 4_000),
+            ])
+            .unwrap();
+
+        let pruned = index.prune(Utc.timestamp_opt(2_000, 0).unwrap()).unwrap();
+        assert_eq!(pruned, 1);
+
+        let found = index
+            .search(&ArchiveQuery {
+                text: "great".to_owned(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].content, "great ending");
+    }
+}
@@ -3,11 +3,12 @@ use std::sync::Arc;
 use anyhow::Context;
 use chrono::prelude::*;
 use chrono_humanize::HumanTime;
-use tokio::{sync::mpsc::Sender, time::sleep};
+use tokio::sync::mpsc::Sender;
 use tracing::{error, info, instrument};
 
 use super::discord_api::DiscordMessageData;
 use utility::{
+    birthdays::{self, BirthdayEntry, BirthdayStore, Celebrant},
     config::{Config, Talent},
     here,
 };
@@ -18,8 +19,16 @@ impl BirthdayReminder {
     #[instrument(skip(config, notifier_sender))]
     pub async fn start(config: Arc<Config>, notifier_sender: Sender<DiscordMessageData>) {
         tokio::spawn(async move {
+            let store = match birthdays::store() {
+                Ok(store) => store,
+                Err(e) => {
+                    error!("{:#}", e);
+                    return;
+                }
+            };
+
             tokio::select! {
-                e = Self::run(&config, notifier_sender) => {
+                e = Self::run(&config, store, notifier_sender) => {
                     if let Err(e) = e {
                         error!("{:#}", e);
                     }
@@ -35,39 +44,65 @@ impl BirthdayReminder {
         });
     }
 
-    #[instrument(skip(config, notifier_sender))]
+    /// Sends every birthday when it starts, looking for the next one again whenever
+    /// a birthday is set or removed.
+    #[instrument(skip(config, store, notifier_sender))]
     async fn run(
         config: &Config,
+        store: &BirthdayStore,
         notifier_sender: Sender<DiscordMessageData>,
     ) -> anyhow::Result<()> {
+        let clock = &config.clock;
+        let mut changed = store.subscribe();
+
         loop {
-            for next_birthday in Self::get_upcoming_birthdays(&config.talents) {
-                let now = Utc::now();
+            changed.borrow_and_update();
 
-                let time_to_next_birthday = next_birthday.birthday - now;
+            let upcoming = Self::get_upcoming_birthdays(&store.entries(), clock.now());
 
-                info!(
-                    "Next birthday is {} {}.",
-                    next_birthday.user,
-                    HumanTime::from(time_to_next_birthday)
-                );
+            let next_at = match upcoming.first() {
+                Some(next_birthday) => {
+                    info!(
+                        "Next birthday is {} {}.",
+                        next_birthday.celebrant,
+                        HumanTime::from(next_birthday.birthday - clock.now())
+                    );
 
-                sleep(time_to_next_birthday.to_std().context(here!())?).await;
+                    next_birthday.birthday
+                }
+                None => {
+                    changed.changed().await.context(here!())?;
+                    continue;
+                }
+            };
 
+            tokio::select! {
+                _ = clock.sleep_until(next_at) => {}
+                res = changed.changed() => {
+                    res.context(here!())?;
+                    info!("Birthdays were edited, looking for the next one again.");
+                    continue;
+                }
+            }
+
+            // Birthdays on the same day in the same timezone start at the same time.
+            for birthday in upcoming.into_iter().take_while(|b| b.birthday == next_at) {
                 notifier_sender
-                    .send(DiscordMessageData::Birthday(next_birthday))
+                    .send(DiscordMessageData::Birthday(birthday))
                     .await
                     .context(here!())?;
             }
         }
     }
 
-    fn get_upcoming_birthdays(users: &[Talent]) -> Vec<Birthday> {
-        let mut birthday_queue = users
+    fn get_upcoming_birthdays(entries: &[BirthdayEntry], now: DateTime<Utc>) -> Vec<Birthday> {
+        let mut birthday_queue = entries
             .iter()
-            .map(|u| Birthday {
-                user: u.name.clone(),
-                birthday: u.get_next_birthday(),
+            .filter_map(|e| {
+                Some(Birthday {
+                    celebrant: e.celebrant.clone(),
+                    birthday: e.next_after(now)?,
+                })
             })
             .collect::<Vec<_>>();
 
@@ -75,12 +110,19 @@ impl BirthdayReminder {
         birthday_queue
     }
 
-    pub fn get_birthdays(users: &[Talent]) -> Vec<BirthdayRef> {
+    /// The next birthday of every talent, using the birthdays that have been set
+    /// through the bot over the ones in the config.
+    pub fn get_birthdays<'a>(users: &'a [Talent], store: &BirthdayStore) -> Vec<BirthdayRef<'a>> {
+        let now = Utc::now();
+
         let mut birthday_queue = users
             .iter()
             .map(|u| BirthdayRef {
                 user: u,
-                birthday: u.get_next_birthday(),
+                birthday: store
+                    .get(&Celebrant::Talent(u.name.clone()))
+                    .and_then(|e| e.next_after(now))
+                    .unwrap_or_else(|| u.get_next_birthday_after(now)),
             })
             .collect::<Vec<_>>();
 
@@ -91,7 +133,7 @@ impl BirthdayReminder {
 
 #[derive(Debug, Clone)]
 pub struct Birthday {
-    pub user: String,
+    pub celebrant: Celebrant,
     pub birthday: DateTime<Utc>,
 }
 
@@ -100,3 +142,81 @@ pub struct BirthdayRef<'a> {
     pub user: &'a Talent,
     pub birthday: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, future};
+    use rusqlite::Connection;
+    use tokio::sync::mpsc;
+    use utility::{
+        clock::{SharedClock, SimulatedClock},
+        config::{Birthday as TalentBirthday, DatabaseHandle, TalentConfigData},
+    };
+
+    use super::*;
+
+    fn talent(name: &str, day: u8, month: u8) -> Talent {
+        Talent::from(TalentConfigData {
+            name: name.to_owned(),
+            birthday: TalentBirthday {
+                day,
+                month,
+                year: None,
+            },
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn birthdays_are_sent_in_order_and_on_time() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+        let mut config = Config::default();
+        config.talents = vec![talent("Kiara", 6, 7), talent("Calli", 4, 4)];
+        config.clock = SharedClock::new(SimulatedClock::new(start));
+
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        let store = BirthdayStore::load(handle, &config.talents).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let received = async {
+            let mut received = Vec::new();
+
+            while received.len() < 3 {
+                if let Some(DiscordMessageData::Birthday(birthday)) = rx.recv().await {
+                    assert_eq!(config.clock.now(), birthday.birthday);
+                    received.push((birthday.celebrant, birthday.birthday));
+                }
+            }
+
+            received
+        };
+
+        let received = match block_on(future::select(
+            Box::pin(BirthdayReminder::run(&config, &store, tx)),
+            Box::pin(received),
+        )) {
+            future::Either::Right((received, _)) => received,
+            future::Either::Left((res, _)) => panic!("reminder stopped early: {:?}", res),
+        };
+
+        assert_eq!(
+            received,
+            vec![
+                (
+                    Celebrant::Talent("Calli".to_owned()),
+                    Utc.with_ymd_and_hms(2026, 4, 4, 0, 0, 0).unwrap()
+                ),
+                (
+                    Celebrant::Talent("Kiara".to_owned()),
+                    Utc.with_ymd_and_hms(2026, 7, 6, 0, 0, 0).unwrap()
+                ),
+                (
+                    Celebrant::Talent("Calli".to_owned()),
+                    Utc.with_ymd_and_hms(2027, 4, 4, 0, 0, 0).unwrap()
+                ),
+            ]
+        );
+    }
+}
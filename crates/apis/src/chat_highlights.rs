@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use holodex::model::id::VideoId;
+
+use crate::archive_index::clock_time;
+
+/// A minute of a stream where the chat was a lot busier than usual.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlight {
+    /// The minute of the stream, counted from when it started.
+    pub minute: i64,
+    pub messages: usize,
+    /// How many standard deviations above the average the minute's message count is.
+    pub z_score: f32,
+}
+
+impl Highlight {
+    /// The line the highlight is listed with, linking to the start of the minute in the VOD.
+    #[must_use]
+    pub fn format(&self, video: &VideoId) -> String {
+        let offset = self.minute * 60;
+
+        format!(
+            "[{}](https://youtu.be/{video}?t={offset}) {} messages",
+            clock_time(offset),
+            self.messages
+        )
+    }
+}
+
+/// Finds the minutes where the message count is at least `min_z_score` standard deviations
+/// above the average, from the offsets in seconds since the stream started of every message.
+/// Busy minutes in a row count as a single highlight, at the busiest of them.
+/// Returns at most `max` of the busiest highlights, in the order they happened.
+#[must_use]
+pub fn find_highlights<I>(offsets: I, min_z_score: f32, max: usize) -> Vec<Highlight>
+where
+    I: IntoIterator<Item = i64>,
+{
+    // Messages sent before the stream started can't be linked to.
+    let mut counts = BTreeMap::new();
+
+    for offset in offsets.into_iter().filter(|o| *o >= 0) {
+        *counts.entry(offset / 60).or_insert(0_usize) += 1;
+    }
+
+    let last_minute = match counts.keys().next_back() {
+        Some(m) => *m,
+        None => return Vec::new(),
+    };
+
+    // Quiet minutes count towards the average too.
+    let per_minute = (0..=last_minute)
+        .map(|m| counts.get(&m).copied().unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    let len = per_minute.len() as f32;
+    let mean = per_minute.iter().sum::<usize>() as f32 / len;
+    let std_dev = (per_minute
+        .iter()
+        .map(|c| (*c as f32 - mean).powi(2))
+        .sum::<f32>()
+        / len)
+        .sqrt();
+
+    if std_dev == 0.0 {
+        return Vec::new();
+    }
+
+    let mut highlights: Vec<Highlight> = Vec::new();
+    let mut run_end = None;
+
+    for (minute, messages) in (0..).zip(per_minute) {
+        let z_score = (messages as f32 - mean) / std_dev;
+
+        if z_score < min_z_score {
+            continue;
+        }
+
+        let highlight = Highlight {
+            minute,
+            messages,
+            z_score,
+        };
+
+        match highlights.last_mut() {
+            Some(last) if run_end == Some(minute - 1) => {
+                if highlight.messages > last.messages {
+                    *last = highlight;
+                }
+            }
+            _ => highlights.push(highlight),
+        }
+
+        run_end = Some(minute);
+    }
+
+    highlights.sort_by(|a, b| b.messages.cmp(&a.messages));
+    highlights.truncate(max);
+    highlights.sort_by_key(|h| h.minute);
+
+    highlights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Offsets for `count` messages spread over the minute.
+    fn minute(minute: i64, count: i64) -> impl Iterator<Item = i64> {
+        (0..count).map(move |i| minute * 60 + i * 60 / count)
+    }
+
+    #[test]
+    fn busy_minutes_are_highlighted_in_order() {
+        let offsets = (0..30)
+            .flat_map(|m| minute(m, 5))
+            .chain(minute(10, 40))
+            .chain(minute(25, 60));
+
+        let highlights = find_highlights(offsets, 2.0, 10);
+
+        assert_eq!(
+            highlights.iter().map(|h| h.minute).collect::<Vec<_>>(),
+            vec![10, 25]
+        );
+        assert_eq!(highlights[1].messages, 65);
+    }
+
+    #[test]
+    fn busy_minutes_in_a_row_are_one_highlight() {
+        let offsets = (0..30)
+            .flat_map(|m| minute(m, 5))
+            .chain(minute(12, 40))
+            .chain(minute(13, 60))
+            .chain(minute(14, 40));
+
+        let highlights = find_highlights(offsets, 2.0, 10);
+
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(highlights[0].minute, 13);
+    }
+
+    #[test]
+    fn only_the_busiest_are_kept() {
+        let offsets = (0..60)
+            .flat_map(|m| minute(m, 5))
+            .chain(minute(10, 40))
+            .chain(minute(30, 80))
+            .chain(minute(50, 60));
+
+        let highlights = find_highlights(offsets, 2.0, 2);
+
+        assert_eq!(
+            highlights.iter().map(|h| h.minute).collect::<Vec<_>>(),
+            vec![30, 50]
+        );
+    }
+
+    #[test]
+    fn steady_chat_has_no_highlights() {
+        assert!(find_highlights((0..30).flat_map(|m| minute(m, 5)), 2.0, 10).is_empty());
+        assert!(find_highlights(minute(-1, 100), 2.0, 10).is_empty());
+    }
+
+    #[test]
+    fn highlights_link_to_the_vod() {
+        let highlight = Highlight {
+            minute: 62,
+            messages: 120,
+            z_score: 3.0,
+        };
+
+        assert_eq!(
+            highlight.format(&"dQw4w9WgXcQ".parse().unwrap()),
+            "[01:02:00](https://youtu.be/dQw4w9WgXcQ?t=3720) 120 messages"
+        );
+    }
+}
@@ -1,40 +1,78 @@
-use std::{collections::HashMap, sync::Arc, time::Duration as StdDuration};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration as StdDuration,
+};
 
 use anyhow::{anyhow, Context as _};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use futures::{StreamExt, TryStreamExt};
 use holodex::model::{id::VideoId, VideoStatus};
-use lru::LruCache;
 use regex::Regex;
 use serenity::{
-    builder::CreateMessage,
+    builder::{CreateChannel, CreateEmbed, CreateMessage, EditMessage},
     http::Http,
     model::{
-        channel::{Channel, ChannelCategory, Message, MessageReference, MessageType},
-        id::{ChannelId, GuildId, MessageId},
+        channel::{
+            Attachment, Channel, ChannelCategory, Message, MessageReference, MessageType,
+            PermissionOverwrite, PermissionOverwriteType,
+        },
+        guild::{ScheduledEventStatus, ScheduledEventType},
+        id::{ChannelId, GuildId, MessageId, RoleId, ScheduledEventId},
         mention::Mention,
+        Permissions,
     },
     prelude::Context,
     CacheAndHttp,
 };
 use tokio::{
     sync::{broadcast, mpsc, oneshot, watch, Mutex},
-    time::{sleep, Instant},
+    time::sleep,
 };
-use tracing::{debug, debug_span, error, info, instrument, Instrument};
+use tracing::{debug, debug_span, error, info, instrument, warn, Instrument};
 
 use macros::clone_variables;
 use utility::{
-    config::{Config, StreamChatConfig /* , Talent */},
-    discord::{DataOrder, SegmentDataPosition, SegmentedMessage},
+    audit_log::{self, AuditAction, AuditEntry},
+    birthdays::Celebrant,
+    clock::SharedClock,
+    config::{
+        AutoSlowmodeConfig, CancelledAlertAction, ChatHighlightsConfig, Config, Database,
+        EmbedEvent, HoloBranch, Placeholder, ScheduledEventsConfig, StreamAlertsConfig,
+        StreamChatConfig, StreamChatMode, Talent, Template, ThemeConfig, UnknownStreamStatus,
+    },
+    discord::{
+        retry_discord_request, DataOrder, RequestKind, SegmentDataPosition, SegmentedMessage,
+    },
     extensions::MessageExt,
+    feature_flags::{self, Feature},
+    functions::format_duration,
     here, regex,
-    streams::{Livestream, StreamUpdate},
+    streams::{
+        resync_updates, LatencyKind, Livestream, Received, StreamUpdate, StreamUpdateFanout,
+        UpdateSubscription, STREAM_LATENCY,
+    },
+    tweet_subscriptions,
+    types::Cache,
 };
 
+#[cfg(feature = "translation")]
+use crate::alert_translation;
 use crate::{
+    archive_index::{self, ArchiveIndex, IndexedMessage},
     birthday_reminder::Birthday,
-    twitter_api::{HoloTweet, HoloTweetReference, ScheduleUpdate},
+    chat_highlights::find_highlights,
+    image_cache,
+    kofi_webhook::Donation,
+    message_sink::{MessageSink, SentMessage, WebhookIdentity},
+    object_storage::ObjectStorage,
+    readiness::{self, RequiredChannel},
+    tweet_dms::{Delivery, DmBudget},
+    tweet_store::{PostedTweet, TweetStore},
+    twitter_api::{HoloTweet, HoloTweetReference, ScheduleUpdate, TweetMedia},
 };
 
 /* use mchad::{Client, EventData, Listener, RoomEvent, RoomUpdate}; */
@@ -43,25 +81,86 @@ pub struct DiscordApi;
 
 impl DiscordApi {
     const ARCHIVAL_WARNING_TIME: StdDuration = StdDuration::from_secs(5 * 60);
+    const ARCHIVE_RESUME_ATTEMPTS: usize = 3;
+    const ARCHIVE_RESUME_DELAY: StdDuration = StdDuration::from_secs(60);
+    const ALERT_LOOKUP_ATTEMPTS: usize = 6;
+    const SCHEDULE_DIGEST_REFRESH_INTERVAL: StdDuration = StdDuration::from_secs(5 * 60);
+    const SCHEDULE_DIGEST_LOOKBACK: u64 = 20;
+    const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+    const STATUS_BOARD_TITLE: &'static str = "Live now";
+    const TWEET_DIGEST_SNIPPET_LENGTH: usize = 100;
+    const EARLY_CHAT_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(60);
+    /// How many pages of messages are read per slowmode sample, at 100 messages per page.
+    const SLOWMODE_SAMPLE_PAGES: usize = 10;
+    /// What everyone is denied in early stream chats until the stream goes live.
+    const EARLY_CHAT_DENIED: Permissions =
+        Permissions::SEND_MESSAGES.union(Permissions::SEND_MESSAGES_IN_THREADS);
 
-    #[instrument(skip(ctx, config, channel, stream_notifier, index_receiver, guild_ready))]
+    #[instrument(skip(
+        ctx,
+        config,
+        channel,
+        stream_notifier,
+        index_receiver,
+        claimed_chats,
+        cache_flusher
+    ))]
     pub async fn start(
         ctx: Context,
         config: Arc<Config>,
         channel: mpsc::Receiver<DiscordMessageData>,
-        stream_notifier: broadcast::Sender<StreamUpdate>,
+        stream_notifier: StreamUpdateFanout,
         index_receiver: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
-        guild_ready: oneshot::Receiver<()>,
+        claimed_chats: watch::Sender<HashMap<VideoId, ChannelId>>,
+        cache_flusher: broadcast::Receiver<Cache>,
     ) {
-        let stream_notifier_rx = stream_notifier.subscribe();
-        /* let stream_notifier_rx2 = stream_notifier.subscribe(); */
-
         let (archive_tx, archive_rx) = mpsc::unbounded_channel();
+        // Set once every Twitter feed has been backfilled, until then replies are searched for.
+        let backfilled = Arc::new(AtomicBool::new(false));
+
+        if config.twitter.enabled && config.twitter.reply_backfill > 0 {
+            tokio::spawn(
+                clone_variables!(ctx, config, backfilled; {
+                    if Self::backfill_tweet_store(&ctx, &config).await {
+                        backfilled.store(true, Ordering::Release);
+                    }
+                })
+                .instrument(debug_span!("Tweet store backfill")),
+            );
+        }
+
+        let tweet_dm_tx = if config.twitter.enabled && config.twitter.dm_subscriptions.enabled {
+            let (tweet_dm_tx, tweet_dm_rx) = mpsc::unbounded_channel();
+
+            tokio::spawn(
+                clone_variables!(ctx, config; {
+                    tokio::select! {
+                        res = Self::tweet_dm_thread(ctx, &config, tweet_dm_rx) => {
+                            if let Err(e) = res {
+                                error!("{:#}", e);
+                            }
+                        },
+                        e = tokio::signal::ctrl_c() => {
+                            if let Err(e) = e {
+                                error!("{:#}", e);
+                            }
+                        }
+                    }
+
+                    info!(task = "Discord tweet DM thread", "Shutting down.");
+                })
+                .instrument(debug_span!("Discord tweet DM thread")),
+            );
+
+            Some(tweet_dm_tx)
+        } else {
+            None
+        };
 
         tokio::spawn(
-            clone_variables!(ctx, config; {
+            clone_variables!(ctx, config, backfilled; {
                 tokio::select! {
-                    _ = Self::posting_thread(ctx, config, channel) => {},
+                    _ = Self::posting_thread(ctx, config, channel, tweet_dm_tx, cache_flusher, backfilled) => {},
                     e = tokio::signal::ctrl_c() => {
                         if let Err(e) = e {
                             error!("{:#}", e);
@@ -75,17 +174,22 @@ impl DiscordApi {
         );
 
         if config.stream_tracking.chat.enabled {
-            if let Some(index) = index_receiver {
+            if let Some(index) = &index_receiver {
+                let stream_notifier_rx = stream_notifier.subscribe("stream_chats");
+                /* let stream_notifier_rx2 = stream_notifier.subscribe("mchad"); */
+
                 tokio::spawn(
                     clone_variables!(ctx, config, index; {
                         tokio::select! {
                             res = Self::stream_update_thread(
                                 ctx,
                                 &config.stream_tracking.chat,
+                                &config.stream_tracking.alerts,
+                                config.theme,
                                 stream_notifier_rx,
                                 index,
-                                guild_ready,
                                 archive_tx,
+                                claimed_chats,
                             ) => {
                                 if let Err(e) = res {
                                     error!("{:#}", e);
@@ -135,7 +239,7 @@ impl DiscordApi {
                             res = Self::chat_archive_thread(
                                 ctx,
                                 log_ch,
-                                &config.stream_tracking.chat,
+                                &config,
                                 archive_rx,
                             ) => {
                                 if let Err(e) = res {
@@ -155,6 +259,87 @@ impl DiscordApi {
                 );
             }
         }
+
+        if config.stream_tracking.schedule_digest.enabled {
+            if let Some(index) = &index_receiver {
+                tokio::spawn(
+                    clone_variables!(ctx, config, index; {
+                        tokio::select! {
+                            res = Self::schedule_digest_thread(ctx, &config, index) => {
+                                if let Err(e) = res {
+                                    error!("{:#}", e);
+                                }
+                            },
+                            e = tokio::signal::ctrl_c() => {
+                                if let Err(e) = e {
+                                    error!("{:#}", e);
+                                }
+                            }
+                        }
+
+                        info!(task = "Discord schedule digest thread", "Shutting down.");
+                    })
+                    .instrument(debug_span!("Discord schedule digest thread")),
+                );
+            }
+        }
+
+        if config.stream_tracking.status_board.enabled {
+            if let Some(index) = &index_receiver {
+                let status_board_rx = stream_notifier.subscribe("status_board");
+
+                tokio::spawn(
+                    clone_variables!(ctx, config, index; {
+                        tokio::select! {
+                            res = Self::status_board_thread(ctx, &config, status_board_rx, index) => {
+                                if let Err(e) = res {
+                                    error!("{:#}", e);
+                                }
+                            },
+                            e = tokio::signal::ctrl_c() => {
+                                if let Err(e) = e {
+                                    error!("{:#}", e);
+                                }
+                            }
+                        }
+
+                        info!(task = "Discord status board thread", "Shutting down.");
+                    })
+                    .instrument(debug_span!("Discord status board thread")),
+                );
+            }
+        }
+
+        if config.stream_tracking.scheduled_events.enabled {
+            if let Some(index) = index_receiver {
+                let scheduled_events_rx = stream_notifier.subscribe("scheduled_events");
+
+                tokio::spawn(
+                    clone_variables!(ctx, config, index; {
+                        tokio::select! {
+                            res = Self::scheduled_events_thread(
+                                ctx,
+                                &config.stream_tracking.scheduled_events,
+                                scheduled_events_rx,
+                                index,
+                            ) => {
+                                if let Err(e) = res {
+                                    error!("{:#}", e);
+                                }
+                            },
+                            e = tokio::signal::ctrl_c() => {
+                                if let Err(e) = e {
+                                    error!("{:#}", e);
+                                }
+                            }
+                        }
+
+                        info!(task = "Discord scheduled events thread", "Shutting down.");
+                    })
+                    .instrument(debug_span!("Discord scheduled events thread")),
+                );
+            }
+        }
     }
 
     #[instrument(skip(http, f))]
@@ -192,18 +377,7 @@ impl DiscordApi {
                 }
             };
 
-            let twitter_link: &'static Regex = regex!(r#"https://twitter\.com/\d+/status/(\d+)/?"#);
-
-            // Parse tweet ID from the link in the embed.
-            let tweet_id = msg.embeds.iter().find_map(|e| {
-                e.url
-                    .as_ref()
-                    .and_then(|u| twitter_link.captures(u))
-                    .and_then(|cap| cap.get(1))
-                    .and_then(|id| id.as_str().parse::<u64>().ok())
-            });
-
-            if let Some(tweet_id) = tweet_id {
+            if let Some((tweet_id, _)) = Self::posted_tweet(&msg) {
                 debug!("Testing tweet ID: {}", tweet_id);
                 if tweet_id == tweet_ref.tweet {
                     debug!("Found message with matching tweet ID!");
@@ -215,23 +389,111 @@ impl DiscordApi {
         None
     }
 
-    #[instrument(skip(ctx, config, tweet_cache))]
+    /// The ID of the tweet a message was posted for, and the name of the talent who tweeted it.
+    fn posted_tweet(msg: &Message) -> Option<(u64, String)> {
+        let twitter_link: &'static Regex = regex!(r#"https://twitter\.com/[^/]+/status/(\d+)/?"#);
+
+        msg.embeds.iter().find_map(|e| {
+            let author = e.author.as_ref()?;
+
+            let tweet_id = e
+                .url
+                .iter()
+                .chain(author.url.iter())
+                .find_map(|u| twitter_link.captures(u))
+                .and_then(|cap| cap.get(1))
+                .and_then(|id| id.as_str().parse::<u64>().ok())?;
+
+            Some((tweet_id, author.name.clone()))
+        })
+    }
+
+    /// Records the tweets already posted in the Twitter feeds, so that replies to them
+    /// can be linked without searching through the channel history.
+    /// Returns whether every feed was read, so that every posted tweet is in the database.
+    #[instrument(skip(ctx, config))]
+    async fn backfill_tweet_store(ctx: &Context, config: &Config) -> bool {
+        let mut tweet_store = TweetStore::new(&config.database);
+
+        if !tweet_store.is_persistent() {
+            return false;
+        }
+
+        let channels = config
+            .talents
+            .iter()
+            .filter(|t| t.twitter_id.is_some())
+            .filter_map(|t| t.get_twitter_channel(config))
+            .collect::<HashSet<_>>();
+
+        let mut indexed = 0;
+        let mut complete = true;
+
+        for channel in channels {
+            let mut message_stream = channel
+                .messages_iter(&ctx.http)
+                .take(config.twitter.reply_backfill)
+                .boxed();
+
+            while let Some(msg) = message_stream.next().await {
+                let msg = match msg.context(here!()) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        error!("{:?}", e);
+                        complete = false;
+                        break;
+                    }
+                };
+
+                if msg.author.id != ctx.cache.current_user_id() {
+                    continue;
+                }
+
+                let (tweet_id, user_name) = match Self::posted_tweet(&msg) {
+                    Some(t) => t,
+                    None => continue,
+                };
+
+                // The store keeps the latest message of tweets that were posted more than once,
+                // including ones the posting thread saved while the history was being read.
+                tweet_store.insert(
+                    tweet_id,
+                    PostedTweet {
+                        message: MessageReference::from((channel, msg.id)),
+                        user_name,
+                    },
+                );
+
+                indexed += 1;
+            }
+        }
+
+        info!(indexed, complete, "Backfilled tweet store.");
+        complete
+    }
+
+    #[instrument(skip(ctx, config, tweet_store, backfilled))]
     async fn check_if_reply(
         ctx: &Context,
         config: &Config,
         tweet: &HoloTweet,
         twitter_channel: ChannelId,
-        tweet_cache: &mut LruCache<u64, (MessageReference, String)>,
+        tweet_store: &mut TweetStore,
+        backfilled: &AtomicBool,
     ) -> TweetReply {
         // Try to reply to an existing Discord twitter message.
         if let Some(tweet_ref) = &tweet.replied_to {
-            // Check if message exists in our cache.
-            if let Some((msg_ref, user_name)) = tweet_cache.get(&tweet_ref.tweet) {
+            // Check if we've posted the message ourselves.
+            if let Some(PostedTweet {
+                message: msg_ref,
+                user_name,
+            }) = tweet_store.get(tweet_ref.tweet)
+            {
                 if msg_ref.channel_id == twitter_channel {
-                    return TweetReply::SameChannel(user_name.clone(), msg_ref.clone());
+                    return TweetReply::SameChannel(user_name, msg_ref);
                 } else if let Some(msg_id) = msg_ref.message_id {
                     return TweetReply::OtherChannel(
-                        user_name.clone(),
+                        user_name,
                         msg_id
                             .link_ensured(&ctx.http, msg_ref.channel_id, msg_ref.guild_id)
                             .await,
@@ -250,7 +512,20 @@ impl DiscordApi {
                     None => return TweetReply::None,
                 };
 
+                // Once the feeds have been backfilled, every posted tweet is in the database.
+                if backfilled.load(Ordering::Acquire) {
+                    return TweetReply::None;
+                }
+
                 if let Some(msg_ref) = Self::search_for_tweet(ctx, tweet_ref, tweet_channel).await {
+                    tweet_store.insert(
+                        tweet_ref.tweet,
+                        PostedTweet {
+                            message: msg_ref.clone(),
+                            user_name: tweet_user.name.clone(),
+                        },
+                    );
+
                     if tweet_channel == twitter_channel {
                         return TweetReply::SameChannel(tweet_user.name.clone(), msg_ref);
                     } else if let Some(msg_id) = msg_ref.message_id {
@@ -268,26 +543,46 @@ impl DiscordApi {
         TweetReply::None
     }
 
-    #[allow(clippy::too_many_lines)]
-    #[instrument(skip(ctx, config, channel))]
+    #[instrument(skip(ctx, config, channel, tweet_dms, cache_flusher, backfilled))]
     async fn posting_thread(
         ctx: Context,
         config: Arc<Config>,
         mut channel: mpsc::Receiver<DiscordMessageData>,
+        tweet_dms: Option<mpsc::UnboundedSender<HoloTweet>>,
+        mut cache_flusher: broadcast::Receiver<Cache>,
+        backfilled: Arc<AtomicBool>,
     ) {
-        let mut tweet_messages = LruCache::new(1024.try_into().unwrap());
+        let mut tweet_store = TweetStore::new(&config.database);
+        let mut live_alerts = LiveAlerts::new();
 
         loop {
-            if let Some(msg) = channel
-                .recv()
-                .instrument(debug_span!("Waiting for Discord message request."))
-                .await
-            {
-                match msg {
+            let msg = tokio::select! {
+                msg = channel
+                    .recv()
+                    .instrument(debug_span!("Waiting for Discord message request.")) => msg,
+                Ok(cache) = cache_flusher.recv() => {
+                    if cache == Cache::TweetReplies {
+                        info!("Flushing tweet reply cache.");
+                        tweet_store.clear_memory();
+                    }
+
+                    continue;
+                }
+            };
+
+            if let Some(msg) = msg {
+                let result = match msg {
                     DiscordMessageData::Tweet(tweet) => {
                         let tweet_id = tweet.id;
                         let name = tweet.user.name.clone();
 
+                        // Subscribers get their DMs whether or not the tweet has a feed.
+                        if let Some(tweet_dms) = &tweet_dms {
+                            if tweet_dms.send(tweet.clone()).is_err() {
+                                warn!(id = tweet_id, "Tweet DM thread has stopped.");
+                            }
+                        }
+
                         let twitter_channel = match tweet.user.get_twitter_channel(&config) {
                             Some(ch) => ch,
                             None => {
@@ -299,932 +594,4485 @@ impl DiscordApi {
                             }
                         };
 
+                        if !Self::feature_enabled(&ctx, twitter_channel, Feature::Tweets).await {
+                            continue;
+                        }
+
                         let reply = Self::check_if_reply(
                             &ctx,
                             &config,
                             &tweet,
                             twitter_channel,
-                            &mut tweet_messages,
+                            &mut tweet_store,
+                            &backfilled,
                         )
                         .await;
 
-                        let message = Self::send_message(&ctx.http, twitter_channel, |m| {
-                            m.embed(|e| {
-                                e.colour(tweet.user.colour).author(|a| {
-                                    a.name(&tweet.user.name);
-                                    a.url(&tweet.link);
-                                    a.icon_url(&tweet.user.icon);
-
-                                    a
-                                });
-
-                                if let TweetReply::OtherChannel(user, link) = &reply {
-                                    e.field(
-                                        format!("Replying to {}", user),
-                                        format!("[Link to tweet]({})", link),
-                                        false,
-                                    );
-
-                                    if !tweet.text.is_empty() {
-                                        e.field("Tweet".to_string(), tweet.text, false);
-                                    }
-                                } else {
-                                    e.description(&tweet.text);
-                                }
+                        let fallback = config.twitter.media_fallback(twitter_channel);
 
-                                match &tweet.media[..] {
-                                    [] => (),
-                                    [a, ..] => {
-                                        e.image(a);
-                                    }
-                                };
+                        let mirror = match fallback {
+                            Some(fallback) if !Self::tweet_media_available(&tweet).await => {
+                                warn!(id = tweet_id, "Tweet media unavailable, linking mirror.");
+                                Some(fallback.mirror_link(&tweet.link))
+                            }
+                            _ => None,
+                        };
 
-                                if let Some(translation) = &tweet.translation {
-                                    e.field("Machine Translation", translation, false);
-                                }
+                        let result = Self::post_tweet(
+                            &ctx.http,
+                            &config,
+                            twitter_channel,
+                            &tweet,
+                            reply.clone(),
+                            mirror.as_deref(),
+                        )
+                        .await;
 
-                                e
-                            });
+                        // Discord may also reject the embed itself, so retry once through the mirror.
+                        let result = match (result, fallback) {
+                            (Err(e), Some(fallback)) if mirror.is_none() => {
+                                warn!(
+                                    id = tweet_id,
+                                    "Failed to post tweet, linking mirror: {:?}", e
+                                );
 
-                            if let TweetReply::SameChannel(_, msg_ref) = reply {
-                                m.reference_message(msg_ref);
+                                Self::post_tweet(
+                                    &ctx.http,
+                                    &config,
+                                    twitter_channel,
+                                    &tweet,
+                                    reply,
+                                    Some(&fallback.mirror_link(&tweet.link)),
+                                )
+                                .await
                             }
+                            (result, _) => result,
+                        };
 
-                            m
+                        result.map(|m| {
+                            tweet_store.insert(
+                                tweet_id,
+                                PostedTweet {
+                                    message: MessageReference::from((m.channel, m.id)),
+                                    user_name: name,
+                                },
+                            );
                         })
-                        .await
-                        .context(here!());
-
-                        match message {
-                            Ok(m) => {
-                                tweet_messages.put(
-                                    tweet_id,
-                                    (MessageReference::from((twitter_channel, m.id)), name),
-                                );
-                            }
-                            Err(e) => {
-                                error!("{:?}", e);
-                                continue;
-                            }
-                        }
                     }
                     DiscordMessageData::ScheduledLive(live) => {
-                        if let Some(talent) = config.talents.iter().find(|u| **u == live.streamer) {
-                            let livestream_channel = config.stream_tracking.alerts.channel;
-                            let role = talent.discord_role;
-
-                            let message = Self::send_message(&ctx.http, livestream_channel, |m| {
-                                if let Some(role) = role {
-                                    m.content(Mention::from(role))
-                                        .allowed_mentions(|am| am.empty_parse().roles(vec![role]));
-                                }
+                        let channel =
+                            match config.stream_tracking.alerts.channel_for(live.members_only) {
+                                Some(channel) => channel,
+                                None => continue,
+                            };
 
-                                m.embed(|e| {
-                                    e.title(format!("{} just went live!", talent.name))
-                                        .description(live.title)
-                                        .url(&live.url)
-                                        .timestamp(live.start_at)
-                                        .colour(talent.colour)
-                                        .image(&live.thumbnail)
-                                        .author(|a| {
-                                            a.name(&talent.name)
-                                                .url(format!(
-                                                    "https://www.youtube.com/channel/{}",
-                                                    talent.youtube_ch_id.as_ref().unwrap()
-                                                ))
-                                                .icon_url(&talent.icon)
-                                        })
-                                })
-                            })
-                            .await
-                            .context(here!());
+                        if !Self::feature_enabled(&ctx, channel, Feature::LiveAlerts).await {
+                            continue;
+                        }
 
-                            if let Err(e) = message {
-                                error!("{:?}", e);
-                                continue;
+                        #[cfg(feature = "translation")]
+                        let translations = alert_translation::translate_title(&config, &live).await;
+                        #[cfg(not(feature = "translation"))]
+                        let translations = Vec::new();
+
+                        let result =
+                            Self::post_live_alert(&ctx.http, &config, &live, &translations).await;
+
+                        let alerts = &config.stream_tracking.alerts;
+                        let track = alerts.live_viewers
+                            || alerts.on_cancelled != CancelledAlertAction::Keep;
+
+                        result.map(|alert| {
+                            if let (Some(alert), true) = (alert, track) {
+                                // No stream is live for a day, so older alerts can be dropped.
+                                live_alerts.retain(|_, (a, ..)| {
+                                    alert.message.timestamp - a.message.timestamp
+                                        < Duration::days(1)
+                                });
+                                live_alerts.insert(live.id.clone(), (alert, live, translations));
                             }
+                        })
+                    }
+                    DiscordMessageData::LiveViewers(id, viewers) => match live_alerts.get(&id) {
+                        Some((alert, live, translations)) => {
+                            Self::update_live_alert_viewers(
+                                &ctx.http,
+                                &config,
+                                alert,
+                                live,
+                                translations,
+                                viewers,
+                            )
+                            .await
                         }
+                        None => Ok(()),
+                    },
+                    DiscordMessageData::Cancelled(id) => {
+                        Self::take_down_cancelled(&ctx.http, &config, &mut live_alerts, &id).await
                     }
                     DiscordMessageData::ScheduleUpdate(update) => {
-                        if let Some(talent) = config
-                            .talents
-                            .iter()
-                            .find(|u| u.twitter_id.unwrap() == update.twitter_id)
-                        {
-                            let schedule_channel = config.twitter.schedule_updates.channel;
-                            let role = talent.discord_role;
-
-                            let message = Self::send_message(&ctx.http, schedule_channel, |m| {
-                                if let Some(role) = role {
-                                    m.content(Mention::from(role))
-                                        .allowed_mentions(|am| am.empty_parse().roles(vec![role]));
-                                }
-
-                                m.embed(|e| {
-                                    e.title(format!(
-                                        "{} just released a schedule update!",
-                                        talent.name
-                                    ))
-                                    .description(update.tweet_text)
-                                    .url(update.tweet_link)
-                                    .timestamp(update.timestamp)
-                                    .colour(talent.colour)
-                                    .image(update.schedule_image)
-                                    .author(|a| {
-                                        a.name(&talent.name)
-                                            .url(format!(
-                                                "https://www.youtube.com/channel/{}",
-                                                talent.youtube_ch_id.as_ref().unwrap()
-                                            ))
-                                            .icon_url(&talent.icon)
-                                    })
-                                })
-                            })
-                            .await
-                            .context(here!());
+                        let channel = config.twitter.schedule_updates.channel;
 
-                            if let Err(e) = message {
-                                error!("{:?}", e);
-                                continue;
-                            }
+                        if !Self::feature_enabled(&ctx, channel, Feature::ScheduleUpdates).await {
+                            continue;
                         }
+
+                        Self::post_schedule_update(&ctx.http, &config, &update).await
                     }
                     DiscordMessageData::Birthday(birthday) => {
-                        if let Some(talent) =
-                            config.talents.iter().find(|u| u.name == birthday.user)
-                        {
-                            let birthday_channel = config.birthday_alerts.channel;
-                            let role = talent.discord_role;
-
-                            let message = Self::send_message(&ctx.http, birthday_channel, |m| {
-                                if let Some(role) = role {
-                                    m.content(Mention::from(role))
-                                        .allowed_mentions(|am| am.empty_parse().roles(vec![role]));
-                                }
-
-                                m.embed(|e| {
-                                    e.title(format!("It is {}'s birthday today!!!", talent.name))
-                                        .timestamp(birthday.birthday)
-                                        .colour(talent.colour)
-                                        .author(|a| {
-                                            a.name(&talent.name)
-                                                .url(format!(
-                                                    "https://www.youtube.com/channel/{}",
-                                                    talent.youtube_ch_id.as_ref().unwrap()
-                                                ))
-                                                .icon_url(&talent.icon)
-                                        })
-                                })
-                            })
-                            .await
-                            .context(here!());
+                        let channel = match &birthday.celebrant {
+                            Celebrant::Talent(name) => config
+                                .talents
+                                .iter()
+                                .find(|t| t.name == *name)
+                                .map(|t| t.get_birthday_channel(&config)),
+                            Celebrant::User(_) => Some(config.birthday_alerts.channel),
+                        };
 
-                            if let Err(e) = message {
-                                error!("{:?}", e);
-                                continue;
+                        let enabled = match channel {
+                            Some(channel) => {
+                                Self::feature_enabled(&ctx, channel, Feature::Birthdays).await
                             }
+                            None => true,
+                        };
+
+                        if !enabled {
+                            continue;
                         }
+
+                        Self::post_birthday(&ctx.http, &config, &birthday).await
                     }
+                    DiscordMessageData::Donation(donation) => {
+                        Self::post_donation(&ctx.http, &config, &donation).await
+                    }
+                };
+
+                if let Err(e) = result {
+                    error!("{:?}", e);
                 }
             }
         }
     }
 
-    #[allow(clippy::no_effect)]
-    #[instrument(skip(
-        ctx,
-        config,
-        stream_notifier,
-        index_receiver,
-        guild_ready,
-        stream_archiver
-    ))]
-    async fn stream_update_thread(
-        ctx: Context,
-        config: &StreamChatConfig,
-        mut stream_notifier: broadcast::Receiver<StreamUpdate>,
-        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
-        guild_ready: oneshot::Receiver<()>,
-        stream_archiver: mpsc::UnboundedSender<(ChannelId, Option<Livestream>)>,
-    ) -> anyhow::Result<()> {
-        guild_ready.await.context(here!())?;
-
-        let chat_category = config.category;
-        let active_category = chat_category
-            .to_channel(&ctx.http)
-            .await
-            .context(here!())?
-            .category()
-            .unwrap();
+    /// Checks the feature flag of the guild the channel is in, fetching the channel
+    /// if it isn't cached. Channels that can't be fetched have every feature enabled,
+    /// since posting there fails anyway.
+    async fn feature_enabled(ctx: &Context, channel: ChannelId, feature: Feature) -> bool {
+        let guild_id = match ctx.cache.guild_channel(channel) {
+            Some(c) => Some(c.guild_id),
+            None => match channel.to_channel(ctx).await {
+                Ok(c) => c.guild().map(|c| c.guild_id),
+                Err(e) => {
+                    warn!(?e, %channel, "Failed to get the guild of the channel!");
+                    None
+                }
+            },
+        };
 
-        let guild_id = active_category.guild_id;
+        guild_id.map_or(true, |g| feature_flags::is_enabled(g, feature))
+    }
 
-        let ready_index = loop {
-            index_receiver.changed().await.context(here!())?;
-            let index = index_receiver.borrow();
+    /// Checks that every attachment of the tweet can still be fetched from Twitter.
+    async fn tweet_media_available(tweet: &HoloTweet) -> bool {
+        let urls = tweet.media.iter().filter_map(|m| match m {
+            TweetMedia::Photo(url) => Some(url),
+            TweetMedia::Video { url, thumbnail } => url.as_ref().or(thumbnail.as_ref()),
+        });
 
-            if !index.is_empty() {
-                break index.clone();
+        for url in urls {
+            if let Err(e) = utility::http::client()
+                .call("HEAD", url, ureq::Request::call)
+                .await
+            {
+                debug!(%url, "Tweet media unavailable: {:?}", e);
+                return false;
             }
-        };
+        }
 
-        let mut claimed_channels: HashMap<VideoId, (Livestream, ChannelId)> =
-            HashMap::with_capacity(32);
+        true
+    }
 
-        for (ch, topic) in Self::get_old_stream_chats(&ctx, guild_id, chat_category).await? {
-            match Self::try_find_stream_for_channel(&topic, &ready_index) {
-                Some((stream, VideoStatus::Live)) => {
-                    claimed_channels.insert(stream.id.clone(), (stream, ch));
+    /// Posts the tweet, or if a mirror link is given, leaves the media for the mirror's embed to show.
+    /// In feeds shared by several talents, it's posted under the talent's identity if enabled.
+    async fn post_tweet<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        channel: ChannelId,
+        tweet: &HoloTweet,
+        reply: TweetReply,
+        mirror: Option<&str>,
+    ) -> anyhow::Result<SentMessage> {
+        let colour = tweet.user.embed_colour(config, EmbedEvent::Tweet);
+        let icon = image_cache::resolve(&tweet.user.icon).await;
+        let mut message = CreateMessage::default();
+
+        let identity = (config.twitter.webhook_identities
+            && config.is_shared_twitter_feed(channel))
+        .then(|| WebhookIdentity {
+            name: tweet.user.name.clone(),
+            avatar: icon.clone(),
+        });
+
+        // Webhooks can't reply to messages, so the tweet is linked instead.
+        let reply = match (reply, &identity) {
+            (TweetReply::SameChannel(user, msg_ref), Some(_)) => match msg_ref.message_id {
+                Some(id) => {
+                    TweetReply::OtherChannel(user, id.link(msg_ref.channel_id, msg_ref.guild_id))
                 }
-                Some((stream, VideoStatus::Past)) => stream_archiver.send((ch, Some(stream)))?,
-                _ => stream_archiver.send((ch, None))?,
-            }
-        }
+                None => TweetReply::None,
+            },
+            (reply, _) => reply,
+        };
 
-        for stream in ready_index.values() {
-            if claimed_channels.contains_key(&stream.id) || stream.state != VideoStatus::Live {
-                continue;
-            }
+        let media = match mirror {
+            Some(_) => &[][..],
+            None => &tweet.media[..],
+        };
 
-            let claimed_channel = Self::claim_channel(&ctx, &active_category, stream).await?;
-            claimed_channels.insert(stream.id.clone(), (stream.clone(), claimed_channel));
-        }
+        let photos = media
+            .iter()
+            .filter_map(|m| match m {
+                TweetMedia::Photo(url) => Some(url),
+                TweetMedia::Video { .. } => None,
+            })
+            .collect::<Vec<_>>();
 
-        loop {
-            let update = match stream_notifier.recv().await.context(here!()) {
-                Ok(u) => u,
-                Err(e) => {
-                    error!("{:?}", e);
-                    continue;
-                }
-            };
+        let videos = media
+            .iter()
+            .filter_map(|m| match m {
+                TweetMedia::Video { url, thumbnail } => Some((url, thumbnail)),
+                TweetMedia::Photo(_) => None,
+            })
+            .collect::<Vec<_>>();
 
-            match update {
-                StreamUpdate::Started(stream) => {
-                    info!(stream = %stream.title, "Stream started!");
-                    if claimed_channels.contains_key(&stream.id) {
-                        continue;
-                    }
+        message.embed(|e| {
+            e.colour(colour).author(|a| {
+                a.name(&tweet.user.name);
+                a.url(&tweet.link);
+                a.icon_url(&icon);
 
-                    let claim = Self::claim_channel(&ctx, &active_category, &stream).await?;
-                    claimed_channels.insert(stream.id.clone(), (stream, claim));
+                a
+            });
+
+            if let TweetReply::OtherChannel(user, link) = &reply {
+                e.field(
+                    format!("Replying to {}", user),
+                    format!("[Link to tweet]({})", link),
+                    false,
+                );
+
+                if !tweet.text.is_empty() {
+                    e.field("Tweet".to_string(), &tweet.text, false);
                 }
-                StreamUpdate::Ended(id) => {
-                    let (stream, claimed_channel) = match claimed_channels.remove(&id) {
-                        Some(s) => s,
-                        None => continue,
-                    };
+            } else {
+                e.description(&tweet.text);
+            }
 
-                    stream_archiver.send((claimed_channel, Some(stream)))?;
+            // Embeds sharing a URL are shown by Discord as a single image gallery.
+            if photos.len() > 1 {
+                e.url(&tweet.link);
+            }
+
+            match (&photos[..], &videos[..]) {
+                ([photo, ..], _) => {
+                    e.image(photo);
+                }
+                ([], [(_, Some(thumbnail)), ..]) => {
+                    e.image(thumbnail);
                 }
                 _ => (),
+            };
+
+            if let Some(translation) = &tweet.translation {
+                e.field("Machine Translation", translation, false);
             }
+
+            e
+        });
+
+        for photo in photos.iter().skip(1) {
+            message.add_embed(|e| e.url(&tweet.link).image(photo));
         }
-    }
 
-    /* #[instrument(skip(ctx, config, talents, index_receiver, stream_notifier))]
-    async fn mchad_watch_thread(
-        ctx: Arc<CacheAndHttp>,
-        config: &StreamChatConfig,
-        talents: &[Talent],
-        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
-        mut stream_notifier: broadcast::Receiver<StreamUpdate>,
-    ) -> anyhow::Result<()> {
-        let mut live_streams: HashMap<_, _> = loop {
-            index_receiver.changed().await.context(here!())?;
-            let index = index_receiver.borrow();
+        // Discord can't play videos inside rich embeds, but will embed direct links to them.
+        if let Some(mirror) = mirror {
+            message.content(mirror);
+        } else if !videos.is_empty() {
+            let mut links = videos
+                .iter()
+                .map(|(url, _)| url.as_deref().unwrap_or(&tweet.link))
+                .collect::<Vec<_>>();
 
-            if !index.is_empty() {
-                break index
-                    .iter()
-                    .filter(|(_, s)| s.state == VideoStatus::Live)
-                    .map(|(id, l)| (id.clone(), l.streamer.twitter_id))
-                    .collect();
-            }
-        };
+            links.dedup();
+            message.content(links.join("\n"));
+        }
 
-        let guild_id = config
-            .category
-            .to_channel(&ctx.http)
-            .await
-            .context(here!())?
-            .category()
-            .unwrap()
-            .guild_id;
+        if let TweetReply::SameChannel(_, msg_ref) = reply {
+            message.reference_message(msg_ref);
+        }
 
-        let mut mchad = Client::new();
+        match identity {
+            Some(identity) => sink.send_as(channel, &identity, message).await,
+            None => sink.send_message(channel, message).await,
+        }
+    }
+
+    /// Sends tweets by DM to the members subscribed to their talent, sending the ones
+    /// over a member's hourly limit together as a digest once they can be sent another.
+    async fn tweet_dm_thread(
+        ctx: Context,
+        config: &Config,
+        mut tweets: mpsc::UnboundedReceiver<HoloTweet>,
+    ) -> anyhow::Result<()> {
+        let subscriptions = tweet_subscriptions::store().context(here!())?;
+        let mut budget = DmBudget::new(config.twitter.dm_subscriptions.max_per_hour);
 
         loop {
+            let next_digest = budget
+                .next_digest_at()
+                .map(|at| (at - Utc::now()).to_std().unwrap_or_default());
+
             tokio::select! {
-                res = stream_notifier.recv() => {
-                    let update = match res.context(here!()) {
-                        Ok(u) => u,
-                        Err(e) => {
-                            error!("{:?}", e);
-                            continue;
-                        }
+                tweet = tweets.recv() => {
+                    let Some(tweet) = tweet else {
+                        return Ok(());
                     };
 
-                    match update {
-                        StreamUpdate::Started(stream) => {
-                            live_streams.insert(stream.id.clone(), stream.streamer.twitter_id);
-                        }
-                        StreamUpdate::Ended(id) => {
-                            live_streams.remove(&id);
-                        }
-                        _ => (),
-                    }
-                }
+                    let now = Utc::now();
 
-                res = mchad.room_updates.recv() => {
-                    let update = match res.context(here!()) {
-                        Ok(u) => u,
-                        Err(e) => {
-                            error!("{:?}", e);
+                    for user in subscriptions.subscribers(&tweet.user.name) {
+                        if budget.offer(user, tweet.clone(), now) != Delivery::Direct {
                             continue;
                         }
-                    };
-
-                    match update {
-                        RoomUpdate::Added(stream) | RoomUpdate::Changed(_, stream) => {
-                            let video_id: VideoId = match (*stream).parse() {
-                                Ok(id) => id,
-                                Err(e) => {
-                                    error!("{:?}", e);
-                                    continue;
-                                }
-                            };
 
-                            if live_streams.contains_key(&video_id) {
-                                let talent_twitter_id = live_streams.get(&video_id).unwrap();
-                                let talent = match talents.iter().find(|u| u.twitter_id == *talent_twitter_id) {
-                                    Some(u) => u.clone(),
-                                    None => continue,
-                                };
-
-                                if let Some(listener) = mchad.get_listener(&video_id).await {
-                                    let ctx = Arc::clone(&ctx);
+                        let result = match user.create_dm_channel(&ctx).await {
+                            Ok(dm) => Self::post_tweet(
+                                &ctx.http,
+                                config,
+                                dm.id,
+                                &tweet,
+                                TweetReply::None,
+                                None,
+                            )
+                            .await
+                            .map(|_| ()),
+                            Err(e) => Err(e).context(here!()),
+                        };
 
-                                    tokio::spawn(async move {
-                                        Self::bounce_mchad_messages(ctx, guild_id, video_id.clone(), talent, listener).await
-                                    });
-                                }
-                            }
+                        if let Err(e) = result {
+                            warn!(%user, id = tweet.id, "Failed to DM tweet: {:?}", e);
                         }
-
-                        _ => (),
                     }
                 }
+                _ = sleep(next_digest.unwrap_or_default()), if next_digest.is_some() => {}
+            }
+
+            for (user, tweets) in budget.due_digests(Utc::now()) {
+                let result = match user.create_dm_channel(&ctx).await {
+                    Ok(dm) => Self::post_tweet_digest(&ctx.http, config, dm.id, &tweets).await,
+                    Err(e) => Err(e).context(here!()),
+                };
+
+                if let Err(e) = result {
+                    warn!(%user, count = tweets.len(), "Failed to DM tweet digest: {:?}", e);
+                }
             }
         }
     }
 
-    #[instrument(skip(ctx, talent))]
-    async fn bounce_mchad_messages(
-        ctx: Arc<CacheAndHttp>,
-        guild_id: GuildId,
-        stream: VideoId,
-        talent: Talent,
-        listener: Listener,
+    async fn post_tweet_digest<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        channel: ChannelId,
+        tweets: &[HoloTweet],
     ) -> anyhow::Result<()> {
-        let (channel, _) = guild_id.channels(&ctx.http).await?.into_iter().find(|(_, ch)| {
-            matches!(&ch.topic, Some(url) if *url == format!("https://youtube.com/watch?v={}", &stream))
-        }).ok_or_else(|| anyhow!("Failed to find stream!"))?;
+        let colour = match tweets {
+            [tweet, ..] => tweet.user.embed_colour(config, EmbedEvent::Tweet),
+            [] => return Ok(()),
+        };
 
-        let mut posted_messages: Vec<Message> = Vec::with_capacity(1024);
-        let mut message_indices: HashMap<String, (usize, usize)> = HashMap::with_capacity(1024);
-        let mut message_stats: HashMap<MessageId, (usize, usize)> = HashMap::with_capacity(1024);
+        let mut message = CreateMessage::default();
+
+        message.embed(|e| {
+            e.title("Tweets you missed")
+                .description(Self::tweet_digest_description(tweets))
+                .colour(colour)
+                .footer(|f| {
+                    f.text(format!(
+                        "You're sent at most {} tweets an hour, the rest are collected here.",
+                        config.twitter.dm_subscriptions.max_per_hour
+                    ))
+                })
+        });
 
-        let mut last_tl_message = MessageId(0);
+        sink.send_message(channel, message).await?;
 
-        let room_name = listener.room.borrow().name.clone();
+        Ok(())
+    }
 
-        info!(%stream, "Starting to listen!");
+    fn tweet_digest_description(tweets: &[HoloTweet]) -> String {
+        let mut description = String::new();
+
+        for (i, tweet) in tweets.iter().enumerate() {
+            let mut snippet = tweet
+                .text
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace(['[', ']'], "");
+
+            if snippet.chars().count() > Self::TWEET_DIGEST_SNIPPET_LENGTH {
+                snippet = snippet
+                    .chars()
+                    .take(Self::TWEET_DIGEST_SNIPPET_LENGTH - 1)
+                    .chain(std::iter::once('…'))
+                    .collect();
+            } else if snippet.is_empty() {
+                snippet = "Link to tweet".to_owned();
+            }
 
-        let mut event_stream = Box::pin(listener);
+            let line = format!(
+                "**{}** <t:{}:R>: [{}]({})\n",
+                tweet.user.name,
+                tweet.timestamp.timestamp(),
+                snippet,
+                tweet.link
+            );
 
-        let notification_embed = CreateEmbed::default()
-            .author(|a| {
-                a.name("MChad Discord Integration")
-                    .url("https://mchatx.org/")
-            })
-            .colour(talent.colour)
-            .footer(|f| f.text(format!("Room: {}", room_name)))
-            .to_owned();
+            let remaining = format!("…and {} more.", tweets.len() - i);
 
-        let default_embed = CreateEmbed::default()
-            .author(|a| a.name(&talent.english_name).icon_url(&talent.icon))
-            .colour(talent.colour)
-            .to_owned();
+            if description.len() + line.len() + remaining.len() > Self::EMBED_DESCRIPTION_LIMIT {
+                description.push_str(&remaining);
+                break;
+            }
 
-        while let Some(event) = event_stream.next().await {
-            use EventData::*;
+            description.push_str(&line);
+        }
 
-            debug!(?event);
+        description
+    }
 
-            match event {
-                Connect(_msg) => {
-                    debug!(message = %_msg, "Connected to MChad.");
+    /// Returns the alert that was posted, if the streamer is tracked.
+    async fn post_live_alert<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        live: &Livestream,
+        translations: &[TranslatedTitle],
+    ) -> anyhow::Result<Option<PostedAlert>> {
+        let talent = match config.talents.iter().find(|u| **u == live.streamer) {
+            Some(talent) => talent,
+            None => return Ok(None),
+        };
 
-                    let _ = channel
-                        .send_message(&ctx.http, |m| {
-                            m.set_embed(
-                                notification_embed
-                                    .clone()
-                                    .description("Connected to MChad!")
-                                    .to_owned(),
-                            )
-                        })
-                        .await?;
-                }
+        let channel = match config.stream_tracking.alerts.channel_for(live.members_only) {
+            Some(channel) => channel,
+            None => return Ok(None),
+        };
 
-                Update(RoomEvent { id, text, time: _ })
-                | Insert(RoomEvent { id, text, time: _ })
-                    if message_indices.contains_key(&id) =>
-                {
-                    debug!(%id, %text, "Updating message.");
+        let talent = &Self::with_cached_icon(talent).await;
+        let live = &Self::with_cached_images(live).await;
+
+        let roles = live
+            .participants()
+            .filter_map(|t| t.discord_role)
+            .collect::<Vec<_>>();
+
+        let values = AlertValues::live(talent, live, &roles);
+
+        // Translations with a channel of their own replace the title in a copy of the alert.
+        let alert = |translation: Option<&TranslatedTitle>| {
+            let mut message = CreateMessage::default();
+            Self::alert_content(
+                &mut message,
+                &config.stream_tracking.alerts.templates.content,
+                &values,
+                roles.clone(),
+            );
+
+            message.embed(|e| {
+                Self::live_alert_copy_embed(
+                    e,
+                    config,
+                    talent,
+                    live,
+                    translations,
+                    translation,
+                    None,
+                )
+            });
+            message
+        };
 
-                    let (msg_idx, row_idx) = message_indices.get(&id).unwrap();
-                    let msg = posted_messages.get_mut(*msg_idx).unwrap();
+        let sent = sink.send_message(channel, alert(None)).await?;
 
-                    let EmbedRowEdit { size } = msg
-                        .edit_embed_row(&ctx, &default_embed, *row_idx, text)
-                        .await?;
+        STREAM_LATENCY.record(LatencyKind::Alert, &live.id, live.start_at, sent.timestamp);
 
-                    let (_, bytes) = message_stats.get_mut(&msg.id).unwrap();
-                    *bytes = size;
-                }
+        let mut posted = PostedAlert {
+            message: sent,
+            copies: Vec::new(),
+        };
 
-                Insert(RoomEvent { id, text, time: _ })
-                | Update(RoomEvent { id, text, time: _ })
-                    if !message_indices.contains_key(&id) =>
-                {
-                    debug!(%id, %text, "New message.");
+        // Member-only streams stay out of the extra channels, which are usually public.
+        if !live.members_only {
+            let topic_channels = config
+                .stream_tracking
+                .alerts
+                .topic_channels_for(live.topic.as_deref())
+                .filter(|c| *c != channel)
+                .map(|c| (c, None));
 
-                    let should_update_msg = Self::get_last_message_id_in_channel(&ctx, &channel)
-                        .await
-                        .map(|id| {
-                            if id != last_tl_message {
-                                return false;
-                            }
+            let language_channels = translations
+                .iter()
+                .filter_map(|t| t.channel.map(|c| (c, Some(t))));
 
-                            let (_, msg_size) = message_stats.get(&id).unwrap();
+            for (extra_channel, translation) in topic_channels.chain(language_channels) {
+                match sink.send_message(extra_channel, alert(translation)).await {
+                    Ok(copy) => posted.copies.push((copy, translation.cloned())),
+                    Err(e) => {
+                        error!(?e, channel = %extra_channel, "Failed to post alert in extra channel!");
+                    }
+                }
+            }
+        }
 
-                            if msg_size + text.len() > 4096 {
-                                return false;
-                            }
+        Ok(Some(posted))
+    }
 
-                            true
-                        })
-                        .unwrap_or(false);
+    /// Rebuilds a live alert and its copies with the current viewer count of the stream.
+    async fn update_live_alert_viewers<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        alert: &PostedAlert,
+        live: &Livestream,
+        translations: &[TranslatedTitle],
+        viewers: u32,
+    ) -> anyhow::Result<()> {
+        let talent = match config.talents.iter().find(|u| **u == live.streamer) {
+            Some(talent) => talent,
+            None => return Ok(()),
+        };
 
-                    if should_update_msg {
-                        let msg_idx = posted_messages
-                            .iter()
-                            .position(|m| m.id == last_tl_message)
-                            .unwrap();
-                        let message = posted_messages.get_mut(msg_idx).unwrap();
+        let talent = &Self::with_cached_icon(talent).await;
+        let live = &Self::with_cached_images(live).await;
+
+        for (message, translation) in alert.messages() {
+            let mut edit = EditMessage::default();
+            edit.embed(|e| {
+                Self::live_alert_copy_embed(
+                    e,
+                    config,
+                    talent,
+                    live,
+                    translations,
+                    translation,
+                    Some(viewers),
+                )
+            });
 
-                        let EmbedRowAddition { size } =
-                            message.add_embed_row(&ctx, &default_embed, text).await?;
+            sink.edit_message(message.channel, message.id, edit).await?;
+        }
 
-                        let (last_row, bytes) = message_stats.get_mut(&last_tl_message).unwrap();
+        Ok(())
+    }
 
-                        *last_row += 1;
-                        *bytes = size;
+    /// Takes down the alert of a cancelled stream, if one was posted and is still tracked.
+    async fn take_down_cancelled<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        live_alerts: &mut LiveAlerts,
+        id: &VideoId,
+    ) -> anyhow::Result<()> {
+        match live_alerts.remove(id) {
+            Some((alert, live, translations)) => {
+                Self::take_down_live_alert(sink, config, &alert, &live, &translations).await
+            }
+            None => Ok(()),
+        }
+    }
 
-                        message_indices.insert(id, (msg_idx, *last_row));
-                    } else {
-                        let text_length = text.len();
+    /// Deletes the alert of a cancelled stream and its copies, or marks them as cancelled,
+    /// depending on the config.
+    async fn take_down_live_alert<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        alert: &PostedAlert,
+        live: &Livestream,
+        translations: &[TranslatedTitle],
+    ) -> anyhow::Result<()> {
+        let talent = match config.talents.iter().find(|u| **u == live.streamer) {
+            Some(talent) => talent,
+            None => return Ok(()),
+        };
 
-                        let message = channel
-                            .send_message(&ctx.http, |m| {
-                                m.set_embed(default_embed.clone().description(&text).to_owned())
-                            })
-                            .await?;
+        info!(stream = %live.title, "Taking down alert of cancelled stream.");
 
-                        last_tl_message = message.id;
+        match config.stream_tracking.alerts.on_cancelled {
+            CancelledAlertAction::Keep => Ok(()),
+            CancelledAlertAction::Delete => {
+                for (message, _) in alert.messages() {
+                    sink.delete_message(message.channel, message.id).await?;
+                }
 
-                        message_indices.insert(id, (posted_messages.len(), 0));
-                        message_stats.insert(message.id, (0, text_length));
-                        posted_messages.push(message);
+                Ok(())
+            }
+            CancelledAlertAction::MarkCancelled => {
+                let talent = &Self::with_cached_icon(talent).await;
+                let live = &Self::with_cached_images(live).await;
+
+                for (message, translation) in alert.messages() {
+                    let title = translation.map_or(&live.title, |t| &t.title);
+
+                    let mut edit = EditMessage::default();
+                    edit.embed(|e| {
+                        Self::live_alert_copy_embed(
+                            e,
+                            config,
+                            talent,
+                            live,
+                            translations,
+                            translation,
+                            None,
+                        )
+                        .description(format!("~~{title}~~\nThis stream was cancelled."))
+                    });
+
+                    sink.edit_message(message.channel, message.id, edit).await?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// The talent with its icon swapped for the mirrored copy, if the original is unavailable.
+    async fn with_cached_icon(talent: &Talent) -> Talent {
+        let mut talent = talent.clone();
+        talent.icon = image_cache::resolve(&talent.icon).await;
+        talent
+    }
+
+    /// The stream with its thumbnail and the icon of its streamer swapped for their
+    /// mirrored copies, if the originals are unavailable.
+    async fn with_cached_images(stream: &Livestream) -> Livestream {
+        let mut stream = stream.clone();
+        stream.thumbnail = image_cache::resolve(&stream.thumbnail).await;
+        stream.streamer.icon = image_cache::resolve(&stream.streamer.icon).await;
+        stream
+    }
+
+    /// The embed of a live alert, or of a copy of it with the title replaced by its translation.
+    fn live_alert_copy_embed<'a>(
+        embed: &'a mut CreateEmbed,
+        config: &Config,
+        talent: &Talent,
+        live: &Livestream,
+        translations: &[TranslatedTitle],
+        translation: Option<&TranslatedTitle>,
+        viewers: Option<u32>,
+    ) -> &'a mut CreateEmbed {
+        match translation {
+            Some(translation) => Self::live_alert_embed(embed, config, talent, live, &[], viewers)
+                .description(format!("{}\n*{}*", translation.title, live.title)),
+            None => Self::live_alert_embed(embed, config, talent, live, translations, viewers),
+        }
+    }
+
+    fn live_alert_embed<'a>(
+        embed: &'a mut CreateEmbed,
+        config: &Config,
+        talent: &Talent,
+        live: &Livestream,
+        translations: &[TranslatedTitle],
+        viewers: Option<u32>,
+    ) -> &'a mut CreateEmbed {
+        let templates = &config.stream_tracking.alerts.templates;
+        let values = AlertValues::live(talent, live, &[]);
+
+        let title = if live.is_collab() {
+            templates.collab_title.render(|p| values.get(p))
+        } else {
+            templates.title.render(|p| values.get(p))
+        };
+
+        embed
+            .title(&title)
+            .description(&live.title)
+            .url(&live.url)
+            .timestamp(live.start_at)
+            .colour(talent.embed_colour(config, EmbedEvent::LiveAlert))
+            .image(&live.thumbnail)
+            .footer(|f| {
+                if live.members_only {
+                    f.text(format!("{} • Members only", talent.generation_name(config)))
+                } else {
+                    f.text(talent.generation_name(config))
+                }
+            })
+            .author(|a| {
+                a.name(&talent.name)
+                    .url(format!(
+                        "https://www.youtube.com/channel/{}",
+                        talent.youtube_ch_id.as_ref().unwrap()
+                    ))
+                    .icon_url(&talent.icon)
+            });
+
+        for translation in translations.iter().filter(|t| t.channel.is_none()) {
+            embed.field(
+                format!("Title ({})", translation.language),
+                &translation.title,
+                false,
+            );
+        }
+
+        if let (Some(topic), false) = (live.topic_name(), live.members_only) {
+            embed.field("Topic", topic, true);
+        }
+
+        if let Some(viewers) = viewers {
+            embed.field("Watching now", viewers, true);
+        }
+
+        embed
+    }
+
+    async fn post_schedule_update<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        update: &ScheduleUpdate,
+    ) -> anyhow::Result<()> {
+        let talent = match config
+            .talents
+            .iter()
+            .find(|u| u.twitter_id == Some(update.twitter_id))
+        {
+            Some(talent) => talent,
+            None => return Ok(()),
+        };
+
+        let talent = &Self::with_cached_icon(talent).await;
+        let templates = &config.twitter.schedule_updates.templates;
+        let roles = talent.discord_role.into_iter().collect::<Vec<_>>();
+
+        let values = AlertValues {
+            talent: &talent.name,
+            talents: talent.name.clone(),
+            count: 1,
+            title: &update.tweet_text,
+            url: &update.tweet_link,
+            start: update.timestamp,
+            pings: AlertValues::pings(&roles),
+        };
+
+        let mut message = CreateMessage::default();
+        Self::alert_content(&mut message, &templates.content, &values, roles);
+
+        message.embed(|e| {
+            e.title(templates.title.render(|p| values.get(p)))
+                .description(&update.tweet_text)
+                .url(&update.tweet_link)
+                .timestamp(update.timestamp)
+                .colour(talent.embed_colour(config, EmbedEvent::ScheduleUpdate))
+                .image(&update.schedule_image)
+                .author(|a| {
+                    a.name(&talent.name)
+                        .url(format!(
+                            "https://www.youtube.com/channel/{}",
+                            talent.youtube_ch_id.as_ref().unwrap()
+                        ))
+                        .icon_url(&talent.icon)
+                })
+        });
+
+        sink.send_message(config.twitter.schedule_updates.channel, message)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Thanks a supporter for their donation on Ko-fi.
+    async fn post_donation<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        donation: &Donation,
+    ) -> anyhow::Result<()> {
+        let kofi = &config.support.kofi;
+
+        let supporter = donation.from_name.as_deref().unwrap_or("Someone");
+        let thanks = match (donation.subscription, kofi.show_amount) {
+            (true, true) => format!(
+                "{supporter} became a supporter with {} {} a month!",
+                donation.amount, donation.currency
+            ),
+            (true, false) => format!("{supporter} became a supporter!"),
+            (false, true) => format!(
+                "{supporter} donated {} {}!",
+                donation.amount, donation.currency
+            ),
+            (false, false) => format!("{supporter} made a donation!"),
+        };
+
+        let mut message = CreateMessage::default();
+        message.embed(|e| {
+            e.title("Thank you for the support!")
+                .description(thanks)
+                .colour(config.theme.colour(EmbedEvent::Support));
+
+            if let Some(text) = &donation.message {
+                e.field("Message", text, false);
+            }
+
+            e
+        });
+
+        sink.send_message(kofi.channel, message).await?;
+        Ok(())
+    }
+
+    async fn post_birthday<S: MessageSink>(
+        sink: &S,
+        config: &Config,
+        birthday: &Birthday,
+    ) -> anyhow::Result<()> {
+        let name = match &birthday.celebrant {
+            Celebrant::Talent(name) => name,
+            Celebrant::User(user) => {
+                let mut message = CreateMessage::default();
+                message
+                    .content(format!("Happy birthday, {}! 🎂", Mention::from(*user)))
+                    .allowed_mentions(|am| am.empty_parse().users([*user]));
+
+                sink.send_message(config.birthday_alerts.channel, message)
+                    .await?;
+
+                return Ok(());
+            }
+        };
+
+        let talent = match config.talents.iter().find(|u| u.name == *name) {
+            Some(talent) => talent,
+            None => return Ok(()),
+        };
+
+        let talent = &Self::with_cached_icon(talent).await;
+        let templates = &config.birthday_alerts.templates;
+        let roles = talent.discord_role.into_iter().collect::<Vec<_>>();
+        let channel_url = format!(
+            "https://www.youtube.com/channel/{}",
+            talent.youtube_ch_id.as_ref().unwrap()
+        );
+
+        let values = AlertValues {
+            talent: &talent.name,
+            talents: talent.name.clone(),
+            count: 1,
+            title: "",
+            url: &channel_url,
+            start: birthday.birthday,
+            pings: AlertValues::pings(&roles),
+        };
+
+        let mut message = CreateMessage::default();
+        Self::alert_content(&mut message, &templates.content, &values, roles);
+
+        message.embed(|e| {
+            e.title(templates.title.render(|p| values.get(p)))
+                .timestamp(birthday.birthday)
+                .colour(talent.embed_colour(config, EmbedEvent::Birthday))
+                .footer(|f| f.text(talent.generation_name(config)))
+                .author(|a| {
+                    a.name(&talent.name)
+                        .url(&channel_url)
+                        .icon_url(&talent.icon)
+                })
+        });
+
+        sink.send_message(talent.get_birthday_channel(config), message)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sets the message content from the template, allowing only the given roles to be pinged.
+    fn alert_content(
+        message: &mut CreateMessage,
+        template: &Template,
+        values: &AlertValues,
+        roles: Vec<RoleId>,
+    ) {
+        let content = template.render(|p| values.get(p));
+
+        if !content.trim().is_empty() {
+            message
+                .content(content)
+                .allowed_mentions(|am| am.empty_parse().roles(roles));
+        }
+    }
+
+    #[allow(clippy::no_effect, clippy::too_many_arguments)]
+    #[instrument(skip(
+        ctx,
+        config,
+        stream_notifier,
+        index_receiver,
+        stream_archiver,
+        claimed_chats
+    ))]
+    async fn stream_update_thread(
+        ctx: Context,
+        config: &StreamChatConfig,
+        alerts: &StreamAlertsConfig,
+        theme: ThemeConfig,
+        mut stream_notifier: UpdateSubscription,
+        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
+        stream_archiver: mpsc::UnboundedSender<(ChannelId, Option<Livestream>)>,
+        claimed_chats: watch::Sender<HashMap<VideoId, ChannelId>>,
+    ) -> anyhow::Result<()> {
+        let alerts_channel = alerts.channel;
+        // Member-only alerts, and so their threads, can be in a channel of their own.
+        let members_channel = alerts
+            .channel_for(true)
+            .filter(|channel| *channel != alerts_channel);
+
+        let mut required = vec![RequiredChannel::new("Stream alerts", alerts_channel)];
+
+        if let Some(channel) = members_channel {
+            required.push(RequiredChannel::new("Member-only stream alerts", channel));
+        }
+
+        if config.mode == StreamChatMode::Channels {
+            required.push(RequiredChannel::new(
+                "Stream chat category",
+                config.category,
+            ));
+        }
+
+        let channels =
+            readiness::wait_for_channels(&ctx, &required, readiness::DEFAULT_TIMEOUT).await?;
+
+        let active_category = match config.mode {
+            StreamChatMode::Channels => Some(channels.category(config.category)?),
+            StreamChatMode::Threads => None,
+        };
+
+        let guild_id = match &active_category {
+            Some(category) => category.guild_id,
+            None => channels.guild_id(alerts_channel)?,
+        };
+
+        let ready_index = loop {
+            index_receiver.changed().await.context(here!())?;
+            let index = index_receiver.borrow();
+
+            if !index.is_empty() {
+                break index.clone();
+            }
+        };
+
+        let mut claimed_channels: HashMap<VideoId, (Livestream, ChannelId)> =
+            HashMap::with_capacity(32);
+        // Read-only chats claimed for streams that haven't started yet.
+        let mut early_channels: HashMap<VideoId, ChannelId> = HashMap::new();
+        let mut slowmode_monitors: HashMap<ChannelId, oneshot::Sender<()>> = HashMap::new();
+        // Old chats whose stream had an unknown status, left alone until it starts or ends.
+        let mut quarantined_channels: HashMap<VideoId, ChannelId> = HashMap::new();
+        // Chats are claimed in the background, since the alert a thread is started from can
+        // take a while to show up. The streams are removed from here if they end meanwhile.
+        let (claims_tx, mut claims_rx) = mpsc::unbounded_channel();
+        let mut pending_claims: HashSet<VideoId> = HashSet::new();
+
+        let old_stream_chats: Vec<_> = match &active_category {
+            Some(category) => Self::get_old_stream_chats(&ctx, guild_id, category.id)
+                .await?
+                .collect(),
+            None => {
+                let mut threads =
+                    Self::get_old_stream_threads(&ctx, guild_id, alerts_channel).await?;
+
+                if let Some(channel) = members_channel {
+                    threads.extend(Self::get_old_stream_threads(&ctx, guild_id, channel).await?);
+                }
+
+                threads
+            }
+        };
+
+        for (ch, topic) in old_stream_chats {
+            match Self::try_find_stream_for_channel(&topic, &ready_index) {
+                Some((stream, VideoStatus::Live)) => {
+                    Self::start_slowmode_monitor(&ctx, config, theme, ch, &mut slowmode_monitors);
+                    claimed_channels.insert(stream.id.clone(), (stream, ch));
+                }
+                Some((stream, VideoStatus::Past)) => stream_archiver.send((ch, Some(stream)))?,
+                Some((stream, VideoStatus::Upcoming)) if config.early_chats.enabled => {
+                    early_channels.insert(stream.id, ch);
+                }
+                Some((_, VideoStatus::Upcoming)) | None => stream_archiver.send((ch, None))?,
+                Some((stream, status)) => {
+                    warn!(
+                        stream = %stream.title,
+                        channel = %ch,
+                        ?status,
+                        action = ?config.unknown_status,
+                        "Old stream chat has a stream with an unknown status."
+                    );
+
+                    match config.unknown_status {
+                        UnknownStreamStatus::Archive => {
+                            stream_archiver.send((ch, Some(stream)))?;
+                        }
+                        UnknownStreamStatus::Ignore => {
+                            quarantined_channels.insert(stream.id, ch);
+                        }
                     }
                 }
+            }
+        }
 
-                Delete(id) => {
-                    debug!(%id, "Deleting message.");
+        for stream in ready_index.values() {
+            if claimed_channels.contains_key(&stream.id) || stream.state != VideoStatus::Live {
+                continue;
+            }
 
-                    if let Some((msg_idx, row_idx)) = message_indices.remove(&id) {
-                        let mut remove_msg = false;
+            let stream_alerts_channel =
+                match Self::chat_alerts_channel(alerts, active_category.as_ref(), stream) {
+                    Some(channel) => channel,
+                    None => continue,
+                };
 
-                        if let Some(message) = posted_messages.get_mut(msg_idx) {
-                            let EmbedRowRemoval {
-                                msg_deleted,
-                                last_row,
-                                size,
-                            } = message
-                                .remove_embed_row(&ctx, &default_embed, row_idx)
-                                .await?;
+            pending_claims.insert(stream.id.clone());
+            Self::spawn_chat_claim(
+                &ctx,
+                config,
+                active_category.as_ref(),
+                stream_alerts_channel,
+                stream.clone(),
+                &claims_tx,
+            );
+        }
 
-                            if msg_deleted {
-                                remove_msg = true;
-                                message_stats.remove(&message.id);
+        Self::publish_claimed_chats(&claimed_chats, &claimed_channels);
 
-                                if last_tl_message == message.id {
-                                    last_tl_message = MessageId(0);
-                                }
-                            } else {
-                                message_stats.insert(message.id, (last_row, size));
+        let mut early_chat_check = tokio::time::interval(Self::EARLY_CHAT_CHECK_INTERVAL);
+        // Updates rebuilt from the index after falling behind, handled before any new ones.
+        let mut resynced: VecDeque<StreamUpdate> = VecDeque::new();
 
-                                // Decrement indices larger than the deleted row.
-                                message_indices
-                                    .iter_mut()
-                                    .filter(|(_, (m_idx, r_idx))| {
-                                        *m_idx == msg_idx && *r_idx > row_idx
-                                    })
-                                    .for_each(|(_, (_, r_idx))| *r_idx -= 1);
+        loop {
+            let received = match resynced.pop_front() {
+                Some(update) => Received::Update(update),
+                None => tokio::select! {
+                    _ = early_chat_check.tick() => {
+                        if let Some(category) = &active_category {
+                            if config.early_chats.enabled {
+                                let index = index_receiver.borrow().clone();
+
+                                Self::claim_early_chats(
+                                    &ctx,
+                                    config,
+                                    category,
+                                    &index,
+                                    &claimed_channels,
+                                    &mut early_channels,
+                                )
+                                .await;
                             }
                         }
 
-                        if remove_msg {
-                            posted_messages.remove(msg_idx);
+                        continue;
+                    }
+                    Some((stream, claim)) = claims_rx.recv() => {
+                        let channel = match claim {
+                            Ok(channel) => channel,
+                            Err(e) => {
+                                pending_claims.remove(&stream.id);
+                                error!(?e, stream = %stream.title, "Failed to claim stream chat!");
+                                continue;
+                            }
+                        };
+
+                        // The stream ended while its chat was being claimed.
+                        if !pending_claims.remove(&stream.id) {
+                            stream_archiver.send((channel, Some(stream)))?;
+                            continue;
                         }
+
+                        Self::start_slowmode_monitor(
+                            &ctx,
+                            config,
+                            theme,
+                            channel,
+                            &mut slowmode_monitors,
+                        );
+                        claimed_channels.insert(stream.id.clone(), (stream, channel));
+                        Self::publish_claimed_chats(&claimed_chats, &claimed_channels);
+                        continue;
                     }
+                    received = stream_notifier.recv() => received.context(here!())?,
+                },
+            };
+
+            let update = match received {
+                Received::Update(update) => update,
+                Received::Resync => {
+                    let index = index_receiver.borrow().clone();
+                    let known = claimed_channels
+                        .keys()
+                        .map(|id| (id, VideoStatus::Live))
+                        .chain(early_channels.keys().map(|id| (id, VideoStatus::Upcoming)));
+
+                    resynced.extend(resync_updates(&index, known));
+                    continue;
                 }
+            };
 
-                _ => (),
-            }
-        }
+            match update {
+                StreamUpdate::Started(stream) => {
+                    info!(stream = %stream.title, "Stream started!");
+                    if claimed_channels.contains_key(&stream.id)
+                        || pending_claims.contains(&stream.id)
+                    {
+                        continue;
+                    }
 
-        info!(%stream, "Listener dropped.");
+                    let quarantined = quarantined_channels.remove(&stream.id);
 
-        let _ = channel
-            .send_message(&ctx.http, |m| {
-                m.set_embed(
-                    notification_embed
-                        .clone()
-                        .description("MChad listener timed out...")
-                        .to_owned(),
-                )
-            })
-            .await?;
+                    let claim = match (
+                        quarantined,
+                        early_channels.remove(&stream.id),
+                        &active_category,
+                    ) {
+                        (Some(channel), _, _) => {
+                            info!(stream = %stream.title, "Reusing quarantined stream chat.");
+                            channel
+                        }
+                        (None, Some(channel), Some(category)) => {
+                            if let Err(e) =
+                                Self::unlock_stream_chat(&ctx, channel, category, theme).await
+                            {
+                                error!(?e, "Failed to unlock early stream chat!");
+                            }
 
-        Ok(())
-    } */
+                            channel
+                        }
+                        _ => {
+                            let stream_alerts_channel = match Self::chat_alerts_channel(
+                                alerts,
+                                active_category.as_ref(),
+                                &stream,
+                            ) {
+                                Some(channel) => channel,
+                                None => continue,
+                            };
 
-    #[instrument(skip(ctx))]
-    async fn get_old_stream_chats(
-        ctx: &Context,
-        guild: GuildId,
-        chat_category: ChannelId,
-    ) -> anyhow::Result<impl Iterator<Item = (ChannelId, String)>> {
-        let guild_channels = guild.channels(&ctx.http).await?;
+                            pending_claims.insert(stream.id.clone());
+                            Self::spawn_chat_claim(
+                                &ctx,
+                                config,
+                                active_category.as_ref(),
+                                stream_alerts_channel,
+                                stream,
+                                &claims_tx,
+                            );
+                            continue;
+                        }
+                    };
+                    Self::start_slowmode_monitor(
+                        &ctx,
+                        config,
+                        theme,
+                        claim,
+                        &mut slowmode_monitors,
+                    );
+                    claimed_channels.insert(stream.id.clone(), (stream, claim));
+                    Self::publish_claimed_chats(&claimed_chats, &claimed_channels);
+                }
+                StreamUpdate::Ended(id) => {
+                    if pending_claims.remove(&id) {
+                        continue;
+                    }
 
-        Ok(guild_channels.into_iter().filter_map(move |(_, ch)| {
-            ch.parent_id.and_then(|category| {
-                (category == chat_category).then(|| (ch.id, ch.topic.unwrap_or_default()))
-            })
-        }))
+                    if let Some(channel) = quarantined_channels.remove(&id) {
+                        let stream = index_receiver.borrow().get(&id).cloned();
+                        stream_archiver.send((channel, stream))?;
+                        continue;
+                    }
+
+                    let (stream, claimed_channel) = match claimed_channels.remove(&id) {
+                        Some(s) => s,
+                        None => continue,
+                    };
+
+                    if let Some(stop) = slowmode_monitors.remove(&claimed_channel) {
+                        let _ = stop.send(());
+                    }
+
+                    // The index has the viewer counts sampled since the stream started.
+                    let stream = index_receiver.borrow().get(&id).cloned().unwrap_or(stream);
+
+                    Self::publish_claimed_chats(&claimed_chats, &claimed_channels);
+                    stream_archiver.send((claimed_channel, Some(stream)))?;
+                }
+                StreamUpdate::Unscheduled(id) => {
+                    pending_claims.remove(&id);
+
+                    // Nothing can have been said in an early chat, so there's nothing to archive.
+                    if let Some(channel) = early_channels.remove(&id) {
+                        if let Err(e) = Self::close_stream_chat(&ctx, channel, config.mode).await {
+                            error!(?e, "Failed to close early stream chat!");
+                        }
+                    }
+
+                    // A stream cancelled after going live releases its chat like one that ended.
+                    if let Some((stream, claimed_channel)) = claimed_channels.remove(&id) {
+                        if let Some(stop) = slowmode_monitors.remove(&claimed_channel) {
+                            let _ = stop.send(());
+                        }
+
+                        Self::publish_claimed_chats(&claimed_chats, &claimed_channels);
+                        stream_archiver.send((claimed_channel, Some(stream)))?;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Claims a chat for the stream in a task of its own, sending the result back to `claims`.
+    fn spawn_chat_claim(
+        ctx: &Context,
+        config: &StreamChatConfig,
+        category: Option<&ChannelCategory>,
+        alerts_channel: ChannelId,
+        stream: Livestream,
+        claims: &mpsc::UnboundedSender<(Livestream, anyhow::Result<ChannelId>)>,
+    ) {
+        let ctx = ctx.clone();
+        let config = config.clone();
+        let category = category.cloned();
+        let claims = claims.clone();
+
+        tokio::spawn(async move {
+            let claim = Self::claim_stream_chat(
+                &ctx,
+                &config,
+                category.as_ref(),
+                alerts_channel,
+                &stream,
+                false,
+            )
+            .await;
+
+            // The update loop is gone if the receiver is, so the chat has no one to claim it.
+            let _ = claims.send((stream, claim));
+        });
+    }
+
+    /// Claims read-only chats for the upcoming streams that start within the lead time.
+    async fn claim_early_chats(
+        ctx: &Context,
+        config: &StreamChatConfig,
+        category: &ChannelCategory,
+        index: &HashMap<VideoId, Livestream>,
+        claimed_channels: &HashMap<VideoId, (Livestream, ChannelId)>,
+        early_channels: &mut HashMap<VideoId, ChannelId>,
+    ) {
+        let claim_before = Utc::now() + config.early_chats.lead_time;
+
+        for stream in index.values() {
+            if stream.state != VideoStatus::Upcoming
+                || stream.start_at > claim_before
+                || claimed_channels.contains_key(&stream.id)
+                || early_channels.contains_key(&stream.id)
+            {
+                continue;
+            }
+
+            match Self::claim_stream_chat(ctx, config, Some(category), category.id, stream, true)
+                .await
+            {
+                Ok(channel) => {
+                    early_channels.insert(stream.id.clone(), channel);
+                }
+                Err(e) => error!(?e, stream = %stream.title, "Failed to claim early stream chat!"),
+            }
+        }
+    }
+
+    /// The category's permissions, with every role denied sending messages.
+    fn locked_permissions(
+        guild: GuildId,
+        permissions: &[PermissionOverwrite],
+    ) -> Vec<PermissionOverwrite> {
+        let everyone = PermissionOverwriteType::Role(RoleId(guild.0));
+
+        let mut locked = permissions
+            .iter()
+            .cloned()
+            .map(|mut p| {
+                if matches!(p.kind, PermissionOverwriteType::Role(_)) {
+                    p.allow.remove(Self::EARLY_CHAT_DENIED);
+                    p.deny.insert(Self::EARLY_CHAT_DENIED);
+                }
+
+                p
+            })
+            .collect::<Vec<_>>();
+
+        if !locked.iter().any(|p| p.kind == everyone) {
+            locked.push(PermissionOverwrite {
+                allow: Permissions::empty(),
+                deny: Self::EARLY_CHAT_DENIED,
+                kind: everyone,
+            });
+        }
+
+        locked
+    }
+
+    /// Restores the category's permissions in an early stream chat once the stream is live.
+    #[instrument(skip(ctx, category, theme))]
+    async fn unlock_stream_chat(
+        ctx: &Context,
+        channel: ChannelId,
+        category: &ChannelCategory,
+        theme: ThemeConfig,
+    ) -> anyhow::Result<()> {
+        let everyone = PermissionOverwriteType::Role(RoleId(category.guild_id.0));
+
+        for overwrite in &category.permission_overwrites {
+            if matches!(overwrite.kind, PermissionOverwriteType::Role(_)) {
+                channel
+                    .create_permission(&ctx.http, overwrite)
+                    .await
+                    .context(here!())?;
+            }
+        }
+
+        if !category
+            .permission_overwrites
+            .iter()
+            .any(|p| p.kind == everyone)
+        {
+            channel
+                .delete_permission(&ctx.http, everyone)
+                .await
+                .context(here!())?;
+        }
+
+        channel
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.description("The stream has started, chat is now open!")
+                        .colour(theme.colour(EmbedEvent::StreamChat))
+                })
+            })
+            .await
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    fn publish_claimed_chats(
+        claimed_chats: &watch::Sender<HashMap<VideoId, ChannelId>>,
+        claimed_channels: &HashMap<VideoId, (Livestream, ChannelId)>,
+    ) {
+        claimed_chats.send_replace(
+            claimed_channels
+                .iter()
+                .map(|(id, (_, channel))| (id.clone(), *channel))
+                .collect(),
+        );
+    }
+
+    #[instrument(skip(ctx, config, stream_notifier, index_receiver))]
+    async fn scheduled_events_thread(
+        ctx: Context,
+        config: &ScheduledEventsConfig,
+        mut stream_notifier: UpdateSubscription,
+        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
+    ) -> anyhow::Result<()> {
+        let ready_index = loop {
+            index_receiver.changed().await.context(here!())?;
+            let index = index_receiver.borrow();
+
+            if !index.is_empty() {
+                break index.clone();
+            }
+        };
+
+        let mut events: HashMap<VideoId, Vec<(GuildId, ScheduledEventId)>> = HashMap::new();
+        // The streams whose events have been started, to know what to resync.
+        let mut live_events: HashSet<VideoId> = HashSet::new();
+
+        // Reclaim events created before a restart, using the stream URL as identifier.
+        for guild in &config.guilds {
+            let existing = match guild.guild.scheduled_events(&ctx.http, false).await {
+                Ok(existing) => existing,
+                Err(e) => {
+                    error!(?e, guild = %guild.guild, "Failed to get scheduled events!");
+                    continue;
+                }
+            };
+
+            for event in existing {
+                if event.creator_id != Some(ctx.cache.current_user_id()) {
+                    continue;
+                }
+
+                let stream = event.metadata.as_ref().and_then(|m| {
+                    ready_index
+                        .values()
+                        .find(|s| s.url == m.location && s.state != VideoStatus::Past)
+                });
+
+                match stream {
+                    Some(stream) => {
+                        if event.status == ScheduledEventStatus::Active {
+                            live_events.insert(stream.id.clone());
+                        }
+
+                        events
+                            .entry(stream.id.clone())
+                            .or_default()
+                            .push((guild.guild, event.id));
+                    }
+                    None => {
+                        let result = guild
+                            .guild
+                            .delete_scheduled_event(&ctx.http, event.id)
+                            .await;
+
+                        if let Err(e) = result {
+                            error!(?e, event = %event.id, "Failed to delete old scheduled event!");
+                        }
+                    }
+                }
+            }
+        }
+
+        for stream in ready_index.values() {
+            if events.contains_key(&stream.id) {
+                continue;
+            }
+
+            match stream.state {
+                VideoStatus::Upcoming => {
+                    let created = Self::create_scheduled_events(&ctx, config, stream).await;
+                    events.insert(stream.id.clone(), created);
+                }
+                VideoStatus::Live => {
+                    let created = Self::create_scheduled_events(&ctx, config, stream).await;
+                    Self::set_scheduled_events_status(&ctx, &created, ScheduledEventStatus::Active)
+                        .await;
+                    events.insert(stream.id.clone(), created);
+                    live_events.insert(stream.id.clone());
+                }
+                _ => (),
+            }
+        }
+
+        // Updates rebuilt from the index after falling behind, handled before any new ones.
+        let mut resynced: VecDeque<StreamUpdate> = VecDeque::new();
+
+        loop {
+            let received = match resynced.pop_front() {
+                Some(update) => Received::Update(update),
+                None => stream_notifier.recv().await.context(here!())?,
+            };
+
+            let update = match received {
+                Received::Update(update) => update,
+                Received::Resync => {
+                    let index = index_receiver.borrow().clone();
+                    let known = events.keys().map(|id| {
+                        if live_events.contains(id) {
+                            (id, VideoStatus::Live)
+                        } else {
+                            (id, VideoStatus::Upcoming)
+                        }
+                    });
+
+                    resynced.extend(resync_updates(&index, known));
+                    continue;
+                }
+            };
+
+            match update {
+                StreamUpdate::Scheduled(stream) => {
+                    if events.contains_key(&stream.id) {
+                        continue;
+                    }
+
+                    let created = Self::create_scheduled_events(&ctx, config, &stream).await;
+                    events.insert(stream.id.clone(), created);
+                }
+                StreamUpdate::Started(stream) => {
+                    let existing = match events.get(&stream.id) {
+                        Some(e) => e.clone(),
+                        None => {
+                            let created =
+                                Self::create_scheduled_events(&ctx, config, &stream).await;
+                            events.insert(stream.id.clone(), created.clone());
+                            created
+                        }
+                    };
+
+                    Self::set_scheduled_events_status(
+                        &ctx,
+                        &existing,
+                        ScheduledEventStatus::Active,
+                    )
+                    .await;
+                    live_events.insert(stream.id);
+                }
+                StreamUpdate::Ended(id) => {
+                    live_events.remove(&id);
+
+                    if let Some(existing) = events.remove(&id) {
+                        Self::set_scheduled_events_status(
+                            &ctx,
+                            &existing,
+                            ScheduledEventStatus::Completed,
+                        )
+                        .await;
+                    }
+                }
+                StreamUpdate::Unscheduled(id) => {
+                    live_events.remove(&id);
+
+                    for (guild_id, event_id) in events.remove(&id).unwrap_or_default() {
+                        if let Err(e) = guild_id.delete_scheduled_event(&ctx.http, event_id).await {
+                            error!(?e, event = %event_id, "Failed to delete scheduled event!");
+                        }
+                    }
+                }
+                StreamUpdate::Renamed(id, new_name) => {
+                    for (guild_id, event_id) in events.get(&id).into_iter().flatten() {
+                        let result = guild_id
+                            .edit_scheduled_event(&ctx.http, *event_id, |e| {
+                                e.name(Self::scheduled_event_name(&new_name))
+                            })
+                            .await;
+
+                        if let Err(e) = result {
+                            error!(?e, event = %event_id, "Failed to rename scheduled event!");
+                        }
+                    }
+                }
+                StreamUpdate::Rescheduled(id, new_start) => {
+                    for (guild_id, event_id) in events.get(&id).into_iter().flatten() {
+                        let result = guild_id
+                            .edit_scheduled_event(&ctx.http, *event_id, |e| {
+                                e.start_time(new_start)
+                                    .end_time(Self::scheduled_event_end(new_start))
+                            })
+                            .await;
+
+                        if let Err(e) = result {
+                            error!(?e, event = %event_id, "Failed to reschedule scheduled event!");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Creates the events of the stream in the servers that want them, returning the ones
+    /// that were created. Failing to create one doesn't stop the others from being created.
+    async fn create_scheduled_events(
+        ctx: &Context,
+        config: &ScheduledEventsConfig,
+        stream: &Livestream,
+    ) -> Vec<(GuildId, ScheduledEventId)> {
+        // Discord rejects events that start in the past.
+        let start_at = stream.start_at.max(Utc::now() + Duration::minutes(1));
+        let mut created = Vec::with_capacity(config.guilds.len());
+
+        for guild in config
+            .guilds
+            .iter()
+            .filter(|g| g.includes(stream.streamer.branch))
+        {
+            let result = guild
+                .guild
+                .create_scheduled_event(&ctx.http, |e| {
+                    e.name(Self::scheduled_event_name(&stream.title))
+                        .description(
+                            stream
+                                .participants()
+                                .map(|t| t.name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        )
+                        .kind(ScheduledEventType::External)
+                        .location(&stream.url)
+                        .start_time(start_at)
+                        .end_time(Self::scheduled_event_end(start_at))
+                })
+                .await;
+
+            match result {
+                Ok(event) => created.push((guild.guild, event.id)),
+                Err(e) => error!(
+                    ?e,
+                    guild = %guild.guild,
+                    stream = %stream.title,
+                    "Failed to create scheduled event!"
+                ),
+            }
+        }
+
+        created
+    }
+
+    async fn set_scheduled_events_status(
+        ctx: &Context,
+        events: &[(GuildId, ScheduledEventId)],
+        status: ScheduledEventStatus,
+    ) {
+        for (guild_id, event_id) in events {
+            let result = guild_id
+                .edit_scheduled_event(&ctx.http, *event_id, |e| e.status(status))
+                .await;
+
+            if let Err(e) = result {
+                error!(?e, event = %event_id, ?status, "Failed to update scheduled event!");
+            }
+        }
+    }
+
+    fn scheduled_event_name(title: &str) -> String {
+        title.chars().take(100).collect()
+    }
+
+    /// External events require an end time, so assume streams last an hour.
+    fn scheduled_event_end(start_at: DateTime<Utc>) -> DateTime<Utc> {
+        start_at + Duration::hours(1)
+    }
+
+    async fn schedule_digest_thread(
+        ctx: Context,
+        config: &Config,
+        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
+    ) -> anyhow::Result<()> {
+        let digest_config = &config.stream_tracking.schedule_digest;
+        let mut posts: HashMap<HoloBranch, SchedulePost> = HashMap::new();
+
+        // Reclaim the digests posted before a restart, so they're edited rather than reposted.
+        for (branch, channel) in &digest_config.channels {
+            let title = Self::schedule_digest_title(config, *branch);
+
+            let messages = retry_discord_request(RequestKind::Idempotent, || {
+                channel.messages(&ctx.http, |m| m.limit(Self::SCHEDULE_DIGEST_LOOKBACK))
+            })
+            .await;
+
+            let messages = match messages {
+                Ok(messages) => messages,
+                Err(e) => {
+                    warn!(?e, %channel, "Failed to find the previous schedule digest!");
+                    continue;
+                }
+            };
+
+            let previous = messages.into_iter().find(|m| {
+                m.author.id == ctx.cache.current_user_id()
+                    && m.embeds.first().and_then(|e| e.title.as_ref()) == Some(&title)
+            });
+
+            if let Some(message) = previous {
+                posts.insert(
+                    *branch,
+                    SchedulePost {
+                        channel: *channel,
+                        message: message.id,
+                        date: digest_config.day_of(*message.timestamp).0,
+                        description: message
+                            .embeds
+                            .first()
+                            .and_then(|e| e.description.clone())
+                            .unwrap_or_default(),
+                    },
+                );
+            }
+        }
+
+        let mut refresh = tokio::time::interval(Self::SCHEDULE_DIGEST_REFRESH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = refresh.tick() => {},
+                res = index_receiver.changed() => res.context(here!())?,
+            }
+
+            let index = index_receiver.borrow().clone();
+
+            if index.is_empty() {
+                continue;
+            }
+
+            let (date, day_start, day_end) = digest_config.day_of(Utc::now());
+
+            for (branch, channel) in &digest_config.channels {
+                let mut streams = index
+                    .values()
+                    .filter(|s| {
+                        s.streamer.branch == *branch
+                            && matches!(s.state, VideoStatus::Upcoming | VideoStatus::Live)
+                            && (day_start..day_end).contains(&s.start_at)
+                    })
+                    .collect::<Vec<_>>();
+
+                streams.sort_unstable_by_key(|s| s.start_at);
+
+                let description = Self::schedule_digest_description(day_start, &streams);
+
+                if let Err(e) = Self::update_schedule_digest(
+                    &ctx,
+                    config,
+                    &mut posts,
+                    *branch,
+                    *channel,
+                    date,
+                    description,
+                )
+                .await
+                {
+                    error!("{:?}", e);
+                }
+            }
+        }
+    }
+
+    /// Edits today's digest for the branch, or replaces the previous day's digest with a new one.
+    async fn update_schedule_digest(
+        ctx: &Context,
+        config: &Config,
+        posts: &mut HashMap<HoloBranch, SchedulePost>,
+        branch: HoloBranch,
+        channel: ChannelId,
+        date: NaiveDate,
+        description: String,
+    ) -> anyhow::Result<()> {
+        let title = Self::schedule_digest_title(config, branch);
+        let colour = config
+            .branches
+            .get(&branch)
+            .map(|b| b.colour)
+            .filter(|c| *c != 0)
+            .unwrap_or_else(|| config.theme.colour(EmbedEvent::ScheduleDigest));
+
+        if let Some(post) = posts
+            .get_mut(&branch)
+            .filter(|p| p.date == date && p.channel == channel)
+        {
+            if post.description != description {
+                post.channel
+                    .edit_message(&ctx.http, post.message, |m| {
+                        m.embed(|e| e.title(&title).description(&description).colour(colour))
+                    })
+                    .await
+                    .context(here!())?;
+
+                post.description = description;
+            }
+
+            return Ok(());
+        }
+
+        if let Some(old_post) = posts.remove(&branch) {
+            old_post
+                .channel
+                .delete_message(&ctx.http, old_post.message)
+                .await
+                .context(here!())?;
+        }
+
+        let message = channel
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| e.title(&title).description(&description).colour(colour))
+            })
+            .await
+            .context(here!())?;
+
+        posts.insert(
+            branch,
+            SchedulePost {
+                channel,
+                message: message.id,
+                date,
+                description,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn schedule_digest_title(config: &Config, branch: HoloBranch) -> String {
+        format!("{} schedule", config.branch_name(branch))
+    }
+
+    fn schedule_digest_description(day_start: DateTime<Utc>, streams: &[&Livestream]) -> String {
+        let mut description = format!("Streams on <t:{}:D>\n", day_start.timestamp());
+
+        if streams.is_empty() {
+            description.push_str("\nNo streams are scheduled.");
+            return description;
+        }
+
+        for (i, stream) in streams.iter().enumerate() {
+            let line = format!(
+                "\n<t:{}:t> {}**{}**: [{}]({})",
+                stream.start_at.timestamp(),
+                if stream.state == VideoStatus::Live {
+                    "🔴 "
+                } else {
+                    ""
+                },
+                stream.streamer.name,
+                stream.title.replace(['[', ']'], ""),
+                stream.url
+            );
+
+            // Leave room for the note about the streams that didn't fit.
+            if description.len() + line.len() > Self::EMBED_DESCRIPTION_LIMIT - 32 {
+                description.push_str(&format!("\n...and {} more.", streams.len() - i));
+                break;
+            }
+
+            description.push_str(&line);
+        }
+
+        description
+    }
+
+    async fn status_board_thread(
+        ctx: Context,
+        config: &Config,
+        mut stream_updates: UpdateSubscription,
+        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
+    ) -> anyhow::Result<()> {
+        let colour = config.theme.colour(EmbedEvent::LiveAlert);
+        let mut boards: HashMap<ChannelId, (MessageId, String)> = HashMap::new();
+
+        // Reclaim the boards posted before a restart, so they're edited rather than reposted.
+        for channel in &config.stream_tracking.status_board.channels {
+            let messages = retry_discord_request(RequestKind::Idempotent, || {
+                channel.messages(&ctx.http, |m| m.limit(Self::SCHEDULE_DIGEST_LOOKBACK))
+            })
+            .await;
+
+            let messages = match messages {
+                Ok(messages) => messages,
+                Err(e) => {
+                    warn!(?e, %channel, "Failed to find the previous status board!");
+                    continue;
+                }
+            };
+
+            let previous = messages.into_iter().find(|m| {
+                m.author.id == ctx.cache.current_user_id()
+                    && m.embeds.first().and_then(|e| e.title.as_deref())
+                        == Some(Self::STATUS_BOARD_TITLE)
+            });
+
+            if let Some(message) = previous {
+                let description = message
+                    .embeds
+                    .first()
+                    .and_then(|e| e.description.clone())
+                    .unwrap_or_default();
+
+                boards.insert(*channel, (message.id, description));
+            }
+        }
+
+        loop {
+            let description = {
+                let index = index_receiver.borrow();
+
+                let mut live = index
+                    .values()
+                    .filter(|s| s.state == VideoStatus::Live)
+                    .collect::<Vec<_>>();
+
+                live.sort_unstable_by_key(|s| s.start_at);
+                Self::status_board_description(&live)
+            };
+
+            for channel in &config.stream_tracking.status_board.channels {
+                if let Err(e) = Self::update_status_board(
+                    &ctx.http,
+                    &mut boards,
+                    *channel,
+                    colour,
+                    &description,
+                )
+                .await
+                {
+                    error!("{:?}", e);
+                }
+            }
+
+            tokio::select! {
+                // The board is rebuilt from the index either way, so resyncs need no handling.
+                res = stream_updates.recv() => {
+                    res.context(here!())?;
+                }
+                res = index_receiver.changed() => res.context(here!())?,
+            }
+        }
+    }
+
+    /// Edits the board in the channel if the live streams changed, or posts it if there's none yet.
+    async fn update_status_board<S: MessageSink>(
+        sink: &S,
+        boards: &mut HashMap<ChannelId, (MessageId, String)>,
+        channel: ChannelId,
+        colour: u32,
+        description: &str,
+    ) -> anyhow::Result<()> {
+        let embed = |e: &mut CreateEmbed| {
+            e.title(Self::STATUS_BOARD_TITLE)
+                .description(description)
+                .colour(colour);
+        };
+
+        match boards.get_mut(&channel) {
+            Some((_, current)) if current == description => (),
+            Some((message, current)) => {
+                let mut edit = EditMessage::default();
+                edit.embed(|e| {
+                    embed(e);
+                    e
+                });
+
+                sink.edit_message(channel, *message, edit).await?;
+                *current = description.to_owned();
+            }
+            None => {
+                let mut message = CreateMessage::default();
+                message.embed(|e| {
+                    embed(e);
+                    e
+                });
+
+                let sent = sink.send_message(channel, message).await?;
+                boards.insert(channel, (sent.id, description.to_owned()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn status_board_description(streams: &[&Livestream]) -> String {
+        if streams.is_empty() {
+            return "Nobody is live right now.".to_owned();
+        }
+
+        let mut description = String::new();
+
+        for (i, stream) in streams.iter().enumerate() {
+            let line = format!(
+                "🔴 **{}**: [{}]({}), live since <t:{}:R>\n",
+                stream
+                    .participants()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                stream.title.replace(['[', ']'], ""),
+                stream.url,
+                stream.start_at.timestamp()
+            );
+
+            // Leave room for the note about the streams that didn't fit.
+            if description.len() + line.len() > Self::EMBED_DESCRIPTION_LIMIT - 32 {
+                description.push_str(&format!("...and {} more.", streams.len() - i));
+                break;
+            }
+
+            description.push_str(&line);
+        }
+
+        description
+    }
+
+    /* #[instrument(skip(ctx, config, talents, index_receiver, stream_notifier))]
+    async fn mchad_watch_thread(
+        ctx: Arc<CacheAndHttp>,
+        config: &StreamChatConfig,
+        talents: &[Talent],
+        mut index_receiver: watch::Receiver<HashMap<VideoId, Livestream>>,
+        mut stream_notifier: UpdateSubscription,
+    ) -> anyhow::Result<()> {
+        let mut live_streams: HashMap<_, _> = loop {
+            index_receiver.changed().await.context(here!())?;
+            let index = index_receiver.borrow();
+
+            if !index.is_empty() {
+                break index
+                    .iter()
+                    .filter(|(_, s)| s.state == VideoStatus::Live)
+                    .map(|(id, l)| (id.clone(), l.streamer.twitter_id))
+                    .collect();
+            }
+        };
+
+        let guild_id = readiness::wait_for_channels(
+            &ctx,
+            &[RequiredChannel::new("Stream chat category", config.category)],
+            readiness::DEFAULT_TIMEOUT,
+        )
+        .await?
+        .category(config.category)?
+        .guild_id;
+
+        let mut mchad = Client::new();
+
+        loop {
+            tokio::select! {
+                res = stream_notifier.recv() => {
+                    let update = match res.context(here!())? {
+                        Received::Update(u) => u,
+                        Received::Resync => {
+                            live_streams = index_receiver
+                                .borrow()
+                                .iter()
+                                .filter(|(_, s)| s.state == VideoStatus::Live)
+                                .map(|(id, l)| (id.clone(), l.streamer.twitter_id))
+                                .collect();
+                            continue;
+                        }
+                    };
+
+                    match update {
+                        StreamUpdate::Started(stream) => {
+                            live_streams.insert(stream.id.clone(), stream.streamer.twitter_id);
+                        }
+                        StreamUpdate::Ended(id) => {
+                            live_streams.remove(&id);
+                        }
+                        _ => (),
+                    }
+                }
+
+                res = mchad.room_updates.recv() => {
+                    let update = match res.context(here!()) {
+                        Ok(u) => u,
+                        Err(e) => {
+                            error!("{:?}", e);
+                            continue;
+                        }
+                    };
+
+                    match update {
+                        RoomUpdate::Added(stream) | RoomUpdate::Changed(_, stream) => {
+                            let video_id: VideoId = match (*stream).parse() {
+                                Ok(id) => id,
+                                Err(e) => {
+                                    error!("{:?}", e);
+                                    continue;
+                                }
+                            };
+
+                            if live_streams.contains_key(&video_id) {
+                                let talent_twitter_id = live_streams.get(&video_id).unwrap();
+                                let talent = match talents.iter().find(|u| u.twitter_id == *talent_twitter_id) {
+                                    Some(u) => u.clone(),
+                                    None => continue,
+                                };
+
+                                if let Some(listener) = mchad.get_listener(&video_id).await {
+                                    let ctx = Arc::clone(&ctx);
+
+                                    tokio::spawn(async move {
+                                        Self::bounce_mchad_messages(ctx, guild_id, video_id.clone(), talent, listener).await
+                                    });
+                                }
+                            }
+                        }
+
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
+    #[instrument(skip(ctx, talent))]
+    async fn bounce_mchad_messages(
+        ctx: Arc<CacheAndHttp>,
+        guild_id: GuildId,
+        stream: VideoId,
+        talent: Talent,
+        listener: Listener,
+    ) -> anyhow::Result<()> {
+        let (channel, _) = guild_id.channels(&ctx.http).await?.into_iter().find(|(_, ch)| {
+            matches!(&ch.topic, Some(url) if *url == format!("https://youtube.com/watch?v={}", &stream))
+        }).ok_or_else(|| anyhow!("Failed to find stream!"))?;
+
+        let mut posted_messages: Vec<Message> = Vec::with_capacity(1024);
+        let mut message_indices: HashMap<String, (usize, usize)> = HashMap::with_capacity(1024);
+        let mut message_stats: HashMap<MessageId, (usize, usize)> = HashMap::with_capacity(1024);
+
+        let mut last_tl_message = MessageId(0);
+
+        let room_name = listener.room.borrow().name.clone();
+
+        info!(%stream, "Starting to listen!");
+
+        let mut event_stream = Box::pin(listener);
+
+        let notification_embed = CreateEmbed::default()
+            .author(|a| {
+                a.name("MChad Discord Integration")
+                    .url("https://mchatx.org/")
+            })
+            .colour(talent.colour)
+            .footer(|f| f.text(format!("Room: {}", room_name)))
+            .to_owned();
+
+        let default_embed = CreateEmbed::default()
+            .author(|a| a.name(&talent.english_name).icon_url(&talent.icon))
+            .colour(talent.colour)
+            .to_owned();
+
+        while let Some(event) = event_stream.next().await {
+            use EventData::*;
+
+            debug!(?event);
+
+            match event {
+                Connect(_msg) => {
+                    debug!(message = %_msg, "Connected to MChad.");
+
+                    let _ = channel
+                        .send_message(&ctx.http, |m| {
+                            m.set_embed(
+                                notification_embed
+                                    .clone()
+                                    .description("Connected to MChad!")
+                                    .to_owned(),
+                            )
+                        })
+                        .await?;
+                }
+
+                Update(RoomEvent { id, text, time: _ })
+                | Insert(RoomEvent { id, text, time: _ })
+                    if message_indices.contains_key(&id) =>
+                {
+                    debug!(%id, %text, "Updating message.");
+
+                    let (msg_idx, row_idx) = message_indices.get(&id).unwrap();
+                    let msg = posted_messages.get_mut(*msg_idx).unwrap();
+
+                    let EmbedRowEdit { size } = msg
+                        .edit_embed_row(&ctx, &default_embed, *row_idx, text)
+                        .await?;
+
+                    let (_, bytes) = message_stats.get_mut(&msg.id).unwrap();
+                    *bytes = size;
+                }
+
+                Insert(RoomEvent { id, text, time: _ })
+                | Update(RoomEvent { id, text, time: _ })
+                    if !message_indices.contains_key(&id) =>
+                {
+                    debug!(%id, %text, "New message.");
+
+                    let should_update_msg = Self::get_last_message_id_in_channel(&ctx, &channel)
+                        .await
+                        .map(|id| {
+                            if id != last_tl_message {
+                                return false;
+                            }
+
+                            let (_, msg_size) = message_stats.get(&id).unwrap();
+
+                            if msg_size + text.len() > 4096 {
+                                return false;
+                            }
+
+                            true
+                        })
+                        .unwrap_or(false);
+
+                    if should_update_msg {
+                        let msg_idx = posted_messages
+                            .iter()
+                            .position(|m| m.id == last_tl_message)
+                            .unwrap();
+                        let message = posted_messages.get_mut(msg_idx).unwrap();
+
+                        let EmbedRowAddition { size } =
+                            message.add_embed_row(&ctx, &default_embed, text).await?;
+
+                        let (last_row, bytes) = message_stats.get_mut(&last_tl_message).unwrap();
+
+                        *last_row += 1;
+                        *bytes = size;
+
+                        message_indices.insert(id, (msg_idx, *last_row));
+                    } else {
+                        let text_length = text.len();
+
+                        let message = channel
+                            .send_message(&ctx.http, |m| {
+                                m.set_embed(default_embed.clone().description(&text).to_owned())
+                            })
+                            .await?;
+
+                        last_tl_message = message.id;
+
+                        message_indices.insert(id, (posted_messages.len(), 0));
+                        message_stats.insert(message.id, (0, text_length));
+                        posted_messages.push(message);
+                    }
+                }
+
+                Delete(id) => {
+                    debug!(%id, "Deleting message.");
+
+                    if let Some((msg_idx, row_idx)) = message_indices.remove(&id) {
+                        let mut remove_msg = false;
+
+                        if let Some(message) = posted_messages.get_mut(msg_idx) {
+                            let EmbedRowRemoval {
+                                msg_deleted,
+                                last_row,
+                                size,
+                            } = message
+                                .remove_embed_row(&ctx, &default_embed, row_idx)
+                                .await?;
+
+                            if msg_deleted {
+                                remove_msg = true;
+                                message_stats.remove(&message.id);
+
+                                if last_tl_message == message.id {
+                                    last_tl_message = MessageId(0);
+                                }
+                            } else {
+                                message_stats.insert(message.id, (last_row, size));
+
+                                // Decrement indices larger than the deleted row.
+                                message_indices
+                                    .iter_mut()
+                                    .filter(|(_, (m_idx, r_idx))| {
+                                        *m_idx == msg_idx && *r_idx > row_idx
+                                    })
+                                    .for_each(|(_, (_, r_idx))| *r_idx -= 1);
+                            }
+                        }
+
+                        if remove_msg {
+                            posted_messages.remove(msg_idx);
+                        }
+                    }
+                }
+
+                _ => (),
+            }
+        }
+
+        info!(%stream, "Listener dropped.");
+
+        let _ = channel
+            .send_message(&ctx.http, |m| {
+                m.set_embed(
+                    notification_embed
+                        .clone()
+                        .description("MChad listener timed out...")
+                        .to_owned(),
+                )
+            })
+            .await?;
+
+        Ok(())
+    } */
+
+    #[instrument(skip(ctx))]
+    async fn get_old_stream_chats(
+        ctx: &Context,
+        guild: GuildId,
+        chat_category: ChannelId,
+    ) -> anyhow::Result<impl Iterator<Item = (ChannelId, String)>> {
+        let guild_channels = guild.channels(&ctx.http).await?;
+
+        Ok(guild_channels.into_iter().filter_map(move |(_, ch)| {
+            ch.parent_id.and_then(|category| {
+                (category == chat_category).then(|| (ch.id, ch.topic.unwrap_or_default()))
+            })
+        }))
+    }
+
+    #[instrument(skip(ctx))]
+    async fn get_old_stream_threads(
+        ctx: &Context,
+        guild: GuildId,
+        alerts_channel: ChannelId,
+    ) -> anyhow::Result<Vec<(ChannelId, String)>> {
+        let bot_id = ctx.cache.current_user_id();
+        let active_threads = guild.get_active_threads(&ctx.http).await.context(here!())?;
+
+        let mut threads = Vec::new();
+
+        for thread in active_threads.threads {
+            if thread.parent_id != Some(alerts_channel) || thread.owner_id != Some(bot_id) {
+                continue;
+            }
+
+            // Threads created from a message share its ID, so the alert embed gives us the stream.
+            let stream_url = alerts_channel
+                .message(&ctx.http, thread.id.0)
+                .await
+                .ok()
+                .and_then(|m| m.embeds.into_iter().next())
+                .and_then(|e| e.url)
+                .unwrap_or_default();
+
+            threads.push((thread.id, stream_url));
+        }
+
+        Ok(threads)
+    }
+
+    fn try_find_stream_for_channel(
+        topic: &str,
+        index: &HashMap<VideoId, Livestream>,
+    ) -> Option<(Livestream, VideoStatus)> {
+        let stream = index.values().find(|s| s.url == topic)?;
+        Some((stream.clone(), stream.state))
+    }
+
+    #[instrument(skip(ctx))]
+    async fn get_last_message_id_in_channel(
+        ctx: &Arc<CacheAndHttp>,
+        channel: &ChannelId,
+    ) -> Option<MessageId> {
+        match channel.to_channel(&ctx.http).await.context(here!()) {
+            Ok(Channel::Guild(ch)) => ch.last_message_id,
+            Ok(Channel::Private(ch)) => ch.last_message_id,
+            Ok(_) => None,
+            Err(e) => {
+                error!("{:?}", e);
+                None
+            }
+        }
+    }
+
+    #[instrument(skip(ctx, archive_notifier))]
+    async fn chat_archive_thread(
+        ctx: Context,
+        log_ch: ChannelId,
+        config: &Config,
+        mut archive_notifier: mpsc::UnboundedReceiver<(ChannelId, Option<Livestream>)>,
+    ) -> anyhow::Result<()> {
+        readiness::wait_for_channels(
+            &ctx,
+            &[RequiredChannel::new("Stream chat logs", log_ch)],
+            readiness::DEFAULT_TIMEOUT,
+        )
+        .await?;
+
+        let log_ch = Arc::new(Mutex::new(log_ch));
+
+        let storage = if config.object_storage.enabled {
+            match ObjectStorage::new(&config.object_storage) {
+                Ok(storage) => Some(Arc::new(storage)),
+                Err(e) => {
+                    error!(?e, "Failed to set up object storage!");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        while let Some((channel, stream)) = archive_notifier.recv().await {
+            let log_clone = Arc::clone(&log_ch);
+            let storage = storage.clone();
+            let ctx_clone = ctx.clone();
+            let discussion_ch = stream
+                .as_ref()
+                .and_then(|s| s.streamer.get_stream_discussion_channel(config));
+
+            let mode = config.stream_tracking.chat.mode;
+            let pin_vod_summary = config.stream_tracking.chat.pin_vod_summary;
+            let highlights = config.stream_tracking.chat.highlights.clone();
+            let clock = config.clock.clone();
+            let theme = config.theme;
+            let database = config.database.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = Self::archive_channel(
+                    &ctx_clone,
+                    &database,
+                    &clock,
+                    theme,
+                    channel,
+                    mode,
+                    stream,
+                    log_clone,
+                    discussion_ch,
+                    pin_vod_summary,
+                    &highlights,
+                    storage.as_deref(),
+                )
+                .await
+                {
+                    error!("{:?}", e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip(ctx, database, clock, storage))]
+    async fn archive_channel(
+        ctx: &Context,
+        database: &Database,
+        clock: &SharedClock,
+        theme: ThemeConfig,
+        channel: ChannelId,
+        mode: StreamChatMode,
+        stream: Option<Livestream>,
+        log_channel: Arc<Mutex<ChannelId>>,
+        discussion_ch: Option<ChannelId>,
+        pin_vod_summary: bool,
+        highlights: &ChatHighlightsConfig,
+        storage: Option<&ObjectStorage>,
+    ) -> anyhow::Result<()> {
+        let cache = &ctx.cache;
+
+        let message_stream = channel.messages_iter(&ctx.http);
+        let stream_start = match stream.as_ref() {
+            Some(s) => s.start_at,
+            None => *channel.created_at(),
+        };
+        let stream_id = stream.as_ref().map(|s| &s.id);
+
+        let messages = message_stream
+            .try_filter_map(|msg| async move {
+                if !Self::should_message_be_archived(&msg) {
+                    return Ok(None);
+                }
+
+                let mut attachment_urls = Vec::with_capacity(msg.attachments.len());
+
+                for attachment in &msg.attachments {
+                    attachment_urls.push(match storage {
+                        Some(storage) => Self::copy_attachment(storage, channel, attachment).await,
+                        None => attachment.url.clone(),
+                    });
+                }
+
+                Ok(Some(ArchivedMessage {
+                    author: Mention::from(msg.author.id),
+                    author_name: msg.author.name.clone(),
+                    content: msg.content_safe(cache),
+                    video_id: stream_id,
+                    timestamp: *msg.timestamp - stream_start,
+                    attachment_urls,
+                }))
+            })
+            .try_collect::<Vec<_>>()
+            .await
+            .context(here!())?;
+
+        if messages.is_empty() {
+            Self::close_stream_chat(ctx, channel, mode).await?;
+            return Ok(());
+        }
+
+        let guild = match cache.guild_channel(channel) {
+            Some(c) => Some(c.guild_id),
+            None => channel
+                .to_channel(ctx)
+                .await
+                .ok()
+                .and_then(Channel::guild)
+                .map(|c| c.guild_id),
+        };
+
+        if let Err(e) = Self::index_archive(
+            database,
+            guild,
+            channel,
+            stream.as_ref(),
+            stream_start,
+            &messages,
+        ) {
+            error!(?e, %channel, "Failed to index archived chat!");
+        }
+
+        let transcript_url = match storage {
+            Some(storage) => Self::upload_transcript(storage, channel, &messages).await,
+            None => None,
+        };
+        let transcript_link = transcript_url.map(|url| format!("[Download]({url})"));
+
+        let highlights = match &stream {
+            Some(stream) if highlights.enabled => find_highlights(
+                messages.iter().map(|m| m.timestamp.num_seconds()),
+                highlights.min_z_score,
+                highlights.max_highlights,
+            )
+            .iter()
+            .map(|h| h.format(&stream.id))
+            .collect::<Vec<_>>()
+            .join("\n"),
+            _ => String::new(),
+        };
+
+        let messages = messages.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        let start_time = clock.now();
+
+        Self::announce_archival(&ctx.http, &theme, channel, stream.as_ref(), discussion_ch).await?;
+
+        let mut seg_msg = SegmentedMessage::<String, Livestream>::new();
+        let seg_msg = seg_msg
+            .data(messages)
+            .order(DataOrder::Reverse)
+            .position(SegmentDataPosition::Fields)
+            .colour(
+                stream
+                    .as_ref()
+                    .map(|s| s.streamer.colour)
+                    .filter(|c| *c != 0)
+                    .unwrap_or_else(|| theme.colour(EmbedEvent::StreamChat)),
+            )
+            .segment_format(Box::new(|e, i, _| {
+                e.title(format!("Log {}", i + 1));
+            }))
+            .link_format(Box::new(|i, m, _| {
+                format!("[Log {}]({})\n", i + 1, m.link())
+            }));
+
+        let stream = match &stream {
+            Some(stream) => Some(Self::with_cached_images(stream).await),
+            None => None,
+        };
+        let summary_stream = stream.clone();
+
+        let seg_msg = match stream {
+            Some(stream) => seg_msg.index_format(Box::new(move |e, i, _| {
+                if i == 0 {
+                    if let Some(link) = &transcript_link {
+                        e.field("Full transcript", link, false);
+                    }
+
+                    if !highlights.is_empty() {
+                        e.field("Highlights", &highlights, false);
+                    }
+
+                    e.title(format!("Logs from {}", &stream.title))
+                        .url(&stream.url)
+                        .thumbnail(&stream.thumbnail)
+                        .timestamp(stream.duration.map_or(start_time, |d| stream.start_at + d))
+                        .author(|a| {
+                            a.name(&stream.streamer.name)
+                                .url(format!(
+                                    "https://www.youtube.com/channel/{}",
+                                    &stream.streamer.youtube_ch_id.as_ref().unwrap()
+                                ))
+                                .icon_url(&stream.streamer.icon)
+                        });
+                }
+            })),
+            None => seg_msg.index_format(Box::new(move |e, i, _| {
+                if i == 0 {
+                    if let Some(link) = &transcript_link {
+                        e.field("Full transcript", link, false);
+                    }
+
+                    e.title("Logs from unknown stream").timestamp(start_time);
+                }
+            })),
+        };
+
+        seg_msg.on_failure(Box::new(move |failure| {
+            error!(
+                %channel,
+                part = %failure.part,
+                segments = failure.total_segments,
+                "Failed to post chat log: {:?}",
+                failure.source
+            );
+        }));
+
+        let mut attempt = 0;
+
+        // Whatever was posted before a failure is kept, so only the rest is posted when resuming.
+        let log_message = loop {
+            match seg_msg.create(ctx, Arc::clone(&log_channel)).await {
+                Ok(message) => break message,
+                Err(_) if attempt < Self::ARCHIVE_RESUME_ATTEMPTS => {
+                    attempt += 1;
+                    warn!(
+                        %channel,
+                        attempt,
+                        posted = seg_msg.segments_posted(),
+                        "Resuming chat log."
+                    );
+                    sleep(Self::ARCHIVE_RESUME_DELAY).await;
+                }
+                Err(e) => return Err(e).context(here!()),
+            }
+        };
+
+        if let (true, Some(stream), Some(discussion_ch)) =
+            (pin_vod_summary, summary_stream, discussion_ch)
+        {
+            if let Err(e) =
+                Self::pin_vod_summary(&ctx.http, discussion_ch, &stream, &log_message.link()).await
+            {
+                error!("{:?}", e);
+            }
+        }
+
+        let archive_at =
+            start_time + Duration::from_std(Self::ARCHIVAL_WARNING_TIME).context(here!())?;
+        clock.sleep_until(archive_at).await;
+
+        Self::close_stream_chat(ctx, channel, mode).await?;
+
+        Ok(())
+    }
+
+    /// Uploads a copy of the attachment, returning its URL,
+    /// or the Discord URL if it couldn't be copied.
+    async fn copy_attachment(
+        storage: &ObjectStorage,
+        channel: ChannelId,
+        attachment: &Attachment,
+    ) -> String {
+        let copy = async {
+            let data = attachment.download().await.context(here!())?;
+
+            storage
+                .upload(
+                    &format!(
+                        "archives/{channel}/attachments/{}-{}",
+                        attachment.id,
+                        ObjectStorage::key_segment(&attachment.filename)
+                    ),
+                    &data,
+                    attachment
+                        .content_type
+                        .as_deref()
+                        .unwrap_or("application/octet-stream"),
+                )
+                .await
+        };
+
+        match copy.await {
+            Ok(url) => url,
+            Err(e) => {
+                warn!(?e, attachment = %attachment.url, "Failed to copy attachment!");
+                attachment.url.clone()
+            }
+        }
+    }
+
+    /// Adds the messages to the archive index, so that they can be searched for.
+    fn index_archive(
+        database: &Database,
+        guild: Option<GuildId>,
+        channel: ChannelId,
+        stream: Option<&Livestream>,
+        stream_start: DateTime<Utc>,
+        messages: &[ArchivedMessage<'_>],
+    ) -> anyhow::Result<()> {
+        let messages = messages
+            .iter()
+            .map(|m| IndexedMessage {
+                guild,
+                channel,
+                talent: stream.map(|s| s.streamer.name.clone()),
+                video_id: m.video_id.map(ToString::to_string),
+                stream_title: stream.map(|s| s.title.clone()),
+                author_name: m.author_name.clone(),
+                content: m.content.clone(),
+                sent_at: stream_start + m.timestamp,
+                offset: m.timestamp.num_seconds(),
+            })
+            .collect::<Vec<_>>();
+
+        ArchiveIndex::open(database)
+            .context(here!())?
+            .insert(&messages)
+    }
+
+    /// Uploads the whole chat as a text file, returning its URL if it succeeded.
+    async fn upload_transcript(
+        storage: &ObjectStorage,
+        channel: ChannelId,
+        messages: &[ArchivedMessage<'_>],
+    ) -> Option<String> {
+        // Messages are fetched newest first.
+        let transcript = messages
+            .iter()
+            .rev()
+            .map(ArchivedMessage::transcript_line)
+            .collect::<String>();
+
+        let key = format!("archives/{channel}/transcript.txt");
+
+        match storage
+            .upload(&key, transcript.as_bytes(), "text/plain; charset=utf-8")
+            .await
+        {
+            Ok(url) => Some(url),
+            Err(e) => {
+                error!(?e, %channel, "Failed to upload transcript!");
+                None
+            }
+        }
+    }
+
+    /// Warns the chat that it's about to be archived, pointing to where the discussion can continue.
+    async fn announce_archival<S: MessageSink>(
+        sink: &S,
+        theme: &ThemeConfig,
+        channel: ChannelId,
+        stream: Option<&Livestream>,
+        discussion_ch: Option<ChannelId>,
+    ) -> anyhow::Result<()> {
+        let formatted_archival_time = match (
+            Self::ARCHIVAL_WARNING_TIME.as_secs() / 60,
+            Self::ARCHIVAL_WARNING_TIME.as_secs() % 60,
+        ) {
+            (0, 0..=30) => "now".to_string(),
+            (m, 50..=59) => format!("in {} minutes", m + 1),
+            (m, 0..=10) => format!("in {} minutes", m),
+            (0, s) => format!("in {} seconds", s),
+            (m, s) => format!("in {} minutes and {} seconds", m, s),
+        };
+
+        let description = match discussion_ch {
+            Some(discussion_ch) => format!(
+                "Feel free to continue talking in {}!\nThis stream will be archived {}.",
+                Mention::from(discussion_ch),
+                formatted_archival_time
+            ),
+            None => format!("This stream will be archived {}.", formatted_archival_time),
+        };
+
+        let mut message = CreateMessage::default();
+        message.embed(|e| {
+            e.title("Stream has ended!")
+                .description(description)
+                .colour(
+                    stream
+                        .map(|s| s.streamer.colour)
+                        .filter(|c| *c != 0)
+                        .unwrap_or_else(|| theme.colour(EmbedEvent::StreamChat)),
+                )
+        });
+
+        sink.send_message(channel, message).await?;
+        Ok(())
+    }
+
+    async fn pin_vod_summary<S: MessageSink>(
+        sink: &S,
+        channel: ChannelId,
+        stream: &Livestream,
+        log_link: &str,
+    ) -> anyhow::Result<()> {
+        let duration = stream
+            .duration
+            .unwrap_or_else(|| Utc::now() - stream.start_at);
+
+        let mut message = CreateMessage::default();
+        message.embed(|e| {
+            e.title(format!("VOD: {}", stream.title))
+                .url(&stream.url)
+                .thumbnail(&stream.thumbnail)
+                .colour(stream.streamer.colour)
+                .field(
+                    "Duration",
+                    format!(
+                        "{}:{:02}:{:02}",
+                        duration.num_hours(),
+                        duration.num_minutes() % 60,
+                        duration.num_seconds() % 60
+                    ),
+                    true,
+                )
+                .field("Chat log", format!("[Jump to log]({})", log_link), true)
+                .timestamp(stream.start_at)
+                .author(|a| {
+                    a.name(&stream.streamer.name)
+                        .url(format!(
+                            "https://www.youtube.com/channel/{}",
+                            stream.streamer.youtube_ch_id.as_ref().unwrap()
+                        ))
+                        .icon_url(&stream.streamer.icon)
+                });
+
+            if let (Some(peak), Some(average)) = (stream.viewers.peak(), stream.viewers.average()) {
+                e.field("Peak viewers", peak, true)
+                    .field("Average viewers", average, true);
+            }
+
+            e
+        });
+
+        let summary = sink.send_message(channel, message).await?;
+        sink.pin_message(channel, summary.id).await?;
+
+        Ok(())
+    }
+
+    async fn close_stream_chat(
+        ctx: &Context,
+        channel: ChannelId,
+        mode: StreamChatMode,
+    ) -> anyhow::Result<()> {
+        let entry = match mode {
+            StreamChatMode::Channels => {
+                let deleted = channel.delete(&ctx.http).await.context(here!())?;
+
+                let (name, guild_id) = match deleted {
+                    Channel::Guild(c) => (format!("#{}", c.name), Some(c.guild_id)),
+                    _ => (channel.to_string(), None),
+                };
+
+                AuditEntry::new(AuditAction::ChannelDeleted, name).guild(guild_id)
+            }
+            StreamChatMode::Threads => {
+                let thread = channel
+                    .edit_thread(&ctx.http, |t| t.archived(true).locked(true))
+                    .await
+                    .context(here!())?;
+
+                AuditEntry::new(
+                    AuditAction::ThreadArchived,
+                    Mention::from(channel).to_string(),
+                )
+                .guild(Some(thread.guild_id))
+            }
+        };
+
+        audit_log::record(
+            &ctx.http,
+            entry.details("Stream chat closed after the stream ended."),
+        )
+        .await;
+
+        Ok(())
+    }
+
+    fn should_message_be_archived(msg: &Message) -> bool {
+        if msg.author.bot {
+            return false;
+        }
+
+        if msg.content.is_empty() && msg.attachments.is_empty() {
+            return false;
+        }
+
+        if msg.content.len() > 1000 {
+            return false;
+        }
+
+        match msg.kind {
+            MessageType::Regular | MessageType::InlineReply => (),
+            _ => return false,
+        }
+
+        if msg.attachments.is_empty() && msg.is_only_emojis() {
+            return false;
+        }
+
+        true
+    }
+
+    fn stream_chat_name(stream: &Livestream) -> String {
+        if stream.is_collab() {
+            format!(
+                "{}-collab-stream",
+                stream
+                    .participants()
+                    .map(|t| t.emoji.as_str())
+                    .collect::<String>()
+            )
+        } else {
+            format!(
+                "{}-{}-stream",
+                stream.streamer.emoji,
+                stream.streamer.name.to_ascii_lowercase().replace(' ', "-")
+            )
+        }
+    }
+
+    fn start_slowmode_monitor(
+        ctx: &Context,
+        config: &StreamChatConfig,
+        theme: ThemeConfig,
+        channel: ChannelId,
+        monitors: &mut HashMap<ChannelId, oneshot::Sender<()>>,
+    ) {
+        if !config.slowmode.enabled {
+            return;
+        }
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        monitors.insert(channel, stop_tx);
+
+        let ctx = ctx.clone();
+        let slowmode_config = config.slowmode.clone();
+        let colour = theme.colour(EmbedEvent::StreamChat);
+
+        tokio::spawn(async move {
+            if let Err(e) =
+                Self::slowmode_monitor(&ctx, channel, &slowmode_config, colour, stop_rx).await
+            {
+                error!("{:?}", e);
+            }
+        });
+    }
+
+    /// Samples the message rate of a stream chat, and adjusts its slowmode until the stream ends.
+    #[instrument(skip(ctx, config, stop))]
+    async fn slowmode_monitor(
+        ctx: &Context,
+        channel: ChannelId,
+        config: &AutoSlowmodeConfig,
+        colour: u32,
+        mut stop: oneshot::Receiver<()>,
+    ) -> anyhow::Result<()> {
+        let original_slowmode = channel
+            .to_channel(&ctx.http)
+            .await
+            .context(here!())?
+            .guild()
+            .and_then(|c| c.rate_limit_per_user)
+            .unwrap_or(0);
+
+        let sample_interval = StdDuration::from_secs(config.sample_interval.max(1));
+
+        let mut current_slowmode = original_slowmode;
+        let mut samples_below = 0;
+        let mut sampled_at = Utc::now();
+        let mut last_message = Self::first_message_id_at(sampled_at);
+
+        loop {
+            tokio::select! {
+                _ = sleep(sample_interval) => (),
+                _ = &mut stop => break,
+            }
+
+            // Every message sent since the last sample is counted, so that busy chats
+            // aren't capped at what fits in a single page.
+            let mut sent = 0;
+            let mut read_all = false;
+
+            for _ in 0..Self::SLOWMODE_SAMPLE_PAGES {
+                let messages = channel
+                    .messages(&ctx.http, |r| r.after(last_message).limit(100))
+                    .await
+                    .context(here!())?;
+
+                if let Some(newest) = messages.iter().map(|m| m.id).max() {
+                    last_message = newest;
+                }
+
+                sent += messages.iter().filter(|m| !m.author.bot).count();
+
+                if messages.len() < 100 {
+                    read_all = true;
+                    break;
+                }
+            }
+
+            let now = Utc::now();
+            let window = (now - sampled_at).to_std().unwrap_or(sample_interval);
+
+            // Anything past the pages that were read is skipped, as the rate is already known.
+            if !read_all {
+                last_message = last_message.max(Self::first_message_id_at(now));
+            }
+
+            sampled_at = now;
+
+            #[allow(clippy::cast_precision_loss)]
+            let rate = sent as f32 / window.as_secs_f32().max(1.0);
+
+            let target = config
+                .slowmode_for(rate)
+                .unwrap_or(original_slowmode)
+                .max(original_slowmode);
+
+            if target < current_slowmode {
+                samples_below += 1;
+
+                if samples_below < config.relax_after {
+                    continue;
+                }
+            }
+
+            samples_below = 0;
+
+            if target == current_slowmode {
+                continue;
+            }
+
+            channel
+                .edit(&ctx.http, |c| c.rate_limit_per_user(target))
+                .await
+                .context(here!())?;
+
+            let announcement = if target > current_slowmode {
+                format!("Chat is busy, slowmode set to {target} seconds.")
+            } else if target == 0 {
+                "Chat has calmed down, slowmode removed.".to_string()
+            } else {
+                format!("Chat has calmed down, slowmode set to {target} seconds.")
+            };
+
+            channel
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.description(announcement).colour(colour))
+                })
+                .await
+                .context(here!())?;
+
+            debug!(%rate, slowmode = %target, "Stream chat slowmode changed.");
+            current_slowmode = target;
+        }
+
+        if current_slowmode != original_slowmode {
+            channel
+                .edit(&ctx.http, |c| c.rate_limit_per_user(original_slowmode))
+                .await
+                .context(here!())?;
+        }
+
+        Ok(())
+    }
+
+    /// The lowest ID a message sent at the given time can have, as IDs start with their timestamp.
+    fn first_message_id_at(time: DateTime<Utc>) -> MessageId {
+        const DISCORD_EPOCH_MS: i64 = 1_420_070_400_000;
+
+        let since_epoch = u64::try_from(time.timestamp_millis() - DISCORD_EPOCH_MS).unwrap_or(0);
+        MessageId(since_epoch << 22)
+    }
+
+    async fn claim_stream_chat(
+        ctx: &Context,
+        config: &StreamChatConfig,
+        category: Option<&ChannelCategory>,
+        alerts_channel: ChannelId,
+        stream: &Livestream,
+        locked: bool,
+    ) -> anyhow::Result<ChannelId> {
+        let category = match category {
+            Some(category) => category,
+            None => return Self::claim_thread(ctx, alerts_channel, stream).await,
+        };
+
+        let permissions = if locked {
+            Self::locked_permissions(category.guild_id, &category.permission_overwrites)
+        } else {
+            category.permission_overwrites.clone()
+        };
+
+        let channel = Self::claim_channel(
+            &ctx.http,
+            category.guild_id,
+            category.id,
+            &permissions,
+            stream,
+            config.pin_now_watching,
+        )
+        .await?;
+
+        audit_log::record(
+            &ctx.http,
+            AuditEntry::new(
+                AuditAction::ChannelCreated,
+                Mention::from(channel).to_string(),
+            )
+            .guild(Some(category.guild_id))
+            .details(format!("Stream chat for {}", stream.url)),
+        )
+        .await;
+
+        Ok(channel)
+    }
+
+    /// The channel the alert of the stream is in, where its thread is started in `Threads` mode.
+    /// `None` if the stream gets no chat, because there's no alert to start a thread from.
+    fn chat_alerts_channel(
+        alerts: &StreamAlertsConfig,
+        category: Option<&ChannelCategory>,
+        stream: &Livestream,
+    ) -> Option<ChannelId> {
+        match (alerts.channel_for(stream.members_only), category) {
+            (Some(channel), _) => Some(channel),
+            (None, Some(_)) => Some(alerts.channel),
+            (None, None) => {
+                info!(stream = %stream.title, "No alert to start a thread from.");
+                None
+            }
+        }
+    }
+
+    #[instrument(skip(ctx))]
+    async fn claim_thread(
+        ctx: &Context,
+        alerts_channel: ChannelId,
+        stream: &Livestream,
+    ) -> anyhow::Result<ChannelId> {
+        let thread_name = Self::stream_chat_name(stream);
+
+        // The alert is posted by another task, so give it some time to show up.
+        let mut alert = None;
+
+        for _ in 0..Self::ALERT_LOOKUP_ATTEMPTS {
+            alert = alerts_channel
+                .messages(&ctx.http, |r| r.limit(50))
+                .await
+                .context(here!())?
+                .into_iter()
+                .find(|m| {
+                    m.embeds
+                        .iter()
+                        .any(|e| e.url.as_deref() == Some(stream.url.as_str()))
+                });
+
+            if alert.is_some() {
+                break;
+            }
+
+            sleep(StdDuration::from_secs(10)).await;
+        }
+
+        let alert = alert
+            .ok_or_else(|| anyhow!("Could not find alert message for stream: {}", stream.title))?;
+
+        let thread = alerts_channel
+            .create_public_thread(&ctx.http, alert.id, |t| {
+                t.name(thread_name).auto_archive_duration(1440)
+            })
+            .await
+            .context(here!())?;
+
+        Ok(thread.id)
+    }
+
+    #[instrument(skip(sink, permissions))]
+    async fn claim_channel<S: MessageSink>(
+        sink: &S,
+        guild: GuildId,
+        category: ChannelId,
+        permissions: &[PermissionOverwrite],
+        stream: &Livestream,
+        pin_now_watching: bool,
+    ) -> anyhow::Result<ChannelId> {
+        let mut create_channel = CreateChannel::default();
+        create_channel
+            .name(Self::stream_chat_name(stream))
+            .category(category)
+            .position(1)
+            .topic(&stream.url)
+            .permissions(permissions.to_vec());
+
+        let channel = sink.create_channel(guild, create_channel).await?;
+        let stream = &Self::with_cached_images(stream).await;
+
+        let mut message = CreateMessage::default();
+        message.embed(|e| {
+            e.title("Now watching")
+                .description(&stream.title)
+                .url(&stream.url)
+                .timestamp(stream.start_at)
+                .colour(stream.streamer.colour)
+                .image(&stream.thumbnail)
+                .author(|a| {
+                    a.name(&stream.streamer.name)
+                        .url(format!(
+                            "https://www.youtube.com/channel/{}",
+                            stream.streamer.youtube_ch_id.as_ref().unwrap()
+                        ))
+                        .icon_url(&stream.streamer.icon)
+                })
+        });
+
+        let now_watching = sink.send_message(channel, message).await?;
+
+        if pin_now_watching {
+            sink.pin_message(channel, now_watching.id).await?;
+        }
+
+        Ok(channel)
+    }
+}
+
+#[derive(Debug)]
+pub enum DiscordMessageData {
+    Tweet(HoloTweet),
+    ScheduledLive(Livestream),
+    ScheduleUpdate(ScheduleUpdate),
+    Birthday(Birthday),
+    /// The current viewer count of a live stream, for updating its alert.
+    LiveViewers(VideoId, u32),
+    /// A stream was cancelled, so its alert should be taken down.
+    Cancelled(VideoId),
+    Donation(Donation),
+}
+
+/// The alerts posted for streams, with what they were posted for, so they can be edited later.
+type LiveAlerts = HashMap<VideoId, (PostedAlert, Livestream, Vec<TranslatedTitle>)>;
+
+/// A live alert, along with its copies in topic and language channels.
+#[derive(Debug, Clone)]
+struct PostedAlert {
+    message: SentMessage,
+    /// The copies, with the translation that replaced the title in those for languages.
+    copies: Vec<(SentMessage, Option<TranslatedTitle>)>,
+}
+
+impl PostedAlert {
+    /// The alert followed by its copies.
+    fn messages(&self) -> impl Iterator<Item = (&SentMessage, Option<&TranslatedTitle>)> {
+        std::iter::once((&self.message, None)).chain(
+            self.copies
+                .iter()
+                .map(|(message, translation)| (message, translation.as_ref())),
+        )
+    }
+}
+
+/// The title of a stream translated for its alert.
+#[derive(Debug, Clone)]
+pub struct TranslatedTitle {
+    /// The DeepL code of the language, such as `JA`.
+    pub language: String,
+    /// The channel to post a copy of the alert in, if it's not added to the alert.
+    pub channel: Option<ChannelId>,
+    pub title: String,
+}
+
+#[derive(Debug)]
+struct SchedulePost {
+    channel: ChannelId,
+    message: MessageId,
+    date: NaiveDate,
+    description: String,
+}
+
+struct ArchivedMessage<'a> {
+    pub author: Mention,
+    pub author_name: String,
+    pub content: String,
+    pub timestamp: Duration,
+    pub attachment_urls: Vec<String>,
+    pub video_id: Option<&'a VideoId>,
+}
+
+impl ArchivedMessage<'_> {
+    /// The time since the stream started, as `[-][HH:]MM:SS`.
+    fn clock_time(&self) -> String {
+        archive_index::clock_time(self.timestamp.num_seconds())
+    }
+
+    pub fn format_timestamp(&self) -> String {
+        let timestamp = self.clock_time();
+
+        match &self.video_id {
+            Some(id) if self.timestamp.num_seconds() >= 0 => format!(
+                "[{timestamp}](https://youtu.be/{id}?t={secs})",
+                secs = self.timestamp.num_seconds()
+            ),
+            _ => timestamp,
+        }
+    }
+
+    /// The message as it's written in the plain text transcript.
+    pub fn transcript_line(&self) -> String {
+        let mut line = format!(
+            "[{}] {}: {}\n",
+            self.clock_time(),
+            self.author_name,
+            self.content
+        );
+
+        if !self.attachment_urls.is_empty() {
+            line.push_str(&self.attachment_urls.join(" "));
+            line.push('\n');
+        }
+
+        line
+    }
+}
+
+impl std::fmt::Display for ArchivedMessage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} {}: {}",
+            self.format_timestamp(),
+            self.author,
+            self.content
+        )?;
+
+        if !self.attachment_urls.is_empty() {
+            writeln!(f, "{}", self.attachment_urls.join(" "))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Clone)]
+enum TweetReply {
+    None,
+    SameChannel(String, MessageReference),
+    OtherChannel(String, String),
+}
+
+/// What the placeholders in alert templates are replaced with.
+struct AlertValues<'a> {
+    talent: &'a str,
+    talents: String,
+    /// How many talents are taking part.
+    count: usize,
+    title: &'a str,
+    url: &'a str,
+    start: DateTime<Utc>,
+    pings: String,
+}
+
+impl<'a> AlertValues<'a> {
+    fn live(talent: &'a Talent, live: &'a Livestream, roles: &[RoleId]) -> Self {
+        Self {
+            talent: &talent.name,
+            talents: live
+                .participants()
+                .map(|t| t.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            count: live.participants().count(),
+            title: &live.title,
+            url: &live.url,
+            start: live.start_at,
+            pings: Self::pings(roles),
+        }
+    }
+
+    fn pings(roles: &[RoleId]) -> String {
+        roles
+            .iter()
+            .map(|r| Mention::from(*r).to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn get(&self, placeholder: Placeholder) -> String {
+        match placeholder {
+            Placeholder::Talent => self.talent.to_owned(),
+            Placeholder::Talents => self.talents.clone(),
+            Placeholder::Title => self.title.to_owned(),
+            Placeholder::Url => self.url.to_owned(),
+            Placeholder::StartRelative => format!("<t:{}:R>", self.start.timestamp()),
+            Placeholder::Pings => self.pings.clone(),
+            Placeholder::Count => self.count.to_string(),
+            Placeholder::StartsIn => {
+                format_duration((self.start - Utc::now()).to_std().unwrap_or_default())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::TimeZone;
+    use futures::executor::block_on;
+    use serde_json::{json, Value};
+    use serenity::model::id::{RoleId, UserId};
+    use utility::{
+        config::{
+            HoloBranch, HoloGeneration, Talent, TalentConfigData, TopicAlertChannel,
+            TweetMediaFallback,
+        },
+        discord::assert_golden,
+        streams::ViewerStats,
+    };
+
+    use super::*;
+    use crate::{
+        holo_api::{HoloApi, VideoUpdate},
+        message_sink::mock::{MockSink, SinkCall},
+    };
+
+    fn talent(name: &str, twitter_id: u64, role: Option<u64>) -> Talent {
+        Talent::from(TalentConfigData {
+            name: name.to_owned(),
+            branch: HoloBranch::HoloEN,
+            generation: HoloGeneration::Myth,
+            youtube_ch_id: Some("UCL_qhgtOy0dy1Agp8vkySQg".parse().unwrap()),
+            twitter_id: Some(twitter_id),
+            discord_role: role.map(RoleId),
+            ..Default::default()
+        })
+    }
+
+    fn stream(streamer: Talent, collaborators: Vec<Talent>) -> Livestream {
+        Livestream {
+            id: "dQw4w9WgXcQ".parse().unwrap(),
+            title: "Karaoke!".to_owned(),
+            thumbnail: "https://i3.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg".to_owned(),
+            url: "https://youtube.com/watch?v=dQw4w9WgXcQ".to_owned(),
+            streamer,
+            collaborators,
+            created_at: Utc::now(),
+            start_at: Utc::now(),
+            duration: None,
+            state: VideoStatus::Live,
+            topic: None,
+            members_only: false,
+            viewers: ViewerStats::default(),
+        }
+    }
+
+    fn config(talents: Vec<Talent>) -> Config {
+        let mut config = Config::default();
+        config.stream_tracking.alerts.channel = ChannelId(100);
+        config.talents = talents;
+        config
+    }
+
+    /// A talent and stream with every field that shows up in embeds filled in, for golden tests.
+    fn golden_talent() -> Talent {
+        Talent::from(TalentConfigData {
+            name: "Mori Calliope".to_owned(),
+            icon: "https://yt3.ggpht.com/calli.png".to_owned(),
+            branch: HoloBranch::HoloEN,
+            generation: HoloGeneration::Myth,
+            youtube_ch_id: Some("UCL_qhgtOy0dy1Agp8vkySQg".parse().unwrap()),
+            twitter_id: Some(1),
+            colour: 16_711_762,
+            discord_role: Some(RoleId(10)),
+            ..Default::default()
+        })
+    }
+
+    fn golden_stream() -> Livestream {
+        let start_at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+        Livestream {
+            created_at: start_at - Duration::days(1),
+            start_at,
+            duration: Some(Duration::seconds(5025)),
+            ..stream(golden_talent(), Vec::new())
+        }
+    }
+
+    fn assert_golden_embeds(sink: &MockSink, name: &str) {
+        let sent = sent_messages(sink);
+
+        assert_golden(
+            format!("{}/golden/{name}.json", env!("CARGO_MANIFEST_DIR")),
+            &sent[0].1["embeds"],
+        );
+    }
+
+    fn sent_messages(sink: &MockSink) -> Vec<(ChannelId, HashMap<&'static str, Value>)> {
+        sink.calls()
+            .into_iter()
+            .filter_map(|c| match c {
+                SinkCall::Send { channel, message } => Some((channel, message)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn live_alert_mentions_every_participant() {
+        let host = talent("Calli", 1, Some(10));
+        let guest = talent("Kiara", 2, Some(20));
+        let config = config(vec![host.clone(), guest.clone()]);
+        let sink = MockSink::default();
+
+        block_on(DiscordApi::post_live_alert(
+            &sink,
+            &config,
+            &stream(host, vec![guest]),
+            &[],
+        ))
+        .unwrap();
+
+        let sent = sent_messages(&sink);
+        assert_eq!(sent.len(), 1);
+
+        let (channel, message) = &sent[0];
+        assert_eq!(*channel, ChannelId(100));
+        assert_eq!(message["content"], json!("<@&10> <@&20>"));
+        assert_eq!(
+            message["embeds"][0]["title"],
+            json!("Calli, Kiara just went live in a collab!")
+        );
+    }
+
+    #[test]
+    fn live_alert_uses_configured_templates() {
+        let host = talent("Calli", 1, Some(10));
+        let mut config = config(vec![host.clone()]);
+
+        let templates = &mut config.stream_tracking.alerts.templates;
+        templates.title = "{talent} is singing: {title}".parse().unwrap();
+        templates.content = "{url} {pings} #holoEN".parse().unwrap();
+
+        let sink = MockSink::default();
+
+        block_on(DiscordApi::post_live_alert(
+            &sink,
+            &config,
+            &stream(host, Vec::new()),
+            &[],
+        ))
+        .unwrap();
+
+        let (_, message) = &sent_messages(&sink)[0];
+        assert_eq!(
+            message["content"],
+            json!("https://youtube.com/watch?v=dQw4w9WgXcQ <@&10> #holoEN")
+        );
+        assert_eq!(
+            message["embeds"][0]["title"],
+            json!("Calli is singing: Karaoke!")
+        );
+    }
+
+    #[test]
+    fn live_alert_skips_untracked_talents() {
+        let config = config(vec![talent("Calli", 1, None)]);
+        let sink = MockSink::default();
+
+        block_on(DiscordApi::post_live_alert(
+            &sink,
+            &config,
+            &stream(talent("Someone else", 3, None), Vec::new()),
+            &[],
+        ))
+        .unwrap();
+
+        assert!(sink.calls().is_empty());
+    }
+
+    #[test]
+    fn members_only_alerts_are_routed_or_suppressed() {
+        let host = talent("Calli", 1, None);
+        let mut config = config(vec![host.clone()]);
+        config.stream_tracking.alerts.members_only.channel = Some(ChannelId(200));
+
+        let live = Livestream {
+            members_only: true,
+            ..stream(host, Vec::new())
+        };
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::post_live_alert(&sink, &config, &live, &[])).unwrap();
+
+        let (channel, message) = &sent_messages(&sink)[0];
+        assert_eq!(*channel, ChannelId(200));
+        assert!(message["embeds"][0]["footer"]["text"]
+            .as_str()
+            .unwrap()
+            .ends_with(" • Members only"));
+
+        config.stream_tracking.alerts.members_only.enabled = false;
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::post_live_alert(&sink, &config, &live, &[])).unwrap();
+
+        assert!(sink.calls().is_empty());
+    }
+
+    #[test]
+    fn live_alerts_are_copied_into_topic_channels() {
+        let host = talent("Calli", 1, None);
+        let mut config = config(vec![host.clone()]);
+        config.stream_tracking.alerts.topic_channels = vec![
+            TopicAlertChannel {
+                channel: ChannelId(300),
+                topics: vec!["Singing".to_owned()],
+            },
+            TopicAlertChannel {
+                channel: ChannelId(400),
+                topics: vec!["minecraft".to_owned()],
+            },
+        ];
+
+        let live = Livestream {
+            topic: Some("singing".to_owned()),
+            ..stream(host, Vec::new())
+        };
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::post_live_alert(&sink, &config, &live, &[])).unwrap();
+
+        let sent = sent_messages(&sink);
+        assert_eq!(
+            sent.iter().map(|(c, _)| *c).collect::<Vec<_>>(),
+            vec![ChannelId(100), ChannelId(300)]
+        );
+        assert_eq!(
+            sent[0].1["embeds"][0]["fields"][0]["value"],
+            json!("Singing")
+        );
+    }
+
+    #[test]
+    fn translated_titles_are_added_or_posted_in_their_channels() {
+        let host = talent("Calli", 1, None);
+        let config = config(vec![host.clone()]);
+        let live = stream(host, Vec::new());
+
+        let translations = [
+            TranslatedTitle {
+                language: "JA".to_owned(),
+                channel: None,
+                title: "カラオケ！".to_owned(),
+            },
+            TranslatedTitle {
+                language: "ID".to_owned(),
+                channel: Some(ChannelId(500)),
+                title: "Karaoke! (ID)".to_owned(),
+            },
+        ];
+
+        let sink = MockSink::default();
+        let posted = block_on(DiscordApi::post_live_alert(
+            &sink,
+            &config,
+            &live,
+            &translations,
+        ))
+        .unwrap()
+        .unwrap();
+
+        let sent = sent_messages(&sink);
+        assert_eq!(sent.len(), 2);
+
+        // The copy is kept, so that it's edited along with the alert.
+        assert_eq!(posted.copies.len(), 1);
+        assert_eq!(posted.copies[0].0.channel, ChannelId(500));
+        assert_eq!(
+            posted.copies[0].1.as_ref().map(|t| t.language.as_str()),
+            Some("ID")
+        );
+
+        let (channel, alert) = &sent[0];
+        assert_eq!(*channel, ChannelId(100));
+        assert_eq!(alert["embeds"][0]["description"], json!("Karaoke!"));
+        assert_eq!(
+            alert["embeds"][0]["fields"][0],
+            json!({"name": "Title (JA)", "value": "カラオケ！", "inline": false})
+        );
+
+        let (channel, copy) = &sent[1];
+        assert_eq!(*channel, ChannelId(500));
+        assert_eq!(
+            copy["embeds"][0]["description"],
+            json!("Karaoke! (ID)\n*Karaoke!*")
+        );
+    }
+
+    #[test]
+    fn donations_are_thanked() {
+        let mut config = config(Vec::new());
+        config.support.kofi.channel = ChannelId(600);
+
+        let donation = Donation {
+            from_name: Some("Pekora".to_owned()),
+            message: Some("Keep it up peko!".to_owned()),
+            amount: "3.00".to_owned(),
+            currency: "USD".to_owned(),
+            subscription: false,
+        };
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::post_donation(&sink, &config, &donation)).unwrap();
+
+        let (channel, message) = &sent_messages(&sink)[0];
+        assert_eq!(*channel, ChannelId(600));
+        assert_eq!(
+            message["embeds"][0]["description"],
+            json!("Pekora made a donation!")
+        );
+        assert_eq!(
+            message["embeds"][0]["fields"][0]["value"],
+            json!("Keep it up peko!")
+        );
+
+        config.support.kofi.show_amount = true;
+
+        let donation = Donation {
+            from_name: None,
+            subscription: true,
+            ..donation
+        };
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::post_donation(&sink, &config, &donation)).unwrap();
+
+        let (_, message) = &sent_messages(&sink)[0];
+        assert_eq!(
+            message["embeds"][0]["description"],
+            json!("Someone became a supporter with 3.00 USD a month!")
+        );
     }
 
-    fn try_find_stream_for_channel(
-        topic: &str,
-        index: &HashMap<VideoId, Livestream>,
-    ) -> Option<(Livestream, VideoStatus)> {
-        let stream = index.values().find(|s| s.url == topic)?;
+    #[test]
+    fn live_alert_viewers_are_edited_into_the_alert() {
+        let host = talent("Calli", 1, None);
+        let config = config(vec![host.clone()]);
+        let sink = MockSink::default();
+
+        let alert = PostedAlert {
+            message: SentMessage {
+                channel: ChannelId(100),
+                id: MessageId(7),
+                timestamp: Utc::now(),
+            },
+            copies: Vec::new(),
+        };
 
-        match &stream.state {
-            VideoStatus::Upcoming => {
-                error!("This should never happen.");
-                None
+        block_on(DiscordApi::update_live_alert_viewers(
+            &sink,
+            &config,
+            &alert,
+            &stream(host, Vec::new()),
+            &[],
+            1234,
+        ))
+        .unwrap();
+
+        match &sink.calls()[..] {
+            [SinkCall::Edit {
+                channel,
+                message,
+                edit,
+            }] => {
+                assert_eq!(*channel, ChannelId(100));
+                assert_eq!(*message, MessageId(7));
+                assert_eq!(
+                    edit["embeds"][0]["fields"][0],
+                    json!({ "name": "Watching now", "value": "1234", "inline": true })
+                );
             }
-            VideoStatus::Live | VideoStatus::Past => Some((stream.clone(), stream.state)),
-            VideoStatus::New => todo!(),
-            VideoStatus::Missing => todo!(),
-            _ => todo!(),
+            calls => panic!("Unexpected calls: {calls:?}"),
         }
     }
 
-    #[instrument(skip(ctx))]
-    async fn get_last_message_id_in_channel(
-        ctx: &Arc<CacheAndHttp>,
-        channel: &ChannelId,
-    ) -> Option<MessageId> {
-        match channel.to_channel(&ctx.http).await.context(here!()) {
-            Ok(Channel::Guild(ch)) => ch.last_message_id,
-            Ok(Channel::Private(ch)) => ch.last_message_id,
-            Ok(_) => None,
-            Err(e) => {
-                error!("{:?}", e);
-                None
+    #[test]
+    fn cancelled_stream_alerts_are_marked_or_deleted() {
+        let host = talent("Calli", 1, None);
+        let mut config = config(vec![host.clone()]);
+        let live = stream(host, Vec::new());
+
+        config.stream_tracking.alerts.on_cancelled = CancelledAlertAction::MarkCancelled;
+
+        let translation = TranslatedTitle {
+            language: "ID".to_owned(),
+            channel: Some(ChannelId(500)),
+            title: "Karaoke! (ID)".to_owned(),
+        };
+
+        let alert = PostedAlert {
+            message: SentMessage {
+                channel: ChannelId(100),
+                id: MessageId(7),
+                timestamp: Utc::now(),
+            },
+            copies: vec![(
+                SentMessage {
+                    channel: ChannelId(500),
+                    id: MessageId(8),
+                    timestamp: Utc::now(),
+                },
+                Some(translation),
+            )],
+        };
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::take_down_live_alert(
+            &sink,
+            &config,
+            &alert,
+            &live,
+            &[],
+        ))
+        .unwrap();
+
+        match &sink.calls()[..] {
+            [SinkCall::Edit { message, edit, .. }, SinkCall::Edit {
+                message: copy,
+                edit: copy_edit,
+                ..
+            }] => {
+                assert_eq!(*message, MessageId(7));
+                assert_eq!(
+                    edit["embeds"][0]["description"],
+                    json!(format!("~~{}~~\nThis stream was cancelled.", live.title))
+                );
+
+                assert_eq!(*copy, MessageId(8));
+                assert_eq!(
+                    copy_edit["embeds"][0]["description"],
+                    json!("~~Karaoke! (ID)~~\nThis stream was cancelled.")
+                );
             }
+            calls => panic!("Unexpected calls: {calls:?}"),
         }
+
+        config.stream_tracking.alerts.on_cancelled = CancelledAlertAction::Delete;
+
+        let sink = MockSink::default();
+        block_on(DiscordApi::take_down_live_alert(
+            &sink,
+            &config,
+            &alert,
+            &live,
+            &[],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            sink.calls(),
+            vec![
+                SinkCall::Delete {
+                    channel: ChannelId(100),
+                    message: MessageId(7),
+                },
+                SinkCall::Delete {
+                    channel: ChannelId(500),
+                    message: MessageId(8),
+                }
+            ]
+        );
     }
 
-    #[instrument(skip(ctx, archive_notifier))]
-    async fn chat_archive_thread(
-        ctx: Context,
-        log_ch: ChannelId,
-        config: &StreamChatConfig,
-        mut archive_notifier: mpsc::UnboundedReceiver<(ChannelId, Option<Livestream>)>,
-    ) -> anyhow::Result<()> {
-        let log_ch = Arc::new(Mutex::new(log_ch));
+    #[test]
+    fn streams_that_disappear_after_going_live_get_their_alert_taken_down() {
+        let host = talent("Calli", 1, None);
+        let mut config = config(vec![host.clone()]);
+        config.stream_tracking.alerts.on_cancelled = CancelledAlertAction::MarkCancelled;
+
+        // The alert went out at the scheduled start, before Holodex saw the stream go live.
+        let live = stream(host, Vec::new());
+        let now = Utc::now();
+
+        let mut stream_index = HashMap::from([(live.id.clone(), (None, live.clone()))]);
+        let mut stream_queue = tokio_util::time::DelayQueue::new();
+
+        let mut live_alerts = LiveAlerts::from([(
+            live.id.clone(),
+            (
+                PostedAlert {
+                    message: SentMessage {
+                        channel: ChannelId(100),
+                        id: MessageId(7),
+                        timestamp: now,
+                    },
+                    copies: Vec::new(),
+                },
+                live.clone(),
+                Vec::new(),
+            ),
+        )]);
+
+        let update = HoloApi::status_transition(
+            &live,
+            VideoStatus::Missing,
+            None,
+            None,
+            live.created_at,
+            now,
+        );
+        let updates = HoloApi::apply_updates(
+            update.into_iter().collect(),
+            &mut stream_index,
+            &mut stream_queue,
+        );
 
-        while let Some((channel, stream)) = archive_notifier.recv().await {
-            let log_clone = Arc::clone(&log_ch);
-            let ctx_clone = ctx.clone();
-            let discussion_ch = stream
-                .as_ref()
-                .and_then(|s| config.post_stream_discussion.get(&s.streamer.branch))
-                .copied();
+        let sink = MockSink::default();
+        for message in HoloApi::cancelled_alerts(&config.stream_tracking, &updates) {
+            match message {
+                DiscordMessageData::Cancelled(id) => block_on(DiscordApi::take_down_cancelled(
+                    &sink,
+                    &config,
+                    &mut live_alerts,
+                    &id,
+                ))
+                .unwrap(),
+                _ => panic!("Unexpected message for a cancelled stream."),
+            }
+        }
 
-            tokio::spawn(async move {
-                if let Err(e) =
-                    Self::archive_channel(&ctx_clone, channel, stream, log_clone, discussion_ch)
-                        .await
-                {
-                    error!("{:?}", e);
-                }
-            });
+        match &sink.calls()[..] {
+            [SinkCall::Edit { message, edit, .. }] => {
+                assert_eq!(*message, MessageId(7));
+                assert_eq!(
+                    edit["embeds"][0]["description"],
+                    json!(format!("~~{}~~\nThis stream was cancelled.", live.title))
+                );
+            }
+            calls => panic!("Unexpected calls: {calls:?}"),
         }
 
-        Ok(())
+        assert!(stream_index.is_empty());
+        assert!(live_alerts.is_empty());
     }
 
-    #[instrument(skip(ctx))]
-    async fn archive_channel(
-        ctx: &Context,
-        channel: ChannelId,
-        stream: Option<Livestream>,
-        log_channel: Arc<Mutex<ChannelId>>,
-        discussion_ch: Option<ChannelId>,
-    ) -> anyhow::Result<()> {
-        let cache = &ctx.cache;
+    #[test]
+    fn streams_that_ended_normally_keep_their_alert() {
+        let host = talent("Calli", 1, None);
+        let mut config = config(vec![host.clone()]);
+        config.stream_tracking.alerts.on_cancelled = CancelledAlertAction::MarkCancelled;
+
+        let live = stream(host, Vec::new());
+        let now = Utc::now();
+
+        // Went live an hour ago, and the archive was privated once it ended.
+        let update = HoloApi::status_transition(
+            &live,
+            VideoStatus::Missing,
+            Some(now - Duration::hours(1)),
+            None,
+            live.created_at,
+            now,
+        );
+        assert!(matches!(update, Some(VideoUpdate::Ended(_))));
+
+        // Disappeared right after starting, but left a recording behind.
+        let update = HoloApi::status_transition(
+            &live,
+            VideoStatus::Missing,
+            Some(now - Duration::minutes(2)),
+            Some(Duration::minutes(2)),
+            live.created_at,
+            now,
+        );
+        assert!(matches!(update, Some(VideoUpdate::Ended(_))));
+    }
 
-        let message_stream = channel.messages_iter(&ctx.http);
-        let stream_start = match stream.as_ref() {
-            Some(s) => s.start_at,
-            None => *channel.created_at(),
+    #[test]
+    fn cancelled_alerts_are_kept_by_default() {
+        let config = config(Vec::new());
+        let updates = vec![StreamUpdate::Unscheduled("dQw4w9WgXcQ".parse().unwrap())];
+
+        assert!(HoloApi::cancelled_alerts(&config.stream_tracking, &updates).is_empty());
+    }
+
+    #[test]
+    fn archived_messages_are_written_to_the_transcript() {
+        let video_id = "dQw4w9WgXcQ".parse().unwrap();
+
+        let message = ArchivedMessage {
+            author: Mention::from(UserId(1)),
+            author_name: "Kiara".to_owned(),
+            content: "kikkeriki".to_owned(),
+            timestamp: Duration::seconds(3725),
+            attachment_urls: vec!["https://cdn.example.com/a.png".to_owned()],
+            video_id: Some(&video_id),
         };
-        let stream_id = stream.as_ref().map(|s| &s.id);
 
-        let messages = message_stream
-            .try_filter_map(|msg| async move {
-                if !Self::should_message_be_archived(&msg) {
-                    return Ok(None);
-                }
+        assert_eq!(
+            message.format_timestamp(),
+            "[01:02:05](https://youtu.be/dQw4w9WgXcQ?t=3725)"
+        );
+        assert_eq!(
+            message.transcript_line(),
+            "[01:02:05] Kiara: kikkeriki\nhttps://cdn.example.com/a.png\n"
+        );
 
-                Ok(Some(ArchivedMessage {
-                    author: Mention::from(msg.author.id),
-                    content: msg.content_safe(cache),
-                    video_id: stream_id,
-                    timestamp: *msg.timestamp - stream_start,
-                    attachment_urls: msg.attachments.iter().map(|a| a.url.clone()).collect(),
-                }))
-            })
-            .map_ok(|msg| msg.to_string())
-            .try_collect::<Vec<String>>()
-            .await
-            .context(here!())?;
+        let early = ArchivedMessage {
+            timestamp: Duration::seconds(-90),
+            ..message
+        };
 
-        if messages.is_empty() {
-            channel.delete(&ctx.http).await.context(here!())?;
-            return Ok(());
+        assert_eq!(early.format_timestamp(), "-01:30");
+    }
+
+    #[test]
+    fn vod_summary_shows_viewer_stats() {
+        let mut stream = stream(talent("Calli", 1, None), Vec::new());
+
+        for viewers in [1000, 3000, 2000] {
+            stream.viewers.record(Utc::now(), viewers);
         }
 
-        let start_time = Instant::now();
+        let sink = MockSink::default();
 
-        channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    e.title("Stream has ended!");
+        block_on(DiscordApi::pin_vod_summary(
+            &sink,
+            ChannelId(6),
+            &stream,
+            "https://discord.com/channels/1/4/3",
+        ))
+        .unwrap();
 
-                    let formatted_archival_time = match (
-                        Self::ARCHIVAL_WARNING_TIME.as_secs() / 60,
-                        Self::ARCHIVAL_WARNING_TIME.as_secs() % 60
-                    ) {
-                        (0, 0..=30) => "now".to_string(),
-                        (m, 50..=59) => format!("in {} minutes", m + 1),
-                        (m, 0..=10) => format!("in {} minutes", m),
-                        (0, s) => format!("in {} seconds", s),
-                        (m, s) => format!("in {} minutes and {} seconds", m, s),
-                    };
+        let sent = sent_messages(&sink);
+        let fields = &sent[0].1["embeds"][0]["fields"];
 
-                    e.description(
-                        if let Some(discussion_ch) = &discussion_ch {
-                        format!(
-                            "Feel free to continue talking in {}!\nThis stream will be archived {}.",
-                            Mention::from(*discussion_ch), formatted_archival_time
-                        )
-                    } else {
-                        format!("This stream will be archived {}.", formatted_archival_time)
-                    });
+        assert_eq!(fields[2]["name"], json!("Peak viewers"));
+        assert_eq!(fields[2]["value"], json!("3000"));
+        assert_eq!(fields[3]["name"], json!("Average viewers"));
+        assert_eq!(fields[3]["value"], json!("2000"));
+    }
 
-                    e.colour(
-                        stream
-                            .as_ref()
-                            .map(|s| s.streamer.colour)
-                            .unwrap_or(6_282_735),
-                    )
-                })
-            })
-            .await.context(here!())?;
+    #[test]
+    fn status_board_is_posted_once_and_then_edited() {
+        let sink = MockSink::default();
+        let mut boards = HashMap::new();
+        let calli = stream(talent("Calli", 1, None), Vec::new());
+
+        let live = DiscordApi::status_board_description(&[&calli]);
+        let nobody = DiscordApi::status_board_description(&[]);
+
+        for description in [&live, &live, &nobody] {
+            block_on(DiscordApi::update_status_board(
+                &sink,
+                &mut boards,
+                ChannelId(8),
+                0,
+                description,
+            ))
+            .unwrap();
+        }
 
-        let mut seg_msg = SegmentedMessage::<String, Livestream>::new();
-        let seg_msg = seg_msg
-            .data(messages)
-            .order(DataOrder::Reverse)
-            .position(SegmentDataPosition::Fields)
-            .segment_format(Box::new(|e, i, _| {
-                e.title(format!("Log {}", i + 1));
-            }))
-            .link_format(Box::new(|i, m, _| {
-                format!("[Log {}]({})\n", i + 1, m.link())
-            }));
+        let calls = sink.calls();
+        assert_eq!(calls.len(), 2);
+
+        match &calls[..] {
+            [SinkCall::Send { message, .. }, SinkCall::Edit { edit, .. }] => {
+                assert!(message["embeds"][0]["description"]
+                    .as_str()
+                    .unwrap()
+                    .contains("**Calli**: [Karaoke!](https://youtube.com/watch?v=dQw4w9WgXcQ)"));
+                assert_eq!(
+                    edit["embeds"][0]["description"],
+                    json!("Nobody is live right now.")
+                );
+            }
+            calls => panic!("Unexpected calls: {calls:?}"),
+        }
+    }
 
-        let seg_msg = match stream {
-            Some(stream) => seg_msg
-                .colour(stream.streamer.colour)
-                .index_format(Box::new(move |e, i, _| {
-                    if i == 0 {
-                        e.title(format!("Logs from {}", &stream.title))
-                            .url(&stream.url)
-                            .thumbnail(&stream.thumbnail)
-                            .timestamp(
-                                stream
-                                    .duration
-                                    .map_or_else(Utc::now, |d| stream.start_at + d),
-                            )
-                            .author(|a| {
-                                a.name(&stream.streamer.name)
-                                    .url(format!(
-                                        "https://www.youtube.com/channel/{}",
-                                        &stream.streamer.youtube_ch_id.as_ref().unwrap()
-                                    ))
-                                    .icon_url(&stream.streamer.icon)
-                            });
-                    }
-                })),
-            None => seg_msg.index_format(Box::new(|e, i, _| {
-                if i == 0 {
-                    e.title("Logs from unknown stream").timestamp(Utc::now());
-                }
-            })),
+    #[test]
+    fn claimed_channel_gets_pinned_now_watching() {
+        let sink = MockSink::default();
+        let stream = stream(talent("Calli", 1, None), Vec::new());
+
+        let channel = block_on(DiscordApi::claim_channel(
+            &sink,
+            GuildId(1),
+            ChannelId(2),
+            &[],
+            &stream,
+            true,
+        ))
+        .unwrap();
+
+        let calls = sink.calls();
+        assert_eq!(calls.len(), 3);
+
+        match &calls[0] {
+            SinkCall::CreateChannel { guild, channel } => {
+                assert_eq!(*guild, GuildId(1));
+                assert_eq!(channel["topic"], json!(stream.url));
+                assert_eq!(channel["parent_id"], json!(2));
+            }
+            call => panic!("Expected a channel to be created, got {call:?}"),
+        }
+
+        match (&calls[1], &calls[2]) {
+            (
+                SinkCall::Send {
+                    channel: sent_in,
+                    message,
+                },
+                SinkCall::Pin {
+                    channel: pinned_in, ..
+                },
+            ) => {
+                assert_eq!(*sent_in, channel);
+                assert_eq!(*pinned_in, channel);
+                assert_eq!(message["embeds"][0]["title"], json!("Now watching"));
+            }
+            calls => panic!("Expected the embed to be sent and pinned, got {calls:?}"),
+        }
+    }
+
+    #[test]
+    fn early_chats_are_locked_for_every_role() {
+        let moderators = PermissionOverwrite {
+            allow: Permissions::SEND_MESSAGES | Permissions::MANAGE_MESSAGES,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(RoleId(2)),
+        };
+        let member = PermissionOverwrite {
+            allow: Permissions::SEND_MESSAGES,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(UserId(3)),
         };
 
-        seg_msg.create(ctx, log_channel).await.context(here!())?;
+        let locked = DiscordApi::locked_permissions(GuildId(1), &[moderators, member.clone()]);
 
-        let archival_time = Instant::now() - start_time;
-        let time_to_wait = Self::ARCHIVAL_WARNING_TIME - archival_time;
+        assert_eq!(locked.len(), 3);
+        assert_eq!(locked[0].allow, Permissions::MANAGE_MESSAGES);
+        assert!(locked[0].deny.contains(Permissions::SEND_MESSAGES));
+        assert_eq!(locked[1].allow, member.allow);
+        assert_eq!(locked[2].kind, PermissionOverwriteType::Role(RoleId(1)));
+        assert!(locked[2].deny.contains(Permissions::SEND_MESSAGES));
+    }
 
-        sleep(time_to_wait).await;
+    #[test]
+    fn archival_notice_points_to_discussion() {
+        let sink = MockSink::default();
+        let stream = stream(talent("Calli", 1, None), Vec::new());
+
+        block_on(DiscordApi::announce_archival(
+            &sink,
+            &ThemeConfig::default(),
+            ChannelId(5),
+            Some(&stream),
+            Some(ChannelId(6)),
+        ))
+        .unwrap();
+
+        let sent = sent_messages(&sink);
+        let description = sent[0].1["embeds"][0]["description"].as_str().unwrap();
+
+        assert!(description.contains("<#6>"));
+        assert!(description.ends_with("in 5 minutes."));
+    }
 
-        channel.delete(&ctx.http).await.context(here!())?;
+    #[test]
+    fn tweet_reply_in_same_channel_references_original() {
+        let sink = MockSink::default();
+        let tweet = HoloTweet {
+            id: 2,
+            user: talent("Calli", 1, None),
+            text: "Replying to myself".to_owned(),
+            link: "https://twitter.com/moricalliope/status/2".to_owned(),
+            timestamp: Utc::now(),
+            media: Vec::new(),
+            translation: None,
+            replied_to: None,
+        };
 
-        Ok(())
+        let reply = TweetReply::SameChannel(
+            "Calli".to_owned(),
+            MessageReference::from((ChannelId(7), MessageId(1))),
+        );
+
+        block_on(DiscordApi::post_tweet(
+            &sink,
+            &Config::default(),
+            ChannelId(7),
+            &tweet,
+            reply,
+            None,
+        ))
+        .unwrap();
+
+        let sent = sent_messages(&sink);
+        assert!(sent[0].1.contains_key("message_reference"));
+        assert_eq!(
+            sent[0].1["embeds"][0]["description"],
+            json!("Replying to myself")
+        );
     }
 
-    fn should_message_be_archived(msg: &Message) -> bool {
-        if msg.author.bot {
-            return false;
-        }
+    #[test]
+    fn tweet_in_shared_feed_is_posted_as_talent() {
+        let sink = MockSink::default();
 
-        if msg.content.is_empty() && msg.attachments.is_empty() {
-            return false;
-        }
+        let mut calli = talent("Calli", 1, None);
+        calli.icon = "https://yt3.ggpht.com/calli.png".to_owned();
 
-        if msg.content.len() > 1000 {
-            return false;
-        }
+        let mut config = config(vec![calli.clone(), talent("Kiara", 2, None)]);
+        config.twitter.webhook_identities = true;
+        config.twitter.feeds.insert(
+            HoloBranch::HoloEN,
+            HashMap::from([(HoloGeneration::Myth, ChannelId(7))]),
+        );
 
-        match msg.kind {
-            MessageType::Regular | MessageType::InlineReply => (),
-            _ => return false,
-        }
+        let tweet = HoloTweet {
+            id: 2,
+            user: calli,
+            text: "Replying to myself".to_owned(),
+            link: "https://twitter.com/moricalliope/status/2".to_owned(),
+            timestamp: Utc::now(),
+            media: Vec::new(),
+            translation: None,
+            replied_to: None,
+        };
 
-        if msg.attachments.is_empty() && msg.is_only_emojis() {
-            return false;
+        let reply = TweetReply::SameChannel(
+            "Calli".to_owned(),
+            MessageReference::from((ChannelId(7), MessageId(1))),
+        );
+
+        block_on(DiscordApi::post_tweet(
+            &sink,
+            &config,
+            ChannelId(7),
+            &tweet,
+            reply,
+            None,
+        ))
+        .unwrap();
+
+        match &sink.calls()[..] {
+            [SinkCall::SendAs {
+                channel,
+                identity,
+                message,
+            }] => {
+                assert_eq!(*channel, ChannelId(7));
+                assert_eq!(identity.name, "Calli");
+                assert_eq!(identity.avatar, "https://yt3.ggpht.com/calli.png");
+                assert!(!message.contains_key("message_reference"));
+                assert_eq!(
+                    message["embeds"][0]["fields"][0]["name"],
+                    json!("Replying to Calli")
+                );
+            }
+            calls => panic!("Unexpected calls: {calls:?}"),
         }
 
-        true
+        // Talents with a feed of their own keep posting as the bot.
+        config
+            .twitter
+            .feed_overrides
+            .insert("Kiara".to_owned(), ChannelId(8));
+
+        let sink = MockSink::default();
+
+        block_on(DiscordApi::post_tweet(
+            &sink,
+            &config,
+            ChannelId(7),
+            &tweet,
+            TweetReply::None,
+            None,
+        ))
+        .unwrap();
+
+        assert_eq!(sent_messages(&sink).len(), 1);
     }
 
-    #[instrument(skip(ctx))]
-    async fn claim_channel(
-        ctx: &Context,
-        category: &ChannelCategory,
-        stream: &Livestream,
-    ) -> anyhow::Result<ChannelId> {
-        let channel_name = format!(
-            "{}-{}-stream",
-            stream.streamer.emoji,
-            stream.streamer.name.to_ascii_lowercase().replace(' ', "-")
+    #[test]
+    fn tweet_video_is_linked_with_thumbnail() {
+        let sink = MockSink::default();
+        let tweet = HoloTweet {
+            id: 3,
+            user: talent("Calli", 1, None),
+            text: "New MV!".to_owned(),
+            link: "https://twitter.com/moricalliope/status/3".to_owned(),
+            timestamp: Utc::now(),
+            media: vec![TweetMedia::Video {
+                url: Some("https://video.twimg.com/mv.mp4".to_owned()),
+                thumbnail: Some("https://pbs.twimg.com/mv_thumb.jpg".to_owned()),
+            }],
+            translation: None,
+            replied_to: None,
+        };
+
+        block_on(DiscordApi::post_tweet(
+            &sink,
+            &Config::default(),
+            ChannelId(7),
+            &tweet,
+            TweetReply::None,
+            None,
+        ))
+        .unwrap();
+
+        let sent = sent_messages(&sink);
+        assert_eq!(
+            sent[0].1["content"],
+            json!("https://video.twimg.com/mv.mp4")
         );
-        let channel_topic = &stream.url;
-
-        let channel = category
-            .guild_id
-            .create_channel(&ctx.http, |c| {
-                c.name(channel_name)
-                    .category(category.id)
-                    .position(1)
-                    .topic(channel_topic)
-                    .permissions(category.permission_overwrites.clone())
-            })
-            .await
-            .context(here!())?;
+        assert_eq!(
+            sent[0].1["embeds"][0]["image"]["url"],
+            json!("https://pbs.twimg.com/mv_thumb.jpg")
+        );
+    }
 
-        channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    e.title("Now watching")
-                        .description(&stream.title)
-                        .url(&stream.url)
-                        .timestamp(stream.start_at)
-                        .colour(stream.streamer.colour)
-                        .image(&stream.thumbnail)
-                        .author(|a| {
-                            a.name(&stream.streamer.name)
-                                .url(format!(
-                                    "https://www.youtube.com/channel/{}",
-                                    stream.streamer.youtube_ch_id.as_ref().unwrap()
-                                ))
-                                .icon_url(&stream.streamer.icon)
-                        })
-                })
-            })
-            .await
-            .context(here!())?;
+    #[test]
+    fn tweet_through_mirror_drops_media() {
+        let sink = MockSink::default();
+        let fallback = TweetMediaFallback::default();
+        let tweet = HoloTweet {
+            id: 4,
+            user: talent("Calli", 1, None),
+            text: "Look at this!".to_owned(),
+            link: "https://twitter.com/moricalliope/status/4".to_owned(),
+            timestamp: Utc::now(),
+            media: vec![TweetMedia::Photo(
+                "https://pbs.twimg.com/media/expired.jpg".to_owned(),
+            )],
+            translation: None,
+            replied_to: None,
+        };
 
-        Ok(channel.id)
+        block_on(DiscordApi::post_tweet(
+            &sink,
+            &Config::default(),
+            ChannelId(7),
+            &tweet,
+            TweetReply::None,
+            Some(&fallback.mirror_link(&tweet.link)),
+        ))
+        .unwrap();
+
+        let sent = sent_messages(&sink);
+        assert_eq!(
+            sent[0].1["content"],
+            json!("https://fxtwitter.com/moricalliope/status/4")
+        );
+        assert!(sent[0].1["embeds"][0].get("image").is_none());
     }
-}
 
-#[derive(Debug)]
-pub enum DiscordMessageData {
-    Tweet(HoloTweet),
-    ScheduledLive(Livestream),
-    ScheduleUpdate(ScheduleUpdate),
-    Birthday(Birthday),
-}
+    #[test]
+    fn tweet_digest_lists_tweets_within_embed_limit() {
+        let tweets = (0..100)
+            .map(|id| HoloTweet {
+                id,
+                user: talent("Calli", 1, None),
+                text: format!("Tweet [number]\n{id} {}", "a".repeat(200)),
+                link: format!("https://twitter.com/moricalliope/status/{id}"),
+                timestamp: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+                media: Vec::new(),
+                translation: None,
+                replied_to: None,
+            })
+            .collect::<Vec<_>>();
 
-struct ArchivedMessage<'a> {
-    pub author: Mention,
-    pub content: String,
-    pub timestamp: Duration,
-    pub attachment_urls: Vec<String>,
-    pub video_id: Option<&'a VideoId>,
-}
+        let description = DiscordApi::tweet_digest_description(&tweets);
 
-impl ArchivedMessage<'_> {
-    pub fn format_timestamp(&self) -> String {
-        let hours = (self.timestamp.num_hours() != 0)
-            .then(|| format!("{:02}:", self.timestamp.num_hours().abs()))
-            .unwrap_or_default();
+        assert!(description.len() <= DiscordApi::EMBED_DESCRIPTION_LIMIT);
+        assert!(description.starts_with("**Calli** <t:1700000000:R>: [Tweet number 0 aaa"));
+        assert!(description.contains("…](https://twitter.com/moricalliope/status/0)\n"));
+        assert!(description.ends_with("more."));
+    }
 
-        let minutes = self.timestamp.num_minutes() % 60;
-        let seconds = self.timestamp.num_seconds() % 60;
+    #[test]
+    fn golden_tweet_embed() {
+        let sink = MockSink::default();
+        let tweet = HoloTweet {
+            id: 2,
+            user: golden_talent(),
+            text: "Come hang out later!".to_owned(),
+            link: "https://twitter.com/moricalliope/status/2".to_owned(),
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 1, 9, 30, 0).unwrap(),
+            media: vec![
+                TweetMedia::Photo("https://pbs.twimg.com/media/first.jpg".to_owned()),
+                TweetMedia::Photo("https://pbs.twimg.com/media/second.jpg".to_owned()),
+            ],
+            translation: Some("Come hang out later!".to_owned()),
+            replied_to: None,
+        };
 
-        // Check if message was sent before the stream started.
-        if self.timestamp.num_seconds() < 0 {
-            format!("-{}{:02}:{:02}", hours, minutes.abs(), seconds.abs())
-        } else {
-            let timestamp = format!("{}{:02}:{:02}", hours, minutes, seconds);
+        let reply = TweetReply::OtherChannel(
+            "Kiara".to_owned(),
+            "https://twitter.com/takanashikiara/status/1".to_owned(),
+        );
 
-            if let Some(id) = &self.video_id {
-                let url = format!(
-                    "https://youtu.be/{id}?t={secs}",
-                    id = id,
-                    secs = self.timestamp.num_seconds()
-                );
-                format!("[{time}]({url})", time = timestamp, url = url)
-            } else {
-                timestamp
-            }
-        }
+        block_on(DiscordApi::post_tweet(
+            &sink,
+            &Config::default(),
+            ChannelId(7),
+            &tweet,
+            reply,
+            None,
+        ))
+        .unwrap();
+        assert_golden_embeds(&sink, "tweet");
     }
-}
 
-impl std::fmt::Display for ArchivedMessage<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        writeln!(
-            f,
-            "{} {}: {}",
-            self.format_timestamp(),
-            self.author,
-            self.content
-        )?;
+    #[test]
+    fn golden_live_alert_embed() {
+        let sink = MockSink::default();
+        let config = config(vec![golden_talent()]);
+
+        block_on(DiscordApi::post_live_alert(
+            &sink,
+            &config,
+            &golden_stream(),
+            &[],
+        ))
+        .unwrap();
+        assert_golden_embeds(&sink, "live_alert");
+    }
 
-        if !self.attachment_urls.is_empty() {
-            writeln!(f, "{}", self.attachment_urls.join(" "))
-        } else {
-            Ok(())
-        }
+    #[test]
+    fn golden_birthday_embed() {
+        let sink = MockSink::default();
+        let config = config(vec![golden_talent()]);
+        let birthday = Birthday {
+            celebrant: Celebrant::Talent("Mori Calliope".to_owned()),
+            birthday: Utc.with_ymd_and_hms(2026, 4, 4, 0, 0, 0).unwrap(),
+        };
+
+        block_on(DiscordApi::post_birthday(&sink, &config, &birthday)).unwrap();
+        assert_golden_embeds(&sink, "birthday");
     }
-}
 
-enum TweetReply {
-    None,
-    SameChannel(String, MessageReference),
-    OtherChannel(String, String),
+    #[test]
+    fn golden_archive_embeds() {
+        let sink = MockSink::default();
+        let stream = golden_stream();
+
+        block_on(DiscordApi::announce_archival(
+            &sink,
+            &ThemeConfig::default(),
+            ChannelId(5),
+            Some(&stream),
+            Some(ChannelId(6)),
+        ))
+        .unwrap();
+        assert_golden_embeds(&sink, "archival_notice");
+
+        let sink = MockSink::default();
+
+        block_on(DiscordApi::pin_vod_summary(
+            &sink,
+            ChannelId(6),
+            &stream,
+            "https://discord.com/channels/1/4/3",
+        ))
+        .unwrap();
+        assert_golden_embeds(&sink, "vod_summary");
+    }
+
+    #[test]
+    fn first_message_id_is_at_the_start_of_the_millisecond() {
+        // The example snowflake from Discord's documentation, sent at 1462015105796.
+        let sent_at = Utc.timestamp_millis_opt(1_462_015_105_796).unwrap();
+        let id = DiscordApi::first_message_id_at(sent_at);
+
+        assert!(id <= MessageId(175_928_847_299_117_063));
+        assert_eq!(id.created_at().unix_timestamp(), sent_at.timestamp());
+        assert!(
+            DiscordApi::first_message_id_at(sent_at + Duration::milliseconds(1))
+                > MessageId(175_928_847_299_117_063)
+        );
+    }
 }
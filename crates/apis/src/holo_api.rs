@@ -11,24 +11,31 @@ use holodex::{
     model::{
         builders::VideoFilterBuilder,
         id::{ChannelId, VideoId},
-        ChannelMin, Order, Organisation, Video, VideoChannel, VideoFilter, VideoSortingCriteria,
-        VideoStatus,
+        ChannelMin, ExtraVideoInfo, Order, Organisation, Video, VideoChannel, VideoFilter,
+        VideoSortingCriteria, VideoStatus,
     },
     Client,
 };
+use once_cell::sync::Lazy;
 use tokio::{
     sync::{broadcast, mpsc, watch},
-    time::{self, MissedTickBehavior},
+    time,
 };
 use tokio_util::time::{delay_queue, DelayQueue};
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use utility::{
-    config::{Config, Database, DatabaseOperations, StreamTrackingConfig, Talent},
+    config::{
+        CancelledAlertAction, Config, Database, DatabaseOperations, StreamTrackingConfig, Talent,
+    },
     discord::NotifiedStreamsCache,
     functions::try_run,
     here,
-    streams::{Livestream, StreamUpdate},
+    rate_limiter::RateLimiter,
+    streams::{
+        IndexCommand, LatencyKind, Livestream, StreamState, StreamUpdate, StreamUpdateFanout,
+        STREAM_LATENCY,
+    },
     types::Service,
 };
 
@@ -36,6 +43,11 @@ use crate::discord_api::DiscordMessageData;
 
 type StreamIndex = HashMap<VideoId, (Option<delay_queue::Key>, Livestream)>;
 
+/// Shared by every Holodex request, so that paged lookups can't get the key
+/// throttled while the stream indexer is polling.
+static HOLODEX_RATE_LIMITER: Lazy<RateLimiter> =
+    Lazy::new(|| RateLimiter::new(HoloApi::REQUESTS_PER_MINUTE, Duration::from_secs(60)));
+
 #[derive(Debug, Clone)]
 pub(crate) enum VideoUpdate {
     Scheduled(VideoId),
@@ -50,6 +62,14 @@ pub(crate) enum VideoUpdate {
         id: VideoId,
         new_start: DateTime<Utc>,
     },
+    Retopiced {
+        id: VideoId,
+        topic: Option<String>,
+    },
+    Viewers {
+        id: VideoId,
+        count: u32,
+    },
 }
 
 pub struct HoloApi;
@@ -58,25 +78,43 @@ impl HoloApi {
     const INITIAL_STREAM_FETCH_COUNT: u32 = 100;
     const NEW_STREAM_FETCH_COUNT: u32 = 100;
     const UPDATE_INTERVAL: Duration = Duration::from_secs(60);
-
-    #[instrument(skip(config, live_sender, stream_updates))]
+    /// Used while a stream is about to start or should have been detected as live,
+    /// each poll costs two requests, so this stays well within the rate limit.
+    const FAST_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+    /// How long before its scheduled start a stream makes polling speed up.
+    const FAST_UPDATE_LEAD_TIME: chrono::Duration = chrono::Duration::minutes(2);
+    /// Bounds for how long after its scheduled start a stream keeps polling fast,
+    /// the actual window follows the measured live detection latency.
+    const MIN_DETECTION_WINDOW: chrono::Duration = chrono::Duration::minutes(5);
+    const MAX_DETECTION_WINDOW: chrono::Duration = chrono::Duration::minutes(30);
+    /// How soon after going live a stream without a recording can disappear
+    /// and still be treated as cancelled rather than ended.
+    const CANCELLED_WINDOW: chrono::Duration = chrono::Duration::minutes(30);
+    const REQUESTS_PER_MINUTE: u32 = 30;
+
+    #[instrument(skip(config, live_sender, stream_updates, talents))]
     pub async fn start(
         config: Arc<Config>,
         live_sender: mpsc::Sender<DiscordMessageData>,
-        stream_updates: broadcast::Sender<StreamUpdate>,
+        stream_updates: StreamUpdateFanout,
         mut service_restarter: broadcast::Receiver<Service>,
+        mut talents: watch::Receiver<Vec<Talent>>,
+        mut index_commands: mpsc::Receiver<IndexCommand>,
     ) -> watch::Receiver<HashMap<VideoId, Livestream>> {
         let (index_sender, index_receiver) = watch::channel(HashMap::new());
 
         tokio::spawn(async move {
             loop {
+                let current_talents = talents.borrow_and_update().clone();
+
                 let indexer = Self::stream_producer(
                     &config.stream_tracking,
                     &config.database,
-                    &config.talents,
+                    &current_talents,
                     &live_sender,
                     &index_sender,
                     &stream_updates,
+                    &mut index_commands,
                 );
 
                 info!("Stream indexer starting!");
@@ -92,6 +130,11 @@ impl HoloApi {
                     }
 
                     Ok(Service::StreamIndexer) = service_restarter.recv() => { }
+
+                    Ok(()) = talents.changed() => {
+                        info!("Talent roster changed, restarting stream indexer.");
+                        continue;
+                    }
                 }
 
                 info!("Stream indexer is restarting in 10 seconds...");
@@ -104,14 +147,23 @@ impl HoloApi {
         index_receiver
     }
 
-    #[instrument(skip(config, database, talents, live_sender, index_sender, stream_updates))]
+    #[instrument(skip(
+        config,
+        database,
+        talents,
+        live_sender,
+        index_sender,
+        stream_updates,
+        index_commands
+    ))]
     async fn stream_producer(
         config: &StreamTrackingConfig,
         database: &Database,
         talents: &[Talent],
         live_sender: &mpsc::Sender<DiscordMessageData>,
         index_sender: &watch::Sender<HashMap<VideoId, Livestream>>,
-        stream_updates: &broadcast::Sender<StreamUpdate>,
+        stream_updates: &StreamUpdateFanout,
+        index_commands: &mut mpsc::Receiver<IndexCommand>,
     ) -> anyhow::Result<()> {
         let client = Client::new(&config.holodex_token)?;
 
@@ -126,6 +178,7 @@ impl HoloApi {
             .order(Order::Ascending)
             .after(Utc::now())
             .limit(Self::NEW_STREAM_FETCH_COUNT)
+            .include(&[ExtraVideoInfo::Mentions])
             .build();
 
         let mut notified_streams = NotifiedStreamsCache::new(128.try_into().unwrap());
@@ -158,10 +211,13 @@ impl HoloApi {
 
         // Start by fetching the latest N streams.
         {
+            HOLODEX_RATE_LIMITER.acquire().await;
+
             let streams = client
                 .videos(
                     &VideoFilterBuilder::new()
                         .limit(Self::INITIAL_STREAM_FETCH_COUNT)
+                        .include(&[ExtraVideoInfo::Mentions])
                         .status(&[
                             VideoStatus::New,
                             VideoStatus::Upcoming,
@@ -202,22 +258,15 @@ impl HoloApi {
                 stream_index.insert(id, (Some(key), stream));
             }
 
-            trace!("Starting stream index update!");
-            let index = stream_index
-                .clone()
-                .into_iter()
-                .map(|(id, (_, s))| (id, s))
-                .collect();
-            index_sender.send(index).context(here!())?;
-            debug!(size = %stream_index.len(), "Stream index updated!");
+            Self::send_index(&stream_index, index_sender)?;
         }
 
-        let mut update_interval = time::interval(Self::UPDATE_INTERVAL);
-        update_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let poll_timer = time::sleep(Duration::ZERO);
+        tokio::pin!(poll_timer);
 
         // Wait for receiving end of the channel to be established.
-        if config.chat.enabled {
-            while stream_updates.receiver_count() == 0 {
+        if config.broadcasts_updates() {
+            while stream_updates.subscriber_count() == 0 {
                 tokio::time::sleep(Duration::from_secs(1)).await;
             }
         }
@@ -247,10 +296,8 @@ impl HoloApi {
                     if !notified_streams.contains(&live_id) {
                         notified_streams.put(live_id, ());
 
-                        if config.chat.enabled {
-                            if let Err(e) = stream_updates.send(StreamUpdate::Started((*stream).clone())) {
-                                error!("{:#}", e);
-                            };
+                        if config.broadcasts_updates() {
+                            stream_updates.send(StreamUpdate::Started((*stream).clone()));
                         }
 
                         live_sender
@@ -262,27 +309,71 @@ impl HoloApi {
                 }
 
                 // Poll Holodex API
-                _ = update_interval.tick() => {
+                _ = &mut poll_timer => {
+                    let polled_at = Utc::now();
+
                     let updates = Self::poll_holodex(&client, &filter, &mut stream_index, &mut stream_queue, &user_map)
                         .await
                         .context(here!())?;
 
-                    if config.chat.enabled && !updates.is_empty() {
+                    for cancelled in Self::cancelled_alerts(config, &updates) {
+                        live_sender.send(cancelled).await.context(here!())?;
+                    }
+
+                    // The index goes out first, so that ended streams can be looked up with their final viewer counts.
+                    if config.broadcasts_updates() && !updates.is_empty() {
+                        Self::send_index(&stream_index, index_sender)?;
+
                         for update in updates {
-                            stream_updates.send(update).context(here!())?;
+                            stream_updates.send(update);
                         }
+                    }
 
-                        trace!("Starting stream index update!");
-                        let index = stream_index
-                            .clone()
-                            .into_iter()
-                            .map(|(id, (_, s))| (id, s))
-                            .collect();
-                        index_sender.send(index).context(here!())?;
-                        debug!(size = %stream_index.len(), "Stream index updated!");
+                    if config.alerts.live_viewers {
+                        for (_, stream) in stream_index.values() {
+                            match stream.viewers.latest() {
+                                Some((at, viewers)) if at >= polled_at => {
+                                    live_sender
+                                        .send(DiscordMessageData::LiveViewers(stream.id.clone(), viewers))
+                                        .await
+                                        .context(here!())?;
+                                }
+                                _ => (),
+                            }
+                        }
                     }
 
                     filter.after = Some(Utc::now());
+
+                    let interval = Self::poll_interval(&stream_index);
+                    trace!(?interval, "Next Holodex poll scheduled.");
+                    poll_timer.as_mut().reset(time::Instant::now() + interval);
+                }
+
+                Some(command) = index_commands.recv() => {
+                    match command {
+                        IndexCommand::Refresh => {
+                            info!("Manual stream index refresh requested.");
+                            poll_timer.as_mut().reset(time::Instant::now());
+                        }
+                        IndexCommand::SetState(id, state) => {
+                            let update = match Self::force_state(&mut stream_index, &mut stream_queue, &id, state) {
+                                Some(update) => update,
+                                None => {
+                                    warn!(%id, "Entry not found in index!");
+                                    continue;
+                                }
+                            };
+
+                            info!(%id, ?state, "Stream state forced!");
+
+                            if config.broadcasts_updates() {
+                                stream_updates.send(update);
+                            }
+
+                            Self::send_index(&stream_index, index_sender)?;
+                        }
+                    }
                 }
 
                 res = tokio::signal::ctrl_c() => {
@@ -317,10 +408,62 @@ impl HoloApi {
         stream_queue: &mut DelayQueue<VideoId>,
         user_map: &HashMap<ChannelId, Talent>,
     ) -> anyhow::Result<Vec<StreamUpdate>> {
+        // Fetch updates for the streams that are currently live or scheduled.
+        let video_updates = Self::get_stream_updates(client, stream_index).await?;
+        let mut updates = Self::apply_updates(video_updates, stream_index, stream_queue);
+
+        let new_streams: Vec<_> = try_run(|| async {
+            HOLODEX_RATE_LIMITER.acquire().await;
+
+            client
+                .video_stream(filter)
+                .try_filter(|v| ready(!stream_index.contains_key(&v.id)))
+                .try_filter_map(|v| ready(Ok(Self::process_stream(v, user_map))))
+                .try_collect()
+                .await
+                .map_err(|e| e.into())
+        })
+        .await?;
+
+        let now = Utc::now();
+
+        // Fetch new streams since last update.
+        for (id, stream) in new_streams.into_iter().map(|v| (v.id.clone(), v)) {
+            info!(name = %stream.title, from = %stream.streamer.name, "New stream added to index!");
+            updates.push(StreamUpdate::Scheduled(stream.clone()));
+
+            match &stream.state {
+                VideoStatus::Upcoming if stream.start_at > now => {
+                    // Unwrap is fine because we just checked that the start time is in the future.
+                    let key =
+                        stream_queue.insert(id.clone(), (stream.start_at - now).to_std().unwrap());
+                    stream_index.insert(id, (Some(key), stream));
+                }
+                VideoStatus::Upcoming => {
+                    warn!(
+                        ?stream,
+                        "Upcoming stream has a start time that has already passed!"
+                    );
+                    stream_index.insert(id, (None, stream));
+                }
+                _ => {
+                    stream_index.insert(id, (None, stream));
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Applies the updates from Holodex to the index, returning the changes to broadcast.
+    pub(crate) fn apply_updates(
+        video_updates: Vec<VideoUpdate>,
+        stream_index: &mut StreamIndex,
+        stream_queue: &mut DelayQueue<VideoId>,
+    ) -> Vec<StreamUpdate> {
         let mut updates = Vec::new();
 
-        // Fetch updates for the streams that are currently live or scheduled.
-        for update in Self::get_stream_updates(client, stream_index).await? {
+        for update in video_updates {
             trace!(?update, "Stream update received!");
 
             match update {
@@ -359,8 +502,9 @@ impl HoloApi {
                 }
                 VideoUpdate::Unscheduled(id) => {
                     if let Some((opt_key, entry)) = stream_index.remove(&id) {
+                        info!(title = %entry.title, "Unscheduled video!");
+
                         if let Some(key) = opt_key {
-                            info!(title = %entry.title, "Unscheduled video!");
                             stream_queue.remove(&key);
                         }
 
@@ -370,13 +514,23 @@ impl HoloApi {
                 VideoUpdate::Renamed { id, new_name } => {
                     if let Some((_, entry)) = stream_index.get_mut(&id) {
                         info!(%new_name, "Renaming video!");
-                        entry.title = new_name.clone();
+                        entry.set_title(new_name.clone());
 
                         updates.push(StreamUpdate::Renamed(id, new_name));
                     } else {
                         warn!(?id, name = ?new_name, "Entry not found in index!");
                     }
                 }
+                VideoUpdate::Retopiced { id, topic } => {
+                    if let Some((_, entry)) = stream_index.get_mut(&id) {
+                        entry.set_topic(topic);
+                    }
+                }
+                VideoUpdate::Viewers { id, count } => {
+                    if let Some((_, entry)) = stream_index.get_mut(&id) {
+                        entry.viewers.record(Utc::now(), count);
+                    }
+                }
                 VideoUpdate::Rescheduled { id, new_start } => {
                     if let Some((opt_key, entry)) = stream_index.get_mut(&id) {
                         entry.start_at = new_start;
@@ -395,45 +549,57 @@ impl HoloApi {
             }
         }
 
-        let new_streams: Vec<_> = try_run(|| async {
-            client
-                .video_stream(filter)
-                .try_filter(|v| ready(!stream_index.contains_key(&v.id)))
-                .try_filter_map(|v| ready(Ok(Self::process_stream(v, user_map))))
-                .try_collect()
-                .await
-                .map_err(|e| e.into())
-        })
-        .await?;
+        updates
+    }
 
-        let now = Utc::now();
+    /// The alerts to take down for the streams that were cancelled, unless they're kept.
+    pub(crate) fn cancelled_alerts(
+        config: &StreamTrackingConfig,
+        updates: &[StreamUpdate],
+    ) -> Vec<DiscordMessageData> {
+        if config.alerts.on_cancelled == CancelledAlertAction::Keep {
+            return Vec::new();
+        }
 
-        // Fetch new streams since last update.
-        for (id, stream) in new_streams.into_iter().map(|v| (v.id.clone(), v)) {
-            info!(name = %stream.title, from = %stream.streamer.name, "New stream added to index!");
-            updates.push(StreamUpdate::Scheduled(stream.clone()));
+        updates
+            .iter()
+            .filter_map(|u| match u {
+                StreamUpdate::Unscheduled(id) => Some(DiscordMessageData::Cancelled(id.clone())),
+                _ => None,
+            })
+            .collect()
+    }
 
-            match &stream.state {
-                VideoStatus::Upcoming if stream.start_at > now => {
-                    // Unwrap is fine because we just checked that the start time is in the future.
-                    let key =
-                        stream_queue.insert(id.clone(), (stream.start_at - now).to_std().unwrap());
-                    stream_index.insert(id, (Some(key), stream));
-                }
-                VideoStatus::Upcoming => {
-                    warn!(
-                        ?stream,
-                        "Upcoming stream has a start time that has already passed!"
-                    );
-                    stream_index.insert(id, (None, stream));
-                }
-                _ => {
-                    stream_index.insert(id, (None, stream));
-                }
-            }
+    /// Fetches the streams that went live since the given time and are still live,
+    /// for catching up on alerts after downtime.
+    #[instrument(skip(config, talents))]
+    pub async fn get_missed_streams(
+        config: &Config,
+        talents: &[Talent],
+        since: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Livestream>> {
+        let user_map = talents
+            .iter()
+            .filter_map(|u| u.youtube_ch_id.as_ref().map(|id| (id.clone(), u.clone())))
+            .collect::<HashMap<_, _>>();
+
+        let mut pages =
+            VideoPages::hololive(&config.stream_tracking.holodex_token, VideoStatus::Live)?
+                .after(since)
+                .page_size(Self::NEW_STREAM_FETCH_COUNT);
+
+        let mut streams = Vec::new();
+
+        while let Some(page) = pages.next_page().await.context(here!())? {
+            streams.extend(
+                page.into_iter()
+                    .filter_map(|v| Self::process_stream(v, &user_map))
+                    .filter(|s| s.state == VideoStatus::Live && s.start_at >= since),
+            );
         }
 
-        Ok(updates)
+        streams.sort_unstable_by_key(|s| s.start_at);
+        Ok(streams)
     }
 
     #[instrument(skip(video, users))]
@@ -444,9 +610,96 @@ impl HoloApi {
             }
         }
 
-        users
-            .get(video.channel.id())
-            .map(|talent| Livestream::from_video_and_talent(video, talent))
+        let talent = users.get(video.channel.id())?;
+
+        // Mentioned channels that we track are treated as collab participants.
+        let collaborators = video
+            .mentions
+            .iter()
+            .filter_map(|c| users.get(c.id()))
+            .filter(|t| *t != talent)
+            .cloned()
+            .collect();
+
+        Some(Livestream {
+            collaborators,
+            ..Livestream::from_video_and_talent(video, talent)
+        })
+    }
+
+    fn send_index(
+        stream_index: &StreamIndex,
+        index_sender: &watch::Sender<HashMap<VideoId, Livestream>>,
+    ) -> anyhow::Result<()> {
+        trace!("Starting stream index update!");
+        let index = stream_index
+            .clone()
+            .into_iter()
+            .map(|(id, (_, s))| (id, s))
+            .collect();
+        index_sender.send(index).context(here!())?;
+        debug!(size = %stream_index.len(), "Stream index updated!");
+
+        Ok(())
+    }
+
+    /// Moves a stream into a state by hand, returning the update to broadcast.
+    /// Forcing a stream live claims a chat for it, but doesn't post an alert.
+    fn force_state(
+        stream_index: &mut StreamIndex,
+        stream_queue: &mut DelayQueue<VideoId>,
+        id: &VideoId,
+        state: StreamState,
+    ) -> Option<StreamUpdate> {
+        let (key, stream) = stream_index.get_mut(id)?;
+
+        if let Some(key) = key.take() {
+            stream_queue.remove(&key);
+        }
+
+        match state {
+            StreamState::Scheduled => {
+                stream.state = VideoStatus::Upcoming;
+
+                if let Ok(remind_in) = (stream.start_at - Utc::now()).to_std() {
+                    *key = Some(stream_queue.insert(id.clone(), remind_in));
+                }
+
+                Some(StreamUpdate::Scheduled(stream.clone()))
+            }
+            StreamState::Live => {
+                stream.state = VideoStatus::Live;
+                Some(StreamUpdate::Started(stream.clone()))
+            }
+            StreamState::Ended => {
+                stream.state = VideoStatus::Past;
+                Some(StreamUpdate::Ended(id.clone()))
+            }
+        }
+    }
+
+    /// Polls faster while any stream is about to start or is late to be detected as live,
+    /// with the window after the scheduled start tuned from the measured detection latency.
+    fn poll_interval(stream_index: &StreamIndex) -> Duration {
+        let detection_window = STREAM_LATENCY
+            .summary(LatencyKind::Detection)
+            .map_or(Self::MIN_DETECTION_WINDOW, |s| s.p90 * 2)
+            .clamp(Self::MIN_DETECTION_WINDOW, Self::MAX_DETECTION_WINDOW);
+
+        let now = Utc::now();
+
+        let stream_starting = stream_index.values().any(|(_, s)| {
+            matches!(s.state, VideoStatus::Upcoming | VideoStatus::Live)
+                && s.start_at - Self::FAST_UPDATE_LEAD_TIME <= now
+                && now <= s.start_at + detection_window
+                && !STREAM_LATENCY.contains(LatencyKind::Detection, &s.id)
+        });
+
+        if stream_starting {
+            Self::FAST_UPDATE_INTERVAL
+        } else {
+            Self::UPDATE_INTERVAL
+        }
     }
 
     fn get_duration_until_stream(stream: &Livestream) -> Option<std::time::Duration> {
@@ -506,6 +759,8 @@ impl HoloApi {
             ])
             .build();
 
+        HOLODEX_RATE_LIMITER.acquire().await;
+
         let streams = client.video_stream(&filter);
         futures::pin_mut!(streams);
 
@@ -524,6 +779,26 @@ impl HoloApi {
                 }
             };
 
+            // Streams are usually set live by the queue before Holodex confirms it,
+            // so the detection latency is measured on the first poll that sees it live.
+            // Streams that went live long ago were live before a restart, and would skew it.
+            if let (VideoStatus::Live, Some(started_at)) =
+                (stream.status, stream.live_info.start_actual)
+            {
+                if now - started_at < Self::MAX_DETECTION_WINDOW {
+                    STREAM_LATENCY.record(LatencyKind::Detection, &entry.id, started_at, now);
+                }
+            }
+
+            if let Some(count) = stream.live_info.live_viewers {
+                if stream.status == VideoStatus::Live {
+                    updates.push(VideoUpdate::Viewers {
+                        id: entry.id.clone(),
+                        count,
+                    });
+                }
+            }
+
             if entry.title != stream.title && !stream.title.is_empty() {
                 info!(before = %entry.title, after = %stream.title, "Video renamed!");
                 updates.push(VideoUpdate::Renamed {
@@ -532,6 +807,16 @@ impl HoloApi {
                 });
             }
 
+            // Member-only streams are often only tagged shortly before they start,
+            // so this has to be up to date by the time the alert is sent.
+            if entry.topic != stream.topic {
+                debug!(before = ?entry.topic, after = ?stream.topic, video = %stream.title, "Video topic changed!");
+                updates.push(VideoUpdate::Retopiced {
+                    id: entry.id.clone(),
+                    topic: stream.topic.clone(),
+                });
+            }
+
             if entry.state != VideoStatus::Past
                 && entry.start_at
                     != stream
@@ -555,38 +840,202 @@ impl HoloApi {
                 });
             }
 
-            updates.push(match (entry.state, stream.status) {
-                (VideoStatus::Missing | VideoStatus::New, VideoStatus::Upcoming) => {
-                    debug!(video = %stream.title, "Video scheduled!");
-                    VideoUpdate::Scheduled(entry.id.clone())
-                }
-                (VideoStatus::Upcoming | VideoStatus::Missing, VideoStatus::Live) => {
-                    debug!(video = %stream.title, "Video started!");
-                    VideoUpdate::Started(entry.id.clone())
-                }
-                (VideoStatus::Live, VideoStatus::Past | VideoStatus::Missing) => {
-                    info!(video = %stream.title, "Video ended!");
-                    VideoUpdate::Ended(entry.id.clone())
-                }
-                (VideoStatus::Upcoming, VideoStatus::Missing) => {
-                    info!(video = %stream.title, "Video unscheduled!");
-                    VideoUpdate::Unscheduled(entry.id.clone())
-                }
-                // Compensate for cache delay in Holodex.
-                (VideoStatus::Live, VideoStatus::Upcoming)
-                    if (now - stream.available_at) < chrono::Duration::minutes(5) =>
-                {
-                    continue
-                }
-                _ if entry.state != stream.status => {
-                    warn!(before = ?entry.state, after = ?stream.status,
-                        video = %stream.title, "Unknown status transition!");
-                    continue;
-                }
-                _ => continue,
-            });
+            let update = Self::status_transition(
+                entry,
+                stream.status,
+                stream.live_info.start_actual,
+                stream.duration.filter(|d| !d.is_zero()),
+                stream.available_at,
+                now,
+            );
+
+            updates.extend(update);
         }
 
         Ok(updates)
     }
+
+    /// The update for a change in the status of a stream, as reported by Holodex.
+    /// A stream that disappears after going live is only cancelled if it never
+    /// actually started, or disappeared shortly after starting without a recording.
+    pub(crate) fn status_transition(
+        entry: &Livestream,
+        status: VideoStatus,
+        started_at: Option<DateTime<Utc>>,
+        duration: Option<chrono::Duration>,
+        available_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<VideoUpdate> {
+        let update = match (entry.state, status) {
+            (VideoStatus::Missing | VideoStatus::New, VideoStatus::Upcoming) => {
+                debug!(video = %entry.title, "Video scheduled!");
+                VideoUpdate::Scheduled(entry.id.clone())
+            }
+            (VideoStatus::Upcoming | VideoStatus::Missing, VideoStatus::Live) => {
+                debug!(video = %entry.title, "Video started!");
+                VideoUpdate::Started(entry.id.clone())
+            }
+            (VideoStatus::Live, VideoStatus::Missing)
+                if duration.is_none()
+                    && started_at.map_or(true, |start| now - start < Self::CANCELLED_WINDOW) =>
+            {
+                info!(video = %entry.title, "Video cancelled after going live!");
+                VideoUpdate::Unscheduled(entry.id.clone())
+            }
+            (VideoStatus::Live, VideoStatus::Past | VideoStatus::Missing) => {
+                info!(video = %entry.title, "Video ended!");
+                VideoUpdate::Ended(entry.id.clone())
+            }
+            (VideoStatus::Upcoming, VideoStatus::Missing) => {
+                info!(video = %entry.title, "Video unscheduled!");
+                VideoUpdate::Unscheduled(entry.id.clone())
+            }
+            // Compensate for cache delay in Holodex.
+            (VideoStatus::Live, VideoStatus::Upcoming)
+                if (now - available_at) < chrono::Duration::minutes(5) =>
+            {
+                return None
+            }
+            _ if entry.state != status => {
+                warn!(before = ?entry.state, after = ?status,
+                    video = %entry.title, "Unknown status transition!");
+                return None;
+            }
+            _ => return None,
+        };
+
+        Some(update)
+    }
+}
+
+/// Pages through the videos on Holodex, taking the shared rate limiter and retrying
+/// with backoff for every page, so that long lookups don't get the key throttled.
+pub struct VideoPages {
+    client: Client,
+    channel: Option<ChannelId>,
+    status: VideoStatus,
+    newest_first: bool,
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+    page_size: u32,
+    offset: u32,
+    exhausted: bool,
+}
+
+impl VideoPages {
+    const MAX_PAGE_SIZE: u32 = 50;
+
+    /// The past videos of a channel, newest first.
+    pub fn channel_history(token: &str, channel: ChannelId) -> anyhow::Result<Self> {
+        Ok(Self {
+            channel: Some(channel),
+            ..Self::new(token, VideoStatus::Past, true)?
+        })
+    }
+
+    /// The videos of every Hololive channel with the status, oldest first.
+    pub fn hololive(token: &str, status: VideoStatus) -> anyhow::Result<Self> {
+        Self::new(token, status, false)
+    }
+
+    fn new(token: &str, status: VideoStatus, newest_first: bool) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: Client::new(token)?,
+            channel: None,
+            status,
+            newest_first,
+            after: None,
+            before: None,
+            page_size: Self::MAX_PAGE_SIZE,
+            offset: 0,
+            exhausted: false,
+        })
+    }
+
+    /// Only include videos that became available after the given time.
+    #[must_use]
+    pub fn after(mut self, after: DateTime<Utc>) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    /// Only include videos that became available before the given time.
+    #[must_use]
+    pub fn before(mut self, before: DateTime<Utc>) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    #[must_use]
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.clamp(1, Self::MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Fetches the next page of videos, or `None` once every video has been fetched.
+    #[instrument(skip(self), fields(channel = ?self.channel, offset = self.offset))]
+    pub async fn next_page(&mut self) -> anyhow::Result<Option<Vec<Video>>> {
+        if self.exhausted {
+            return Ok(None);
+        }
+
+        let mut builder = VideoFilterBuilder::new()
+            .status(&[self.status])
+            .sort_by(VideoSortingCriteria::AvailableAt)
+            .order(if self.newest_first {
+                Order::Descending
+            } else {
+                Order::Ascending
+            })
+            .include(&[ExtraVideoInfo::Mentions])
+            .offset(self.offset)
+            .limit(self.page_size);
+
+        builder = match &self.channel {
+            Some(channel) => builder.channel_id(channel.clone()),
+            None => builder.organisation(Organisation::Hololive),
+        };
+
+        if let Some(after) = self.after {
+            builder = builder.after(after);
+        }
+
+        if let Some(before) = self.before {
+            builder = builder.before(before);
+        }
+
+        let filter = builder.build();
+
+        let videos: Vec<Video> = try_run(|| async {
+            HOLODEX_RATE_LIMITER.acquire().await;
+
+            Ok(self.client.videos(&filter)?.into_iter().collect())
+        })
+        .await?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let fetched = videos.len() as u32;
+
+        self.offset += fetched;
+        self.exhausted = fetched < self.page_size;
+
+        Ok((!videos.is_empty()).then_some(videos))
+    }
+
+    /// Collects every video, up to the first one that became available before the given time.
+    /// Only stops early for pages that are newest first.
+    pub async fn collect_since(mut self, since: DateTime<Utc>) -> anyhow::Result<Vec<Video>> {
+        let mut videos = Vec::new();
+
+        while let Some(page) = self.next_page().await? {
+            let reached_end = self.newest_first && page.iter().any(|v| v.available_at < since);
+            videos.extend(page.into_iter().filter(|v| v.available_at >= since));
+
+            if reached_end {
+                break;
+            }
+        }
+
+        Ok(videos)
+    }
 }
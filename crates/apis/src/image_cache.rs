@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use tracing::{debug, info, instrument, warn};
+use utility::{
+    config::{Config, DatabaseHandle, ImageCacheConfig},
+    here,
+};
+
+use crate::object_storage::ObjectStorage;
+
+static IMAGE_CACHE: OnceCell<ImageCache> = OnceCell::new();
+
+/// Sets up the image cache if it's enabled, which needs the object storage to be set up as well.
+pub fn init(config: &Config) -> anyhow::Result<()> {
+    if !config.image_cache.enabled {
+        return Ok(());
+    }
+
+    if !config.object_storage.enabled {
+        bail!("The image cache needs object storage to be enabled!");
+    }
+
+    let storage = ObjectStorage::new(&config.object_storage).context(here!())?;
+    let handle = config.database.get_handle().context(here!())?;
+
+    IMAGE_CACHE
+        .set(ImageCache::load(config.image_cache.clone(), storage, handle).context(here!())?)
+        .map_err(|_| anyhow!("Image cache has already been initialized!"))
+}
+
+/// The URL an embed should use for the image, which is its mirror if the original is no longer
+/// available. Returns the original if the image cache isn't enabled.
+pub async fn resolve(url: &str) -> String {
+    match IMAGE_CACHE.get() {
+        Some(cache) if !url.is_empty() => cache.resolve(url).await,
+        _ => url.to_owned(),
+    }
+}
+
+/// Counts how often images are used, mirroring the frequently used ones to the object storage
+/// while they're still available, so that they can be swapped in once they aren't.
+pub struct ImageCache {
+    config: ImageCacheConfig,
+    storage: ObjectStorage,
+    database: Mutex<DatabaseHandle>,
+    images: Mutex<HashMap<String, CachedImage>>,
+}
+
+#[derive(Debug, Default)]
+struct CachedImage {
+    uses: u32,
+    mirror: Option<String>,
+    /// When the original was last checked, and whether it was available.
+    checked: Option<(DateTime<Utc>, bool)>,
+    mirroring: bool,
+}
+
+impl ImageCache {
+    const TABLE_NAME: &'static str = "ImageMirrors";
+    /// Icons and thumbnails are well below this, anything larger isn't worth mirroring.
+    const MAX_IMAGE_SIZE: u64 = 10 * 1024 * 1024;
+
+    fn load(
+        config: ImageCacheConfig,
+        storage: ObjectStorage,
+        handle: DatabaseHandle,
+    ) -> anyhow::Result<Self> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("url", "TEXT", Some("PRIMARY KEY")),
+                    ("mirror", "TEXT", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        let mut images = HashMap::new();
+
+        match &handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!("SELECT url, mirror FROM {}", Self::TABLE_NAME))
+                    .context(here!())?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })
+                    .context(here!())?;
+
+                for row in rows {
+                    let (url, mirror) = row.context(here!())?;
+
+                    images.insert(
+                        url,
+                        CachedImage {
+                            mirror: Some(mirror),
+                            ..CachedImage::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        debug!(mirrored = images.len(), "Loaded image mirrors.");
+
+        Ok(Self {
+            config,
+            storage,
+            database: Mutex::new(handle),
+            images: Mutex::new(images),
+        })
+    }
+
+    async fn resolve(&'static self, url: &str) -> String {
+        let now = Utc::now();
+
+        let checked = {
+            let mut images = self.images();
+            let image = images.entry(url.to_owned()).or_default();
+            image.uses = image.uses.saturating_add(1);
+
+            let checked = image
+                .checked
+                .filter(|(at, _)| now - *at < self.config.check_interval)
+                .map(|(_, available)| available);
+
+            // Marked as checked before the request is sent, so that the embeds that are
+            // resolved while it's in flight reuse the previous result instead of sending
+            // their own requests. Until the first check is done, the original is assumed to work.
+            if checked.is_none() {
+                let previous = image.checked.map_or(true, |(_, available)| available);
+                image.checked = Some((now, previous));
+            }
+
+            checked
+        };
+
+        let available = match checked {
+            Some(available) => available,
+            None => {
+                let available = utility::http::client()
+                    .call("HEAD", url, ureq::Request::call)
+                    .await
+                    .is_ok();
+
+                if !available {
+                    warn!(%url, "Image is unavailable.");
+                }
+
+                if let Some(image) = self.images().get_mut(url) {
+                    image.checked = Some((now, available));
+                }
+
+                available
+            }
+        };
+
+        let mut images = self.images();
+
+        let image = match images.get_mut(url) {
+            Some(image) => image,
+            None => return url.to_owned(),
+        };
+
+        if !available {
+            return image.mirror.clone().unwrap_or_else(|| url.to_owned());
+        }
+
+        if image.mirror.is_none() && !image.mirroring && image.uses >= self.config.min_uses {
+            image.mirroring = true;
+
+            let url = url.to_owned();
+
+            tokio::spawn(async move {
+                let mirror = match self.mirror(&url).await {
+                    Ok(mirror) => Some(mirror),
+                    Err(e) => {
+                        warn!(%url, "Failed to mirror image: {:?}", e);
+                        None
+                    }
+                };
+
+                if let Some(image) = self.images().get_mut(&url) {
+                    image.mirroring = false;
+                    image.mirror = mirror;
+                }
+            });
+        }
+
+        url.to_owned()
+    }
+
+    #[instrument(skip(self))]
+    async fn mirror(&self, url: &str) -> anyhow::Result<String> {
+        let response = utility::http::client()
+            .call("GET", url, ureq::Request::call)
+            .await?;
+
+        let content_type = response.content_type().to_owned();
+
+        if !content_type.starts_with("image/") {
+            bail!("{url} is not an image, but {content_type}.");
+        }
+
+        let length = response
+            .header("Content-Length")
+            .and_then(|l| l.parse::<u64>().ok());
+
+        if length.map_or(false, |l| l > Self::MAX_IMAGE_SIZE) {
+            bail!("{url} is too large to mirror.");
+        }
+
+        // One byte more than allowed is read, to tell images that are too large apart
+        // from ones that are exactly the maximum size.
+        let data = tokio::task::spawn_blocking(move || {
+            let mut data = Vec::new();
+
+            response
+                .into_reader()
+                .take(Self::MAX_IMAGE_SIZE + 1)
+                .read_to_end(&mut data)
+                .map(|_| data)
+        })
+        .await
+        .context(here!())?
+        .context(here!())?;
+
+        if data.len() as u64 > Self::MAX_IMAGE_SIZE {
+            bail!("{url} is too large to mirror.");
+        }
+
+        let mirror = self
+            .storage
+            .upload(&mirror_key(url, &content_type), &data, &content_type)
+            .await?;
+
+        let handle = self
+            .database
+            .lock()
+            .map_err(|_| anyhow!("Image cache database lock was poisoned!"))?;
+
+        match &*handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO {} (url, mirror) VALUES (?, ?)",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![url, mirror],
+                )
+                .context(here!())?;
+            }
+        }
+
+        info!(%url, %mirror, "Mirrored image.");
+        Ok(mirror)
+    }
+
+    fn images(&self) -> MutexGuard<'_, HashMap<String, CachedImage>> {
+        self.images.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Where the image is stored, which has to stay the same between restarts
+/// so that mirroring an image again overwrites the previous copy.
+fn mirror_key(url: &str, content_type: &str) -> String {
+    // FNV-1a, since the hasher in the standard library may change between releases.
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    let extension = match content_type {
+        "image/png" => ".png",
+        "image/jpeg" => ".jpg",
+        "image/gif" => ".gif",
+        "image/webp" => ".webp",
+        _ => "",
+    };
+
+    format!("images/{hash:016x}{extension}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirror_keys_are_stable() {
+        assert_eq!(
+            mirror_key("https://yt3.ggpht.com/calli.png", "image/png"),
+            mirror_key("https://yt3.ggpht.com/calli.png", "image/png")
+        );
+        assert_ne!(
+            mirror_key("https://yt3.ggpht.com/calli.png", "image/png"),
+            mirror_key("https://yt3.ggpht.com/kiara.png", "image/png")
+        );
+
+        let key = mirror_key(
+            "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg",
+            "image/jpeg",
+        );
+        assert!(key.starts_with("images/"));
+        assert!(key.ends_with(".jpg"));
+        assert_eq!(key.len(), "images/".len() + 16 + ".jpg".len());
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{extract::State, http::StatusCode, routing::post, Form, Router};
+use serde::Deserialize;
+use tokio::sync::mpsc::Sender;
+use tracing::{error, info, instrument, warn};
+
+use super::discord_api::DiscordMessageData;
+use utility::{config::Config, here};
+
+/// A donation, or the first payment of a subscription, made on Ko-fi.
+#[derive(Debug, Clone)]
+pub struct Donation {
+    /// The name of the supporter, `None` if they want to stay anonymous.
+    pub from_name: Option<String>,
+    pub message: Option<String>,
+    pub amount: String,
+    pub currency: String,
+    pub subscription: bool,
+}
+
+/// Receives the webhooks Ko-fi sends for payments, passing the donations on to be thanked.
+pub struct KofiWebhook;
+
+#[derive(Clone)]
+struct WebhookState {
+    verification_token: Arc<str>,
+    notifier_sender: Sender<DiscordMessageData>,
+}
+
+/// Ko-fi posts a form with the payment as JSON in its only field.
+#[derive(Debug, Deserialize)]
+struct WebhookForm {
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Payment {
+    verification_token: String,
+    #[serde(rename = "type")]
+    kind: String,
+    is_public: bool,
+    from_name: String,
+    message: Option<String>,
+    amount: String,
+    currency: String,
+    #[serde(default)]
+    is_subscription_payment: bool,
+    #[serde(default)]
+    is_first_subscription_payment: bool,
+}
+
+impl KofiWebhook {
+    #[instrument(skip(config, notifier_sender))]
+    pub async fn start(config: Arc<Config>, notifier_sender: Sender<DiscordMessageData>) {
+        tokio::spawn(async move {
+            tokio::select! {
+                e = Self::run(&config, notifier_sender) => {
+                    if let Err(e) = e {
+                        error!("{:#}", e);
+                    }
+                }
+                e = tokio::signal::ctrl_c() => {
+                    if let Err(e) = e {
+                        error!("{:#}", e);
+                    }
+                }
+            }
+
+            info!(task = "Ko-fi webhook", "Shutting down.");
+        });
+    }
+
+    async fn run(
+        config: &Config,
+        notifier_sender: Sender<DiscordMessageData>,
+    ) -> anyhow::Result<()> {
+        let kofi = &config.support.kofi;
+
+        let state = WebhookState {
+            verification_token: Arc::from(kofi.verification_token.as_str()),
+            notifier_sender,
+        };
+
+        let app = Router::new()
+            .route(&kofi.path, post(Self::receive))
+            .with_state(state);
+
+        info!(address = %kofi.address, path = %kofi.path, "Listening for Ko-fi webhooks.");
+
+        axum::Server::try_bind(&kofi.address)
+            .context(here!())?
+            .serve(app.into_make_service())
+            .await
+            .context(here!())
+    }
+
+    async fn receive(
+        State(state): State<WebhookState>,
+        Form(form): Form<WebhookForm>,
+    ) -> StatusCode {
+        let donation = match Self::parse(&form.data, &state.verification_token) {
+            Ok(Some(donation)) => donation,
+            Ok(None) => return StatusCode::OK,
+            Err(status) => return status,
+        };
+
+        match state
+            .notifier_sender
+            .send(DiscordMessageData::Donation(donation))
+            .await
+        {
+            Ok(()) => StatusCode::OK,
+            Err(e) => {
+                error!(?e, "Failed to pass on donation!");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// The donation in the webhook, `None` if it's a payment that isn't thanked, such as
+    /// a shop order or a renewed subscription.
+    fn parse(data: &str, verification_token: &str) -> Result<Option<Donation>, StatusCode> {
+        let payment: Payment = serde_json::from_str(data).map_err(|e| {
+            warn!(?e, "Received malformed Ko-fi webhook.");
+            StatusCode::BAD_REQUEST
+        })?;
+
+        if verification_token.is_empty() || payment.verification_token != verification_token {
+            warn!("Received Ko-fi webhook with the wrong verification token.");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let thanked = match payment.kind.as_str() {
+            "Donation" => true,
+            "Subscription" => {
+                !payment.is_subscription_payment || payment.is_first_subscription_payment
+            }
+            _ => false,
+        };
+
+        if !thanked {
+            return Ok(None);
+        }
+
+        // Messages of private donations are only meant for the creator.
+        Ok(Some(Donation {
+            from_name: payment.is_public.then_some(payment.from_name),
+            message: payment
+                .message
+                .filter(|m| payment.is_public && !m.trim().is_empty()),
+            amount: payment.amount,
+            currency: payment.currency,
+            subscription: payment.kind == "Subscription",
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn payment(kind: &str, is_public: bool, renewal: bool) -> String {
+        json!({
+            "verification_token": "secret",
+            "message_id": "3a1fac0c-f960-4506-a60e-824979a74e74",
+            "timestamp": "2026-03-01T12:00:00Z",
+            "type": kind,
+            "is_public": is_public,
+            "from_name": "Pekora",
+            "message": "Keep it up peko!",
+            "amount": "3.00",
+            "url": "https://ko-fi.com/Home/CoffeeShop?txid=0",
+            "currency": "USD",
+            "is_subscription_payment": renewal,
+            "is_first_subscription_payment": false,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn donations_are_parsed() {
+        let donation = KofiWebhook::parse(&payment("Donation", true, false), "secret")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(donation.from_name.as_deref(), Some("Pekora"));
+        assert_eq!(donation.message.as_deref(), Some("Keep it up peko!"));
+        assert_eq!(donation.amount, "3.00");
+        assert!(!donation.subscription);
+    }
+
+    #[test]
+    fn private_donations_are_anonymous() {
+        let donation = KofiWebhook::parse(&payment("Donation", false, false), "secret")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(donation.from_name, None);
+        assert_eq!(donation.message, None);
+    }
+
+    #[test]
+    fn only_donations_and_new_subscriptions_are_thanked() {
+        let parse = |kind, renewal| {
+            KofiWebhook::parse(&payment(kind, true, renewal), "secret")
+                .unwrap()
+                .is_some()
+        };
+
+        assert!(parse("Subscription", false));
+        assert!(!parse("Subscription", true));
+        assert!(!parse("Shop Order", false));
+    }
+
+    #[test]
+    fn webhooks_must_have_the_token() {
+        assert_eq!(
+            KofiWebhook::parse(&payment("Donation", true, false), "other").unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            KofiWebhook::parse(&payment("Donation", true, false), "").unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            KofiWebhook::parse("{}", "secret").unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
@@ -1,10 +1,24 @@
+pub mod archive_index;
 pub mod birthday_reminder;
+pub mod chat_highlights;
 pub mod discord_api;
 pub mod holo_api;
+pub mod image_cache;
+pub mod kofi_webhook;
 pub mod meme_api;
-// pub mod reminder_notifier;
-pub mod translation_api;
+pub mod message_sink;
+pub mod object_storage;
+pub mod readiness;
+pub mod tweet_dms;
+pub mod tweet_store;
 pub mod twitter_api;
 
 #[cfg(feature = "openai")]
 pub mod openai_api;
+
+#[cfg(feature = "translation")]
+pub mod alert_translation;
+#[cfg(feature = "translation")]
+pub mod translation_api;
+#[cfg(feature = "translation")]
+pub mod translation_usage;
@@ -9,7 +9,7 @@ use serenity::prelude::TypeMapKey;
 use tokio::sync::RwLock;
 use tracing::{info, instrument};
 
-use utility::{config::MemeCreationConfig, here};
+use utility::{config::MemeCreationConfig, here, http};
 
 pub type MemeCache = Arc<RwLock<Vec<Meme>>>;
 
@@ -18,7 +18,6 @@ static LAST_CACHE_UPDATE: OnceCell<RwLock<SystemTime>> = OnceCell::new();
 
 #[derive(Debug, Clone)]
 pub struct MemeApi {
-    agent: ureq::Agent,
     username: String,
     password: String,
 }
@@ -27,24 +26,22 @@ impl MemeApi {
     const CACHE_EXPIRATION_TIME: Duration = Duration::from_secs(60 * 60 * 24);
 
     pub fn new(config: &MemeCreationConfig) -> anyhow::Result<Self> {
-        let agent = ureq::builder()
-            .user_agent(concat!(
-                env!("CARGO_PKG_NAME"),
-                "/",
-                env!("CARGO_PKG_VERSION"),
-            ))
-            .build();
-
         CACHE.get_or_init(|| Arc::new(RwLock::new(Vec::with_capacity(100))));
         LAST_CACHE_UPDATE.get_or_init(|| RwLock::new(SystemTime::now()));
 
         Ok(Self {
-            agent,
             username: config.imgflip_user.clone(),
             password: config.imgflip_pass.clone(),
         })
     }
 
+    /// Drops the cached templates, so that they're fetched again on next use.
+    pub async fn clear_cache() {
+        if let Some(cache) = CACHE.get() {
+            cache.write().await.clear();
+        }
+    }
+
     #[instrument(skip(self))]
     pub async fn get_popular_memes(&self) -> anyhow::Result<MemeCache> {
         let mut last_update = LAST_CACHE_UPDATE.get().unwrap().write().await;
@@ -56,7 +53,13 @@ impl MemeApi {
         }
 
         if cache.is_empty() {
-            let response = self.agent.get("https://api.imgflip.com/get_memes").call()?;
+            let response = http::client()
+                .call(
+                    "GET",
+                    "https://api.imgflip.com/get_memes",
+                    ureq::Request::call,
+                )
+                .await?;
             let response: PopularMemesResponse = response.into_json()?;
 
             if response.success {
@@ -96,31 +99,42 @@ impl MemeApi {
             query.extend(vec![("text1", captions.get(1).unwrap().to_owned())]);
         }
 
-        let mut request = self.agent.post("https://api.imgflip.com/caption_image");
-
-        for (key, value) in query {
-            request = request.query(key, &value);
-        }
-
-        let response = if meme.box_count > 2 {
-            let boxes = captions
-                .iter()
-                .map(|c| MemeBox {
-                    text: c.to_string(),
-                    x: None,
-                    y: None,
-                    width: None,
-                    height: None,
-                    color: None,
-                    outline_color: None,
-                })
-                .collect::<Vec<_>>();
-            request
-                .send_json(serde_json::to_value(boxes)?)
-                .context(here!())?
-        } else {
-            request.call().context(here!())?
-        };
+        let boxes = (meme.box_count > 2)
+            .then(|| {
+                let boxes = captions
+                    .iter()
+                    .map(|c| MemeBox {
+                        text: c.to_string(),
+                        x: None,
+                        y: None,
+                        width: None,
+                        height: None,
+                        color: None,
+                        outline_color: None,
+                    })
+                    .collect::<Vec<_>>();
+
+                serde_json::to_value(boxes)
+            })
+            .transpose()?;
+
+        let response = http::client()
+            .call(
+                "POST",
+                "https://api.imgflip.com/caption_image",
+                move |request| {
+                    let request = query
+                        .iter()
+                        .fold(request, |r, (key, value)| r.query(key, value));
+
+                    match &boxes {
+                        Some(boxes) => request.send_json(boxes.clone()),
+                        None => request.call(),
+                    }
+                },
+            )
+            .await
+            .context(here!())?;
         let response: MemeResponse = response.into_json().context(here!())?;
 
         if response.success {
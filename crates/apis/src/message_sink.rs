@@ -0,0 +1,334 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serenity::{
+    builder::{CreateChannel, CreateMessage, EditMessage},
+    http::Http,
+    model::id::{ChannelId, GuildId, MessageId},
+};
+
+use tracing::warn;
+
+use utility::here;
+
+/// The parts of the Discord HTTP API used to post alerts and manage stream chats,
+/// so that deciding what gets posted where can be tested without talking to Discord.
+#[async_trait]
+pub trait MessageSink: Send + Sync {
+    async fn send_message(
+        &self,
+        channel: ChannelId,
+        message: CreateMessage<'static>,
+    ) -> anyhow::Result<SentMessage>;
+
+    /// Posts the message through a webhook in the channel, so that it shows up under another
+    /// name and avatar. Only the content and embeds of the message are kept.
+    async fn send_as(
+        &self,
+        channel: ChannelId,
+        identity: &WebhookIdentity,
+        message: CreateMessage<'static>,
+    ) -> anyhow::Result<SentMessage>;
+
+    async fn edit_message(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        edit: EditMessage<'static>,
+    ) -> anyhow::Result<()>;
+
+    async fn delete_message(&self, channel: ChannelId, message: MessageId) -> anyhow::Result<()>;
+
+    /// Pins the message, unless the channel already has as many pins as Discord allows.
+    async fn pin_message(&self, channel: ChannelId, message: MessageId) -> anyhow::Result<()>;
+
+    async fn create_channel(
+        &self,
+        guild: GuildId,
+        channel: CreateChannel,
+    ) -> anyhow::Result<ChannelId>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentMessage {
+    pub channel: ChannelId,
+    pub id: MessageId,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The name and avatar a message posted through a webhook is shown with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookIdentity {
+    pub name: String,
+    pub avatar: String,
+}
+
+/// The name of the webhooks the bot creates to post under other identities.
+const WEBHOOK_NAME: &str = "Feed";
+
+/// How many messages Discord lets a channel have pinned.
+const MAX_PINS: usize = 50;
+
+#[async_trait]
+impl MessageSink for Arc<Http> {
+    async fn send_message(
+        &self,
+        channel: ChannelId,
+        message: CreateMessage<'static>,
+    ) -> anyhow::Result<SentMessage> {
+        let sent = channel
+            .send_message(self, |m| {
+                *m = message;
+                m
+            })
+            .await
+            .context(here!())?;
+
+        Ok(SentMessage {
+            channel,
+            id: sent.id,
+            timestamp: *sent.timestamp,
+        })
+    }
+
+    async fn send_as(
+        &self,
+        channel: ChannelId,
+        identity: &WebhookIdentity,
+        message: CreateMessage<'static>,
+    ) -> anyhow::Result<SentMessage> {
+        // Only webhooks created by the bot come with the token needed to post through them.
+        let existing = channel
+            .webhooks(self)
+            .await
+            .context(here!())?
+            .into_iter()
+            .find(|w| w.token.is_some() && w.name.as_deref() == Some(WEBHOOK_NAME));
+
+        let webhook = match existing {
+            Some(webhook) => webhook,
+            None => channel
+                .create_webhook(self, WEBHOOK_NAME)
+                .await
+                .context(here!())?,
+        };
+
+        let mut fields = message.0;
+
+        let sent = webhook
+            .execute(self, true, |w| {
+                w.username(&identity.name).avatar_url(&identity.avatar);
+
+                for key in ["content", "embeds"] {
+                    if let Some(value) = fields.remove(key) {
+                        w.0.insert(key, value);
+                    }
+                }
+
+                w
+            })
+            .await
+            .context(here!())?
+            .ok_or_else(|| anyhow!("Webhook didn't return the posted message."))?;
+
+        Ok(SentMessage {
+            channel,
+            id: sent.id,
+            timestamp: *sent.timestamp,
+        })
+    }
+
+    async fn edit_message(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        edit: EditMessage<'static>,
+    ) -> anyhow::Result<()> {
+        channel
+            .edit_message(self, message, |m| {
+                *m = edit;
+                m
+            })
+            .await
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel: ChannelId, message: MessageId) -> anyhow::Result<()> {
+        channel.delete_message(self, message).await.context(here!())
+    }
+
+    async fn pin_message(&self, channel: ChannelId, message: MessageId) -> anyhow::Result<()> {
+        let pins = channel.pins(self).await.context(here!())?;
+
+        if pins.len() >= MAX_PINS {
+            warn!(%channel, %message, "Channel has too many pins to pin the message.");
+            return Ok(());
+        }
+
+        channel.pin(self, message).await.context(here!())
+    }
+
+    async fn create_channel(
+        &self,
+        guild: GuildId,
+        channel: CreateChannel,
+    ) -> anyhow::Result<ChannelId> {
+        let created = guild
+            .create_channel(self, |c| {
+                *c = channel;
+                c
+            })
+            .await
+            .context(here!())?;
+
+        Ok(created.id)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+    };
+
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Everything a [`MockSink`] was asked to do, with the builders flattened into their JSON.
+    #[derive(Debug, Clone, PartialEq)]
+    pub(crate) enum SinkCall {
+        Send {
+            channel: ChannelId,
+            message: HashMap<&'static str, Value>,
+        },
+        SendAs {
+            channel: ChannelId,
+            identity: WebhookIdentity,
+            message: HashMap<&'static str, Value>,
+        },
+        Edit {
+            channel: ChannelId,
+            message: MessageId,
+            edit: HashMap<&'static str, Value>,
+        },
+        Delete {
+            channel: ChannelId,
+            message: MessageId,
+        },
+        Pin {
+            channel: ChannelId,
+            message: MessageId,
+        },
+        CreateChannel {
+            guild: GuildId,
+            channel: HashMap<&'static str, Value>,
+        },
+    }
+
+    #[derive(Debug, Default)]
+    pub(crate) struct MockSink {
+        calls: Mutex<Vec<SinkCall>>,
+        next_id: AtomicU64,
+    }
+
+    impl MockSink {
+        pub(crate) fn calls(&self) -> Vec<SinkCall> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        fn record(&self, call: SinkCall) -> u64 {
+            self.calls.lock().unwrap().push(call);
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        }
+    }
+
+    #[async_trait]
+    impl MessageSink for MockSink {
+        async fn send_message(
+            &self,
+            channel: ChannelId,
+            message: CreateMessage<'static>,
+        ) -> anyhow::Result<SentMessage> {
+            let id = self.record(SinkCall::Send {
+                channel,
+                message: message.0,
+            });
+
+            Ok(SentMessage {
+                channel,
+                id: MessageId(id),
+                timestamp: Utc::now(),
+            })
+        }
+
+        async fn send_as(
+            &self,
+            channel: ChannelId,
+            identity: &WebhookIdentity,
+            message: CreateMessage<'static>,
+        ) -> anyhow::Result<SentMessage> {
+            let id = self.record(SinkCall::SendAs {
+                channel,
+                identity: identity.clone(),
+                message: message.0,
+            });
+
+            Ok(SentMessage {
+                channel,
+                id: MessageId(id),
+                timestamp: Utc::now(),
+            })
+        }
+
+        async fn edit_message(
+            &self,
+            channel: ChannelId,
+            message: MessageId,
+            edit: EditMessage<'static>,
+        ) -> anyhow::Result<()> {
+            self.record(SinkCall::Edit {
+                channel,
+                message,
+                edit: edit.0,
+            });
+
+            Ok(())
+        }
+
+        async fn delete_message(
+            &self,
+            channel: ChannelId,
+            message: MessageId,
+        ) -> anyhow::Result<()> {
+            self.record(SinkCall::Delete { channel, message });
+            Ok(())
+        }
+
+        async fn pin_message(&self, channel: ChannelId, message: MessageId) -> anyhow::Result<()> {
+            self.record(SinkCall::Pin { channel, message });
+            Ok(())
+        }
+
+        async fn create_channel(
+            &self,
+            guild: GuildId,
+            channel: CreateChannel,
+        ) -> anyhow::Result<ChannelId> {
+            let id = self.record(SinkCall::CreateChannel {
+                guild,
+                channel: channel.0,
+            });
+
+            Ok(ChannelId(id))
+        }
+    }
+}
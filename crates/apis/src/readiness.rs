@@ -0,0 +1,101 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::bail;
+use serenity::{
+    client::Context,
+    model::{
+        channel::{Channel, ChannelCategory},
+        id::{ChannelId, GuildId},
+    },
+};
+use tokio::time::Instant;
+use tracing::{debug, instrument};
+
+/// How long tasks wait for their channels to be cached before giving up.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A channel that a task needs to be cached before it can start.
+#[derive(Debug, Clone, Copy)]
+pub struct RequiredChannel {
+    /// What the channel is configured as, shown if it can't be found.
+    pub purpose: &'static str,
+    pub id: ChannelId,
+}
+
+impl RequiredChannel {
+    #[must_use]
+    pub const fn new(purpose: &'static str, id: ChannelId) -> Self {
+        Self { purpose, id }
+    }
+}
+
+/// The required channels, as they were in the cache once they all were.
+#[derive(Debug)]
+pub struct ReadyChannels(HashMap<ChannelId, Channel>);
+
+impl ReadyChannels {
+    pub fn category(&self, id: ChannelId) -> anyhow::Result<ChannelCategory> {
+        match self.0.get(&id) {
+            Some(Channel::Category(category)) => Ok(category.clone()),
+            Some(_) => bail!("Channel {id} is not a category!"),
+            None => bail!("Channel {id} was not waited for!"),
+        }
+    }
+
+    pub fn guild_id(&self, id: ChannelId) -> anyhow::Result<GuildId> {
+        match self.0.get(&id) {
+            Some(Channel::Guild(channel)) => Ok(channel.guild_id),
+            Some(Channel::Category(category)) => Ok(category.guild_id),
+            Some(_) => bail!("Channel {id} is not in a guild!"),
+            None => bail!("Channel {id} was not waited for!"),
+        }
+    }
+}
+
+/// Waits until every required channel is in the cache, which happens once Discord
+/// has sent the guild they're in. Fails with the channels that are still missing
+/// if that takes longer than `timeout`.
+#[instrument(skip(ctx))]
+pub async fn wait_for_channels(
+    ctx: &Context,
+    required: &[RequiredChannel],
+    timeout: Duration,
+) -> anyhow::Result<ReadyChannels> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let missing = required
+            .iter()
+            .filter(|r| ctx.cache.channel(r.id).is_none())
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            debug!("Required channels are cached.");
+
+            return Ok(ReadyChannels(
+                required
+                    .iter()
+                    .filter_map(|r| Some((r.id, ctx.cache.channel(r.id)?)))
+                    .collect(),
+            ));
+        }
+
+        if Instant::now() >= deadline {
+            let missing = missing
+                .iter()
+                .map(|r| format!("{} ({})", r.purpose, r.id))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            bail!(
+                "Timed out after {} seconds waiting for channels to be cached: {missing}. \
+                Check that the IDs are right, and that the bot is in their servers and can see them.",
+                timeout.as_secs()
+            );
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -2,12 +2,18 @@ use std::collections::HashMap;
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
-use deepl::{DeepL, LanguageList, TranslatableTextList};
+use deepl::{DeepL, Language, LanguageList, ModelType, TranslatableTextList, TranslationOptions};
 /* use libretranslate::{translate, Language}; */
 use serde::Deserialize;
 use tracing::{info, instrument};
 
-use utility::{config::TranslatorConfig, here, types::TranslatorType};
+use utility::{
+    config::{Database, TranslatorConfig},
+    here, http,
+    types::TranslatorType,
+};
+
+use crate::translation_usage::{AccountedDeepL, UsageFeature};
 
 pub struct TranslationApi {
     translators: HashMap<TranslatorType, Box<dyn Translator + 'static>>,
@@ -22,7 +28,11 @@ impl std::fmt::Debug for TranslationApi {
 }
 
 impl TranslationApi {
-    pub fn new(config: &HashMap<TranslatorType, TranslatorConfig>) -> anyhow::Result<Self> {
+    /// The characters used by the translators are recorded in `database`.
+    pub fn new(
+        config: &HashMap<TranslatorType, TranslatorConfig>,
+        database: &Database,
+    ) -> anyhow::Result<Self> {
         let mut translators: HashMap<TranslatorType, Box<dyn Translator + 'static>> =
             HashMap::new();
 
@@ -31,7 +41,7 @@ impl TranslationApi {
 
         for (translator_type, conf) in config {
             let mut translator: Box<dyn Translator + 'static> = match translator_type {
-                TranslatorType::DeepL => Box::<DeepLApi>::default(),
+                TranslatorType::DeepL => Box::new(DeepLApi::new(database.clone())),
                 /* TranslatorType::Azure => Box::<AzureApi>::default()), */
                 /* TranslatorType::Libre => Box::<LibreApi>::default()), */
             };
@@ -73,7 +83,13 @@ impl TranslationApi {
 #[async_trait]
 pub trait Translator: Send + Sync {
     fn initialize(&mut self, config: &TranslatorConfig) -> anyhow::Result<()>;
-    async fn translate(&self, text: &str, from: &str) -> anyhow::Result<String>;
+    /// Translates `text` to English, using `context` to improve the translation if the translator supports it.
+    async fn translate(
+        &self,
+        text: &str,
+        from: &str,
+        context: Option<&str>,
+    ) -> anyhow::Result<String>;
 }
 
 /* #[derive(Debug, Default)]
@@ -156,26 +172,57 @@ impl Translator for AzureApi {
     }
 } */
 
-#[derive(Default)]
 struct DeepLApi {
-    client: Option<DeepL>,
+    client: Option<AccountedDeepL>,
     supported_languages: LanguageList,
+    model_type: Option<ModelType>,
+    database: Database,
+}
+
+impl DeepLApi {
+    fn new(database: Database) -> Self {
+        Self {
+            client: None,
+            supported_languages: LanguageList::new(),
+            model_type: None,
+            database,
+        }
+    }
 }
 
 #[async_trait]
 impl Translator for DeepLApi {
     fn initialize(&mut self, config: &TranslatorConfig) -> anyhow::Result<()> {
-        let client = DeepL::new(config.token.clone());
+        let client = DeepL::with_agent(config.token.clone(), http::client().agent().clone());
+
+        self.model_type = match config.model_type.as_deref() {
+            Some("quality_optimized") => Some(ModelType::QualityOptimized),
+            Some("prefer_quality_optimized") => Some(ModelType::PreferQualityOptimized),
+            Some("latency_optimized") => Some(ModelType::LatencyOptimized),
+            Some(model_type) => {
+                return Err(anyhow!("Unknown DeepL model type: {model_type}").context(here!()))
+            }
+            None => None,
+        };
 
         self.supported_languages = client.source_languages()?;
-        self.client = Some(client);
+        self.client = Some(AccountedDeepL::new(
+            client,
+            self.database.clone(),
+            UsageFeature::TweetTranslation,
+        ));
 
         Ok(())
     }
 
     #[allow(clippy::cast_precision_loss)]
     #[instrument(skip(self))]
-    async fn translate(&self, text: &str, from: &str) -> anyhow::Result<String> {
+    async fn translate(
+        &self,
+        text: &str,
+        from: &str,
+        context: Option<&str>,
+    ) -> anyhow::Result<String> {
         let client = match &self.client {
             Some(client) => client,
             None => {
@@ -186,24 +233,22 @@ impl Translator for DeepLApi {
             }
         };
 
-        let upper_lang = match from {
-            "jp" => "JA".to_owned(),
-            "in" => "ID".to_owned(),
-            l => l.to_ascii_uppercase(),
+        let language = match from {
+            "jp" => Language::Japanese,
+            "in" => Language::Indonesian,
+            l => l.parse::<Language>().context(here!())?,
         };
 
-        let lang = match self
+        if !self
             .supported_languages
             .iter()
-            .find(|l| l.language == upper_lang)
+            .any(|l| l.language == language.code())
         {
-            Some(lang) => lang,
-            None => {
-                return Err(anyhow!("Unsupported language.").context(here!()));
-            }
-        };
+            return Err(anyhow!("Unsupported language.").context(here!()));
+        }
 
         let usage = client
+            .client()
             .usage_information()
             .map_err(|e| anyhow!("{}", e))
             .context(here!())?;
@@ -213,13 +258,19 @@ impl Translator for DeepLApi {
         }
 
         let text_list = TranslatableTextList {
-            source_language: Some(lang.language.to_owned()),
-            target_language: "EN-US".to_owned(),
+            source_language: Some(language),
+            target_language: Language::EnglishAmerican,
             texts: vec![text.to_owned()],
         };
 
+        let options = TranslationOptions {
+            context: context.map(ToOwned::to_owned),
+            model_type: self.model_type,
+            ..Default::default()
+        };
+
         let result = client
-            .translate(None, text_list)
+            .translate(Some(options), text_list)
             .map_err(|e| anyhow!("{}", e))
             .context(here!())?;
 
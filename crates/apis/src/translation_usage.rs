@@ -0,0 +1,284 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use deepl::{
+    DeepL, Error, ImprovedText, RephraseOptions, TranslatableTextList, TranslatedText,
+    TranslationOptions,
+};
+use tracing::error;
+
+use utility::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+/// The parts of the bot that spend translation characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UsageFeature {
+    TweetTranslation,
+    Rephrase,
+    TranslationRequest,
+    AlertTranslation,
+}
+
+impl UsageFeature {
+    pub const ALL: [Self; 4] = [
+        Self::TweetTranslation,
+        Self::Rephrase,
+        Self::TranslationRequest,
+        Self::AlertTranslation,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TweetTranslation => "Tweet translation",
+            Self::Rephrase => "/rephrase",
+            Self::TranslationRequest => "Stream chat translation requests",
+            Self::AlertTranslation => "Stream alert translations",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::TweetTranslation => "tweet_translation",
+            Self::Rephrase => "rephrase",
+            Self::TranslationRequest => "translation_request",
+            Self::AlertTranslation => "alert_translation",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.key() == key)
+    }
+}
+
+/// Creates the table the usage is recorded in.
+pub fn init(database: &Database) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+    TranslationUsage::create_table(&handle).context(here!())
+}
+
+/// Records how many characters each feature has sent to the translator.
+pub struct TranslationUsage {
+    handle: DatabaseHandle,
+}
+
+impl TranslationUsage {
+    const TABLE_NAME: &'static str = "TranslationUsage";
+
+    /// Opens the usage records, which [`init`] must have set up.
+    pub fn open(database: &Database) -> anyhow::Result<Self> {
+        let handle = database.get_handle().context(here!())?;
+        Ok(Self { handle })
+    }
+
+    fn create_table(handle: &DatabaseHandle) -> anyhow::Result<()> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("feature", "TEXT", Some("NOT NULL")),
+                    ("characters", "INTEGER", Some("NOT NULL")),
+                    ("used_at", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())
+    }
+
+    pub fn record(
+        &self,
+        feature: UsageFeature,
+        characters: usize,
+        used_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        match &self.handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "INSERT INTO {} (feature, characters, used_at) VALUES (?, ?, ?)",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![feature.key(), characters, used_at.timestamp()],
+                )
+                .context(here!())?,
+        };
+
+        Ok(())
+    }
+
+    /// The characters used by every feature between `from` and `to`, most used first.
+    /// Features that haven't used any are included as well.
+    pub fn breakdown(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<(UsageFeature, u64)>> {
+        let mut usage = UsageFeature::ALL.map(|f| (f, 0));
+
+        match &self.handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT feature, SUM(characters) FROM {} WHERE used_at >= ? AND used_at < ? GROUP BY feature",
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let rows = stmt
+                    .query_map([from.timestamp(), to.timestamp()], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+                    })
+                    .context(here!())?;
+
+                for row in rows {
+                    let (key, characters) = row.context(here!())?;
+
+                    // Features that have since been removed aren't shown.
+                    let feature = match UsageFeature::from_key(&key) {
+                        Some(feature) => feature,
+                        None => continue,
+                    };
+
+                    if let Some(entry) = usage.iter_mut().find(|(f, _)| *f == feature) {
+                        entry.1 = characters;
+                    }
+                }
+            }
+        }
+
+        let mut usage = Vec::from(usage);
+        usage.sort_by(|a, b| b.1.cmp(&a.1));
+
+        Ok(usage)
+    }
+}
+
+/// Wraps a DeepL client, recording the characters each request uses for a feature.
+/// Failing to record them is logged, but doesn't fail the request.
+pub struct AccountedDeepL {
+    client: DeepL,
+    database: Database,
+    feature: UsageFeature,
+}
+
+impl AccountedDeepL {
+    #[must_use]
+    pub fn new(client: DeepL, database: Database, feature: UsageFeature) -> Self {
+        Self {
+            client,
+            database,
+            feature,
+        }
+    }
+
+    #[must_use]
+    pub fn client(&self) -> &DeepL {
+        &self.client
+    }
+
+    /// Translates the texts in as many requests as DeepL needs, recording the usage of every
+    /// request that succeeded. Fails with the error of the first request that failed.
+    pub fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>, Error> {
+        let characters = text_list
+            .texts
+            .iter()
+            .map(|t| t.chars().count())
+            .collect::<Vec<_>>();
+
+        let mut translations = Vec::with_capacity(characters.len());
+        let mut error = None;
+
+        for chunk in self.client.translate_batch(options, text_list) {
+            match chunk.result {
+                Ok(translated) => {
+                    self.record(characters[chunk.texts].iter().sum());
+                    translations.extend(translated);
+                }
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(translations),
+        }
+    }
+
+    pub fn rephrase(
+        &self,
+        text: String,
+        options: Option<RephraseOptions>,
+    ) -> Result<ImprovedText, Error> {
+        let characters = text.chars().count();
+        let result = self.client.rephrase(text, options);
+
+        if result.is_ok() {
+            self.record(characters);
+        }
+
+        result
+    }
+
+    fn record(&self, characters: usize) {
+        let recorded = TranslationUsage::open(&self.database)
+            .and_then(|usage| usage.record(self.feature, characters, Utc::now()));
+
+        if let Err(e) = recorded {
+            error!(?e, feature = ?self.feature, "Failed to record translation usage!");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn usage_is_broken_down_per_feature() {
+        let usage = TranslationUsage::open(&Database::SQLite {
+            path: PathBuf::from(":memory:"),
+        })
+        .unwrap();
+        TranslationUsage::create_table(&usage.handle).unwrap();
+
+        let at = |t| Utc.timestamp_opt(t, 0).unwrap();
+
+        for (feature, characters, used_at) in [
+            (UsageFeature::Rephrase, 100, 1_000),
+            (UsageFeature::Rephrase, 50, 2_000),
+            (UsageFeature::TweetTranslation, 20, 2_000),
+            (UsageFeature::TweetTranslation, 500, 5_000),
+        ] {
+            usage.record(feature, characters, at(used_at)).unwrap();
+        }
+
+        assert_eq!(
+            usage.breakdown(at(0), at(3_000)).unwrap(),
+            vec![
+                (UsageFeature::Rephrase, 150),
+                (UsageFeature::TweetTranslation, 20),
+                (UsageFeature::TranslationRequest, 0),
+                (UsageFeature::AlertTranslation, 0)
+            ]
+        );
+        assert_eq!(
+            usage.breakdown(at(3_000), at(6_000)).unwrap(),
+            vec![
+                (UsageFeature::TweetTranslation, 500),
+                (UsageFeature::Rephrase, 0),
+                (UsageFeature::TranslationRequest, 0),
+                (UsageFeature::AlertTranslation, 0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serenity::model::id::UserId;
+
+/// How tweets sent by DM should be delivered to a subscriber.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    Direct,
+    /// The subscriber has been sent too many DMs in the last hour,
+    /// so the tweet waits in their digest instead.
+    Digested,
+}
+
+/// Limits how many DMs each subscriber is sent in an hour, collecting what's over the limit
+/// into a digest that's sent as a single DM once they can be sent another.
+#[derive(Debug)]
+pub struct DmBudget<T> {
+    max_per_hour: usize,
+    sent: HashMap<UserId, VecDeque<DateTime<Utc>>>,
+    digests: HashMap<UserId, Vec<T>>,
+}
+
+impl<T> DmBudget<T> {
+    #[must_use]
+    pub fn new(max_per_hour: u32) -> Self {
+        Self {
+            max_per_hour: (max_per_hour as usize).max(1),
+            sent: HashMap::new(),
+            digests: HashMap::new(),
+        }
+    }
+
+    /// Decides how the item is delivered to the user, keeping it for their digest if it's over the limit.
+    pub fn offer(&mut self, user: UserId, item: T, now: DateTime<Utc>) -> Delivery {
+        // Anything sent directly would arrive before the digest waiting in front of it.
+        if !self.digests.contains_key(&user) && self.try_spend(user, now) {
+            return Delivery::Direct;
+        }
+
+        self.digests.entry(user).or_default().push(item);
+        Delivery::Digested
+    }
+
+    /// Takes the digests of the users that can be sent another DM.
+    pub fn due_digests(&mut self, now: DateTime<Utc>) -> Vec<(UserId, Vec<T>)> {
+        let users = self.digests.keys().copied().collect::<Vec<_>>();
+
+        users
+            .into_iter()
+            .filter(|&user| self.try_spend(user, now))
+            .filter_map(|user| Some((user, self.digests.remove(&user)?)))
+            .collect()
+    }
+
+    /// When the next digest can be sent, if there are any waiting.
+    #[must_use]
+    pub fn next_digest_at(&self) -> Option<DateTime<Utc>> {
+        self.digests
+            .keys()
+            .filter_map(|user| self.sent.get(user)?.front())
+            .map(|&oldest| oldest + Duration::hours(1))
+            .min()
+    }
+
+    fn try_spend(&mut self, user: UserId, now: DateTime<Utc>) -> bool {
+        let sent = self.sent.entry(user).or_default();
+
+        while sent
+            .front()
+            .map_or(false, |&t| t + Duration::hours(1) <= now)
+        {
+            sent.pop_front();
+        }
+
+        if sent.len() >= self.max_per_hour {
+            return false;
+        }
+
+        sent.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn tweets_over_the_limit_are_digested() {
+        let mut budget = DmBudget::new(2);
+        let user = UserId(1);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(budget.offer(user, 1, start), Delivery::Direct);
+        assert_eq!(
+            budget.offer(user, 2, start + Duration::minutes(10)),
+            Delivery::Direct
+        );
+        assert_eq!(
+            budget.offer(user, 3, start + Duration::minutes(20)),
+            Delivery::Digested
+        );
+
+        // Other users have budgets of their own.
+        assert_eq!(budget.offer(UserId(2), 4, start), Delivery::Direct);
+
+        assert_eq!(budget.next_digest_at(), Some(start + Duration::hours(1)));
+        assert!(budget.due_digests(start + Duration::minutes(59)).is_empty());
+
+        // Once the digest is waiting, tweets are added to it even if there's room again.
+        assert_eq!(
+            budget.offer(user, 5, start + Duration::hours(1)),
+            Delivery::Digested
+        );
+
+        assert_eq!(
+            budget.due_digests(start + Duration::hours(1)),
+            vec![(user, vec![3, 5])]
+        );
+        assert_eq!(budget.next_digest_at(), None);
+    }
+}
@@ -0,0 +1,258 @@
+use anyhow::Context;
+use lru::LruCache;
+use rusqlite::OptionalExtension;
+use serenity::model::{
+    channel::MessageReference,
+    id::{ChannelId, GuildId, MessageId},
+};
+use tracing::{error, instrument};
+
+use utility::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+/// The Discord message a tweet was posted as, and the name of the talent who tweeted it.
+#[derive(Debug, Clone)]
+pub struct PostedTweet {
+    pub message: MessageReference,
+    pub user_name: String,
+}
+
+/// Remembers which Discord message each tweet was posted as, so that replies can link to it
+/// even after a restart. Recently used tweets are kept in memory in front of the database.
+pub struct TweetStore {
+    recent: LruCache<u64, PostedTweet>,
+    handle: Option<DatabaseHandle>,
+}
+
+impl TweetStore {
+    const TABLE_NAME: &'static str = "TweetMessages";
+    const MEMORY_CAPACITY: usize = 1024;
+
+    /// Falls back to only keeping tweets in memory if the database can't be opened.
+    #[instrument(skip(database))]
+    pub fn new(database: &Database) -> Self {
+        let handle = match Self::open(database) {
+            Ok(h) => Some(h),
+            Err(e) => {
+                error!(
+                    ?e,
+                    "Failed to open tweet store, keeping tweets in memory only!"
+                );
+                None
+            }
+        };
+
+        Self {
+            recent: LruCache::new(Self::MEMORY_CAPACITY.try_into().unwrap()),
+            handle,
+        }
+    }
+
+    fn open(database: &Database) -> anyhow::Result<DatabaseHandle> {
+        let handle = database.get_handle().context(here!())?;
+
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("tweet_id", "INTEGER", Some("PRIMARY KEY")),
+                    ("guild_id", "INTEGER", None),
+                    ("channel_id", "INTEGER", Some("NOT NULL")),
+                    ("message_id", "INTEGER", Some("NOT NULL")),
+                    ("user_name", "TEXT", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        Ok(handle)
+    }
+
+    pub fn get(&mut self, tweet_id: u64) -> Option<PostedTweet> {
+        if let Some(posted) = self.recent.get(&tweet_id) {
+            return Some(posted.clone());
+        }
+
+        let posted = match self.load(tweet_id) {
+            Ok(posted) => posted?,
+            Err(e) => {
+                error!(?e, tweet_id, "Failed to look up tweet!");
+                return None;
+            }
+        };
+
+        self.recent.put(tweet_id, posted.clone());
+        Some(posted)
+    }
+
+    /// Records the message a tweet was posted as. Tweets that were posted more than once
+    /// keep their latest message, whichever order the messages are recorded in.
+    pub fn insert(&mut self, tweet_id: u64, posted: PostedTweet) {
+        if self.handle.is_some() {
+            match self.save(tweet_id, &posted) {
+                // The database may have kept a newer message, so it's read from there next time.
+                Ok(()) => {
+                    self.recent.pop(&tweet_id);
+                    return;
+                }
+                Err(e) => error!(?e, tweet_id, "Failed to save tweet!"),
+            }
+        }
+
+        let newer = self
+            .recent
+            .peek(&tweet_id)
+            .map_or(true, |p| p.message.message_id <= posted.message.message_id);
+
+        if newer {
+            self.recent.put(tweet_id, posted);
+        }
+    }
+
+    /// Whether tweets are kept in the database, and not only in memory.
+    pub fn is_persistent(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Drops the tweets kept in memory, they're still available from the database.
+    pub fn clear_memory(&mut self) {
+        self.recent.clear();
+    }
+
+    fn load(&self, tweet_id: u64) -> anyhow::Result<Option<PostedTweet>> {
+        let handle = match &self.handle {
+            Some(h) => h,
+            None => return Ok(None),
+        };
+
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .query_row(
+                    &format!(
+                        "SELECT guild_id, channel_id, message_id, user_name FROM {} WHERE tweet_id = ?",
+                        Self::TABLE_NAME
+                    ),
+                    [tweet_id],
+                    |row| {
+                        let mut message = MessageReference::from((
+                            ChannelId(row.get(1)?),
+                            MessageId(row.get(2)?),
+                        ));
+                        message.guild_id = row.get::<_, Option<u64>>(0)?.map(GuildId);
+
+                        Ok(PostedTweet {
+                            message,
+                            user_name: row.get(3)?,
+                        })
+                    },
+                )
+                .optional()
+                .context(here!()),
+        }
+    }
+
+    fn save(&self, tweet_id: u64, posted: &PostedTweet) -> anyhow::Result<()> {
+        let handle = match &self.handle {
+            Some(h) => h,
+            None => return Ok(()),
+        };
+
+        let message_id = posted
+            .message
+            .message_id
+            .ok_or_else(|| anyhow::anyhow!("Tweet message reference has no message ID."))?;
+
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "INSERT INTO {} (tweet_id, guild_id, channel_id, message_id, user_name) VALUES (?, ?, ?, ?, ?) \
+                        ON CONFLICT (tweet_id) DO UPDATE SET guild_id = excluded.guild_id, channel_id = excluded.channel_id, \
+                        message_id = excluded.message_id, user_name = excluded.user_name WHERE excluded.message_id > message_id",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![
+                        tweet_id,
+                        posted.message.guild_id.map(|g| g.0),
+                        posted.message.channel_id.0,
+                        message_id.0,
+                        posted.user_name,
+                    ],
+                )
+                .context(here!())?,
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn store() -> TweetStore {
+        TweetStore::new(&Database::SQLite {
+            path: PathBuf::from(":memory:"),
+        })
+    }
+
+    fn posted(channel: u64, message: u64) -> PostedTweet {
+        let mut reference = MessageReference::from((ChannelId(channel), MessageId(message)));
+        reference.guild_id = Some(GuildId(3));
+
+        PostedTweet {
+            message: reference,
+            user_name: "Pekora".to_owned(),
+        }
+    }
+
+    #[test]
+    fn tweets_are_found_after_memory_is_cleared() {
+        let mut store = store();
+
+        store.insert(42, posted(1, 2));
+        store.clear_memory();
+
+        let found = store.get(42).unwrap();
+
+        assert_eq!(found.message.channel_id, ChannelId(1));
+        assert_eq!(found.message.message_id, Some(MessageId(2)));
+        assert_eq!(found.message.guild_id, Some(GuildId(3)));
+        assert_eq!(found.user_name, "Pekora");
+    }
+
+    #[test]
+    fn reposted_tweets_replace_the_old_message() {
+        let mut store = store();
+
+        store.insert(42, posted(1, 2));
+        store.insert(42, posted(1, 5));
+        store.clear_memory();
+
+        assert_eq!(
+            store.get(42).unwrap().message.message_id,
+            Some(MessageId(5))
+        );
+    }
+
+    #[test]
+    fn older_messages_dont_replace_newer_ones() {
+        let mut store = store();
+
+        store.insert(42, posted(1, 5));
+        store.insert(42, posted(1, 2));
+
+        assert_eq!(
+            store.get(42).unwrap().message.message_id,
+            Some(MessageId(5))
+        );
+    }
+
+    #[test]
+    fn unknown_tweets_are_missing() {
+        assert!(store().get(42).is_none());
+    }
+}
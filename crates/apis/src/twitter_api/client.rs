@@ -0,0 +1,464 @@
+use std::{convert::TryInto, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use chrono::prelude::*;
+use futures::StreamExt;
+use tokio::sync::{broadcast, mpsc::Sender, watch};
+use tracing::{debug, error, info, instrument, trace, warn};
+use twitter::{
+    search::RecentSearch, streams::FilteredStream, MediaType, NetworkOptions,
+    RecentSearchParameters, Rule, StreamParameters, Tweet,
+};
+
+use super::{HoloTweet, HoloTweetReference, ScheduleUpdate, TweetMedia};
+use crate::{discord_api::DiscordMessageData, translation_api::TranslationApi};
+use utility::{
+    config::{self, Config, NetworkConfig, Talent},
+    here, http,
+    types::Service,
+};
+
+#[async_trait]
+trait TweetExt {
+    async fn translate(&self, translator: &TranslationApi, talent: &Talent) -> Option<String>;
+    fn schedule_update(&self, talent: &Talent) -> Option<ScheduleUpdate>;
+    fn talent_reply(&self, talents: &[Talent]) -> Option<HoloTweetReference>;
+    fn convert_entities_to_links(&self) -> String;
+}
+
+#[async_trait]
+impl TweetExt for Tweet {
+    async fn translate(&self, translator: &TranslationApi, talent: &Talent) -> Option<String> {
+        let lang = self.data.lang?.to_639_1()?;
+
+        // Tweets are short, so knowing who wrote them helps with names and references.
+        let context = format!("A tweet by {}, a Hololive VTuber.", talent.name);
+
+        match translator
+            .get_translator_for_lang(lang)?
+            .translate(&self.data.text, lang, Some(&context))
+            .await
+            .context(here!())
+        {
+            Ok(tl) => Some(tl),
+            Err(e) => {
+                error!("{:?}", e);
+                None
+            }
+        }
+    }
+
+    fn schedule_update(&self, talent: &Talent) -> Option<ScheduleUpdate> {
+        let keyword = talent.schedule_keyword.as_ref()?;
+        let includes = self.includes.as_ref()?;
+
+        if includes.media.is_empty()
+            || !self
+                .data
+                .text
+                .to_lowercase()
+                .contains(&keyword.to_lowercase())
+        {
+            return None;
+        }
+
+        let schedule_image = match &includes.media[..] {
+            [media, ..] => match media.url.as_ref() {
+                Some(url) => url.to_string(),
+                None => {
+                    warn!("Detected schedule image had no URL.");
+                    return None;
+                }
+            },
+            [] => {
+                warn!("Detected schedule post didn't include image!");
+                return None;
+            }
+        };
+
+        Some(ScheduleUpdate {
+            twitter_id: self.data.author_id.unwrap().0,
+            tweet_text: self.data.text.clone(),
+            schedule_image,
+            tweet_link: format!(
+                "https://twitter.com/{}/status/{}",
+                talent.twitter_handle.as_ref().unwrap(),
+                self.data.id
+            ),
+            timestamp: self.data.created_at.unwrap(),
+        })
+    }
+
+    fn talent_reply(&self, talents: &[Talent]) -> Option<HoloTweetReference> {
+        let reference = self.data.referenced_tweets.first()?;
+
+        let replied_to_user = match &reference.reply_type {
+            twitter::TweetReferenceType::RepliedTo => self.data.in_reply_to_user_id?,
+            twitter::TweetReferenceType::Quoted => {
+                self.includes
+                    .as_ref()?
+                    .tweets
+                    .iter()
+                    .find(|t| t.id == reference.id)?
+                    .author_id?
+            }
+            _ => {
+                warn!(reply_type = ?reference.reply_type, "Unknown reply type");
+                return None;
+            }
+        };
+
+        if talents
+            .iter()
+            .any(|u| matches!(u.twitter_id, Some(id) if id == replied_to_user.0))
+        {
+            Some(HoloTweetReference {
+                user: replied_to_user.0,
+                tweet: reference.id.0,
+            })
+        } else {
+            // If tweet is replying to someone who is not a Hololive talent, don't show the tweet.
+            None
+        }
+    }
+
+    fn convert_entities_to_links(&self) -> String {
+        let entities = self.data.entities.iter().filter(|e| {
+            matches!(
+                e,
+                twitter::Entity::Hashtag { .. } | twitter::Entity::Url { .. }
+            )
+        });
+
+        let mut text = self.data.text.clone();
+
+        for entity in entities {
+            entity.embed_link(&mut text);
+        }
+
+        text
+    }
+}
+
+pub struct TwitterApi;
+
+impl TwitterApi {
+    #[instrument(skip(config, notifier_sender, talents))]
+    pub async fn start(
+        config: Arc<Config>,
+        notifier_sender: Sender<DiscordMessageData>,
+        mut service_restarter: broadcast::Receiver<Service>,
+        mut talents: watch::Receiver<Vec<Talent>>,
+    ) -> anyhow::Result<()> {
+        let network = Self::network_options(&config.network)?;
+
+        tokio::spawn(async move {
+            loop {
+                let current_talents = talents.borrow_and_update().clone();
+
+                let tweet_handler =
+                    Self::tweet_handler(&config, &network, &current_talents, &notifier_sender);
+
+                info!("Tweet handler starting!");
+
+                tokio::select! {
+                    res = tweet_handler => {
+                        match res {
+                            Ok(()) => break,
+                            Err(e) => {
+                                error!("{:?}", e);
+                            }
+                        }
+                    }
+
+                    Ok(Service::TwitterFeed) = service_restarter.recv() => { }
+
+                    Ok(()) = talents.changed() => {
+                        info!("Talent roster changed, restarting tweet handler.");
+                        continue;
+                    }
+                }
+
+                info!("Tweet handler is restarting in 1 minute...");
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    #[instrument(skip(config, network, talents, notifier_sender))]
+    async fn tweet_handler(
+        config: &Config,
+        network: &NetworkOptions,
+        talents: &[Talent],
+        notifier_sender: &Sender<DiscordMessageData>,
+    ) -> anyhow::Result<()> {
+        let translator = TranslationApi::new(&config.twitter.feed_translation, &config.database)?;
+        let rules = Self::create_talent_rules(talents.iter().filter(|t| t.twitter_id.is_some()))?;
+
+        let create_stream = || async {
+            FilteredStream::with_options(
+                &config.twitter.token,
+                Self::tweet_parameters(),
+                64,
+                network,
+            )
+            .await
+        };
+
+        let mut stream = create_stream().await?;
+        stream.set_rules(rules).await?;
+
+        loop {
+            let timeout = tokio::time::sleep(std::time::Duration::from_secs(60 * 60));
+
+            tokio::select! {
+                Some(tweet) = stream.next() => {
+                    trace!(?tweet, "Tweet received!");
+
+                    match Self::process_tweet(tweet, config, talents, &translator).await {
+                        Ok(Some(discord_message)) => {
+                            trace!(update = ?discord_message, "Tweet update detected!");
+                            notifier_sender
+                                .send(discord_message)
+                                .await
+                                .context(here!())?;
+                        }
+                        Ok(None) => (),
+                        Err(e) => error!("{:?}", e),
+                    }
+                }
+
+                _ = timeout => {
+                    warn!("No tweet received in the last hour, restarting stream...");
+                    stream = create_stream().await?;
+                }
+
+                res = tokio::signal::ctrl_c() => {
+                    if let Err(e) = res {
+                        error!("{:?}", e);
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Translates the network config into the options understood by the Twitter client.
+    fn network_options(config: &NetworkConfig) -> anyhow::Result<NetworkOptions> {
+        let socks5_proxy = config.socks5_proxy().map(ToOwned::to_owned);
+
+        if socks5_proxy.is_none() && config.proxy_url.is_some() {
+            warn!("The Twitter stream only supports SOCKS5 proxies, connecting directly.");
+        }
+
+        Ok(NetworkOptions {
+            socks5_proxy,
+            extra_root_certs: http::load_ca_certs(&config.extra_ca_certs).context(here!())?,
+        })
+    }
+
+    /// Fetches tweets posted by talents since the given time, for catching up after downtime.
+    #[instrument(skip(config))]
+    pub async fn get_missed_tweets(
+        config: &Config,
+        since: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<DiscordMessageData>> {
+        let translator = TranslationApi::new(&config.twitter.feed_translation, &config.database)?;
+        let search = RecentSearch::with_options(
+            &config.twitter.token,
+            &Self::network_options(&config.network)?,
+        )?;
+
+        let rules =
+            Self::create_talent_rules(config.talents.iter().filter(|t| t.twitter_id.is_some()))?;
+
+        let mut messages = Vec::new();
+
+        for rule in rules {
+            let tweets = search
+                .search(RecentSearchParameters {
+                    query: rule.value,
+                    start_time: Some(since.with_nanosecond(0).unwrap_or(since)),
+                    max_results: Some(RecentSearch::MAX_RESULTS_PER_PAGE),
+                    fields: Self::tweet_parameters(),
+                    ..Default::default()
+                })
+                .await
+                .context(here!())?;
+
+            for tweet in tweets {
+                match Self::process_tweet(tweet, config, &config.talents, &translator).await {
+                    Ok(Some(message)) => messages.push(message),
+                    Ok(None) => (),
+                    Err(e) => error!("{:?}", e),
+                }
+            }
+        }
+
+        Ok(messages)
+    }
+
+    fn tweet_parameters() -> StreamParameters {
+        use twitter::{MediaField as MF, RequestedExpansion as RE, TweetField as TF};
+
+        StreamParameters {
+            expansions: vec![RE::AttachedMedia, RE::ReferencedTweet],
+            media_fields: vec![MF::Url, MF::PreviewImageUrl, MF::Variants],
+            tweet_fields: vec![
+                TF::AuthorId,
+                TF::CreatedAt,
+                TF::Lang,
+                TF::InReplyToUserId,
+                TF::ReferencedTweets,
+                TF::Entities,
+            ],
+            ..Default::default()
+        }
+    }
+
+    async fn process_tweet(
+        tweet: twitter::Tweet,
+        config: &Config,
+        talents: &[Talent],
+        translator: &TranslationApi,
+    ) -> anyhow::Result<Option<DiscordMessageData>> {
+        let author = match tweet.data.author_id {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+
+        // Find who made the tweet.
+        let talent = talents
+            .iter()
+            .find(|u| u.twitter_id.unwrap() == author.0)
+            .ok_or_else(|| anyhow!("Could not find user with twitter ID: {}", author))
+            .context(here!())?;
+
+        trace!(talent = %talent.name, "Found talent who sent tweet.");
+
+        // Check for schedule keyword.
+        if let Some(schedule_update) = tweet.schedule_update(talent) {
+            info!("New schedule update from {}.", talent.name);
+            return Ok(Some(DiscordMessageData::ScheduleUpdate(schedule_update)));
+        }
+
+        if Self::is_filtered(&tweet, talent, config) {
+            debug!(talent = %talent.name, id = %tweet.data.id, "Tweet filtered out of feed.");
+            return Ok(None);
+        }
+
+        // Check if we're replying to another talent.
+        let replied_to = if !tweet.data.referenced_tweets.is_empty() {
+            tweet.talent_reply(talents)
+        } else {
+            None
+        };
+
+        // Add attachments if they exist.
+        let media = tweet
+            .attached_media()
+            .filter_map(TweetMedia::from_media)
+            .collect();
+
+        // Check if translation is necessary.
+        let translation = tweet.translate(translator, talent).await;
+
+        info!("New tweet from {}.", talent.name);
+
+        Ok(Some(DiscordMessageData::Tweet(HoloTweet {
+            id: tweet.data.id.0,
+            user: <config::Talent as Clone>::clone(talent),
+            text: tweet.convert_entities_to_links(),
+            // text: tweet.data.text,
+            link: format!(
+                "https://twitter.com/{}/status/{}",
+                talent.twitter_handle.as_ref().unwrap(),
+                tweet.data.id
+            ),
+            timestamp: tweet.data.created_at.unwrap(),
+            media,
+            translation,
+            replied_to,
+        })))
+    }
+
+    /// Checks the tweet against the filters of the feed it would be posted in.
+    fn is_filtered(tweet: &Tweet, talent: &Talent, config: &Config) -> bool {
+        let channel = match talent.get_twitter_channel(config) {
+            Some(c) => c,
+            None => return false,
+        };
+
+        let has_media = tweet
+            .data
+            .attachments
+            .as_ref()
+            .map_or(false, |a| !a.media_keys.is_empty());
+
+        let lang = tweet.data.lang.and_then(|l| l.to_639_1());
+
+        config
+            .twitter
+            .filters
+            .iter()
+            .filter(|f| f.applies_to(channel))
+            .any(|f| !f.allows(&tweet.data.text, has_media, lang))
+    }
+
+    fn create_talent_rules<'a, It: Iterator<Item = &'a Talent>>(
+        talents: It,
+    ) -> Result<Vec<Rule>, twitter::Error> {
+        const RULE_SUFFIX: &str = "-is:retweet";
+        const RULE_SEPARATOR: &str = " OR ";
+        const ID_PREFIX: &str = "from:";
+        const GROUPING_LENGTH: usize = "() ".len();
+
+        const RULE_MAX_LEN: usize = FilteredStream::MAX_RULE_LENGTH;
+        const ID_MAX_LEN: usize = 20;
+
+        const ID_WITH_PREFIX_LEN: usize = ID_MAX_LEN + ID_PREFIX.len();
+        const RULE_MAX_LEN_WITHOUT_FIXES: usize =
+            RULE_MAX_LEN - RULE_SUFFIX.len() - GROUPING_LENGTH;
+
+        const MAX_IDS_PER_RULE: usize = (RULE_MAX_LEN_WITHOUT_FIXES + RULE_SEPARATOR.len())
+            / (ID_WITH_PREFIX_LEN + RULE_SEPARATOR.len());
+
+        debug_assert_eq!(ID_MAX_LEN, u64::MAX.to_string().len());
+
+        talents
+            .map(|t| format!("{}{}", ID_PREFIX, t.twitter_id.unwrap()))
+            .collect::<Vec<String>>()
+            .chunks(MAX_IDS_PER_RULE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let value = if chunk.len() == 1 {
+                    format!("{} {}", chunk[0], RULE_SUFFIX)
+                } else {
+                    format!("({}) {}", chunk.join(RULE_SEPARATOR), RULE_SUFFIX)
+                };
+
+                Ok(Rule {
+                    value: value.try_into()?,
+                    tag: format!("Hololive Talents #{}", i + 1),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+    }
+}
+
+impl TweetMedia {
+    fn from_media(media: &twitter::Media) -> Option<Self> {
+        match media.media_type {
+            MediaType::Photo => media.url.clone().map(Self::Photo),
+            MediaType::Video | MediaType::AnimatedGif => Some(Self::Video {
+                url: media.best_mp4_variant().map(|u| u.to_owned()),
+                thumbnail: media.preview_image_url.clone(),
+            }),
+        }
+    }
+}
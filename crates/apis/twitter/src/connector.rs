@@ -0,0 +1,117 @@
+//! Connection setup shared by the streaming and REST clients.
+
+use std::{future::Future, pin::Pin, task::Poll};
+
+use hyper::{client::HttpConnector, service::Service, Client, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+
+use crate::{errors::Error, streams::twitter_stream::TwitterStream};
+
+pub(crate) type HttpsClient = Client<HttpsConnector<ProxyConnector>>;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Default)]
+/// Network settings for the connections made to the Twitter API.
+pub struct NetworkOptions {
+    /// Address of a SOCKS5 proxy to connect through, ex. `localhost:9050`.
+    pub socks5_proxy: Option<String>,
+    /// DER-encoded root certificates to trust, in addition to the native ones.
+    pub extra_root_certs: Vec<Vec<u8>>,
+    /// Base URL to send requests to instead of the Twitter API, ex. a local server
+    /// replaying recorded responses. Unlike the default, this may use plain HTTP.
+    pub api_endpoint: Option<String>,
+}
+
+impl NetworkOptions {
+    pub(crate) fn api_endpoint(&self) -> String {
+        self.api_endpoint
+            .clone()
+            .unwrap_or_else(|| TwitterStream::API_ENDPOINT.to_owned())
+    }
+}
+
+pub(crate) fn build_client(options: &NetworkOptions) -> Result<HttpsClient, Error> {
+    let mut roots = rustls::RootCertStore::empty();
+
+    let native_certs = rustls_native_certs::load_native_certs()
+        .map_err(|e| Error::InvalidNetworkOptions(e.to_string()))?;
+
+    for cert in native_certs {
+        // Some systems ship certificates rustls can't parse, those are skipped.
+        let _ = roots.add(&rustls::Certificate(cert.0));
+    }
+
+    for cert in &options.extra_root_certs {
+        roots
+            .add(&rustls::Certificate(cert.clone()))
+            .map_err(|e| Error::InvalidNetworkOptions(e.to_string()))?;
+    }
+
+    let tls = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    let https = HttpsConnectorBuilder::new().with_tls_config(tls);
+
+    let https = match options.api_endpoint {
+        Some(_) => https.https_or_http(),
+        None => https.https_only(),
+    };
+
+    let https = https
+        .enable_http1()
+        .wrap_connector(ProxyConnector::new(options.socks5_proxy.clone()));
+
+    Ok(Client::builder().build(https))
+}
+
+#[derive(Debug, Clone)]
+/// Opens TCP connections either directly or through a SOCKS5 proxy.
+pub(crate) struct ProxyConnector {
+    http: HttpConnector,
+    socks5_proxy: Option<String>,
+}
+
+impl ProxyConnector {
+    fn new(socks5_proxy: Option<String>) -> Self {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+
+        Self { http, socks5_proxy }
+    }
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = TcpStream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let proxy = match &self.socks5_proxy {
+            Some(proxy) => proxy.clone(),
+            None => {
+                let connection = self.http.call(uri);
+                return Box::pin(async move { connection.await.map_err(Into::into) });
+            }
+        };
+
+        Box::pin(async move {
+            let host = uri.host().ok_or("URI is missing a host.")?.to_owned();
+            let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+                Some("http") => 80,
+                _ => 443,
+            });
+
+            let stream = Socks5Stream::connect(proxy.as_str(), (host.as_str(), port)).await?;
+            Ok(stream.into_inner())
+        })
+    }
+}
@@ -15,6 +15,9 @@ pub enum Error {
     #[error("Error creating HTTP client: {0:?}")]
     /// An error occurred while creating the HTTP client.
     HttpClientCreationError(#[source] hyper::Error),
+    #[error("Invalid network options: {0}")]
+    /// The proxy or certificates provided to the client could not be used.
+    InvalidNetworkOptions(String),
     #[error("Error sending request to {endpoint}: {source:?}")]
     /// An error occurred while sending an API request.
     ApiRequestFailed {
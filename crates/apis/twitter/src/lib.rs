@@ -1,11 +1,14 @@
+mod connector;
 mod errors;
 mod macros;
 mod types;
 mod util;
 
 pub mod builders;
+pub mod search;
 pub mod streams;
 
+pub use connector::NetworkOptions;
 pub use errors::Error;
 pub use types::id::*;
 pub use types::*;
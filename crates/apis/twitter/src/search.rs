@@ -0,0 +1,287 @@
+use hyper::{header, Body, Request};
+use tracing::warn;
+
+use crate::{
+    connector::{build_client, HttpsClient, NetworkOptions},
+    errors::Error,
+    streams::twitter_stream::TwitterStream,
+    types::*,
+    util::{check_rate_limit, validate_response},
+};
+
+pub struct RecentSearch {
+    client: HttpsClient,
+    token: String,
+    api_endpoint: String,
+}
+
+impl RecentSearch {
+    pub const MAX_RESULTS_PER_PAGE: u8 = 100;
+    /// How many pages a search follows at most, so that a broad query can't use up
+    /// the rate limit on its own.
+    pub const MAX_PAGES: usize = 10;
+
+    pub fn new(token: &str) -> Self {
+        Self::with_options(token, &NetworkOptions::default())
+            .expect("Failed to load the native root certificates.")
+    }
+
+    /// Creates a client that connects according to the given network options.
+    pub fn with_options(token: &str, options: &NetworkOptions) -> Result<Self, Error> {
+        let client = build_client(options)?;
+
+        let token = if token.starts_with("Bearer ") {
+            token.to_owned()
+        } else {
+            format!("Bearer {}", token)
+        };
+
+        Ok(Self {
+            client,
+            token,
+            api_endpoint: options.api_endpoint(),
+        })
+    }
+
+    pub(crate) fn from_client(client: HttpsClient, token: String, api_endpoint: String) -> Self {
+        Self {
+            client,
+            token,
+            api_endpoint,
+        }
+    }
+
+    /// Fetches the tweets matching the parameters, following pagination until exhausted
+    /// or until `MAX_PAGES` pages have been fetched.
+    pub async fn search(
+        &self,
+        mut parameters: RecentSearchParameters,
+    ) -> Result<Vec<Tweet>, Error> {
+        let mut tweets = Vec::new();
+
+        for _ in 0..Self::MAX_PAGES {
+            let response = self.search_page(&parameters).await?;
+            tweets.extend(Self::split_response(response.data, response.includes));
+
+            match response.meta.next_token {
+                Some(token) => parameters.next_token = Some(token),
+                None => return Ok(tweets),
+            }
+        }
+
+        warn!(
+            query = %parameters.query.0,
+            pages = Self::MAX_PAGES,
+            "Search has more results than the page limit, skipping the rest."
+        );
+
+        Ok(tweets)
+    }
+
+    async fn search_page(
+        &self,
+        parameters: &RecentSearchParameters,
+    ) -> Result<RecentSearchResponse, Error> {
+        let query = serde_urlencoded::to_string(parameters).unwrap();
+
+        let request = Request::get(
+            format!("{}/2/tweets/search/recent?{}", self.api_endpoint, query)
+                .parse::<hyper::Uri>()
+                .unwrap(),
+        )
+        .header(header::USER_AGENT, TwitterStream::USER_AGENT)
+        .header(header::AUTHORIZATION, &self.token)
+        .body(Body::empty())
+        .unwrap();
+
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| Error::ApiRequestFailed {
+                endpoint: "GET /2/tweets/search/recent",
+                source: e,
+            })?;
+
+        check_rate_limit(&response)?;
+
+        validate_response(response)
+            .await
+            .map_err(|e| Error::InvalidResponse {
+                endpoint: "GET /2/tweets/search/recent",
+                source: e,
+            })
+    }
+
+    /// Search results share a single set of expansions, so hand each tweet the ones it references.
+    fn split_response(data: Vec<TweetInfo>, includes: Option<Expansions>) -> Vec<Tweet> {
+        let mut includes = includes.unwrap_or_default();
+
+        data.into_iter()
+            .map(|info| {
+                let media_keys = info
+                    .attachments
+                    .as_ref()
+                    .map(|a| a.media_keys.as_slice())
+                    .unwrap_or_default();
+
+                let (media, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut includes.media)
+                    .into_iter()
+                    .partition(|m| media_keys.contains(&m.media_key));
+                includes.media = rest;
+
+                let (referenced, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut includes.tweets)
+                    .into_iter()
+                    .partition(|t| info.referenced_tweets.iter().any(|r| r.id == t.id));
+                includes.tweets = rest;
+
+                Tweet {
+                    data: info,
+                    includes: Some(Expansions {
+                        media,
+                        tweets: referenced,
+                        ..Default::default()
+                    }),
+                    matching_rules: Vec::new(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{Method::GET, MockServer};
+
+    use super::*;
+    use crate::types::id::TweetId;
+
+    const RESET: &str = "1636243200";
+
+    fn client(server: &MockServer) -> RecentSearch {
+        let options = NetworkOptions {
+            api_endpoint: Some(server.base_url()),
+            ..Default::default()
+        };
+
+        RecentSearch::with_options("test-token", &options).unwrap()
+    }
+
+    fn parameters() -> RecentSearchParameters {
+        RecentSearchParameters {
+            query: RuleString::try_from("from:takanashikiara".to_owned()).unwrap(),
+            max_results: Some(RecentSearch::MAX_RESULTS_PER_PAGE),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn search_follows_pagination() {
+        let server = MockServer::start_async().await;
+
+        // Mocks are matched in the order they were defined, so the follow-up page goes first.
+        let page_2 = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/2/tweets/search/recent")
+                    .query_param("next_token", "b26v89c19zqg8o3fo7gesq314yb9l2l4ptqy");
+                then.status(200)
+                    .header("x-rate-limit-remaining", "449")
+                    .header("x-rate-limit-limit", "450")
+                    .header("x-rate-limit-reset", RESET)
+                    .body(include_str!("../fixtures/recent_search_page_2.json"));
+            })
+            .await;
+
+        let page_1 = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/2/tweets/search/recent")
+                    .header("authorization", "Bearer test-token")
+                    .query_param("query", "from:takanashikiara");
+                then.status(200)
+                    .header("x-rate-limit-remaining", "450")
+                    .header("x-rate-limit-limit", "450")
+                    .header("x-rate-limit-reset", RESET)
+                    .body(include_str!("../fixtures/recent_search_page_1.json"));
+            })
+            .await;
+
+        let tweets = client(&server).search(parameters()).await.unwrap();
+
+        page_1.assert_async().await;
+        page_2.assert_async().await;
+
+        let ids = tweets.iter().map(|t| t.data.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [
+                TweetId(1458038855005782017),
+                TweetId(1458172771054206977),
+                TweetId(1457845923012251648)
+            ]
+        );
+
+        // Each tweet only gets the expansions it references.
+        assert_eq!(
+            tweets[0].attached_photos().collect::<Vec<_>>(),
+            ["https://pbs.twimg.com/media/FDsn3jLaIAcBCgz.jpg"]
+        );
+        assert!(tweets[0].includes.as_ref().unwrap().tweets.is_empty());
+
+        assert_eq!(tweets[1].attached_photos().count(), 0);
+        assert_eq!(tweets[1].includes.as_ref().unwrap().tweets.len(), 1);
+
+        assert!(tweets[2].includes.as_ref().unwrap().media.is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_stops_at_page_limit() {
+        let server = MockServer::start_async().await;
+
+        // Every page points to another one.
+        let pages = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/2/tweets/search/recent");
+                then.status(200)
+                    .header("x-rate-limit-remaining", "450")
+                    .header("x-rate-limit-limit", "450")
+                    .header("x-rate-limit-reset", RESET)
+                    .body(include_str!("../fixtures/recent_search_page_1.json"));
+            })
+            .await;
+
+        let tweets = client(&server).search(parameters()).await.unwrap();
+
+        assert_eq!(pages.hits_async().await, RecentSearch::MAX_PAGES);
+        assert_eq!(tweets.len(), 2 * RecentSearch::MAX_PAGES);
+    }
+
+    #[tokio::test]
+    async fn search_stops_at_rate_limit() {
+        let server = MockServer::start_async().await;
+
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/2/tweets/search/recent");
+                then.status(429)
+                    .header("x-rate-limit-remaining", "0")
+                    .header("x-rate-limit-limit", "450")
+                    .header("x-rate-limit-reset", RESET)
+                    .body(include_str!("../fixtures/recent_search_rate_limited.json"));
+            })
+            .await;
+
+        match client(&server).search(parameters()).await {
+            Err(Error::RateLimitReached {
+                requests_made,
+                request_limit,
+                ..
+            }) => {
+                assert_eq!(requests_made, 450);
+                assert_eq!(request_limit, 450);
+            }
+            res => panic!("expected the rate limit to be hit, got {:?}", res),
+        }
+    }
+}
@@ -1,23 +1,31 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
 
 use futures_lite::Stream;
-use hyper::{client::HttpConnector, header, Body, Client, Request};
+use hyper::{header, Body, Request};
 use tokio::sync::mpsc::{self};
 use tracing::{error, info};
 
 use crate::{
+    connector::{build_client, HttpsClient, NetworkOptions},
     errors::Error,
-    streams::twitter_stream::TwitterStream,
+    search::RecentSearch,
+    streams::twitter_stream::{Backfill, TwitterStream},
     types::id::*,
     types::*,
     util::{check_rate_limit, validate_response, VecExt},
 };
 
 pub struct FilteredStream {
-    client: hyper::client::Client<hyper_rustls::HttpsConnector<HttpConnector>>,
+    client: HttpsClient,
     tweet_stream: mpsc::Receiver<Tweet>,
     token: String,
+    api_endpoint: String,
     rules: HashMap<RuleId, ActiveRule>,
+    /// The active rules, shared with the stream for backfilling after reconnecting.
+    backfill_rules: Arc<RwLock<Vec<ActiveRule>>>,
     exit_notifier: mpsc::Sender<()>,
 }
 
@@ -41,13 +49,17 @@ impl FilteredStream {
         parameters: StreamParameters,
         buffer_size: usize,
     ) -> Result<Self, Error> {
-        let https = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_native_roots()
-            .https_only()
-            .enable_http1()
-            .build();
+        Self::with_options(token, parameters, buffer_size, &NetworkOptions::default()).await
+    }
 
-        let client = Client::builder().build(https);
+    /// Connects to the stream according to the given network options.
+    pub async fn with_options(
+        token: &str,
+        parameters: StreamParameters,
+        buffer_size: usize,
+        options: &NetworkOptions,
+    ) -> Result<Self, Error> {
+        let client = build_client(options)?;
 
         let token = if token.starts_with("Bearer ") {
             token.to_owned()
@@ -55,12 +67,22 @@ impl FilteredStream {
             format!("Bearer {}", token)
         };
 
+        let api_endpoint = options.api_endpoint();
+        let backfill_rules = Arc::default();
+
+        let backfill = Backfill {
+            search: RecentSearch::from_client(client.clone(), token.clone(), api_endpoint.clone()),
+            rules: Arc::clone(&backfill_rules),
+        };
+
         let (tweet_stream, exit_notifier) = TwitterStream::create(
             "/2/tweets/search/stream",
             token.clone(),
+            api_endpoint.clone(),
             client.clone(),
             parameters,
             buffer_size,
+            Some(backfill),
         )
         .await?;
 
@@ -68,12 +90,15 @@ impl FilteredStream {
             client,
             tweet_stream,
             token,
+            api_endpoint,
             exit_notifier,
             rules: HashMap::new(),
+            backfill_rules,
         };
 
         tracing::info!("Fetching rules...");
         stream.rules = stream.fetch_rules().await?;
+        stream.sync_backfill_rules();
         tracing::info!("Twitter rules set up!");
 
         Ok(stream)
@@ -81,12 +106,9 @@ impl FilteredStream {
 
     async fn fetch_rules(&self) -> Result<HashMap<RuleId, ActiveRule>, Error> {
         let request = Request::get(
-            format!(
-                "{}/2/tweets/search/stream/rules",
-                TwitterStream::API_ENDPOINT
-            )
-            .parse::<hyper::Uri>()
-            .unwrap(),
+            format!("{}/2/tweets/search/stream/rules", self.api_endpoint)
+                .parse::<hyper::Uri>()
+                .unwrap(),
         )
         .header(header::USER_AGENT, TwitterStream::USER_AGENT)
         .header(header::AUTHORIZATION, &self.token)
@@ -167,12 +189,9 @@ impl FilteredStream {
         let update = RuleUpdate::add(rules.to_vec());
 
         let request = Request::post(
-            format!(
-                "{}/2/tweets/search/stream/rules",
-                TwitterStream::API_ENDPOINT
-            )
-            .parse::<hyper::Uri>()
-            .unwrap(),
+            format!("{}/2/tweets/search/stream/rules", self.api_endpoint)
+                .parse::<hyper::Uri>()
+                .unwrap(),
         )
         .header(header::USER_AGENT, TwitterStream::USER_AGENT)
         .header(header::AUTHORIZATION, &self.token)
@@ -216,6 +235,8 @@ impl FilteredStream {
             .map(|r| (r.id, r));
 
         self.rules.extend(new_rules);
+        self.sync_backfill_rules();
+
         Ok(())
     }
 
@@ -228,12 +249,9 @@ impl FilteredStream {
         let update = RuleUpdate::remove(rules.to_vec());
 
         let request = Request::post(
-            format!(
-                "{}/2/tweets/search/stream/rules",
-                TwitterStream::API_ENDPOINT
-            )
-            .parse::<hyper::Uri>()
-            .unwrap(),
+            format!("{}/2/tweets/search/stream/rules", self.api_endpoint)
+                .parse::<hyper::Uri>()
+                .unwrap(),
         )
         .header(header::USER_AGENT, TwitterStream::USER_AGENT)
         .header(header::AUTHORIZATION, &self.token)
@@ -277,11 +295,21 @@ impl FilteredStream {
             }
         }
 
-        Ok(rules
+        let removed_rules = rules
             .iter()
             .filter_map(|r| self.rules.remove(r))
             .map(|r| r.into())
-            .collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        self.sync_backfill_rules();
+
+        Ok(removed_rules)
+    }
+
+    fn sync_backfill_rules(&self) {
+        if let Ok(mut rules) = self.backfill_rules.write() {
+            *rules = self.rules.values().cloned().collect();
+        }
     }
 
     pub async fn validate_rules(&self, rules: &[Rule]) -> Result<(), Error> {
@@ -309,7 +337,7 @@ impl FilteredStream {
         let request = Request::post(
             format!(
                 "{}/2/tweets/search/stream/rules?dry_run=true",
-                TwitterStream::API_ENDPOINT
+                self.api_endpoint
             )
             .parse::<hyper::Uri>()
             .unwrap(),
@@ -1,6 +1,6 @@
 mod filtered_stream;
 mod sampled_stream;
-mod twitter_stream;
+pub(crate) mod twitter_stream;
 
 pub use filtered_stream::FilteredStream;
 pub use sampled_stream::SampledStream;
@@ -1,8 +1,12 @@
 use futures_lite::Stream;
-use hyper::Client;
 use tokio::sync::mpsc;
 
-use crate::{errors::Error, streams::twitter_stream::TwitterStream, types::*};
+use crate::{
+    connector::{build_client, NetworkOptions},
+    errors::Error,
+    streams::twitter_stream::TwitterStream,
+    types::*,
+};
 
 pub struct SampledStream {
     tweet_stream: mpsc::Receiver<Tweet>,
@@ -19,13 +23,17 @@ impl SampledStream {
         parameters: StreamParameters,
         buffer_size: usize,
     ) -> Result<Self, Error> {
-        let https = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_native_roots()
-            .https_only()
-            .enable_http1()
-            .build();
+        Self::with_options(token, parameters, buffer_size, &NetworkOptions::default()).await
+    }
 
-        let client = Client::builder().build(https);
+    /// Connects to the stream according to the given network options.
+    pub async fn with_options(
+        token: &str,
+        parameters: StreamParameters,
+        buffer_size: usize,
+        options: &NetworkOptions,
+    ) -> Result<Self, Error> {
+        let client = build_client(options)?;
 
         let token = if token.starts_with("Bearer ") {
             token.to_owned()
@@ -36,9 +44,11 @@ impl SampledStream {
         let (tweet_stream, exit_notifier) = TwitterStream::create(
             "/2/tweets/sample/stream",
             token,
+            options.api_endpoint(),
             client,
             parameters,
             buffer_size,
+            None,
         )
         .await?;
 
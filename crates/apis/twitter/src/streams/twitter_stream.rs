@@ -1,16 +1,25 @@
-use std::{error::Error as _, io::ErrorKind, time::Duration};
+use std::{
+    collections::{HashSet, VecDeque},
+    error::Error as _,
+    io::ErrorKind,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
-use backoff::ExponentialBackoff;
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use chrono::{DateTime, Timelike, Utc};
 use futures_lite::{Stream, StreamExt};
-use hyper::{body::Bytes, client::HttpConnector, header, Body, Client, Request, Uri};
+use hyper::{body::Bytes, header, Body, Request, Uri};
 use tokio::{
     sync::mpsc::{self, error::TrySendError},
-    time::{error::Elapsed, timeout},
+    time::{error::Elapsed, sleep, timeout},
 };
 use tracing::{debug, error, trace, warn};
 
 use crate::{
+    connector::HttpsClient,
     errors::{Error, ServerError, ValidationError},
+    search::RecentSearch,
     types::*,
     util::{check_rate_limit, try_run_with_config, validate_json_bytes},
 };
@@ -25,10 +34,48 @@ pub(crate) enum MessageType {
     Skip,
 }
 
+/// Searches for the tweets matching the stream rules that were missed while disconnected.
+pub(crate) struct Backfill {
+    pub search: RecentSearch,
+    pub rules: Arc<RwLock<Vec<ActiveRule>>>,
+}
+
+/// The most recently emitted tweets, so that a tweet found by both
+/// the backfill and the stream is only emitted once.
+#[derive(Debug, Default)]
+struct RecentTweets {
+    order: VecDeque<TweetId>,
+    ids: HashSet<TweetId>,
+}
+
+impl RecentTweets {
+    const CAPACITY: usize = 1024;
+
+    /// Returns `false` if the tweet has already been seen.
+    fn insert(&mut self, id: TweetId) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+
+        self.order.push_back(id);
+
+        if self.order.len() > Self::CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+
+        true
+    }
+}
+
 pub(crate) struct TwitterStream {
-    client: Client<hyper_rustls::HttpsConnector<HttpConnector>>,
+    client: HttpsClient,
     token: String,
+    api_endpoint: String,
     endpoint: &'static str,
+    backfill: Option<Backfill>,
+    recent_tweets: RecentTweets,
 }
 
 impl TwitterStream {
@@ -36,17 +83,25 @@ impl TwitterStream {
     pub const USER_AGENT: &'static str =
         concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
+    /// How far before the last received message the backfill starts searching.
+    const BACKFILL_MARGIN: i64 = 30;
+
     pub async fn create(
         endpoint: &'static str,
         token: String,
-        client: Client<hyper_rustls::HttpsConnector<HttpConnector>>,
+        api_endpoint: String,
+        client: HttpsClient,
         parameters: StreamParameters,
         buffer_size: usize,
+        backfill: Option<Backfill>,
     ) -> Result<(mpsc::Receiver<Tweet>, mpsc::Sender<()>), Error> {
         let mut stream = Self {
             client,
             token,
+            api_endpoint,
             endpoint,
+            backfill,
+            recent_tweets: RecentTweets::default(),
         };
 
         let (tx, rx) = mpsc::channel(buffer_size);
@@ -73,7 +128,7 @@ impl TwitterStream {
         try_run_with_config(
             || async {
                 let request = Request::get(
-                    format!("{}{}?{}", Self::API_ENDPOINT, self.endpoint, query)
+                    format!("{}{}?{}", self.api_endpoint, self.endpoint, query)
                         .parse::<Uri>()
                         .unwrap(),
                 )
@@ -126,13 +181,42 @@ impl TwitterStream {
         mut exit_receiver: mpsc::Receiver<()>,
         parameters: StreamParameters,
     ) -> Result<(), Error> {
+        let mut reconnect_backoff = ExponentialBackoff {
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(5 * 60),
+            max_elapsed_time: None,
+            ..ExponentialBackoff::default()
+        };
+
+        let mut last_received: Option<DateTime<Utc>> = None;
+
         loop {
             let mut stream = Box::pin(self.connect(&parameters).await?);
             debug!("Connected to Twitter stream!");
 
+            if let Some(since) = last_received {
+                for tweet in self.backfill(since, &parameters).await {
+                    if !self.recent_tweets.insert(tweet.data.id) {
+                        continue;
+                    }
+
+                    if sender.send(tweet).await.is_err() {
+                        debug!("Stream receiver dropped, halting stream.");
+                        return Ok(());
+                    }
+                }
+            }
+
+            last_received = Some(Utc::now());
+
             loop {
                 tokio::select! {
                     res = timeout(Duration::from_secs(30), stream.next()) => {
+                        if let Ok(Some(Ok(_))) = &res {
+                            last_received = Some(Utc::now());
+                            reconnect_backoff.reset();
+                        }
+
                         let tweet = match self.handle_possible_message(res).await {
                             MessageType::Tweet(t) => {
                                 trace!("Tweet successfully parsed!");
@@ -163,6 +247,11 @@ impl TwitterStream {
                             }
                         };
 
+                        if !self.recent_tweets.insert(tweet.data.id) {
+                            trace!("Tweet already emitted, skipping.");
+                            continue;
+                        }
+
                         match sender.try_send(*tweet) {
                             Ok(_) => (),
                             Err(TrySendError::Full(_)) => {
@@ -181,9 +270,70 @@ impl TwitterStream {
                     }
                 }
             }
+
+            let delay = reconnect_backoff
+                .next_backoff()
+                .unwrap_or(reconnect_backoff.max_interval);
+
+            debug!(?delay, "Waiting before reconnecting to Twitter stream...");
+
+            tokio::select! {
+                _ = sleep(delay) => (),
+                _ = exit_receiver.recv() => {
+                    return Ok(());
+                }
+            }
         }
     }
 
+    /// Searches for the tweets posted since the stream was last known to be connected,
+    /// oldest first.
+    async fn backfill(&self, since: DateTime<Utc>, parameters: &StreamParameters) -> Vec<Tweet> {
+        let backfill = match &self.backfill {
+            Some(b) => b,
+            None => return Vec::new(),
+        };
+
+        let start_time = since - chrono::Duration::seconds(Self::BACKFILL_MARGIN);
+        let start_time = start_time.with_nanosecond(0).unwrap_or(start_time);
+
+        let rules = match backfill.rules.read() {
+            Ok(rules) => rules.clone(),
+            Err(_) => return Vec::new(),
+        };
+
+        debug!(%start_time, rule_count = rules.len(), "Backfilling missed tweets.");
+
+        let mut tweets = Vec::new();
+
+        for rule in rules {
+            let result = backfill
+                .search
+                .search(RecentSearchParameters {
+                    query: rule.value.clone(),
+                    start_time: Some(start_time),
+                    max_results: Some(RecentSearch::MAX_RESULTS_PER_PAGE),
+                    fields: parameters.clone(),
+                    ..Default::default()
+                })
+                .await;
+
+            match result {
+                Ok(found) => tweets.extend(found.into_iter().map(|mut tweet| {
+                    tweet.matching_rules.push(MatchingRule {
+                        id: rule.id,
+                        tag: rule.tag.clone(),
+                    });
+                    tweet
+                })),
+                Err(e) => warn!(error = ?e, rule = ?rule.value, "Failed to backfill tweets."),
+            }
+        }
+
+        tweets.sort_unstable_by_key(|t| t.data.id);
+        tweets
+    }
+
     async fn handle_possible_message(
         &self,
         message: Result<Option<Result<Bytes, hyper::Error>>, Elapsed>,
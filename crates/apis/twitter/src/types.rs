@@ -128,6 +128,7 @@ pub enum MediaField {
     Width,
     AltText,
     Url,
+    Variants,
 }
 
 #[non_exhaustive]
@@ -212,6 +213,29 @@ pub struct RecentTweetCountParameters {
     pub until_id: Option<TweetId>,
 }
 
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct RecentSearchParameters {
+    pub query: RuleString,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<DateTime<Utc>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since_id: Option<TweetId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until_id: Option<TweetId>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_token: Option<String>,
+
+    #[serde(flatten)]
+    pub fields: StreamParameters,
+}
+
 #[cfg(feature = "academic_research_track")]
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct AllTweetCountParameters {
@@ -387,6 +411,10 @@ pub struct Tweet {
 }
 
 impl Tweet {
+    pub fn attached_media(&self) -> impl Iterator<Item = &Media> {
+        self.includes.iter().flat_map(|i| i.media.iter())
+    }
+
     pub fn attached_photos(&self) -> impl Iterator<Item = &str> {
         self.includes
             .iter()
@@ -848,6 +876,10 @@ pub struct Media {
     pub width: Option<u32>,
     #[serde(default)]
     pub alt_text: Option<String>,
+    #[serde(default)]
+    pub preview_image_url: Option<String>,
+    #[serde(default)]
+    pub variants: Vec<MediaVariant>,
 
     #[cfg(feature = "metrics")]
     #[serde(default)]
@@ -863,6 +895,25 @@ pub struct Media {
     pub public_metrics: Option<ViewCount>,
 }
 
+impl Media {
+    /// Returns the highest quality MP4 variant of a video or GIF, if any.
+    pub fn best_mp4_variant(&self) -> Option<&str> {
+        self.variants
+            .iter()
+            .filter(|v| v.content_type == "video/mp4")
+            .max_by_key(|v| v.bit_rate.unwrap_or_default())
+            .map(|v| v.url.as_str())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MediaVariant {
+    pub content_type: String,
+    pub url: String,
+    #[serde(default)]
+    pub bit_rate: Option<u64>,
+}
+
 #[cfg(feature = "metrics")]
 #[derive(Debug, Deserialize)]
 pub struct MediaMetrics {
@@ -1043,6 +1094,23 @@ pub struct MatchingRule {
     pub tag: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub(crate) struct RecentSearchResponse {
+    #[serde(default = "Vec::new")]
+    pub data: Vec<TweetInfo>,
+    #[serde(default)]
+    pub includes: Option<Expansions>,
+    pub meta: RecentSearchResponseMeta,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub(crate) struct RecentSearchResponseMeta {
+    pub result_count: usize,
+    #[serde(default)]
+    pub next_token: Option<String>,
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 pub(crate) struct RuleRequestResponseMeta {
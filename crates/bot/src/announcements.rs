@@ -0,0 +1,459 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Months, TimeZone, Utc};
+use itertools::Itertools;
+use poise::ChoiceParameter;
+use rusqlite::Row;
+use serde::Deserialize;
+use serenity::{
+    client::Context as Ctx,
+    model::{
+        id::{ChannelId, GuildId, RoleId, UserId},
+        mention::Mention,
+    },
+};
+use tokio::{sync::Notify, time::sleep};
+use tracing::{error, info, instrument, warn};
+use utility::{
+    config::{Database, DatabaseHandle},
+    feature_flags::{self, Feature},
+    here,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    #[name = "Once"]
+    Once,
+    #[name = "Daily"]
+    Daily,
+    #[name = "Weekly"]
+    Weekly,
+    #[name = "Monthly"]
+    Monthly,
+}
+
+impl Recurrence {
+    /// When a repeating announcement that was first due at `first` is due next after `after`.
+    /// Months are counted from the first time, so that announcements on the 31st are posted
+    /// on the last day of shorter months without moving to an earlier day for good.
+    pub fn next_after(self, first: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let nth = |n: u32| match self {
+            Self::Once => None,
+            Self::Daily => Some(first + Duration::days(n.into())),
+            Self::Weekly => Some(first + Duration::weeks(n.into())),
+            Self::Monthly => first.checked_add_months(Months::new(n)),
+        };
+
+        // Starts from an estimate, so that old announcements don't count every day since.
+        let elapsed = u32::try_from((after - first).num_days()).unwrap_or(0);
+        let mut n = match self {
+            Self::Once => return None,
+            Self::Daily => elapsed,
+            Self::Weekly => elapsed / 7,
+            Self::Monthly => elapsed / 31,
+        }
+        .max(1);
+
+        loop {
+            match nth(n) {
+                Some(time) if time <= after => n += 1,
+                next => return next,
+            }
+        }
+    }
+
+    fn as_i64(self) -> i64 {
+        match self {
+            Self::Once => 0,
+            Self::Daily => 1,
+            Self::Weekly => 2,
+            Self::Monthly => 3,
+        }
+    }
+
+    fn from_i64(value: i64) -> Self {
+        match value {
+            1 => Self::Daily,
+            2 => Self::Weekly,
+            3 => Self::Monthly,
+            _ => Self::Once,
+        }
+    }
+}
+
+/// A message moderators scheduled to be posted, kept in the database until it's been delivered.
+#[derive(Debug, Clone)]
+pub struct Announcement {
+    pub id: i64,
+    pub guild: GuildId,
+    pub channel: ChannelId,
+    pub author: UserId,
+    pub content: String,
+    /// The roles that are pinged, no other mentions in the content ping anyone.
+    pub roles: Vec<RoleId>,
+    pub next_at: DateTime<Utc>,
+    /// When it was first due, which repeating announcements are scheduled from.
+    pub first_at: DateTime<Utc>,
+    pub recurrence: Recurrence,
+}
+
+impl Announcement {
+    const TABLE_NAME: &'static str = "Announcements";
+    const COLUMNS: &'static str =
+        "id, guild_id, channel_id, author_id, content, roles, next_at, recurrence, first_at";
+    const IDLE_WAIT: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+    /// How many minutes to wait before trying to post an announcement that failed again.
+    const RETRY_DELAY_MINUTES: i64 = 5;
+    /// How many times an announcement is tried before that time is skipped.
+    const MAX_ATTEMPTS: u32 = 5;
+
+    pub fn create_table(handle: &DatabaseHandle) -> anyhow::Result<()> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("id", "INTEGER", Some("PRIMARY KEY AUTOINCREMENT")),
+                    ("guild_id", "INTEGER", Some("NOT NULL")),
+                    ("channel_id", "INTEGER", Some("NOT NULL")),
+                    ("author_id", "INTEGER", Some("NOT NULL")),
+                    ("content", "TEXT", Some("NOT NULL")),
+                    ("roles", "TEXT", Some("NOT NULL")),
+                    ("next_at", "INTEGER", Some("NOT NULL")),
+                    ("recurrence", "INTEGER", Some("NOT NULL")),
+                    ("first_at", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    /// Saves the announcement, returning its ID.
+    pub fn insert(&self, handle: &DatabaseHandle) -> anyhow::Result<i64> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "INSERT INTO {} (guild_id, channel_id, author_id, content, roles, next_at, recurrence, first_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![
+                        self.guild.0,
+                        self.channel.0,
+                        self.author.0,
+                        self.content,
+                        self.roles.iter().map(|r| r.0.to_string()).join(","),
+                        self.next_at.timestamp(),
+                        self.recurrence.as_i64(),
+                        self.first_at.timestamp(),
+                    ],
+                )
+                .context(here!())?;
+
+                Ok(h.last_insert_rowid())
+            }
+        }
+    }
+
+    /// The announcements scheduled in the guild, soonest first.
+    pub fn list(handle: &DatabaseHandle, guild: GuildId) -> anyhow::Result<Vec<Self>> {
+        Self::query(
+            handle,
+            "WHERE guild_id = ? ORDER BY next_at",
+            rusqlite::params![guild.0],
+        )
+    }
+
+    /// Cancels an announcement in the guild, returning whether it existed.
+    pub fn cancel(handle: &DatabaseHandle, guild: GuildId, id: i64) -> anyhow::Result<bool> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "DELETE FROM {} WHERE id = ? AND guild_id = ?",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![id, guild.0],
+                )
+                .map(|n| n > 0)
+                .context(here!()),
+        }
+    }
+
+    fn due(handle: &DatabaseHandle, now: DateTime<Utc>) -> anyhow::Result<Vec<Self>> {
+        Self::query(
+            handle,
+            "WHERE next_at <= ? ORDER BY next_at",
+            rusqlite::params![now.timestamp()],
+        )
+    }
+
+    /// When the next announcement after `now` is due. The ones that are already due
+    /// are left out, since they're waiting to be tried again.
+    fn next_due_after(
+        handle: &DatabaseHandle,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let next: Option<i64> = h
+                    .query_row(
+                        &format!(
+                            "SELECT MIN(next_at) FROM {} WHERE next_at > ?",
+                            Self::TABLE_NAME
+                        ),
+                        [now.timestamp()],
+                        |row| row.get(0),
+                    )
+                    .context(here!())?;
+
+                Ok(next.and_then(|t| Utc.timestamp_opt(t, 0).single()))
+            }
+        }
+    }
+
+    /// Moves a repeating announcement to its next time after `now`, skipping the ones
+    /// missed while the bot was offline, or removes it if it doesn't repeat.
+    fn advance(&self, handle: &DatabaseHandle, now: DateTime<Utc>) -> anyhow::Result<()> {
+        let next_at = self
+            .recurrence
+            .next_after(self.first_at, now.max(self.next_at));
+
+        match (handle, next_at) {
+            (DatabaseHandle::SQLite(h), Some(next_at)) => h
+                .execute(
+                    &format!("UPDATE {} SET next_at = ? WHERE id = ?", Self::TABLE_NAME),
+                    rusqlite::params![next_at.timestamp(), self.id],
+                )
+                .context(here!())?,
+            (DatabaseHandle::SQLite(h), None) => h
+                .execute(
+                    &format!("DELETE FROM {} WHERE id = ?", Self::TABLE_NAME),
+                    [self.id],
+                )
+                .context(here!())?,
+        };
+
+        Ok(())
+    }
+
+    fn query(
+        handle: &DatabaseHandle,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> anyhow::Result<Vec<Self>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT {} FROM {} {filter}",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let announcements = stmt
+                    .query_map(params, Self::from_row)
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                Ok(announcements)
+            }
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            guild: GuildId(row.get(1)?),
+            channel: ChannelId(row.get(2)?),
+            author: UserId(row.get(3)?),
+            content: row.get(4)?,
+            roles: row
+                .get::<_, String>(5)?
+                .split(',')
+                .filter_map(|r| r.parse().ok().map(RoleId))
+                .collect(),
+            next_at: Utc
+                .timestamp_opt(row.get(6)?, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            recurrence: Recurrence::from_i64(row.get(7)?),
+            first_at: Utc
+                .timestamp_opt(row.get(8)?, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+        })
+    }
+
+    async fn deliver(&self, ctx: &Ctx) -> anyhow::Result<()> {
+        let pings = self
+            .roles
+            .iter()
+            .map(|r| Mention::from(*r).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let content = if pings.is_empty() {
+            self.content.clone()
+        } else {
+            format!("{pings}\n{}", self.content)
+        };
+
+        self.channel
+            .send_message(&ctx.http, |m| {
+                m.content(content)
+                    .allowed_mentions(|am| am.empty_parse().roles(self.roles.clone()))
+            })
+            .await
+            .context(here!())?;
+
+        Ok(())
+    }
+}
+
+/// Posts announcements when they're due. Commands that change the schedule
+/// wake it up through `changed`, so that it knows when the next one is due.
+///
+/// Announcements that fail to be posted are tried again a few times before that time is
+/// skipped, and errors from the database are logged, so that one broken announcement
+/// doesn't stop the rest.
+#[instrument(skip(ctx, database, changed))]
+pub async fn scheduler(ctx: Ctx, database: &Database, changed: Arc<Notify>) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+    // How many times announcements have failed to be posted, and when to try again.
+    let mut retries: HashMap<i64, (u32, DateTime<Utc>)> = HashMap::new();
+
+    loop {
+        let now = Utc::now();
+
+        let due = match Announcement::due(&handle, now) {
+            Ok(due) => due,
+            Err(e) => {
+                error!(?e, "Failed to get the announcements that are due!");
+                Vec::new()
+            }
+        };
+
+        // Announcements that were cancelled or posted since don't need to be tried again.
+        retries.retain(|id, _| due.iter().any(|a| a.id == *id));
+
+        for announcement in due {
+            let id = announcement.id;
+            let attempts = match retries.get(&id) {
+                Some((_, at)) if *at > now => continue,
+                Some((attempts, _)) => *attempts,
+                None => 0,
+            };
+
+            // Announcements that are due while the feature is disabled are skipped, not delayed,
+            // and ones that were given up on are only moved to their next time.
+            if attempts < Announcement::MAX_ATTEMPTS
+                && feature_flags::is_enabled(announcement.guild, Feature::Announcements)
+            {
+                info!(id, channel = %announcement.channel, "Posting announcement.");
+
+                if let Err(e) = announcement.deliver(&ctx).await {
+                    let attempts = attempts + 1;
+
+                    if attempts < Announcement::MAX_ATTEMPTS {
+                        warn!(
+                            ?e,
+                            id, attempts, "Failed to post announcement, trying again later."
+                        );
+                        retries.insert(
+                            id,
+                            (
+                                attempts,
+                                now + Duration::minutes(Announcement::RETRY_DELAY_MINUTES),
+                            ),
+                        );
+                        continue;
+                    }
+
+                    error!(
+                        ?e,
+                        id, "Failed to post announcement, skipping it this time!"
+                    );
+                }
+            }
+
+            retries.remove(&id);
+
+            if let Err(e) = announcement.advance(&handle, now) {
+                error!(?e, id, "Failed to move announcement to its next time!");
+                // It has already been posted, so it's only moved when trying again.
+                retries.insert(
+                    id,
+                    (
+                        Announcement::MAX_ATTEMPTS,
+                        now + Duration::minutes(Announcement::RETRY_DELAY_MINUTES),
+                    ),
+                );
+            }
+        }
+
+        let next_due = match Announcement::next_due_after(&handle, now) {
+            Ok(next_due) => next_due,
+            Err(e) => {
+                error!(?e, "Failed to get when the next announcement is due!");
+                Some(now + Duration::minutes(Announcement::RETRY_DELAY_MINUTES))
+            }
+        };
+
+        let next_retry = retries.values().map(|(_, at)| *at).min();
+
+        let wait = match next_due.into_iter().chain(next_retry).min() {
+            Some(next) => (next - Utc::now()).to_std().unwrap_or_default(),
+            // Nothing is scheduled, so only a new announcement can wake it up.
+            None => Announcement::IDLE_WAIT,
+        };
+
+        tokio::select! {
+            _ = sleep(wait) => {}
+            _ = changed.notified() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monthly_announcements_keep_their_day() {
+        let first = Utc.with_ymd_and_hms(2024, 1, 31, 18, 0, 0).unwrap();
+
+        let feb = Recurrence::Monthly.next_after(first, first).unwrap();
+        assert_eq!(feb, Utc.with_ymd_and_hms(2024, 2, 29, 18, 0, 0).unwrap());
+
+        let mar = Recurrence::Monthly.next_after(first, feb).unwrap();
+        assert_eq!(mar, Utc.with_ymd_and_hms(2024, 3, 31, 18, 0, 0).unwrap());
+
+        // Times missed while the bot was offline are skipped.
+        let later = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            Recurrence::Monthly.next_after(first, later),
+            Some(Utc.with_ymd_and_hms(2024, 6, 30, 18, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn daily_and_weekly_announcements_skip_missed_times() {
+        let first = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 13, 0, 0).unwrap();
+
+        assert_eq!(
+            Recurrence::Daily.next_after(first, now),
+            Some(Utc.with_ymd_and_hms(2024, 1, 11, 12, 0, 0).unwrap())
+        );
+        assert_eq!(
+            Recurrence::Weekly.next_after(first, now),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap())
+        );
+        assert_eq!(Recurrence::Once.next_after(first, now), None);
+    }
+}
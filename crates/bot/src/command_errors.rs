@@ -0,0 +1,206 @@
+use std::fmt::Display;
+
+use nanorand::Rng;
+use poise::serenity_prelude::{InteractionResponseType, MessageComponentInteraction, Permissions};
+use serenity::{
+    client::Context as Ctx, http::error::Error as HttpError, model::error::Error as ModelError,
+};
+use tracing::error;
+
+use crate::DataWrapper;
+
+/// Errors with a known cause, which are explained to the user rather than reported as bugs.
+/// Commands can return these directly, and they're also recognized further down the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandError {
+    PermissionDenied,
+    QuotaExceeded,
+    NotInVoiceChannel,
+    /// The command can only be used in a server.
+    GuildOnly,
+    /// The command can only be used in an age-restricted channel.
+    NsfwOnly,
+    /// The command ran for longer than it's allowed to, and was cancelled.
+    TimedOut,
+}
+
+impl CommandError {
+    /// Finds the first known error among the causes of `error`.
+    pub fn find(error: &anyhow::Error) -> Option<Self> {
+        error.chain().find_map(|cause| {
+            if let Some(e) = cause.downcast_ref::<Self>() {
+                return Some(*e);
+            }
+
+            #[cfg(feature = "translation")]
+            if let Some(deepl::Error::QuotaExceeded) = cause.downcast_ref::<deepl::Error>() {
+                return Some(Self::QuotaExceeded);
+            }
+
+            match cause.downcast_ref::<serenity::Error>()? {
+                serenity::Error::Model(ModelError::InvalidPermissions(_)) => {
+                    Some(Self::PermissionDenied)
+                }
+                serenity::Error::Http(e) => match e.as_ref() {
+                    HttpError::UnsuccessfulRequest(r) if r.status_code.as_u16() == 403 => {
+                        Some(Self::PermissionDenied)
+                    }
+                    _ => None,
+                },
+                _ => None,
+            }
+        })
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::PermissionDenied => "Missing permissions.",
+            Self::QuotaExceeded => "API quota exceeded.",
+            Self::NotInVoiceChannel => "User is not in a voice channel.",
+            Self::GuildOnly => "Command is only available in servers.",
+            Self::NsfwOnly => "Command is only available in age-restricted channels.",
+            Self::TimedOut => "Command timed out.",
+        })
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// The languages replies are translated to, picked from the user's Discord locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReplyLanguage {
+    English,
+    Japanese,
+}
+
+impl ReplyLanguage {
+    fn from_locale(locale: Option<&str>) -> Self {
+        match locale {
+            Some(l) if l.starts_with("ja") => Self::Japanese,
+            _ => Self::English,
+        }
+    }
+
+    fn describe(self, error: Option<CommandError>) -> &'static str {
+        use CommandError::*;
+        use ReplyLanguage::*;
+
+        match (self, error) {
+            (English, Some(PermissionDenied)) => {
+                "I don't have the permissions needed to do that here."
+            }
+            (English, Some(QuotaExceeded)) => {
+                "The quota for that service has been used up, please try again later."
+            }
+            (English, Some(NotInVoiceChannel)) => "You need to be in a voice channel to do that.",
+            (English, Some(GuildOnly)) => "That can only be done in a server.",
+            (English, Some(NsfwOnly)) => "That can only be done in an age-restricted channel.",
+            (English, Some(TimedOut)) => "That took too long, so it was cancelled.",
+            (English, None) => "Something went wrong, please try again later.",
+
+            (Japanese, Some(PermissionDenied)) => "この操作に必要な権限がありません。",
+            (Japanese, Some(QuotaExceeded)) => {
+                "利用上限に達しました。しばらくしてからもう一度お試しください。"
+            }
+            (Japanese, Some(NotInVoiceChannel)) => {
+                "ボイスチャンネルに参加してからもう一度お試しください。"
+            }
+            (Japanese, Some(GuildOnly)) => "この操作はサーバー内でのみ使用できます。",
+            (Japanese, Some(NsfwOnly)) => "この操作は年齢制限チャンネルでのみ使用できます。",
+            (Japanese, Some(TimedOut)) => "時間がかかりすぎたため、キャンセルされました。",
+            (Japanese, None) => "エラーが発生しました。しばらくしてからもう一度お試しください。",
+        }
+    }
+
+    fn missing_permissions(self) -> &'static str {
+        match self {
+            Self::English => "Missing permissions",
+            Self::Japanese => "不足している権限",
+        }
+    }
+
+    fn error_id(self) -> &'static str {
+        match self {
+            Self::English => "Error ID",
+            Self::Japanese => "エラーID",
+        }
+    }
+}
+
+/// Logs the error with a correlation ID, and returns the reply to show the user,
+/// which includes the ID so the logs can be found if the error is reported.
+fn report(error: &anyhow::Error, source: &str, locale: Option<&str>) -> String {
+    let correlation_id = format!("{:08x}", nanorand::tls_rng().generate::<u32>());
+    let known = CommandError::find(error);
+
+    error!(%source, %correlation_id, ?known, "Command error: {:?}", error);
+
+    let language = ReplyLanguage::from_locale(locale);
+
+    format!(
+        "{} ({}: `{correlation_id}`)",
+        language.describe(known),
+        language.error_id()
+    )
+}
+
+pub async fn handle_command_error(
+    ctx: poise::Context<'_, DataWrapper, anyhow::Error>,
+    error: anyhow::Error,
+) {
+    let reply = report(&error, &ctx.command().qualified_name, ctx.locale());
+
+    if let Err(e) = ctx.send(|m| m.ephemeral(true).content(reply)).await {
+        error!(?e, "Failed to reply with command error!");
+    }
+}
+
+/// Explains to the user why a command couldn't be used, such as when one of its checks failed.
+/// These aren't bugs, so they're neither logged as errors nor given a correlation ID.
+pub async fn handle_check_failure(
+    ctx: poise::Context<'_, DataWrapper, anyhow::Error>,
+    reason: CommandError,
+    missing_permissions: Option<Permissions>,
+) {
+    let language = ReplyLanguage::from_locale(ctx.locale());
+    let mut reply = language.describe(Some(reason)).to_owned();
+
+    if let Some(permissions) = missing_permissions.filter(|p| !p.is_empty()) {
+        reply += &format!(
+            " ({}: {})",
+            language.missing_permissions(),
+            permissions.get_permission_names().join(", ")
+        );
+    }
+
+    if let Err(e) = ctx.send(|m| m.ephemeral(true).content(reply)).await {
+        error!(?e, "Failed to reply with check failure!");
+    }
+}
+
+pub async fn handle_component_error(
+    ctx: &Ctx,
+    component: &MessageComponentInteraction,
+    error: anyhow::Error,
+) {
+    let reply = report(&error, &component.data.custom_id, Some(&component.locale));
+
+    let responded = component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.ephemeral(true).content(&reply))
+        })
+        .await;
+
+    // The interaction may already have been responded to before the error happened.
+    if responded.is_err() {
+        if let Err(e) = component
+            .create_followup_message(&ctx.http, |m| m.ephemeral(true).content(&reply))
+            .await
+        {
+            error!(?e, "Failed to reply with interaction error!");
+        }
+    }
+}
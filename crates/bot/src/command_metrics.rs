@@ -0,0 +1,287 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Write,
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use tracing::{error, info};
+
+use crate::commands::Context;
+
+/// How long every command invocation since the bot started took,
+/// shared between the command hooks and the `/debug commands` command.
+pub static COMMAND_METRICS: Lazy<CommandMetrics> = Lazy::new(CommandMetrics::default);
+
+#[derive(Debug, Clone)]
+struct CommandSample {
+    command: String,
+    started_at: DateTime<Utc>,
+    /// From the user invoking the command until it started running.
+    queue_wait: Duration,
+    duration: Duration,
+    success: bool,
+}
+
+/// When the command started running, kept in the invocation data until it finishes.
+struct CommandStart(DateTime<Utc>);
+
+#[derive(Debug, Clone)]
+pub struct CommandSummary {
+    pub command: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub mean: Duration,
+    pub max: Duration,
+    pub mean_queue_wait: Duration,
+}
+
+impl CommandSummary {
+    #[must_use]
+    pub fn failure_rate(&self) -> f64 {
+        self.failures as f64 / self.runs as f64
+    }
+}
+
+/// Everything a command has done since the bot started, for the metrics exporter.
+#[derive(Debug, Default, Clone, Copy)]
+struct CommandTotals {
+    successes: u64,
+    failures: u64,
+    duration_seconds: f64,
+    queue_wait_seconds: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct CommandMetrics {
+    samples: Mutex<VecDeque<CommandSample>>,
+    totals: Mutex<BTreeMap<String, CommandTotals>>,
+}
+
+impl CommandMetrics {
+    const MAX_SAMPLES: usize = 10_000;
+
+    /// Samples older than this are dropped.
+    fn retention() -> Duration {
+        Duration::hours(24)
+    }
+
+    fn record(&self, sample: CommandSample) {
+        info!(
+            target: "metrics",
+            command = %sample.command,
+            duration_ms = sample.duration.num_milliseconds(),
+            queue_wait_ms = sample.queue_wait.num_milliseconds(),
+            success = sample.success,
+            "Command executed."
+        );
+
+        match self.totals.lock() {
+            Ok(mut totals) => {
+                let totals = totals.entry(sample.command.clone()).or_default();
+
+                if sample.success {
+                    totals.successes += 1;
+                } else {
+                    totals.failures += 1;
+                }
+
+                totals.duration_seconds += seconds(sample.duration);
+                totals.queue_wait_seconds += seconds(sample.queue_wait);
+            }
+            Err(e) => error!("Command metrics lock was poisoned: {:?}", e),
+        }
+
+        let mut samples = match self.samples.lock() {
+            Ok(samples) => samples,
+            Err(e) => {
+                error!("Command metrics lock was poisoned: {:?}", e);
+                return;
+            }
+        };
+
+        let oldest = sample.started_at - Self::retention();
+        samples.push_back(sample);
+
+        while samples.len() > Self::MAX_SAMPLES
+            || matches!(samples.front(), Some(s) if s.started_at < oldest)
+        {
+            samples.pop_front();
+        }
+    }
+
+    /// Every command run since `since`, in no particular order.
+    pub fn summaries(&self, since: DateTime<Utc>) -> Vec<CommandSummary> {
+        let samples = match self.samples.lock() {
+            Ok(samples) => samples,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut commands = HashMap::<&str, Vec<&CommandSample>>::new();
+
+        for sample in samples.iter().filter(|s| s.started_at >= since) {
+            commands.entry(&sample.command).or_default().push(sample);
+        }
+
+        commands
+            .into_iter()
+            .map(|(command, samples)| {
+                let runs = samples.len();
+                let total = |f: fn(&CommandSample) -> Duration| {
+                    samples.iter().fold(Duration::zero(), |acc, s| acc + f(s))
+                };
+
+                CommandSummary {
+                    command: command.to_owned(),
+                    runs,
+                    failures: samples.iter().filter(|s| !s.success).count(),
+                    mean: total(|s| s.duration) / runs as i32,
+                    max: samples
+                        .iter()
+                        .map(|s| s.duration)
+                        .max()
+                        .unwrap_or_else(Duration::zero),
+                    mean_queue_wait: total(|s| s.queue_wait) / runs as i32,
+                }
+            })
+            .collect()
+    }
+
+    /// The totals of every command since the bot started, in the Prometheus text format.
+    pub fn prometheus(&self) -> String {
+        const RUNS: &str = "holo_bot_command_runs_total";
+        const DURATION: &str = "holo_bot_command_duration_seconds_total";
+        const QUEUE_WAIT: &str = "holo_bot_command_queue_wait_seconds_total";
+
+        let totals = match self.totals.lock() {
+            Ok(totals) => totals.clone(),
+            Err(_) => return String::new(),
+        };
+
+        let mut output = String::new();
+        let labels = totals
+            .iter()
+            .map(|(command, totals)| (format!("command=\"{}\"", escape_label(command)), totals))
+            .collect::<Vec<_>>();
+
+        let _ = writeln!(
+            output,
+            "# HELP {RUNS} Commands run since the bot started.\n# TYPE {RUNS} counter"
+        );
+
+        for (labels, totals) in &labels {
+            let _ = writeln!(
+                output,
+                "{RUNS}{{{labels},outcome=\"success\"}} {}",
+                totals.successes
+            );
+            let _ = writeln!(
+                output,
+                "{RUNS}{{{labels},outcome=\"failure\"}} {}",
+                totals.failures
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP {DURATION} Time spent running commands.\n# TYPE {DURATION} counter"
+        );
+
+        for (labels, totals) in &labels {
+            let _ = writeln!(output, "{DURATION}{{{labels}}} {}", totals.duration_seconds);
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP {QUEUE_WAIT} Time between commands being used and starting to run.\n\
+             # TYPE {QUEUE_WAIT} counter"
+        );
+
+        for (labels, totals) in &labels {
+            let _ = writeln!(
+                output,
+                "{QUEUE_WAIT}{{{labels}}} {}",
+                totals.queue_wait_seconds
+            );
+        }
+
+        output
+    }
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.to_std().map_or(0.0, |d| d.as_secs_f64())
+}
+
+/// Escapes a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub async fn before_command(ctx: Context<'_>) {
+    ctx.set_invocation_data(CommandStart(Utc::now())).await;
+}
+
+pub async fn after_command(ctx: Context<'_>, success: bool) {
+    let started_at = match ctx.invocation_data::<CommandStart>().await {
+        Some(start) => start.0,
+        None => return,
+    };
+
+    let invoked_at = *ctx.created_at();
+
+    COMMAND_METRICS.record(CommandSample {
+        command: ctx.command().qualified_name.clone(),
+        started_at,
+        queue_wait: (started_at - invoked_at).max(Duration::zero()),
+        duration: Utc::now() - started_at,
+        success,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_are_exported_per_command() {
+        let metrics = CommandMetrics::default();
+        let started_at = Utc::now();
+
+        for (command, success) in [
+            ("timezone set", true),
+            ("timezone set", false),
+            ("quote", true),
+        ] {
+            metrics.record(CommandSample {
+                command: command.to_owned(),
+                started_at,
+                queue_wait: Duration::milliseconds(250),
+                duration: Duration::seconds(2),
+                success,
+            });
+        }
+
+        let exported = metrics.prometheus();
+
+        for line in [
+            "# TYPE holo_bot_command_runs_total counter",
+            "holo_bot_command_runs_total{command=\"timezone set\",outcome=\"success\"} 1",
+            "holo_bot_command_runs_total{command=\"timezone set\",outcome=\"failure\"} 1",
+            "holo_bot_command_runs_total{command=\"quote\",outcome=\"failure\"} 0",
+            "holo_bot_command_duration_seconds_total{command=\"timezone set\"} 4",
+            "holo_bot_command_queue_wait_seconds_total{command=\"quote\"} 0.25",
+        ] {
+            assert!(exported.lines().any(|l| l == line), "{line}\n{exported}");
+        }
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label("a \"b\"\\\n"), "a \\\"b\\\"\\\\\\n");
+    }
+}
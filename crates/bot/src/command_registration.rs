@@ -0,0 +1,252 @@
+use anyhow::{bail, Context};
+use poise::serenity_prelude::CreateApplicationCommands;
+use serenity::{
+    http::Http,
+    json::Value,
+    model::{application::command::Command as RegisteredCommand, id::GuildId},
+};
+use tracing::info;
+use utility::{
+    config::{CommandRegistrationConfig, CommandScope},
+    here,
+};
+
+use crate::DataWrapper;
+
+type Command = poise::Command<DataWrapper, anyhow::Error>;
+
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_DESCRIPTION_LENGTH: usize = 100;
+const MAX_OPTIONS: usize = 25;
+const MAX_CHOICES: usize = 25;
+
+/// Registers the global commands, replacing every global command registered before.
+pub async fn register_global(
+    http: &Http,
+    config: &CommandRegistrationConfig,
+    commands: &[Command],
+) -> anyhow::Result<usize> {
+    // Every command is registered in the development guild instead.
+    let commands = match config.dev_guild {
+        Some(_) => Vec::new(),
+        None => in_scope(config, commands, CommandScope::Global),
+    };
+
+    let builder = build(&commands);
+    validate(&builder).context(here!())?;
+
+    let registered = http
+        .get_global_application_commands()
+        .await
+        .context(here!())?;
+
+    log_stale("global", &registered, &builder);
+
+    let registered = http
+        .create_global_application_commands(&Value::Array(builder.0))
+        .await
+        .context(here!())?;
+
+    Ok(registered.len())
+}
+
+/// Registers the guild's commands, replacing every command registered in the guild before.
+pub async fn register_guild(
+    http: &Http,
+    config: &CommandRegistrationConfig,
+    commands: &[Command],
+    guild_id: GuildId,
+) -> anyhow::Result<usize> {
+    let commands = match config.dev_guild {
+        Some(dev_guild) if dev_guild == guild_id => commands.iter().collect(),
+        Some(_) => Vec::new(),
+        None => in_scope(config, commands, CommandScope::Guild),
+    };
+
+    let builder = build(&commands);
+    validate(&builder).context(here!())?;
+
+    let registered = http
+        .get_guild_application_commands(guild_id.0)
+        .await
+        .context(here!())?;
+
+    log_stale(&guild_id.to_string(), &registered, &builder);
+
+    let registered = http
+        .create_guild_application_commands(guild_id.0, &Value::Array(builder.0))
+        .await
+        .context(here!())?;
+
+    Ok(registered.len())
+}
+
+fn in_scope<'a>(
+    config: &CommandRegistrationConfig,
+    commands: &'a [Command],
+    scope: CommandScope,
+) -> Vec<&'a Command> {
+    commands
+        .iter()
+        .filter(|c| config.scope_for(&c.name, c.category.as_deref()) == scope)
+        .collect()
+}
+
+/// Same as [`poise::builtins::create_application_commands`], for a subset of the commands.
+fn build(commands: &[&Command]) -> CreateApplicationCommands {
+    let mut builder = CreateApplicationCommands::default();
+
+    for command in commands {
+        if let Some(slash_command) = command.create_as_slash_command() {
+            builder.add_application_command(slash_command);
+        }
+
+        if let Some(context_menu_command) = command.create_as_context_menu_command() {
+            builder.add_application_command(context_menu_command);
+        }
+    }
+
+    builder
+}
+
+/// Checks the commands against Discord's limits, since Discord rejects the whole batch
+/// without saying which command broke them.
+fn validate(builder: &CreateApplicationCommands) -> anyhow::Result<()> {
+    let mut problems = Vec::new();
+
+    for command in &builder.0 {
+        let name = str_field(command, "name");
+
+        // Context menu commands have display names, and no description or options.
+        if command.get("type").and_then(Value::as_u64).unwrap_or(1) != 1 {
+            if !(1..=MAX_NAME_LENGTH).contains(&name.chars().count()) {
+                problems.push(format!(
+                    "`{name}`: the name must be 1-{MAX_NAME_LENGTH} characters."
+                ));
+            }
+
+            continue;
+        }
+
+        validate_command(name, command, &mut problems);
+    }
+
+    if !problems.is_empty() {
+        bail!("Invalid commands:\n{}", problems.join("\n"));
+    }
+
+    Ok(())
+}
+
+/// Checks a slash command, or one of its options, where `path` is its full name.
+fn validate_command(path: &str, command: &Value, problems: &mut Vec<String>) {
+    let name = str_field(command, "name");
+
+    let valid_name = (1..=MAX_NAME_LENGTH).contains(&name.chars().count())
+        && name
+            .chars()
+            .all(|c| c == '-' || c == '_' || c.is_alphanumeric())
+        && !name.chars().any(char::is_uppercase);
+
+    if !valid_name {
+        problems.push(format!(
+            "`{path}`: the name must be 1-{MAX_NAME_LENGTH} lowercase letters, numbers, `-` or `_`."
+        ));
+    }
+
+    let description = str_field(command, "description");
+
+    if !(1..=MAX_DESCRIPTION_LENGTH).contains(&description.chars().count()) {
+        problems.push(format!(
+            "`{path}`: the description must be 1-{MAX_DESCRIPTION_LENGTH} characters, but is {}.",
+            description.chars().count()
+        ));
+    }
+
+    let options = command
+        .get("options")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+
+    if options.len() > MAX_OPTIONS {
+        problems.push(format!(
+            "`{path}`: there can be at most {MAX_OPTIONS} options, but there are {}.",
+            options.len()
+        ));
+    }
+
+    for option in options {
+        let option_name = str_field(option, "name");
+        validate_command(&format!("{path} {option_name}"), option, problems);
+    }
+
+    let choices = command
+        .get("choices")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+
+    if choices.len() > MAX_CHOICES {
+        problems.push(format!(
+            "`{path}`: there can be at most {MAX_CHOICES} choices, but there are {}.",
+            choices.len()
+        ));
+    }
+
+    for choice in choices {
+        let choice_name = str_field(choice, "name");
+
+        if !(1..=MAX_DESCRIPTION_LENGTH).contains(&choice_name.chars().count()) {
+            problems.push(format!(
+                "`{path}`: the choice `{choice_name}` must be 1-{MAX_DESCRIPTION_LENGTH} characters."
+            ));
+        }
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Logs the registered commands that are about to be removed, since they no longer exist.
+fn log_stale(scope: &str, registered: &[RegisteredCommand], builder: &CreateApplicationCommands) {
+    let names = builder
+        .0
+        .iter()
+        .filter_map(|c| c.get("name")?.as_str())
+        .collect::<Vec<_>>();
+
+    for command in registered {
+        if !names.contains(&command.name.as_str()) {
+            info!(%scope, command = %command.name, "Removing stale command.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_command_is_valid() {
+        let commands = crate::commands::get_commands();
+        let builder = build(&commands.iter().collect::<Vec<_>>());
+
+        if let Err(e) = validate(&builder) {
+            panic!("{e}");
+        }
+    }
+
+    #[test]
+    fn invalid_commands_are_rejected() {
+        let mut builder = CreateApplicationCommands::default();
+        builder.0.push(serde_json::json!({
+            "type": 1,
+            "name": "Stream",
+            "description": "d".repeat(MAX_DESCRIPTION_LENGTH + 1),
+        }));
+
+        let e = validate(&builder).unwrap_err().to_string();
+        assert!(e.contains("the name must be"));
+        assert!(e.contains("the description must be"));
+    }
+}
@@ -5,42 +5,93 @@ mod prelude;
 pub(crate) mod config;
 // pub(crate) mod music;
 
+mod admin;
+mod announce;
+mod archive;
+mod audit;
+mod backfill;
 mod birthdays;
+mod command_list;
+mod debug;
 mod donate;
+mod easter_egg;
 mod eightball;
 mod emoji_usage;
+mod emote;
+pub(crate) mod features;
+mod giveaway;
 mod help;
 mod live;
 mod meme;
 mod move_conversation;
-mod ogey;
 pub(crate) mod pekofy;
+mod poll;
+mod quote;
+mod ratelimit;
+mod reminder;
+#[cfg(feature = "translation")]
+mod rephrase;
+mod setup;
 mod sticker_usage;
+#[cfg(feature = "twitter")]
+mod subscribe;
+pub(crate) mod talent;
+mod timeout_guard;
 mod timestamp;
+pub(crate) mod timezone;
 mod tsfmt;
 mod upcoming;
+#[cfg(feature = "translation")]
+mod usage;
 pub(crate) mod uwuify;
 
 pub(crate) fn get_commands() -> Vec<prelude::Command> {
-    vec![
+    let mut commands = vec![
         config::config(),
         // music::music(),
+        admin::admin(),
+        announce::announce(),
+        archive::archive(),
+        audit::audit(),
+        backfill::backfill(),
         birthdays::birthdays(),
+        birthdays::birthday(),
+        command_list::commands(),
+        debug::debug(),
         donate::donate(),
+        easter_egg::easter_egg(),
         eightball::eightball(),
         emoji_usage::emoji_usage(),
+        emote::emote(),
+        features::features(),
+        giveaway::giveaway(),
         help::help(),
         live::live(),
         meme::meme(),
         move_conversation::move_conversation(),
-        ogey::ogey(),
         pekofy::pekofy(),
         pekofy::pekofy_message(),
+        poll::poll(),
+        quote::quote(),
+        ratelimit::ratelimit(),
+        reminder::reminder(),
+        setup::setup(),
         sticker_usage::sticker_usage(),
+        talent::talent(),
         timestamp::timestamp(),
+        timezone::timezone(),
         tsfmt::tsfmt(),
         upcoming::upcoming(),
         uwuify::uwuify(),
         uwuify::uwuify_message(),
-    ]
+    ];
+
+    #[cfg(feature = "translation")]
+    commands.extend([rephrase::rephrase(), usage::usage()]);
+
+    #[cfg(feature = "twitter")]
+    commands.push(subscribe::subscribe());
+
+    timeout_guard::guard_commands(&mut commands);
+    commands
 }
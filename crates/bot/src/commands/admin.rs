@@ -0,0 +1,204 @@
+use apis::meme_api::MemeApi;
+use chrono::Utc;
+use poise::serenity_prelude::CacheHttp;
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    logging,
+    types::{Cache, Service},
+};
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum LogLevel {
+    #[name = "trace"]
+    Trace,
+    #[name = "debug"]
+    Debug,
+    #[name = "info"]
+    Info,
+    #[name = "warn"]
+    Warn,
+    #[name = "error"]
+    Error,
+    #[name = "off"]
+    Off,
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    subcommands(
+        "reload_config",
+        "restart",
+        "log_filter",
+        "log_level",
+        "flush_cache",
+        "stats"
+    ),
+    category = "Admin"
+)]
+/// Operate the bot while it's running.
+pub(crate) async fn admin(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Read the config files from disk again. The talent roster is applied immediately,
+/// other settings take effect after a restart.
+pub(crate) async fn reload_config(ctx: Context<'_>) -> anyhow::Result<()> {
+    let config = ctx.data().config.reload().await.context(here!())?;
+    let talent_count = config.talents.len();
+
+    {
+        let mut data = ctx.data().data.write().await;
+
+        data.talent_updates.send_replace(config.talents.clone());
+        data.edited_config = Some(Config::clone(&config));
+    }
+
+    record(ctx, "Config", "Reloaded from disk.").await;
+
+    reply(
+        ctx,
+        &format!("Reloaded the config, tracking {talent_count} talent(s)."),
+    )
+    .await
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Restart a background service.
+pub(crate) async fn restart(
+    ctx: Context<'_>,
+    #[description = "The service to restart."] service: Service,
+) -> anyhow::Result<()> {
+    ctx.data()
+        .data
+        .read()
+        .await
+        .service_restarter
+        .send(service)?;
+
+    reply(ctx, &format!("Restarting {service}...")).await
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Show or change the log filter, using the same syntax as `RUST_LOG`.
+pub(crate) async fn log_filter(
+    ctx: Context<'_>,
+    #[description = "The new filter, such as `info,apis=debug`."] filter: Option<String>,
+) -> anyhow::Result<()> {
+    let filter = match filter {
+        Some(f) => f,
+        None => {
+            let content = match logging::current_filter() {
+                Some(f) => format!("The log filter is `{f}`."),
+                None => "The log filter can't be changed at runtime.".to_owned(),
+            };
+
+            return reply(ctx, &content).await;
+        }
+    };
+
+    let old_filter = logging::current_filter().unwrap_or_default();
+
+    if let Err(e) = logging::set_filter(&filter) {
+        return reply(ctx, &format!("Error! {e}")).await;
+    }
+
+    record(ctx, "Log filter", format!("{old_filter} → {filter}")).await;
+
+    reply(ctx, &format!("The log filter is now `{filter}`.")).await
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Change the log level of a single module, keeping the rest of the log filter.
+pub(crate) async fn log_level(
+    ctx: Context<'_>,
+    #[description = "The module, such as `apis::holo_api`."] target: String,
+    #[description = "The level to log at."] level: LogLevel,
+) -> anyhow::Result<()> {
+    let old_filter = logging::current_filter().unwrap_or_default();
+
+    let filter = match logging::set_target_level(target.trim(), &level.to_string()) {
+        Ok(f) => f,
+        Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    record(ctx, "Log filter", format!("{old_filter} → {filter}")).await;
+
+    reply(ctx, &format!("The log filter is now `{filter}`.")).await
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Drop a cache, so that its contents are fetched again.
+pub(crate) async fn flush_cache(
+    ctx: Context<'_>,
+    #[description = "The cache to flush."] cache: Cache,
+) -> anyhow::Result<()> {
+    match cache {
+        Cache::TweetReplies => {
+            ctx.data().data.read().await.cache_flusher.send(cache)?;
+        }
+        Cache::Webhooks => ctx.data().data.write().await.webhook_cache.clear(),
+        Cache::MemeTemplates => MemeApi::clear_cache().await,
+    }
+
+    reply(ctx, &format!("Flushed the {cache} cache.")).await
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Show how long the bot has been running and how much memory it uses.
+pub(crate) async fn stats(ctx: Context<'_>) -> anyhow::Result<()> {
+    let started_at = ctx.data().data.read().await.started_at;
+    let uptime = Utc::now() - started_at;
+
+    let memory = match resident_memory() {
+        Some(kb) => format!("{:.1} MB", kb as f64 / 1024.0),
+        None => "Unknown".to_owned(),
+    };
+
+    ctx.send(|m| {
+        m.ephemeral(true).embed(|e| {
+            e.title("Stats")
+                .colour(ctx.data().config.theme.default)
+                .field(
+                    "Uptime",
+                    format!(
+                        "{}d {}h {}m",
+                        uptime.num_days(),
+                        uptime.num_hours() % 24,
+                        uptime.num_minutes() % 60
+                    ),
+                    true,
+                )
+                .field("Started", format!("<t:{}:f>", started_at.timestamp()), true)
+                .field("Memory", memory, true)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// The resident set size of the process in kilobytes, only available on Linux.
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+
+    status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmRSS:"))
+        .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+async fn record(ctx: Context<'_>, target: &str, details: impl Into<String>) {
+    audit_log::record(
+        ctx.http(),
+        AuditEntry::new(AuditAction::ConfigChanged, target)
+            .guild(ctx.guild_id())
+            .actor(ctx.author().id)
+            .details(details),
+    )
+    .await;
+}
@@ -0,0 +1,177 @@
+use chrono::Utc;
+use poise::{
+    serenity_prelude::{GuildChannel, Role},
+    Modal,
+};
+use unicode_truncate::UnicodeTruncateStr;
+use utility::functions::{try_parse_written_time, try_parse_written_time_with_tz};
+
+use super::{
+    prelude::*,
+    timezone::{autocomplete_timezone, get_user_timezone},
+};
+use crate::announcements::{Announcement, Recurrence};
+
+#[derive(Debug, Modal)]
+#[name = "Schedule announcement"]
+struct AnnouncementModal {
+    #[name = "Message"]
+    #[placeholder = "The message to post."]
+    #[paragraph]
+    #[max_length = 1900]
+    content: String,
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    check = "announcements_enabled",
+    subcommands("schedule", "list", "cancel"),
+    category = "Admin"
+)]
+/// Schedule messages to be posted in this server.
+pub(crate) async fn announce(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+/// Write a message to be posted at a later time, opens a form to write it in.
+pub(crate) async fn schedule(
+    ctx: ApplicationContext<'_>,
+    #[description = "The channel to post in."] channel: GuildChannel,
+    #[description = "When to post it, ex. `tomorrow at 18:00`."] when: String,
+    #[description = "Your timezone in IANA format (ex. America/New_York), defaults to your stored timezone."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+    #[description = "How often to post it, defaults to once."] recurrence: Option<Recurrence>,
+    #[description = "A role to ping."] ping: Option<Role>,
+) -> anyhow::Result<()> {
+    // The form can only be opened from an application context.
+    let modal_ctx = ctx;
+    let ctx = Context::from(ctx);
+
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    if channel.guild_id != guild_id {
+        return reply(ctx, "Error! The channel has to be in this server.").await;
+    }
+
+    let parsed = match (timezone, get_user_timezone(ctx).await) {
+        (None, Some(stored)) => try_parse_written_time_with_tz(&when, &stored),
+        (timezone, _) => try_parse_written_time(&when, timezone.as_deref()),
+    };
+
+    let next_at = match parsed {
+        Ok(time) if time > Utc::now() => time,
+        Ok(_) => return reply(ctx, "Error! The time has to be in the future.").await,
+        Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    let content = match AnnouncementModal::execute(modal_ctx).await? {
+        Some(modal) => modal.content,
+        None => return Ok(()),
+    };
+
+    let mut announcement = Announcement {
+        id: 0,
+        guild: guild_id,
+        channel: channel.id,
+        author: ctx.author().id,
+        content,
+        roles: ping.map(|r| r.id).into_iter().collect(),
+        next_at,
+        first_at: next_at,
+        recurrence: recurrence.unwrap_or(Recurrence::Once),
+    };
+
+    {
+        let data = ctx.data().data.read().await;
+
+        announcement.id = {
+            let handle = data.database.lock().await;
+            announcement.insert(&handle).context(here!())?
+        };
+
+        data.announcements_changed.notify_one();
+    }
+
+    reply(
+        ctx,
+        &format!(
+            "Scheduled announcement {} in {}, it will be posted <t:{}:F>.",
+            announcement.id,
+            Mention::from(announcement.channel),
+            announcement.next_at.timestamp()
+        ),
+    )
+    .await
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+/// Show the announcements scheduled in this server.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let announcements = {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        Announcement::list(&handle, guild_id).context(here!())?
+    };
+
+    let content = if announcements.is_empty() {
+        "There are no announcements scheduled in this server.".to_owned()
+    } else {
+        announcements
+            .iter()
+            .map(|a| {
+                let (preview, _) = a.content.unicode_truncate(50);
+
+                format!(
+                    "**{}**: {} <t:{}:f> ({}), \"{preview}\"",
+                    a.id,
+                    Mention::from(a.channel),
+                    a.next_at.timestamp(),
+                    a.recurrence,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+/// Cancel a scheduled announcement.
+pub(crate) async fn cancel(
+    ctx: Context<'_>,
+    #[description = "The ID of the announcement, as shown by `/announce list`."] id: i64,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let cancelled = {
+        let data = ctx.data().data.read().await;
+
+        let cancelled = {
+            let handle = data.database.lock().await;
+            Announcement::cancel(&handle, guild_id, id).context(here!())?
+        };
+
+        data.announcements_changed.notify_one();
+        cancelled
+    };
+
+    let content = if cancelled {
+        format!("Cancelled announcement {id}.")
+    } else {
+        format!("Error! No announcement with the ID {id} is scheduled in this server.")
+    };
+
+    reply(ctx, &content).await
+}
+
+async fn announcements_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.announcements.enabled)
+}
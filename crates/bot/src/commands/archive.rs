@@ -0,0 +1,102 @@
+use apis::archive_index::{ArchiveIndex, ArchiveQuery};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+use super::{prelude::*, talent::autocomplete_talent};
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    check = "archives_enabled",
+    subcommands("search"),
+    category = "Utility"
+)]
+/// Look through the chats of past streams.
+pub(crate) async fn archive(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+/// Search the archived stream chats for messages containing every word of the query.
+pub(crate) async fn search(
+    ctx: Context<'_>,
+    #[description = "The words to search for."] query: String,
+    #[description = "Only search the chats of this talent's streams."]
+    #[autocomplete = "autocomplete_talent"]
+    talent: Option<String>,
+    #[description = "Only search messages sent on or after this date, ex. `2023-01-31`."]
+    from: Option<String>,
+    #[description = "Only search messages sent on or before this date, ex. `2023-02-28`."]
+    to: Option<String>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let (from, to) = match (parse_date(from, false), parse_date(to, true)) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    let results = ArchiveIndex::open(&ctx.data().config.database)
+        .and_then(|index| {
+            index.search(&ArchiveQuery {
+                text: query.clone(),
+                guild: Some(guild_id),
+                talent,
+                from,
+                to,
+            })
+        })
+        .context(here!())?;
+
+    if results.is_empty() {
+        return reply(ctx, "No archived messages matched the search.").await;
+    }
+
+    PaginatedList::new()
+        .title(format!("Archived messages matching \"{query}\""))
+        .layout(PageLayout::Standard { items_per_page: 5 })
+        .data(&results)
+        .format(Box::new(|m, _| {
+            let time = match m.video_link() {
+                Some(link) => format!("[{}]({link})", m.clock_time()),
+                None => m.clock_time(),
+            };
+
+            format!(
+                "**{}** <t:{}:d> {}\n{time} {}: {}\n\n",
+                m.talent.as_deref().unwrap_or("Unknown talent"),
+                m.sent_at.timestamp(),
+                m.stream_title.as_deref().unwrap_or_default(),
+                m.author_name,
+                m.content
+            )
+        }))
+        .display(ctx)
+        .await?;
+
+    Ok(())
+}
+
+/// Parses a date as the start of the day, or as the end of it if `end_of_day` is set.
+fn parse_date(date: Option<String>, end_of_day: bool) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| anyhow!("\"{date}\" is not a date in the format YYYY-MM-DD."))?,
+        None => return Ok(None),
+    };
+
+    let start = Utc.from_utc_datetime(
+        &date
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(|| anyhow!("Invalid date."))?,
+    );
+
+    Ok(Some(if end_of_day {
+        start + Duration::days(1) - Duration::seconds(1)
+    } else {
+        start
+    }))
+}
+
+async fn archives_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.stream_tracking.enabled)
+}
@@ -0,0 +1,64 @@
+use utility::audit_log;
+
+use super::prelude::*;
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS",
+    subcommands("recent"),
+    category = "Admin"
+)]
+/// Inspect actions taken by the bot.
+pub(crate) async fn audit(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Show the most recent actions taken by the bot in this server.
+pub(crate) async fn recent(
+    ctx: Context<'_>,
+    #[description = "How many entries to show, defaults to 10."]
+    #[min = 1]
+    #[max = 25]
+    count: Option<usize>,
+) -> anyhow::Result<()> {
+    let entries = audit_log::recent(ctx.guild_id(), count.unwrap_or(10)).context(here!())?;
+
+    let content = if entries.is_empty() {
+        "No actions have been recorded yet.".to_owned()
+    } else {
+        entries
+            .iter()
+            .map(|entry| {
+                let actor = entry
+                    .actor
+                    .map_or_else(|| "the bot".to_owned(), |a| Mention::from(a).to_string());
+
+                let mut line = format!(
+                    "<t:{}:R> **{}** {} by {actor}",
+                    entry.timestamp.timestamp(),
+                    entry.action,
+                    entry.target
+                );
+
+                if !entry.details.is_empty() {
+                    line.push_str(&format!(": {}", entry.details));
+                }
+
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
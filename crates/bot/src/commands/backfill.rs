@@ -0,0 +1,148 @@
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use poise::serenity_prelude::CacheHttp;
+
+#[cfg(feature = "twitter")]
+use apis::twitter_api::TwitterApi;
+use apis::{discord_api::DiscordMessageData, holo_api::HoloApi};
+
+use super::prelude::*;
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    required_permissions = "KICK_MEMBERS",
+    category = "Admin"
+)]
+/// Post alerts for streams that are still live, and tweets missed while the bot was offline.
+pub(crate) async fn backfill(
+    ctx: Context<'_>,
+    #[description = "How many hours back to look."]
+    #[min = 1]
+    #[max = 168]
+    hours: u32,
+) -> anyhow::Result<()> {
+    ctx.defer_ephemeral().await?;
+
+    let config = &ctx.data().config;
+    let since = Utc::now() - Duration::hours(hours.into());
+
+    let mut missed = Vec::new();
+
+    if config.stream_tracking.enabled && config.stream_tracking.alerts.enabled {
+        let talents = ctx.data().data.read().await.talent_updates.borrow().clone();
+
+        let streams = HoloApi::get_missed_streams(config, &talents, since)
+            .await
+            .context(here!())?;
+
+        missed.extend(streams.into_iter().map(DiscordMessageData::ScheduledLive));
+    }
+
+    #[cfg(feature = "twitter")]
+    if config.twitter.enabled {
+        missed.extend(
+            TwitterApi::get_missed_tweets(config, since)
+                .await
+                .context(here!())?,
+        );
+    }
+
+    let mut channels = HashSet::new();
+
+    for message in &missed {
+        match message {
+            DiscordMessageData::ScheduledLive(live) => {
+                channels.extend(config.stream_tracking.alerts.channel_for(live.members_only));
+            }
+            DiscordMessageData::Tweet(tweet) => {
+                channels.extend(tweet.user.get_twitter_channel(config));
+            }
+            DiscordMessageData::ScheduleUpdate(_) => {
+                channels.insert(config.twitter.schedule_updates.channel);
+            }
+            DiscordMessageData::Birthday(_)
+            | DiscordMessageData::LiveViewers(..)
+            | DiscordMessageData::Cancelled(_)
+            | DiscordMessageData::Donation(_) => (),
+        }
+    }
+
+    let mut posted_links = HashSet::new();
+
+    for channel in channels {
+        posted_links.extend(get_posted_links(ctx, channel, since).await);
+    }
+
+    missed.retain(|m| match message_link(m) {
+        Some(link) => !posted_links.contains(link),
+        None => false,
+    });
+    missed.sort_by_key(|m| match m {
+        DiscordMessageData::ScheduledLive(live) => live.start_at,
+        DiscordMessageData::Tweet(tweet) => tweet.timestamp,
+        DiscordMessageData::ScheduleUpdate(update) => update.timestamp,
+        DiscordMessageData::Birthday(birthday) => birthday.birthday,
+        DiscordMessageData::LiveViewers(..)
+        | DiscordMessageData::Cancelled(_)
+        | DiscordMessageData::Donation(_) => Utc::now(),
+    });
+
+    let count = missed.len();
+    let sender = ctx.data().data.read().await.message_sender.clone();
+
+    for message in missed {
+        sender.send(message).await.context(here!())?;
+    }
+
+    ctx.say(format!(
+        "Posted {count} missed alert{} from the last {hours} hours.",
+        if count == 1 { "" } else { "s" }
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// The link that identifies an already posted alert, matching what the posting thread embeds.
+fn message_link(message: &DiscordMessageData) -> Option<&str> {
+    match message {
+        DiscordMessageData::ScheduledLive(live) => Some(&live.url),
+        DiscordMessageData::Tweet(tweet) => Some(&tweet.link),
+        DiscordMessageData::ScheduleUpdate(update) => Some(&update.tweet_link),
+        DiscordMessageData::Birthday(_)
+        | DiscordMessageData::LiveViewers(..)
+        | DiscordMessageData::Cancelled(_)
+        | DiscordMessageData::Donation(_) => None,
+    }
+}
+
+async fn get_posted_links(
+    ctx: Context<'_>,
+    channel: ChannelId,
+    since: chrono::DateTime<Utc>,
+) -> HashSet<String> {
+    let mut links = HashSet::new();
+    let mut messages = channel.messages_iter(ctx.http()).boxed();
+
+    while let Some(message) = messages.next().await {
+        let message = match message {
+            Ok(m) => m,
+            Err(e) => {
+                error!("{:?}", e);
+                break;
+            }
+        };
+
+        if *message.timestamp < since {
+            break;
+        }
+
+        for embed in message.embeds {
+            links.extend(embed.url);
+            links.extend(embed.author.and_then(|a| a.url));
+        }
+    }
+
+    links
+}
@@ -1,27 +1,37 @@
 use std::borrow::Cow;
 
 use chrono::Utc;
+use poise::serenity_prelude::CacheHttp;
 
-use super::prelude::*;
+use super::{prelude::*, talent::autocomplete_talent, timezone::get_user_timezone};
 
 use apis::birthday_reminder::BirthdayReminder;
-use utility::config::HoloBranch;
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    birthdays::{self, parse_birthday, BirthdayEntry, Celebrant},
+    config::HoloBranch,
+};
 
 #[poise::command(
     slash_command,
     prefix_command,
     track_edits,
     check = "birthdays_enabled",
-    required_permissions = "SEND_MESSAGES"
+    required_permissions = "SEND_MESSAGES",
+    category = "Hololive"
 )]
 /// Shows upcoming birthdays.
 pub(crate) async fn birthdays(
     ctx: Context<'_>,
     #[description = "Show only talents from this branch of Hololive."] branch: Option<HoloBranch>,
 ) -> anyhow::Result<()> {
+    let timezone = get_user_timezone(ctx)
+        .await
+        .unwrap_or(chrono_tz::Asia::Tokyo);
+
     let config = &ctx.data().config;
     let users = &config.talents;
-    let get_birthdays = BirthdayReminder::get_birthdays(users);
+    let get_birthdays = BirthdayReminder::get_birthdays(users, birthdays::store()?);
 
     let bdays = get_birthdays
         .iter()
@@ -36,22 +46,40 @@ pub(crate) async fn birthdays(
         })
         .collect::<Vec<_>>();
 
+    // Group by generation, ordered by whichever generation has the nearest birthday.
+    let mut generations: Vec<(String, Vec<String>)> = Vec::new();
+
+    for b in bdays {
+        let generation = b.user.generation_name(config);
+
+        let line = format!(
+            "{:<20} {} ({})",
+            if let Some(role) = b.user.discord_role {
+                Cow::Owned(Mention::from(role).to_string())
+            } else {
+                Cow::Borrowed(&b.user.name)
+            },
+            chrono_humanize::HumanTime::from(b.birthday - Utc::now()).to_text_en(
+                chrono_humanize::Accuracy::Rough,
+                chrono_humanize::Tense::Future
+            ),
+            b.birthday
+                .with_timezone(&timezone)
+                .format("%B %-d, %H:%M %Z")
+        );
+
+        match generations.iter_mut().find(|(g, _)| *g == generation) {
+            Some((_, lines)) => lines.push(line),
+            None => generations.push((generation, vec![line])),
+        }
+    }
+
     PaginatedList::new()
         .title("HoloPro Birthdays")
-        .data(&bdays)
-        .format(Box::new(|b, _| {
-            format!(
-                "{:<20} {}\r\n",
-                if let Some(role) = b.user.discord_role {
-                    Cow::Owned(Mention::from(role).to_string())
-                } else {
-                    Cow::Borrowed(&b.user.name)
-                },
-                chrono_humanize::HumanTime::from(b.birthday - Utc::now()).to_text_en(
-                    chrono_humanize::Accuracy::Rough,
-                    chrono_humanize::Tense::Future
-                )
-            )
+        .layout(PageLayout::Standard { items_per_page: 4 })
+        .data(&generations)
+        .format(Box::new(|(generation, lines), _| {
+            format!("**{}**\r\n{}\r\n\r\n", generation, lines.join("\r\n"))
         }))
         .display(ctx)
         .await?;
@@ -59,6 +87,125 @@ pub(crate) async fn birthdays(
     Ok(())
 }
 
+#[poise::command(
+    slash_command,
+    prefix_command,
+    check = "birthdays_enabled",
+    subcommands("set", "register", "unregister"),
+    category = "Hololive"
+)]
+/// Manage the birthdays that are celebrated.
+pub(crate) async fn birthday(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Set the birthday of a talent.
+pub(crate) async fn set(
+    ctx: Context<'_>,
+    #[description = "The talent whose birthday it is."]
+    #[autocomplete = "autocomplete_talent"]
+    talent: String,
+    #[description = "The birthday, like July 6 or 07-06."] date: String,
+) -> anyhow::Result<()> {
+    let talent = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .talent_updates
+        .borrow()
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(talent.trim()))
+        .cloned();
+
+    let talent = match talent {
+        Some(t) => t,
+        None => return reply(ctx, &format!("Error! No talent named {talent} found.")).await,
+    };
+
+    let (day, month) = match parse_birthday(&date) {
+        Some(date) => date,
+        None => return reply(ctx, &format!("Error! {date} is not a valid date.")).await,
+    };
+
+    let entry = BirthdayEntry {
+        celebrant: Celebrant::Talent(talent.name.clone()),
+        day,
+        month,
+        timezone: talent.timezone,
+    };
+
+    birthdays::store()?.set(entry.clone()).context(here!())?;
+
+    audit_log::record(
+        ctx.http(),
+        AuditEntry::new(AuditAction::BirthdayChanged, &talent.name)
+            .guild(ctx.guild_id())
+            .actor(ctx.author().id)
+            .details(entry.date()),
+    )
+    .await;
+
+    reply(
+        ctx,
+        &format!("{}'s birthday is now {}.", talent.name, entry.date()),
+    )
+    .await
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Register your birthday, to be wished a happy birthday on it.
+pub(crate) async fn register(
+    ctx: Context<'_>,
+    #[description = "Your birthday, like July 6 or 07-06. The year isn't needed."] date: String,
+) -> anyhow::Result<()> {
+    let (day, month) = match parse_birthday(&date) {
+        Some(date) => date,
+        None => return reply(ctx, &format!("Error! {date} is not a valid date.")).await,
+    };
+
+    let entry = BirthdayEntry {
+        celebrant: Celebrant::User(ctx.author().id),
+        day,
+        month,
+        timezone: get_user_timezone(ctx).await.unwrap_or(chrono_tz::UTC),
+    };
+
+    birthdays::store()?.set(entry.clone()).context(here!())?;
+
+    let channel = ctx.data().config.birthday_alerts.channel;
+
+    reply(
+        ctx,
+        &format!(
+            "Your birthday is saved as {}, starting at midnight in {}.\n\
+            Only the day and month are stored, and they're only used to wish you a happy birthday in {}. \
+            Use `/birthday unregister` to remove it.",
+            entry.date(),
+            entry.timezone.name(),
+            Mention::from(channel)
+        ),
+    )
+    .await
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Remove your birthday, so that it's no longer celebrated.
+pub(crate) async fn unregister(ctx: Context<'_>) -> anyhow::Result<()> {
+    let removed = birthdays::store()?
+        .remove(&Celebrant::User(ctx.author().id))
+        .context(here!())?;
+
+    let content = if removed {
+        "Your birthday has been removed."
+    } else {
+        "Error! You haven't registered your birthday."
+    };
+
+    reply(ctx, content).await
+}
+
 async fn birthdays_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
     Ok(ctx.data().config.birthday_alerts.enabled)
 }
@@ -0,0 +1,186 @@
+use std::{borrow::Cow, fmt::Write};
+
+use poise::serenity_prelude::AttachmentType;
+use serde::Serialize;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum ExportFormat {
+    #[name = "Markdown"]
+    Markdown,
+    #[name = "JSON"]
+    Json,
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    subcommands("export"),
+    category = "Admin"
+)]
+/// Document the commands of the bot.
+pub(crate) async fn commands(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Export every command with its options and permissions, such as for the wiki.
+pub(crate) async fn export(
+    ctx: Context<'_>,
+    #[description = "The format of the file, Markdown by default."] format: Option<ExportFormat>,
+) -> anyhow::Result<()> {
+    let manifest = CommandEntry::manifest(&ctx.framework().options().commands);
+
+    let (data, filename) = match format.unwrap_or(ExportFormat::Markdown) {
+        ExportFormat::Markdown => (to_markdown(&manifest).into_bytes(), "commands.md"),
+        ExportFormat::Json => (
+            serde_json::to_vec_pretty(&manifest).context(here!())?,
+            "commands.json",
+        ),
+    };
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("Exported {} command(s).", manifest.len()))
+            .attachment(AttachmentType::Bytes {
+                data: Cow::Owned(data),
+                filename: filename.to_owned(),
+            })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// A command as it's documented, with the metadata declared on it.
+#[derive(Debug, Serialize)]
+struct CommandEntry {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context_menu: Option<String>,
+    category: Option<String>,
+    description: Option<String>,
+    options: Vec<OptionEntry>,
+    permissions: Vec<String>,
+    owners_only: bool,
+    guild_only: bool,
+    subcommands: Vec<CommandEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct OptionEntry {
+    name: String,
+    description: Option<String>,
+    required: bool,
+}
+
+impl CommandEntry {
+    /// The commands that aren't hidden from help, sorted by category and name.
+    fn manifest(commands: &[Command]) -> Vec<Self> {
+        let mut entries = commands
+            .iter()
+            .filter(|c| !c.hide_in_help)
+            .map(Self::new)
+            .collect::<Vec<_>>();
+
+        entries.sort_unstable_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+        entries
+    }
+
+    fn new(command: &Command) -> Self {
+        Self {
+            name: command.qualified_name.clone(),
+            context_menu: command.context_menu_name.map(ToOwned::to_owned),
+            category: command.category.map(ToOwned::to_owned),
+            description: command.description.clone(),
+            options: command
+                .parameters
+                .iter()
+                .map(|p| OptionEntry {
+                    name: p.name.clone(),
+                    description: p.description.clone(),
+                    required: p.required,
+                })
+                .collect(),
+            permissions: command
+                .required_permissions
+                .get_permission_names()
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect(),
+            owners_only: command.owners_only,
+            guild_only: command.guild_only,
+            subcommands: Self::manifest(&command.subcommands),
+        }
+    }
+}
+
+fn to_markdown(manifest: &[CommandEntry]) -> String {
+    let mut markdown = "# Commands\n".to_owned();
+    let mut category = None;
+
+    for command in manifest {
+        if category != Some(&command.category) {
+            category = Some(&command.category);
+
+            let _ = write!(
+                markdown,
+                "\n## {}\n",
+                command.category.as_deref().unwrap_or("Other")
+            );
+        }
+
+        write_command(&mut markdown, command, 3);
+    }
+
+    markdown
+}
+
+fn write_command(markdown: &mut String, command: &CommandEntry, depth: usize) {
+    let heading = "#".repeat(depth.min(6));
+
+    let _ = match &command.context_menu {
+        Some(menu_name) => write!(markdown, "\n{heading} {menu_name} (context menu)\n"),
+        None => write!(markdown, "\n{heading} `/{}`\n", command.name),
+    };
+
+    let _ = write!(
+        markdown,
+        "\n{}\n",
+        command.description.as_deref().unwrap_or("No description.")
+    );
+
+    if !command.options.is_empty() {
+        markdown.push_str("\n**Options:**\n");
+
+        for option in &command.options {
+            let _ = writeln!(
+                markdown,
+                "- `{}`{}: {}",
+                option.name,
+                if option.required { "" } else { " (optional)" },
+                option.description.as_deref().unwrap_or("No description.")
+            );
+        }
+    }
+
+    if !command.permissions.is_empty() {
+        let _ = write!(
+            markdown,
+            "\n**Required permissions:** {}\n",
+            command.permissions.join(", ")
+        );
+    }
+
+    if command.owners_only {
+        markdown.push_str("\n**Only usable by the bot owners.**\n");
+    } else if command.guild_only {
+        markdown.push_str("\n**Only usable in servers.**\n");
+    }
+
+    for subcommand in &command.subcommands {
+        write_command(markdown, subcommand, depth + 1);
+    }
+}
@@ -1,13 +1,26 @@
 use super::prelude::*;
 
-use poise::serenity_prelude::CacheHttp;
-use utility::types::Service;
+use chrono::Duration;
+use poise::serenity_prelude::{CacheHttp, ChannelType, GuildChannel, Role};
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    types::Service,
+};
 
 #[poise::command(
     slash_command,
     prefix_command,
     required_permissions = "KICK_MEMBERS",
-    subcommands("remove_command", "restart_service")
+    subcommands(
+        "view",
+        "set_channel",
+        "set_role",
+        "set_duration",
+        "toggle",
+        "remove_command",
+        "restart_service"
+    ),
+    category = "Admin"
 )]
 /// Configure Pekobot.
 pub async fn config(_ctx: Context<'_>) -> anyhow::Result<()> {
@@ -51,6 +64,14 @@ pub(crate) async fn remove_command(
                 .delete_guild_application_command(guild_id, cmd.id.into())
                 .await?;
 
+            audit_log::record(
+                ctx.http(),
+                AuditEntry::new(AuditAction::CommandRemoved, format!("/{command_name}"))
+                    .guild(ctx.guild_id())
+                    .actor(ctx.author().id),
+            )
+            .await;
+
             ctx.send(|m| {
                 m.ephemeral(true)
                     .content(format!("Removed command: `{command_name}`."))
@@ -95,3 +116,371 @@ async fn autocomplete_command(
 
     commands.into_iter()
 }
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum ConfigSection {
+    #[name = "Stream tracking"]
+    StreamTracking,
+    #[name = "Birthday alerts"]
+    BirthdayAlerts,
+    #[name = "Emoji tracking"]
+    EmojiTracking,
+    #[name = "Meme creation"]
+    MemeCreation,
+    #[name = "Quotes"]
+    Quotes,
+    #[name = "Twitter"]
+    Twitter,
+    #[name = "React temp mute"]
+    ReactTempMute,
+    #[name = "Content filtering"]
+    ContentFiltering,
+    #[name = "Embed compressor"]
+    EmbedCompressor,
+    #[name = "Audit log"]
+    AuditLog,
+    #[name = "Theme"]
+    Theme,
+}
+
+impl ConfigSection {
+    fn to_value(self, config: &Config) -> Result<toml::Value, toml::ser::Error> {
+        match self {
+            Self::StreamTracking => toml::Value::try_from(&config.stream_tracking),
+            Self::BirthdayAlerts => toml::Value::try_from(&config.birthday_alerts),
+            Self::EmojiTracking => toml::Value::try_from(&config.emoji_tracking),
+            Self::MemeCreation => toml::Value::try_from(&config.meme_creation),
+            Self::Quotes => toml::Value::try_from(&config.quotes),
+            Self::Twitter => toml::Value::try_from(&config.twitter),
+            Self::ReactTempMute => toml::Value::try_from(&config.react_temp_mute),
+            Self::ContentFiltering => toml::Value::try_from(&config.content_filtering),
+            Self::EmbedCompressor => toml::Value::try_from(&config.embed_compressor),
+            Self::AuditLog => toml::Value::try_from(&config.audit_log),
+            Self::Theme => toml::Value::try_from(&config.theme),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum ChannelSetting {
+    #[name = "Stream alerts"]
+    StreamAlerts,
+    #[name = "Stream chat category"]
+    StreamChatCategory,
+    #[name = "Stream chat logs"]
+    StreamChatLogs,
+    #[name = "Birthday alerts"]
+    BirthdayAlerts,
+    #[name = "Schedule updates"]
+    ScheduleUpdates,
+    #[name = "Content filtering logs"]
+    ContentFilteringLogs,
+    #[name = "Audit log"]
+    AuditLog,
+}
+
+impl ChannelSetting {
+    pub(crate) fn expected_kind(self) -> ChannelType {
+        match self {
+            Self::StreamChatCategory => ChannelType::Category,
+            _ => ChannelType::Text,
+        }
+    }
+
+    pub(crate) fn get(self, config: &Config) -> Option<ChannelId> {
+        match self {
+            Self::StreamAlerts => Some(config.stream_tracking.alerts.channel),
+            Self::StreamChatCategory => Some(config.stream_tracking.chat.category),
+            Self::StreamChatLogs => config.stream_tracking.chat.logging_channel,
+            Self::BirthdayAlerts => Some(config.birthday_alerts.channel),
+            Self::ScheduleUpdates => Some(config.twitter.schedule_updates.channel),
+            Self::ContentFilteringLogs => Some(config.content_filtering.logging_channel),
+            Self::AuditLog => Some(config.audit_log.channel),
+        }
+    }
+
+    pub(crate) fn get_mut(self, config: &mut Config) -> &mut ChannelId {
+        match self {
+            Self::StreamAlerts => &mut config.stream_tracking.alerts.channel,
+            Self::StreamChatCategory => &mut config.stream_tracking.chat.category,
+            Self::StreamChatLogs => config
+                .stream_tracking
+                .chat
+                .logging_channel
+                .get_or_insert_with(ChannelId::default),
+            Self::BirthdayAlerts => &mut config.birthday_alerts.channel,
+            Self::ScheduleUpdates => &mut config.twitter.schedule_updates.channel,
+            Self::ContentFilteringLogs => &mut config.content_filtering.logging_channel,
+            Self::AuditLog => &mut config.audit_log.channel,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum RoleSetting {
+    #[name = "React temp mute role"]
+    ReactTempMute,
+    #[name = "Content filtering mute role"]
+    ContentFilteringMute,
+    #[name = "Content filtering staff role"]
+    ContentFilteringStaff,
+}
+
+impl RoleSetting {
+    fn get_mut(self, config: &mut Config) -> &mut RoleId {
+        match self {
+            Self::ReactTempMute => &mut config.react_temp_mute.mute_role,
+            Self::ContentFilteringMute => &mut config.content_filtering.mute_role,
+            Self::ContentFilteringStaff => config
+                .content_filtering
+                .staff_role
+                .get_or_insert_with(RoleId::default),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum DurationSetting {
+    #[name = "React temp mute duration"]
+    ReactTempMute,
+    #[name = "React temp mute eligibility window"]
+    ReactTempMuteEligibility,
+}
+
+impl DurationSetting {
+    fn get_mut(self, config: &mut Config) -> &mut Duration {
+        match self {
+            Self::ReactTempMute => &mut config.react_temp_mute.mute_duration,
+            Self::ReactTempMuteEligibility => &mut config.react_temp_mute.eligibility_duration,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum FeatureToggle {
+    #[name = "Stream tracking"]
+    StreamTracking,
+    #[name = "Stream alerts"]
+    StreamAlerts,
+    #[name = "Stream chat"]
+    StreamChat,
+    #[name = "Scheduled events"]
+    ScheduledEvents,
+    #[name = "Birthday alerts"]
+    BirthdayAlerts,
+    #[name = "Emoji tracking"]
+    EmojiTracking,
+    #[name = "Meme creation"]
+    MemeCreation,
+    #[name = "Quotes"]
+    Quotes,
+    #[name = "Twitter"]
+    Twitter,
+    #[name = "Schedule updates"]
+    ScheduleUpdates,
+    #[name = "React temp mute"]
+    ReactTempMute,
+    #[name = "Content filtering"]
+    ContentFiltering,
+    #[name = "Embed compressor"]
+    EmbedCompressor,
+    #[name = "Audit log"]
+    AuditLog,
+}
+
+impl FeatureToggle {
+    fn get_mut(self, config: &mut Config) -> &mut bool {
+        match self {
+            Self::StreamTracking => &mut config.stream_tracking.enabled,
+            Self::StreamAlerts => &mut config.stream_tracking.alerts.enabled,
+            Self::StreamChat => &mut config.stream_tracking.chat.enabled,
+            Self::ScheduledEvents => &mut config.stream_tracking.scheduled_events.enabled,
+            Self::BirthdayAlerts => &mut config.birthday_alerts.enabled,
+            Self::EmojiTracking => &mut config.emoji_tracking.enabled,
+            Self::MemeCreation => &mut config.meme_creation.enabled,
+            Self::Quotes => &mut config.quotes.enabled,
+            Self::Twitter => &mut config.twitter.enabled,
+            Self::ScheduleUpdates => &mut config.twitter.schedule_updates.enabled,
+            Self::ReactTempMute => &mut config.react_temp_mute.enabled,
+            Self::ContentFiltering => &mut config.content_filtering.enabled,
+            Self::EmbedCompressor => &mut config.embed_compressor.enabled,
+            Self::AuditLog => &mut config.audit_log.enabled,
+        }
+    }
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// View a section of the config.
+pub(crate) async fn view(
+    ctx: Context<'_>,
+    #[description = "The section to view."] section: ConfigSection,
+) -> anyhow::Result<()> {
+    let mut value = {
+        let data = ctx.data().data.read().await;
+        let config = data.edited_config.as_ref().unwrap_or(&ctx.data().config);
+
+        section.to_value(config).context(here!())?
+    };
+
+    redact_secrets(&mut value);
+
+    let mut contents = toml::to_string_pretty(&value).context(here!())?;
+    contents.truncate(4000);
+
+    ctx.send(|m| {
+        m.ephemeral(true).embed(|e| {
+            e.title(format!("{section} config"))
+                .description(format!("```toml\n{contents}\n```"))
+                .colour(ctx.data().config.theme.default)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Set a channel used by the bot.
+pub(crate) async fn set_channel(
+    ctx: Context<'_>,
+    #[description = "The setting to change."] setting: ChannelSetting,
+    #[description = "The new channel."] channel: GuildChannel,
+) -> anyhow::Result<()> {
+    if Some(channel.guild_id) != ctx.guild_id() {
+        return reply_invalid(ctx, "The channel has to be in this server.").await;
+    }
+
+    if channel.kind != setting.expected_kind() {
+        return reply_invalid(
+            ctx,
+            &format!(
+                "{setting} has to be a {:?} channel.",
+                setting.expected_kind()
+            ),
+        )
+        .await;
+    }
+
+    update_config(ctx, setting, Mention::from(channel.id).to_string(), |c| {
+        let old = std::mem::replace(setting.get_mut(c), channel.id);
+        Mention::from(old).to_string()
+    })
+    .await
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Set a role used by the bot.
+pub(crate) async fn set_role(
+    ctx: Context<'_>,
+    #[description = "The setting to change."] setting: RoleSetting,
+    #[description = "The new role."] role: Role,
+) -> anyhow::Result<()> {
+    if Some(role.guild_id) != ctx.guild_id() {
+        return reply_invalid(ctx, "The role has to be in this server.").await;
+    }
+
+    update_config(ctx, setting, Mention::from(role.id).to_string(), |c| {
+        let old = std::mem::replace(setting.get_mut(c), role.id);
+        Mention::from(old).to_string()
+    })
+    .await
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Set a duration used by the bot.
+pub(crate) async fn set_duration(
+    ctx: Context<'_>,
+    #[description = "The setting to change."] setting: DurationSetting,
+    #[description = "The new duration, in seconds."]
+    #[min = 1]
+    seconds: u32,
+) -> anyhow::Result<()> {
+    let duration = Duration::seconds(seconds.into());
+
+    update_config(ctx, setting, format!("{seconds} seconds"), |c| {
+        let old = std::mem::replace(setting.get_mut(c), duration);
+        format!("{} seconds", old.num_seconds())
+    })
+    .await
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Enable or disable a feature.
+pub(crate) async fn toggle(
+    ctx: Context<'_>,
+    #[description = "The feature to toggle."] feature: FeatureToggle,
+    #[description = "Whether the feature should be enabled."] enabled: bool,
+) -> anyhow::Result<()> {
+    update_config(ctx, feature, enabled.to_string(), |c| {
+        std::mem::replace(feature.get_mut(c), enabled).to_string()
+    })
+    .await
+}
+
+/// Applies a change to the edited config, saves it to disk, and records it in the audit log.
+async fn update_config<S, F>(
+    ctx: Context<'_>,
+    setting: S,
+    new_value: String,
+    f: F,
+) -> anyhow::Result<()>
+where
+    S: std::fmt::Display,
+    F: FnOnce(&mut Config) -> String,
+{
+    let old_value = {
+        let mut data = ctx.data().data.write().await;
+        let config = data
+            .edited_config
+            .get_or_insert_with(|| Config::clone(&ctx.data().config));
+
+        let old_value = f(config);
+        config.save().context(here!())?;
+
+        old_value
+    };
+
+    audit_log::record(
+        ctx.http(),
+        AuditEntry::new(AuditAction::ConfigChanged, setting.to_string())
+            .guild(ctx.guild_id())
+            .actor(ctx.author().id)
+            .details(format!("{old_value} → {new_value}")),
+    )
+    .await;
+
+    ctx.send(|m| {
+        m.ephemeral(true).content(format!(
+            "Changed {setting} from {old_value} to {new_value}. \
+            The change will take effect once the bot restarts."
+        ))
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn reply_invalid(ctx: Context<'_>, reason: &str) -> anyhow::Result<()> {
+    ctx.send(|m| m.ephemeral(true).content(format!("Error! {reason}")))
+        .await?;
+
+    Ok(())
+}
+
+fn redact_secrets(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                let key = key.to_ascii_lowercase();
+
+                if value.is_str() && (key.contains("token") || key.contains("pass")) {
+                    *value = toml::Value::String("<redacted>".to_owned());
+                } else {
+                    redact_secrets(value);
+                }
+            }
+        }
+        toml::Value::Array(array) => array.iter_mut().for_each(redact_secrets),
+        _ => (),
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::{Duration, Utc};
+
+use super::prelude::*;
+use crate::command_metrics::COMMAND_METRICS;
+
+const MESSAGE_LIMIT: usize = 2000;
+const REPORTED_COMMANDS: usize = 10;
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    required_permissions = "KICK_MEMBERS",
+    subcommands("latency", "commands", "streams"),
+    category = "Admin"
+)]
+/// Inspect the internals of the bot.
+pub(crate) async fn debug(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Show how far behind streams going live the bot has been.
+pub(crate) async fn latency(ctx: Context<'_>) -> anyhow::Result<()> {
+    ctx.send(|m| {
+        m.ephemeral(true).embed(|e| {
+            e.title("Stream go-live latency")
+                .colour(ctx.data().config.theme.default);
+
+            for kind in [LatencyKind::Detection, LatencyKind::Alert] {
+                let summary = match STREAM_LATENCY.summary(kind) {
+                    Some(s) => format!(
+                        "Mean: {}\nMedian: {}\n90th percentile: {}\nMax: {}\nSamples: {}",
+                        format_latency(s.mean),
+                        format_latency(s.median),
+                        format_latency(s.p90),
+                        format_latency(s.max),
+                        s.samples
+                    ),
+                    None => "No streams have gone live yet.".to_owned(),
+                };
+
+                let recent = STREAM_LATENCY
+                    .recent(kind, 5)
+                    .into_iter()
+                    .map(|s| {
+                        format!(
+                            "<t:{}:R> [{}](https://youtube.com/watch?v={}): {}",
+                            s.recorded_at.timestamp(),
+                            s.stream,
+                            s.stream,
+                            format_latency(s.latency)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                e.field(kind, summary, true);
+
+                if !recent.is_empty() {
+                    e.field(format!("Recent {kind}"), recent.join("\n"), true);
+                }
+            }
+
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, owners_only)]
+/// Show the slowest and most error-prone commands of the last 24 hours.
+pub(crate) async fn commands(ctx: Context<'_>) -> anyhow::Result<()> {
+    let mut summaries = COMMAND_METRICS.summaries(Utc::now() - Duration::hours(24));
+
+    if summaries.is_empty() {
+        ctx.send(|m| {
+            m.ephemeral(true)
+                .content("No commands have been used in the last 24 hours.")
+        })
+        .await?;
+
+        return Ok(());
+    }
+
+    summaries.sort_unstable_by(|a, b| b.mean.cmp(&a.mean));
+
+    let slowest = summaries
+        .iter()
+        .take(REPORTED_COMMANDS)
+        .map(|s| {
+            format!(
+                "`/{}`: {} on average, {} at most, {} waiting ({} runs)",
+                s.command,
+                format_latency(s.mean),
+                format_latency(s.max),
+                format_latency(s.mean_queue_wait),
+                s.runs
+            )
+        })
+        .collect::<Vec<_>>();
+
+    summaries.retain(|s| s.failures > 0);
+    summaries.sort_unstable_by(|a, b| {
+        b.failure_rate()
+            .total_cmp(&a.failure_rate())
+            .then(b.failures.cmp(&a.failures))
+    });
+
+    let failing = summaries
+        .iter()
+        .take(REPORTED_COMMANDS)
+        .map(|s| {
+            format!(
+                "`/{}`: {:.0}% failed ({} of {} runs)",
+                s.command,
+                s.failure_rate() * 100.0,
+                s.failures,
+                s.runs
+            )
+        })
+        .collect::<Vec<_>>();
+
+    ctx.send(|m| {
+        m.ephemeral(true).embed(|e| {
+            e.title("Command performance, last 24 hours")
+                .colour(ctx.data().config.theme.default)
+                .field("Slowest", slowest.join("\n"), false)
+                .field(
+                    "Most errors",
+                    if failing.is_empty() {
+                        "No commands have failed.".to_owned()
+                    } else {
+                        failing.join("\n")
+                    },
+                    false,
+                )
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    check = "stream_tracking_enabled",
+    subcommands("list", "force", "refresh")
+)]
+/// Inspect and manage the stream index.
+pub(crate) async fn streams(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    check = "stream_tracking_enabled"
+)]
+/// Show every stream in the index, along with the chat claimed for it.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let lines = {
+        let data = ctx.data().data.read().await;
+
+        let index = data
+            .stream_index
+            .as_ref()
+            .ok_or_else(|| anyhow!("Stream index is not available."))?
+            .borrow();
+        let claimed_chats = data.claimed_chats.borrow();
+
+        let mut streams = index.values().collect::<Vec<_>>();
+        streams.sort_unstable_by_key(|s| s.start_at);
+
+        streams
+            .into_iter()
+            .map(|s| {
+                format!(
+                    "`{}` {:?} <t:{}:f> {} {}: {}",
+                    s.id,
+                    s.state,
+                    s.start_at.timestamp(),
+                    claimed_chats
+                        .get(&s.id)
+                        .map_or_else(|| "-".to_owned(), |c| Mention::from(*c).to_string()),
+                    s.streamer.name,
+                    s.title.chars().take(60).collect::<String>()
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if lines.is_empty() {
+        ctx.send(|m| m.ephemeral(true).content("The stream index is empty."))
+            .await?;
+
+        return Ok(());
+    }
+
+    let mut message = String::new();
+
+    for line in lines {
+        if message.len() + line.len() >= MESSAGE_LIMIT {
+            let content = std::mem::take(&mut message);
+            ctx.send(|m| m.ephemeral(true).content(content)).await?;
+        }
+
+        message.push_str(&line);
+        message.push('\n');
+    }
+
+    ctx.send(|m| m.ephemeral(true).content(message)).await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    check = "stream_tracking_enabled"
+)]
+/// Move a stream into a state, for when Holodex is stuck reporting the wrong one.
+pub(crate) async fn force(
+    ctx: Context<'_>,
+    #[description = "The ID of the stream."]
+    #[autocomplete = "autocomplete_stream"]
+    stream: String,
+    #[description = "The state to move the stream into."] state: StreamState,
+) -> anyhow::Result<()> {
+    let id: VideoId = stream.trim().parse().context(here!())?;
+
+    send_index_command(ctx, IndexCommand::SetState(id.clone(), state)).await?;
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("Moving `{id}` into the {state:?} state."))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    check = "stream_tracking_enabled"
+)]
+/// Poll Holodex for updates right away.
+pub(crate) async fn refresh(ctx: Context<'_>) -> anyhow::Result<()> {
+    send_index_command(ctx, IndexCommand::Refresh).await?;
+
+    ctx.send(|m| m.ephemeral(true).content("Refreshing the stream index."))
+        .await?;
+
+    Ok(())
+}
+
+async fn send_index_command(ctx: Context<'_>, command: IndexCommand) -> anyhow::Result<()> {
+    let sender = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .index_commands
+        .clone()
+        .ok_or_else(|| anyhow!("Stream index is not available."))?;
+
+    sender.send(command).await.context(here!())
+}
+
+async fn autocomplete_stream(
+    ctx: Context<'_>,
+    partial: &str,
+) -> impl Iterator<Item = AutocompleteChoice<String>> {
+    let partial = partial.to_lowercase();
+    let data = ctx.data().data.read().await;
+
+    let mut streams = data
+        .stream_index
+        .as_ref()
+        .map(|index| {
+            index
+                .borrow()
+                .values()
+                .filter(|s| {
+                    s.id.to_string().contains(&partial)
+                        || s.title.to_lowercase().contains(&partial)
+                        || s.streamer.name.to_lowercase().contains(&partial)
+                })
+                .map(|s| AutocompleteChoice {
+                    name: format!("[{:?}] {}: {}", s.state, s.streamer.name, s.title)
+                        .chars()
+                        .take(100)
+                        .collect(),
+                    value: s.id.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    streams.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    streams.into_iter().take(25)
+}
+
+async fn stream_tracking_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.stream_tracking.enabled)
+}
+
+fn format_latency(latency: Duration) -> String {
+    let seconds = latency.num_milliseconds() as f64 / 1000.0;
+
+    if seconds < 60.0 {
+        format!("{seconds:.1}s")
+    } else {
+        format!(
+            "{}m {:02}s",
+            latency.num_minutes(),
+            latency.num_seconds() % 60
+        )
+    }
+}
@@ -1,33 +1,52 @@
+use poise::serenity_prelude::ButtonStyle;
+use utility::config::EmbedEvent;
+
 use super::prelude::*;
 
-#[poise::command(slash_command)]
+/// Discord allows five buttons in each row, and five rows in each message.
+const BUTTONS_PER_ROW: usize = 5;
+const MAX_ROWS: usize = 5;
+
+#[poise::command(slash_command, check = "support_enabled", category = "Utility")]
 /// Support me, peko!
 pub(crate) async fn donate(ctx: Context<'_>) -> anyhow::Result<()> {
+    let config = &ctx.data().config;
+    let support = &config.support;
+
     ctx.send(|m| {
-        m.ephemeral(true).embed(|e| {
-            e
-                .title("Donation Information")
-                .colour(Colour::from_rgb(0xEC, 0x9C, 0xFC))
-                .description(
-                    "*Almondo, almondo peko!*\n\n\
-                    If you are interested in helping support my development, \
-                    and invest in better hosting, we'd appreciate your support peko!\n\n\
-                    Any amount is appreciated, and all donations will go directly towards development \
-                    and new hardware peko!")
-                .field(
-                    "Links", 
-                    "Donations can be made via either [GitHub Sponsors](https://github.com/sponsors/anden3) \
-                    or [Ko-Fi](https://ko-fi.com/anden3) peko! \
-                    Any amount is greatly appreciated peko!", false)
-                .field(
-                    "Disclaimer",
-                    "No donations will ever be required to access any features of the bot, \
-                    so if you feel like you can't spare some extra money, please save it for yourself peko. \
-                    Additionally, please consider that all donations are non-refundable peko.",
-                    false)
-                .footer(|f| f.text("I am made by anden3#0003 peko!"))
-        })
-    }).await?;
+        m.ephemeral(true)
+            .embed(|e| {
+                e.title(&support.title)
+                    .colour(config.theme.colour(EmbedEvent::Support))
+                    .description(&support.description);
+
+                if let Some(footer) = &support.footer {
+                    e.footer(|f| f.text(footer));
+                }
+
+                e
+            })
+            .components(|c| {
+                for links in support.links.chunks(BUTTONS_PER_ROW).take(MAX_ROWS) {
+                    c.create_action_row(|r| {
+                        for link in links {
+                            r.create_button(|b| {
+                                b.style(ButtonStyle::Link).label(&link.label).url(&link.url)
+                            });
+                        }
+
+                        r
+                    });
+                }
+
+                c
+            })
+    })
+    .await?;
 
     Ok(())
 }
+
+async fn support_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.support.enabled)
+}
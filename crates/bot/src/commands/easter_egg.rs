@@ -0,0 +1,245 @@
+use chrono::Duration;
+use poise::serenity_prelude::CacheHttp;
+use utility::audit_log::{self, AuditAction, AuditEntry};
+
+use super::prelude::*;
+use crate::easter_eggs::EasterEggs;
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum ResponseKind {
+    #[name = "Text"]
+    Text,
+    #[name = "Image URL"]
+    Image,
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS",
+    subcommands("add", "remove", "toggle", "cooldown", "list"),
+    category = "Admin"
+)]
+/// Manage the easter eggs the bot replies to in this server.
+pub(crate) async fn easter_egg(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Add a response to a trigger, it's picked at random if the trigger has several.
+pub(crate) async fn add(
+    ctx: Context<'_>,
+    #[description = "The message that triggers the easter egg."] trigger: String,
+    #[description = "The kind of response."] kind: ResponseKind,
+    #[description = "The text, or the URL of the image."] response: String,
+    #[description = "How long to wait between replies, in seconds."] cooldown: Option<u32>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let trigger = EasterEggs::normalize_trigger(&trigger);
+
+    if trigger.is_empty() {
+        return reply(ctx, "Error! The trigger can't be empty.").await;
+    }
+
+    let response = match kind {
+        ResponseKind::Text => EasterEggResponse::Text(response),
+        ResponseKind::Image => EasterEggResponse::Image(response),
+    };
+
+    let entry = audit_entry(&trigger, "Response added.");
+
+    update_eggs(ctx, entry, |eggs| {
+        eggs.add_response(
+            guild_id,
+            &trigger,
+            response,
+            cooldown.map(|c| Duration::seconds(c.into())),
+        );
+
+        true
+    })
+    .await?;
+
+    reply(ctx, &format!("Added a response to \"{trigger}\".")).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Remove an easter egg and all its responses.
+pub(crate) async fn remove(
+    ctx: Context<'_>,
+    #[description = "The trigger to remove."]
+    #[autocomplete = "autocomplete_trigger"]
+    trigger: String,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let trigger = EasterEggs::normalize_trigger(&trigger);
+
+    let entry = audit_entry(&trigger, "Removed.");
+
+    let removed = update_eggs(ctx, entry, |eggs| eggs.remove(guild_id, &trigger)).await?;
+
+    let content = if removed {
+        format!("Removed \"{trigger}\".")
+    } else {
+        format!("Error! No easter egg made in this server is triggered by \"{trigger}\".")
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Enable or disable an easter egg in this server.
+pub(crate) async fn toggle(
+    ctx: Context<'_>,
+    #[description = "The trigger to toggle."]
+    #[autocomplete = "autocomplete_trigger"]
+    trigger: String,
+    #[description = "Whether the easter egg should reply."] enabled: bool,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let trigger = EasterEggs::normalize_trigger(&trigger);
+
+    let entry = audit_entry(&trigger, if enabled { "Enabled." } else { "Disabled." });
+
+    let found = update_eggs(ctx, entry, |eggs| {
+        eggs.update(guild_id, &trigger, |egg| egg.enabled = enabled)
+    })
+    .await?;
+
+    let content = match (found, enabled) {
+        (false, _) => format!("Error! No easter egg is triggered by \"{trigger}\"."),
+        (true, true) => format!("\"{trigger}\" is now enabled."),
+        (true, false) => format!("\"{trigger}\" is now disabled."),
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Set how long an easter egg waits before replying again.
+pub(crate) async fn cooldown(
+    ctx: Context<'_>,
+    #[description = "The trigger to change."]
+    #[autocomplete = "autocomplete_trigger"]
+    trigger: String,
+    #[description = "The cooldown, in seconds."] seconds: u32,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let trigger = EasterEggs::normalize_trigger(&trigger);
+
+    let entry = audit_entry(&trigger, format!("Cooldown set to {seconds} seconds."));
+
+    let found = update_eggs(ctx, entry, |eggs| {
+        eggs.update(guild_id, &trigger, |egg| {
+            egg.cooldown = Duration::seconds(seconds.into())
+        })
+    })
+    .await?;
+
+    let content = if found {
+        format!("\"{trigger}\" now waits {seconds} seconds between replies.")
+    } else {
+        format!("Error! No easter egg is triggered by \"{trigger}\".")
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Show the easter eggs in this server.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let eggs = ctx.data().data.read().await.easter_eggs.list(guild_id);
+
+    let content = if eggs.is_empty() {
+        "There are no easter eggs in this server.".to_owned()
+    } else {
+        eggs.iter()
+            .map(|(trigger, egg)| {
+                format!(
+                    "\"{trigger}\": {} response(s), {} second cooldown{}",
+                    egg.responses.len(),
+                    egg.cooldown.num_seconds(),
+                    if egg.enabled { "" } else { " (disabled)" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    reply(ctx, &content).await
+}
+
+/// Applies a change to the easter eggs, and if anything changed,
+/// saves them and records it in the audit log.
+async fn update_eggs<F>(ctx: Context<'_>, entry: AuditEntry, f: F) -> anyhow::Result<bool>
+where
+    F: FnOnce(&mut EasterEggs) -> bool,
+{
+    {
+        let mut data = ctx.data().data.write().await;
+
+        if !f(&mut data.easter_eggs) {
+            return Ok(false);
+        }
+
+        let handle = data.database.lock().await;
+        data.easter_eggs.save(&handle).context(here!())?;
+    }
+
+    audit_log::record(
+        ctx.http(),
+        entry.guild(ctx.guild_id()).actor(ctx.author().id),
+    )
+    .await;
+
+    Ok(true)
+}
+
+fn audit_entry(trigger: &str, details: impl Into<String>) -> AuditEntry {
+    AuditEntry::new(
+        AuditAction::ConfigChanged,
+        format!("Easter egg \"{trigger}\""),
+    )
+    .details(details)
+}
+
+async fn autocomplete_trigger(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = partial.to_lowercase();
+
+    let eggs = match ctx.guild_id() {
+        Some(guild_id) => ctx.data().data.read().await.easter_eggs.list(guild_id),
+        None => Vec::new(),
+    };
+
+    eggs.into_iter()
+        .map(|(trigger, _)| trigger)
+        .filter(move |trigger| trigger.contains(&partial))
+        .take(25)
+}
@@ -30,7 +30,8 @@ static RESPONSES: &[&str] = &[
     prefix_command,
     rename = "8ball",
     required_permissions = "SEND_MESSAGES",
-    member_cooldown = 60
+    member_cooldown = 60,
+    category = "Fun"
 )]
 /// Roll an 8-ball, peko.
 pub(crate) async fn eightball(
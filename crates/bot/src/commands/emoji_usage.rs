@@ -57,7 +57,8 @@ pub(crate) enum EmojiType {
     prefix_command,
     track_edits,
     check = "emoji_tracking_enabled",
-    required_permissions = "VIEW_AUDIT_LOG"
+    required_permissions = "VIEW_AUDIT_LOG",
+    category = "Statistics"
 )]
 /// Shows the most used custom emotes in this server.
 pub(crate) async fn emoji_usage(
@@ -0,0 +1,264 @@
+use apis::object_storage::ObjectStorage;
+use poise::serenity_prelude::Attachment;
+
+use super::prelude::*;
+use crate::emotes::{Emote, Emotes};
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    check = "emotes_enabled",
+    subcommands("send", "add", "remove", "list"),
+    category = "Fun"
+)]
+/// Post images from this server's emote library.
+pub(crate) async fn emote(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, guild_only, member_cooldown = 5)]
+/// Post an emote.
+pub(crate) async fn send(
+    ctx: Context<'_>,
+    #[description = "The name of the emote."]
+    #[autocomplete = "autocomplete_emote"]
+    name: String,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let name = Emotes::normalize_name(&name);
+
+    let url = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .emotes
+        .get(guild_id, &name)
+        .map(|e| e.url.clone());
+
+    let url = match url {
+        Some(url) => url,
+        None => return reply(ctx, &format!("Error! No emote named \"{name}\" found.")).await,
+    };
+
+    ctx.send(|m| m.embed(|e| e.image(url))).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+/// Add an image to the emote library, replacing any emote with the same name.
+pub(crate) async fn add(
+    ctx: Context<'_>,
+    #[description = "The name to post the emote with."] name: String,
+    #[description = "The image to post."] image: Attachment,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let name = Emotes::normalize_name(&name);
+    let config = &ctx.data().config.emotes;
+
+    if name.is_empty() || name.chars().count() > 32 {
+        return reply(ctx, "Error! The name must be between 1 and 32 characters.").await;
+    }
+
+    if !can_upload(ctx).await {
+        return reply(ctx, "Error! You're not allowed to add emotes.").await;
+    }
+
+    if !matches!(&image.content_type, Some(t) if t.starts_with("image/")) {
+        return reply(ctx, "Error! The attachment must be an image.").await;
+    }
+
+    if image.size > u64::from(config.max_size_kb) * 1024 {
+        return reply(
+            ctx,
+            &format!("Error! Emotes can be at most {} kB.", config.max_size_kb),
+        )
+        .await;
+    }
+
+    let uploader = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .emotes
+        .get(guild_id, &name)
+        .map(|e| e.uploader);
+
+    if let Some(uploader) = uploader {
+        if uploader != ctx.author().id && !can_manage(ctx).await {
+            return reply(
+                ctx,
+                &format!("Error! \"{name}\" was added by someone else."),
+            )
+            .await;
+        }
+    }
+
+    let storage = match storage(ctx) {
+        Some(storage) => storage,
+        None => return reply(ctx, "Error! No storage is set up for emotes.").await,
+    };
+
+    let key = format!(
+        "emotes/{guild_id}/{}-{}",
+        image.id,
+        ObjectStorage::key_segment(&image.filename)
+    );
+    let data = image.download().await.context(here!())?;
+    let content_type = image.content_type.as_deref().unwrap_or("image/png");
+
+    let emote = Emote {
+        uploader: ctx.author().id,
+        url: storage
+            .upload(&key, &data, content_type)
+            .await
+            .context(here!())?,
+        key,
+    };
+
+    let replaced = {
+        let mut data = ctx.data().data.write().await;
+        let data = &mut *data;
+        let handle = data.database.lock().await;
+
+        data.emotes
+            .add(&handle, guild_id, &name, emote)
+            .context(here!())?
+    };
+
+    if let Some(replaced) = replaced {
+        delete_image(&storage, &replaced).await;
+    }
+
+    reply(ctx, &format!("Added \"{name}\".")).await
+}
+
+#[poise::command(slash_command, prefix_command, guild_only)]
+/// Remove an emote, only its uploader and members who can manage emojis can remove it.
+pub(crate) async fn remove(
+    ctx: Context<'_>,
+    #[description = "The name of the emote."]
+    #[autocomplete = "autocomplete_emote"]
+    name: String,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let name = Emotes::normalize_name(&name);
+
+    let uploader = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .emotes
+        .get(guild_id, &name)
+        .map(|e| e.uploader);
+
+    let uploader = match uploader {
+        Some(u) => u,
+        None => return reply(ctx, &format!("Error! No emote named \"{name}\" found.")).await,
+    };
+
+    if uploader != ctx.author().id && !can_manage(ctx).await {
+        return reply(ctx, "Error! You can only remove emotes you added.").await;
+    }
+
+    let removed = {
+        let mut data = ctx.data().data.write().await;
+        let data = &mut *data;
+        let handle = data.database.lock().await;
+
+        data.emotes
+            .remove(&handle, guild_id, &name)
+            .context(here!())?
+    };
+
+    if let (Some(removed), Some(storage)) = (removed, storage(ctx)) {
+        delete_image(&storage, &removed).await;
+    }
+
+    reply(ctx, &format!("Removed \"{name}\".")).await
+}
+
+#[poise::command(slash_command, prefix_command, guild_only)]
+/// Show the emotes in this server.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let names = ctx.data().data.read().await.emotes.names(guild_id);
+
+    let content = if names.is_empty() {
+        "There are no emotes in this server.".to_owned()
+    } else {
+        names.join(", ")
+    };
+
+    reply(ctx, &content).await
+}
+
+/// Where the emote images are uploaded, if object storage is set up.
+fn storage(ctx: Context<'_>) -> Option<ObjectStorage> {
+    let config = &ctx.data().config.object_storage;
+
+    if !config.enabled {
+        return None;
+    }
+
+    match ObjectStorage::new(config) {
+        Ok(storage) => Some(storage),
+        Err(e) => {
+            error!(?e, "Failed to set up object storage!");
+            None
+        }
+    }
+}
+
+/// Deletes the image of an emote that was removed or replaced. The emote is already gone,
+/// so failing to delete its image is only logged.
+async fn delete_image(storage: &ObjectStorage, emote: &Emote) {
+    if let Err(e) = storage.delete(&emote.key).await {
+        warn!(?e, key = %emote.key, "Failed to delete emote image!");
+    }
+}
+
+/// Members who can manage emojis can always add emotes,
+/// others need one of the uploader roles if any are set.
+async fn can_upload(ctx: Context<'_>) -> bool {
+    let uploader_roles = &ctx.data().config.emotes.uploader_roles;
+
+    if uploader_roles.is_empty() || can_manage(ctx).await {
+        return true;
+    }
+
+    ctx.author_member().await.map_or(false, |m| {
+        m.roles.iter().any(|r| uploader_roles.contains(r))
+    })
+}
+
+async fn can_manage(ctx: Context<'_>) -> bool {
+    match ctx.author_member().await {
+        Some(member) => member
+            .permissions(ctx.discord())
+            .map_or(false, |p| p.manage_emojis_and_stickers()),
+        None => false,
+    }
+}
+
+async fn autocomplete_emote(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = Emotes::normalize_name(partial);
+
+    let names = match ctx.guild_id() {
+        Some(guild_id) => ctx.data().data.read().await.emotes.names(guild_id),
+        None => Vec::new(),
+    };
+
+    names
+        .into_iter()
+        .filter(move |name| name.contains(&partial))
+        .take(25)
+}
+
+async fn emotes_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.emotes.enabled)
+}
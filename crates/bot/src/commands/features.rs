@@ -0,0 +1,103 @@
+use super::prelude::*;
+
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    feature_flags::{self, Feature},
+};
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("enable", "disable", "list"),
+    category = "Admin"
+)]
+/// Turn features on or off in this server.
+pub(crate) async fn features(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+/// Turn a feature back on in this server, if it's enabled in the bot config.
+pub(crate) async fn enable(
+    ctx: Context<'_>,
+    #[description = "The feature to enable."] feature: Feature,
+) -> anyhow::Result<()> {
+    set_enabled(ctx, feature, true).await
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+/// Turn a feature off in this server.
+pub(crate) async fn disable(
+    ctx: Context<'_>,
+    #[description = "The feature to disable."] feature: Feature,
+) -> anyhow::Result<()> {
+    set_enabled(ctx, feature, false).await
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+/// Show the features that are turned off in this server.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let disabled = feature_flags::disabled(guild_id);
+
+    let content = if disabled.is_empty() {
+        "Every feature is enabled in this server.".to_owned()
+    } else {
+        format!(
+            "Disabled in this server: {}.",
+            disabled
+                .iter()
+                .map(ChoiceParameter::name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
+
+/// The feature a command belongs to, so that it can be blocked where the feature is disabled.
+pub(crate) fn command_feature(command: &Command) -> Option<Feature> {
+    let root = command.qualified_name.split(' ').next()?;
+
+    match root {
+        "quote" => Some(Feature::Quotes),
+        "emote" => Some(Feature::Emotes),
+        "meme" => Some(Feature::Memes),
+        "poll" => Some(Feature::Polls),
+        "giveaway" => Some(Feature::Giveaways),
+        "announce" => Some(Feature::Announcements),
+        _ => None,
+    }
+}
+
+async fn set_enabled(ctx: Context<'_>, feature: Feature, enabled: bool) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let was_enabled = feature_flags::is_enabled(guild_id, feature);
+
+    feature_flags::set_enabled(guild_id, feature, enabled).context(here!())?;
+
+    if was_enabled != enabled {
+        audit_log::record(
+            ctx.discord(),
+            AuditEntry::new(AuditAction::ConfigChanged, feature.name())
+                .guild(Some(guild_id))
+                .actor(ctx.author().id)
+                .details(if enabled { "Enabled" } else { "Disabled" }),
+        )
+        .await;
+    }
+
+    let state = if enabled { "enabled" } else { "disabled" };
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("{} is now {state} in this server.", feature.name()))
+    })
+    .await?;
+
+    Ok(())
+}
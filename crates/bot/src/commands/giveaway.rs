@@ -0,0 +1,153 @@
+use chrono::{Duration, Utc};
+use utility::functions::parse_duration;
+
+use super::prelude::*;
+use crate::giveaways::Giveaway;
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    check = "giveaways_enabled",
+    subcommands("start", "reroll"),
+    category = "Admin"
+)]
+/// Give away prizes to members who enter.
+pub(crate) async fn giveaway(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    required_bot_permissions = "SEND_MESSAGES"
+)]
+/// Post a giveaway in this channel that members enter by pressing a button.
+pub(crate) async fn start(
+    ctx: Context<'_>,
+    #[description = "What the winners get."] prize: String,
+    #[description = "How long members can enter, ex. `1d 12h` or `30m`."] duration: String,
+    #[description = "How many winners to pick, defaults to one."]
+    #[min = 1]
+    #[max = 20]
+    winners: Option<u32>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let duration = match parse_duration(&duration).map(Duration::from_std) {
+        Ok(Ok(duration)) if duration <= Duration::days(MAX_DURATION_DAYS) => duration,
+        Ok(_) => {
+            return reply(
+                ctx,
+                &format!("Error! Giveaways can run for at most {MAX_DURATION_DAYS} days."),
+            )
+            .await
+        }
+        Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    let mut giveaway = Giveaway {
+        message: MessageId(0),
+        channel: ctx.channel_id(),
+        guild: guild_id,
+        host: ctx.author().id,
+        prize,
+        winner_count: winners.unwrap_or(1),
+        ends_at: Utc::now() + duration,
+        winners: Vec::new(),
+        ended: false,
+    };
+
+    let colour = ctx.data().config.theme.default;
+
+    let message = giveaway
+        .channel
+        .send_message(ctx.discord(), |m| {
+            m.embed(|e| giveaway.embed(e, colour))
+                .components(|c| giveaway.components(c))
+        })
+        .await
+        .context(here!())?;
+
+    giveaway.message = message.id;
+
+    {
+        let data = ctx.data().data.read().await;
+
+        {
+            let handle = data.database.lock().await;
+            giveaway.insert(&handle).context(here!())?;
+        }
+
+        data.giveaways_changed.notify_one();
+    }
+
+    reply(
+        ctx,
+        &format!(
+            "Started the giveaway, it ends <t:{}:F>.",
+            giveaway.ends_at.timestamp()
+        ),
+    )
+    .await
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+/// Pick new winners for a giveaway that has ended, among the entrants who haven't won yet.
+pub(crate) async fn reroll(
+    ctx: Context<'_>,
+    #[description = "The ID or link of the giveaway message."] message: String,
+    #[description = "How many new winners to pick, defaults to one."]
+    #[min = 1]
+    #[max = 20]
+    winners: Option<u32>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let message_id = match message.rsplit('/').next().map(str::parse) {
+        Some(Ok(id)) => MessageId(id),
+        _ => return reply(ctx, "Error! That's not a message ID or link.").await,
+    };
+
+    let (giveaway, new_winners) = {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        let mut giveaway = match Giveaway::get(&handle, message_id).context(here!())? {
+            Some(g) if g.guild == guild_id => g,
+            _ => return reply(ctx, "Error! No giveaway found for that message.").await,
+        };
+
+        if !giveaway.ended {
+            return reply(ctx, "Error! The giveaway hasn't ended yet.").await;
+        }
+
+        let new_winners = giveaway
+            .draw_winners(&handle, winners.unwrap_or(1))
+            .context(here!())?;
+
+        (giveaway, new_winners)
+    };
+
+    giveaway
+        .announce_winners(ctx.discord(), &new_winners, ctx.data().config.theme.default)
+        .await
+        .context(here!())?;
+
+    // Saved after the announcement, so that a failed one can be rerolled again.
+    {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        giveaway.save_winners(&handle).context(here!())?;
+    }
+
+    reply(ctx, &format!("Picked {} new winner(s).", new_winners.len())).await
+}
+
+const MAX_DURATION_DAYS: i64 = 30;
+
+async fn giveaways_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.giveaways.enabled)
+}
@@ -1,24 +1,238 @@
+use std::collections::BTreeMap;
+
+use serenity::builder::CreateEmbed;
+
 use super::prelude::*;
 
-/// Show this menu.
-#[poise::command(prefix_command, track_edits, slash_command)]
+#[derive(Debug)]
+struct HelpCategory {
+    name: String,
+    commands: Vec<String>,
+    page: usize,
+    pages: usize,
+}
+
+/// Show the commands of the bot, or details about a specific one.
+#[poise::command(prefix_command, track_edits, slash_command, category = "Utility")]
 pub async fn help(
     ctx: Context<'_>,
     #[description = "Specific command to show help about"]
     #[autocomplete = "poise::builtins::autocomplete_command"]
     command: Option<String>,
 ) -> Result<(), Error> {
-    let config = poise::builtins::HelpConfiguration {
-        extra_text_at_bottom: &format!(
-            "\
-Type {}help command for more info on a command.
-You can edit your message to the bot and the bot will edit its response.",
-            ctx.prefix()
-        ),
-        show_context_menu_commands: true,
-        ..Default::default()
+    let commands = &ctx.framework().options().commands;
+
+    let command = match command {
+        Some(name) => match find_command(commands, &name) {
+            Some(command) => command,
+            None => {
+                ctx.send(|m| {
+                    m.ephemeral(true)
+                        .content(format!("Error! No command named {name} found."))
+                })
+                .await?;
+
+                return Ok(());
+            }
+        },
+        None => return show_overview(ctx, commands).await,
     };
 
-    poise::builtins::help(ctx, command.as_deref(), config).await?;
+    let rate_limits = match ctx.guild_id() {
+        Some(guild_id) => {
+            let data = ctx.data().data.read().await;
+
+            data.rate_limits
+                .limits(guild_id)
+                .filter(|(name, _)| {
+                    *name == command.qualified_name
+                        || name.starts_with(&format!("{} ", command.qualified_name))
+                })
+                .map(|(name, limit)| {
+                    format!(
+                        "`{}{name}`: {} time(s) every {} seconds",
+                        ctx.prefix(),
+                        limit.uses,
+                        limit.period.num_seconds()
+                    )
+                })
+                .collect::<Vec<_>>()
+        }
+        None => Vec::new(),
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(ctx.data().config.theme.default)
+        .title(command_name(ctx, command));
+
+    let mut description = command.description.clone().unwrap_or_default();
+
+    if let Some(help_text) = command.help_text {
+        description.push_str("\n\n");
+        description.push_str(&help_text());
+    }
+
+    embed.description(description);
+
+    if !command.parameters.is_empty() {
+        embed.field(
+            "Options",
+            command
+                .parameters
+                .iter()
+                .map(|p| {
+                    format!(
+                        "`{}`{}: {}",
+                        p.name,
+                        if p.required { "" } else { " (optional)" },
+                        p.description.as_deref().unwrap_or("No description.")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            false,
+        );
+    }
+
+    let subcommands = visible_commands(&command.subcommands)
+        .map(|c| command_summary(ctx, c))
+        .collect::<Vec<_>>();
+
+    if !subcommands.is_empty() {
+        embed.field("Subcommands", subcommands.join("\n"), false);
+    }
+
+    if !command.required_permissions.is_empty() {
+        embed.field(
+            "Required permissions",
+            command
+                .required_permissions
+                .get_permission_names()
+                .join(", "),
+            false,
+        );
+    }
+
+    if !rate_limits.is_empty() {
+        embed.field("Rate limits", rate_limits.join("\n"), false);
+    }
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            *e = embed;
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Lists the commands grouped by category, one category per page.
+async fn show_overview(ctx: Context<'_>, commands: &[Command]) -> Result<(), Error> {
+    let mut categories = BTreeMap::<&str, Vec<String>>::new();
+
+    for command in visible_commands(commands) {
+        categories
+            .entry(command.category.unwrap_or("Other"))
+            .or_default()
+            .push(command_summary(ctx, command));
+    }
+
+    let pages = categories.len();
+
+    let categories = categories
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, mut commands))| {
+            commands.sort_unstable();
+
+            HelpCategory {
+                name: name.to_owned(),
+                commands,
+                page: i + 1,
+                pages,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let colour = ctx.data().config.theme.default;
+    let footer = format!(
+        "Type {}help command for more info on a command.",
+        ctx.prefix()
+    );
+
+    PaginatedList::new()
+        .layout(PageLayout::Standard { items_per_page: 1 })
+        .data(&categories)
+        .embed(Box::new(move |category, _| {
+            let mut embed = CreateEmbed::default();
+
+            embed
+                .colour(colour)
+                .title(&category.name)
+                .description(category.commands.join("\n"))
+                .footer(|f| {
+                    f.text(format!(
+                        "Page {} of {} | {footer}",
+                        category.page, category.pages
+                    ))
+                });
+
+            embed
+        }))
+        .display(ctx)
+        .await?;
+
     Ok(())
 }
+
+fn visible_commands(commands: &[Command]) -> impl Iterator<Item = &Command> {
+    commands
+        .iter()
+        .filter(|c| !c.hide_in_help && !c.owners_only)
+}
+
+/// Context menu commands can't be invoked by name, so they're shown by their menu entry instead.
+fn command_name(ctx: Context<'_>, command: &Command) -> String {
+    match command.context_menu_name {
+        Some(menu_name) if command.slash_action.is_none() && command.prefix_action.is_none() => {
+            format!("{menu_name} (context menu)")
+        }
+        _ => format!("{}{}", ctx.prefix(), command.qualified_name),
+    }
+}
+
+fn command_summary(ctx: Context<'_>, command: &Command) -> String {
+    format!(
+        "`{}`: {}",
+        command_name(ctx, command),
+        command.description.as_deref().unwrap_or("No description.")
+    )
+}
+
+/// Finds a command by its qualified name, such as `quote add`, or its context menu entry, ignoring case.
+fn find_command<'a>(commands: &'a [Command], name: &str) -> Option<&'a Command> {
+    if let Some(command) = commands.iter().find(|c| {
+        c.context_menu_name
+            .map_or(false, |n| n.eq_ignore_ascii_case(name.trim()))
+    }) {
+        return Some(command);
+    }
+
+    let mut words = name.split_whitespace();
+    let mut command = find_by_name(commands, words.next()?)?;
+
+    for word in words {
+        command = find_by_name(&command.subcommands, word)?;
+    }
+
+    Some(command)
+}
+
+fn find_by_name<'a>(commands: &'a [Command], name: &str) -> Option<&'a Command> {
+    commands.iter().find(|c| {
+        c.name.eq_ignore_ascii_case(name) || c.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    })
+}
@@ -3,7 +3,10 @@ use std::borrow::Cow;
 use chrono::{DateTime, Utc};
 use serenity::builder::CreateEmbed;
 
-use super::prelude::*;
+use super::{
+    prelude::*,
+    timezone::{autocomplete_timezone, format_localized_time, resolve_timezone},
+};
 
 use utility::config::HoloBranch;
 
@@ -12,16 +15,33 @@ use utility::config::HoloBranch;
     prefix_command,
     track_edits,
     check = "stream_tracking_enabled",
-    required_permissions = "SEND_MESSAGES"
+    required_permissions = "SEND_MESSAGES",
+    category = "Hololive"
 )]
 /// Shows the Hololive talents who are live right now.
 pub(crate) async fn live(
     ctx: Context<'_>,
     #[description = "Show only talents from this branch of Hololive."] branch: Option<HoloBranch>,
+    #[description = "Show only streams on this topic (ex. singing)."]
+    #[autocomplete = "autocomplete_topic"]
+    topic: Option<String>,
+    #[description = "Show times in this timezone (ex. America/New_York), defaults to your stored timezone."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
 ) -> anyhow::Result<()> {
+    let timezone = match resolve_timezone(ctx, timezone.as_deref()).await {
+        Ok(tz) => tz,
+        Err(e) => {
+            ctx.send(|m| m.ephemeral(true).content(format!("Error! {e}")))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
     ctx.defer().await?;
 
-    let currently_live = get_currently_live(ctx, branch).await;
+    let currently_live = get_currently_live(ctx, branch, topic.as_deref()).await;
 
     PaginatedList::new()
         .title(format!(
@@ -29,31 +49,23 @@ pub(crate) async fn live(
             branch.map(|b| format!(" from {b}")).unwrap_or_default()
         ))
         .data(&currently_live)
-        .embed(Box::new(|l, _| {
+        .embed(Box::new(move |l, _| {
             let mut embed = CreateEmbed::default();
 
             embed.colour(l.colour);
             embed.thumbnail(l.thumbnail.to_owned());
             embed.timestamp(l.start_at.to_rfc3339());
             embed.description(format!(
-                "{}\r\n{}\r\n<{}>",
+                "{}\r\n{}\r\n<{}>\r\nStarted streaming {}.",
                 if let Some(role) = l.role {
                     Cow::Owned(Mention::from(role).to_string())
                 } else {
                     Cow::Borrowed(&l.name)
                 },
                 l.title,
-                l.url
+                l.url,
+                format_localized_time(l.start_at, timezone)
             ));
-            embed.footer(|f| {
-                f.text(format!(
-                    "Started streaming {}.",
-                    chrono_humanize::HumanTime::from(Utc::now() - l.start_at).to_text_en(
-                        chrono_humanize::Accuracy::Rough,
-                        chrono_humanize::Tense::Past
-                    )
-                ))
-            });
 
             embed
         }))
@@ -74,7 +86,11 @@ struct LiveEmbedData {
     thumbnail: String,
 }
 
-async fn get_currently_live(ctx: Context<'_>, branch: Option<HoloBranch>) -> Vec<LiveEmbedData> {
+async fn get_currently_live(
+    ctx: Context<'_>,
+    branch: Option<HoloBranch>,
+    topic: Option<&str>,
+) -> Vec<LiveEmbedData> {
     let data = ctx.data();
     let read_lock = data.data.read().await;
 
@@ -99,7 +115,7 @@ async fn get_currently_live(ctx: Context<'_>, branch: Option<HoloBranch>) -> Vec
                 }
             }
 
-            true
+            topic.map_or(true, |t| l.has_topic(t))
         })
         .map(|(_, l)| LiveEmbedData {
             name: l.streamer.name.clone(),
@@ -113,6 +129,34 @@ async fn get_currently_live(ctx: Context<'_>, branch: Option<HoloBranch>) -> Vec
         .collect::<Vec<_>>()
 }
 
+/// The topics of the streams in the index.
+pub(super) async fn autocomplete_topic(
+    ctx: Context<'_>,
+    partial: &str,
+) -> impl Iterator<Item = String> {
+    let partial = partial.to_ascii_lowercase();
+    let data = ctx.data();
+    let read_lock = data.data.read().await;
+
+    let mut topics = read_lock
+        .stream_index
+        .as_ref()
+        .map(|index| {
+            index
+                .borrow()
+                .values()
+                .filter_map(|l| l.topic.clone())
+                .filter(|t| t.to_ascii_lowercase().contains(&partial))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    topics.sort_unstable();
+    topics.dedup();
+    topics.truncate(25);
+    topics.into_iter()
+}
+
 async fn stream_tracking_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
     Ok(ctx.data().config.stream_tracking.enabled)
 }
@@ -6,7 +6,9 @@ use apis::meme_api::MemeFont;
     slash_command,
     check = "meme_creation_enabled",
     member_cooldown = 60,
-    required_permissions = "ATTACH_FILES"
+    required_permissions = "ATTACH_FILES",
+    required_bot_permissions = "ATTACH_FILES",
+    category = "Fun"
 )]
 /// Generate a meme, peko!
 pub(crate) async fn meme(
@@ -74,7 +76,7 @@ pub(crate) async fn meme(
 
     ctx.send(|m| {
         m.embed(|e| {
-            e.colour(Colour::new(6_282_735));
+            e.colour(ctx.data().config.theme.default);
             e.image(url)
         })
     })
@@ -8,7 +8,9 @@ use super::prelude::*;
     prefix_command,
     rename = "move",
     required_permissions = "SEND_MESSAGES",
-    member_cooldown = 300
+    required_bot_permissions = "SEND_MESSAGES",
+    member_cooldown = 300,
+    category = "Admin"
 )]
 /// Moves the conversation to a different channel.
 pub(crate) async fn move_conversation(
@@ -11,6 +11,7 @@ use regex::Regex;
 use serenity::{builder::CreateEmbed, model::id::UserId};
 
 use super::prelude::*;
+use crate::command_errors::CommandError;
 
 #[poise::command(
     slash_command,
@@ -78,12 +79,7 @@ pub(crate) async fn join(ctx: Context<'_>) -> anyhow::Result<()> {
 
     let connect_to = match channel_id {
         Some(channel_id) => channel_id,
-        None => {
-            ctx.say("Could not find your voice channel, make sure you are in one peko.")
-                .await?;
-
-            return Ok(());
-        }
+        None => return Err(CommandError::NotInVoiceChannel.into()),
     };
 
     let manager = songbird::get(ctx.discord())
@@ -43,7 +43,8 @@ static MATCH_IF_MESSAGE_IS_ONLY_EMOJIS: Lazy<Regex> = regex_lazy!(r"^(?:\s*<a?:\
     prefix_command,
     slash_command,
     required_permissions = "SEND_MESSAGES",
-    member_cooldown = 15
+    member_cooldown = 15,
+    category = "Fun"
 )]
 /// Pekofies provided text.
 pub(crate) async fn pekofy(
@@ -95,7 +96,8 @@ pub(crate) async fn pekofy(
 #[poise::command(
     context_menu_command = "Pekofy message",
     required_permissions = "SEND_MESSAGES",
-    member_cooldown = 15
+    member_cooldown = 15,
+    category = "Fun"
 )]
 /// Pekofies message.
 pub(crate) async fn pekofy_message(
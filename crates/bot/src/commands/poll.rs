@@ -0,0 +1,168 @@
+use chrono::{Duration, Utc};
+use poise::serenity_prelude::{ButtonStyle, CreateComponents};
+use utility::functions::{try_parse_written_time, try_parse_written_time_with_tz};
+
+use super::{
+    prelude::*,
+    timezone::{autocomplete_timezone, get_user_timezone},
+};
+use crate::polls::{Poll, VoteMode, VotingStyle};
+
+#[poise::command(slash_command, guild_only, subcommands("create"), category = "Fun")]
+/// Let members vote on a question.
+pub(crate) async fn poll(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 30,
+    required_bot_permissions = "SEND_MESSAGES"
+)]
+/// Post a poll with live results, it can be closed early by its creator or a moderator.
+pub(crate) async fn create(
+    ctx: Context<'_>,
+    #[description = "The question to vote on."] question: String,
+    #[description = "2-10 options, separated by `|`, ex. `Pekora | Miko | Suisei`."]
+    options: String,
+    #[description = "Whether members can vote for more than one option, defaults to one."]
+    mode: Option<VoteMode>,
+    #[description = "How members vote, defaults to buttons."] style: Option<VotingStyle>,
+    #[description = "When the poll closes, ex. `in 2 hours`, defaults to a week from now."]
+    ends: Option<String>,
+    #[description = "Your timezone in IANA format (ex. America/New_York), defaults to your stored timezone."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    if let Err(e) = Poll::check_question(&question) {
+        return reply(ctx, &format!("Error! {e}")).await;
+    }
+
+    let options = match Poll::parse_options(&options) {
+        Ok(options) => options,
+        Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    let latest_end = Utc::now() + Duration::days(MAX_DURATION_DAYS);
+
+    let ends_at = match ends {
+        Some(ends) => {
+            let parsed = match (timezone, get_user_timezone(ctx).await) {
+                (None, Some(stored)) => try_parse_written_time_with_tz(&ends, &stored),
+                (timezone, _) => try_parse_written_time(&ends, timezone.as_deref()),
+            };
+
+            match parsed {
+                Ok(time) if time <= Utc::now() => {
+                    return reply(ctx, "Error! The end time has to be in the future.").await
+                }
+                Ok(time) if time > latest_end => {
+                    return reply(
+                        ctx,
+                        &format!("Error! Polls can be open for at most {MAX_DURATION_DAYS} days."),
+                    )
+                    .await
+                }
+                Ok(time) => time,
+                Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+            }
+        }
+        None => latest_end,
+    };
+
+    let mut poll = Poll::new(
+        question,
+        options,
+        mode.unwrap_or(VoteMode::Single),
+        ctx.author().id,
+        ends_at,
+    );
+
+    let style = style.unwrap_or(VotingStyle::Buttons);
+    let colour = ctx.data().config.theme.default;
+
+    let reply_handle = ctx
+        .send(|m| {
+            m.embed(|e| poll.results_embed(e, colour, false))
+                .components(|c| vote_components(c, &poll, style))
+        })
+        .await
+        .context(here!())?;
+
+    let message = reply_handle.message().await.context(here!())?;
+
+    poll.message = message.id;
+    poll.channel = message.channel_id;
+    poll.guild = guild_id;
+
+    // Votes are counted by the interaction handler, and the poll scheduler closes it.
+    let data = ctx.data().data.read().await;
+
+    {
+        let handle = data.database.lock().await;
+        poll.insert(&handle).context(here!())?;
+    }
+
+    data.polls_changed.notify_one();
+
+    Ok(())
+}
+
+const MAX_DURATION_DAYS: i64 = 7;
+
+fn vote_components<'a>(
+    c: &'a mut CreateComponents,
+    poll: &Poll,
+    style: VotingStyle,
+) -> &'a mut CreateComponents {
+    match style {
+        VotingStyle::Buttons => {
+            for (row, options) in poll.options.chunks(5).enumerate() {
+                c.create_action_row(|r| {
+                    for (i, option) in options.iter().enumerate() {
+                        r.create_button(|b| {
+                            b.style(ButtonStyle::Primary)
+                                .label(option)
+                                .custom_id(format!("{}{}", Poll::VOTE_PREFIX, row * 5 + i))
+                        });
+                    }
+
+                    r
+                });
+            }
+        }
+        VotingStyle::SelectMenu => {
+            let max_values = match poll.mode {
+                VoteMode::Single => 1,
+                VoteMode::Multiple => poll.options.len() as u64,
+            };
+
+            c.create_action_row(|r| {
+                r.create_select_menu(|s| {
+                    s.custom_id(Poll::SELECT_ID)
+                        .placeholder("Pick your answer")
+                        .min_values(0)
+                        .max_values(max_values)
+                        .options(|o| {
+                            for (i, option) in poll.options.iter().enumerate() {
+                                o.create_option(|opt| opt.label(option).value(i));
+                            }
+
+                            o
+                        })
+                })
+            });
+        }
+    }
+
+    c.create_action_row(|r| {
+        r.create_button(|b| {
+            b.style(ButtonStyle::Danger)
+                .label("Close poll")
+                .custom_id(Poll::CLOSE_ID)
+        })
+    })
+}
@@ -24,4 +24,12 @@ pub use crate::{
 
 pub type Error = anyhow::Error;
 pub type Context<'a> = poise::Context<'a, DataWrapper, Error>;
+pub type ApplicationContext<'a> = poise::ApplicationContext<'a, DataWrapper, Error>;
 pub type Command = poise::Command<DataWrapper, Error>;
+
+/// Replies with a message only the user who used the command can see.
+pub async fn reply(ctx: Context<'_>, content: &str) -> anyhow::Result<()> {
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
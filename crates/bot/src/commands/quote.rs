@@ -0,0 +1,268 @@
+use serenity::builder::CreateEmbed;
+use utility::config::{DatabaseOperations, Quote};
+
+use super::prelude::*;
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    check = "quotes_enabled",
+    member_cooldown = 30,
+    subcommands("add", "remove", "edit", "get", "search"),
+    category = "Fun"
+)]
+/// Quote-related commands.
+pub(crate) async fn quote(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Add new quote, written as one `name: line` pair per line.
+pub(crate) async fn add(
+    ctx: Context<'_>,
+    #[description = "The quote to add."] quote: String,
+) -> anyhow::Result<()> {
+    let talents = &ctx.data().config.talents;
+
+    let quote = match Quote::from_message(&quote, talents) {
+        Ok(q) => q,
+        Err(e) => {
+            ctx.say(format!("Error: {e}")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut embed = quote.as_embed(talents)?;
+
+    let id = {
+        let mut data = ctx.data().data.write().await;
+        data.quotes.push(quote);
+
+        save_quotes(&data).await?;
+        data.quotes.len()
+    };
+
+    embed.author(|a| a.name("Quote added!"));
+    embed.footer(|f| f.text(format!("ID: {id}")));
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            *e = embed;
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Remove quote.
+pub(crate) async fn remove(
+    ctx: Context<'_>,
+    #[description = "ID of the quote to remove."] id: usize,
+) -> anyhow::Result<()> {
+    let removed = {
+        let mut data = ctx.data().data.write().await;
+
+        match id.checked_sub(1).filter(|i| *i < data.quotes.len()) {
+            Some(index) => {
+                data.quotes.remove(index);
+                save_quotes(&data).await?;
+                true
+            }
+            None => false,
+        }
+    };
+
+    if removed {
+        ctx.say(format!("Quote {id} removed!")).await?;
+    } else {
+        ctx.say(format!("No quote with the ID {id} found!")).await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Edit quote.
+pub(crate) async fn edit(
+    ctx: Context<'_>,
+    #[description = "ID of the quote to edit."] id: usize,
+    #[description = "The replacement quote."] new_quote: String,
+) -> anyhow::Result<()> {
+    let quote = match Quote::from_message(&new_quote, &ctx.data().config.talents) {
+        Ok(q) => q,
+        Err(e) => {
+            ctx.say(format!("Error: {e}")).await?;
+            return Ok(());
+        }
+    };
+
+    let edited = {
+        let mut data = ctx.data().data.write().await;
+
+        match id.checked_sub(1).and_then(|i| data.quotes.get_mut(i)) {
+            Some(existing) => {
+                *existing = quote;
+                save_quotes(&data).await?;
+                true
+            }
+            None => false,
+        }
+    };
+
+    if edited {
+        ctx.say(format!("Quote {id} edited!")).await?;
+    } else {
+        ctx.say(format!("No quote with the ID {id} found!")).await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Get quote by ID.
+pub(crate) async fn get(
+    ctx: Context<'_>,
+    #[description = "ID of the quote to get."] id: usize,
+) -> anyhow::Result<()> {
+    let quote = {
+        let data = ctx.data().data.read().await;
+        id.checked_sub(1).and_then(|i| data.quotes.get(i)).cloned()
+    };
+
+    let quote = match quote {
+        Some(q) => q,
+        None => {
+            ctx.say(format!("No quote with the ID {id} found!")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut embed = quote.as_embed(&ctx.data().config.talents)?;
+    embed.footer(|f| f.text(format!("ID: {id}")));
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            *e = embed;
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, subcommands("by_user", "by_content"))]
+/// Find matching quotes.
+pub(crate) async fn search(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Find quotes with talent.
+pub(crate) async fn by_user(
+    ctx: Context<'_>,
+    #[description = "The name of the user."] user: String,
+) -> anyhow::Result<()> {
+    let name = user.trim().to_lowercase();
+
+    let talent = match ctx
+        .data()
+        .config
+        .talents
+        .iter()
+        .find(|u| u.name.to_lowercase().contains(&name))
+    {
+        Some(t) => t,
+        None => {
+            ctx.say(format!("No talent found with the name {user}!"))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let matching_quotes = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .quotes
+        .iter()
+        .filter(|q| q.lines.iter().any(|l| l.user == talent.name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    show_quotes(ctx, format!("Quotes by {}", talent.name), matching_quotes).await
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Find quotes containing text.
+pub(crate) async fn by_content(
+    ctx: Context<'_>,
+    #[description = "The text to search."] search: String,
+) -> anyhow::Result<()> {
+    let normalized_content = search.trim().to_lowercase();
+
+    let matching_quotes = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .quotes
+        .iter()
+        .filter(|q| {
+            q.lines
+                .iter()
+                .any(|l| l.line.to_lowercase().contains(&normalized_content))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    show_quotes(
+        ctx,
+        format!("Quotes containing \"{search}\""),
+        matching_quotes,
+    )
+    .await
+}
+
+async fn show_quotes(ctx: Context<'_>, title: String, quotes: Vec<Quote>) -> anyhow::Result<()> {
+    if quotes.is_empty() {
+        ctx.say("No matching quotes found!").await?;
+        return Ok(());
+    }
+
+    PaginatedList::new()
+        .title(title)
+        .data(&quotes)
+        .embed(Box::new(|q, _| {
+            let mut embed = CreateEmbed::default();
+
+            embed.fields(
+                q.lines
+                    .iter()
+                    .map(|l| (l.user.clone(), l.line.clone(), false)),
+            );
+
+            embed
+        }))
+        .display(ctx)
+        .await?;
+
+    Ok(())
+}
+
+async fn save_quotes(data: &crate::DiscordData) -> anyhow::Result<()> {
+    let handle = data.database.lock().await;
+
+    data.quotes
+        .clone()
+        .save_to_database(&handle)
+        .context(here!())
+}
+
+async fn quotes_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.quotes.enabled)
+}
@@ -0,0 +1,237 @@
+use chrono::Duration;
+use poise::serenity_prelude::{CacheHttp, Role};
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    discord::CommandRateLimit,
+};
+
+use super::prelude::*;
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS",
+    subcommands("set", "clear", "bypass", "list"),
+    category = "Admin"
+)]
+/// Manage how often members can use commands in this server.
+pub(crate) async fn ratelimit(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Limit how many times each member can use a command within a period.
+pub(crate) async fn set(
+    ctx: Context<'_>,
+    #[description = "The command to limit."]
+    #[autocomplete = "autocomplete_command"]
+    command: String,
+    #[description = "How many times the command can be used in the period."]
+    #[min = 1]
+    uses: u32,
+    #[description = "The length of the period, in seconds."]
+    #[min = 1]
+    seconds: u32,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let command = command.trim().trim_start_matches('/').to_owned();
+
+    if !command_names(&ctx.framework().options().commands).contains(&command) {
+        return reply_invalid(ctx, &format!("No command named {command} found.")).await;
+    }
+
+    let limit = CommandRateLimit {
+        uses,
+        period: Duration::seconds(seconds.into()),
+    };
+
+    let entry = AuditEntry::new(AuditAction::PermissionsChanged, format!("/{command}")).details(
+        format!("Rate limited to {uses} use(s) every {seconds} seconds."),
+    );
+
+    update_limits(ctx, entry, |limits| {
+        limits.set_limit(guild_id, &command, Some(limit))
+    })
+    .await?;
+
+    ctx.send(|m| {
+        m.ephemeral(true).content(format!(
+            "`/{command}` can now be used {uses} time(s) every {seconds} seconds."
+        ))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Remove the rate limit of a command.
+pub(crate) async fn clear(
+    ctx: Context<'_>,
+    #[description = "The command to stop limiting."]
+    #[autocomplete = "autocomplete_command"]
+    command: String,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let command = command.trim().trim_start_matches('/').to_owned();
+
+    let entry = AuditEntry::new(AuditAction::PermissionsChanged, format!("/{command}"))
+        .details("Rate limit removed.");
+
+    update_limits(ctx, entry, |limits| {
+        limits.set_limit(guild_id, &command, None)
+    })
+    .await?;
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("`/{command}` is no longer rate limited."))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Set the role whose members ignore rate limits, or remove it if no role is given.
+pub(crate) async fn bypass(
+    ctx: Context<'_>,
+    #[description = "The role that bypasses rate limits."] role: Option<Role>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+    let role_id = role.as_ref().map(|r| r.id);
+
+    let entry = AuditEntry::new(AuditAction::PermissionsChanged, "Rate limit bypass role")
+        .details(role_id.map_or_else(|| "Removed.".to_owned(), |r| Mention::from(r).to_string()));
+
+    update_limits(ctx, entry, |limits| {
+        limits.set_bypass_role(guild_id, role_id)
+    })
+    .await?;
+
+    let content = match role {
+        Some(role) => format!("Members with {} now bypass rate limits.", role.name),
+        None => "No role bypasses rate limits anymore.".to_owned(),
+    };
+
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+/// Show the rate limits in this server.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let (mut limits, bypass_role) = {
+        let data = ctx.data().data.read().await;
+
+        (
+            data.rate_limits
+                .limits(guild_id)
+                .map(|(command, limit)| (command.to_owned(), *limit))
+                .collect::<Vec<_>>(),
+            data.rate_limits.bypass_role(guild_id),
+        )
+    };
+
+    limits.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut content = if limits.is_empty() {
+        "No commands are rate limited.".to_owned()
+    } else {
+        limits
+            .iter()
+            .map(|(command, limit)| {
+                format!(
+                    "`/{command}`: {} time(s) every {} seconds",
+                    limit.uses,
+                    limit.period.num_seconds()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    if let Some(role) = bypass_role {
+        content.push_str(&format!(
+            "\n\nMembers with {} bypass rate limits.",
+            Mention::from(role)
+        ));
+    }
+
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
+
+/// Applies a change to the rate limits, saves them, and records it in the audit log.
+async fn update_limits<F>(ctx: Context<'_>, entry: AuditEntry, f: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&mut crate::rate_limits::RateLimits),
+{
+    {
+        let mut data = ctx.data().data.write().await;
+        f(&mut data.rate_limits);
+
+        let handle = data.database.lock().await;
+        data.rate_limits.save_settings(&handle).context(here!())?;
+    }
+
+    audit_log::record(
+        ctx.http(),
+        entry.guild(ctx.guild_id()).actor(ctx.author().id),
+    )
+    .await;
+
+    Ok(())
+}
+
+async fn reply_invalid(ctx: Context<'_>, reason: &str) -> anyhow::Result<()> {
+    ctx.send(|m| m.ephemeral(true).content(format!("Error! {reason}")))
+        .await?;
+
+    Ok(())
+}
+
+/// The qualified names of every command, including subcommands.
+fn command_names(commands: &[Command]) -> Vec<String> {
+    commands
+        .iter()
+        .flat_map(|c| {
+            std::iter::once(c.qualified_name.clone()).chain(command_names(&c.subcommands))
+        })
+        .collect()
+}
+
+async fn autocomplete_command(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = partial.to_lowercase();
+
+    command_names(&ctx.framework().options().commands)
+        .into_iter()
+        .filter(move |name| name.contains(&partial))
+        .take(25)
+}
@@ -0,0 +1,356 @@
+use std::borrow::Cow;
+
+use chrono::Utc;
+use poise::serenity_prelude::{Attachment, AttachmentType};
+use unicode_truncate::UnicodeTruncateStr;
+use utility::functions::{try_parse_written_time, try_parse_written_time_with_tz};
+
+use super::{
+    prelude::*,
+    timezone::{autocomplete_timezone, get_user_timezone},
+};
+use crate::reminders::{
+    ExportedReminder, Reminder, ReminderFrequency, ReminderLocation, ReminderLocationOption,
+    ReminderSubscriber,
+};
+
+#[poise::command(
+    slash_command,
+    check = "reminders_enabled",
+    subcommands("add", "remove", "list", "subscribe", "export", "import"),
+    category = "Utility"
+)]
+/// Set reminders, and subscribe to the reminders of others.
+pub(crate) async fn reminder(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+/// Add a new reminder, which others can subscribe to as well if it's public.
+pub(crate) async fn add(
+    ctx: Context<'_>,
+    #[description = "When to remind you, ex. `tomorrow at 18:00`."] when: String,
+    #[description = "What to remind you of."]
+    #[max_length = 1000]
+    message: String,
+    #[description = "How often to remind you, defaults to once."] frequency: Option<
+        ReminderFrequency,
+    >,
+    #[description = "Where to remind you, defaults to your DMs."] location: Option<
+        ReminderLocationOption,
+    >,
+    #[description = "Your timezone in IANA format (ex. America/New_York), defaults to your stored timezone."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+    #[description = "Let others subscribe to the reminder, defaults to no."] public: Option<bool>,
+) -> anyhow::Result<()> {
+    let parsed = match (timezone, get_user_timezone(ctx).await) {
+        (None, Some(stored)) => try_parse_written_time_with_tz(&when, &stored),
+        (timezone, _) => try_parse_written_time(&when, timezone.as_deref()),
+    };
+
+    let next_at = match parsed {
+        Ok(time) if time > Utc::now() => time,
+        Ok(_) => return reply(ctx, "Error! The time has to be in the future.").await,
+        Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    let mut reminder = Reminder {
+        id: 0,
+        author: ctx.author().id,
+        message,
+        next_at,
+        frequency: frequency.unwrap_or(ReminderFrequency::Once),
+        public: public.unwrap_or(false),
+        subscribers: vec![ReminderSubscriber {
+            user: ctx.author().id,
+            location: reminder_location(ctx, location),
+        }],
+    };
+
+    {
+        let data = ctx.data().data.read().await;
+
+        reminder.id = {
+            let handle = data.database.lock().await;
+            reminder.insert(&handle).context(here!())?
+        };
+
+        data.reminder_timers.schedule(reminder.id, reminder.next_at);
+    }
+
+    let colour = ctx.data().config.theme.default;
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("Reminder {} created!", reminder.id))
+                .colour(colour)
+                .description(&reminder.message)
+                .field(
+                    "When",
+                    format!(
+                        "<t:{0}:F> (<t:{0}:R>), {1}",
+                        reminder.next_at.timestamp(),
+                        reminder.frequency.name().to_lowercase()
+                    ),
+                    false,
+                );
+
+            if reminder.public {
+                e.footer(|f| {
+                    f.text(format!(
+                        "Press the button or use /reminder subscribe {} to be reminded too.",
+                        reminder.id
+                    ))
+                });
+            }
+
+            e
+        });
+
+        if reminder.public {
+            m.components(|c| reminder.components(c));
+        }
+
+        m
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+/// Unsubscribe from a reminder, it's removed once nobody is subscribed to it.
+pub(crate) async fn remove(
+    ctx: Context<'_>,
+    #[description = "The ID of the reminder, as shown by `/reminder list`."] id: i64,
+) -> anyhow::Result<()> {
+    let removed = {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        let removed = Reminder::unsubscribe(&handle, id, ctx.author().id).context(here!())?;
+
+        // The reminder itself is removed along with its last subscriber.
+        if removed && Reminder::get(&handle, id).context(here!())?.is_none() {
+            data.reminder_timers.cancel(id);
+        }
+
+        removed
+    };
+
+    let content = if removed {
+        format!("You won't be reminded of reminder {id} anymore.")
+    } else {
+        format!("Error! You're not subscribed to a reminder with the ID {id}.")
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(slash_command)]
+/// Show the reminders you're subscribed to.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let reminders = {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        Reminder::list(&handle, ctx.author().id).context(here!())?
+    };
+
+    let content = if reminders.is_empty() {
+        "You're not subscribed to any reminders.".to_owned()
+    } else {
+        reminders
+            .iter()
+            .map(|r| {
+                let (preview, _) = r.message.unicode_truncate(50);
+
+                format!(
+                    "**{}**: <t:{}:f> ({}, {} subscriber(s)), \"{preview}\"",
+                    r.id,
+                    r.next_at.timestamp(),
+                    r.frequency,
+                    r.subscribers.len(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(slash_command)]
+/// Get reminded of a public reminder someone else has set.
+pub(crate) async fn subscribe(
+    ctx: Context<'_>,
+    #[description = "The ID of the reminder."] id: i64,
+    #[description = "Where to remind you, defaults to your DMs."] location: Option<
+        ReminderLocationOption,
+    >,
+) -> anyhow::Result<()> {
+    let subscriber = ReminderSubscriber {
+        user: ctx.author().id,
+        location: reminder_location(ctx, location),
+    };
+
+    let content = {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        match Reminder::get(&handle, id).context(here!())? {
+            Some(reminder) if reminder.can_subscribe(ctx.author().id) => {
+                if Reminder::subscribe(&handle, id, subscriber).context(here!())? {
+                    format!(
+                        "You'll be reminded of \"{}\" <t:{}:R>.",
+                        reminder.message,
+                        reminder.next_at.timestamp()
+                    )
+                } else {
+                    "You're already subscribed to this reminder.".to_owned()
+                }
+            }
+            _ => format!("Error! No public reminder with the ID {id} exists."),
+        }
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(slash_command)]
+/// Download the reminders you're subscribed to, to back them up or import them elsewhere.
+pub(crate) async fn export(ctx: Context<'_>) -> anyhow::Result<()> {
+    let user = ctx.author().id;
+
+    let reminders = {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+
+        Reminder::list(&handle, user).context(here!())?
+    };
+
+    if reminders.is_empty() {
+        return reply(ctx, "You're not subscribed to any reminders.").await;
+    }
+
+    let exported = reminders
+        .iter()
+        .map(|r| ExportedReminder::new(r, user))
+        .collect::<Vec<_>>();
+
+    let file = serde_json::to_vec_pretty(&exported).context(here!())?;
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("Exported {} reminder(s).", exported.len()))
+            .attachment(AttachmentType::Bytes {
+                data: Cow::Owned(file),
+                filename: "reminders.json".to_owned(),
+            })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+/// Add the reminders from a file made with `/reminder export`.
+pub(crate) async fn import(
+    ctx: Context<'_>,
+    #[description = "The exported reminders."] file: Attachment,
+) -> anyhow::Result<()> {
+    if file.size > MAX_IMPORT_SIZE {
+        return reply(
+            ctx,
+            &format!(
+                "Error! The file can be at most {} kB.",
+                MAX_IMPORT_SIZE / 1024
+            ),
+        )
+        .await;
+    }
+
+    let entries: Vec<ExportedReminder> =
+        match serde_json::from_slice(&file.download().await.context(here!())?) {
+            Ok(entries) => entries,
+            Err(e) => {
+                return reply(
+                    ctx,
+                    &format!("Error! That's not an exported reminder file: {e}"),
+                )
+                .await
+            }
+        };
+
+    if entries.len() > MAX_IMPORTED {
+        return reply(
+            ctx,
+            &format!("Error! At most {MAX_IMPORTED} reminders can be imported at once."),
+        )
+        .await;
+    }
+
+    let user = ctx.author().id;
+    let now = Utc::now();
+
+    let mut imported = 0;
+    let mut duplicates = 0;
+    let mut errors = Vec::new();
+
+    {
+        let data = ctx.data().data.read().await;
+        let handle = data.database.lock().await;
+        let mut existing = Reminder::list(&handle, user).context(here!())?;
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            let reminder = match entry.into_reminder(user, ctx.channel_id(), now) {
+                Ok(r) => r,
+                Err(e) => {
+                    errors.push(format!("Entry {}: {e}", i + 1));
+                    continue;
+                }
+            };
+
+            // Reminders already in the file or already subscribed to aren't added twice.
+            if existing.iter().any(|r| {
+                r.message == reminder.message
+                    && r.next_at == reminder.next_at
+                    && r.frequency == reminder.frequency
+            }) {
+                duplicates += 1;
+                continue;
+            }
+
+            let id = reminder.insert(&handle).context(here!())?;
+            data.reminder_timers.schedule(id, reminder.next_at);
+            existing.push(reminder);
+            imported += 1;
+        }
+    }
+
+    let mut content =
+        format!("Imported {imported} reminder(s), skipped {duplicates} duplicate(s).");
+
+    if !errors.is_empty() {
+        content.push_str("\nSkipped invalid entries:\n");
+        content.push_str(&errors.join("\n"));
+    }
+
+    reply(ctx, &content).await
+}
+
+/// Imported files are small, since they can't contain more than `MAX_IMPORTED` entries.
+const MAX_IMPORT_SIZE: u64 = 256 * 1024;
+const MAX_IMPORTED: usize = 25;
+
+fn reminder_location(ctx: Context<'_>, option: Option<ReminderLocationOption>) -> ReminderLocation {
+    match option {
+        Some(ReminderLocationOption::Channel) => ReminderLocation::Channel(ctx.channel_id()),
+        Some(ReminderLocationOption::DM) | None => ReminderLocation::DM,
+    }
+}
+
+async fn reminders_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.reminders.enabled)
+}
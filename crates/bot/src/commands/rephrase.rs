@@ -0,0 +1,125 @@
+use apis::translation_usage::{AccountedDeepL, UsageFeature};
+use deepl::{DeepL, RephraseOptions, Tone, WritingStyle};
+use utility::http;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub(crate) enum RephraseStyle {
+    #[name = "Simple"]
+    Simple,
+    #[name = "Business"]
+    Business,
+    #[name = "Academic"]
+    Academic,
+    #[name = "Casual"]
+    Casual,
+}
+
+impl From<RephraseStyle> for WritingStyle {
+    fn from(style: RephraseStyle) -> Self {
+        match style {
+            RephraseStyle::Simple => Self::Simple,
+            RephraseStyle::Business => Self::Business,
+            RephraseStyle::Academic => Self::Academic,
+            RephraseStyle::Casual => Self::Casual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub(crate) enum RephraseTone {
+    #[name = "Enthusiastic"]
+    Enthusiastic,
+    #[name = "Friendly"]
+    Friendly,
+    #[name = "Confident"]
+    Confident,
+    #[name = "Diplomatic"]
+    Diplomatic,
+}
+
+impl From<RephraseTone> for Tone {
+    fn from(tone: RephraseTone) -> Self {
+        match tone {
+            RephraseTone::Enthusiastic => Self::Enthusiastic,
+            RephraseTone::Friendly => Self::Friendly,
+            RephraseTone::Confident => Self::Confident,
+            RephraseTone::Diplomatic => Self::Diplomatic,
+        }
+    }
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    check = "rephrase_enabled",
+    category = "Admin"
+)]
+/// Improve the spelling, grammar and wording of a draft, ex. of an announcement.
+pub(crate) async fn rephrase(
+    ctx: Context<'_>,
+    #[description = "The text to improve."]
+    #[max_length = 1900]
+    draft: String,
+    #[description = "The writing style to use, can't be combined with a tone."] style: Option<
+        RephraseStyle,
+    >,
+    #[description = "The tone to use, can't be combined with a writing style."] tone: Option<
+        RephraseTone,
+    >,
+) -> anyhow::Result<()> {
+    if style.is_some() && tone.is_some() {
+        return reply(ctx, "Error! Only one of style and tone can be set.").await;
+    }
+
+    let token = match ctx.data().config.deepl_token() {
+        Some(token) => token.to_owned(),
+        None => return reply(ctx, "Error! No DeepL account is configured.").await,
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let options = RephraseOptions {
+        target_language: None,
+        writing_style: style.map(Into::into),
+        tone: tone.map(Into::into),
+    };
+
+    let database = ctx.data().config.database.clone();
+
+    // The DeepL client is blocking, so it's kept off the async runtime.
+    let improved = tokio::task::spawn_blocking(move || {
+        let client = DeepL::with_agent(token, http::client().agent().clone());
+        let client = AccountedDeepL::new(client, database, UsageFeature::Rephrase);
+
+        client.rephrase(draft, Some(options))
+    })
+    .await
+    .context(here!())?;
+
+    let improved = match improved {
+        Ok(improved) => improved,
+        // Left to the error handler, which explains it the same way everywhere.
+        Err(e @ deepl::Error::QuotaExceeded) => return Err(e).context(here!()),
+        Err(e) => return reply(ctx, &format!("Error! {e}")).await,
+    };
+
+    let colour = ctx.data().config.theme.default;
+
+    ctx.send(|m| {
+        m.ephemeral(true).embed(|e| {
+            e.title("Rephrased draft")
+                .colour(colour)
+                .description(&improved.text)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn rephrase_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.deepl_token().is_some())
+}
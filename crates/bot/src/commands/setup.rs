@@ -0,0 +1,530 @@
+use std::sync::Arc;
+
+use super::prelude::*;
+
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, CacheHttp, GuildChannel, GuildId, InteractionResponseType,
+        MessageComponentInteraction, PermissionOverwrite, PermissionOverwriteType, Permissions,
+    },
+    CreateReply, ReplyHandle,
+};
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    config::Talent,
+};
+
+use super::{
+    config::ChannelSetting,
+    talent::{publish_talents, TALENT_EDITS},
+};
+
+const CHANNEL_SELECT_ID: &str = "setup_channel";
+const CREATE_ID: &str = "setup_create";
+const SKIP_ID: &str = "setup_skip";
+const CANCEL_ID: &str = "setup_cancel";
+const PREVIOUS_PAGE_ID: &str = "setup_previous_page";
+const NEXT_PAGE_ID: &str = "setup_next_page";
+
+/// How long each step waits for a choice before the wizard gives up.
+const STEP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Discord allows at most 25 options in a select menu, so larger servers get several pages.
+const MAX_CHANNEL_OPTIONS: usize = 25;
+
+struct ChannelStep {
+    setting: ChannelSetting,
+    description: &'static str,
+    /// The name of the channel created if none of the existing ones fit.
+    default_name: &'static str,
+}
+
+/// The channels a step can pick from, shown a page at a time.
+struct ChannelPicker<'a> {
+    channels: &'a [GuildChannel],
+    page: usize,
+    current: Option<ChannelId>,
+}
+
+impl ChannelPicker<'_> {
+    fn page_count(&self) -> usize {
+        self.channels.len().div_ceil(MAX_CHANNEL_OPTIONS)
+    }
+
+    fn page_channels(&self) -> &[GuildChannel] {
+        self.channels
+            .chunks(MAX_CHANNEL_OPTIONS)
+            .nth(self.page)
+            .unwrap_or_default()
+    }
+}
+
+const CHANNEL_STEPS: [ChannelStep; 4] = [
+    ChannelStep {
+        setting: ChannelSetting::StreamAlerts,
+        description: "Where alerts are posted when talents go live.",
+        default_name: "stream-alerts",
+    },
+    ChannelStep {
+        setting: ChannelSetting::StreamChatCategory,
+        description: "The category that chat channels are created in while talents are live.",
+        default_name: "Stream Chats",
+    },
+    ChannelStep {
+        setting: ChannelSetting::StreamChatLogs,
+        description: "Where stream chats are archived once the streams end.",
+        default_name: "stream-archive",
+    },
+    ChannelStep {
+        setting: ChannelSetting::AuditLog,
+        description: "Where reports for admins are posted, such as the audit log \
+            and features that are missing permissions.",
+        default_name: "bot-admin",
+    },
+];
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    category = "Admin"
+)]
+/// Walk through picking the channels and roles the bot uses in this server.
+pub(crate) async fn setup(ctx: Context<'_>) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id().ok_or_else(|| anyhow!("Not in a guild."))?;
+
+    let config = {
+        let data = ctx.data().data.read().await;
+        Config::clone(data.edited_config.as_ref().unwrap_or(&ctx.data().config))
+    };
+
+    let colour = config.theme.default;
+    let step_count = CHANNEL_STEPS.len() + 1;
+
+    let reply = ctx
+        .send(|m| {
+            m.ephemeral(true)
+                .embed(|e| e.title("Setup").description("Loading...").colour(colour))
+        })
+        .await
+        .context(here!())?;
+
+    let message = reply.message().await.context(here!())?.into_owned();
+    let mut changes = Vec::new();
+    // Channels created along the way are kept even if the setup is stopped.
+    let mut created = Vec::new();
+
+    for (i, step) in CHANNEL_STEPS.iter().enumerate() {
+        let setting = step.setting;
+        let current = setting.get(&config).filter(|c| c.0 != 0);
+        let channels = guild_channels(ctx, guild_id, setting).await?;
+
+        let mut picker = ChannelPicker {
+            channels: &channels,
+            page: 0,
+            current,
+        };
+
+        let interaction = loop {
+            reply
+                .edit(ctx, |m| {
+                    render_step(
+                        m,
+                        colour,
+                        format!("Setup ({}/{step_count}): {setting}", i + 1),
+                        format!(
+                            "{}\n\nCurrently: {}\n\nPick a channel, create a new one, \
+                            or skip to keep the current one.",
+                            step.description,
+                            describe_channel(current)
+                        ),
+                        Some(&picker),
+                        "Create channel",
+                    )
+                })
+                .await
+                .context(here!())?;
+
+            let interaction = match next_interaction(ctx, &message).await? {
+                Some(interaction) => interaction,
+                None => return finish(ctx, &reply, &stopped("Setup timed out", &created)).await,
+            };
+
+            match interaction.data.custom_id.as_str() {
+                PREVIOUS_PAGE_ID => picker.page = picker.page.saturating_sub(1),
+                NEXT_PAGE_ID => picker.page = (picker.page + 1).min(picker.page_count() - 1),
+                _ => break interaction,
+            }
+        };
+
+        let picked = match interaction.data.custom_id.as_str() {
+            CHANNEL_SELECT_ID => interaction
+                .data
+                .values
+                .first()
+                .and_then(|v| v.parse().ok())
+                .map(ChannelId),
+            CREATE_ID => {
+                let channel = create_channel(ctx, guild_id, step).await?;
+                created.push(channel);
+                Some(channel)
+            }
+            CANCEL_ID => return finish(ctx, &reply, &stopped("Setup cancelled", &created)).await,
+            _ => None,
+        };
+
+        if let Some(channel) = picked.filter(|c| Some(*c) != current) {
+            changes.push((setting, current, channel));
+        }
+    }
+
+    let without_role = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .talent_updates
+        .borrow()
+        .iter()
+        .filter(|t| t.discord_role.is_none())
+        .count();
+    let mut roles_assigned = 0;
+
+    if without_role > 0 {
+        reply
+            .edit(ctx, |m| {
+                render_step(
+                    m,
+                    colour,
+                    format!("Setup ({step_count}/{step_count}): Talent roles"),
+                    format!(
+                        "{without_role} talent(s) don't have a role yet. Existing roles \
+                        with their names are used, and the rest are created."
+                    ),
+                    None,
+                    "Create roles",
+                )
+            })
+            .await
+            .context(here!())?;
+
+        let interaction = match next_interaction(ctx, &message).await? {
+            Some(interaction) => interaction,
+            None => return finish(ctx, &reply, &stopped("Setup timed out", &created)).await,
+        };
+
+        match interaction.data.custom_id.as_str() {
+            CREATE_ID => {
+                // The roster may have changed while waiting, so the current one is used.
+                let _edit = TALENT_EDITS.lock().await;
+                let mut talents = ctx.data().data.read().await.talent_updates.borrow().clone();
+
+                roles_assigned = assign_talent_roles(ctx, guild_id, &mut talents).await?;
+
+                if roles_assigned > 0 {
+                    publish_talents(ctx, talents, AuditAction::TalentEdited, "Talent roles")
+                        .await?;
+                }
+            }
+            CANCEL_ID => {
+                return finish(ctx, &reply, &stopped("Setup cancelled", &created)).await;
+            }
+            _ => (),
+        }
+    }
+
+    if changes.is_empty() && roles_assigned == 0 {
+        return finish(ctx, &reply, &stopped("Setup finished", &created)).await;
+    }
+
+    if !changes.is_empty() {
+        let mut data = ctx.data().data.write().await;
+        let config = data
+            .edited_config
+            .get_or_insert_with(|| Config::clone(&ctx.data().config));
+
+        for (setting, _, channel) in &changes {
+            *setting.get_mut(config) = *channel;
+        }
+
+        config.save().context(here!())?;
+    }
+
+    for (setting, old, new) in &changes {
+        audit_log::record(
+            ctx.http(),
+            AuditEntry::new(AuditAction::ConfigChanged, setting.to_string())
+                .guild(Some(guild_id))
+                .actor(ctx.author().id)
+                .details(format!(
+                    "{} → {}",
+                    describe_channel(*old),
+                    describe_channel(Some(*new))
+                )),
+        )
+        .await;
+    }
+
+    let mut summary = changes
+        .iter()
+        .map(|(setting, _, channel)| format!("{setting}: {}", Mention::from(*channel)))
+        .collect::<Vec<_>>();
+
+    if roles_assigned > 0 {
+        summary.push(format!("Assigned roles to {roles_assigned} talent(s)."));
+    }
+
+    if !changes.is_empty() {
+        summary.push(String::new());
+        summary.push("The channel changes will take effect once the bot restarts.".to_owned());
+    }
+
+    finish(ctx, &reply, &summary.join("\n")).await
+}
+
+/// The channels that can be picked for the setting, in the order Discord lists them.
+async fn guild_channels(
+    ctx: Context<'_>,
+    guild_id: GuildId,
+    setting: ChannelSetting,
+) -> anyhow::Result<Vec<GuildChannel>> {
+    let mut channels = guild_id
+        .channels(ctx.discord())
+        .await
+        .context(here!())?
+        .into_values()
+        .filter(|c| c.kind == setting.expected_kind())
+        .collect::<Vec<_>>();
+
+    channels.sort_by_key(|c| c.position);
+
+    Ok(channels)
+}
+
+async fn create_channel(
+    ctx: Context<'_>,
+    guild_id: GuildId,
+    step: &ChannelStep,
+) -> anyhow::Result<ChannelId> {
+    let bot_id = ctx.discord().cache.current_user_id();
+
+    let channel = guild_id
+        .create_channel(ctx.discord(), |c| {
+            c.name(step.default_name).kind(step.setting.expected_kind());
+
+            // Reports for admins shouldn't be visible to everyone.
+            if let ChannelSetting::AuditLog = step.setting {
+                c.permissions([
+                    PermissionOverwrite {
+                        allow: Permissions::empty(),
+                        deny: Permissions::VIEW_CHANNEL,
+                        kind: PermissionOverwriteType::Role(RoleId(guild_id.0)),
+                    },
+                    PermissionOverwrite {
+                        allow: Permissions::VIEW_CHANNEL
+                            | Permissions::SEND_MESSAGES
+                            | Permissions::EMBED_LINKS,
+                        deny: Permissions::empty(),
+                        kind: PermissionOverwriteType::Member(bot_id),
+                    },
+                ]);
+            }
+
+            c
+        })
+        .await
+        .context(here!())?;
+
+    audit_log::record(
+        ctx.http(),
+        AuditEntry::new(AuditAction::ChannelCreated, channel.name.clone())
+            .guild(Some(guild_id))
+            .actor(ctx.author().id),
+    )
+    .await;
+
+    Ok(channel.id)
+}
+
+/// Gives every talent without a role one, reusing roles that already have their name.
+async fn assign_talent_roles(
+    ctx: Context<'_>,
+    guild_id: GuildId,
+    talents: &mut [Talent],
+) -> anyhow::Result<usize> {
+    let roles = guild_id.roles(ctx.discord()).await.context(here!())?;
+    let mut assigned = 0;
+
+    for talent in talents.iter_mut().filter(|t| t.discord_role.is_none()) {
+        let existing = roles.values().find(|r| r.name == talent.name);
+
+        let role = match existing {
+            Some(role) => role.id,
+            None => {
+                guild_id
+                    .create_role(ctx.discord(), |r| {
+                        r.name(&talent.name)
+                            .colour(talent.colour.into())
+                            .mentionable(true)
+                    })
+                    .await
+                    .context(here!())?
+                    .id
+            }
+        };
+
+        talent.discord_role = Some(role);
+        assigned += 1;
+    }
+
+    Ok(assigned)
+}
+
+/// Waits for one of the step's components to be used, and acknowledges it.
+async fn next_interaction(
+    ctx: Context<'_>,
+    message: &Message,
+) -> anyhow::Result<Option<Arc<MessageComponentInteraction>>> {
+    let interaction = message
+        .await_component_interaction(ctx.discord())
+        .author_id(ctx.author().id)
+        .timeout(STEP_TIMEOUT)
+        .await;
+
+    let interaction = match interaction {
+        Some(interaction) => interaction,
+        None => return Ok(None),
+    };
+
+    interaction
+        .create_interaction_response(ctx.discord(), |r| {
+            r.kind(InteractionResponseType::DeferredUpdateMessage)
+        })
+        .await
+        .context(here!())?;
+
+    Ok(Some(interaction))
+}
+
+fn render_step<'a, 'att>(
+    m: &'a mut CreateReply<'att>,
+    colour: u32,
+    title: String,
+    description: String,
+    picker: Option<&ChannelPicker<'_>>,
+    create_label: &str,
+) -> &'a mut CreateReply<'att> {
+    // Discord rejects select menus without any options.
+    let picker = picker.filter(|p| !p.channels.is_empty());
+
+    m.embed(|e| {
+        e.title(title).description(description).colour(colour);
+
+        if let Some(picker) = picker.filter(|p| p.page_count() > 1) {
+            e.footer(|f| f.text(format!("Page {}/{}", picker.page + 1, picker.page_count())));
+        }
+
+        e
+    })
+    .components(|c| {
+        if let Some(picker) = picker {
+            c.create_action_row(|r| {
+                r.create_select_menu(|s| {
+                    s.custom_id(CHANNEL_SELECT_ID)
+                        .placeholder("Pick a channel")
+                        .options(|o| {
+                            for channel in picker.page_channels() {
+                                o.create_option(|opt| {
+                                    opt.label(channel.name.chars().take(100).collect::<String>())
+                                        .value(channel.id)
+                                        .default_selection(Some(channel.id) == picker.current)
+                                });
+                            }
+
+                            o
+                        })
+                })
+            });
+
+            if picker.page_count() > 1 {
+                c.create_action_row(|r| {
+                    r.create_button(|b| {
+                        b.custom_id(PREVIOUS_PAGE_ID)
+                            .label("Previous page")
+                            .style(ButtonStyle::Secondary)
+                            .disabled(picker.page == 0)
+                    })
+                    .create_button(|b| {
+                        b.custom_id(NEXT_PAGE_ID)
+                            .label("Next page")
+                            .style(ButtonStyle::Secondary)
+                            .disabled(picker.page + 1 >= picker.page_count())
+                    })
+                });
+            }
+        }
+
+        c.create_action_row(|r| {
+            r.create_button(|b| {
+                b.custom_id(CREATE_ID)
+                    .label(create_label)
+                    .style(ButtonStyle::Primary)
+            })
+            .create_button(|b| {
+                b.custom_id(SKIP_ID)
+                    .label("Skip")
+                    .style(ButtonStyle::Secondary)
+            })
+            .create_button(|b| {
+                b.custom_id(CANCEL_ID)
+                    .label("Cancel")
+                    .style(ButtonStyle::Danger)
+            })
+        })
+    })
+}
+
+async fn finish(
+    ctx: Context<'_>,
+    reply: &ReplyHandle<'_>,
+    description: &str,
+) -> anyhow::Result<()> {
+    reply
+        .edit(ctx, |m| {
+            m.embed(|e| {
+                e.title("Setup")
+                    .description(description)
+                    .colour(ctx.data().config.theme.default)
+            })
+            .components(|c| c)
+        })
+        .await
+        .context(here!())?;
+
+    Ok(())
+}
+
+/// Describes why the setup stopped early, along with the channels it created before that,
+/// since those are kept.
+fn stopped(reason: &str, created: &[ChannelId]) -> String {
+    if created.is_empty() {
+        return format!("{reason}, nothing was changed.");
+    }
+
+    format!(
+        "{reason}, no settings were changed. These channels were created and kept, \
+        delete them if they're not needed: {}",
+        created
+            .iter()
+            .map(|c| Mention::from(*c).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn describe_channel(channel: Option<ChannelId>) -> String {
+    match channel {
+        Some(channel) => Mention::from(channel).to_string(),
+        None => "Not set".to_owned(),
+    }
+}
@@ -38,7 +38,8 @@ impl Default for StickerOrder {
     prefix_command,
     track_edits,
     check = "sticker_tracking_enabled",
-    required_permissions = "VIEW_AUDIT_LOG"
+    required_permissions = "VIEW_AUDIT_LOG",
+    category = "Statistics"
 )]
 /// Shows the most used stickers in this server.
 pub(crate) async fn sticker_usage(
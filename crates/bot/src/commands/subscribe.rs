@@ -0,0 +1,94 @@
+use super::{prelude::*, talent::autocomplete_talent};
+
+use utility::{config::Talent, tweet_subscriptions};
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    check = "tweet_dms_enabled",
+    subcommands("tweets", "list"),
+    category = "Hololive"
+)]
+/// Get things sent to you by DM.
+pub(crate) async fn subscribe(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Get the tweets of a talent sent to you by DM, or stop getting them.
+pub(crate) async fn tweets(
+    ctx: Context<'_>,
+    #[description = "The talent whose tweets you want."]
+    #[autocomplete = "autocomplete_talent"]
+    talent: String,
+    #[description = "Stop getting the talent's tweets instead."] stop: Option<bool>,
+) -> anyhow::Result<()> {
+    let talent = match find_talent(ctx, &talent).await {
+        Some(t) if t.twitter_id.is_some() => t,
+        Some(t) => {
+            return reply(
+                ctx,
+                &format!("Error! {} has no tracked Twitter account.", t.name),
+            )
+            .await
+        }
+        None => return reply(ctx, &format!("Error! No talent named {talent} found.")).await,
+    };
+
+    let store = tweet_subscriptions::store()?;
+    let user = ctx.author().id;
+
+    let content = if stop.unwrap_or(false) {
+        if store.unsubscribe(user, &talent.name).context(here!())? {
+            format!("You'll no longer be sent {}'s tweets.", talent.name)
+        } else {
+            format!("Error! You aren't subscribed to {}'s tweets.", talent.name)
+        }
+    } else if store.subscribe(user, &talent.name).context(here!())? {
+        format!(
+            "You'll be sent {}'s tweets by DM, so make sure your DMs are open. \
+            If you're sent more than {} tweets in an hour, the rest are sent together later.",
+            talent.name,
+            ctx.data().config.twitter.dm_subscriptions.max_per_hour
+        )
+    } else {
+        format!(
+            "Error! You're already subscribed to {}'s tweets.",
+            talent.name
+        )
+    };
+
+    reply(ctx, &content).await
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Show the talents whose tweets you're sent.
+pub(crate) async fn list(ctx: Context<'_>) -> anyhow::Result<()> {
+    let talents = tweet_subscriptions::store()?.subscriptions(ctx.author().id);
+
+    let content = if talents.is_empty() {
+        "You aren't subscribed to any tweets.".to_owned()
+    } else {
+        format!("You're sent the tweets of: {}.", talents.join(", "))
+    };
+
+    reply(ctx, &content).await
+}
+
+async fn find_talent(ctx: Context<'_>, name: &str) -> Option<Talent> {
+    ctx.data()
+        .data
+        .read()
+        .await
+        .talent_updates
+        .borrow()
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name.trim()))
+        .cloned()
+}
+
+async fn tweet_dms_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    let twitter = &ctx.data().config.twitter;
+
+    Ok(twitter.enabled && twitter.dm_subscriptions.enabled)
+}
@@ -0,0 +1,337 @@
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::{CacheHttp, Role};
+use tokio::sync::Mutex;
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    config::{Birthday, HoloBranch, HoloGeneration, Talent},
+    functions::try_get_timezone,
+};
+
+use super::{prelude::*, timezone::autocomplete_timezone};
+
+/// Held from reading the roster until the changed roster is published,
+/// so that edits made at the same time don't undo each other.
+pub(crate) static TALENT_EDITS: Lazy<Mutex<()>> = Lazy::new(Mutex::default);
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    required_permissions = "KICK_MEMBERS",
+    subcommands("add", "edit", "remove"),
+    category = "Admin"
+)]
+/// Manage the tracked talents.
+pub(crate) async fn talent(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Add a new talent to track.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn add(
+    ctx: Context<'_>,
+    #[description = "The name of the talent."] name: String,
+    #[description = "The branch the talent belongs to."] branch: HoloBranch,
+    #[description = "The generation the talent belongs to."] generation: HoloGeneration,
+    #[description = "The emoji of the talent."] emoji: String,
+    #[description = "Link to the icon of the talent."] icon: String,
+    #[description = "The colour of the talent, in hex (ex. #FF00FF)."] colour: String,
+    #[description = "The YouTube channel ID of the talent."] youtube_channel: Option<String>,
+    #[description = "The Twitter handle of the talent, without the @."] twitter_handle: Option<
+        String,
+    >,
+    #[description = "The Twitter ID of the talent."] twitter_id: Option<u64>,
+    #[description = "The role to ping for the talent."] role: Option<Role>,
+    #[description = "The timezone of the talent in IANA format."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+) -> anyhow::Result<()> {
+    let mut talent = Talent {
+        name: name.trim().to_owned(),
+        emoji,
+        icon,
+        branch,
+        generation,
+        birthday: Birthday::default(),
+        timezone: Tz::Asia__Tokyo,
+        youtube_ch_id: None,
+        twitter_handle,
+        twitter_id,
+        schedule_keyword: None,
+        colour: 0,
+        discord_role: role.map(|r| r.id),
+    };
+
+    let changes = TalentChanges {
+        colour: Some(colour),
+        youtube_channel,
+        timezone,
+        ..Default::default()
+    };
+
+    if let Err(e) = changes.apply(&mut talent) {
+        return reply_invalid(ctx, &e).await;
+    }
+
+    let _edit = TALENT_EDITS.lock().await;
+    let mut talents = ctx.data().data.read().await.talent_updates.borrow().clone();
+
+    if talents
+        .iter()
+        .any(|t| t.name.eq_ignore_ascii_case(&talent.name))
+    {
+        return reply_invalid(ctx, &format!("{} is already being tracked.", talent.name)).await;
+    }
+
+    let name = talent.name.clone();
+    talents.push(talent);
+
+    publish_talents(ctx, talents, AuditAction::TalentAdded, &name).await?;
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("{name} is now being tracked!"))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Edit a tracked talent.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn edit(
+    ctx: Context<'_>,
+    #[description = "The talent to edit."]
+    #[autocomplete = "autocomplete_talent"]
+    name: String,
+    #[description = "The new name of the talent."] new_name: Option<String>,
+    #[description = "The branch the talent belongs to."] branch: Option<HoloBranch>,
+    #[description = "The generation the talent belongs to."] generation: Option<HoloGeneration>,
+    #[description = "The emoji of the talent."] emoji: Option<String>,
+    #[description = "Link to the icon of the talent."] icon: Option<String>,
+    #[description = "The colour of the talent, in hex (ex. #FF00FF)."] colour: Option<String>,
+    #[description = "The YouTube channel ID of the talent."] youtube_channel: Option<String>,
+    #[description = "The Twitter handle of the talent, without the @."] twitter_handle: Option<
+        String,
+    >,
+    #[description = "The Twitter ID of the talent."] twitter_id: Option<u64>,
+    #[description = "The role to ping for the talent."] role: Option<Role>,
+    #[description = "The timezone of the talent in IANA format."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+) -> anyhow::Result<()> {
+    let _edit = TALENT_EDITS.lock().await;
+    let mut talents = ctx.data().data.read().await.talent_updates.borrow().clone();
+
+    let index = match talents
+        .iter()
+        .position(|t| t.name.eq_ignore_ascii_case(name.trim()))
+    {
+        Some(index) => index,
+        None => return reply_invalid(ctx, &format!("No talent named {name} found.")).await,
+    };
+
+    let changes = TalentChanges {
+        name: new_name,
+        branch,
+        generation,
+        emoji,
+        icon,
+        colour,
+        youtube_channel,
+        twitter_handle,
+        twitter_id,
+        role: role.map(|r| r.id),
+        timezone,
+    };
+
+    if let Err(e) = changes.apply(&mut talents[index]) {
+        return reply_invalid(ctx, &e).await;
+    }
+
+    let new_name = talents[index].name.clone();
+
+    if talents
+        .iter()
+        .enumerate()
+        .any(|(i, t)| i != index && t.name.eq_ignore_ascii_case(&new_name))
+    {
+        return reply_invalid(ctx, &format!("{new_name} is already being tracked.")).await;
+    }
+
+    publish_talents(ctx, talents, AuditAction::TalentEdited, &new_name).await?;
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("{new_name} has been updated!"))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, required_permissions = "KICK_MEMBERS")]
+/// Stop tracking a talent.
+pub(crate) async fn remove(
+    ctx: Context<'_>,
+    #[description = "The talent to remove."]
+    #[autocomplete = "autocomplete_talent"]
+    name: String,
+) -> anyhow::Result<()> {
+    let _edit = TALENT_EDITS.lock().await;
+    let mut talents = ctx.data().data.read().await.talent_updates.borrow().clone();
+    let talent_count = talents.len();
+
+    talents.retain(|t| !t.name.eq_ignore_ascii_case(name.trim()));
+
+    if talents.len() == talent_count {
+        return reply_invalid(ctx, &format!("No talent named {name} found.")).await;
+    }
+
+    publish_talents(ctx, talents, AuditAction::TalentRemoved, &name).await?;
+
+    ctx.send(|m| {
+        m.ephemeral(true)
+            .content(format!("{name} is no longer being tracked."))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct TalentChanges {
+    name: Option<String>,
+    branch: Option<HoloBranch>,
+    generation: Option<HoloGeneration>,
+    emoji: Option<String>,
+    icon: Option<String>,
+    colour: Option<String>,
+    youtube_channel: Option<String>,
+    twitter_handle: Option<String>,
+    twitter_id: Option<u64>,
+    role: Option<RoleId>,
+    timezone: Option<String>,
+}
+
+impl TalentChanges {
+    fn apply(self, talent: &mut Talent) -> Result<(), String> {
+        if let Some(colour) = self.colour {
+            let hex = colour
+                .trim()
+                .trim_start_matches('#')
+                .trim_start_matches("0x");
+
+            talent.colour = u32::from_str_radix(hex, 16)
+                .map_err(|_| format!("{colour} is not a valid hex colour."))?;
+        }
+
+        if let Some(channel) = self.youtube_channel {
+            talent.youtube_ch_id = Some(
+                channel
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("{channel} is not a valid YouTube channel ID."))?,
+            );
+        }
+
+        if let Some(timezone) = self.timezone {
+            talent.timezone = *try_get_timezone(&timezone).map_err(|e| e.to_string())?;
+        }
+
+        if let Some(name) = self.name {
+            talent.name = name.trim().to_owned();
+        }
+
+        if let Some(branch) = self.branch {
+            talent.branch = branch;
+        }
+
+        if let Some(generation) = self.generation {
+            talent.generation = generation;
+        }
+
+        if let Some(emoji) = self.emoji {
+            talent.emoji = emoji;
+        }
+
+        if let Some(icon) = self.icon {
+            talent.icon = icon;
+        }
+
+        if let Some(handle) = self.twitter_handle {
+            talent.twitter_handle = Some(handle.trim().trim_start_matches('@').to_owned());
+        }
+
+        if let Some(id) = self.twitter_id {
+            talent.twitter_id = Some(id);
+        }
+
+        if let Some(role) = self.role {
+            talent.discord_role = Some(role);
+        }
+
+        if talent.twitter_id.is_some() && talent.twitter_handle.is_none() {
+            return Err("Talents with a Twitter ID also need a Twitter handle.".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+/// Saves the talent roster to disk, hands it to the stream and tweet trackers,
+/// and records the change in the audit log. [`TALENT_EDITS`] should be held while the
+/// roster is changed and published.
+pub(crate) async fn publish_talents(
+    ctx: Context<'_>,
+    talents: Vec<Talent>,
+    action: AuditAction,
+    name: &str,
+) -> anyhow::Result<()> {
+    ctx.data().config.save_talents(&talents).context(here!())?;
+    ctx.data()
+        .data
+        .read()
+        .await
+        .talent_updates
+        .send_replace(talents);
+
+    audit_log::record(
+        ctx.http(),
+        AuditEntry::new(action, name)
+            .guild(ctx.guild_id())
+            .actor(ctx.author().id),
+    )
+    .await;
+
+    Ok(())
+}
+
+async fn reply_invalid(ctx: Context<'_>, reason: &str) -> anyhow::Result<()> {
+    ctx.send(|m| m.ephemeral(true).content(format!("Error! {reason}")))
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn autocomplete_talent(
+    ctx: Context<'_>,
+    partial: &str,
+) -> impl Iterator<Item = String> {
+    let partial = partial.to_lowercase();
+
+    ctx.data()
+        .data
+        .read()
+        .await
+        .talent_updates
+        .borrow()
+        .iter()
+        .map(|t| t.name.clone())
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(25)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
@@ -0,0 +1,94 @@
+use std::sync::atomic::Ordering;
+
+use futures::future::BoxFuture;
+use poise::FrameworkError;
+use tokio::{select, time::sleep};
+
+use super::prelude::*;
+use crate::command_errors::CommandError;
+
+type SlashAction = for<'a> fn(
+    ApplicationContext<'a>,
+) -> BoxFuture<'a, Result<(), FrameworkError<'a, DataWrapper, Error>>>;
+
+/// The original action of a guarded command, kept in its `custom_data`.
+struct GuardedAction(SlashAction);
+
+/// Wraps every slash command, and their subcommands, in [`run_guarded`].
+pub(crate) fn guard_commands(commands: &mut [Command]) {
+    for command in commands {
+        if let Some(action) = command.slash_action.take() {
+            command.custom_data = Box::new(GuardedAction(action));
+            command.slash_action = Some(run_guarded);
+        }
+
+        guard_commands(&mut command.subcommands);
+    }
+}
+
+/// Defers the response if the command hasn't responded in time, so the interaction
+/// doesn't expire, and cancels the command if it runs for longer than it's allowed to
+/// without responding. Commands that have responded themselves, such as polls and
+/// paginated lists waiting on their users, are left to run for as long as they need.
+fn run_guarded(
+    ctx: ApplicationContext<'_>,
+) -> BoxFuture<'_, Result<(), FrameworkError<'_, DataWrapper, Error>>> {
+    Box::pin(async move {
+        let action = match ctx.command.custom_data.downcast_ref::<GuardedAction>() {
+            Some(GuardedAction(action)) => *action,
+            None => return Ok(()),
+        };
+
+        let (defer_after, max_duration) = ctx
+            .data
+            .config
+            .command_timeouts
+            .limits_for(&ctx.command.qualified_name);
+
+        let command = action(ctx);
+        let defer = sleep(defer_after.to_std().unwrap_or_default());
+        let deadline = sleep(max_duration.to_std().unwrap_or_default());
+        tokio::pin!(command, defer, deadline);
+
+        let mut defer_checked = false;
+        let mut deferred = false;
+
+        loop {
+            select! {
+                result = &mut command => return result,
+                _ = &mut defer, if !defer_checked => {
+                    defer_checked = true;
+
+                    if ctx.has_sent_initial_response.load(Ordering::SeqCst) {
+                        continue;
+                    }
+
+                    deferred = true;
+
+                    debug!(command = %ctx.command.qualified_name, "Deferring slow command.");
+
+                    if let Err(e) = Context::Application(ctx)
+                        .defer_response(ctx.command.ephemeral)
+                        .await
+                    {
+                        warn!(?e, "Failed to defer command response!");
+                    }
+                }
+                _ = &mut deadline => {
+                    // A deferred response is the guard's own, so it doesn't count.
+                    if !deferred && ctx.has_sent_initial_response.load(Ordering::SeqCst) {
+                        debug!(command = %ctx.command.qualified_name, "Command responded, lifting its deadline.");
+                        break;
+                    }
+
+                    return Err(FrameworkError::Command {
+                        error: CommandError::TimedOut.into(),
+                        ctx: Context::Application(ctx),
+                    });
+                }
+            }
+        }
+
+        command.await
+    })
+}
@@ -1,6 +1,6 @@
-use utility::functions::try_parse_written_time;
+use utility::functions::{try_parse_written_time, try_parse_written_time_with_tz};
 
-use super::prelude::*;
+use super::{prelude::*, timezone::get_user_timezone};
 
 #[derive(Debug, ChoiceParameter)]
 pub enum TimestampFormat {
@@ -93,21 +93,26 @@ impl TimestampFormat {
     slash_command,
     prefix_command,
     track_edits,
-    required_permissions = "SEND_MESSAGES"
+    required_permissions = "SEND_MESSAGES",
+    category = "Utility"
 )]
 /// Given a relative time, outputs a Discord timestamp.
 pub(crate) async fn timestamp(
     ctx: Context<'_>,
 
     #[description = "What the time is"] when: String,
-    #[description = "Your timezone in IANA format (ex. America/New_York)."] timezone: Option<
-        String,
-    >,
+    #[description = "Your timezone in IANA format (ex. America/New_York), defaults to your stored timezone."]
+    timezone: Option<String>,
     #[description = "The format of the timestamp."] format: Option<TimestampFormat>,
 ) -> anyhow::Result<()> {
     ctx.defer_ephemeral().await?;
 
-    let time = match try_parse_written_time(&when, timezone.as_deref()) {
+    let parsed = match (timezone, get_user_timezone(ctx).await) {
+        (None, Some(stored)) => try_parse_written_time_with_tz(&when, &stored),
+        (timezone, _) => try_parse_written_time(&when, timezone.as_deref()),
+    };
+
+    let time = match parsed {
         Ok(time) => time,
         Err(e) => {
             ctx.say(MessageBuilder::new().push_codeblock(e, None).build())
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use poise::serenity_prelude::User;
+use utility::{config::DatabaseOperations, functions::try_get_timezone};
+
+use super::prelude::*;
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("set", "get", "clear"),
+    category = "Utility"
+)]
+/// Manage your timezone, which is used when showing or parsing times.
+pub(crate) async fn timezone(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Set your timezone.
+pub(crate) async fn set(
+    ctx: Context<'_>,
+    #[description = "Your timezone in IANA format (ex. America/New_York)."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: String,
+) -> anyhow::Result<()> {
+    let timezone = match try_get_timezone(&timezone) {
+        Ok(tz) => *tz,
+        Err(e) => {
+            ctx.send(|m| m.ephemeral(true).content(format!("Error! {e}")))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let user = ctx.author().id;
+
+    {
+        let mut data = ctx.data().data.write().await;
+
+        {
+            let handle = data.database.lock().await;
+            HashMap::from([(user, timezone)])
+                .save_to_database(&handle)
+                .context(here!())?;
+        }
+
+        data.user_timezones.insert(user, timezone);
+    }
+
+    ctx.send(|m| {
+        m.ephemeral(true).content(format!(
+            "Your timezone has been set to `{}`.",
+            timezone.name()
+        ))
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Show the timezone of yourself or another user.
+pub(crate) async fn get(
+    ctx: Context<'_>,
+    #[description = "The user to look up, defaults to yourself."] user: Option<User>,
+) -> anyhow::Result<()> {
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let timezone = ctx
+        .data()
+        .data
+        .read()
+        .await
+        .user_timezones
+        .get(&user.id)
+        .copied();
+
+    let content = match timezone {
+        Some(tz) => format!("{}'s timezone is `{}`.", user.name, tz.name()),
+        None => format!("{} has not set a timezone.", user.name),
+    };
+
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+/// Remove your stored timezone.
+pub(crate) async fn clear(ctx: Context<'_>) -> anyhow::Result<()> {
+    let user = ctx.author().id;
+
+    let removed = {
+        let mut data = ctx.data().data.write().await;
+
+        {
+            let handle = data.database.lock().await;
+            handle
+                .delete(
+                    <HashMap<UserId, Tz> as DatabaseOperations<_>>::TABLE_NAME,
+                    "user_id",
+                    user.as_u64(),
+                )
+                .context(here!())?;
+        }
+
+        data.user_timezones.remove(&user).is_some()
+    };
+
+    let content = if removed {
+        "Your timezone has been removed."
+    } else {
+        "You have not set a timezone."
+    };
+
+    ctx.send(|m| m.ephemeral(true).content(content)).await?;
+
+    Ok(())
+}
+
+/// Returns the stored timezone of the invoking user, if any.
+pub(crate) async fn get_user_timezone(ctx: Context<'_>) -> Option<Tz> {
+    ctx.data()
+        .data
+        .read()
+        .await
+        .user_timezones
+        .get(&ctx.author().id)
+        .copied()
+}
+
+/// Returns the timezone given to a command, or the stored timezone of the invoking user if none was given.
+pub(crate) async fn resolve_timezone(
+    ctx: Context<'_>,
+    timezone: Option<&str>,
+) -> anyhow::Result<Option<Tz>> {
+    match timezone {
+        Some(tz) => try_get_timezone(tz).map(|tz| Some(*tz)),
+        None => Ok(get_user_timezone(ctx).await),
+    }
+}
+
+/// Formats a time with Discord timestamp markup, which is shown relative to now and in the
+/// viewer's local time, followed by the time in `timezone` if there is one.
+pub(crate) fn format_localized_time(time: DateTime<Utc>, timezone: Option<Tz>) -> String {
+    let timestamp = time.timestamp();
+
+    match timezone {
+        Some(tz) => format!(
+            "<t:{timestamp}:R>, at <t:{timestamp}:t> ({})",
+            time.with_timezone(&tz).format("%H:%M %Z")
+        ),
+        None => format!("<t:{timestamp}:R>, at <t:{timestamp}:t>"),
+    }
+}
+
+pub(crate) async fn autocomplete_timezone(
+    _ctx: Context<'_>,
+    partial: &str,
+) -> impl Iterator<Item = String> {
+    let partial = partial.to_ascii_lowercase();
+
+    TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(move |name| name.to_ascii_lowercase().contains(&partial))
+        .take(25)
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
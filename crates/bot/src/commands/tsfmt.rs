@@ -11,7 +11,12 @@ use super::prelude::*;
 
 static TS_FMT_RGX: once_cell::sync::Lazy<Regex> = regex_lazy!(r"(?m)\{(.+?):?(\w)?\}");
 
-#[poise::command(prefix_command, track_edits, required_permissions = "SEND_MESSAGES")]
+#[poise::command(
+    prefix_command,
+    track_edits,
+    required_permissions = "SEND_MESSAGES",
+    category = "Utility"
+)]
 /// Formats string and evaluates all time expressions enclosed in {..}.
 pub(crate) async fn tsfmt(
     ctx: Context<'_>,
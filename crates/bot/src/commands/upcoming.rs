@@ -3,7 +3,11 @@ use std::borrow::Cow;
 use chrono::{DateTime, Utc};
 use serenity::builder::CreateEmbed;
 
-use super::prelude::*;
+use super::{
+    live::autocomplete_topic,
+    prelude::*,
+    timezone::{autocomplete_timezone, format_localized_time, resolve_timezone},
+};
 
 use utility::config::HoloBranch;
 
@@ -12,17 +16,34 @@ use utility::config::HoloBranch;
     prefix_command,
     track_edits,
     check = "stream_tracking_enabled",
-    required_permissions = "SEND_MESSAGES"
+    required_permissions = "SEND_MESSAGES",
+    category = "Hololive"
 )]
 /// Shows scheduled streams.
 pub(crate) async fn upcoming(
     ctx: Context<'_>,
     #[description = "Show only talents from this branch of Hololive."] branch: Option<HoloBranch>,
     #[description = "How many minutes to look ahead."] until: Option<u32>,
+    #[description = "Show only streams on this topic (ex. singing)."]
+    #[autocomplete = "autocomplete_topic"]
+    topic: Option<String>,
+    #[description = "Show times in this timezone (ex. America/New_York), defaults to your stored timezone."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
 ) -> anyhow::Result<()> {
+    let timezone = match resolve_timezone(ctx, timezone.as_deref()).await {
+        Ok(tz) => tz,
+        Err(e) => {
+            ctx.send(|m| m.ephemeral(true).content(format!("Error! {e}")))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
     let until = until.unwrap_or(60);
 
-    let scheduled = get_scheduled(ctx, branch, until as i64).await;
+    let scheduled = get_scheduled(ctx, branch, topic.as_deref(), until as i64).await;
 
     PaginatedList::new()
         .title(format!(
@@ -30,33 +51,25 @@ pub(crate) async fn upcoming(
             branch.map(|b| format!(" from {b}")).unwrap_or_default()
         ))
         .data(&scheduled)
-        .embed(Box::new(|s, _| {
+        .embed(Box::new(move |s, _| {
             let mut embed = CreateEmbed::default();
 
             embed.description(format!(
-                "{}\r\n{}\r\n<{}>",
+                "{}\r\n{}\r\n<{}>\r\nStarts {}.",
                 if let Some(role) = s.role {
                     Cow::Owned(Mention::from(role).to_string())
                 } else {
                     Cow::Borrowed(&s.name)
                 },
                 s.title,
-                s.url
+                s.url,
+                format_localized_time(s.start_at, timezone)
             ));
 
             embed
                 .colour(s.colour)
                 .thumbnail(s.thumbnail.to_owned())
-                .timestamp(s.start_at.to_rfc3339())
-                .footer(|f| {
-                    f.text(format!(
-                        "Starts {}",
-                        chrono_humanize::HumanTime::from(s.start_at - Utc::now()).to_text_en(
-                            chrono_humanize::Accuracy::Rough,
-                            chrono_humanize::Tense::Future
-                        )
-                    ))
-                });
+                .timestamp(s.start_at.to_rfc3339());
 
             embed
         }))
@@ -80,6 +93,7 @@ struct ScheduledEmbedData {
 async fn get_scheduled(
     ctx: Context<'_>,
     branch: Option<HoloBranch>,
+    topic: Option<&str>,
     until: i64,
 ) -> Vec<ScheduledEmbedData> {
     let data = ctx.data();
@@ -105,7 +119,7 @@ async fn get_scheduled(
                 }
             }
 
-            true
+            topic.map_or(true, |t| l.has_topic(t))
         })
         .map(|(_, l)| ScheduledEmbedData {
             name: l.streamer.name.clone(),
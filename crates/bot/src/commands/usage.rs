@@ -0,0 +1,117 @@
+use apis::translation_usage::TranslationUsage;
+use chrono::{DateTime, Datelike, Months, NaiveDate, TimeZone, Utc};
+use deepl::DeepL;
+use utility::http;
+
+use super::prelude::*;
+use crate::polls::bar;
+
+#[poise::command(
+    slash_command,
+    owners_only,
+    check = "usage_enabled",
+    subcommands("breakdown"),
+    category = "Admin"
+)]
+/// Show how the translation characters are being used.
+pub(crate) async fn usage(_ctx: Context<'_>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[poise::command(slash_command, owners_only)]
+/// Show how many translation characters each feature has used in a month.
+pub(crate) async fn breakdown(
+    ctx: Context<'_>,
+    #[description = "The month to show, ex. `2023-01`, defaults to this month."] month: Option<
+        String,
+    >,
+) -> anyhow::Result<()> {
+    let (from, to) = match month_range(month.as_deref()) {
+        Ok(range) => range,
+        Err(e) => {
+            ctx.send(|m| m.ephemeral(true).content(format!("Error! {e}")))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let usage = TranslationUsage::open(&ctx.data().config.database)
+        .and_then(|usage| usage.breakdown(from, to))
+        .context(here!())?;
+
+    let total = usage.iter().map(|(_, c)| c).sum::<u64>();
+
+    let chart = usage
+        .iter()
+        .map(|(feature, count)| format!("**{}**\n{}", feature.name(), bar(*count, total)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // The account's own count covers everything, including what was used before tracking.
+    let account = match ctx.data().config.deepl_token() {
+        Some(token) => {
+            let client = DeepL::with_agent(token.to_owned(), http::client().agent().clone());
+
+            tokio::task::spawn_blocking(move || client.usage_information())
+                .await
+                .context(here!())?
+                .ok()
+        }
+        None => None,
+    };
+
+    let colour = ctx.data().config.theme.default;
+
+    ctx.send(|m| {
+        m.ephemeral(true).embed(|e| {
+            e.title(format!("Translation usage in {}", from.format("%B %Y")))
+                .colour(colour)
+                .description(chart)
+                .field("Total", format!("{total} characters"), true);
+
+            if let Some(account) = &account {
+                e.field(
+                    "Used this billing period",
+                    format!(
+                        "{} of {} characters",
+                        account.character_count, account.character_limit
+                    ),
+                    true,
+                );
+            }
+
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// The start of the month, and the start of the month after it.
+fn month_range(month: Option<&str>) -> anyhow::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = match month {
+        Some(month) => NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+            .map_err(|_| anyhow!("\"{month}\" is not a month in the format YYYY-MM."))?,
+        None => Utc::now().date_naive().with_day(1).unwrap(),
+    };
+
+    let end = start
+        .checked_add_months(Months::new(1))
+        .ok_or_else(|| anyhow!("Invalid month."))?;
+
+    let midnight = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .map(|time| Utc.from_utc_datetime(&time))
+            .ok_or_else(|| anyhow!("Invalid month."))
+    };
+
+    Ok((midnight(start)?, midnight(end)?))
+}
+
+async fn usage_enabled(ctx: Context<'_>) -> anyhow::Result<bool> {
+    Ok(ctx.data().config.deepl_token().is_some())
+}
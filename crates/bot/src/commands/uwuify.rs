@@ -7,7 +7,8 @@ use super::prelude::*;
     prefix_command,
     slash_command,
     required_permissions = "SEND_MESSAGES",
-    member_cooldown = 15
+    member_cooldown = 15,
+    category = "Fun"
 )]
 /// Uwuifies provided text.
 pub(crate) async fn uwuify(
@@ -25,7 +26,8 @@ pub(crate) async fn uwuify(
 #[poise::command(
     context_menu_command = "Uwuify message",
     required_permissions = "SEND_MESSAGES",
-    member_cooldown = 15
+    member_cooldown = 15,
+    category = "Fun"
 )]
 /// Uwuifies message.
 pub(crate) async fn uwuify_message(
@@ -1,51 +1,66 @@
 use std::{
-    cell::RefCell,
     collections::{hash_map::Entry, HashMap},
     sync::Arc,
 };
 
 use anyhow::{anyhow, Context as _};
-use chrono::{Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
 use futures::future::BoxFuture;
 use holodex::model::id::VideoId;
 use macros::clone_variables;
 // use music_queue::{MusicData, Queue};
 use poise::{
     serenity_prelude::{
-        AttachmentType, ChannelId, ExecuteWebhook, GatewayIntents, Mentionable, User, Webhook,
+        AttachmentType, ChannelId, ExecuteWebhook, GatewayIntents, Interaction, Mentionable, User,
+        Webhook,
     },
     Context, Event, Framework, FrameworkContext,
 };
 use serenity::{
     client::Context as Ctx,
     model::{
-        id::{EmojiId, StickerId},
+        id::{EmojiId, GuildId, StickerId, UserId},
         prelude::{Mention, ReactionType},
     },
 };
 // use songbird::SerenityInit;
 use tokio::{
     select,
-    sync::{broadcast, mpsc, oneshot, watch, Mutex, RwLock},
+    sync::{broadcast, mpsc, oneshot, watch, Mutex, Notify, RwLock},
     task::JoinHandle,
 };
 use tracing::{debug, error, info};
 
-use apis::meme_api::MemeApi;
+use apis::{discord_api::DiscordMessageData, meme_api::MemeApi};
 use url::Url;
 use utility::{
     config::{
-        Config, ContentFilterAction, DatabaseHandle, EmojiStats,
-        EmojiUsageSource, /* SavedMusicQueue */
+        Config, ContentFilterAction, DatabaseHandle, DatabaseOperations, EmojiStats,
+        EmojiUsageSource, Quote, Talent, /* SavedMusicQueue */
     },
     discord::*,
     extensions::MessageExt,
-    here,
+    feature_flags, here,
     streams::*,
-    types::Service,
+    types::{Cache, Service},
 };
 
-use crate::{commands as cmds, resource_tracking, temp_mute_react};
+#[cfg(feature = "translation")]
+use crate::translation_requests::{self, TranslationRequests};
+use crate::{
+    announcements::{self, Announcement},
+    command_errors::{self, CommandError},
+    command_metrics, command_registration, commands as cmds,
+    easter_eggs::EasterEggs,
+    emotes::Emotes,
+    giveaways::{self, Giveaway},
+    polls::{self, Poll},
+    preflight, presence,
+    rate_limits::RateLimits,
+    reminders::{self, Reminder, ReminderTimers},
+    resource_tracking, temp_mute_react, verification, web_api,
+};
 
 pub struct DataWrapper {
     pub config: Arc<Config>,
@@ -56,7 +71,9 @@ pub struct DiscordData {
     pub database: Mutex<DatabaseHandle>,
 
     pub stream_index: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
-    pub stream_updates: Option<broadcast::Sender<StreamUpdate>>,
+    pub stream_updates: Option<StreamUpdateFanout>,
+    pub index_commands: Option<mpsc::Sender<IndexCommand>>,
+    pub claimed_chats: watch::Receiver<HashMap<VideoId, ChannelId>>,
 
     pub meme_creator: Option<MemeApi>,
     // pub music_data: Option<MusicData>,
@@ -64,27 +81,68 @@ pub struct DiscordData {
         Option<mpsc::Sender<ResourceUsageEvent<EmojiId, EmojiUsageSource, EmojiStats>>>,
     pub sticker_usage_counter: Option<mpsc::Sender<ResourceUsageEvent<StickerId, (), u64>>>,
 
-    pub guild_notifier: Mutex<RefCell<Option<oneshot::Sender<()>>>>,
     pub service_restarter: broadcast::Sender<Service>,
+    pub cache_flusher: broadcast::Sender<Cache>,
+    pub message_sender: mpsc::Sender<DiscordMessageData>,
+    pub talent_updates: watch::Sender<Vec<Talent>>,
 
     pub webhook_cache: HashMap<ChannelId, Webhook>,
+    pub user_timezones: HashMap<UserId, Tz>,
+    pub quotes: Vec<Quote>,
+    pub rate_limits: RateLimits,
+    pub easter_eggs: EasterEggs,
+    pub emotes: Emotes,
+    #[cfg(feature = "translation")]
+    pub translation_requests: TranslationRequests,
+    /// Wakes up the announcement scheduler when announcements are added or cancelled.
+    pub announcements_changed: Arc<Notify>,
+    /// Wakes up the giveaway scheduler when giveaways are started.
+    pub giveaways_changed: Arc<Notify>,
+    /// Wakes up the poll scheduler when polls are posted.
+    pub polls_changed: Arc<Notify>,
+    /// Tells the reminder scheduler when reminders are added or removed.
+    pub reminder_timers: Arc<ReminderTimers>,
+
+    /// Config edits made at runtime, which take effect after a restart.
+    pub edited_config: Option<Config>,
+    pub started_at: DateTime<Utc>,
 }
 
 impl DiscordData {
+    #[allow(clippy::too_many_arguments)]
     pub fn load(
         ctx: &Ctx,
         config: &Config,
         stream_index: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
-        stream_updates: broadcast::Sender<StreamUpdate>,
-        guild_notifier: oneshot::Sender<()>,
+        stream_updates: StreamUpdateFanout,
         service_restarter: broadcast::Sender<Service>,
+        cache_flusher: broadcast::Sender<Cache>,
+        message_sender: mpsc::Sender<DiscordMessageData>,
+        talent_updates: watch::Sender<Vec<Talent>>,
+        index_commands: mpsc::Sender<IndexCommand>,
+        claimed_chats: watch::Receiver<HashMap<VideoId, ChannelId>>,
     ) -> anyhow::Result<Self> {
         let database = config.database.get_handle()?;
 
-        let (stream_index, stream_updates) = if config.stream_tracking.enabled {
-            (stream_index, Some(stream_updates))
+        HashMap::<UserId, Tz>::create_table(&database).context(here!())?;
+        let user_timezones =
+            HashMap::<UserId, Tz>::load_from_database(&database).context(here!())?;
+
+        Vec::<Quote>::create_table(&database).context(here!())?;
+        let quotes = Vec::<Quote>::load_from_database(&database).context(here!())?;
+
+        let rate_limits = RateLimits::load(&database).context(here!())?;
+        let easter_eggs = EasterEggs::load(&database).context(here!())?;
+        let emotes = Emotes::load(&database).context(here!())?;
+        Announcement::create_table(&database).context(here!())?;
+        Poll::create_tables(&database).context(here!())?;
+        Giveaway::create_tables(&database).context(here!())?;
+        Reminder::create_tables(&database).context(here!())?;
+
+        let (stream_index, stream_updates, index_commands) = if config.stream_tracking.enabled {
+            (stream_index, Some(stream_updates), Some(index_commands))
         } else {
-            (None, None)
+            (None, None, None)
         };
 
         let meme_creator = config
@@ -116,11 +174,107 @@ impl DiscordData {
             (None, None)
         };
 
+        let announcements_changed = Arc::new(Notify::new());
+
+        if config.announcements.enabled {
+            let ctx = ctx.clone();
+            let database = &config.database;
+            let changed = Arc::clone(&announcements_changed);
+
+            tokio::spawn(clone_variables!(database; {
+                if let Err(e) = announcements::scheduler(ctx, &database, changed).await.context(here!()) {
+                    error!("{:?}", e);
+                }
+            }));
+        }
+
+        if config.web_api.enabled {
+            let state = web_api::ApiState::new(
+                ctx.clone(),
+                config,
+                stream_index.clone(),
+                talent_updates.subscribe(),
+                Arc::clone(&announcements_changed),
+            )?;
+            let address = config.web_api.address;
+
+            tokio::spawn(async move {
+                if let Err(e) = web_api::serve(address, state).await.context(here!()) {
+                    error!("{:?}", e);
+                }
+            });
+        }
+
+        let giveaways_changed = Arc::new(Notify::new());
+
+        if config.giveaways.enabled {
+            let ctx = ctx.clone();
+            let database = &config.database;
+            let changed = Arc::clone(&giveaways_changed);
+            let colour = config.theme.default;
+
+            tokio::spawn(clone_variables!(database; {
+                if let Err(e) = giveaways::scheduler(ctx, &database, changed, colour).await.context(here!()) {
+                    error!("{:?}", e);
+                }
+            }));
+        }
+
+        let polls_changed = Arc::new(Notify::new());
+
+        {
+            let ctx = ctx.clone();
+            let database = &config.database;
+            let changed = Arc::clone(&polls_changed);
+            let colour = config.theme.default;
+
+            tokio::spawn(clone_variables!(database; {
+                if let Err(e) = polls::scheduler(ctx, &database, changed, colour).await.context(here!()) {
+                    error!("{:?}", e);
+                }
+            }));
+        }
+
+        let reminder_timers = Arc::new(ReminderTimers::default());
+
+        if config.reminders.enabled {
+            let ctx = ctx.clone();
+            let database = &config.database;
+            let timers = Arc::clone(&reminder_timers);
+
+            tokio::spawn(clone_variables!(database; {
+                let scheduler = async {
+                    let handle = database.get_handle().context(here!())?;
+                    reminders::scheduler(ctx, handle, timers).await
+                };
+
+                if let Err(e) = scheduler.await.context(here!()) {
+                    error!("{:?}", e);
+                }
+            }));
+        }
+
+        if config.presence.enabled {
+            let ctx = ctx.clone();
+            let presence_config = config.presence.clone();
+            let stream_index = stream_index.clone();
+
+            tokio::spawn(async move {
+                let result = presence::manager(ctx, presence_config, stream_index)
+                    .await
+                    .context(here!());
+
+                if let Err(e) = result {
+                    error!("{:?}", e);
+                }
+            });
+        }
+
         if config.react_temp_mute.enabled {
             let ctx = ctx.clone();
 
             tokio::spawn(clone_variables!(config; {
-                if let Err(e) = temp_mute_react::handler(ctx, &config.react_temp_mute).await.context(here!()) {
+                if let Err(e) = temp_mute_react::handler(ctx, &config.react_temp_mute, config.theme).await.context(here!()) {
                     error!("{:?}", e);
                 }
             }));
@@ -133,14 +287,31 @@ impl DiscordData {
             // music_data: None,
             stream_index,
             stream_updates,
+            index_commands,
+            claimed_chats,
 
             emoji_usage_counter,
             sticker_usage_counter,
 
-            guild_notifier: Mutex::new(RefCell::new(Some(guild_notifier))),
             service_restarter,
+            cache_flusher,
+            message_sender,
+            talent_updates,
 
             webhook_cache: HashMap::new(),
+            user_timezones,
+            quotes,
+            rate_limits,
+            easter_eggs,
+            emotes,
+            #[cfg(feature = "translation")]
+            translation_requests: TranslationRequests::default(),
+            announcements_changed,
+            giveaways_changed,
+            polls_changed,
+            reminder_timers,
+            edited_config: None,
+            started_at: Utc::now(),
         })
     }
 }
@@ -148,15 +319,32 @@ impl DiscordData {
 pub struct DiscordBot;
 
 impl DiscordBot {
+    #[allow(clippy::too_many_arguments)]
     pub async fn start(
         config: Arc<Config>,
-        stream_update: broadcast::Sender<StreamUpdate>,
+        stream_update: StreamUpdateFanout,
         index_receiver: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
-        guild_ready: oneshot::Sender<()>,
         service_restarter: broadcast::Sender<Service>,
+        cache_flusher: broadcast::Sender<Cache>,
+        message_sender: mpsc::Sender<DiscordMessageData>,
+        talent_updates: watch::Sender<Vec<Talent>>,
+        index_commands: mpsc::Sender<IndexCommand>,
+        claimed_chats: watch::Receiver<HashMap<VideoId, ChannelId>>,
     ) -> anyhow::Result<(JoinHandle<()>, Ctx)> {
         let (ctx_tx, ctx_rx) = oneshot::channel();
 
+        let mut intents = GatewayIntents::GUILDS
+            | GatewayIntents::GUILD_EMOJIS_AND_STICKERS
+            | GatewayIntents::GUILD_MESSAGES
+            | GatewayIntents::GUILD_MESSAGE_REACTIONS
+            | GatewayIntents::GUILD_VOICE_STATES
+            | GatewayIntents::MESSAGE_CONTENT;
+
+        // Privileged, so it's only requested when it's needed to see members join.
+        if config.verification.enabled {
+            intents |= GatewayIntents::GUILD_MEMBERS;
+        }
+
         let client_builder = poise::Framework::builder()
             .token(&config.discord_token)
             .initialize_owners(true)
@@ -169,8 +357,12 @@ impl DiscordBot {
                         &config,
                         index_receiver,
                         stream_update,
-                        guild_ready,
                         service_restarter,
+                        cache_flusher,
+                        message_sender,
+                        talent_updates,
+                        index_commands,
+                        claimed_chats,
                     )?;
 
                     Ok(DataWrapper {
@@ -179,14 +371,7 @@ impl DiscordBot {
                     })
                 })
             })
-            .intents(
-                GatewayIntents::GUILDS
-                    | GatewayIntents::GUILD_EMOJIS_AND_STICKERS
-                    | GatewayIntents::GUILD_MESSAGES
-                    | GatewayIntents::GUILD_MESSAGE_REACTIONS
-                    | GatewayIntents::GUILD_VOICE_STATES
-                    | GatewayIntents::MESSAGE_CONTENT,
-            )
+            .intents(intents)
             // .client_settings(|c| c.register_songbird())
             .options(poise::FrameworkOptions {
                 prefix_options: poise::PrefixFrameworkOptions {
@@ -201,6 +386,8 @@ impl DiscordBot {
                 event_handler: Self::handle_discord_event,
                 on_error: |error| Box::pin(Self::on_error(error)),
                 command_check: Some(Self::should_fail),
+                pre_command: |ctx| Box::pin(command_metrics::before_command(ctx)),
+                post_command: |ctx| Box::pin(command_metrics::after_command(ctx, true)),
                 commands: cmds::get_commands(),
                 ..Default::default()
             });
@@ -209,6 +396,7 @@ impl DiscordBot {
 
         let task = tokio::spawn(async move {
             let client_clone = Arc::clone(&client);
+            let usage_saver = tokio::spawn(Self::save_rate_limit_usage(Arc::clone(&client)));
 
             let status = select! {
                 e = client.start() => {
@@ -219,6 +407,8 @@ impl DiscordBot {
                 }
             };
 
+            usage_saver.abort();
+
             if let Err(e) = Self::save_client_data(client_clone).await {
                 error!("{:?}", e);
             }
@@ -235,6 +425,23 @@ impl DiscordBot {
         Ok((task, cache))
     }
 
+    /// Registers the slash commands in a guild, replacing the ones registered there,
+    /// without connecting to the gateway. Returns how many commands were registered.
+    pub async fn register_commands(config: &Config, guild_id: u64) -> anyhow::Result<usize> {
+        let http = serenity::http::Http::new(&config.discord_token);
+
+        let application = http.get_current_application_info().await.context(here!())?;
+        http.set_application_id(application.id.0);
+
+        command_registration::register_guild(
+            &http,
+            &config.command_registration,
+            &cmds::get_commands(),
+            GuildId(guild_id),
+        )
+        .await
+    }
+
     fn should_fail(
         ctx: Context<'_, DataWrapper, anyhow::Error>,
     ) -> BoxFuture<'_, anyhow::Result<bool>> {
@@ -253,10 +460,81 @@ impl DiscordBot {
                 return Ok(false);
             }
 
-            Ok(true)
+            if !Self::check_feature_flag(ctx).await? {
+                return Ok(false);
+            }
+
+            Self::check_rate_limit(ctx).await
         })
     }
 
+    /// Checks that the feature the command belongs to hasn't been disabled in the guild.
+    async fn check_feature_flag(
+        ctx: Context<'_, DataWrapper, anyhow::Error>,
+    ) -> anyhow::Result<bool> {
+        let (guild_id, feature) = match (
+            ctx.guild_id(),
+            cmds::features::command_feature(ctx.command()),
+        ) {
+            (Some(g), Some(f)) => (g, f),
+            _ => return Ok(true),
+        };
+
+        if feature_flags::is_enabled(guild_id, feature) {
+            return Ok(true);
+        }
+
+        ctx.send(|m| {
+            m.ephemeral(true)
+                .content("This feature has been disabled in this server.")
+        })
+        .await
+        .context(here!())?;
+
+        Ok(false)
+    }
+
+    /// Checks the command against the rate limits set for the guild,
+    /// telling the member when they can use it again if they're limited.
+    async fn check_rate_limit(
+        ctx: Context<'_, DataWrapper, anyhow::Error>,
+    ) -> anyhow::Result<bool> {
+        let guild_id = match ctx.guild_id() {
+            Some(g) => g,
+            None => return Ok(true),
+        };
+
+        let roles = ctx
+            .author_member()
+            .await
+            .map(|m| m.roles.clone())
+            .unwrap_or_default();
+
+        let result = ctx.data().data.write().await.rate_limits.try_use(
+            guild_id,
+            ctx.author().id,
+            &roles,
+            &ctx.command().qualified_name,
+            Utc::now(),
+        );
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(available_at) => {
+                ctx.send(|m| {
+                    m.ephemeral(true).content(format!(
+                        "You can use this command again <t:{}:R>.",
+                        available_at.timestamp()
+                    ))
+                })
+                .await
+                .context(here!())?;
+
+                Ok(false)
+            }
+        }
+    }
+
     fn handle_discord_event<'a>(
         ctx: &'a Ctx,
         event: &'a Event<'_>,
@@ -274,6 +552,22 @@ impl DiscordBot {
                             guild_id.name(ctx).unwrap_or_else(|| "<unknown>".to_owned())
                         );
                     }
+
+                    let registered = command_registration::register_global(
+                        &ctx.http,
+                        &data.config.command_registration,
+                        &framework.options().commands,
+                    )
+                    .await;
+
+                    match registered {
+                        Ok(count) => info!(count, "Registered global commands."),
+                        Err(e) => error!("{:?}", e),
+                    }
+
+                    if let Err(e) = preflight::run(ctx, &data.config).await {
+                        error!("{:?}", e);
+                    }
                 }
 
                 Event::GuildCreate {
@@ -286,28 +580,13 @@ impl DiscordBot {
 
                     info!(name = %guild.name, "Guild initialized!");
 
-                    let commands_builder =
-                        poise::builtins::create_application_commands(&framework.options().commands);
-
-                    let commands_builder = serenity::json::Value::Array(commands_builder.0);
-
-                    ctx.http
-                        .create_guild_application_commands(guild.id.0, &commands_builder)
-                        .await?;
-
-                    {
-                        let read_lock = data.data.read().await;
-                        let sender_lock = read_lock.guild_notifier.lock().await;
-                        let sender = sender_lock.replace(None);
-
-                        if let Some(sender) = sender {
-                            sender
-                                .send(())
-                                .map_err(|_| anyhow!("Failed to send notification!"))
-                        } else {
-                            Err(anyhow!("Failed to get notification sender!"))
-                        }?;
-                    }
+                    command_registration::register_guild(
+                        &ctx.http,
+                        &data.config.command_registration,
+                        &framework.options().commands,
+                        guild.id,
+                    )
+                    .await?;
 
                     /* if data.config.music_bot.enabled {
                         let db_handle = match data.config.database.get_handle() {
@@ -486,7 +765,8 @@ impl DiscordBot {
 
                     if data.config.content_filtering.enabled {
                         let filter_config = &data.config.content_filtering;
-                        let filter_actions = filter_config.filter(msg).into_actions();
+                        let filter_actions =
+                            filter_config.filter(msg, &data.config.theme).into_actions();
 
                         for action in filter_actions {
                             match action {
@@ -581,8 +861,74 @@ impl DiscordBot {
                         }
                     }
 
+                    if let Some(guild_id) = msg.guild_id {
+                        let response = data.data.read().await.easter_eggs.trigger(
+                            guild_id,
+                            &msg.content,
+                            Utc::now(),
+                        );
+
+                        if let Some(response) = response {
+                            if let Err(e) = msg
+                                .channel_id
+                                .send_message(&ctx.http, |m| match &response {
+                                    EasterEggResponse::Text(text) => m.content(text),
+                                    EasterEggResponse::Image(url) => m.embed(|e| e.image(url)),
+                                })
+                                .await
+                            {
+                                error!(err = %e, "Failed to send easter egg response.");
+                            }
+                        }
+                    }
+
                     if data.config.embed_compressor.enabled {}
                 }
+                Event::InteractionCreate {
+                    interaction: Interaction::MessageComponent(component),
+                } if component.data.custom_id == Giveaway::ENTER_ID => {
+                    if data.config.giveaways.enabled {
+                        giveaways::handle_entry(ctx, component, data)
+                            .await
+                            .context(here!())?;
+                    }
+                }
+                Event::InteractionCreate {
+                    interaction: Interaction::MessageComponent(component),
+                } if component.data.custom_id.starts_with(Poll::ID_PREFIX) => {
+                    polls::handle_interaction(ctx, component, data)
+                        .await
+                        .context(here!())?;
+                }
+                Event::InteractionCreate {
+                    interaction: Interaction::MessageComponent(component),
+                } if component
+                    .data
+                    .custom_id
+                    .starts_with(Reminder::SUBSCRIBE_PREFIX) =>
+                {
+                    if data.config.reminders.enabled {
+                        reminders::handle_subscribe_button(ctx, component, data)
+                            .await
+                            .context(here!())?;
+                    }
+                }
+                Event::GuildMemberAddition { new_member } => {
+                    if data.config.verification.enabled {
+                        verification::on_member_join(ctx, new_member, &data.config.verification)
+                            .await
+                            .context(here!())?;
+                    }
+                }
+                Event::InteractionCreate {
+                    interaction: Interaction::MessageComponent(component),
+                } if verification::is_verification_button(&component.data.custom_id) => {
+                    if data.config.verification.enabled {
+                        verification::handle_button(ctx, component, &data.config.verification)
+                            .await
+                            .context(here!())?;
+                    }
+                }
                 Event::ReactionAdd { add_reaction } => {
                     if data.config.emoji_tracking.enabled {
                         if let ReactionType::Custom {
@@ -607,6 +953,11 @@ impl DiscordBot {
                             }
                         }
                     }
+
+                    #[cfg(feature = "translation")]
+                    translation_requests::handle_reaction(ctx, add_reaction, data)
+                        .await
+                        .context(here!())?;
                 }
 
                 _ => (),
@@ -623,7 +974,36 @@ impl DiscordBot {
         match error {
             poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {error:?}"),
             poise::FrameworkError::Command { error, ctx } => {
-                error!(command = %ctx.command().name, "Command error: {:?}", error,);
+                command_metrics::after_command(ctx, false).await;
+                command_errors::handle_command_error(ctx, error).await;
+            }
+            poise::FrameworkError::EventHandler {
+                error,
+                ctx,
+                event:
+                    Event::InteractionCreate {
+                        interaction: Interaction::MessageComponent(component),
+                    },
+                ..
+            } => {
+                command_errors::handle_component_error(ctx, component, error).await;
+            }
+            poise::FrameworkError::GuildOnly { ctx } => {
+                command_errors::handle_check_failure(ctx, CommandError::GuildOnly, None).await;
+            }
+            poise::FrameworkError::NsfwOnly { ctx } => {
+                command_errors::handle_check_failure(ctx, CommandError::NsfwOnly, None).await;
+            }
+            poise::FrameworkError::MissingBotPermissions {
+                missing_permissions,
+                ctx,
+            } => {
+                command_errors::handle_check_failure(
+                    ctx,
+                    CommandError::PermissionDenied,
+                    Some(missing_permissions),
+                )
+                .await;
             }
             error => {
                 if let Err(e) = poise::builtins::on_error(error).await {
@@ -664,14 +1044,48 @@ impl DiscordBot {
         }
     }
 
+    /// Saves the command uses that count towards rate limits every few minutes,
+    /// so that they aren't all lost if the bot doesn't shut down cleanly.
+    async fn save_rate_limit_usage(client: Arc<Framework<DataWrapper, anyhow::Error>>) {
+        const SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+        let user_data = client.user_data().await;
+        let mut interval = tokio::time::interval(SAVE_INTERVAL);
+
+        // The first tick completes immediately, and there's nothing to save yet.
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let connection = match user_data.config.database.get_handle() {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!(?e, "Failed to save rate limit usage!");
+                    continue;
+                }
+            };
+
+            let data = user_data.data.read().await;
+
+            if let Err(e) = data.rate_limits.save_usage(&connection) {
+                error!(?e, "Failed to save rate limit usage!");
+            }
+        }
+    }
+
     async fn save_client_data(
         client: Arc<Framework<DataWrapper, anyhow::Error>>,
     ) -> anyhow::Result<()> {
         let user_data = client.user_data().await;
-        let _connection = user_data.config.database.get_handle()?;
+        let connection = user_data.config.database.get_handle()?;
 
         let data = user_data.data.read().await;
 
+        if let Err(e) = data.rate_limits.save_usage(&connection) {
+            error!(?e, "Saving error!");
+        }
+
         if let Some(s) = &data.emoji_usage_counter {
             if let Err(e) = s.send(EmojiUsageEvent::Terminate).await {
                 error!(?e, "Saving error!");
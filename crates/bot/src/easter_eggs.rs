@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use nanorand::Rng;
+use serenity::model::id::GuildId;
+use utility::{
+    config::{DatabaseHandle, DatabaseOperations},
+    discord::{EasterEgg, EasterEggResponse},
+    here,
+};
+
+/// Fun replies to trigger words, managed per guild by admins and kept in the database.
+/// Built-in eggs apply to every guild until an admin changes or disables them there.
+#[derive(Debug, Default)]
+pub struct EasterEggs {
+    eggs: HashMap<(GuildId, String), EasterEgg>,
+    /// Behind its own lock, so that messages only need to read the eggs to trigger them.
+    last_triggered: Mutex<HashMap<(GuildId, String), DateTime<Utc>>>,
+}
+
+impl EasterEggs {
+    const BUILT_IN_TRIGGERS: &'static [&'static str] = &["ogey"];
+
+    pub fn load(handle: &DatabaseHandle) -> anyhow::Result<Self> {
+        HashMap::<(GuildId, String), EasterEgg>::create_table(handle).context(here!())?;
+
+        Ok(Self {
+            eggs: HashMap::<(GuildId, String), EasterEgg>::load_from_database(handle)
+                .context(here!())?,
+            last_triggered: Mutex::default(),
+        })
+    }
+
+    pub fn save(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        self.eggs.clone().save_to_database(handle).context(here!())
+    }
+
+    /// Triggers are matched against whole messages, ignoring case and surrounding whitespace.
+    pub fn normalize_trigger(trigger: &str) -> String {
+        trigger.trim().to_lowercase()
+    }
+
+    fn built_in(trigger: &str) -> Option<EasterEgg> {
+        let responses = match trigger {
+            "ogey" => vec![EasterEggResponse::Text(
+                "rrat <:pekoSlurp:824792426530734110>".to_owned(),
+            )],
+            _ => return None,
+        };
+
+        Some(EasterEgg {
+            responses,
+            cooldown: Duration::seconds(60),
+            enabled: true,
+        })
+    }
+
+    pub fn get(&self, guild: GuildId, trigger: &str) -> Option<EasterEgg> {
+        self.eggs
+            .get(&(guild, trigger.to_owned()))
+            .cloned()
+            .or_else(|| Self::built_in(trigger))
+    }
+
+    /// Every egg in the guild, including the built-in ones it hasn't changed, sorted by trigger.
+    pub fn list(&self, guild: GuildId) -> Vec<(String, EasterEgg)> {
+        let mut eggs = self
+            .eggs
+            .iter()
+            .filter(|((g, _), _)| *g == guild)
+            .map(|((_, trigger), egg)| (trigger.clone(), egg.clone()))
+            .collect::<Vec<_>>();
+
+        for trigger in Self::BUILT_IN_TRIGGERS {
+            if !self.eggs.contains_key(&(guild, (*trigger).to_owned())) {
+                if let Some(egg) = Self::built_in(trigger) {
+                    eggs.push(((*trigger).to_owned(), egg));
+                }
+            }
+        }
+
+        eggs.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        eggs
+    }
+
+    /// Adds a response to the pool of the trigger, creating the egg if it doesn't exist.
+    pub fn add_response(
+        &mut self,
+        guild: GuildId,
+        trigger: &str,
+        response: EasterEggResponse,
+        cooldown: Option<Duration>,
+    ) {
+        let mut egg = self.get(guild, trigger).unwrap_or(EasterEgg {
+            responses: Vec::new(),
+            cooldown: Duration::seconds(60),
+            enabled: true,
+        });
+
+        egg.responses.push(response);
+
+        if let Some(cooldown) = cooldown {
+            egg.cooldown = cooldown;
+        }
+
+        self.eggs.insert((guild, trigger.to_owned()), egg);
+    }
+
+    /// Removes an egg made in the guild, returning whether it existed.
+    /// Built-in eggs can only be disabled, so removing one just restores its defaults.
+    pub fn remove(&mut self, guild: GuildId, trigger: &str) -> bool {
+        self.last_triggered().remove(&(guild, trigger.to_owned()));
+        self.eggs.remove(&(guild, trigger.to_owned())).is_some()
+    }
+
+    /// Applies a change to an egg in the guild, returning whether the egg exists.
+    pub fn update<F>(&mut self, guild: GuildId, trigger: &str, f: F) -> bool
+    where
+        F: FnOnce(&mut EasterEgg),
+    {
+        match self.get(guild, trigger) {
+            Some(mut egg) => {
+                f(&mut egg);
+                self.eggs.insert((guild, trigger.to_owned()), egg);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks a random response if the message matches an enabled egg that isn't on cooldown.
+    pub fn trigger(
+        &self,
+        guild: GuildId,
+        content: &str,
+        now: DateTime<Utc>,
+    ) -> Option<EasterEggResponse> {
+        let trigger = Self::normalize_trigger(content);
+
+        let egg = self.get(guild, &trigger)?;
+
+        if !egg.enabled || egg.responses.is_empty() {
+            return None;
+        }
+
+        let key = (guild, trigger);
+        let mut last_triggered = self.last_triggered();
+
+        if matches!(last_triggered.get(&key), Some(last) if *last + egg.cooldown > now) {
+            return None;
+        }
+
+        last_triggered.insert(key, now);
+        drop(last_triggered);
+
+        let index = nanorand::tls_rng().generate_range(0..egg.responses.len());
+        egg.responses.into_iter().nth(index)
+    }
+
+    fn last_triggered(&self) -> MutexGuard<'_, HashMap<(GuildId, String), DateTime<Utc>>> {
+        self.last_triggered
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Context;
+use serenity::model::id::{GuildId, UserId};
+use utility::{config::DatabaseHandle, here};
+
+/// An uploaded emote, its image is kept in the object storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emote {
+    pub uploader: UserId,
+    /// The key of the image in the object storage.
+    pub key: String,
+    /// The URL the image is posted with.
+    pub url: String,
+}
+
+/// Images members have uploaded to post by name, kept per guild. The images are uploaded
+/// to the object storage, and only where to find them is kept in the database.
+#[derive(Debug, Default)]
+pub struct Emotes {
+    emotes: HashMap<GuildId, BTreeMap<String, Emote>>,
+}
+
+impl Emotes {
+    const TABLE_NAME: &'static str = "Emotes";
+
+    pub fn load(handle: &DatabaseHandle) -> anyhow::Result<Self> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("guild_id", "INTEGER", Some("NOT NULL")),
+                    ("name", "TEXT", Some("NOT NULL")),
+                    ("added_by", "INTEGER", Some("NOT NULL")),
+                    ("key", "TEXT", Some("NOT NULL")),
+                    ("url", "TEXT", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        let mut emotes: HashMap<GuildId, BTreeMap<String, Emote>> = HashMap::new();
+
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT guild_id, name, added_by, key, url FROM {}",
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            GuildId(row.get(0)?),
+                            row.get::<_, String>(1)?,
+                            Emote {
+                                uploader: UserId(row.get(2)?),
+                                key: row.get(3)?,
+                                url: row.get(4)?,
+                            },
+                        ))
+                    })
+                    .context(here!())?;
+
+                for row in rows {
+                    let (guild, name, emote) = row.context(here!())?;
+                    emotes.entry(guild).or_default().insert(name, emote);
+                }
+            }
+        }
+
+        Ok(Self { emotes })
+    }
+
+    /// Names are matched ignoring case and surrounding whitespace.
+    pub fn normalize_name(name: &str) -> String {
+        name.trim().to_lowercase()
+    }
+
+    /// The names of every emote in the guild, sorted.
+    pub fn names(&self, guild: GuildId) -> Vec<String> {
+        self.emotes
+            .get(&guild)
+            .map(|e| e.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, guild: GuildId, name: &str) -> Option<&Emote> {
+        self.emotes.get(&guild)?.get(name)
+    }
+
+    /// Adds an emote, returning the one it replaced with the same name, if any.
+    pub fn add(
+        &mut self,
+        handle: &DatabaseHandle,
+        guild: GuildId,
+        name: &str,
+        emote: Emote,
+    ) -> anyhow::Result<Option<Emote>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let tx = h.unchecked_transaction().context(here!())?;
+
+                tx.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE guild_id = ? AND name = ?",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![guild.0, name],
+                )
+                .context(here!())?;
+
+                tx.execute(
+                    &format!(
+                        "INSERT INTO {} (guild_id, name, added_by, key, url) VALUES (?, ?, ?, ?, ?)",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![guild.0, name, emote.uploader.0, emote.key, emote.url],
+                )
+                .context(here!())?;
+
+                tx.commit().context(here!())?;
+            }
+        }
+
+        Ok(self
+            .emotes
+            .entry(guild)
+            .or_default()
+            .insert(name.to_owned(), emote))
+    }
+
+    /// Removes an emote, returning it if it existed.
+    pub fn remove(
+        &mut self,
+        handle: &DatabaseHandle,
+        guild: GuildId,
+        name: &str,
+    ) -> anyhow::Result<Option<Emote>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "DELETE FROM {} WHERE guild_id = ? AND name = ?",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![guild.0, name],
+                )
+                .context(here!())?,
+        };
+
+        Ok(self.emotes.get_mut(&guild).and_then(|e| e.remove(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    fn emote(uploader: u64, key: &str) -> Emote {
+        Emote {
+            uploader: UserId(uploader),
+            key: key.to_owned(),
+            url: format!("https://cdn.example.com/{key}"),
+        }
+    }
+
+    #[test]
+    fn replaced_emotes_are_returned() {
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        let mut emotes = Emotes::load(&handle).unwrap();
+
+        let first = emote(1, "emotes/1/10-peko.png");
+        let second = emote(2, "emotes/1/20-peko.png");
+
+        assert_eq!(
+            emotes
+                .add(&handle, GuildId(1), "peko", first.clone())
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            emotes
+                .add(&handle, GuildId(1), "peko", second.clone())
+                .unwrap(),
+            Some(first)
+        );
+
+        let reloaded = Emotes::load(&handle).unwrap();
+        assert_eq!(reloaded.get(GuildId(1), "peko"), Some(&second));
+        assert!(reloaded.get(GuildId(2), "peko").is_none());
+
+        assert_eq!(
+            emotes.remove(&handle, GuildId(1), "peko").unwrap(),
+            Some(second)
+        );
+        assert!(Emotes::load(&handle).unwrap().names(GuildId(1)).is_empty());
+    }
+}
@@ -0,0 +1,640 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use itertools::Itertools;
+use nanorand::Rng;
+use poise::serenity_prelude::{ButtonStyle, InteractionResponseType, MessageComponentInteraction};
+use rusqlite::{OptionalExtension, Row};
+use serenity::{
+    builder::{CreateComponents, CreateEmbed},
+    client::Context as Ctx,
+    model::{
+        id::{ChannelId, GuildId, MessageId, UserId},
+        mention::Mention,
+    },
+};
+use tokio::{sync::Notify, time::sleep};
+use tracing::{error, info, instrument, warn};
+use utility::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+use crate::DataWrapper;
+
+/// A prize members enter by pressing the button on its message, kept in the database
+/// after it ends so that its winners can be rerolled.
+#[derive(Debug, Clone)]
+pub struct Giveaway {
+    pub message: MessageId,
+    pub channel: ChannelId,
+    pub guild: GuildId,
+    pub host: UserId,
+    pub prize: String,
+    pub winner_count: u32,
+    pub ends_at: DateTime<Utc>,
+    /// Everyone who has won, including winners that were rerolled.
+    pub winners: Vec<UserId>,
+    pub ended: bool,
+}
+
+impl Giveaway {
+    pub const ENTER_ID: &'static str = "giveaway:enter";
+
+    const TABLE_NAME: &'static str = "Giveaways";
+    const ENTRANTS_TABLE_NAME: &'static str = "GiveawayEntrants";
+    const COLUMNS: &'static str =
+        "message_id, channel_id, guild_id, host_id, prize, winner_count, ends_at, winners, ended";
+    const IDLE_WAIT: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+    /// How many minutes to wait before trying to end a giveaway or announce its winners again.
+    const RETRY_DELAY_MINUTES: i64 = 5;
+    /// How many times the winners are announced before giving up.
+    const MAX_ATTEMPTS: u32 = 5;
+
+    pub fn create_tables(handle: &DatabaseHandle) -> anyhow::Result<()> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("message_id", "INTEGER", Some("PRIMARY KEY")),
+                    ("channel_id", "INTEGER", Some("NOT NULL")),
+                    ("guild_id", "INTEGER", Some("NOT NULL")),
+                    ("host_id", "INTEGER", Some("NOT NULL")),
+                    ("prize", "TEXT", Some("NOT NULL")),
+                    ("winner_count", "INTEGER", Some("NOT NULL")),
+                    ("ends_at", "INTEGER", Some("NOT NULL")),
+                    ("winners", "TEXT", Some("NOT NULL")),
+                    ("ended", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        handle
+            .create_table(
+                Self::ENTRANTS_TABLE_NAME,
+                &[
+                    ("message_id", "INTEGER", Some("NOT NULL")),
+                    ("user_id", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    pub fn insert(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "INSERT INTO {} ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                        Self::TABLE_NAME,
+                        Self::COLUMNS
+                    ),
+                    rusqlite::params![
+                        self.message.0,
+                        self.channel.0,
+                        self.guild.0,
+                        self.host.0,
+                        self.prize,
+                        self.winner_count,
+                        self.ends_at.timestamp(),
+                        self.winners.iter().map(|u| u.0.to_string()).join(","),
+                        self.ended,
+                    ],
+                )
+                .context(here!())?,
+        };
+
+        Ok(())
+    }
+
+    pub fn get(handle: &DatabaseHandle, message: MessageId) -> anyhow::Result<Option<Self>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM {} WHERE message_id = ?",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ),
+                    [message.0],
+                    Self::from_row,
+                )
+                .optional()
+                .context(here!()),
+        }
+    }
+
+    /// Adds the member to the entrants, returning whether they hadn't already entered.
+    pub fn enter(
+        handle: &DatabaseHandle,
+        message: MessageId,
+        user: UserId,
+    ) -> anyhow::Result<bool> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "INSERT INTO {0} (message_id, user_id) SELECT ?1, ?2 WHERE NOT EXISTS (SELECT 1 FROM {0} WHERE message_id = ?1 AND user_id = ?2)",
+                        Self::ENTRANTS_TABLE_NAME
+                    ),
+                    [message.0, user.0],
+                )
+                .map(|n| n > 0)
+                .context(here!()),
+        }
+    }
+
+    pub fn entrants(&self, handle: &DatabaseHandle) -> anyhow::Result<Vec<UserId>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT user_id FROM {} WHERE message_id = ?",
+                        Self::ENTRANTS_TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let entrants = stmt
+                    .query_map([self.message.0], |row| row.get(0).map(UserId))
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                Ok(entrants)
+            }
+        }
+    }
+
+    /// Picks up to `count` winners among the entrants who haven't won yet and ends the giveaway,
+    /// returning the new winners. They're only stored by [`Self::save_winners`], which is done
+    /// once they've been announced, so that winners nobody was told about are drawn again.
+    pub fn draw_winners(
+        &mut self,
+        handle: &DatabaseHandle,
+        count: u32,
+    ) -> anyhow::Result<Vec<UserId>> {
+        let mut candidates = self
+            .entrants(handle)
+            .context(here!())?
+            .into_iter()
+            .filter(|u| !self.winners.contains(u))
+            .collect::<Vec<_>>();
+
+        nanorand::tls_rng().shuffle(&mut candidates);
+        candidates.truncate(count as usize);
+
+        self.winners.extend(&candidates);
+        self.ended = true;
+
+        Ok(candidates)
+    }
+
+    /// Stores the winners, and whether the giveaway has ended.
+    pub fn save_winners(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "UPDATE {} SET winners = ?, ended = ? WHERE message_id = ?",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![
+                        self.winners.iter().map(|u| u.0.to_string()).join(","),
+                        self.ended,
+                        self.message.0,
+                    ],
+                )
+                .context(here!())?,
+        };
+
+        Ok(())
+    }
+
+    pub fn embed<'a>(&self, embed: &'a mut CreateEmbed, colour: u32) -> &'a mut CreateEmbed {
+        embed
+            .title(&self.prize)
+            .colour(colour)
+            .field("Hosted by", Mention::from(self.host), true);
+
+        if !self.ended {
+            return embed
+                .description(format!(
+                    "Press the button to enter! Ends <t:{}:R>.",
+                    self.ends_at.timestamp()
+                ))
+                .field("Winners", self.winner_count, true);
+        }
+
+        let winners = if self.winners.is_empty() {
+            "Nobody entered.".to_owned()
+        } else {
+            self.winners.iter().map(|u| Mention::from(*u)).join(", ")
+        };
+
+        embed
+            .description(format!("Ended <t:{}:R>.", self.ends_at.timestamp()))
+            .field("Winners", winners, true)
+    }
+
+    pub fn components(&self, components: &mut CreateComponents) -> &mut CreateComponents {
+        if self.ended {
+            return components;
+        }
+
+        components.create_action_row(|r| {
+            r.create_button(|b| {
+                b.style(ButtonStyle::Success)
+                    .label("Enter")
+                    .emoji('🎉')
+                    .custom_id(Self::ENTER_ID)
+            })
+        })
+    }
+
+    /// Updates the giveaway message and congratulates the new winners.
+    pub async fn announce_winners(
+        &self,
+        ctx: &Ctx,
+        winners: &[UserId],
+        colour: u32,
+    ) -> anyhow::Result<()> {
+        self.channel
+            .edit_message(&ctx.http, self.message, |m| {
+                m.embed(|e| self.embed(e, colour))
+                    .components(|c| self.components(c))
+            })
+            .await
+            .context(here!())?;
+
+        let content = if winners.is_empty() {
+            format!("Nobody could be picked as a winner of **{}**.", self.prize)
+        } else {
+            format!(
+                "Congratulations {}, you won **{}**!",
+                winners.iter().map(|u| Mention::from(*u)).join(", "),
+                self.prize
+            )
+        };
+
+        self.channel
+            .send_message(&ctx.http, |m| {
+                m.content(content)
+                    .reference_message((self.channel, self.message))
+                    .allowed_mentions(|am| am.empty_parse().users(winners.to_vec()))
+            })
+            .await
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    fn due(handle: &DatabaseHandle, now: DateTime<Utc>) -> anyhow::Result<Vec<Self>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT {} FROM {} WHERE ended = 0 AND ends_at <= ? ORDER BY ends_at",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let giveaways = stmt
+                    .query_map([now.timestamp()], Self::from_row)
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                Ok(giveaways)
+            }
+        }
+    }
+
+    /// When the next giveaway after `now` ends. The ones that have already ended
+    /// are left out, since they're waiting to be tried again.
+    fn next_due_after(
+        handle: &DatabaseHandle,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let next: Option<i64> = h
+                    .query_row(
+                        &format!(
+                            "SELECT MIN(ends_at) FROM {} WHERE ended = 0 AND ends_at > ?",
+                            Self::TABLE_NAME
+                        ),
+                        [now.timestamp()],
+                        |row| row.get(0),
+                    )
+                    .context(here!())?;
+
+                Ok(next.and_then(|t| Utc.timestamp_opt(t, 0).single()))
+            }
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            message: MessageId(row.get(0)?),
+            channel: ChannelId(row.get(1)?),
+            guild: GuildId(row.get(2)?),
+            host: UserId(row.get(3)?),
+            prize: row.get(4)?,
+            winner_count: row.get(5)?,
+            ends_at: Utc
+                .timestamp_opt(row.get(6)?, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            winners: row
+                .get::<_, String>(7)?
+                .split(',')
+                .filter_map(|u| u.parse().ok().map(UserId))
+                .collect(),
+            ended: row.get(8)?,
+        })
+    }
+}
+
+/// Enters the member who pressed the button into the giveaway, if they have the required role.
+pub async fn handle_entry(
+    ctx: &Ctx,
+    interaction: &MessageComponentInteraction,
+    data: &DataWrapper,
+) -> anyhow::Result<()> {
+    let missing_role = data.config.giveaways.required_role.filter(|role| {
+        !interaction
+            .member
+            .as_ref()
+            .map_or(false, |m| m.roles.contains(role))
+    });
+
+    let content = match missing_role {
+        Some(role) => format!(
+            "Error! You need the {} role to enter giveaways.",
+            Mention::from(role)
+        ),
+        None => {
+            let data = data.data.read().await;
+            let handle = data.database.lock().await;
+
+            match Giveaway::get(&handle, interaction.message.id).context(here!())? {
+                Some(giveaway) if !giveaway.ended && giveaway.ends_at > Utc::now() => {
+                    if Giveaway::enter(&handle, giveaway.message, interaction.user.id)
+                        .context(here!())?
+                    {
+                        format!("You've entered the giveaway for **{}**!", giveaway.prize)
+                    } else {
+                        "You've already entered this giveaway.".to_owned()
+                    }
+                }
+                _ => "Error! This giveaway has ended.".to_owned(),
+            }
+        }
+    };
+
+    interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.ephemeral(true).content(content))
+        })
+        .await
+        .context(here!())?;
+
+    Ok(())
+}
+
+/// Winners the scheduler has drawn, which are saved once they've been announced.
+struct PendingWinners {
+    giveaway: Giveaway,
+    winners: Vec<UserId>,
+    announced: bool,
+    /// How many times announcing the winners has failed.
+    attempts: u32,
+    retry_at: DateTime<Utc>,
+}
+
+/// Picks the winners of giveaways when they end. Commands that start giveaways
+/// wake it up through `changed`, so that it knows when the next one ends.
+///
+/// Failing to draw or announce the winners of one giveaway is logged and tried again later,
+/// so that it doesn't stop the rest.
+#[instrument(skip(ctx, database, changed))]
+pub async fn scheduler(
+    ctx: Ctx,
+    database: &Database,
+    changed: Arc<Notify>,
+    colour: u32,
+) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+    let retry_delay = Duration::minutes(Giveaway::RETRY_DELAY_MINUTES);
+    // Giveaways whose winners couldn't be drawn, and when to try again.
+    let mut failed: HashMap<MessageId, DateTime<Utc>> = HashMap::new();
+    // Winners that have been drawn but not yet announced and saved.
+    let mut pending: Vec<PendingWinners> = Vec::new();
+
+    loop {
+        let now = Utc::now();
+
+        let due = match Giveaway::due(&handle, now) {
+            Ok(due) => due,
+            Err(e) => {
+                error!(?e, "Failed to get the giveaways that have ended!");
+                Vec::new()
+            }
+        };
+
+        // Giveaways that were ended or deleted since don't need to be tried again.
+        failed.retain(|message, _| due.iter().any(|g| g.message == *message));
+
+        for mut giveaway in due {
+            if failed.get(&giveaway.message).map_or(false, |at| *at > now) {
+                continue;
+            }
+
+            // Giveaways stay due until their winners are saved, which waits for the announcement.
+            if pending
+                .iter()
+                .any(|p| p.giveaway.message == giveaway.message)
+            {
+                continue;
+            }
+
+            info!(message = %giveaway.message, prize = %giveaway.prize, "Giveaway ended.");
+
+            match giveaway.draw_winners(&handle, giveaway.winner_count) {
+                Ok(winners) => {
+                    failed.remove(&giveaway.message);
+                    pending.push(PendingWinners {
+                        giveaway,
+                        winners,
+                        announced: false,
+                        attempts: 0,
+                        retry_at: now,
+                    });
+                }
+                Err(e) => {
+                    error!(?e, message = %giveaway.message, "Failed to draw giveaway winners!");
+                    failed.insert(giveaway.message, now + retry_delay);
+                }
+            }
+        }
+
+        let mut waiting = Vec::with_capacity(pending.len());
+
+        for mut p in pending.drain(..) {
+            if p.retry_at > now {
+                waiting.push(p);
+                continue;
+            }
+
+            if !p.announced {
+                match p.giveaway.announce_winners(&ctx, &p.winners, colour).await {
+                    Ok(()) => p.announced = true,
+                    Err(e) => {
+                        p.attempts += 1;
+
+                        if p.attempts < Giveaway::MAX_ATTEMPTS {
+                            warn!(
+                                ?e,
+                                message = %p.giveaway.message,
+                                attempts = p.attempts,
+                                "Failed to announce giveaway winners, trying again later."
+                            );
+                            p.retry_at = now + retry_delay;
+                            waiting.push(p);
+                            continue;
+                        }
+
+                        // Saved anyway, so that the giveaway isn't drawn again forever.
+                        error!(
+                            ?e,
+                            message = %p.giveaway.message,
+                            "Failed to announce giveaway winners, giving up!"
+                        );
+                    }
+                }
+            }
+
+            if let Err(e) = p.giveaway.save_winners(&handle) {
+                error!(?e, message = %p.giveaway.message, "Failed to save giveaway winners!");
+                p.retry_at = now + retry_delay;
+                waiting.push(p);
+            }
+        }
+
+        pending = waiting;
+
+        let next_due = match Giveaway::next_due_after(&handle, now) {
+            Ok(next_due) => next_due,
+            Err(e) => {
+                error!(?e, "Failed to get when the next giveaway ends!");
+                Some(now + retry_delay)
+            }
+        };
+
+        let next_retry = failed
+            .values()
+            .copied()
+            .chain(pending.iter().map(|p| p.retry_at))
+            .min();
+
+        let wait = match next_due.into_iter().chain(next_retry).min() {
+            Some(next) => (next - Utc::now()).to_std().unwrap_or_default(),
+            // Nothing is running, so only a new giveaway can wake it up.
+            None => Giveaway::IDLE_WAIT,
+        };
+
+        tokio::select! {
+            _ = sleep(wait) => {}
+            _ = changed.notified() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    fn giveaway(handle: &DatabaseHandle, entrants: &[u64]) -> Giveaway {
+        let giveaway = Giveaway {
+            message: MessageId(10),
+            channel: ChannelId(20),
+            guild: GuildId(30),
+            host: UserId(1),
+            prize: "Membership".to_owned(),
+            winner_count: 2,
+            ends_at: Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap(),
+            winners: Vec::new(),
+            ended: false,
+        };
+        giveaway.insert(handle).unwrap();
+
+        for user in entrants {
+            Giveaway::enter(handle, giveaway.message, UserId(*user)).unwrap();
+        }
+
+        giveaway
+    }
+
+    #[test]
+    fn members_can_only_enter_once() {
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        Giveaway::create_tables(&handle).unwrap();
+
+        let giveaway = giveaway(&handle, &[2]);
+
+        assert!(!Giveaway::enter(&handle, giveaway.message, UserId(2)).unwrap());
+        assert!(Giveaway::enter(&handle, giveaway.message, UserId(3)).unwrap());
+        assert_eq!(giveaway.entrants(&handle).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn winners_are_only_stored_once_saved() {
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        Giveaway::create_tables(&handle).unwrap();
+
+        let mut giveaway = giveaway(&handle, &[2, 3, 4]);
+        let now = giveaway.ends_at;
+
+        let winners = giveaway.draw_winners(&handle, 2).unwrap();
+        assert_eq!(winners.len(), 2);
+        assert!(winners.iter().all(|u| [2, 3, 4].contains(&u.0)));
+        assert!(giveaway.ended);
+
+        // Until the winners have been announced, a restart draws them again.
+        assert_eq!(Giveaway::due(&handle, now).unwrap().len(), 1);
+
+        giveaway.save_winners(&handle).unwrap();
+        assert!(Giveaway::due(&handle, now).unwrap().is_empty());
+
+        let stored = Giveaway::get(&handle, giveaway.message).unwrap().unwrap();
+        assert!(stored.ended);
+        assert_eq!(stored.winners, winners);
+    }
+
+    #[test]
+    fn rerolls_only_pick_members_who_have_not_won() {
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        Giveaway::create_tables(&handle).unwrap();
+
+        let mut giveaway = giveaway(&handle, &[2, 3, 4]);
+        let first = giveaway.draw_winners(&handle, 2).unwrap();
+        giveaway.save_winners(&handle).unwrap();
+
+        let mut stored = Giveaway::get(&handle, giveaway.message).unwrap().unwrap();
+        let rerolled = stored.draw_winners(&handle, 2).unwrap();
+
+        assert_eq!(rerolled.len(), 1);
+        assert!(!first.contains(&rerolled[0]));
+        assert_eq!(stored.winners.len(), 3);
+
+        // Everyone has won, so there's nobody left to pick.
+        assert!(stored.draw_winners(&handle, 1).unwrap().is_empty());
+    }
+}
@@ -1,7 +1,23 @@
+mod announcements;
+mod command_errors;
+mod command_metrics;
+mod command_registration;
 mod commands;
 mod discord_bot;
+mod easter_eggs;
+mod emotes;
+mod giveaways;
 mod paginated_list;
+mod polls;
+mod preflight;
+mod presence;
+mod rate_limits;
+mod reminders;
 mod resource_tracking;
 mod temp_mute_react;
+#[cfg(feature = "translation")]
+mod translation_requests;
+mod verification;
+mod web_api;
 
 pub use discord_bot::*;
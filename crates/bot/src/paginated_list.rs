@@ -9,12 +9,14 @@ use poise::{
 use serenity::{
     builder::CreateEmbed,
     model::channel::{Message, ReactionType},
-    utils::Colour,
 };
 use tokio::{sync::oneshot, time::Duration};
 use tokio_util::sync::CancellationToken;
 use tracing::error;
-use utility::here;
+use utility::{
+    discord::{retry_discord_request, RequestKind},
+    here,
+};
 
 use crate::commands::Context;
 
@@ -161,16 +163,16 @@ impl<'a, D: std::fmt::Debug> PaginatedList<'a, D> {
         let token = self.token.take().unwrap_or_default();
         let message_sender = self.message_sender.take();
 
-        let mut reply_handle = {
+        let reply_handle = {
             let reply_handle = self
-                .create_page(&data, current_page as usize, required_pages, ctx, None)
+                .send_page(&data, current_page as usize, required_pages, ctx)
                 .await;
 
             match reply_handle {
                 Ok(handle) => handle,
                 Err(err) => {
                     error!("{err:?}");
-                    return Err(anyhow!(err)).context(here!());
+                    return Err(err).context(here!());
                 }
             }
         };
@@ -218,34 +220,30 @@ impl<'a, D: std::fmt::Debug> PaginatedList<'a, D> {
                         None => break,
                     };
 
-                    match page_turn.data.custom_id.as_str() {
-                        "back" => {
-                            current_page -= 1;
-
-                            if current_page < 1 {
-                                current_page = required_pages as i32;
-                            }
-                        }
-                        "forward" => {
-                            current_page += 1;
-
-                            if current_page > required_pages as i32 {
-                                current_page = 1;
-                            }
-                        }
+                    let next_page = match page_turn.data.custom_id.as_str() {
+                        "back" if current_page <= 1 => required_pages as i32,
+                        "back" => current_page - 1,
+                        "forward" if current_page >= required_pages as i32 => 1,
+                        "forward" => current_page + 1,
                         _ => continue,
-                    }
+                    };
 
-                    page_turn.create_interaction_response(&ctx, |r| {
-                        r.kind(InteractionResponseType::DeferredUpdateMessage)
-                    }).await.context(here!())?;
+                    retry_discord_request(RequestKind::InteractionResponse, || {
+                        page_turn.create_interaction_response(&ctx, |r| {
+                            r.kind(InteractionResponseType::DeferredUpdateMessage)
+                        })
+                    })
+                    .await
+                    .context(here!())?;
 
-                    reply_handle = self.create_page(
-                        &data, current_page as usize,
-                        required_pages,
-                        ctx, Some(reply_handle)
-                    )
-                    .await?;
+                    // The list stays on the page that's shown if the next one can't be.
+                    match self
+                        .edit_page(&data, next_page as usize, required_pages, ctx, &reply_handle)
+                        .await
+                    {
+                        Ok(()) => current_page = next_page,
+                        Err(e) => error!("{:?}", e),
+                    }
                 }
             }
         }
@@ -271,145 +269,160 @@ impl<'a, D: std::fmt::Debug> PaginatedList<'a, D> {
         Ok(())
     }
 
-    async fn create_page<'b>(
+    /// Sends the page, retrying if the request is rate limited.
+    async fn send_page<'b>(
         &'b self,
         data: &FormattedData<'b, D>,
         page: usize,
         required_pages: usize,
         ctx: Context<'b>,
-        reply_handle: Option<ReplyHandle<'b>>,
-    ) -> anyhow::Result<poise::ReplyHandle<'b>> {
-        let page = {
-            let mut m = CreateReply::default();
-
-            if required_pages > 1 {
-                m.components(|c| {
-                    c.create_action_row(|r| {
-                        r.create_button(|b| {
-                            b.style(ButtonStyle::Secondary)
-                                .label("Back")
-                                .custom_id("back")
-                                .emoji(ReactionType::Unicode("👈".to_string()))
-                        })
-                        .create_button(|b| {
-                            b.style(ButtonStyle::Secondary)
-                                .label("Forward")
-                                .custom_id("forward")
-                                .emoji(ReactionType::Unicode("👉".to_string()))
-                        })
+    ) -> anyhow::Result<ReplyHandle<'b>> {
+        retry_discord_request(RequestKind::InteractionResponse, || {
+            ctx.send(|r| {
+                *r = self.build_page(data, page, required_pages, ctx);
+                r
+            })
+        })
+        .await
+        .with_context(|| format!("Failed to show page {page} of {required_pages}."))
+    }
+
+    /// Replaces the shown page, retrying if the request is rate limited.
+    async fn edit_page<'b>(
+        &'b self,
+        data: &FormattedData<'b, D>,
+        page: usize,
+        required_pages: usize,
+        ctx: Context<'b>,
+        reply_handle: &ReplyHandle<'_>,
+    ) -> anyhow::Result<()> {
+        retry_discord_request(RequestKind::Idempotent, || {
+            reply_handle.edit(ctx, |r| {
+                *r = self.build_page(data, page, required_pages, ctx);
+                r
+            })
+        })
+        .await
+        .with_context(|| format!("Failed to turn to page {page} of {required_pages}."))
+    }
+
+    fn build_page<'b>(
+        &self,
+        data: &FormattedData<'_, D>,
+        page: usize,
+        required_pages: usize,
+        ctx: Context<'_>,
+    ) -> CreateReply<'b> {
+        let mut m = CreateReply::default();
+
+        if required_pages > 1 {
+            m.components(|c| {
+                c.create_action_row(|r| {
+                    r.create_button(|b| {
+                        b.style(ButtonStyle::Secondary)
+                            .label("Back")
+                            .custom_id("back")
+                            .emoji(ReactionType::Unicode("👈".to_string()))
                     })
-                });
-            }
+                    .create_button(|b| {
+                        b.style(ButtonStyle::Secondary)
+                            .label("Forward")
+                            .custom_id("forward")
+                            .emoji(ReactionType::Unicode("👉".to_string()))
+                    })
+                })
+            });
+        }
 
-            if let Some(func) = &self.embed_func {
-                match (&self.layout, data) {
-                    (PageLayout::Standard { items_per_page }, FormattedData::Standard(d)) => {
-                        let embed_page = d
-                            .iter()
-                            .skip((page - 1) * *items_per_page)
-                            .take(*items_per_page);
-
-                        m.embeds.clear();
-
-                        for embed in embed_page {
-                            m.embed(|m| {
-                                *m = func(embed, &self.params);
-                                m
-                            });
-                        }
+        if let Some(func) = &self.embed_func {
+            match (&self.layout, data) {
+                (PageLayout::Standard { items_per_page }, FormattedData::Standard(d)) => {
+                    let embed_page = d
+                        .iter()
+                        .skip((page - 1) * *items_per_page)
+                        .take(*items_per_page);
+
+                    m.embeds.clear();
+
+                    for embed in embed_page {
+                        m.embed(|m| {
+                            *m = func(embed, &self.params);
+                            m
+                        });
                     }
-                    _ => error!("Invalid layout and data format found!"),
                 }
-            } else {
-                m.embed(|e| {
-                    e.colour(Colour::new(6_282_735));
+                _ => error!("Invalid layout and data format found!"),
+            }
+        } else {
+            m.embed(|e| {
+                e.colour(ctx.data().config.theme.default);
 
-                    if let Some(title) = &self.title {
-                        e.title(title);
-                    }
+                if let Some(title) = &self.title {
+                    e.title(title);
+                }
 
-                    match (&self.layout, data) {
-                        (PageLayout::Standard { items_per_page }, FormattedData::Standard(d)) => {
-                            if let Some(func) = &self.format_func {
-                                e.description(
-                                    d.iter()
-                                        .skip((page - 1) * *items_per_page)
-                                        .take(*items_per_page)
-                                        .fold(String::new(), |mut acc, element| {
-                                            acc += func(element, &self.params).as_str();
-                                            acc
-                                        }),
-                                );
-                            }
-                        }
-                        (
-                            PageLayout::Chunked {
-                                chunk_size,
-                                chunks_per_page,
-                            },
-                            FormattedData::Chunked(d),
-                        ) => {
-                            e.fields(
+                match (&self.layout, data) {
+                    (PageLayout::Standard { items_per_page }, FormattedData::Standard(d)) => {
+                        if let Some(func) = &self.format_func {
+                            e.description(
                                 d.iter()
-                                    .skip((page - 1) * chunks_per_page)
-                                    .take(*chunks_per_page)
-                                    .map(|(i, chunk)| {
-                                        (
-                                            format!(
-                                                "{}-{}",
-                                                i * chunk_size + 1,
-                                                i * chunk_size + chunk.len()
-                                            ),
-                                            chunk.iter().fold(String::new(), |mut acc, element| {
-                                                acc += match &self.format_func {
-                                                    Some(func) => func(element, &self.params),
-                                                    None => format!("{element:?}"),
-                                                }
-                                                .as_str();
-                                                acc
-                                            }),
-                                            true,
-                                        )
+                                    .skip((page - 1) * *items_per_page)
+                                    .take(*items_per_page)
+                                    .fold(String::new(), |mut acc, element| {
+                                        acc += func(element, &self.params).as_str();
+                                        acc
                                     }),
                             );
                         }
-                        _ => error!("Invalid layout and data format found!"),
                     }
-
-                    match self.show_page_count {
-                        ShowPageCount::Always => {
-                            e.footer(|f| f.text(format!("Page {page} of {required_pages}")));
-                        }
-                        ShowPageCount::WhenSeveralPages if required_pages > 1 => {
-                            e.footer(|f| f.text(format!("Page {page} of {required_pages}")));
-                        }
-                        _ => (),
+                    (
+                        PageLayout::Chunked {
+                            chunk_size,
+                            chunks_per_page,
+                        },
+                        FormattedData::Chunked(d),
+                    ) => {
+                        e.fields(
+                            d.iter()
+                                .skip((page - 1) * chunks_per_page)
+                                .take(*chunks_per_page)
+                                .map(|(i, chunk)| {
+                                    (
+                                        format!(
+                                            "{}-{}",
+                                            i * chunk_size + 1,
+                                            i * chunk_size + chunk.len()
+                                        ),
+                                        chunk.iter().fold(String::new(), |mut acc, element| {
+                                            acc += match &self.format_func {
+                                                Some(func) => func(element, &self.params),
+                                                None => format!("{element:?}"),
+                                            }
+                                            .as_str();
+                                            acc
+                                        }),
+                                        true,
+                                    )
+                                }),
+                        );
                     }
-                    e
-                });
-            }
-
-            m
-        };
+                    _ => error!("Invalid layout and data format found!"),
+                }
 
-        match reply_handle {
-            Some(handle) => handle
-                .edit(ctx, |r| {
-                    *r = page;
-                    r
-                })
-                .await
-                .map(|_| handle)
-                .map_err(|e| e.into()),
-
-            None => ctx
-                .send(|r| {
-                    *r = page;
-                    r
-                })
-                .await
-                .map_err(|e| e.into()),
+                match self.show_page_count {
+                    ShowPageCount::Always => {
+                        e.footer(|f| f.text(format!("Page {page} of {required_pages}")));
+                    }
+                    ShowPageCount::WhenSeveralPages if required_pages > 1 => {
+                        e.footer(|f| f.text(format!("Page {page} of {required_pages}")));
+                    }
+                    _ => (),
+                }
+                e
+            });
         }
+
+        m
     }
 }
 
@@ -0,0 +1,739 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use chrono::{DateTime, TimeZone, Utc};
+use poise::{
+    serenity_prelude::{InteractionResponseType, MessageComponentInteraction},
+    ChoiceParameter,
+};
+use rusqlite::{types::Type, OptionalExtension, Row};
+use serenity::{
+    builder::CreateEmbed,
+    client::Context as Ctx,
+    model::id::{ChannelId, GuildId, MessageId, UserId},
+};
+use tokio::{sync::Notify, time::sleep};
+use tracing::{error, info, instrument};
+use utility::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+use crate::DataWrapper;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum VoteMode {
+    #[name = "One vote per member"]
+    Single,
+    #[name = "Members can vote for several options"]
+    Multiple,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum VotingStyle {
+    #[name = "Buttons"]
+    Buttons,
+    #[name = "Select menu"]
+    SelectMenu,
+}
+
+/// A poll that is being voted on, kept in the database until it closes so that
+/// votes keep being counted across restarts.
+#[derive(Debug, Clone)]
+pub struct Poll {
+    pub message: MessageId,
+    pub channel: ChannelId,
+    pub guild: GuildId,
+    pub question: String,
+    pub options: Vec<String>,
+    pub mode: VoteMode,
+    pub author: UserId,
+    pub ends_at: DateTime<Utc>,
+    votes: HashMap<UserId, BTreeSet<usize>>,
+}
+
+impl Poll {
+    pub const MIN_OPTIONS: usize = 2;
+    pub const MAX_OPTIONS: usize = 10;
+    /// Discord doesn't allow longer embed titles.
+    pub const MAX_QUESTION_LENGTH: usize = 256;
+    /// Discord doesn't allow longer button labels.
+    pub const MAX_OPTION_LENGTH: usize = 80;
+
+    pub const ID_PREFIX: &'static str = "poll:";
+    pub const VOTE_PREFIX: &'static str = "poll:vote:";
+    pub const SELECT_ID: &'static str = "poll:select";
+    pub const CLOSE_ID: &'static str = "poll:close";
+
+    const TABLE_NAME: &'static str = "Polls";
+    const VOTES_TABLE_NAME: &'static str = "PollVotes";
+    const RESULTS_TABLE_NAME: &'static str = "PollResults";
+    const COLUMNS: &'static str =
+        "message_id, channel_id, guild_id, author_id, question, options, multiple_votes, ends_at";
+    const IDLE_WAIT: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+    const RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+    const BAR_WIDTH: usize = 12;
+
+    pub fn new(
+        question: String,
+        options: Vec<String>,
+        mode: VoteMode,
+        author: UserId,
+        ends_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            message: MessageId(0),
+            channel: ChannelId(0),
+            guild: GuildId(0),
+            question,
+            options,
+            mode,
+            author,
+            ends_at,
+            votes: HashMap::new(),
+        }
+    }
+
+    /// Checks that the question fits in the title of the poll embed.
+    pub fn check_question(question: &str) -> Result<(), String> {
+        if question.chars().count() > Self::MAX_QUESTION_LENGTH {
+            return Err(format!(
+                "Questions can be at most {} characters long.",
+                Self::MAX_QUESTION_LENGTH
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Splits the options written as `a | b | c`, checking that there are enough of them.
+    pub fn parse_options(options: &str) -> Result<Vec<String>, String> {
+        let options = options
+            .split('|')
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+
+        if !(Self::MIN_OPTIONS..=Self::MAX_OPTIONS).contains(&options.len()) {
+            return Err(format!(
+                "Polls need between {} and {} options, separated by `|`.",
+                Self::MIN_OPTIONS,
+                Self::MAX_OPTIONS
+            ));
+        }
+
+        if options
+            .iter()
+            .any(|o| o.chars().count() > Self::MAX_OPTION_LENGTH)
+        {
+            return Err(format!(
+                "Options can be at most {} characters long.",
+                Self::MAX_OPTION_LENGTH
+            ));
+        }
+
+        Ok(options)
+    }
+
+    /// Votes for the option, or takes the vote back if the member already voted for it.
+    /// In single-vote polls, this replaces the member's previous vote.
+    pub fn toggle_vote(&mut self, user: UserId, option: usize) {
+        if option >= self.options.len() {
+            return;
+        }
+
+        let votes = self.votes.entry(user).or_default();
+
+        if !votes.remove(&option) {
+            if self.mode == VoteMode::Single {
+                votes.clear();
+            }
+
+            votes.insert(option);
+        }
+    }
+
+    /// Replaces the member's votes, as picked in the select menu.
+    pub fn set_votes(&mut self, user: UserId, options: impl IntoIterator<Item = usize>) {
+        let mut options = options
+            .into_iter()
+            .filter(|o| *o < self.options.len())
+            .collect::<BTreeSet<_>>();
+
+        if self.mode == VoteMode::Single {
+            options = options.into_iter().take(1).collect();
+        }
+
+        self.votes.insert(user, options);
+    }
+
+    /// The number of votes for each option.
+    pub fn tally(&self) -> Vec<u64> {
+        let mut tally = vec![0; self.options.len()];
+
+        for option in self.votes.values().flatten() {
+            // Votes for options that don't exist can only come from a corrupted database.
+            if let Some(count) = tally.get_mut(*option) {
+                *count += 1;
+            }
+        }
+
+        tally
+    }
+
+    pub fn voter_count(&self) -> usize {
+        self.votes.values().filter(|v| !v.is_empty()).count()
+    }
+
+    pub fn results_embed<'a>(
+        &self,
+        embed: &'a mut CreateEmbed,
+        colour: u32,
+        closed: bool,
+    ) -> &'a mut CreateEmbed {
+        let tally = self.tally();
+        let total = tally.iter().sum::<u64>();
+
+        embed.title(&self.question).colour(colour).fields(
+            self.options
+                .iter()
+                .zip(&tally)
+                .map(|(option, count)| (option.clone(), bar(*count, total), false)),
+        );
+
+        let voters = self.voter_count();
+
+        if closed {
+            embed.footer(|f| f.text(format!("Closed, {voters} member(s) voted.")))
+        } else {
+            embed
+                .description(format!("Closes <t:{}:R>.", self.ends_at.timestamp()))
+                .footer(|f| f.text(format!("{voters} member(s) voted.")))
+        }
+    }
+
+    pub fn create_tables(handle: &DatabaseHandle) -> anyhow::Result<()> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("message_id", "INTEGER", Some("PRIMARY KEY")),
+                    ("channel_id", "INTEGER", Some("NOT NULL")),
+                    ("guild_id", "INTEGER", Some("NOT NULL")),
+                    ("author_id", "INTEGER", Some("NOT NULL")),
+                    ("question", "TEXT", Some("NOT NULL")),
+                    ("options", "TEXT", Some("NOT NULL")),
+                    ("multiple_votes", "INTEGER", Some("NOT NULL")),
+                    ("ends_at", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        handle
+            .create_table(
+                Self::VOTES_TABLE_NAME,
+                &[
+                    ("message_id", "INTEGER", Some("NOT NULL")),
+                    ("user_id", "INTEGER", Some("NOT NULL")),
+                    ("option", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        handle
+            .create_table(
+                Self::RESULTS_TABLE_NAME,
+                &[
+                    ("message_id", "INTEGER", Some("PRIMARY KEY")),
+                    ("guild_id", "INTEGER", None),
+                    ("channel_id", "INTEGER", Some("NOT NULL")),
+                    ("author_id", "INTEGER", Some("NOT NULL")),
+                    ("question", "TEXT", Some("NOT NULL")),
+                    ("results", "TEXT", Some("NOT NULL")),
+                    ("voters", "INTEGER", Some("NOT NULL")),
+                    ("closed_at", "INTEGER", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    pub fn insert(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "INSERT INTO {} ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                        Self::TABLE_NAME,
+                        Self::COLUMNS
+                    ),
+                    rusqlite::params![
+                        self.message.0,
+                        self.channel.0,
+                        self.guild.0,
+                        self.author.0,
+                        self.question,
+                        serde_json::to_string(&self.options).context(here!())?,
+                        self.mode == VoteMode::Multiple,
+                        self.ends_at.timestamp(),
+                    ],
+                )
+                .context(here!())?,
+        };
+
+        Ok(())
+    }
+
+    /// Gets the poll posted in the message, with its votes, if it's still open.
+    pub fn get(handle: &DatabaseHandle, message: MessageId) -> anyhow::Result<Option<Self>> {
+        let poll = match handle {
+            DatabaseHandle::SQLite(h) => h
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM {} WHERE message_id = ?",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ),
+                    [message.0],
+                    Self::from_row,
+                )
+                .optional()
+                .context(here!())?,
+        };
+
+        match poll {
+            Some(mut poll) => {
+                poll.load_votes(handle).context(here!())?;
+                Ok(Some(poll))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Stores the member's current votes, replacing the ones stored before.
+    pub fn save_votes(&self, handle: &DatabaseHandle, user: UserId) -> anyhow::Result<()> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE message_id = ? AND user_id = ?",
+                        Self::VOTES_TABLE_NAME
+                    ),
+                    [self.message.0, user.0],
+                )
+                .context(here!())?;
+
+                let mut stmt = h
+                    .prepare(&format!(
+                        "INSERT INTO {} (message_id, user_id, option) VALUES (?, ?, ?)",
+                        Self::VOTES_TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                for option in self.votes.get(&user).into_iter().flatten() {
+                    stmt.execute(rusqlite::params![self.message.0, user.0, option])
+                        .context(here!())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores the final results and removes the poll and its votes, so that they're kept
+    /// after the poll message is gone.
+    pub fn close(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        let results = self
+            .options
+            .iter()
+            .zip(self.tally())
+            .map(|(option, count)| format!("{option}: {count}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO {} (message_id, guild_id, channel_id, author_id, question, results, voters, closed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                        Self::RESULTS_TABLE_NAME
+                    ),
+                    rusqlite::params![
+                        self.message.0,
+                        self.guild.0,
+                        self.channel.0,
+                        self.author.0,
+                        self.question,
+                        results,
+                        self.voter_count(),
+                        Utc::now().timestamp(),
+                    ],
+                )
+                .context(here!())?;
+
+                for table in [Self::TABLE_NAME, Self::VOTES_TABLE_NAME] {
+                    h.execute(
+                        &format!("DELETE FROM {table} WHERE message_id = ?"),
+                        [self.message.0],
+                    )
+                    .context(here!())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_votes(&mut self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT user_id, option FROM {} WHERE message_id = ?",
+                        Self::VOTES_TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let votes = stmt
+                    .query_map([self.message.0], |row| {
+                        Ok((UserId(row.get(0)?), row.get::<_, usize>(1)?))
+                    })
+                    .context(here!())?;
+
+                for vote in votes {
+                    let (user, option) = vote.context(here!())?;
+                    self.votes.entry(user).or_default().insert(option);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn due(handle: &DatabaseHandle, now: DateTime<Utc>) -> anyhow::Result<Vec<Self>> {
+        let mut polls = match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT {} FROM {} WHERE ends_at <= ? ORDER BY ends_at",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                stmt.query_map([now.timestamp()], Self::from_row)
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?
+            }
+        };
+
+        for poll in &mut polls {
+            poll.load_votes(handle).context(here!())?;
+        }
+
+        Ok(polls)
+    }
+
+    fn next_due_at(handle: &DatabaseHandle) -> anyhow::Result<Option<DateTime<Utc>>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let next: Option<i64> = h
+                    .query_row(
+                        &format!("SELECT MIN(ends_at) FROM {}", Self::TABLE_NAME),
+                        [],
+                        |row| row.get(0),
+                    )
+                    .context(here!())?;
+
+                Ok(next.and_then(|t| Utc.timestamp_opt(t, 0).single()))
+            }
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            message: MessageId(row.get(0)?),
+            channel: ChannelId(row.get(1)?),
+            guild: GuildId(row.get(2)?),
+            author: UserId(row.get(3)?),
+            question: row.get(4)?,
+            options: serde_json::from_str(&row.get::<_, String>(5)?).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
+            })?,
+            mode: if row.get(6)? {
+                VoteMode::Multiple
+            } else {
+                VoteMode::Single
+            },
+            ends_at: Utc
+                .timestamp_opt(row.get(7)?, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            votes: HashMap::new(),
+        })
+    }
+}
+
+/// Renders a share of the total as a bar, ex. `████░░░░░░░░ 33% (2)`.
+pub(crate) fn bar(count: u64, total: u64) -> String {
+    let share = if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    };
+
+    let filled = (share * Poll::BAR_WIDTH as f64).round() as usize;
+
+    format!(
+        "{}{} {:.0}% ({count})",
+        "█".repeat(filled),
+        "░".repeat(Poll::BAR_WIDTH - filled),
+        share * 100.0
+    )
+}
+
+/// Counts the vote, or closes the poll if its creator or a moderator pressed the close button.
+pub async fn handle_interaction(
+    ctx: &Ctx,
+    interaction: &MessageComponentInteraction,
+    data: &DataWrapper,
+) -> anyhow::Result<()> {
+    let user = interaction.user.id;
+
+    let outcome = {
+        let data = data.data.read().await;
+        let handle = data.database.lock().await;
+
+        match Poll::get(&handle, interaction.message.id).context(here!())? {
+            None => Err("Error! This poll has closed."),
+            Some(mut poll) => match interaction.data.custom_id.as_str() {
+                Poll::CLOSE_ID if user == poll.author || can_manage(interaction) => {
+                    poll.close(&handle).context(here!())?;
+                    Ok((poll, true))
+                }
+                Poll::CLOSE_ID => Err(CLOSE_DENIED),
+                id => {
+                    if id == Poll::SELECT_ID {
+                        let options = interaction
+                            .data
+                            .values
+                            .iter()
+                            .filter_map(|v| v.parse().ok());
+                        poll.set_votes(user, options);
+                    } else if let Some(option) = id
+                        .strip_prefix(Poll::VOTE_PREFIX)
+                        .and_then(|i| i.parse().ok())
+                    {
+                        poll.toggle_vote(user, option);
+                    } else {
+                        return Ok(());
+                    }
+
+                    poll.save_votes(&handle, user).context(here!())?;
+                    Ok((poll, false))
+                }
+            },
+        }
+    };
+
+    let colour = data.config.theme.default;
+
+    interaction
+        .create_interaction_response(&ctx.http, |r| match &outcome {
+            Ok((poll, closed)) => r
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| {
+                    d.embed(|e| poll.results_embed(e, colour, *closed));
+
+                    if *closed {
+                        d.components(|c| c);
+                    }
+
+                    d
+                }),
+            Err(content) => r
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.ephemeral(true).content(content)),
+        })
+        .await
+        .context(here!())?;
+
+    Ok(())
+}
+
+/// Closes polls when they end. The poll command wakes it up through `changed`,
+/// so that it knows when the next one ends.
+#[instrument(skip(ctx, database, changed))]
+pub async fn scheduler(
+    ctx: Ctx,
+    database: &Database,
+    changed: Arc<Notify>,
+    colour: u32,
+) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+
+    loop {
+        // Polls that fail to close stay due, so they're only tried again after a delay.
+        let mut failed = false;
+
+        let due = match Poll::due(&handle, Utc::now()) {
+            Ok(due) => due,
+            Err(e) => {
+                error!(?e, "Failed to get the polls that have ended!");
+                failed = true;
+                Vec::new()
+            }
+        };
+
+        for poll in due {
+            // Closed first, so that a message that can't be edited doesn't keep it open.
+            if let Err(e) = poll.close(&handle) {
+                error!(?e, message = %poll.message, "Failed to close poll!");
+                failed = true;
+                continue;
+            }
+
+            info!(message = %poll.message, question = %poll.question, "Poll closed.");
+
+            if let Err(e) = poll
+                .channel
+                .edit_message(&ctx.http, poll.message, |m| {
+                    m.embed(|e| poll.results_embed(e, colour, true))
+                        .components(|c| c)
+                })
+                .await
+            {
+                error!(?e, message = %poll.message, "Failed to show the poll as closed!");
+            }
+        }
+
+        let wait = match Poll::next_due_at(&handle) {
+            Ok(Some(next)) => (next - Utc::now()).to_std().unwrap_or_default(),
+            // Nothing is open, so only a new poll can wake it up.
+            Ok(None) => Poll::IDLE_WAIT,
+            Err(e) => {
+                error!(?e, "Failed to get when the next poll ends!");
+                failed = true;
+                Poll::RETRY_DELAY
+            }
+        };
+
+        let wait = if failed {
+            wait.max(Poll::RETRY_DELAY)
+        } else {
+            wait
+        };
+
+        tokio::select! {
+            _ = sleep(wait) => {}
+            _ = changed.notified() => {}
+        }
+    }
+}
+
+const CLOSE_DENIED: &str = "Error! Only the creator of the poll or a moderator can close it.";
+
+fn can_manage(interaction: &MessageComponentInteraction) -> bool {
+    interaction
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .map_or(false, |p| p.manage_messages())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    #[test]
+    fn votes_are_kept_until_the_poll_closes() {
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        Poll::create_tables(&handle).unwrap();
+
+        let mut poll = Poll::new(
+            "Who's the best?".to_owned(),
+            vec!["Pekora".to_owned(), "Miko".to_owned()],
+            VoteMode::Single,
+            UserId(1),
+            Utc::now(),
+        );
+        poll.message = MessageId(10);
+        poll.insert(&handle).unwrap();
+
+        poll.toggle_vote(UserId(2), 0);
+        poll.save_votes(&handle, UserId(2)).unwrap();
+        poll.toggle_vote(UserId(2), 1);
+        poll.save_votes(&handle, UserId(2)).unwrap();
+        poll.toggle_vote(UserId(3), 1);
+        poll.save_votes(&handle, UserId(3)).unwrap();
+
+        let stored = Poll::get(&handle, poll.message).unwrap().unwrap();
+        assert_eq!(stored.tally(), vec![0, 2]);
+        assert_eq!(stored.voter_count(), 2);
+
+        let due = Poll::due(&handle, Utc::now()).unwrap();
+        assert_eq!(due.len(), 1);
+
+        due[0].close(&handle).unwrap();
+        assert!(Poll::get(&handle, poll.message).unwrap().is_none());
+        assert!(Poll::next_due_at(&handle).unwrap().is_none());
+    }
+
+    #[test]
+    fn corrupted_polls_are_not_read_as_empty() {
+        let handle = DatabaseHandle::SQLite(Connection::open_in_memory().unwrap());
+        Poll::create_tables(&handle).unwrap();
+
+        let mut poll = Poll::new(
+            "Who's the best?".to_owned(),
+            vec!["Pekora".to_owned(), "Miko".to_owned()],
+            VoteMode::Single,
+            UserId(1),
+            Utc::now(),
+        );
+        poll.message = MessageId(10);
+        poll.insert(&handle).unwrap();
+
+        let DatabaseHandle::SQLite(h) = &handle;
+        h.execute(
+            &format!(
+                "INSERT INTO {} (message_id, user_id, option) VALUES (10, 2, 7)",
+                Poll::VOTES_TABLE_NAME
+            ),
+            [],
+        )
+        .unwrap();
+
+        let stored = Poll::get(&handle, poll.message).unwrap().unwrap();
+        assert_eq!(stored.tally(), vec![0, 0]);
+
+        h.execute(
+            &format!("UPDATE {} SET options = 'Pekora'", Poll::TABLE_NAME),
+            [],
+        )
+        .unwrap();
+
+        assert!(Poll::get(&handle, poll.message).is_err());
+        assert!(Poll::due(&handle, Utc::now()).is_err());
+    }
+
+    #[test]
+    fn long_questions_are_rejected() {
+        assert!(Poll::check_question(&"a".repeat(Poll::MAX_QUESTION_LENGTH)).is_ok());
+        assert!(Poll::check_question(&"a".repeat(Poll::MAX_QUESTION_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn bars_show_the_share_of_the_total() {
+        assert_eq!(bar(1, 3), "████░░░░░░░░ 33% (1)");
+        assert_eq!(bar(0, 0), "░░░░░░░░░░░░ 0% (0)");
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Context;
+use serenity::{
+    client::Context as Ctx,
+    model::{id::ChannelId, mention::Mention, Permissions},
+};
+use tracing::{info, instrument, warn};
+use unicode_truncate::UnicodeTruncateStr;
+use utility::{
+    config::{Config, StreamChatMode},
+    here,
+};
+
+/// Whether the check has run, since the cache can become ready again after reconnecting.
+static HAS_RUN: AtomicBool = AtomicBool::new(false);
+
+const MAX_FIELD_LENGTH: usize = 1024;
+const MAX_FIELDS: usize = 25;
+/// Discord allows 6000 characters per embed, some are left for the title and description.
+const MAX_EMBED_LENGTH: usize = 5500;
+
+/// The permissions a feature needs in one of the channels it's configured with.
+struct Requirement {
+    feature: &'static str,
+    channel: ChannelId,
+    permissions: Permissions,
+}
+
+/// Checks that the bot has the permissions every enabled feature needs in the channels
+/// they're configured with, and posts the ones that are missing to the audit log channel.
+/// They're posted there even if the audit log is disabled, since that's where admins look.
+/// Only the first call does anything.
+#[instrument(skip(ctx, config))]
+pub async fn run(ctx: &Ctx, config: &Config) -> anyhow::Result<()> {
+    if HAS_RUN.swap(true, Ordering::AcqRel) {
+        return Ok(());
+    }
+
+    let bot = ctx.cache.current_user_id();
+    let mut problems = BTreeMap::<&str, Vec<String>>::new();
+
+    for requirement in requirements(config) {
+        let problem = match ctx.cache.guild_channel(requirement.channel) {
+            Some(channel) => match channel.permissions_for_user(&ctx.cache, bot) {
+                Ok(granted) => {
+                    let missing = requirement.permissions - granted;
+
+                    if missing.is_empty() {
+                        continue;
+                    }
+
+                    format!(
+                        "{}: missing {}",
+                        Mention::from(channel.id),
+                        missing.get_permission_names().join(", ")
+                    )
+                }
+                Err(e) => format!(
+                    "{}: permissions couldn't be checked ({e})",
+                    Mention::from(channel.id)
+                ),
+            },
+            None => format!("`{}`: channel not found", requirement.channel),
+        };
+
+        warn!(feature = requirement.feature, %problem, "Missing permissions!");
+        problems
+            .entry(requirement.feature)
+            .or_default()
+            .push(problem);
+    }
+
+    if problems.is_empty() {
+        info!("Every configured channel has the permissions it needs.");
+        return Ok(());
+    }
+
+    let channel = config.audit_log.channel;
+
+    if channel.0 == 0 {
+        return Ok(());
+    }
+
+    for fields in embeds(fields(&problems)) {
+        channel
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.title("Missing permissions")
+                        .description(
+                            "Some features won't work until the bot is given these permissions.",
+                        )
+                        .colour(config.theme.default)
+                        .fields(fields.into_iter().map(|(name, value)| (name, value, false)))
+                })
+            })
+            .await
+            .context(here!())?;
+    }
+
+    Ok(())
+}
+
+/// One field per feature, split into as many as needed to fit in Discord's field limit.
+fn fields(problems: &BTreeMap<&str, Vec<String>>) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+
+    for (feature, lines) in problems {
+        let mut value = String::new();
+
+        for line in lines {
+            let (line, _) = line.unicode_truncate(MAX_FIELD_LENGTH);
+
+            if !value.is_empty() && value.len() + 1 + line.len() > MAX_FIELD_LENGTH {
+                fields.push(((*feature).to_owned(), std::mem::take(&mut value)));
+            }
+
+            if !value.is_empty() {
+                value.push('\n');
+            }
+
+            value.push_str(line);
+        }
+
+        fields.push(((*feature).to_owned(), value));
+    }
+
+    fields
+}
+
+/// Groups the fields into as many embeds as needed to fit in Discord's embed limits.
+fn embeds(fields: Vec<(String, String)>) -> Vec<Vec<(String, String)>> {
+    let mut embeds = Vec::new();
+    let mut embed: Vec<(String, String)> = Vec::new();
+    let mut length = 0;
+
+    for field in fields {
+        let field_length = field.0.len() + field.1.len();
+
+        if !embed.is_empty()
+            && (embed.len() == MAX_FIELDS || length + field_length > MAX_EMBED_LENGTH)
+        {
+            embeds.push(std::mem::take(&mut embed));
+            length = 0;
+        }
+
+        length += field_length;
+        embed.push(field);
+    }
+
+    if !embed.is_empty() {
+        embeds.push(embed);
+    }
+
+    embeds
+}
+
+fn requirements(config: &Config) -> Vec<Requirement> {
+    let post = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS;
+    let ping = post | Permissions::MENTION_EVERYONE;
+
+    let mut requirements = Vec::new();
+    let mut require = |feature, channel, permissions| {
+        requirements.push(Requirement {
+            feature,
+            channel,
+            permissions,
+        });
+    };
+
+    let streams = &config.stream_tracking;
+
+    if streams.enabled {
+        if streams.alerts.enabled {
+            let mut permissions = ping;
+
+            // Chats are threads on the alert messages.
+            if streams.chat.enabled && streams.chat.mode == StreamChatMode::Threads {
+                permissions |= Permissions::CREATE_PUBLIC_THREADS
+                    | Permissions::SEND_MESSAGES_IN_THREADS
+                    | Permissions::MANAGE_THREADS;
+            }
+
+            require("Stream alerts", streams.alerts.channel, permissions);
+
+            if let Some(channel) = streams.alerts.members_only.channel {
+                if streams.alerts.members_only.enabled {
+                    require("Member-only stream alerts", channel, permissions);
+                }
+            }
+        }
+
+        if streams.chat.enabled {
+            if streams.chat.mode == StreamChatMode::Channels {
+                require(
+                    "Stream chats",
+                    streams.chat.category,
+                    post | Permissions::MANAGE_CHANNELS
+                        | Permissions::MANAGE_ROLES
+                        | Permissions::MANAGE_MESSAGES
+                        | Permissions::READ_MESSAGE_HISTORY,
+                );
+            }
+
+            if let Some(channel) = streams.chat.logging_channel {
+                require(
+                    "Stream chat archives",
+                    channel,
+                    post | Permissions::ATTACH_FILES,
+                );
+            }
+
+            for channel in streams.chat.post_stream_discussion.values() {
+                require("Post-stream discussions", *channel, post);
+            }
+        }
+
+        if streams.schedule_digest.enabled {
+            for channel in streams.schedule_digest.channels.values() {
+                require("Schedule digests", *channel, post);
+            }
+        }
+
+        if streams.status_board.enabled {
+            for channel in &streams.status_board.channels {
+                require(
+                    "Status board",
+                    *channel,
+                    post | Permissions::READ_MESSAGE_HISTORY,
+                );
+            }
+        }
+    }
+
+    if config.birthday_alerts.enabled {
+        require("Birthday alerts", config.birthday_alerts.channel, ping);
+    }
+
+    if config.support.enabled && config.support.kofi.enabled {
+        require("Ko-fi donations", config.support.kofi.channel, post);
+    }
+
+    let twitter = &config.twitter;
+
+    if twitter.enabled {
+        let mut permissions = post | Permissions::ATTACH_FILES;
+
+        if twitter.webhook_identities {
+            permissions |= Permissions::MANAGE_WEBHOOKS;
+        }
+
+        let feeds = twitter
+            .feeds
+            .values()
+            .flat_map(|f| f.values())
+            .chain(twitter.feed_overrides.values());
+
+        for channel in feeds {
+            require("Twitter feeds", *channel, permissions);
+        }
+
+        if twitter.schedule_updates.enabled {
+            require("Schedule updates", twitter.schedule_updates.channel, ping);
+        }
+    }
+
+    if config.verification.enabled {
+        require("Verification", config.verification.channel, post);
+    }
+
+    if config.react_temp_mute.enabled {
+        if let Some(channel) = config.react_temp_mute.logging_channel {
+            require("Reaction mutes", channel, post);
+        }
+    }
+
+    if config.content_filtering.enabled {
+        require(
+            "Content filtering",
+            config.content_filtering.logging_channel,
+            post | Permissions::ATTACH_FILES,
+        );
+    }
+
+    if config.audit_log.enabled {
+        require("Audit log", config.audit_log.channel, post);
+    }
+
+    requirements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_features_are_split_into_several_fields() {
+        let line = "x".repeat(300);
+        let problems = BTreeMap::from([
+            ("Twitter feeds", vec![line; 6]),
+            ("Verification", vec!["y".repeat(2000)]),
+        ]);
+
+        let fields = fields(&problems);
+
+        assert_eq!(fields.len(), 3);
+        assert!(fields.iter().all(|(_, v)| v.len() <= MAX_FIELD_LENGTH));
+        assert_eq!(fields[0].1.lines().count(), 3);
+        assert_eq!(fields[1].1.lines().count(), 3);
+        assert_eq!(fields[2].0, "Verification");
+    }
+
+    #[test]
+    fn fields_are_split_into_several_embeds() {
+        let short = vec![("Feature".to_owned(), "Missing".to_owned()); MAX_FIELDS + 1];
+        assert_eq!(
+            embeds(short).iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![25, 1]
+        );
+
+        let long = vec![("Feature".to_owned(), "x".repeat(MAX_FIELD_LENGTH)); 6];
+        assert_eq!(
+            embeds(long).iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![5, 1]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use holodex::model::{id::VideoId, VideoStatus};
+use serenity::{client::Context as Ctx, model::gateway::Activity};
+use tokio::{select, sync::watch};
+use tracing::{instrument, trace};
+use utility::{
+    config::{Placeholder, PresenceConfig},
+    functions::format_duration,
+    streams::Livestream,
+};
+
+/// Cycles the bot's activity through the statuses that currently apply,
+/// moving on to the next one every `config.interval`.
+#[instrument(skip(ctx, stream_index))]
+pub async fn manager(
+    ctx: Ctx,
+    config: PresenceConfig,
+    mut stream_index: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
+) -> anyhow::Result<()> {
+    let interval = config.interval.to_std()?;
+    let mut ticker = tokio::time::interval(interval);
+    let mut position = 0;
+    let mut current = None;
+
+    loop {
+        // Updates refresh the status that's shown, ticks move on to the next one.
+        let advance = select! {
+            _ = ticker.tick() => true,
+            changed = index_changed(&mut stream_index) => {
+                if !changed {
+                    stream_index = None;
+                }
+
+                false
+            }
+        };
+
+        let activities = match &stream_index {
+            Some(index) => activities(&config, &index.borrow()),
+            None => Vec::new(),
+        };
+
+        if activities.is_empty() {
+            if current.take().is_some() {
+                ctx.reset_presence().await;
+            }
+
+            continue;
+        }
+
+        if advance {
+            position += 1;
+        }
+
+        position %= activities.len();
+        let activity = activities.into_iter().nth(position).unwrap();
+
+        if current.as_ref() == Some(&activity) {
+            continue;
+        }
+
+        trace!(?activity, "Updating presence.");
+        ctx.set_activity(Activity::watching(&activity)).await;
+
+        current = Some(activity);
+    }
+}
+
+/// Waits for the stream index to change, returning false if it has shut down.
+async fn index_changed(
+    stream_index: &mut Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
+) -> bool {
+    match stream_index {
+        Some(index) => index.changed().await.is_ok(),
+        None => std::future::pending().await,
+    }
+}
+
+/// What the bot can be shown watching, in the order it cycles through them.
+fn activities(config: &PresenceConfig, streams: &HashMap<VideoId, Livestream>) -> Vec<String> {
+    let templates = &config.templates;
+    let mut activities = Vec::new();
+
+    let live = streams
+        .values()
+        .filter(|s| s.state == VideoStatus::Live)
+        .count();
+
+    if live > 0 {
+        activities.push(templates.live.render(|p| match p {
+            Placeholder::Count => live.to_string(),
+            _ => String::new(),
+        }));
+    }
+
+    let now = Utc::now();
+
+    let next = streams
+        .values()
+        .filter(|s| s.state == VideoStatus::Upcoming && s.start_at > now)
+        .min_by_key(|s| s.start_at);
+
+    if let Some(next) = next {
+        activities.push(templates.upcoming.render(|p| {
+            match p {
+                Placeholder::Talent => next.streamer.name.clone(),
+                Placeholder::Talents => next
+                    .participants()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Placeholder::Title => next.title.clone(),
+                Placeholder::Url => next.url.clone(),
+                Placeholder::Count => next.participants().count().to_string(),
+                Placeholder::StartsIn => {
+                    format_duration((next.start_at - now).to_std().unwrap_or_default())
+                }
+                Placeholder::StartRelative | Placeholder::Pings => String::new(),
+            }
+        }));
+    }
+
+    activities
+}
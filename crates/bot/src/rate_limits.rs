@@ -0,0 +1,163 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serenity::model::id::{GuildId, RoleId, UserId};
+use utility::{
+    config::{DatabaseHandle, DatabaseOperations},
+    discord::{CommandRateLimit, CommandUse},
+    here,
+};
+
+/// Per-guild command rate limits set by admins, kept in the database so that
+/// both the limits and how much members have used them survive restarts.
+#[derive(Debug, Default)]
+pub struct RateLimits {
+    limits: HashMap<(GuildId, String), CommandRateLimit>,
+    bypass_roles: HashMap<GuildId, RoleId>,
+    usage: HashMap<(GuildId, UserId, String), VecDeque<DateTime<Utc>>>,
+}
+
+impl RateLimits {
+    pub fn load(handle: &DatabaseHandle) -> anyhow::Result<Self> {
+        HashMap::<(GuildId, String), CommandRateLimit>::create_table(handle).context(here!())?;
+        HashMap::<GuildId, RoleId>::create_table(handle).context(here!())?;
+        Vec::<CommandUse>::create_table(handle).context(here!())?;
+
+        let limits = HashMap::<(GuildId, String), CommandRateLimit>::load_from_database(handle)
+            .context(here!())?;
+        let bypass_roles =
+            HashMap::<GuildId, RoleId>::load_from_database(handle).context(here!())?;
+
+        let mut usage: HashMap<_, VecDeque<_>> = HashMap::new();
+
+        for command_use in Vec::<CommandUse>::load_from_database(handle).context(here!())? {
+            usage
+                .entry((command_use.guild, command_use.user, command_use.command))
+                .or_default()
+                .push_back(command_use.used_at);
+        }
+
+        for uses in usage.values_mut() {
+            uses.make_contiguous().sort_unstable();
+        }
+
+        Ok(Self {
+            limits,
+            bypass_roles,
+            usage,
+        })
+    }
+
+    /// Saves the limits and bypass roles.
+    pub fn save_settings(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        self.limits
+            .clone()
+            .save_to_database(handle)
+            .context(here!())?;
+
+        self.bypass_roles
+            .clone()
+            .save_to_database(handle)
+            .context(here!())
+    }
+
+    /// Saves the command uses that still count towards a limit.
+    pub fn save_usage(&self, handle: &DatabaseHandle) -> anyhow::Result<()> {
+        let now = Utc::now();
+
+        self.usage
+            .iter()
+            .filter_map(|((guild, user, command), uses)| {
+                let limit = self.limits.get(&(*guild, command.clone()))?;
+
+                Some(
+                    uses.iter()
+                        .filter(move |used_at| **used_at + limit.period > now)
+                        .map(|used_at| CommandUse {
+                            guild: *guild,
+                            user: *user,
+                            command: command.clone(),
+                            used_at: *used_at,
+                        }),
+                )
+            })
+            .flatten()
+            .collect::<Vec<_>>()
+            .save_to_database(handle)
+            .context(here!())
+    }
+
+    pub fn limits(&self, guild: GuildId) -> impl Iterator<Item = (&str, &CommandRateLimit)> {
+        self.limits
+            .iter()
+            .filter(move |((g, _), _)| *g == guild)
+            .map(|((_, command), limit)| (command.as_str(), limit))
+    }
+
+    pub fn bypass_role(&self, guild: GuildId) -> Option<RoleId> {
+        self.bypass_roles.get(&guild).copied()
+    }
+
+    pub fn set_limit(&mut self, guild: GuildId, command: &str, limit: Option<CommandRateLimit>) {
+        let key = (guild, command.to_owned());
+
+        match limit {
+            Some(limit) => {
+                self.limits.insert(key, limit);
+            }
+            None => {
+                self.limits.remove(&key);
+            }
+        }
+    }
+
+    pub fn set_bypass_role(&mut self, guild: GuildId, role: Option<RoleId>) {
+        match role {
+            Some(role) => {
+                self.bypass_roles.insert(guild, role);
+            }
+            None => {
+                self.bypass_roles.remove(&guild);
+            }
+        }
+    }
+
+    /// Records a use of the command, unless the member is rate limited,
+    /// in which case the time they can use it again is returned.
+    pub fn try_use(
+        &mut self,
+        guild: GuildId,
+        user: UserId,
+        roles: &[RoleId],
+        command: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), DateTime<Utc>> {
+        let limit = match self.limits.get(&(guild, command.to_owned())) {
+            Some(limit) => *limit,
+            None => return Ok(()),
+        };
+
+        if matches!(self.bypass_roles.get(&guild), Some(role) if roles.contains(role)) {
+            return Ok(());
+        }
+
+        let uses = self
+            .usage
+            .entry((guild, user, command.to_owned()))
+            .or_default();
+
+        while matches!(uses.front(), Some(used_at) if *used_at + limit.period <= now) {
+            uses.pop_front();
+        }
+
+        if uses.len() >= limit.uses as usize {
+            return Err(uses
+                .front()
+                .map_or(now + limit.period, |used_at| *used_at + limit.period));
+        }
+
+        uses.push_back(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,882 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Months, TimeZone, Utc};
+use poise::{
+    serenity_prelude::{ButtonStyle, InteractionResponseType, MessageComponentInteraction},
+    ChoiceParameter,
+};
+use rusqlite::{OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serenity::{
+    builder::CreateComponents,
+    client::Context as Ctx,
+    model::{
+        id::{ChannelId, UserId},
+        mention::Mention,
+    },
+};
+use tokio::{sync::Notify, time::sleep};
+use tracing::{error, info, instrument};
+use utility::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+use crate::DataWrapper;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ChoiceParameter)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderFrequency {
+    #[name = "Once"]
+    Once,
+    #[name = "Daily"]
+    Daily,
+    #[name = "Weekly"]
+    Weekly,
+    #[name = "Monthly"]
+    Monthly,
+    #[name = "Yearly"]
+    Yearly,
+}
+
+impl ReminderFrequency {
+    /// When the reminder should go off next, if it repeats.
+    pub fn next_after(self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Once => None,
+            Self::Daily => Some(time + Duration::days(1)),
+            Self::Weekly => Some(time + Duration::weeks(1)),
+            Self::Monthly => time.checked_add_months(Months::new(1)),
+            Self::Yearly => time.checked_add_months(Months::new(12)),
+        }
+    }
+
+    /// The first time the reminder should go off after `now`, skipping the times
+    /// that have already passed, or `None` if it doesn't repeat and `time` has passed.
+    pub fn next_from(self, time: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut next_at = Some(time);
+
+        while let Some(time) = next_at.filter(|t| *t <= now) {
+            next_at = self.next_after(time);
+        }
+
+        next_at
+    }
+
+    fn as_i64(self) -> i64 {
+        match self {
+            Self::Once => 0,
+            Self::Daily => 1,
+            Self::Weekly => 2,
+            Self::Monthly => 3,
+            Self::Yearly => 4,
+        }
+    }
+
+    fn from_i64(value: i64) -> Self {
+        match value {
+            1 => Self::Daily,
+            2 => Self::Weekly,
+            3 => Self::Monthly,
+            4 => Self::Yearly,
+            _ => Self::Once,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum ReminderLocationOption {
+    #[name = "DM"]
+    DM,
+    #[name = "This channel"]
+    Channel,
+}
+
+/// Where a subscriber wants to be reminded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReminderLocation {
+    DM,
+    Channel(ChannelId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReminderSubscriber {
+    pub user: UserId,
+    pub location: ReminderLocation,
+}
+
+/// A reminder that others can subscribe to if its author shared it, kept in the database
+/// until it has gone off for the last time or everyone has unsubscribed.
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub id: i64,
+    pub author: UserId,
+    pub message: String,
+    pub next_at: DateTime<Utc>,
+    pub frequency: ReminderFrequency,
+    /// Whether anyone who knows its ID can subscribe to it, not only its author.
+    pub public: bool,
+    pub subscribers: Vec<ReminderSubscriber>,
+}
+
+impl Reminder {
+    pub const SUBSCRIBE_PREFIX: &'static str = "reminder:subscribe:";
+
+    const TABLE_NAME: &'static str = "Reminders";
+    const SUBSCRIBERS_TABLE_NAME: &'static str = "ReminderSubscribers";
+    const COLUMNS: &'static str = "id, author_id, message, next_at, frequency, public";
+
+    pub fn create_tables(handle: &DatabaseHandle) -> anyhow::Result<()> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("id", "INTEGER", Some("PRIMARY KEY AUTOINCREMENT")),
+                    ("author_id", "INTEGER", Some("NOT NULL")),
+                    ("message", "TEXT", Some("NOT NULL")),
+                    ("next_at", "INTEGER", Some("NOT NULL")),
+                    ("frequency", "INTEGER", Some("NOT NULL")),
+                    ("public", "INTEGER", Some("NOT NULL DEFAULT 0")),
+                ],
+            )
+            .context(here!())?;
+
+        // Subscribers without a channel are reminded in their DMs.
+        handle
+            .create_table(
+                Self::SUBSCRIBERS_TABLE_NAME,
+                &[
+                    ("reminder_id", "INTEGER", Some("NOT NULL")),
+                    ("user_id", "INTEGER", Some("NOT NULL")),
+                    ("channel_id", "INTEGER", None),
+                ],
+            )
+            .context(here!())?;
+
+        Ok(())
+    }
+
+    /// Saves the reminder and its subscribers, returning its ID.
+    pub fn insert(&self, handle: &DatabaseHandle) -> anyhow::Result<i64> {
+        let id = match handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "INSERT INTO {} (author_id, message, next_at, frequency, public) VALUES (?, ?, ?, ?, ?)",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![
+                        self.author.0,
+                        self.message,
+                        self.next_at.timestamp(),
+                        self.frequency.as_i64(),
+                        self.public,
+                    ],
+                )
+                .context(here!())?;
+
+                h.last_insert_rowid()
+            }
+        };
+
+        for subscriber in &self.subscribers {
+            Self::subscribe(handle, id, *subscriber).context(here!())?;
+        }
+
+        Ok(id)
+    }
+
+    pub fn get(handle: &DatabaseHandle, id: i64) -> anyhow::Result<Option<Self>> {
+        let reminder = match handle {
+            DatabaseHandle::SQLite(h) => h
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM {} WHERE id = ?",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ),
+                    [id],
+                    Self::from_row,
+                )
+                .optional()
+                .context(here!())?,
+        };
+
+        reminder
+            .map(|r| r.with_subscribers(handle))
+            .transpose()
+            .context(here!())
+    }
+
+    /// The reminders the user is subscribed to, soonest first.
+    pub fn list(handle: &DatabaseHandle, user: UserId) -> anyhow::Result<Vec<Self>> {
+        Self::query(
+            handle,
+            &format!(
+                "WHERE id IN (SELECT reminder_id FROM {} WHERE user_id = ?) ORDER BY next_at",
+                Self::SUBSCRIBERS_TABLE_NAME
+            ),
+            rusqlite::params![user.0],
+        )
+    }
+
+    /// Whether the user can subscribe to the reminder, which is only shared if it's public.
+    pub fn can_subscribe(&self, user: UserId) -> bool {
+        self.public || self.author == user
+    }
+
+    /// Adds a subscriber to a reminder, returning false if they were already subscribed.
+    pub fn subscribe(
+        handle: &DatabaseHandle,
+        id: i64,
+        subscriber: ReminderSubscriber,
+    ) -> anyhow::Result<bool> {
+        let channel = match subscriber.location {
+            ReminderLocation::DM => None,
+            ReminderLocation::Channel(channel) => Some(channel.0),
+        };
+
+        match handle {
+            DatabaseHandle::SQLite(h) => h
+                .execute(
+                    &format!(
+                        "INSERT INTO {0} (reminder_id, user_id, channel_id) SELECT ?1, ?2, ?3 WHERE NOT EXISTS (SELECT 1 FROM {0} WHERE reminder_id = ?1 AND user_id = ?2)",
+                        Self::SUBSCRIBERS_TABLE_NAME
+                    ),
+                    rusqlite::params![id, subscriber.user.0, channel],
+                )
+                .map(|n| n > 0)
+                .context(here!()),
+        }
+    }
+
+    /// Removes a subscriber from a reminder, returning whether they were subscribed.
+    /// Reminders nobody is subscribed to anymore are removed.
+    pub fn unsubscribe(handle: &DatabaseHandle, id: i64, user: UserId) -> anyhow::Result<bool> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let removed = h
+                    .execute(
+                        &format!(
+                            "DELETE FROM {} WHERE reminder_id = ? AND user_id = ?",
+                            Self::SUBSCRIBERS_TABLE_NAME
+                        ),
+                        rusqlite::params![id, user.0],
+                    )
+                    .context(here!())?;
+
+                h.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE id = ? AND NOT EXISTS (SELECT 1 FROM {} WHERE reminder_id = ?)",
+                        Self::TABLE_NAME,
+                        Self::SUBSCRIBERS_TABLE_NAME
+                    ),
+                    rusqlite::params![id, id],
+                )
+                .context(here!())?;
+
+                Ok(removed > 0)
+            }
+        }
+    }
+
+    /// The "Remind me too" button shown on the confirmation of a new public reminder.
+    pub fn components(&self, c: &mut CreateComponents) -> &mut CreateComponents {
+        c.create_action_row(|r| {
+            r.create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label("Remind me too")
+                    .custom_id(format!("{}{}", Self::SUBSCRIBE_PREFIX, self.id))
+            })
+        })
+    }
+
+    fn due_times(handle: &DatabaseHandle) -> anyhow::Result<Vec<(i64, DateTime<Utc>)>> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!("SELECT id, next_at FROM {}", Self::TABLE_NAME))
+                    .context(here!())?;
+
+                let times = stmt
+                    .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                Ok(times
+                    .into_iter()
+                    .filter_map(|(id, t)| Some((id, Utc.timestamp_opt(t, 0).single()?)))
+                    .collect())
+            }
+        }
+    }
+
+    /// Moves a repeating reminder to its next time after `now`, skipping the ones
+    /// missed while the bot was offline, or removes it if it doesn't repeat.
+    fn advance(
+        &self,
+        handle: &DatabaseHandle,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let next_at = self
+            .frequency
+            .next_after(self.next_at)
+            .and_then(|time| self.frequency.next_from(time, now));
+
+        match (handle, next_at) {
+            (DatabaseHandle::SQLite(h), Some(next_at)) => {
+                h.execute(
+                    &format!("UPDATE {} SET next_at = ? WHERE id = ?", Self::TABLE_NAME),
+                    rusqlite::params![next_at.timestamp(), self.id],
+                )
+                .context(here!())?;
+            }
+            (DatabaseHandle::SQLite(h), None) => {
+                h.execute(
+                    &format!("DELETE FROM {} WHERE id = ?", Self::TABLE_NAME),
+                    [self.id],
+                )
+                .context(here!())?;
+
+                h.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE reminder_id = ?",
+                        Self::SUBSCRIBERS_TABLE_NAME
+                    ),
+                    [self.id],
+                )
+                .context(here!())?;
+            }
+        }
+
+        Ok(next_at)
+    }
+
+    fn query(
+        handle: &DatabaseHandle,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> anyhow::Result<Vec<Self>> {
+        let reminders = match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT {} FROM {} {filter}",
+                        Self::COLUMNS,
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let reminders = stmt
+                    .query_map(params, Self::from_row)
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                reminders
+            }
+        };
+
+        reminders
+            .into_iter()
+            .map(|r| r.with_subscribers(handle))
+            .collect()
+    }
+
+    fn with_subscribers(mut self, handle: &DatabaseHandle) -> anyhow::Result<Self> {
+        match handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT user_id, channel_id FROM {} WHERE reminder_id = ?",
+                        Self::SUBSCRIBERS_TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                self.subscribers = stmt
+                    .query_map([self.id], |row| {
+                        Ok(ReminderSubscriber {
+                            user: UserId(row.get(0)?),
+                            location: match row.get::<_, Option<u64>>(1)? {
+                                Some(channel) => ReminderLocation::Channel(ChannelId(channel)),
+                                None => ReminderLocation::DM,
+                            },
+                        })
+                    })
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            author: UserId(row.get(1)?),
+            message: row.get(2)?,
+            next_at: Utc
+                .timestamp_opt(row.get(3)?, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            frequency: ReminderFrequency::from_i64(row.get(4)?),
+            public: row.get(5)?,
+            subscribers: Vec::new(),
+        })
+    }
+
+    /// Reminds every subscriber, with a single message per channel
+    /// that mentions everyone who wanted to be reminded there.
+    async fn deliver(&self, ctx: &Ctx) -> anyhow::Result<()> {
+        let mut channels: HashMap<ChannelId, Vec<UserId>> = HashMap::new();
+
+        for subscriber in &self.subscribers {
+            match subscriber.location {
+                ReminderLocation::DM => {
+                    let result = async {
+                        subscriber
+                            .user
+                            .create_dm_channel(&ctx.http)
+                            .await?
+                            .send_message(&ctx.http, |m| {
+                                m.content(format!("**Reminder:** {}", self.message))
+                            })
+                            .await
+                    }
+                    .await;
+
+                    // Users can have DMs closed, which shouldn't stop the others from being reminded.
+                    if let Err(e) = result {
+                        error!(?e, user = %subscriber.user, "Failed to DM reminder!");
+                    }
+                }
+                ReminderLocation::Channel(channel) => {
+                    channels.entry(channel).or_default().push(subscriber.user);
+                }
+            }
+        }
+
+        for (channel, users) in channels {
+            let pings = users
+                .iter()
+                .map(|u| Mention::from(*u).to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            if let Err(e) = channel
+                .send_message(&ctx.http, |m| {
+                    m.content(format!("{pings}\n**Reminder:** {}", self.message))
+                        .allowed_mentions(|am| am.empty_parse().users(users.clone()))
+                })
+                .await
+                .context(here!())
+            {
+                error!(?e, %channel, "Failed to post reminder!");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A reminder as it's written in exported files, without anything tied to the server
+/// it was set in so that it can be imported elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedReminder {
+    pub message: String,
+    pub time: DateTime<Utc>,
+    pub frequency: ReminderFrequency,
+    /// Reminders sent in a channel are imported to the channel the import is done in.
+    #[serde(default)]
+    pub in_channel: bool,
+}
+
+impl ExportedReminder {
+    pub const MAX_MESSAGE_LENGTH: usize = 1000;
+
+    pub fn new(reminder: &Reminder, user: UserId) -> Self {
+        let in_channel = reminder
+            .subscribers
+            .iter()
+            .any(|s| s.user == user && matches!(s.location, ReminderLocation::Channel(_)));
+
+        Self {
+            message: reminder.message.clone(),
+            time: reminder.next_at,
+            frequency: reminder.frequency,
+            in_channel,
+        }
+    }
+
+    /// Turns the entry into a reminder for the user, moving repeating reminders to their
+    /// next time if it has passed.
+    pub fn into_reminder(
+        self,
+        user: UserId,
+        channel: ChannelId,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Reminder> {
+        let message = self.message.trim();
+
+        if message.is_empty() {
+            anyhow::bail!("The message is empty.");
+        }
+
+        if message.chars().count() > Self::MAX_MESSAGE_LENGTH {
+            anyhow::bail!(
+                "The message is longer than {} characters.",
+                Self::MAX_MESSAGE_LENGTH
+            );
+        }
+
+        let next_at = self
+            .frequency
+            .next_from(self.time, now)
+            .ok_or_else(|| anyhow::anyhow!("The reminder has already gone off."))?;
+
+        Ok(Reminder {
+            id: 0,
+            author: user,
+            message: message.to_owned(),
+            next_at,
+            frequency: self.frequency,
+            public: false,
+            subscribers: vec![ReminderSubscriber {
+                user,
+                location: if self.in_channel {
+                    ReminderLocation::Channel(channel)
+                } else {
+                    ReminderLocation::DM
+                },
+            }],
+        })
+    }
+}
+
+/// Subscribes the user who pressed "Remind me too", they're reminded in their DMs.
+pub async fn handle_subscribe_button(
+    ctx: &Ctx,
+    interaction: &MessageComponentInteraction,
+    data: &DataWrapper,
+) -> anyhow::Result<()> {
+    let id = interaction
+        .data
+        .custom_id
+        .strip_prefix(Reminder::SUBSCRIBE_PREFIX)
+        .and_then(|id| id.parse::<i64>().ok());
+
+    let content = {
+        let data = data.data.read().await;
+        let handle = data.database.lock().await;
+
+        match id.map(|id| Reminder::get(&handle, id)).transpose()? {
+            Some(Some(reminder)) if reminder.can_subscribe(interaction.user.id) => {
+                let subscriber = ReminderSubscriber {
+                    user: interaction.user.id,
+                    location: ReminderLocation::DM,
+                };
+
+                if Reminder::subscribe(&handle, reminder.id, subscriber).context(here!())? {
+                    format!(
+                        "You'll be reminded in your DMs <t:{}:R>.",
+                        reminder.next_at.timestamp()
+                    )
+                } else {
+                    "You're already subscribed to this reminder.".to_owned()
+                }
+            }
+            _ => "Error! This reminder has already gone off.".to_owned(),
+        }
+    };
+
+    interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.ephemeral(true).content(content))
+        })
+        .await
+        .context(here!())?;
+
+    Ok(())
+}
+
+/// Where the scheduler keeps reminders, so that they're still scheduled after a restart.
+pub trait ReminderStore {
+    /// When every reminder is due next, to schedule them on startup.
+    fn due_times(&self) -> anyhow::Result<Vec<(i64, DateTime<Utc>)>>;
+
+    fn load(&self, id: i64) -> anyhow::Result<Option<Reminder>>;
+
+    /// Records that the reminder went off at `now`, returning when it's due next, if ever.
+    fn advance(
+        &self,
+        reminder: &Reminder,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<DateTime<Utc>>>;
+}
+
+impl ReminderStore for DatabaseHandle {
+    fn due_times(&self) -> anyhow::Result<Vec<(i64, DateTime<Utc>)>> {
+        Reminder::due_times(self)
+    }
+
+    fn load(&self, id: i64) -> anyhow::Result<Option<Reminder>> {
+        Reminder::get(self, id)
+    }
+
+    fn advance(
+        &self,
+        reminder: &Reminder,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        reminder.advance(self, now)
+    }
+}
+
+/// When each reminder is due, kept in memory so that the scheduler can sleep until exactly
+/// the next one. Rescheduling or cancelling a reminder leaves its old entry in the heap,
+/// which is skipped once it reaches the top.
+#[derive(Debug, Default)]
+pub struct ReminderTimers {
+    queue: Mutex<DueQueue>,
+    changed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct DueQueue {
+    heap: BinaryHeap<Reverse<(DateTime<Utc>, i64)>>,
+    due: HashMap<i64, DateTime<Utc>>,
+}
+
+impl DueQueue {
+    fn is_current(&self, at: DateTime<Utc>, id: i64) -> bool {
+        self.due.get(&id) == Some(&at)
+    }
+
+    fn pop_stale(&mut self) {
+        while let Some(Reverse((at, id))) = self.heap.peek().copied() {
+            if self.is_current(at, id) {
+                break;
+            }
+
+            self.heap.pop();
+        }
+    }
+}
+
+impl ReminderTimers {
+    /// Schedules the reminder, replacing the time it was scheduled at before.
+    pub fn schedule(&self, id: i64, at: DateTime<Utc>) {
+        let mut queue = self.queue();
+        queue.due.insert(id, at);
+        queue.heap.push(Reverse((at, id)));
+        drop(queue);
+
+        self.changed.notify_one();
+    }
+
+    pub fn cancel(&self, id: i64) {
+        if self.queue().due.remove(&id).is_some() {
+            self.changed.notify_one();
+        }
+    }
+
+    fn next_due_at(&self) -> Option<DateTime<Utc>> {
+        let mut queue = self.queue();
+        queue.pop_stale();
+        queue.heap.peek().map(|Reverse((at, _))| *at)
+    }
+
+    /// Removes and returns the reminders that are due at `now`, soonest first.
+    fn pop_due(&self, now: DateTime<Utc>) -> Vec<i64> {
+        let mut queue = self.queue();
+        let mut due = Vec::new();
+
+        queue.pop_stale();
+
+        while let Some(Reverse((at, id))) = queue.heap.peek().copied() {
+            if at > now {
+                break;
+            }
+
+            queue.heap.pop();
+            queue.due.remove(&id);
+            due.push(id);
+            queue.pop_stale();
+        }
+
+        due
+    }
+
+    fn queue(&self) -> MutexGuard<'_, DueQueue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// How many minutes to wait before trying to load a reminder that failed to load again.
+const RETRY_DELAY_MINUTES: i64 = 5;
+
+/// Sends reminders when they're due, sleeping until the next one is. Commands that add
+/// or remove reminders update `timers`, which wakes it up to sleep until the new next one.
+#[instrument(skip(ctx, store, timers))]
+pub async fn scheduler(
+    ctx: Ctx,
+    store: impl ReminderStore,
+    timers: Arc<ReminderTimers>,
+) -> anyhow::Result<()> {
+    for (id, at) in store.due_times().context(here!())? {
+        timers.schedule(id, at);
+    }
+
+    loop {
+        let now = Utc::now();
+
+        for id in timers.pop_due(now) {
+            let reminder = match store.load(id) {
+                Ok(Some(reminder)) => reminder,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!(?e, id, "Failed to load reminder, trying again later!");
+                    timers.schedule(id, now + Duration::minutes(RETRY_DELAY_MINUTES));
+                    continue;
+                }
+            };
+
+            info!(
+                id = reminder.id,
+                subscribers = reminder.subscribers.len(),
+                "Sending reminder."
+            );
+
+            if let Err(e) = reminder.deliver(&ctx).await {
+                error!(?e, id = reminder.id, "Failed to send reminder!");
+            }
+
+            // If it can't be moved, it's left at the time it was sent at in the database,
+            // rather than being sent again until it can.
+            match store.advance(&reminder, now) {
+                Ok(Some(next_at)) => timers.schedule(id, next_at),
+                Ok(None) => (),
+                Err(e) => error!(?e, id, "Failed to move reminder to its next time!"),
+            }
+        }
+
+        match timers.next_due_at() {
+            Some(next_at) => {
+                let wait = (next_at - Utc::now()).to_std().unwrap_or_default();
+
+                tokio::select! {
+                    _ = sleep(wait) => {}
+                    _ = timers.changed.notified() => {}
+                }
+            }
+            // Nothing is scheduled, so only a new reminder can wake it up.
+            None => timers.changed.notified().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    fn reminder(next_at: DateTime<Utc>, frequency: ReminderFrequency) -> Reminder {
+        Reminder {
+            id: 0,
+            author: UserId(1),
+            message: "Watch the stream!".to_owned(),
+            next_at,
+            frequency,
+            public: false,
+            subscribers: vec![ReminderSubscriber {
+                user: UserId(1),
+                location: ReminderLocation::DM,
+            }],
+        }
+    }
+
+    #[test]
+    fn timers_pop_due_reminders_in_order() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let timers = ReminderTimers::default();
+
+        timers.schedule(1, now - Duration::minutes(1));
+        timers.schedule(2, now - Duration::minutes(5));
+        timers.schedule(3, now + Duration::minutes(5));
+        timers.schedule(4, now - Duration::minutes(3));
+
+        assert_eq!(timers.pop_due(now), vec![2, 4, 1]);
+        assert_eq!(timers.next_due_at(), Some(now + Duration::minutes(5)));
+        assert!(timers.pop_due(now).is_empty());
+    }
+
+    #[test]
+    fn timers_skip_rescheduled_and_cancelled_reminders() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let timers = ReminderTimers::default();
+
+        timers.schedule(1, now - Duration::minutes(2));
+        timers.schedule(2, now - Duration::minutes(1));
+        timers.schedule(1, now + Duration::minutes(10));
+        timers.cancel(2);
+
+        assert!(timers.pop_due(now).is_empty());
+        assert_eq!(timers.next_due_at(), Some(now + Duration::minutes(10)));
+
+        timers.cancel(1);
+        assert_eq!(timers.next_due_at(), None);
+    }
+
+    #[test]
+    fn reminders_are_persisted() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let path = std::env::temp_dir().join(format!("reminders_{}.db", std::process::id()));
+        let open = || DatabaseHandle::SQLite(Connection::open(&path).unwrap());
+
+        let handle = open();
+        Reminder::create_tables(&handle).unwrap();
+        let daily = reminder(now, ReminderFrequency::Daily)
+            .insert(&handle)
+            .unwrap();
+        let once = reminder(now + Duration::hours(1), ReminderFrequency::Once)
+            .insert(&handle)
+            .unwrap();
+        drop(handle);
+
+        let store = open();
+        let mut times = store.due_times().unwrap();
+        times.sort();
+        assert_eq!(times, vec![(daily, now), (once, now + Duration::hours(1))]);
+
+        let reminder = store.load(daily).unwrap().unwrap();
+        assert_eq!(reminder.subscribers.len(), 1);
+        assert_eq!(
+            store.advance(&reminder, now).unwrap(),
+            Some(now + Duration::days(1))
+        );
+
+        let reminder = store.load(once).unwrap().unwrap();
+        assert_eq!(store.advance(&reminder, now).unwrap(), None);
+        drop(store);
+
+        let store = open();
+        assert_eq!(
+            store.due_times().unwrap(),
+            vec![(daily, now + Duration::days(1))]
+        );
+        assert!(store.load(once).unwrap().is_none());
+        drop(store);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
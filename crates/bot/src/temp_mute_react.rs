@@ -10,15 +10,22 @@ use serenity::{
     client::Context as Ctx,
     model::{channel::ReactionType, id::UserId, mention::Mention},
     prelude::Mentionable,
-    utils::Color,
 };
 use tokio::{select, time::sleep};
 use tracing::{debug, error, instrument};
 use unicode_truncate::UnicodeTruncateStr;
-use utility::{config::ReactTempMuteConfig, here};
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    config::{EmbedEvent, ReactTempMuteConfig, ThemeConfig},
+    here,
+};
 
-#[instrument(skip(ctx, config))]
-pub async fn handler(ctx: Ctx, config: &ReactTempMuteConfig) -> anyhow::Result<()> {
+#[instrument(skip(ctx, config, theme))]
+pub async fn handler(
+    ctx: Ctx,
+    config: &ReactTempMuteConfig,
+    theme: ThemeConfig,
+) -> anyhow::Result<()> {
     struct ReactedMessage {
         count: usize,
         reacters: HashSet<UserId>,
@@ -230,6 +237,21 @@ pub async fn handler(ctx: Ctx, config: &ReactTempMuteConfig) -> anyhow::Result<(
                     return;
                 }
 
+                audit_log::record(
+                    &http,
+                    AuditEntry::new(
+                        AuditAction::PermissionsChanged,
+                        Mention::from(author_id).to_string(),
+                    )
+                    .guild(Some(guild_id))
+                    .details(format!(
+                        "Muted for {} minutes after {} reactions.",
+                        config.mute_duration.num_minutes(),
+                        config.required_reaction_count
+                    )),
+                )
+                .await;
+
                 sleep(config.mute_duration.to_std().unwrap()).await;
 
                 if let Err(e) = member
@@ -294,7 +316,7 @@ pub async fn handler(ctx: Ctx, config: &ReactTempMuteConfig) -> anyhow::Result<(
                                         .unwrap_or_else(|| message.author.default_avatar_url()),
                                 )
                             });
-                            e.colour(Color::RED);
+                            e.colour(theme.colour(EmbedEvent::Moderation));
                             e.fields([("Channel", message.channel_id.mention().to_string(), true)]);
 
                             if !content.is_empty() {
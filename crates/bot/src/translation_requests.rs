@@ -0,0 +1,216 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Context;
+use apis::translation_usage::{AccountedDeepL, UsageFeature};
+use chrono::{DateTime, Utc};
+use deepl::{DeepL, Language, TranslatableTextList};
+use lru::LruCache;
+use serenity::{
+    client::Context as Ctx,
+    model::{
+        channel::Reaction,
+        id::{MessageId, UserId},
+    },
+};
+use tracing::{error, instrument};
+use utility::{
+    config::{EmbedEvent, TranslationRequestConfig},
+    here, http,
+};
+
+use crate::DataWrapper;
+
+/// Whether a translation request should be carried out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    Allowed,
+    /// Someone else has already requested a translation of the message.
+    AlreadyTranslated,
+    /// The user has requested as many translations as they can for now.
+    RateLimited,
+}
+
+/// Keeps track of the translations users have requested in stream chats, so that each
+/// user stays within their limit and each message is only translated once.
+#[derive(Debug)]
+pub struct TranslationRequests {
+    requests: HashMap<UserId, VecDeque<DateTime<Utc>>>,
+    translated: LruCache<MessageId, ()>,
+}
+
+impl Default for TranslationRequests {
+    fn default() -> Self {
+        Self {
+            requests: HashMap::new(),
+            translated: LruCache::new(Self::REMEMBERED_MESSAGES),
+        }
+    }
+}
+
+impl TranslationRequests {
+    const REMEMBERED_MESSAGES: usize = 512;
+
+    /// Records the request, unless the message has been translated or the user is rate limited.
+    pub fn try_request(
+        &mut self,
+        user: UserId,
+        message: MessageId,
+        config: &TranslationRequestConfig,
+        now: DateTime<Utc>,
+    ) -> RequestOutcome {
+        if self.translated.contains(&message) {
+            return RequestOutcome::AlreadyTranslated;
+        }
+
+        let requests = self.requests.entry(user).or_default();
+
+        while matches!(requests.front(), Some(at) if *at + config.period <= now) {
+            requests.pop_front();
+        }
+
+        if requests.len() >= config.max_per_user {
+            return RequestOutcome::RateLimited;
+        }
+
+        requests.push_back(now);
+        self.translated.put(message, ());
+
+        RequestOutcome::Allowed
+    }
+
+    /// Lets the message be requested again, ex. if translating it failed.
+    pub fn forget(&mut self, message: MessageId) {
+        self.translated.pop(&message);
+    }
+}
+
+#[instrument(skip(ctx, data))]
+pub async fn handle_reaction(
+    ctx: &Ctx,
+    reaction: &Reaction,
+    data: &DataWrapper,
+) -> anyhow::Result<()> {
+    let config = &data.config.stream_tracking.chat.translation_requests;
+
+    // Custom emojis are compared in their `<:name:id>` form.
+    if !config.enabled || reaction.emoji.to_string() != config.emoji {
+        return Ok(());
+    }
+
+    let user = match reaction.user_id {
+        Some(user) if user != ctx.cache.current_user_id() => user,
+        _ => return Ok(()),
+    };
+
+    let token = match data.config.deepl_token() {
+        Some(token) => token.to_owned(),
+        None => return Ok(()),
+    };
+
+    let outcome = {
+        let mut data = data.data.write().await;
+
+        if !data
+            .claimed_chats
+            .borrow()
+            .values()
+            .any(|c| *c == reaction.channel_id)
+        {
+            return Ok(());
+        }
+
+        data.translation_requests
+            .try_request(user, reaction.message_id, config, Utc::now())
+    };
+
+    match outcome {
+        RequestOutcome::Allowed => (),
+        RequestOutcome::AlreadyTranslated => return Ok(()),
+        // Reactions can't be answered privately, so removing it is the only sign it was ignored.
+        RequestOutcome::RateLimited => {
+            if let Err(e) = reaction.delete(&ctx.http).await {
+                error!(?e, "Failed to remove rate limited translation request!");
+            }
+
+            return Ok(());
+        }
+    }
+
+    let result = translate(ctx, reaction, user, token, data).await;
+
+    if result.is_err() {
+        data.data
+            .write()
+            .await
+            .translation_requests
+            .forget(reaction.message_id);
+    }
+
+    result
+}
+
+async fn translate(
+    ctx: &Ctx,
+    reaction: &Reaction,
+    user: UserId,
+    token: String,
+    data: &DataWrapper,
+) -> anyhow::Result<()> {
+    let message = reaction.message(&ctx.http).await.context(here!())?;
+    let text = message.content_safe(&ctx.cache);
+
+    if message.author.bot || text.trim().is_empty() {
+        return Ok(());
+    }
+
+    let database = data.config.database.clone();
+
+    // The DeepL client is blocking, so it's kept off the async runtime.
+    let translated = tokio::task::spawn_blocking(move || {
+        let client = DeepL::with_agent(token, http::client().agent().clone());
+        let client = AccountedDeepL::new(client, database, UsageFeature::TranslationRequest);
+
+        client.translate(
+            None,
+            TranslatableTextList {
+                source_language: None,
+                target_language: Language::EnglishAmerican,
+                texts: vec![text],
+            },
+        )
+    })
+    .await
+    .context(here!())?
+    .map_err(|e| anyhow::anyhow!("{e}"))
+    .context(here!())?;
+
+    let translated = match translated.into_iter().next() {
+        Some(translated) => translated,
+        None => return Ok(()),
+    };
+
+    let requester = user.to_user(ctx).await.context(here!())?;
+    let colour = data.config.theme.colour(EmbedEvent::StreamChat);
+
+    message
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(&message)
+                .allowed_mentions(|a| a.empty_parse().replied_user(false))
+                .embed(|e| {
+                    e.author(|a| a.name(&message.author.name).icon_url(message.author.face()))
+                        .colour(colour)
+                        .description(&translated.text)
+                        .footer(|f| {
+                            f.text(format!(
+                                "Translated from {} for {}",
+                                translated.detected_source_language, requester.name
+                            ))
+                        })
+                })
+        })
+        .await
+        .context(here!())?;
+
+    Ok(())
+}
@@ -0,0 +1,321 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use nanorand::Rng;
+use poise::serenity_prelude::{
+    ActionRowComponent, ButtonStyle, CollectModalInteraction, InputTextStyle,
+    InteractionResponseType, Member, MessageComponentInteraction,
+};
+use serenity::{
+    client::Context as Ctx,
+    model::{
+        id::{GuildId, MessageId, UserId},
+        mention::Mention,
+    },
+};
+use tokio::time::sleep;
+use tracing::{error, info};
+use utility::{
+    audit_log::{self, AuditAction, AuditEntry},
+    config::{VerificationConfig, VerificationMethod},
+    here,
+};
+
+const START_PREFIX: &str = "verification:start:";
+const CAPTCHA_PREFIX: &str = "verification:captcha:";
+const CAPTCHA_ANSWER_ID: &str = "answer";
+
+/// How long members have to answer the captcha once it's been shown.
+const CAPTCHA_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+pub fn is_verification_button(custom_id: &str) -> bool {
+    custom_id.starts_with(START_PREFIX)
+}
+
+/// Asks a new member to verify in the verification channel, and handles the timeout if one
+/// is set. Timeouts are only tracked while the bot is running, so prompts posted before a
+/// restart never expire.
+pub async fn on_member_join(
+    ctx: &Ctx,
+    member: &Member,
+    config: &VerificationConfig,
+) -> anyhow::Result<()> {
+    // The member role tells which guild verification has been set up in.
+    if member.user.bot
+        || ctx
+            .cache
+            .role(member.guild_id, config.member_role)
+            .is_none()
+    {
+        return Ok(());
+    }
+
+    let user = member.user.id;
+
+    let prompt = config
+        .channel
+        .send_message(&ctx.http, |m| {
+            m.content(format!(
+                "Welcome {}! Press the button below to get access to the server.",
+                Mention::from(user)
+            ))
+            .allowed_mentions(|am| am.empty_parse().users([user]))
+            .components(|c| {
+                c.create_action_row(|r| {
+                    r.create_button(|b| {
+                        b.style(ButtonStyle::Success)
+                            .label("Verify")
+                            .custom_id(format!("{START_PREFIX}{user}"))
+                    })
+                })
+            })
+        })
+        .await
+        .context(here!())?;
+
+    if let Some(timeout) = config.timeout.and_then(|t| t.to_std().ok()) {
+        tokio::spawn(expire(
+            ctx.clone(),
+            member.guild_id,
+            user,
+            prompt.id,
+            config.clone(),
+            timeout,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verifies the member who pressed the button on their prompt,
+/// after they've answered a captcha if enabled.
+pub async fn handle_button(
+    ctx: &Ctx,
+    interaction: &MessageComponentInteraction,
+    config: &VerificationConfig,
+) -> anyhow::Result<()> {
+    let user = interaction.user.id;
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Verification button pressed outside of a guild."))?;
+
+    let prompted_user = interaction
+        .data
+        .custom_id
+        .strip_prefix(START_PREFIX)
+        .and_then(|id| id.parse().ok())
+        .map(UserId);
+
+    if prompted_user != Some(user) {
+        interaction
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| {
+                        d.ephemeral(true)
+                            .content("Error! This verification prompt is for someone else.")
+                    })
+            })
+            .await
+            .context(here!())?;
+
+        return Ok(());
+    }
+
+    match config.method {
+        VerificationMethod::Button => {
+            verify(
+                ctx,
+                guild_id,
+                user,
+                config,
+                "Pressed the verification button.",
+            )
+            .await?;
+
+            interaction
+                .create_interaction_response(&ctx.http, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| {
+                            d.ephemeral(true).content("You've been verified, welcome!")
+                        })
+                })
+                .await
+                .context(here!())?;
+        }
+        VerificationMethod::Captcha => {
+            let (a, b) = {
+                let mut rng = nanorand::tls_rng();
+                (rng.generate_range(1_u32..10), rng.generate_range(1_u32..10))
+            };
+
+            let custom_id = format!("{CAPTCHA_PREFIX}{}", interaction.id);
+
+            interaction
+                .create_interaction_response(&ctx.http, |r| {
+                    r.kind(InteractionResponseType::Modal)
+                        .interaction_response_data(|d| {
+                            d.custom_id(&custom_id)
+                                .title("Verification")
+                                .components(|c| {
+                                    c.create_action_row(|r| {
+                                        r.create_input_text(|t| {
+                                            t.custom_id(CAPTCHA_ANSWER_ID)
+                                                .label(format!("What is {a} + {b}?"))
+                                                .style(InputTextStyle::Short)
+                                                .required(true)
+                                        })
+                                    })
+                                })
+                        })
+                })
+                .await
+                .context(here!())?;
+
+            let response = CollectModalInteraction::new(&ctx.shard)
+                .author_id(user)
+                .filter(move |m| m.data.custom_id == custom_id)
+                .timeout(CAPTCHA_TIMEOUT)
+                .await;
+
+            let response = match response {
+                Some(r) => r,
+                None => return Ok(()),
+            };
+
+            let answer = response
+                .data
+                .components
+                .iter()
+                .flat_map(|r| &r.components)
+                .find_map(|c| match c {
+                    ActionRowComponent::InputText(t) if t.custom_id == CAPTCHA_ANSWER_ID => {
+                        t.value.trim().parse::<u32>().ok()
+                    }
+                    _ => None,
+                });
+
+            let passed = answer == Some(a + b);
+
+            if passed {
+                verify(ctx, guild_id, user, config, "Answered the captcha.").await?;
+            }
+
+            response
+                .create_interaction_response(&ctx.http, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| {
+                            d.ephemeral(true).content(if passed {
+                                "You've been verified, welcome!"
+                            } else {
+                                "Error! That's not the right answer, press the button to try again."
+                            })
+                        })
+                })
+                .await
+                .context(here!())?;
+
+            if !passed {
+                return Ok(());
+            }
+        }
+    }
+
+    interaction
+        .message
+        .delete(&ctx.http)
+        .await
+        .context(here!())?;
+
+    Ok(())
+}
+
+async fn verify(
+    ctx: &Ctx,
+    guild: GuildId,
+    user: UserId,
+    config: &VerificationConfig,
+    details: &str,
+) -> anyhow::Result<()> {
+    ctx.http
+        .add_member_role(guild.0, user.0, config.member_role.0, Some("Verified"))
+        .await
+        .context(here!())?;
+
+    info!(%user, "Member verified.");
+
+    audit_log::record(
+        &ctx.http,
+        AuditEntry::new(AuditAction::MemberVerified, Mention::from(user).to_string())
+            .guild(Some(guild))
+            .actor(user)
+            .details(details),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Removes the prompt once the timeout has passed, and kicks the member
+/// if they still haven't verified and the config says to.
+async fn expire(
+    ctx: Ctx,
+    guild: GuildId,
+    user: UserId,
+    prompt: MessageId,
+    config: VerificationConfig,
+    timeout: Duration,
+) {
+    sleep(timeout).await;
+
+    // The member has either left or been verified if the prompt is already gone.
+    if config.channel.message(&ctx.http, prompt).await.is_err() {
+        return;
+    }
+
+    // Fails if the member has left.
+    let unverified = guild
+        .member(&ctx.http, user)
+        .await
+        .ok()
+        .filter(|m| !m.roles.contains(&config.member_role));
+
+    if let Err(e) = config
+        .channel
+        .delete_message(&ctx.http, prompt)
+        .await
+        .context(here!())
+    {
+        error!(?e, "Failed to remove verification prompt!");
+    }
+
+    let member = match unverified {
+        Some(m) => m,
+        None => return,
+    };
+
+    let details = if config.kick_on_timeout {
+        if let Err(e) = member
+            .kick_with_reason(&ctx.http, "Didn't verify in time.")
+            .await
+            .context(here!())
+        {
+            error!(?e, "Failed to kick unverified member!");
+            return;
+        }
+
+        "Kicked for not verifying in time."
+    } else {
+        "Didn't verify in time, the member role has to be given manually."
+    };
+
+    audit_log::record(
+        &ctx.http,
+        AuditEntry::new(
+            AuditAction::VerificationExpired,
+            Mention::from(user).to_string(),
+        )
+        .guild(Some(guild))
+        .details(details),
+    )
+    .await;
+}
@@ -0,0 +1,493 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        request::Parts,
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use holodex::model::{id::VideoId, VideoStatus};
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context as Ctx,
+    model::id::{ChannelId, RoleId},
+};
+use subtle::ConstantTimeEq;
+use tokio::sync::{watch, Mutex, Notify};
+use tracing::{info, instrument};
+
+use utility::{
+    config::{Config, DatabaseHandle, HoloBranch, HoloGeneration, Talent},
+    here,
+    streams::Livestream,
+};
+
+use crate::{
+    announcements::{Announcement, Recurrence},
+    command_metrics::COMMAND_METRICS,
+};
+
+/// The longest announcement that can be scheduled, the same as in `/announce schedule`.
+const MAX_ANNOUNCEMENT_LENGTH: usize = 1900;
+/// The furthest ahead streams can be looked up, which is as far as Holodex schedules go.
+const MAX_LOOKAHEAD_HOURS: u32 = 24 * 14;
+
+#[derive(Clone)]
+pub(crate) struct ApiState {
+    ctx: Ctx,
+    token: Arc<str>,
+    stream_index: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
+    talents: watch::Receiver<Vec<Talent>>,
+    database: Arc<Mutex<DatabaseHandle>>,
+    announcements_enabled: bool,
+    announcements_changed: Arc<Notify>,
+}
+
+impl ApiState {
+    pub(crate) fn new(
+        ctx: Ctx,
+        config: &Config,
+        stream_index: Option<watch::Receiver<HashMap<VideoId, Livestream>>>,
+        talents: watch::Receiver<Vec<Talent>>,
+        announcements_changed: Arc<Notify>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            ctx,
+            token: Arc::from(config.web_api.token.as_str()),
+            stream_index,
+            talents,
+            database: Arc::new(Mutex::new(config.database.get_handle().context(here!())?)),
+            announcements_enabled: config.announcements.enabled,
+            announcements_changed,
+        })
+    }
+
+    /// The streams in the index matching the filter, soonest first.
+    /// They're always in the same order, so that their `ETag` only changes with them.
+    fn streams<F>(&self, filter: F) -> Result<Vec<StreamEntry>, ApiError>
+    where
+        F: Fn(&Livestream) -> bool,
+    {
+        let index = self.stream_index.as_ref().ok_or_else(|| {
+            ApiError(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Stream tracking is disabled.".to_owned(),
+            )
+        })?;
+
+        let mut streams = index
+            .borrow()
+            .values()
+            .filter(|l| filter(l))
+            .map(StreamEntry::from)
+            .collect::<Vec<_>>();
+
+        streams.sort_unstable_by(|a, b| (a.start_at, &a.id).cmp(&(b.start_at, &b.id)));
+        Ok(streams)
+    }
+}
+
+/// Serves the API until it fails.
+#[instrument(skip(state))]
+pub(crate) async fn serve(address: SocketAddr, state: ApiState) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/streams/live", get(live_streams))
+        .route("/streams/upcoming", get(upcoming_streams))
+        .route("/streams", get(query_streams))
+        .route("/talents", get(query_talents))
+        .route("/announcements", post(create_announcement))
+        .route("/metrics", get(metrics))
+        .with_state(state);
+
+    info!(%address, "Serving the web API.");
+
+    axum::Server::try_bind(&address)
+        .context(here!())?
+        .serve(app.into_make_service())
+        .await
+        .context(here!())
+}
+
+/// Requests with the configured bearer token.
+struct Authorized;
+
+#[async_trait]
+impl FromRequestParts<ApiState> for Authorized {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &ApiState) -> Result<Self, ApiError> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+
+        match token {
+            Some(token)
+                if !state.token.is_empty()
+                    && bool::from(token.as_bytes().ct_eq(state.token.as_bytes())) =>
+            {
+                Ok(Self)
+            }
+            _ => Err(ApiError(
+                StatusCode::UNAUTHORIZED,
+                "Missing or wrong bearer token.".to_owned(),
+            )),
+        }
+    }
+}
+
+/// The response with an `ETag` of the body, or `304 Not Modified` if the client has it already.
+fn cached<T: Serialize>(headers: &HeaderMap, body: &T) -> Result<Response, ApiError> {
+    let json = serde_json::to_vec(body).context(here!())?;
+
+    let mut hasher = DefaultHasher::new();
+    json.hash(&mut hasher);
+    let etag = format!("\"{:016x}\"", hasher.finish());
+
+    let known = headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|t| t.trim().trim_start_matches("W/"))
+        .any(|t| t == etag || t == "*");
+
+    if known {
+        return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response());
+    }
+
+    Ok((
+        [(ETAG, etag), (CONTENT_TYPE, "application/json".to_owned())],
+        json,
+    )
+        .into_response())
+}
+
+/// Errors are sent as JSON, with a message saying what went wrong.
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        tracing::error!(?e, "Web API request failed!");
+        Self(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong.".to_owned(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StreamEntry {
+    id: String,
+    title: String,
+    url: String,
+    thumbnail: String,
+    streamer: String,
+    collaborators: Vec<String>,
+    start_at: DateTime<Utc>,
+    live: bool,
+    topic: Option<String>,
+    members_only: bool,
+}
+
+impl From<&Livestream> for StreamEntry {
+    fn from(stream: &Livestream) -> Self {
+        Self {
+            id: stream.id.to_string(),
+            title: stream.title.clone(),
+            url: stream.url.clone(),
+            thumbnail: stream.thumbnail.clone(),
+            streamer: stream.streamer.name.clone(),
+            collaborators: stream
+                .collaborators
+                .iter()
+                .map(|t| t.name.clone())
+                .collect(),
+            start_at: stream.start_at,
+            live: stream.state == VideoStatus::Live,
+            topic: stream.topic.clone(),
+            members_only: stream.members_only,
+        }
+    }
+}
+
+async fn live_streams(
+    _: Authorized,
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let streams = state.streams(|l| l.state == VideoStatus::Live)?;
+    cached(&headers, &streams)
+}
+
+#[derive(Debug, Deserialize)]
+struct UpcomingQuery {
+    /// How many hours to look ahead, a day by default and at most two weeks.
+    hours: Option<u32>,
+}
+
+async fn upcoming_streams(
+    _: Authorized,
+    State(state): State<ApiState>,
+    Query(query): Query<UpcomingQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let now = Utc::now();
+    let hours = query.hours.unwrap_or(24).min(MAX_LOOKAHEAD_HOURS);
+    let until = now + Duration::hours(hours.into());
+
+    let streams = state.streams(|l| {
+        l.state == VideoStatus::Upcoming && l.start_at >= now && l.start_at <= until
+    })?;
+    cached(&headers, &streams)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StreamStatus {
+    Live,
+    Upcoming,
+}
+
+/// Filters for `/streams`, which all have to match. Leaving one out matches everything.
+#[derive(Debug, Deserialize)]
+struct StreamQuery {
+    /// The branch of the streamer hosting the stream.
+    branch: Option<HoloBranch>,
+    /// The generation of the streamer hosting the stream.
+    generation: Option<HoloGeneration>,
+    /// The name of the streamer or one of the collaborators, ignoring case.
+    talent: Option<String>,
+    status: Option<StreamStatus>,
+    /// How many hours to look ahead for upcoming streams, at most two weeks.
+    within_hours: Option<u32>,
+    topic: Option<String>,
+}
+
+impl StreamQuery {
+    fn matches(&self, stream: &Livestream, now: DateTime<Utc>) -> bool {
+        let status = match stream.state {
+            VideoStatus::Live => StreamStatus::Live,
+            VideoStatus::Upcoming => StreamStatus::Upcoming,
+            _ => return false,
+        };
+
+        if self.status.map_or(false, |s| s != status) {
+            return false;
+        }
+
+        if status == StreamStatus::Upcoming {
+            if let Some(hours) = self.within_hours {
+                let hours = hours.min(MAX_LOOKAHEAD_HOURS);
+
+                if stream.start_at > now + Duration::hours(hours.into()) {
+                    return false;
+                }
+            }
+        }
+
+        if self.branch.map_or(false, |b| b != stream.streamer.branch)
+            || self
+                .generation
+                .map_or(false, |g| g != stream.streamer.generation)
+        {
+            return false;
+        }
+
+        if let Some(talent) = &self.talent {
+            let mut participants =
+                std::iter::once(&stream.streamer).chain(stream.collaborators.iter());
+
+            if !participants.any(|t| t.name.eq_ignore_ascii_case(talent)) {
+                return false;
+            }
+        }
+
+        match &self.topic {
+            Some(topic) => stream
+                .topic
+                .as_deref()
+                .map_or(false, |t| t.eq_ignore_ascii_case(topic)),
+            None => true,
+        }
+    }
+}
+
+async fn query_streams(
+    _: Authorized,
+    State(state): State<ApiState>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let now = Utc::now();
+
+    let streams = state.streams(|l| query.matches(l, now))?;
+    cached(&headers, &streams)
+}
+
+#[derive(Debug, Deserialize)]
+struct TalentQuery {
+    branch: Option<HoloBranch>,
+    generation: Option<HoloGeneration>,
+}
+
+#[derive(Debug, Serialize)]
+struct TalentEntry {
+    name: String,
+    branch: HoloBranch,
+    generation: HoloGeneration,
+    youtube_channel: Option<String>,
+    twitter_handle: Option<String>,
+    /// Whether they're streaming right now, `None` if stream tracking is disabled.
+    live: Option<bool>,
+}
+
+async fn query_talents(
+    _: Authorized,
+    State(state): State<ApiState>,
+    Query(query): Query<TalentQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let live = state.stream_index.as_ref().map(|index| {
+        index
+            .borrow()
+            .values()
+            .filter(|l| l.state == VideoStatus::Live)
+            .map(|l| l.streamer.name.clone())
+            .collect::<Vec<_>>()
+    });
+
+    let talents = state
+        .talents
+        .borrow()
+        .iter()
+        .filter(|t| query.branch.map_or(true, |b| b == t.branch))
+        .filter(|t| query.generation.map_or(true, |g| g == t.generation))
+        .map(|t| TalentEntry {
+            name: t.name.clone(),
+            branch: t.branch,
+            generation: t.generation,
+            youtube_channel: t.youtube_ch_id.as_ref().map(ToString::to_string),
+            twitter_handle: t.twitter_handle.clone(),
+            live: live.as_ref().map(|live| live.contains(&t.name)),
+        })
+        .collect::<Vec<_>>();
+
+    cached(&headers, &talents)
+}
+
+#[derive(Debug, Deserialize)]
+struct NewAnnouncement {
+    channel: ChannelId,
+    content: String,
+    /// Posted right away if not given.
+    at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    roles: Vec<RoleId>,
+    recurrence: Option<Recurrence>,
+}
+
+#[derive(Debug, Serialize)]
+struct CreatedAnnouncement {
+    id: i64,
+    next_at: DateTime<Utc>,
+}
+
+async fn create_announcement(
+    _: Authorized,
+    State(state): State<ApiState>,
+    Json(request): Json<NewAnnouncement>,
+) -> Result<(StatusCode, Json<CreatedAnnouncement>), ApiError> {
+    let bad_request = |message: &str| Err(ApiError(StatusCode::BAD_REQUEST, message.to_owned()));
+
+    if !state.announcements_enabled {
+        return Err(ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Announcements are disabled.".to_owned(),
+        ));
+    }
+
+    if request.content.trim().is_empty() {
+        return bad_request("The announcement is empty.");
+    }
+
+    if request.content.chars().count() > MAX_ANNOUNCEMENT_LENGTH {
+        return bad_request("The announcement is too long.");
+    }
+
+    let guild = match state.ctx.cache.guild_channel(request.channel) {
+        Some(channel) => channel.guild_id,
+        None => return bad_request("The channel isn't in a server the bot is in."),
+    };
+
+    if let Some(role) = request
+        .roles
+        .iter()
+        .find(|r| state.ctx.cache.role(guild, **r).is_none())
+    {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("The role {role} isn't in the server of the channel."),
+        ));
+    }
+
+    let next_at = request.at.unwrap_or_else(Utc::now).max(Utc::now());
+
+    let announcement = Announcement {
+        id: 0,
+        guild,
+        channel: request.channel,
+        author: state.ctx.cache.current_user_id(),
+        content: request.content,
+        roles: request.roles,
+        next_at,
+        first_at: next_at,
+        recurrence: request.recurrence.unwrap_or(Recurrence::Once),
+    };
+
+    let id = {
+        let handle = state.database.lock().await;
+        announcement.insert(&handle).context(here!())?
+    };
+
+    state.announcements_changed.notify_one();
+    info!(id, channel = %announcement.channel, "Announcement scheduled through the web API.");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedAnnouncement {
+            id,
+            next_at: announcement.next_at,
+        }),
+    ))
+}
+
+/// The command metrics, for Prometheus to scrape with the bearer token.
+async fn metrics(_: Authorized) -> Response {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        COMMAND_METRICS.prometheus(),
+    )
+        .into_response()
+}
@@ -1,6 +1,8 @@
-use tracing::{error, Level};
+use anyhow::Context;
+use tracing::{error, info, Level};
 use tracing_appender::non_blocking::WorkerGuard;
-use tracing_subscriber::{filter::EnvFilter, fmt, prelude::*};
+use tracing_subscriber::{filter::EnvFilter, fmt, prelude::*, reload, Registry};
+use utility::{config::LoggingConfig, here, logging};
 
 pub struct Logger {}
 
@@ -40,6 +42,8 @@ impl Logger {
             .add_directive("serenity::client::bridge=warn".parse()?)
             .add_directive(Level::INFO.into());
 
+        let filter = Self::reloadable(filter)?;
+
         tracing_subscriber::registry()
             .with(filter)
             .with(fmt::Layer::new().with_writer(non_blocking))
@@ -75,17 +79,69 @@ impl Logger {
             .add_directive("hyper=info".parse()?)
             .add_directive(Level::DEBUG.into());
 
+        let filter = Self::reloadable(filter)?;
+
         tracing_subscriber::registry()
             // .with(console_layer)
+            .with(filter)
             .with(
                 fmt::Layer::new()
                     .with_ansi(true)
                     .with_writer(std::io::stdout)
-                    .pretty()
-                    .with_filter(filter),
+                    .pretty(),
             )
             .init();
 
         Ok(None)
     }
+
+    /// Applies the log filter from the config, or the default filter if it doesn't set one.
+    pub fn apply_config(config: &LoggingConfig) -> anyhow::Result<()> {
+        let filter = match &config.filter {
+            Some(filter) => {
+                logging::set_filter(filter)?;
+                filter.clone()
+            }
+            None => logging::reset_filter()?,
+        };
+
+        info!(%filter, "Log filter applied.");
+        Ok(())
+    }
+
+    /// Re-reads the config whenever the process receives SIGHUP, and applies its log filter.
+    #[cfg(unix)]
+    pub fn reload_on_hangup(config: std::sync::Arc<utility::config::Config>) -> anyhow::Result<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = signal(SignalKind::hangup()).context(here!())?;
+
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                let result = match config.reload().await {
+                    Ok(config) => Self::apply_config(&config.logging),
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = result {
+                    error!(?e, "Failed to reload log filter!");
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Wraps the filter so that it can be replaced at runtime through [`logging`].
+    fn reloadable(filter: EnvFilter) -> anyhow::Result<reload::Layer<EnvFilter, Registry>> {
+        let directives = filter.to_string();
+        let (filter, handle) = reload::Layer::new(filter);
+
+        logging::init(directives, move |directives| {
+            let filter = EnvFilter::try_new(directives).context(here!())?;
+            handle.reload(filter).context(here!())
+        })?;
+
+        Ok(filter)
+    }
 }
@@ -48,44 +48,256 @@
 
 mod logger;
 
-use std::{path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use tokio::sync::{broadcast, mpsc, oneshot};
+use anyhow::{bail, Context};
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+use tokio::sync::{broadcast, mpsc, watch};
 use tracing::{info, instrument};
 
+#[cfg(feature = "translation")]
+use apis::translation_usage;
+#[cfg(feature = "twitter")]
+use apis::twitter_api::TwitterApi;
 use apis::{
+    archive_index::ArchiveIndex,
     birthday_reminder::BirthdayReminder,
     discord_api::{DiscordApi, DiscordMessageData},
     holo_api::HoloApi,
-    twitter_api::TwitterApi,
+    image_cache,
+    kofi_webhook::KofiWebhook,
 };
 use bot::DiscordBot;
-use utility::{config::Config, streams::StreamUpdate};
+use utility::{
+    config::{Config, ConfigError, CURRENT_VERSION},
+    streams::StreamUpdateFanout,
+};
+
+/// Discord bot for Hololive servers.
+#[derive(Debug, Parser)]
+#[clap(version, about)]
+struct Cli {
+    /// Same as the `check-config` command.
+    #[clap(long, hide = true)]
+    check_config: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the bot. This is the default.
+    Run,
+    /// Validate the config file, and print it upgraded to the current version of the format.
+    CheckConfig,
+    /// Write everything stored in the database to a JSON file.
+    ExportData {
+        /// The file to write to, printed if not given.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Register the slash commands in a guild again, replacing the ones registered there.
+    RegisterCommands {
+        /// The ID of the guild.
+        guild_id: u64,
+    },
+    /// Delete archived stream chat messages from the search index.
+    PruneArchives {
+        /// Delete the messages sent more than this many days ago.
+        #[clap(long)]
+        older_than_days: u32,
+    },
+}
 
 fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let command = match cli.command {
+        Some(command) => command,
+        None if cli.check_config => Command::CheckConfig,
+        None => Command::Run,
+    };
+
+    match command {
+        Command::Run => run(),
+        Command::CheckConfig => check_config(),
+        Command::ExportData { output } => export_data(output.as_deref()),
+        Command::RegisterCommands { guild_id } => register_commands(guild_id),
+        Command::PruneArchives { older_than_days } => prune_archives(older_than_days),
+    }
+}
+
+fn run() -> anyhow::Result<()> {
     let _logging_guard = logger::Logger::initialize()?;
 
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async move { async_main().await })
 }
 
+fn check_config() -> anyhow::Result<()> {
+    let check = match Config::check(get_config_path()) {
+        Ok(check) => check,
+        Err(e) => match e.downcast_ref::<ConfigError>() {
+            Some(error) => {
+                eprintln!("Config is invalid:\n{}", error.report());
+                bail!("Config is invalid.");
+            }
+            None => return Err(e),
+        },
+    };
+
+    check_features(&check.config)?;
+    #[cfg(feature = "translation")]
+    check_alert_languages(&check.config)?;
+
+    if check.migrations.is_empty() {
+        eprintln!("Config is valid, and up to date (version {CURRENT_VERSION}).");
+    } else {
+        eprintln!(
+            "Config is valid, but written in version {}. Upgraded to version {CURRENT_VERSION}:",
+            check.file_version
+        );
+
+        for migration in &check.migrations {
+            eprintln!("  - {migration}");
+        }
+    }
+
+    println!("{}", check.to_toml()?);
+    Ok(())
+}
+
+/// Makes sure the bot was built with everything the config turns on.
+fn check_features(config: &Config) -> anyhow::Result<()> {
+    let translation = config.twitter.feed_translation.values().any(|t| t.enabled)
+        || config.stream_tracking.chat.translation_requests.enabled
+        || !config.stream_tracking.alerts.translations.is_empty();
+
+    let missing = [
+        ("twitter", config.twitter.enabled, cfg!(feature = "twitter")),
+        ("translation", translation, cfg!(feature = "translation")),
+        (
+            "openai",
+            config.ai_chatbot.enabled,
+            cfg!(feature = "openai"),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, enabled, compiled)| *enabled && !compiled)
+    .map(|(feature, ..)| format!("`{feature}`"))
+    .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        bail!(
+            "The config turns on things the bot was built without, rebuild it with the {} feature(s).",
+            missing.join(", ")
+        );
+    }
+
+    // The music bot is commented out in the source for now, so no build has it.
+    if config.music_bot.enabled {
+        bail!("The music bot isn't available yet, turn it off in the config.");
+    }
+
+    Ok(())
+}
+
+/// Makes sure alert titles are only translated to languages DeepL knows.
+#[cfg(feature = "translation")]
+fn check_alert_languages(config: &Config) -> anyhow::Result<()> {
+    let unknown = apis::alert_translation::unknown_languages(config);
+
+    if !unknown.is_empty() {
+        bail!(
+            "Alert titles can't be translated to the unknown language(s) {}.",
+            unknown.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+fn export_data(output: Option<&Path>) -> anyhow::Result<()> {
+    let config = Config::check(get_config_path())?.config;
+
+    let tables = config.database.get_handle()?.export_tables()?;
+    let table_count = tables.len();
+    let data = serde_json::to_string_pretty(&tables)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, data)
+                .with_context(|| format!("Could not write to {}.", path.display()))?;
+
+            eprintln!("Exported {table_count} table(s) to {}.", path.display());
+        }
+        None => println!("{data}"),
+    }
+
+    Ok(())
+}
+
+fn register_commands(guild_id: u64) -> anyhow::Result<()> {
+    let config = Config::check(get_config_path())?.config;
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let count = rt.block_on(DiscordBot::register_commands(&config, guild_id))?;
+
+    eprintln!("Registered {count} command(s) in guild {guild_id}.");
+    Ok(())
+}
+
+fn prune_archives(older_than_days: u32) -> anyhow::Result<()> {
+    let config = Config::check(get_config_path())?.config;
+
+    let before = Utc::now() - Duration::days(older_than_days.into());
+    let pruned = ArchiveIndex::open(&config.database)?.prune(before)?;
+
+    eprintln!("Deleted {pruned} archived message(s) sent before {before}.");
+    Ok(())
+}
+
 #[allow(clippy::too_many_lines, clippy::unreachable)]
 #[instrument]
 async fn async_main() -> anyhow::Result<()> {
     let config = Config::load(get_config_path()).await?;
+    check_features(&config)?;
+    #[cfg(feature = "translation")]
+    check_alert_languages(&config)?;
+    utility::http::init(&config.network)?;
+    utility::audit_log::init(&config.database, &config.audit_log, &config.theme)?;
+    utility::feature_flags::init(&config.database)?;
+    utility::birthdays::init(&config.database, &config.talents)?;
+    utility::tweet_subscriptions::init(&config.database)?;
+    image_cache::init(&config)?;
+    #[cfg(feature = "translation")]
+    translation_usage::init(&config.database)?;
+
+    if config.logging.filter.is_some() {
+        logger::Logger::apply_config(&config.logging)?;
+    }
+
+    #[cfg(unix)]
+    logger::Logger::reload_on_hangup(Arc::<Config>::clone(&config))?;
 
     let (discord_message_tx, discord_message_rx): (
         mpsc::Sender<DiscordMessageData>,
         mpsc::Receiver<DiscordMessageData>,
     ) = mpsc::channel(10);
 
-    let (stream_update_tx, _): (
-        broadcast::Sender<StreamUpdate>,
-        broadcast::Receiver<StreamUpdate>,
-    ) = broadcast::channel(64);
+    let stream_update_tx = StreamUpdateFanout::new(config.stream_tracking.update_queues.clone());
 
-    let (guild_ready_tx, guild_ready_rx) = oneshot::channel();
     let (service_restarter, _) = broadcast::channel(4);
+    let (cache_flusher, _) = broadcast::channel(4);
+    let (talent_updates, talents) = watch::channel(config.talents.clone());
+    let (index_commands, index_command_rx) = mpsc::channel(4);
+    let (claimed_chats_tx, claimed_chats) = watch::channel(HashMap::new());
 
     #[allow(clippy::if_then_some_else_none)]
     let stream_indexing = if config.stream_tracking.enabled {
@@ -97,6 +309,8 @@ async fn async_main() -> anyhow::Result<()> {
                 discord_message_tx.clone(),
                 stream_update_tx.clone(),
                 service_restarter,
+                talents.clone(),
+                index_command_rx,
             )
             .await,
         )
@@ -104,6 +318,7 @@ async fn async_main() -> anyhow::Result<()> {
         None
     };
 
+    #[cfg(feature = "twitter")]
     if config.twitter.enabled {
         let service_restarter = service_restarter.subscribe();
 
@@ -111,6 +326,7 @@ async fn async_main() -> anyhow::Result<()> {
             Arc::<Config>::clone(&config),
             discord_message_tx.clone(),
             service_restarter,
+            talents.clone(),
         )
         .await?;
     }
@@ -119,12 +335,20 @@ async fn async_main() -> anyhow::Result<()> {
         BirthdayReminder::start(Arc::<Config>::clone(&config), discord_message_tx.clone()).await;
     }
 
+    if config.support.enabled && config.support.kofi.enabled {
+        KofiWebhook::start(Arc::<Config>::clone(&config), discord_message_tx.clone()).await;
+    }
+
     let (task, cache) = DiscordBot::start(
         Arc::<Config>::clone(&config),
         stream_update_tx.clone(),
         stream_indexing.clone(),
-        guild_ready_tx,
         service_restarter,
+        cache_flusher.clone(),
+        discord_message_tx.clone(),
+        talent_updates,
+        index_commands,
+        claimed_chats,
     )
     .await?;
 
@@ -134,7 +358,8 @@ async fn async_main() -> anyhow::Result<()> {
         discord_message_rx,
         stream_update_tx.clone(),
         stream_indexing,
-        guild_ready_rx,
+        claimed_chats_tx,
+        cache_flusher.subscribe(),
     )
     .await;
 
@@ -0,0 +1,278 @@
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, TimeZone, Utc};
+use once_cell::sync::OnceCell;
+use rusqlite::ToSql;
+use serenity::{
+    http::Http,
+    model::id::{ChannelId, GuildId, UserId},
+    utils::Colour,
+};
+use strum::{Display, EnumString};
+use tracing::error;
+
+use crate::{
+    config::{AuditLogConfig, Database, DatabaseHandle, DatabaseOperations, ThemeConfig},
+    here,
+};
+
+static AUDIT_LOG: OnceCell<AuditLog> = OnceCell::new();
+
+/// Sets up the audit log from the config, entries recorded before this are only traced.
+pub fn init(
+    database: &Database,
+    config: &AuditLogConfig,
+    theme: &ThemeConfig,
+) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+    Vec::<AuditEntry>::create_table(&handle).context(here!())?;
+
+    AUDIT_LOG
+        .set(AuditLog {
+            database: Mutex::new(handle),
+            channel: config.enabled.then_some(config.channel),
+            theme: *theme,
+        })
+        .map_err(|_| anyhow!("Audit log has already been initialized!"))
+}
+
+/// Records an action taken by or through the bot, and mirrors it to the audit channel.
+/// Failures are logged rather than returned, so that they never abort the action itself.
+pub async fn record(http: impl AsRef<Http>, entry: AuditEntry) {
+    let log = match AUDIT_LOG.get() {
+        Some(log) => log,
+        None => {
+            error!(?entry, "Audit log used before being initialized!");
+            return;
+        }
+    };
+
+    if let Err(e) = log.save(&entry) {
+        error!("{:?}", e);
+    }
+
+    if let Some(channel) = log.channel {
+        if let Err(e) = entry.post(http.as_ref(), channel, &log.theme).await {
+            error!("{:?}", e);
+        }
+    }
+}
+
+/// The most recent entries, newest first, optionally limited to a single guild.
+pub fn recent(guild: Option<GuildId>, limit: usize) -> anyhow::Result<Vec<AuditEntry>> {
+    let log = AUDIT_LOG
+        .get()
+        .ok_or_else(|| anyhow!("Audit log has not been initialized!"))?;
+
+    let handle = log
+        .database
+        .lock()
+        .map_err(|_| anyhow!("Audit log database lock was poisoned!"))?;
+
+    match &*handle {
+        DatabaseHandle::SQLite(h) => {
+            let mut stmt = h
+                .prepare(
+                    "SELECT timestamp, action, guild_id, actor_id, target, details FROM AuditLog \
+                    WHERE ?1 IS NULL OR guild_id = ?1 ORDER BY timestamp DESC LIMIT ?2",
+                )
+                .context(here!())?;
+
+            let guild = guild.map(|g| *g.as_u64());
+            let results = stmt.query_and_then(rusqlite::params![guild, limit], |row| {
+                <Vec<AuditEntry> as DatabaseOperations<_>>::from_row(row)
+            })?;
+
+            results.collect()
+        }
+    }
+}
+
+struct AuditLog {
+    database: Mutex<DatabaseHandle>,
+    channel: Option<ChannelId>,
+    theme: ThemeConfig,
+}
+
+impl AuditLog {
+    fn save(&self, entry: &AuditEntry) -> anyhow::Result<()> {
+        let handle = self
+            .database
+            .lock()
+            .map_err(|_| anyhow!("Audit log database lock was poisoned!"))?;
+
+        vec![entry.clone()]
+            .save_to_database(&handle)
+            .context(here!())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum AuditAction {
+    #[strum(serialize = "Channel created")]
+    ChannelCreated,
+    #[strum(serialize = "Channel deleted")]
+    ChannelDeleted,
+    #[strum(serialize = "Thread archived")]
+    ThreadArchived,
+    #[strum(serialize = "Config changed")]
+    ConfigChanged,
+    #[strum(serialize = "Command removed")]
+    CommandRemoved,
+    #[strum(serialize = "Permissions changed")]
+    PermissionsChanged,
+    #[strum(serialize = "Talent added")]
+    TalentAdded,
+    #[strum(serialize = "Talent edited")]
+    TalentEdited,
+    #[strum(serialize = "Talent removed")]
+    TalentRemoved,
+    #[strum(serialize = "Member verified")]
+    MemberVerified,
+    #[strum(serialize = "Verification expired")]
+    VerificationExpired,
+    #[strum(serialize = "Birthday changed")]
+    BirthdayChanged,
+}
+
+impl AuditAction {
+    fn colour(self, theme: &ThemeConfig) -> Colour {
+        match self {
+            Self::ChannelCreated | Self::TalentAdded | Self::MemberVerified => Colour::DARK_GREEN,
+            Self::ChannelDeleted | Self::CommandRemoved | Self::TalentRemoved => {
+                Colour(theme.error)
+            }
+            Self::ThreadArchived
+            | Self::ConfigChanged
+            | Self::TalentEdited
+            | Self::BirthdayChanged => Colour(theme.default),
+            Self::PermissionsChanged | Self::VerificationExpired => Colour::ORANGE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    pub guild: Option<GuildId>,
+    /// The user who made the bot take the action, or `None` if the bot did it on its own.
+    pub actor: Option<UserId>,
+    /// What the action was applied to, ex. a channel mention or talent name.
+    pub target: String,
+    pub details: String,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction, target: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            action,
+            guild: None,
+            actor: None,
+            target: target.into(),
+            details: String::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn guild(mut self, guild: Option<GuildId>) -> Self {
+        self.guild = guild;
+        self
+    }
+
+    #[must_use]
+    pub fn actor(mut self, actor: UserId) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    #[must_use]
+    pub fn details(mut self, details: impl Into<String>) -> Self {
+        self.details = details.into();
+        self
+    }
+
+    async fn post(
+        &self,
+        http: &Http,
+        channel: ChannelId,
+        theme: &ThemeConfig,
+    ) -> anyhow::Result<()> {
+        channel
+            .send_message(http, |m| {
+                m.embed(|e| {
+                    e.title(self.action)
+                        .field("Target", &self.target, true)
+                        .field(
+                            "By",
+                            self.actor
+                                .map_or_else(|| "The bot".to_owned(), |a| format!("<@{a}>")),
+                            true,
+                        )
+                        .timestamp(self.timestamp)
+                        .colour(self.action.colour(theme));
+
+                    if !self.details.is_empty() {
+                        e.description(&self.details);
+                    }
+
+                    e
+                })
+            })
+            .await
+            .context(here!())?;
+
+        Ok(())
+    }
+}
+
+impl DatabaseOperations<'_, AuditEntry> for Vec<AuditEntry> {
+    type LoadItemContainer = Self;
+
+    const TABLE_NAME: &'static str = "AuditLog";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("timestamp", "INTEGER", Some("NOT NULL")),
+        ("action", "TEXT", Some("NOT NULL")),
+        ("guild_id", "INTEGER", None),
+        ("actor_id", "INTEGER", None),
+        ("target", "TEXT", Some("NOT NULL")),
+        ("details", "TEXT", Some("NOT NULL")),
+    ];
+
+    fn into_row(entry: AuditEntry) -> Vec<Box<dyn ToSql>> {
+        vec![
+            Box::new(entry.timestamp.timestamp()),
+            Box::new(entry.action.to_string()),
+            Box::new(entry.guild.map(|g| *g.as_u64())),
+            Box::new(entry.actor.map(|a| *a.as_u64())),
+            Box::new(entry.target),
+            Box::new(entry.details),
+        ]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<AuditEntry> {
+        Ok(AuditEntry {
+            timestamp: Utc
+                .timestamp_opt(row.get("timestamp").context(here!())?, 0)
+                .single()
+                .context(here!())?,
+            action: row
+                .get::<_, String>("action")
+                .context(here!())?
+                .parse()
+                .context(here!())?,
+            guild: row
+                .get::<_, Option<u64>>("guild_id")
+                .context(here!())?
+                .map(GuildId),
+            actor: row
+                .get::<_, Option<u64>>("actor_id")
+                .context(here!())?
+                .map(UserId),
+            target: row.get("target").context(here!())?,
+            details: row.get("details").context(here!())?,
+        })
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Mutex, RwLock},
+};
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::OnceCell;
+use serenity::model::{id::UserId, mention::Mention};
+use tokio::sync::watch;
+use tracing::error;
+
+use crate::{
+    config::{Database, DatabaseHandle, Talent},
+    here,
+};
+
+static BIRTHDAYS: OnceCell<BirthdayStore> = OnceCell::new();
+
+/// Loads the stored birthdays, using the ones in the talent config for talents
+/// that haven't had theirs set through the bot.
+pub fn init(database: &Database, talents: &[Talent]) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+
+    BIRTHDAYS
+        .set(BirthdayStore::load(handle, talents).context(here!())?)
+        .map_err(|_| anyhow!("Birthdays have already been initialized!"))
+}
+
+pub fn store() -> anyhow::Result<&'static BirthdayStore> {
+    BIRTHDAYS
+        .get()
+        .ok_or_else(|| anyhow!("Birthdays have not been initialized!"))
+}
+
+/// Whose birthday it is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Celebrant {
+    /// A tracked talent, by name.
+    Talent(String),
+    /// A member who registered their own birthday.
+    User(UserId),
+}
+
+impl Display for Celebrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Talent(name) => write!(f, "{name}"),
+            Self::User(user) => write!(f, "{}", Mention::from(*user)),
+        }
+    }
+}
+
+/// A birthday without the year, which is never stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BirthdayEntry {
+    pub celebrant: Celebrant,
+    pub day: u8,
+    pub month: u8,
+    /// The birthday starts at midnight in this timezone.
+    pub timezone: Tz,
+}
+
+impl BirthdayEntry {
+    /// The first time the birthday starts after `now`. Birthdays on February 29th
+    /// are only celebrated on leap years.
+    #[must_use]
+    pub fn next_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (now.year()..=now.year() + 8).find_map(|year| {
+            let start = self
+                .timezone
+                .with_ymd_and_hms(year, self.month.into(), self.day.into(), 0, 0, 0)
+                .earliest()?
+                .with_timezone(&Utc);
+
+            (start > now).then_some(start)
+        })
+    }
+
+    /// The date written out, like "July 6".
+    #[must_use]
+    pub fn date(&self) -> String {
+        NaiveDate::from_ymd_opt(2024, self.month.into(), self.day.into())
+            .map_or_else(String::new, |d| d.format("%B %-d").to_string())
+    }
+}
+
+/// Parses a birthday written like "July 6", "6 July" or "07-06", returning the day and month.
+#[must_use]
+pub fn parse_birthday(date: &str) -> Option<(u8, u8)> {
+    let date = date.trim();
+
+    // Parsed within a leap year, so that February 29th is valid.
+    let parsed = ["%B %d", "%d %B", "%b %d", "%d %b", "%m-%d", "%m/%d"]
+        .iter()
+        .find_map(|format| {
+            NaiveDate::parse_from_str(&format!("2024 {date}"), &format!("%Y {format}")).ok()
+        })?;
+
+    Some((
+        parsed.day().try_into().ok()?,
+        parsed.month().try_into().ok()?,
+    ))
+}
+
+/// The birthdays the bot celebrates, kept in the database so that they can be edited
+/// without touching the config. Anything waiting for the next birthday should
+/// [`subscribe`](Self::subscribe) to know when it has to look again.
+pub struct BirthdayStore {
+    database: Mutex<DatabaseHandle>,
+    entries: RwLock<HashMap<Celebrant, BirthdayEntry>>,
+    changed: watch::Sender<()>,
+}
+
+impl BirthdayStore {
+    const TABLE_NAME: &'static str = "Birthdays";
+
+    /// Loads the stored birthdays, falling back to the config for the talents without one.
+    pub fn load(handle: DatabaseHandle, talents: &[Talent]) -> anyhow::Result<Self> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("talent", "TEXT", None),
+                    ("user_id", "INTEGER", None),
+                    ("day", "INTEGER", Some("NOT NULL")),
+                    ("month", "INTEGER", Some("NOT NULL")),
+                    ("timezone", "TEXT", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        let mut entries = talents
+            .iter()
+            .map(|t| {
+                let entry = BirthdayEntry {
+                    celebrant: Celebrant::Talent(t.name.clone()),
+                    day: t.birthday.day,
+                    month: t.birthday.month,
+                    timezone: t.timezone,
+                };
+
+                (entry.celebrant.clone(), entry)
+            })
+            .collect::<HashMap<_, _>>();
+
+        match &handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT talent, user_id, day, month, timezone FROM {}",
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, Option<String>>(0)?,
+                            row.get::<_, Option<u64>>(1)?,
+                            row.get::<_, u8>(2)?,
+                            row.get::<_, u8>(3)?,
+                            row.get::<_, String>(4)?,
+                        ))
+                    })
+                    .context(here!())?;
+
+                for row in rows {
+                    let (talent, user, day, month, timezone) = row.context(here!())?;
+
+                    let celebrant = match (talent, user) {
+                        (Some(name), _) => Celebrant::Talent(name),
+                        (None, Some(user)) => Celebrant::User(UserId(user)),
+                        (None, None) => continue,
+                    };
+
+                    let timezone = match timezone.parse() {
+                        Ok(tz) => tz,
+                        Err(e) => {
+                            error!(%celebrant, %timezone, %e, "Invalid birthday timezone!");
+                            continue;
+                        }
+                    };
+
+                    entries.insert(
+                        celebrant.clone(),
+                        BirthdayEntry {
+                            celebrant,
+                            day,
+                            month,
+                            timezone,
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(Self {
+            database: Mutex::new(handle),
+            entries: RwLock::new(entries),
+            changed: watch::channel(()).0,
+        })
+    }
+
+    #[must_use]
+    pub fn entries(&self) -> Vec<BirthdayEntry> {
+        self.entries
+            .read()
+            .map(|e| e.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn get(&self, celebrant: &Celebrant) -> Option<BirthdayEntry> {
+        self.entries
+            .read()
+            .ok()
+            .and_then(|e| e.get(celebrant).cloned())
+    }
+
+    /// Notified whenever a birthday is set or removed.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    pub fn set(&self, entry: BirthdayEntry) -> anyhow::Result<()> {
+        self.write(&entry.celebrant, Some(&entry))?;
+
+        self.entries
+            .write()
+            .map_err(|_| anyhow!("Birthday lock was poisoned!"))?
+            .insert(entry.celebrant.clone(), entry);
+
+        self.changed.send_replace(());
+        Ok(())
+    }
+
+    /// Removes the birthday, returning whether there was one.
+    pub fn remove(&self, celebrant: &Celebrant) -> anyhow::Result<bool> {
+        self.write(celebrant, None)?;
+
+        let removed = self
+            .entries
+            .write()
+            .map_err(|_| anyhow!("Birthday lock was poisoned!"))?
+            .remove(celebrant)
+            .is_some();
+
+        self.changed.send_replace(());
+        Ok(removed)
+    }
+
+    fn write(&self, celebrant: &Celebrant, entry: Option<&BirthdayEntry>) -> anyhow::Result<()> {
+        let handle = self
+            .database
+            .lock()
+            .map_err(|_| anyhow!("Birthday database lock was poisoned!"))?;
+
+        let (talent, user) = match celebrant {
+            Celebrant::Talent(name) => (Some(name.as_str()), None),
+            Celebrant::User(user) => (None, Some(user.0)),
+        };
+
+        match &*handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE talent IS ? AND user_id IS ?",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![talent, user],
+                )
+                .context(here!())?;
+
+                if let Some(entry) = entry {
+                    h.execute(
+                        &format!(
+                            "INSERT INTO {} (talent, user_id, day, month, timezone) VALUES (?, ?, ?, ?, ?)",
+                            Self::TABLE_NAME
+                        ),
+                        rusqlite::params![
+                            talent,
+                            user,
+                            entry.day,
+                            entry.month,
+                            entry.timezone.name()
+                        ],
+                    )
+                    .context(here!())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::TempDatabase;
+
+    use super::*;
+
+    #[test]
+    fn birthdays_are_parsed_in_several_formats() {
+        assert_eq!(parse_birthday("July 6"), Some((6, 7)));
+        assert_eq!(parse_birthday("6 jul"), Some((6, 7)));
+        assert_eq!(parse_birthday("07-06"), Some((6, 7)));
+        assert_eq!(parse_birthday("February 29"), Some((29, 2)));
+        assert_eq!(parse_birthday("February 30"), None);
+        assert_eq!(parse_birthday("soon"), None);
+    }
+
+    #[test]
+    fn leap_day_birthdays_wait_for_leap_years() {
+        let entry = BirthdayEntry {
+            celebrant: Celebrant::User(UserId(1)),
+            day: 29,
+            month: 2,
+            timezone: Tz::UTC,
+        };
+
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            entry.next_after(now),
+            Some(Utc.with_ymd_and_hms(2028, 2, 29, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn birthdays_are_persisted() {
+        let database = TempDatabase::new("birthdays");
+
+        let store = BirthdayStore::load(database.open(), &[]).unwrap();
+        let changed = store.subscribe();
+
+        let entry = BirthdayEntry {
+            celebrant: Celebrant::User(UserId(1)),
+            day: 6,
+            month: 7,
+            timezone: Tz::Asia__Tokyo,
+        };
+
+        store.set(entry.clone()).unwrap();
+        assert!(changed.has_changed().unwrap());
+
+        let reloaded = BirthdayStore::load(database.open(), &[]).unwrap();
+        assert_eq!(reloaded.entries(), vec![entry.clone()]);
+
+        assert!(reloaded.remove(&entry.celebrant).unwrap());
+        assert!(BirthdayStore::load(database.open(), &[])
+            .unwrap()
+            .entries()
+            .is_empty());
+    }
+}
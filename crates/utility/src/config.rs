@@ -1,14 +1,23 @@
+mod errors;
 mod functions;
+mod migrations;
+mod templates;
 mod types;
 
-use std::{fmt::Display, path::Path, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use chrono::prelude::*;
 use chrono_tz::Tz;
 // use music_queue::EnqueuedItem;
 use rusqlite::{
-    types::{FromSql, FromSqlError, FromSqlResult, ValueRef},
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef},
     ToSql,
 };
 use serde::{Deserialize, Serialize};
@@ -22,13 +31,20 @@ use serenity::{
 use strum::{Display, EnumIter, EnumString};
 use tracing::{error, instrument};
 
-use crate::{functions::is_default, here};
+use crate::{clock::SharedClock, functions::is_default, here, types::TranslatorType};
 
+pub use self::errors::ConfigError;
 use self::functions::*;
+pub use self::migrations::{ConfigCheck, SchemaVersion, CURRENT_VERSION};
+pub use self::templates::*;
 pub use self::types::*;
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Config {
+    /// The version of the config format, see [`CURRENT_VERSION`].
+    #[serde(default)]
+    pub version: SchemaVersion,
+
     pub discord_token: String,
     pub blocked: BlockedEntities,
     #[serde(skip_serializing_if = "is_default")]
@@ -58,32 +74,90 @@ pub struct Config {
     #[serde(default)]
     pub quotes: QuoteConfig,
 
+    #[serde(default)]
+    pub emotes: EmoteConfig,
+
+    #[serde(default)]
+    pub announcements: AnnouncementConfig,
+
+    #[serde(default)]
+    pub support: SupportConfig,
+
+    #[serde(default)]
+    pub giveaways: GiveawayConfig,
+
+    #[serde(default)]
+    pub verification: VerificationConfig,
+
     #[serde(default)]
     pub twitter: TwitterConfig,
 
     #[serde(default)]
     pub react_temp_mute: ReactTempMuteConfig,
 
+    #[serde(default)]
+    pub presence: PresenceConfig,
+
+    #[serde(default)]
+    pub command_timeouts: CommandTimeoutConfig,
+
+    #[serde(default)]
+    pub command_registration: CommandRegistrationConfig,
+
     #[serde(default)]
     pub content_filtering: ContentFilteringConfig,
 
     #[serde(default)]
     pub embed_compressor: EmbedCompressorConfig,
 
+    #[serde(default)]
+    pub audit_log: AuditLogConfig,
+
+    #[serde(default)]
+    pub branches: HashMap<HoloBranch, BranchConfig>,
+
+    /// Called `http` in older config files.
+    #[serde(default, alias = "http")]
+    pub network: NetworkConfig,
+
+    #[serde(default)]
+    pub web_api: WebApiConfig,
+
+    #[serde(default)]
+    pub object_storage: ObjectStorageConfig,
+
+    #[serde(default)]
+    pub image_cache: ImageCacheConfig,
+
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
     #[serde(skip)]
     pub talents: Vec<Talent>,
+
+    #[serde(skip)]
+    pub path: PathBuf,
+
+    #[serde(skip)]
+    pub clock: SharedClock,
 }
 
 impl Config {
     #[instrument]
-    pub async fn load(folder: &'static Path) -> anyhow::Result<Arc<Self>> {
+    pub async fn load(folder: &Path) -> anyhow::Result<Arc<Self>> {
         let config_path = folder.join("config.toml");
         let talents_path = folder.join("talents.toml");
 
-        let mut config: Config = match load_toml_file_or_create_default(&config_path) {
+        let mut config = match migrations::load_config_file(&config_path) {
             Ok(c) => c,
             Err(e) => {
-                error!(?e, "Failed to open config file!");
+                match e.downcast_ref::<ConfigError>() {
+                    Some(error) => error!("Invalid config file!\n{}", error.report()),
+                    None => error!(?e, "Failed to open config file!"),
+                }
                 return Err(e);
             }
         };
@@ -91,14 +165,125 @@ impl Config {
         let talent_file: TalentFile = match load_toml_file_or_create_default(&talents_path) {
             Ok(t) => t,
             Err(e) => {
-                error!(?e, "Failed to open talents file!");
+                match e.downcast_ref::<ConfigError>() {
+                    Some(error) => error!("Invalid talents file!\n{}", error.report()),
+                    None => error!(?e, "Failed to open talents file!"),
+                }
                 return Err(e);
             }
         };
         config.talents = talent_file.talents.into_iter().map(|t| t.into()).collect();
+        config.path = config_path;
 
         Ok(Arc::new(config))
     }
+
+    /// Reads the config file in the folder and upgrades it to the current version
+    /// of the format, without creating it if it's missing.
+    pub fn check(folder: &Path) -> anyhow::Result<ConfigCheck> {
+        let config_path = folder.join("config.toml");
+
+        let contents = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Could not read config file at {}.", config_path.display()))?;
+
+        migrations::parse_config(&contents)
+            .with_context(|| format!("Invalid config file at {}.", config_path.display()))
+    }
+
+    /// Reads the config and talents files again from the folder this config was loaded from.
+    pub async fn reload(&self) -> anyhow::Result<Arc<Self>> {
+        let folder = self
+            .path
+            .parent()
+            .ok_or_else(|| anyhow!("Config path has no parent folder."))?;
+
+        let mut config = Self::load(folder).await?;
+
+        if let Some(config) = Arc::get_mut(&mut config) {
+            config.clock = self.clock.clone();
+        }
+
+        Ok(config)
+    }
+
+    /// Writes the config back to the file it was loaded from.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let contents = toml::to_string_pretty(self).context(here!())?;
+        std::fs::write(&self.path, contents).context(here!())?;
+
+        Ok(())
+    }
+
+    /// Writes the talent roster to the talents file next to the config file.
+    pub fn save_talents(&self, talents: &[Talent]) -> anyhow::Result<()> {
+        let talent_file = TalentFile {
+            talents: talents.iter().cloned().map(Into::into).collect(),
+        };
+
+        let contents = toml::to_string_pretty(&talent_file).context(here!())?;
+        std::fs::write(self.path.with_file_name("talents.toml"), contents).context(here!())?;
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn branch_name(&self, branch: HoloBranch) -> String {
+        self.branches
+            .get(&branch)
+            .map_or_else(|| branch.to_string(), |b| b.name.clone())
+    }
+
+    /// The full name of a generation, prefixed by the name of its branch.
+    #[must_use]
+    pub fn generation_name(&self, branch: HoloBranch, generation: HoloGeneration) -> String {
+        let generation_name = self
+            .branches
+            .get(&branch)
+            .and_then(|b| b.generations.get(&generation))
+            .map_or_else(|| generation.to_string(), |g| g.name.clone());
+
+        format!("{} {}", self.branch_name(branch), generation_name)
+    }
+
+    /// Whether tweets from more than one talent are posted in the channel.
+    #[must_use]
+    pub fn is_shared_twitter_feed(&self, channel: ChannelId) -> bool {
+        self.talents
+            .iter()
+            .filter(|t| t.twitter_id.is_some() && t.get_twitter_channel(self) == Some(channel))
+            .nth(1)
+            .is_some()
+    }
+
+    /// The token of the DeepL account, if one is set up for translating tweets.
+    /// It's also used by the other features that need DeepL.
+    #[must_use]
+    pub fn deepl_token(&self) -> Option<&str> {
+        self.twitter
+            .feed_translation
+            .get(&TranslatorType::DeepL)
+            .filter(|c| c.enabled)
+            .map(|c| c.token.as_str())
+    }
+
+    /// Looks up a channel for the generation, falling back to the one set for the branch.
+    fn group_channel<F>(
+        &self,
+        branch: HoloBranch,
+        generation: HoloGeneration,
+        channel: F,
+    ) -> Option<ChannelId>
+    where
+        F: Fn(&GroupChannels) -> Option<ChannelId>,
+    {
+        let branch = self.branches.get(&branch)?;
+
+        branch
+            .generations
+            .get(&generation)
+            .and_then(|g| channel(&g.channels))
+            .or_else(|| channel(&branch.channels))
+    }
 }
 
 impl TypeMapKey for Config {
@@ -233,7 +418,11 @@ pub struct Talent {
 impl Talent {
     #[must_use]
     pub fn get_next_birthday(&self) -> DateTime<Utc> {
-        let now = Utc::now();
+        self.get_next_birthday_after(Utc::now())
+    }
+
+    #[must_use]
+    pub fn get_next_birthday_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
         let Birthday {
             day,
             month,
@@ -247,7 +436,7 @@ impl Talent {
             .unwrap()
             .with_timezone(&Utc);
 
-        if birthday < now {
+        if birthday <= now {
             birthday.with_year(current_year + 1).unwrap_or(birthday)
         } else {
             birthday
@@ -258,10 +447,73 @@ impl Talent {
     pub fn get_twitter_channel(&self, config: &Config) -> Option<ChannelId> {
         config
             .twitter
-            .feeds
-            .get(&self.branch)
-            .and_then(|branch| branch.get(&self.generation))
+            .feed_overrides
+            .get(&self.name)
             .copied()
+            .or_else(|| config.group_channel(self.branch, self.generation, |c| c.twitter_feed))
+            .or_else(|| {
+                config
+                    .twitter
+                    .feeds
+                    .get(&self.branch)
+                    .and_then(|branch| branch.get(&self.generation))
+                    .copied()
+            })
+    }
+
+    #[must_use]
+    pub fn get_stream_discussion_channel(&self, config: &Config) -> Option<ChannelId> {
+        config
+            .group_channel(self.branch, self.generation, |c| c.stream_discussion)
+            .or_else(|| {
+                config
+                    .stream_tracking
+                    .chat
+                    .post_stream_discussion
+                    .get(&self.branch)
+                    .copied()
+            })
+    }
+
+    #[must_use]
+    pub fn get_birthday_channel(&self, config: &Config) -> ChannelId {
+        config
+            .group_channel(self.branch, self.generation, |c| c.birthday_alerts)
+            .unwrap_or(config.birthday_alerts.channel)
+    }
+
+    #[must_use]
+    pub fn generation_name(&self, config: &Config) -> String {
+        config.generation_name(self.branch, self.generation)
+    }
+
+    /// The colour of the talent, or of their generation or branch if the talent has none.
+    #[must_use]
+    pub fn get_colour(&self, config: &Config) -> u32 {
+        if self.colour != 0 {
+            return self.colour;
+        }
+
+        config
+            .branches
+            .get(&self.branch)
+            .map(|b| {
+                b.generations
+                    .get(&self.generation)
+                    .map(|g| g.colour)
+                    .filter(|c| *c != 0)
+                    .unwrap_or(b.colour)
+            })
+            .unwrap_or_default()
+    }
+
+    /// The colour of the talent, or the theme's colour for the event if the talent has none.
+    #[must_use]
+    pub fn embed_colour(&self, config: &Config, event: EmbedEvent) -> u32 {
+        match self.get_colour(config) {
+            0 => config.theme.colour(event),
+            colour => colour,
+        }
     }
 }
 
@@ -327,6 +579,30 @@ impl From<TalentConfigData> for Talent {
     }
 }
 
+impl From<Talent> for TalentConfigData {
+    fn from(talent: Talent) -> Self {
+        Self {
+            name: talent.name,
+            emoji: talent.emoji,
+            icon: talent.icon,
+
+            branch: talent.branch,
+            generation: talent.generation,
+
+            birthday: talent.birthday,
+            timezone: Some(talent.timezone),
+
+            youtube_ch_id: talent.youtube_ch_id,
+            twitter_handle: talent.twitter_handle,
+            twitter_id: talent.twitter_id,
+            schedule_keyword: talent.schedule_keyword,
+
+            colour: talent.colour,
+            discord_role: talent.discord_role,
+        }
+    }
+}
+
 pub trait UserCollection {
     fn find_by_name(&self, name: &str) -> Option<&Talent>;
 }
@@ -424,6 +700,139 @@ impl FromSql for HoloGeneration {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct BranchConfig {
+    pub name: String,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub colour: u32,
+
+    #[serde(default)]
+    pub channels: GroupChannels,
+
+    #[serde(default)]
+    pub generations: HashMap<HoloGeneration, GenerationConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct GenerationConfig {
+    pub name: String,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub colour: u32,
+
+    #[serde(default)]
+    pub channels: GroupChannels,
+}
+
+/// Channels used for a branch or generation, instead of the global defaults.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct GroupChannels {
+    #[serde(default)]
+    pub twitter_feed: Option<ChannelId>,
+    #[serde(default)]
+    pub stream_discussion: Option<ChannelId>,
+    #[serde(default)]
+    pub birthday_alerts: Option<ChannelId>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Quote {
+    pub lines: Vec<QuoteLine>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuoteLine {
+    pub user: String,
+    pub line: String,
+}
+
+impl Quote {
+    /// Parses a quote written as one `name: line` pair per line, where `name` refers to a talent.
+    pub fn from_message(message: &str, talents: &[Talent]) -> anyhow::Result<Self> {
+        let lines = message
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                let (name, line) = l
+                    .split_once(':')
+                    .ok_or_else(|| anyhow::anyhow!("Line is missing a speaker: {}", l))?;
+
+                let name = name.trim().to_lowercase();
+                let talent = talents
+                    .iter()
+                    .find(|t| t.name.to_lowercase().contains(&name))
+                    .ok_or_else(|| anyhow::anyhow!("No talent found with the name {}!", name))?;
+
+                Ok(QuoteLine {
+                    user: talent.name.clone(),
+                    line: line.trim().to_owned(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if lines.is_empty() {
+            return Err(anyhow::anyhow!("Quote is empty!"));
+        }
+
+        Ok(Self { lines })
+    }
+
+    pub fn as_embed(&self, talents: &[Talent]) -> anyhow::Result<serenity::builder::CreateEmbed> {
+        let first_speaker = self
+            .lines
+            .first()
+            .and_then(|l| talents.iter().find(|t| t.name == l.user))
+            .ok_or_else(|| anyhow::anyhow!("Quote has no known speakers!"))?;
+
+        let mut embed = serenity::builder::CreateEmbed::default();
+
+        embed
+            .colour(first_speaker.colour)
+            .thumbnail(&first_speaker.icon)
+            .fields(
+                self.lines
+                    .iter()
+                    .map(|l| (l.user.clone(), l.line.clone(), false)),
+            );
+
+        Ok(embed)
+    }
+}
+
+impl FromSql for Quote {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        serde_json::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+impl ToSql for Quote {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(
+            serde_json::to_string(self)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
+        )))
+    }
+}
+
+impl DatabaseOperations<'_, Quote> for Vec<Quote> {
+    type LoadItemContainer = Self;
+
+    const TRUNCATE_TABLE: bool = true;
+    const TABLE_NAME: &'static str = "Quotes";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] =
+        &[("quote", "TEXT", Some("NOT NULL"))];
+
+    fn into_row(quote: Quote) -> Vec<Box<dyn ToSql>> {
+        vec![Box::new(quote)]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<Quote> {
+        row.get("quote").context(here!())
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum EmojiUsageSource {
     InText,
@@ -0,0 +1,229 @@
+use std::fmt::{Display, Write};
+
+/// A problem with a config file, pointing to where in the file it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The file isn't valid TOML.
+    Syntax {
+        /// The line and column the error is at, starting from 1.
+        position: Option<(usize, usize)>,
+        message: String,
+    },
+    /// A key that has to be set isn't.
+    MissingKey { path: String },
+    /// A key is set to a value of the wrong type, or to one that isn't allowed.
+    InvalidValue {
+        path: String,
+        expected: Option<String>,
+        message: String,
+    },
+}
+
+impl ConfigError {
+    pub(crate) fn syntax(error: &toml::de::Error) -> Self {
+        Self::Syntax {
+            position: error.line_col().map(|(line, col)| (line + 1, col + 1)),
+            message: error.to_string(),
+        }
+    }
+
+    pub(crate) fn from_path_error<E: Display>(error: serde_path_to_error::Error<E>) -> Self {
+        let path = match error.path().to_string() {
+            path if path == "." => String::new(),
+            path => path,
+        };
+
+        let message = error.into_inner().to_string();
+
+        if let Some(field) = message
+            .strip_prefix("missing field `")
+            .and_then(|m| m.strip_suffix('`'))
+        {
+            return Self::MissingKey {
+                path: join_path(&path, field),
+            };
+        }
+
+        Self::InvalidValue {
+            expected: message
+                .split_once(", expected ")
+                .map(|(_, expected)| expected.to_owned()),
+            path,
+            message,
+        }
+    }
+
+    /// The key the error is at, like `network.timeout`.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Syntax { .. } => None,
+            Self::MissingKey { path } | Self::InvalidValue { path, .. } => Some(path),
+        }
+    }
+
+    /// What the value should have been, if the error says.
+    #[must_use]
+    pub fn expected(&self) -> Option<&str> {
+        match self {
+            Self::InvalidValue { expected, .. } => expected.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// How the error might be fixed.
+    #[must_use]
+    pub fn suggestion(&self) -> Option<&'static str> {
+        let (path, expected) = match self {
+            Self::Syntax { .. } => {
+                return Some("Check the line for missing quotes, brackets or commas.")
+            }
+            Self::MissingKey { .. } => return Some("The key is required, so add it to the file."),
+            Self::InvalidValue { path, expected, .. } => {
+                (path, expected.as_deref().unwrap_or_default())
+            }
+        };
+
+        let keys = path.split('.').collect::<Vec<_>>();
+
+        if keys.iter().any(|k| is_discord_id_key(k)) {
+            Some("Discord IDs must be numbers or numeric strings, like 123456789012345678.")
+        } else if keys.iter().any(|k| k.contains("timezone")) {
+            Some("Timezones are written as IANA names in quotes, like \"Asia/Tokyo\".")
+        } else if expected.starts_with("one of") || expected.starts_with("variant") {
+            Some("The value has to be one of the listed options, written in quotes.")
+        } else if expected.contains("bool") {
+            Some("Write `true` or `false`, without quotes.")
+        } else if ["integer", "u8", "u16", "u32", "u64", "usize", "i32", "i64"]
+            .iter()
+            .any(|t| expected.contains(t))
+        {
+            Some("Numbers are written without quotes.")
+        } else if expected.contains("string") {
+            Some("Text has to be written in quotes.")
+        } else {
+            None
+        }
+    }
+
+    /// The error along with what was expected and how it might be fixed, on separate lines.
+    #[must_use]
+    pub fn report(&self) -> String {
+        let mut report = match self {
+            Self::Syntax {
+                position: Some((line, col)),
+                ..
+            } => format!("Invalid TOML at line {line}, column {col}."),
+            Self::Syntax { position: None, .. } => "Invalid TOML.".to_owned(),
+            Self::MissingKey { path } => format!("Missing key `{path}`."),
+            Self::InvalidValue { path, .. } => format!("Invalid value at `{path}`."),
+        };
+
+        if let Self::Syntax { message, .. } | Self::InvalidValue { message, .. } = self {
+            let _ = write!(report, "\n  Problem:  {message}");
+        }
+
+        if let Some(expected) = self.expected() {
+            let _ = write!(report, "\n  Expected: {expected}");
+        }
+
+        if let Some(suggestion) = self.suggestion() {
+            let _ = write!(report, "\n  Hint:     {suggestion}");
+        }
+
+        report
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax { message, .. } => write!(f, "Invalid TOML: {message}")?,
+            Self::MissingKey { path } => write!(f, "Missing key `{path}`.")?,
+            Self::InvalidValue { path, message, .. } => {
+                write!(f, "Invalid value at `{path}`: {message}.")?;
+            }
+        }
+
+        match self.suggestion() {
+            Some(suggestion) => write!(f, " {suggestion}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn is_discord_id_key(key: &str) -> bool {
+    let key = key.trim_end_matches('s');
+
+    ["channel", "role", "guild"]
+        .iter()
+        .any(|k| key.ends_with(k))
+        || key == "user"
+        || key.ends_with("_id")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Alerts {
+        channel: serenity::model::id::ChannelId,
+        enabled: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct File {
+        alerts: Alerts,
+    }
+
+    fn parse(contents: &str) -> ConfigError {
+        let value = toml::from_str::<toml::Value>(contents).unwrap();
+        ConfigError::from_path_error(
+            serde_path_to_error::deserialize::<_, File>(value).unwrap_err(),
+        )
+    }
+
+    #[test]
+    fn invalid_ids_suggest_numbers() {
+        let error = parse("[alerts]\nchannel = \"general\"\nenabled = true\n");
+
+        assert_eq!(error.path(), Some("alerts.channel"));
+        assert!(error.suggestion().unwrap().contains("numeric strings"));
+    }
+
+    #[test]
+    fn wrong_types_say_what_was_expected() {
+        let error = parse("[alerts]\nchannel = 1\nenabled = \"yes\"\n");
+
+        assert_eq!(error.path(), Some("alerts.enabled"));
+        assert_eq!(error.expected(), Some("a boolean"));
+        assert!(error.report().contains("Hint:     Write `true` or `false`"));
+    }
+
+    #[test]
+    fn missing_keys_point_to_the_key() {
+        let error = parse("[alerts]\nchannel = 1\n");
+
+        assert_eq!(
+            error,
+            ConfigError::MissingKey {
+                path: "alerts.enabled".to_owned()
+            }
+        );
+    }
+}
@@ -8,6 +8,7 @@ use anyhow::{bail, Context};
 use serde::{de::DeserializeOwned, Serialize};
 use tracing::warn;
 
+use super::ConfigError;
 use crate::here;
 
 pub(crate) fn load_toml_file_or_create_default<T>(path: &Path) -> anyhow::Result<T>
@@ -15,13 +16,30 @@ where
     T: Serialize,
     T: DeserializeOwned,
     T: std::default::Default,
+{
+    match read_file_or_create_default::<T>(path)? {
+        Some(file_str) => {
+            let deserializer = &mut toml::Deserializer::new(&file_str);
+
+            serde_path_to_error::deserialize(deserializer)
+                .map_err(ConfigError::from_path_error)
+                .with_context(|| format!("Invalid file at {}.", path.display()))
+        }
+        None => Ok(T::default()),
+    }
+}
+
+/// Reads the file, or writes the default value of `T` to it and returns `None` if it doesn't exist.
+pub(crate) fn read_file_or_create_default<T>(path: &Path) -> anyhow::Result<Option<String>>
+where
+    T: Serialize,
+    T: std::default::Default,
 {
     let mut file = match fs::File::open(path) {
         Ok(f) => f,
         Err(e) => match e.kind() {
             ErrorKind::NotFound => {
-                let default_value = T::default();
-                let default_file = toml::to_string_pretty(&default_value).context(here!())?;
+                let default_file = toml::to_string_pretty(&T::default()).context(here!())?;
                 fs::write(path, default_file).context(here!())?;
 
                 warn!(
@@ -29,7 +47,7 @@ where
                     path.display()
                 );
 
-                return Ok(default_value);
+                return Ok(None);
             }
             ErrorKind::PermissionDenied => bail!(
                 "Insufficient permissions to open config file at {}: {}.",
@@ -43,8 +61,7 @@ where
     let mut file_str = String::new();
     file.read_to_string(&mut file_str).context(here!())?;
 
-    let data: T = toml::from_str(&file_str).context(here!())?;
-    Ok(data)
+    Ok(Some(file_str))
 }
 
 #[cfg(test)]
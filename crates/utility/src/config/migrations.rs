@@ -0,0 +1,278 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use serde::{Deserialize, Serialize};
+use toml::{value::Table, Value};
+use tracing::warn;
+
+use super::{functions::read_file_or_create_default, Config, ConfigError};
+use crate::here;
+
+/// The version of the config format this build reads.
+/// Bump it and add a migration whenever a key is moved, renamed or changes meaning.
+pub const CURRENT_VERSION: u32 = 1;
+
+/// The version of the format a config file is written in.
+/// Files written before the format was versioned have no version, and are treated as version 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct SchemaVersion(pub u32);
+
+impl Default for SchemaVersion {
+    fn default() -> Self {
+        Self(CURRENT_VERSION)
+    }
+}
+
+struct Migration {
+    description: &'static str,
+    apply: fn(&mut Table) -> anyhow::Result<()>,
+}
+
+/// The migration at index `i` upgrades a config from version `i + 1` to `i + 2`.
+const MIGRATIONS: [Migration; (CURRENT_VERSION - 1) as usize] = [];
+
+/// The result of reading a config file and upgrading it to the current version.
+#[derive(Debug)]
+pub struct ConfigCheck {
+    pub config: Config,
+    /// The version the file was written in.
+    pub file_version: u32,
+    /// The version the file was upgraded to.
+    pub version: u32,
+    /// Descriptions of the migrations that were applied, in order.
+    pub migrations: Vec<&'static str>,
+}
+
+impl ConfigCheck {
+    /// The config as it would be written in the current version of the format.
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        toml::to_string_pretty(&self.config).context(here!())
+    }
+}
+
+/// Reads the config file, upgrading it in memory if it's written in an older version
+/// of the format, or creates a default one if there isn't one.
+pub(crate) fn load_config_file(path: &Path) -> anyhow::Result<Config> {
+    let contents = match read_file_or_create_default::<Config>(path)? {
+        Some(contents) => contents,
+        None => return Ok(Config::default()),
+    };
+
+    let check = parse_config(&contents)
+        .with_context(|| format!("Invalid config file at {}.", path.display()))?;
+
+    if check.file_version < check.version {
+        warn!(
+            from = check.file_version,
+            to = check.version,
+            migrations = ?check.migrations,
+            "Config file is written in an older format, and was upgraded in memory. \
+            Run the `check-config` command to see the upgraded file."
+        );
+    }
+
+    Ok(check.config)
+}
+
+pub(crate) fn parse_config(contents: &str) -> anyhow::Result<ConfigCheck> {
+    parse_with_migrations(contents, &MIGRATIONS)
+}
+
+/// Parses the config, upgrading it with `migrations`, where the migration at index `i`
+/// upgrades a config from version `i + 1` to `i + 2`.
+fn parse_with_migrations(contents: &str, migrations: &[Migration]) -> anyhow::Result<ConfigCheck> {
+    let mut table: Table = toml::from_str(contents).map_err(|e| ConfigError::syntax(&e))?;
+
+    let file_version = match table.get("version") {
+        None => 1,
+        Some(Value::Integer(v)) if *v >= 1 => u32::try_from(*v).context(here!())?,
+        Some(v) => {
+            return Err(ConfigError::InvalidValue {
+                path: "version".to_owned(),
+                expected: Some("a positive integer".to_owned()),
+                message: format!("expected a positive integer, got {v}"),
+            }
+            .into())
+        }
+    };
+
+    let version = u32::try_from(migrations.len()).context(here!())? + 1;
+
+    if file_version > version {
+        bail!(
+            "Config version {file_version} is newer than the newest version this build \
+            supports ({version})."
+        );
+    }
+
+    let applied = migrate(&mut table, file_version, migrations)?;
+    let config = deserialize(table)?;
+
+    Ok(ConfigCheck {
+        config,
+        file_version,
+        version,
+        migrations: applied,
+    })
+}
+
+fn migrate(
+    table: &mut Table,
+    from: u32,
+    migrations: &[Migration],
+) -> anyhow::Result<Vec<&'static str>> {
+    let pending = migrations
+        .get((from - 1) as usize..)
+        .ok_or_else(|| anyhow!("No migrations from config version {from}."))?;
+
+    for (version, migration) in (from..).zip(pending) {
+        (migration.apply)(table).with_context(|| {
+            format!(
+                "Failed to migrate config from version {version} to {}: {}",
+                version + 1,
+                migration.description
+            )
+        })?;
+    }
+
+    let version = from + u32::try_from(pending.len()).context(here!())?;
+    table.insert("version".to_owned(), Value::Integer(version.into()));
+
+    Ok(pending.iter().map(|m| m.description).collect())
+}
+
+/// Deserializes the config, pointing to the key that failed if it's invalid.
+fn deserialize(table: Table) -> Result<Config, ConfigError> {
+    serde_path_to_error::deserialize(Value::Table(table)).map_err(ConfigError::from_path_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+        discord_token = "token"
+
+        [blocked]
+
+        [database]
+        backend = "SQLite"
+        parameters = { path = "database.db" }
+    "#;
+
+    #[test]
+    fn unversioned_files_are_the_first_version() {
+        let check = parse_config(BASE).unwrap();
+
+        assert_eq!(check.file_version, 1);
+        assert!(check.migrations.is_empty());
+        assert_eq!(check.config.version, SchemaVersion(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn old_network_keys_are_still_read() {
+        let contents =
+            format!("{BASE}\n[http]\nproxy = \"socks5://localhost:9050\"\ntimeout = 10\n");
+
+        let check = parse_config(&contents).unwrap();
+
+        assert_eq!(
+            check.config.network.proxy_url.as_deref(),
+            Some("socks5://localhost:9050")
+        );
+        assert_eq!(check.config.network.timeout, 10);
+    }
+
+    #[test]
+    fn current_files_are_not_migrated() {
+        let contents = format!("version = {CURRENT_VERSION}\n{BASE}");
+        let check = parse_config(&contents).unwrap();
+
+        assert_eq!(check.file_version, CURRENT_VERSION);
+        assert!(check.migrations.is_empty());
+    }
+
+    /// Renames `token` to `discord_token`, for testing upgrades from version 1 to 2.
+    const RENAME_TOKEN: Migration = Migration {
+        description: "Renamed `token` to `discord_token`.",
+        apply: |table| {
+            let token = table
+                .remove("token")
+                .ok_or_else(|| anyhow!("`token` is missing."))?;
+
+            table.insert("discord_token".to_owned(), token);
+            Ok(())
+        },
+    };
+
+    const FAILING: Migration = Migration {
+        description: "Always fails.",
+        apply: |_| bail!("Unsupported key."),
+    };
+
+    #[test]
+    fn old_files_are_upgraded_step_by_step() {
+        let contents = format!("version = 1\n{}", BASE.replace("discord_token", "token"));
+        let check = parse_with_migrations(&contents, &[RENAME_TOKEN]).unwrap();
+
+        assert_eq!(check.file_version, 1);
+        assert_eq!(check.version, 2);
+        assert_eq!(check.migrations, vec![RENAME_TOKEN.description]);
+        assert_eq!(check.config.version, SchemaVersion(2));
+        assert_eq!(check.config.discord_token, "token");
+
+        let contents = format!("version = 2\n{BASE}");
+        let check = parse_with_migrations(&contents, &[RENAME_TOKEN]).unwrap();
+
+        assert_eq!(check.file_version, 2);
+        assert!(check.migrations.is_empty());
+    }
+
+    #[test]
+    fn failed_migrations_name_their_versions() {
+        let contents = format!("version = 1\n{}", BASE.replace("discord_token", "token"));
+        let error = parse_with_migrations(&contents, &[RENAME_TOKEN, FAILING]).unwrap_err();
+        let message = format!("{error:#}");
+
+        assert!(
+            message.contains("from version 2 to 3: Always fails."),
+            "{message}"
+        );
+        assert!(message.contains("Unsupported key."), "{message}");
+    }
+
+    #[test]
+    fn newer_files_are_rejected() {
+        let contents = format!("version = {}\n{BASE}", CURRENT_VERSION + 1);
+        assert!(parse_config(&contents).is_err());
+    }
+
+    #[test]
+    fn conflicting_keys_are_rejected() {
+        let contents = format!("{BASE}\n[http]\ntimeout = 10\n\n[network]\ntimeout = 20\n");
+        assert!(parse_config(&contents).is_err());
+    }
+
+    #[test]
+    fn errors_point_to_the_invalid_key() {
+        let contents = format!("{BASE}\n[network]\ntimeout = \"soon\"\n");
+        let error = parse_config(&contents).unwrap_err().to_string();
+
+        assert!(error.contains("`network.timeout`"), "{error}");
+    }
+
+    #[test]
+    fn syntax_errors_have_a_position() {
+        let contents = format!("{BASE}\n[network\n");
+        let error = parse_config(&contents).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<ConfigError>(),
+            Some(ConfigError::Syntax {
+                position: Some(_),
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{EnumIter, EnumString, IntoEnumIterator};
+
+/// A value that can be inserted into a [`Template`], written as `{name}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, EnumString, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum Placeholder {
+    /// The name of the talent the alert is about.
+    Talent,
+    /// The names of every talent taking part, separated by commas.
+    Talents,
+    Title,
+    Url,
+    /// When the stream started or the event happened, shown relative to the reader.
+    StartRelative,
+    /// Mentions of the roles that should be pinged.
+    Pings,
+    /// How many there are of something, ex. streams that are live.
+    Count,
+    /// How long until the stream starts, written out like `1h 20m`.
+    StartsIn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// Alert text with `{placeholder}` variables, checked when the config is loaded.
+/// Literal braces are written as `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq, Eq, DeserializeFromStr, SerializeDisplay)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    #[must_use]
+    pub fn render<F>(&self, value: F) -> String
+    where
+        F: Fn(Placeholder) -> String,
+    {
+        self.segments
+            .iter()
+            .map(|s| match s {
+                Segment::Text(text) => text.clone(),
+                Segment::Placeholder(p) => value(*p),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let (name, rest) = chars
+                        .as_str()
+                        .split_once('}')
+                        .ok_or_else(|| anyhow!("Unclosed placeholder in template {s:?}."))?;
+
+                    let placeholder = name.trim().parse().map_err(|_| {
+                        anyhow!(
+                            "Unknown placeholder {{{name}}} in template {s:?}, expected one of {}.",
+                            Placeholder::iter().map(|p| format!("{{{p}}}")).join(", ")
+                        )
+                    })?;
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+
+                    segments.push(Segment::Placeholder(placeholder));
+                    chars = rest.chars();
+                }
+                '}' => bail!("Unmatched closing brace in template {s:?}, write it as `}}}}`."),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self {
+            source: s.to_owned(),
+            segments,
+        })
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn template(source: &str) -> Template {
+    source.parse().expect("default templates are valid")
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LiveAlertTemplates {
+    #[serde(default = "LiveAlertTemplates::default_title")]
+    pub title: Template,
+    #[serde(default = "LiveAlertTemplates::default_collab_title")]
+    pub collab_title: Template,
+    /// The message content, where the role pings are placed.
+    #[serde(default = "default_content")]
+    pub content: Template,
+}
+
+impl LiveAlertTemplates {
+    fn default_title() -> Template {
+        template("{talent} just went live!")
+    }
+
+    fn default_collab_title() -> Template {
+        template("{talents} just went live in a collab!")
+    }
+}
+
+impl Default for LiveAlertTemplates {
+    fn default() -> Self {
+        Self {
+            title: Self::default_title(),
+            collab_title: Self::default_collab_title(),
+            content: default_content(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ScheduleUpdateTemplates {
+    #[serde(default = "ScheduleUpdateTemplates::default_title")]
+    pub title: Template,
+    #[serde(default = "default_content")]
+    pub content: Template,
+}
+
+impl ScheduleUpdateTemplates {
+    fn default_title() -> Template {
+        template("{talent} just released a schedule update!")
+    }
+}
+
+impl Default for ScheduleUpdateTemplates {
+    fn default() -> Self {
+        Self {
+            title: Self::default_title(),
+            content: default_content(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BirthdayTemplates {
+    #[serde(default = "BirthdayTemplates::default_title")]
+    pub title: Template,
+    #[serde(default = "default_content")]
+    pub content: Template,
+}
+
+impl BirthdayTemplates {
+    fn default_title() -> Template {
+        template("It is {talent}'s birthday today!!!")
+    }
+}
+
+impl Default for BirthdayTemplates {
+    fn default() -> Self {
+        Self {
+            title: Self::default_title(),
+            content: default_content(),
+        }
+    }
+}
+
+/// The statuses the bot cycles through in its presence.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PresenceTemplates {
+    #[serde(default = "PresenceTemplates::default_live")]
+    pub live: Template,
+    #[serde(default = "PresenceTemplates::default_upcoming")]
+    pub upcoming: Template,
+}
+
+impl PresenceTemplates {
+    fn default_live() -> Template {
+        template("{count} live streams")
+    }
+
+    fn default_upcoming() -> Template {
+        template("{talent} in {starts_in}")
+    }
+}
+
+impl Default for PresenceTemplates {
+    fn default() -> Self {
+        Self {
+            live: Self::default_live(),
+            upcoming: Self::default_upcoming(),
+        }
+    }
+}
+
+fn default_content() -> Template {
+    template("{pings}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(p: Placeholder) -> String {
+        match p {
+            Placeholder::Talent => "Pekora".to_owned(),
+            Placeholder::Talents => "Pekora, Miko".to_owned(),
+            Placeholder::Title => "Minecraft".to_owned(),
+            Placeholder::Url => "https://youtu.be/abc".to_owned(),
+            Placeholder::StartRelative => "<t:0:R>".to_owned(),
+            Placeholder::Pings => "<@&1>".to_owned(),
+            Placeholder::Count => "3".to_owned(),
+            Placeholder::StartsIn => "1h 20m".to_owned(),
+        }
+    }
+
+    #[test]
+    fn placeholders_are_replaced() {
+        let template: Template = "{pings} {talent} is playing {title}! #hololive"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            template.render(values),
+            "<@&1> Pekora is playing Minecraft! #hololive"
+        );
+    }
+
+    #[test]
+    fn escaped_braces_are_kept() {
+        let template: Template = "{{{talent}}}".parse().unwrap();
+
+        assert_eq!(template.render(values), "{Pekora}");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_rejected() {
+        assert!("{talent} played {game}".parse::<Template>().is_err());
+        assert!("{talent".parse::<Template>().is_err());
+        assert!("talent}".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn templates_round_trip_through_config() {
+        let templates: BirthdayTemplates =
+            toml::from_str(r#"content = "{pings} #{talent}""#).unwrap();
+
+        assert_eq!(templates.title, BirthdayTemplates::default_title());
+        assert_eq!(templates.content.to_string(), "{pings} #{talent}");
+        assert!(toml::from_str::<BirthdayTemplates>(r#"title = "{nope}""#).is_err());
+    }
+}
@@ -1,15 +1,19 @@
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
+    net::SocketAddr,
     path::{Path, PathBuf},
 };
 
 use anyhow::Context;
-use chrono::Duration;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use itertools::Itertools;
-use rusqlite::{params_from_iter, Connection, OptionalExtension, ToSql};
+use regex::Regex;
+use rusqlite::{params_from_iter, types::ValueRef, Connection, OptionalExtension, ToSql};
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DisplayFromStr, DurationSeconds};
+use serde_hex::{CompactPfx, SerHex};
+use serde_with::{serde_as, DisplayFromStr, DurationMilliSeconds, DurationSeconds};
 use serenity::{
     builder::CreateEmbed,
     model::{
@@ -22,7 +26,10 @@ use serenity::{
 
 use crate::{functions::default_true, here, types::TranslatorType};
 
-use super::{HoloBranch, HoloGeneration, TalentConfigData};
+use super::{
+    BirthdayTemplates, HoloBranch, HoloGeneration, LiveAlertTemplates, PresenceTemplates,
+    ScheduleUpdateTemplates, TalentConfigData,
+};
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub(crate) struct TalentFile {
@@ -64,6 +71,17 @@ impl Database {
     }
 }
 
+fn sql_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => b.iter().copied().collect::<Vec<_>>().into(),
+    }
+}
+
 #[derive(Debug)]
 pub enum DatabaseHandle {
     SQLite(Connection),
@@ -138,6 +156,73 @@ impl DatabaseHandle {
         }
     }
 
+    /// Every table in the database, as a map from table names to their rows.
+    /// The tables that back full-text indexes internally are left out.
+    pub fn export_tables(&self) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+        match self {
+            DatabaseHandle::SQLite(h) => {
+                let tables = h
+                    .prepare(
+                        "SELECT name, sql FROM sqlite_master \
+                        WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+                    )
+                    .context(here!())?
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+                    })
+                    .context(here!())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(here!())?;
+
+                let shadow_prefixes = tables
+                    .iter()
+                    .filter(|(_, sql)| {
+                        sql.as_deref()
+                            .map_or(false, |s| s.starts_with("CREATE VIRTUAL TABLE"))
+                    })
+                    .map(|(name, _)| format!("{name}_"))
+                    .collect::<Vec<_>>();
+
+                let mut export = serde_json::Map::new();
+
+                for (table, _) in &tables {
+                    if shadow_prefixes.iter().any(|p| table.starts_with(p)) {
+                        continue;
+                    }
+
+                    let mut stmt = h
+                        .prepare(&format!("SELECT * FROM \"{table}\""))
+                        .context(here!())?;
+
+                    let columns = stmt
+                        .column_names()
+                        .into_iter()
+                        .map(str::to_owned)
+                        .collect::<Vec<_>>();
+
+                    let rows = stmt
+                        .query_map([], |row| {
+                            columns
+                                .iter()
+                                .enumerate()
+                                .map(|(i, column)| {
+                                    Ok((column.clone(), sql_to_json(row.get_ref(i)?)))
+                                })
+                                .collect::<rusqlite::Result<serde_json::Map<_, _>>>()
+                        })
+                        .context(here!())?
+                        .map(|row| row.map(serde_json::Value::Object))
+                        .collect::<Result<Vec<_>, _>>()
+                        .context(here!())?;
+
+                    export.insert(table.clone(), serde_json::Value::Array(rows));
+                }
+
+                Ok(export)
+            }
+        }
+    }
+
     pub fn truncate_table(&self, table: &str) -> anyhow::Result<bool> {
         match self {
             DatabaseHandle::SQLite(h) => h
@@ -147,6 +232,15 @@ impl DatabaseHandle {
         }
     }
 
+    pub fn delete(&self, table: &str, key: &str, value: &dyn ToSql) -> anyhow::Result<bool> {
+        match self {
+            DatabaseHandle::SQLite(h) => h
+                .execute(&format!("DELETE FROM {} WHERE {} = ?", table, key), [value])
+                .map(|n| n > 0)
+                .context(here!()),
+        }
+    }
+
     pub fn insert<'a, K, V>(&self, table: &str, keys: K, values: V) -> anyhow::Result<()>
     where
         K: Iterator<Item = &'a str> + Clone,
@@ -213,6 +307,61 @@ pub struct StreamTrackingConfig {
 
     #[serde(default)]
     pub chat: StreamChatConfig,
+
+    #[serde(default)]
+    pub scheduled_events: ScheduledEventsConfig,
+
+    #[serde(default)]
+    pub schedule_digest: ScheduleDigestConfig,
+
+    #[serde(default)]
+    pub status_board: StatusBoardConfig,
+
+    #[serde(default)]
+    pub update_queues: UpdateQueueConfig,
+}
+
+impl StreamTrackingConfig {
+    /// Whether anything listens to the stream updates, so that they need to be sent out.
+    #[must_use]
+    pub fn broadcasts_updates(&self) -> bool {
+        self.chat.enabled || self.status_board.enabled
+    }
+}
+
+/// How many stream updates each subscriber can fall behind before it has to resync from the index.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdateQueueConfig {
+    #[serde(default = "UpdateQueueConfig::default_capacity")]
+    pub default_capacity: usize,
+
+    /// Capacities for specific subscribers, by name.
+    #[serde(default)]
+    pub capacities: HashMap<String, usize>,
+}
+
+impl UpdateQueueConfig {
+    fn default_capacity() -> usize {
+        64
+    }
+
+    #[must_use]
+    pub fn capacity(&self, subscriber: &str) -> usize {
+        self.capacities
+            .get(subscriber)
+            .copied()
+            .unwrap_or(self.default_capacity)
+            .max(1)
+    }
+}
+
+impl Default for UpdateQueueConfig {
+    fn default() -> Self {
+        Self {
+            default_capacity: Self::default_capacity(),
+            capacities: HashMap::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
@@ -220,6 +369,97 @@ pub struct StreamAlertsConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
     pub channel: ChannelId,
+
+    /// Keep a field with the current viewer count in live alerts, updated while the stream is live.
+    #[serde(default)]
+    pub live_viewers: bool,
+
+    /// What to do with the alert of a stream that's cancelled after going live.
+    #[serde(default)]
+    pub on_cancelled: CancelledAlertAction,
+
+    #[serde(default)]
+    pub members_only: MembersOnlyAlertsConfig,
+
+    /// Channels that also get the alerts of streams on some topics, such as a karaoke channel.
+    #[serde(default)]
+    pub topic_channels: Vec<TopicAlertChannel>,
+
+    /// Languages to translate the titles of streams to, using the DeepL account set up
+    /// for translating tweets.
+    #[serde(default)]
+    pub translations: Vec<AlertLanguage>,
+
+    #[serde(default)]
+    pub templates: LiveAlertTemplates,
+}
+
+impl StreamAlertsConfig {
+    /// The channel to post the alert of a stream in, or `None` if it shouldn't be posted.
+    #[must_use]
+    pub fn channel_for(&self, members_only: bool) -> Option<ChannelId> {
+        if !members_only {
+            Some(self.channel)
+        } else if self.members_only.enabled {
+            Some(self.members_only.channel.unwrap_or(self.channel))
+        } else {
+            None
+        }
+    }
+
+    /// The topic channels that also get the alert of a stream on the topic.
+    pub fn topic_channels_for<'a>(
+        &'a self,
+        topic: Option<&'a str>,
+    ) -> impl Iterator<Item = ChannelId> + 'a {
+        self.topic_channels
+            .iter()
+            .filter(move |c| {
+                topic.map_or(false, |topic| {
+                    c.topics.iter().any(|t| t.eq_ignore_ascii_case(topic))
+                })
+            })
+            .map(|c| c.channel)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AlertLanguage {
+    /// The DeepL code of the language, such as `JA` or `EN-US`.
+    pub language: String,
+
+    /// Post a copy of the alert with the translated title here,
+    /// instead of adding the translation to the alert.
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TopicAlertChannel {
+    pub channel: ChannelId,
+    /// Holodex topics, such as `singing` or `minecraft`, matched ignoring case.
+    pub topics: Vec<String>,
+}
+
+/// Alerts for streams that only channel members can watch.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MembersOnlyAlertsConfig {
+    /// Post alerts for member-only streams at all.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Post them here instead of in the alert channel.
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+}
+
+impl Default for MembersOnlyAlertsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channel: None,
+        }
+    }
 }
 
 #[serde_as]
@@ -227,6 +467,12 @@ pub struct StreamAlertsConfig {
 pub struct StreamChatConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
+
+    #[serde(default)]
+    pub mode: StreamChatMode,
+
+    /// The category to create stream chats in, only used in [`StreamChatMode::Channels`].
+    #[serde(default)]
     pub category: ChannelId,
 
     #[serde(default)]
@@ -235,6 +481,333 @@ pub struct StreamChatConfig {
     #[serde(default)]
     #[serde_as(as = "HashMap<DisplayFromStr, _>")]
     pub post_stream_discussion: HashMap<HoloBranch, ChannelId>,
+
+    /// Pin the "Now watching" embed in newly claimed stream chats.
+    #[serde(default)]
+    pub pin_now_watching: bool,
+
+    /// Pin a summary with the VOD, duration and chat log in the discussion channel after archiving.
+    #[serde(default)]
+    pub pin_vod_summary: bool,
+
+    #[serde(default)]
+    pub slowmode: AutoSlowmodeConfig,
+
+    #[serde(default)]
+    pub highlights: ChatHighlightsConfig,
+
+    #[serde(default)]
+    pub translation_requests: TranslationRequestConfig,
+
+    #[serde(default)]
+    pub early_chats: EarlyChatConfig,
+
+    /// What to do on startup with old chats whose stream is new or missing in Holodex.
+    #[serde(default)]
+    pub unknown_status: UnknownStreamStatus,
+}
+
+/// Claims stream chats for upcoming streams ahead of time, keeping them read-only until the
+/// stream goes live. Only used in [`StreamChatMode::Channels`].
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EarlyChatConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How long before the scheduled start the chat is claimed.
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "EarlyChatConfig::default_lead_time")]
+    pub lead_time: Duration,
+}
+
+impl EarlyChatConfig {
+    fn default_lead_time() -> Duration {
+        Duration::minutes(15)
+    }
+}
+
+impl Default for EarlyChatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lead_time: Self::default_lead_time(),
+        }
+    }
+}
+
+/// Translates messages in stream chats when someone reacts to them with the emoji.
+/// Uses the DeepL account set up for translating tweets.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TranslationRequestConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default = "TranslationRequestConfig::default_emoji")]
+    pub emoji: String,
+
+    /// How many translations each user can request within `period`.
+    #[serde(default = "TranslationRequestConfig::default_max_per_user")]
+    pub max_per_user: usize,
+
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "TranslationRequestConfig::default_period")]
+    pub period: Duration,
+}
+
+impl TranslationRequestConfig {
+    fn default_emoji() -> String {
+        "🌐".to_owned()
+    }
+
+    fn default_max_per_user() -> usize {
+        5
+    }
+
+    fn default_period() -> Duration {
+        Duration::hours(1)
+    }
+}
+
+impl Default for TranslationRequestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            emoji: Self::default_emoji(),
+            max_per_user: Self::default_max_per_user(),
+            period: Self::default_period(),
+        }
+    }
+}
+
+/// Lists the moments of a stream where the chat was a lot busier than usual in its archive,
+/// based on how many messages were sent each minute.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChatHighlightsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How many standard deviations above the average a minute's message count must be.
+    #[serde(default = "ChatHighlightsConfig::default_min_z_score")]
+    pub min_z_score: f32,
+
+    /// How many highlights to list at most, the busiest ones are kept.
+    #[serde(default = "ChatHighlightsConfig::default_max_highlights")]
+    pub max_highlights: usize,
+}
+
+impl ChatHighlightsConfig {
+    fn default_min_z_score() -> f32 {
+        2.0
+    }
+
+    fn default_max_highlights() -> usize {
+        10
+    }
+}
+
+impl Default for ChatHighlightsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_z_score: Self::default_min_z_score(),
+            max_highlights: Self::default_max_highlights(),
+        }
+    }
+}
+
+/// Applies slowmode to busy stream chats, based on how many messages are sent per second.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AutoSlowmodeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How often, in seconds, the message rate is sampled.
+    #[serde(default = "AutoSlowmodeConfig::default_sample_interval")]
+    pub sample_interval: u64,
+
+    /// How many samples in a row need to be below a threshold before slowmode is relaxed.
+    #[serde(default = "AutoSlowmodeConfig::default_relax_after")]
+    pub relax_after: usize,
+
+    #[serde(default = "AutoSlowmodeConfig::default_thresholds")]
+    pub thresholds: Vec<SlowmodeThreshold>,
+}
+
+impl AutoSlowmodeConfig {
+    fn default_sample_interval() -> u64 {
+        20
+    }
+
+    fn default_relax_after() -> usize {
+        3
+    }
+
+    fn default_thresholds() -> Vec<SlowmodeThreshold> {
+        vec![
+            SlowmodeThreshold {
+                messages_per_second: 1.0,
+                slowmode: 2,
+            },
+            SlowmodeThreshold {
+                messages_per_second: 2.0,
+                slowmode: 5,
+            },
+            SlowmodeThreshold {
+                messages_per_second: 4.0,
+                slowmode: 10,
+            },
+        ]
+    }
+
+    /// The slowmode to use for the given message rate, if any threshold is reached.
+    pub fn slowmode_for(&self, messages_per_second: f32) -> Option<u64> {
+        self.thresholds
+            .iter()
+            .filter(|t| messages_per_second >= t.messages_per_second)
+            .map(|t| t.slowmode)
+            .max()
+    }
+}
+
+impl Default for AutoSlowmodeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_interval: Self::default_sample_interval(),
+            relax_after: Self::default_relax_after(),
+            thresholds: Self::default_thresholds(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlowmodeThreshold {
+    pub messages_per_second: f32,
+    /// Slowmode in seconds.
+    pub slowmode: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamChatMode {
+    /// Create a channel under the chat category for each stream.
+    #[default]
+    Channels,
+    /// Create a public thread on the alert message for each stream.
+    Threads,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelledAlertAction {
+    /// Leave the alert as it is.
+    #[default]
+    Keep,
+    /// Delete the alert.
+    Delete,
+    /// Edit the alert to say that the stream was cancelled.
+    MarkCancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownStreamStatus {
+    /// Archive the chat as if the stream had ended.
+    Archive,
+    /// Leave the chat alone until the stream starts or ends.
+    #[default]
+    Ignore,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ScheduledEventsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub guilds: Vec<ScheduledEventsGuildConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduledEventsGuildConfig {
+    pub guild: GuildId,
+
+    /// Only mirror streams from these branches, or all branches if empty.
+    #[serde(default)]
+    pub branches: Vec<HoloBranch>,
+}
+
+impl ScheduledEventsGuildConfig {
+    pub fn includes(&self, branch: HoloBranch) -> bool {
+        self.branches.is_empty() || self.branches.contains(&branch)
+    }
+}
+
+/// Posts a daily embed per branch listing the day's streams, edited as the schedule changes.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduleDigestConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(default)]
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    pub channels: HashMap<HoloBranch, ChannelId>,
+
+    /// The timezone whose midnight starts a new digest.
+    #[serde(default = "ScheduleDigestConfig::default_timezone")]
+    #[serde_as(as = "DisplayFromStr")]
+    pub timezone: Tz,
+}
+
+impl ScheduleDigestConfig {
+    fn default_timezone() -> Tz {
+        Tz::UTC
+    }
+
+    /// The date of the digest covering `time`, along with when that day starts and ends.
+    pub fn day_of(&self, time: DateTime<Utc>) -> (NaiveDate, DateTime<Utc>, DateTime<Utc>) {
+        let date = time.with_timezone(&self.timezone).date_naive();
+        let midnight = NaiveTime::from_hms_opt(0, 0, 0).expect("Midnight is a valid time.");
+
+        let start_of = |date: NaiveDate| {
+            let midnight = date.and_time(midnight);
+
+            self.timezone
+                .from_local_datetime(&midnight)
+                .earliest()
+                .map_or_else(
+                    || Utc.from_utc_datetime(&midnight),
+                    |t| t.with_timezone(&Utc),
+                )
+        };
+
+        (date, start_of(date), start_of(date + Duration::days(1)))
+    }
+}
+
+impl Default for ScheduleDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channels: HashMap::new(),
+            timezone: Self::default_timezone(),
+        }
+    }
+}
+
+/// Keeps a single embed listing the streams that are live, edited as streams start and end,
+/// for servers that prefer a board over a feed of alerts.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct StatusBoardConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The channels to keep a board in, usually one per guild.
+    #[serde(default)]
+    pub channels: Vec<ChannelId>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
@@ -249,6 +822,9 @@ pub struct BirthdayAlertsConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
     pub channel: ChannelId,
+
+    #[serde(default)]
+    pub templates: BirthdayTemplates,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
@@ -284,20 +860,415 @@ pub struct QuoteConfig {
     pub enabled: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq)]
-pub struct TwitterConfig {
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AnnouncementConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
-    pub token: String,
+}
 
-    #[serde(default)]
-    pub schedule_updates: ScheduleUpdateConfig,
+/// What `/donate` shows, and thanking supporters for their donations on Ko-fi.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SupportConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(default = "SupportConfig::default_title")]
+    pub title: String,
+
+    #[serde(default = "SupportConfig::default_description")]
+    pub description: String,
+
+    /// Shown as buttons under the message.
+    #[serde(default = "SupportConfig::default_links")]
+    pub links: Vec<SupportLink>,
+
+    #[serde(default = "SupportConfig::default_footer")]
+    pub footer: Option<String>,
+
+    #[serde(default)]
+    pub kofi: KofiWebhookConfig,
+}
+
+impl SupportConfig {
+    fn default_title() -> String {
+        "Donation Information".to_owned()
+    }
+
+    fn default_description() -> String {
+        "*Almondo, almondo peko!*\n\n\
+        If you are interested in helping support my development, \
+        and invest in better hosting, we'd appreciate your support peko!\n\n\
+        Any amount is appreciated, and all donations will go directly towards development \
+        and new hardware peko!\n\n\
+        No donations will ever be required to access any features of the bot, \
+        so if you feel like you can't spare some extra money, please save it for yourself peko. \
+        Additionally, please consider that all donations are non-refundable peko."
+            .to_owned()
+    }
+
+    fn default_links() -> Vec<SupportLink> {
+        vec![
+            SupportLink {
+                label: "GitHub Sponsors".to_owned(),
+                url: "https://github.com/sponsors/anden3".to_owned(),
+            },
+            SupportLink {
+                label: "Ko-fi".to_owned(),
+                url: "https://ko-fi.com/anden3".to_owned(),
+            },
+        ]
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn default_footer() -> Option<String> {
+        Some("I am made by anden3#0003 peko!".to_owned())
+    }
+}
+
+impl Default for SupportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            title: Self::default_title(),
+            description: Self::default_description(),
+            links: Self::default_links(),
+            footer: Self::default_footer(),
+            kofi: KofiWebhookConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SupportLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// Listens for the webhooks Ko-fi sends for donations, and thanks the supporters in a channel.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KofiWebhookConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default = "KofiWebhookConfig::default_address")]
+    pub address: SocketAddr,
+
+    #[serde(default = "KofiWebhookConfig::default_path")]
+    pub path: String,
+
+    /// The token shown in the webhook settings on Ko-fi, sent along with every webhook.
+    #[serde(default)]
+    pub verification_token: String,
+
+    /// Where supporters are thanked.
+    #[serde(default)]
+    pub channel: ChannelId,
+
+    /// Include the amount donated in the thanks.
+    #[serde(default)]
+    pub show_amount: bool,
+}
+
+impl KofiWebhookConfig {
+    fn default_address() -> SocketAddr {
+        SocketAddr::from(([0, 0, 0, 0], 8420))
+    }
+
+    fn default_path() -> String {
+        "/kofi".to_owned()
+    }
+}
+
+impl Default for KofiWebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: Self::default_address(),
+            path: Self::default_path(),
+            verification_token: String::new(),
+            channel: ChannelId::default(),
+            show_amount: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationMethod {
+    /// Press a button.
+    #[default]
+    Button,
+    /// Press a button and answer a simple sum in a form.
+    Captcha,
+}
+
+/// New members have to verify themselves before they're given the member role.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct VerificationConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Where new members are asked to verify.
+    pub channel: ChannelId,
+    pub member_role: RoleId,
+
+    #[serde(default)]
+    pub method: VerificationMethod,
+
+    /// How long new members have to verify, or forever if unset.
+    #[serde(default)]
+    #[serde_as(as = "Option<DurationSeconds<i64>>")]
+    pub timeout: Option<Duration>,
+
+    /// Whether members who haven't verified when the timeout passes are kicked.
+    #[serde(default)]
+    pub kick_on_timeout: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct GiveawayConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// The role members need to enter giveaways, or none to let everyone enter.
+    #[serde(default)]
+    pub required_role: Option<RoleId>,
+}
+
+/// Images members can upload to a per-guild library and post by name.
+/// The images are uploaded to the object storage, so it needs to be set up as well.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmoteConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// The roles allowed to add emotes, or every member if empty.
+    /// Members who can manage emojis are always allowed.
+    #[serde(default)]
+    pub uploader_roles: Vec<RoleId>,
+
+    /// The largest image that can be added, in kilobytes.
+    #[serde(default = "EmoteConfig::default_max_size_kb")]
+    pub max_size_kb: u32,
+}
+
+impl EmoteConfig {
+    fn default_max_size_kb() -> u32 {
+        8 * 1024
+    }
+}
+
+impl Default for EmoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            uploader_roles: Vec::new(),
+            max_size_kb: Self::default_max_size_kb(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq)]
+pub struct TwitterConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub token: String,
+
+    #[serde(default)]
+    pub schedule_updates: ScheduleUpdateConfig,
 
     #[serde(default)]
     pub feeds: HashMap<HoloBranch, HashMap<HoloGeneration, ChannelId>>,
 
+    /// Feeds for single talents by name, used instead of the feed of their branch or generation.
+    #[serde(default)]
+    pub feed_overrides: HashMap<String, ChannelId>,
+
+    /// Whether tweets posted in feeds shared by several talents are posted through a webhook,
+    /// so that they show up under the name and avatar of the talent.
+    #[serde(default)]
+    pub webhook_identities: bool,
+
     #[serde(default)]
     pub feed_translation: HashMap<TranslatorType, TranslatorConfig>,
+
+    #[serde(default)]
+    pub filters: Vec<TweetFilter>,
+
+    /// How tweets are linked when their media can't be shown, the first matching entry is used.
+    #[serde(default)]
+    pub media_fallbacks: Vec<TweetMediaFallback>,
+
+    /// How many messages to read from each feed on startup, to find the tweets posted before
+    /// they were recorded. Once enabled, replies are only linked to recorded tweets.
+    #[serde(default)]
+    pub reply_backfill: usize,
+
+    #[serde(default)]
+    pub dm_subscriptions: TweetDmConfig,
+}
+
+impl TwitterConfig {
+    #[must_use]
+    pub fn media_fallback(&self, channel: ChannelId) -> Option<&TweetMediaFallback> {
+        self.media_fallbacks.iter().find(|f| f.applies_to(channel))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TweetMediaFallback {
+    /// The feeds the fallback applies to, or every feed if empty.
+    #[serde(default)]
+    pub channels: Vec<ChannelId>,
+
+    /// The mirror tweet links are rewritten to, such as `fxtwitter.com` or `vxtwitter.com`.
+    #[serde(default = "TweetMediaFallback::default_mirror")]
+    pub mirror: String,
+}
+
+impl TweetMediaFallback {
+    fn default_mirror() -> String {
+        "fxtwitter.com".to_owned()
+    }
+
+    #[must_use]
+    pub fn applies_to(&self, channel: ChannelId) -> bool {
+        self.channels.is_empty() || self.channels.contains(&channel)
+    }
+
+    /// Points a tweet link at the mirror, keeping the path to the tweet.
+    #[must_use]
+    pub fn mirror_link(&self, link: &str) -> String {
+        let path = link
+            .split_once("://")
+            .map_or(link, |(_, rest)| rest)
+            .split_once('/')
+            .map_or("", |(_, path)| path);
+
+        format!("https://{}/{}", self.mirror, path)
+    }
+}
+
+impl Default for TweetMediaFallback {
+    fn default() -> Self {
+        Self {
+            channels: Vec::new(),
+            mirror: Self::default_mirror(),
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct TweetFilter {
+    /// The feeds the filter applies to, or every feed if empty.
+    #[serde(default)]
+    pub channels: Vec<ChannelId>,
+
+    /// Tweets containing any of these keywords are dropped, ignoring case.
+    #[serde(default)]
+    pub blocked_keywords: Vec<String>,
+
+    /// Tweets matching any of these patterns are dropped.
+    #[serde(default)]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub blocked_patterns: Vec<Regex>,
+
+    /// Only keep tweets with attached media.
+    #[serde(default)]
+    pub media_only: bool,
+
+    /// Tweets with fewer characters than this are dropped.
+    #[serde(default)]
+    pub min_length: usize,
+
+    /// ISO 639-1 codes of the languages to keep, or every language if empty.
+    /// Tweets that Twitter couldn't detect a language for are always kept.
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
+// Regexes can't be compared directly, so compare the patterns instead.
+impl PartialEq for TweetFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.channels == other.channels
+            && self.blocked_keywords == other.blocked_keywords
+            && self
+                .blocked_patterns
+                .iter()
+                .map(Regex::as_str)
+                .eq(other.blocked_patterns.iter().map(Regex::as_str))
+            && self.media_only == other.media_only
+            && self.min_length == other.min_length
+            && self.languages == other.languages
+    }
+}
+
+impl Eq for TweetFilter {}
+
+impl TweetFilter {
+    #[must_use]
+    pub fn applies_to(&self, channel: ChannelId) -> bool {
+        self.channels.is_empty() || self.channels.contains(&channel)
+    }
+
+    #[must_use]
+    pub fn allows(&self, text: &str, has_media: bool, lang: Option<&str>) -> bool {
+        if self.media_only && !has_media {
+            return false;
+        }
+
+        if text.chars().count() < self.min_length {
+            return false;
+        }
+
+        if let Some(lang) = lang {
+            if !self.languages.is_empty()
+                && !self.languages.iter().any(|l| l.eq_ignore_ascii_case(lang))
+            {
+                return false;
+            }
+        }
+
+        let lowercase = text.to_lowercase();
+
+        if self
+            .blocked_keywords
+            .iter()
+            .any(|k| lowercase.contains(&k.to_lowercase()))
+        {
+            return false;
+        }
+
+        !self.blocked_patterns.iter().any(|p| p.is_match(text))
+    }
+}
+
+/// Tweets sent by DM to the members subscribed to a talent, separately from the feeds.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TweetDmConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How many tweets a member is sent each hour, the rest are collected into a digest
+    /// that's sent once they can be sent another.
+    #[serde(default = "TweetDmConfig::default_max_per_hour")]
+    pub max_per_hour: u32,
+}
+
+impl TweetDmConfig {
+    fn default_max_per_hour() -> u32 {
+        10
+    }
+}
+
+impl Default for TweetDmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_per_hour: Self::default_max_per_hour(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq)]
@@ -305,6 +1276,9 @@ pub struct ScheduleUpdateConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
     pub channel: ChannelId,
+
+    #[serde(default)]
+    pub templates: ScheduleUpdateTemplates,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -314,6 +1288,41 @@ pub struct TranslatorConfig {
     pub token: String,
     #[serde(default)]
     pub languages: Vec<String>,
+    /// Which model translates the texts, if the translator has several, ex. `quality_optimized` for DeepL.
+    #[serde(default)]
+    pub model_type: Option<String>,
+}
+
+/// Cycles the bot's activity through the live streams and the next upcoming stream.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PresenceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How long each status is shown before moving on to the next one.
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "PresenceConfig::default_interval")]
+    pub interval: Duration,
+
+    #[serde(default)]
+    pub templates: PresenceTemplates,
+}
+
+impl PresenceConfig {
+    fn default_interval() -> Duration {
+        Duration::minutes(1)
+    }
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Self::default_interval(),
+            templates: PresenceTemplates::default(),
+        }
+    }
 }
 
 #[serde_as]
@@ -392,7 +1401,7 @@ impl ContentFilterResult<'_> {
 }
 
 impl ContentFilteringConfig {
-    pub fn filter<'a>(&'a self, msg: &'a Message) -> ContentFilterResult<'a> {
+    pub fn filter<'a>(&'a self, msg: &'a Message, theme: &ThemeConfig) -> ContentFilterResult<'a> {
         use ContentFilterAction::*;
 
         if !self.enabled || msg.author.bot {
@@ -424,7 +1433,7 @@ impl ContentFilteringConfig {
 
                     embed.author(|a| a.name("Content Filtering"));
                     embed.title("Video from known doxxer removed");
-                    embed.colour(Colour::RED);
+                    embed.colour(theme.colour(EmbedEvent::Moderation));
 
                     embed.fields([
                         (
@@ -454,7 +1463,7 @@ impl ContentFilteringConfig {
             actions.extend(
                 blacklisted_channels_in_msg
                     .iter()
-                    .map(|c| Log(c.to_embed(self))),
+                    .map(|c| Log(c.to_embed(self, theme))),
             );
 
             return ContentFilterResult::ContainsBlacklistedYTChannel(
@@ -475,12 +1484,12 @@ pub struct BlacklistedYTChannel {
 }
 
 impl BlacklistedYTChannel {
-    pub fn to_embed(&self, config: &ContentFilteringConfig) -> CreateEmbed {
+    pub fn to_embed(&self, config: &ContentFilteringConfig, theme: &ThemeConfig) -> CreateEmbed {
         let mut embed = CreateEmbed::default();
         embed
             .title("Video from blacklisted YT channel removed")
             .author(|a| a.name("Content Filtering"))
-            .colour(Colour::RED)
+            .colour(theme.colour(EmbedEvent::Moderation))
             .fields([
                 ("Name", &self.name, true),
                 ("Reason for blacklist", &self.reason, true),
@@ -509,3 +1518,384 @@ pub struct EmbedCompressorConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
 }
+
+/// Keeps slash commands within Discord's interaction window, and stops them if they hang.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommandTimeoutConfig {
+    /// How long a command can run before its response is deferred,
+    /// in milliseconds. Discord gives up on the interaction after 3 seconds.
+    #[serde_as(as = "DurationMilliSeconds<i64>")]
+    #[serde(default = "CommandTimeoutConfig::default_defer_after")]
+    pub defer_after: Duration,
+
+    /// How long a command can run before it's cancelled, unless it has responded by then.
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "CommandTimeoutConfig::default_max_duration")]
+    pub max_duration: Duration,
+
+    /// Limits for specific commands, by their full name, ex. `music add`.
+    #[serde(default)]
+    pub commands: HashMap<String, CommandTimeoutOverride>,
+}
+
+impl CommandTimeoutConfig {
+    fn default_defer_after() -> Duration {
+        Duration::milliseconds(2000)
+    }
+
+    fn default_max_duration() -> Duration {
+        Duration::minutes(5)
+    }
+
+    /// When to defer the command's response, and when to cancel it.
+    #[must_use]
+    pub fn limits_for(&self, command: &str) -> (Duration, Duration) {
+        let limits = self.commands.get(command);
+
+        (
+            limits
+                .and_then(|l| l.defer_after)
+                .unwrap_or(self.defer_after),
+            limits
+                .and_then(|l| l.max_duration)
+                .unwrap_or(self.max_duration),
+        )
+    }
+}
+
+impl Default for CommandTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            defer_after: Self::default_defer_after(),
+            max_duration: Self::default_max_duration(),
+            commands: HashMap::new(),
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommandTimeoutOverride {
+    #[serde_as(as = "Option<DurationMilliSeconds<i64>>")]
+    #[serde(default)]
+    pub defer_after: Option<Duration>,
+
+    #[serde_as(as = "Option<DurationSeconds<i64>>")]
+    #[serde(default)]
+    pub max_duration: Option<Duration>,
+}
+
+/// Where slash commands are registered with Discord.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct CommandRegistrationConfig {
+    /// Where commands are registered unless their group says otherwise.
+    #[serde(default)]
+    pub default_scope: CommandScope,
+
+    /// Scopes for groups of commands, by category, ex. `Admin`,
+    /// or by the name of a top-level command, which takes precedence.
+    #[serde(default)]
+    pub groups: HashMap<String, CommandScope>,
+
+    /// Registers every command in this guild, and nowhere else.
+    /// Guild commands show up immediately, which makes testing changes faster.
+    #[serde(default)]
+    pub dev_guild: Option<GuildId>,
+}
+
+impl CommandRegistrationConfig {
+    #[must_use]
+    pub fn scope_for(&self, command: &str, category: Option<&str>) -> CommandScope {
+        self.groups
+            .get(command)
+            .or_else(|| category.and_then(|c| self.groups.get(c)))
+            .copied()
+            .unwrap_or(self.default_scope)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandScope {
+    /// Available in every guild the bot is in, but can take a while to update.
+    Global,
+    /// Registered in each guild separately when the bot joins it.
+    #[default]
+    Guild,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AuditLogConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub channel: ChannelId,
+}
+
+/// A REST API for external tools, such as stream overlays, to read the stream index from
+/// and schedule announcements through. Command metrics are served at `/metrics` for Prometheus.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default = "WebApiConfig::default_address")]
+    pub address: SocketAddr,
+
+    /// Sent by clients as a bearer token. Every request is refused while it's empty.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl WebApiConfig {
+    fn default_address() -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 8421))
+    }
+}
+
+impl Default for WebApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: Self::default_address(),
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NetworkConfig {
+    /// Proxy to send all API requests through, ex. `socks5://localhost:9050`.
+    #[serde(default, alias = "proxy")]
+    pub proxy_url: Option<String>,
+
+    /// PEM files with extra root certificates to trust, for intercepting proxies.
+    #[serde(default)]
+    pub extra_ca_certs: Vec<PathBuf>,
+
+    /// Request timeout in seconds.
+    #[serde(default = "NetworkConfig::default_timeout")]
+    pub timeout: u64,
+
+    #[serde(default = "NetworkConfig::default_max_retries")]
+    pub max_retries: u32,
+
+    /// Rate limits keyed by host, ex. `"api.imgflip.com"`.
+    #[serde(default)]
+    pub rate_limits: HashMap<String, HostRateLimit>,
+}
+
+impl NetworkConfig {
+    /// The address of the proxy if it is a SOCKS5 proxy, which is the only kind
+    /// the streaming clients are able to connect through.
+    #[must_use]
+    pub fn socks5_proxy(&self) -> Option<&str> {
+        let proxy = self.proxy_url.as_deref()?;
+
+        proxy
+            .strip_prefix("socks5://")
+            .or_else(|| proxy.strip_prefix("socks5h://"))
+    }
+
+    fn default_timeout() -> u64 {
+        30
+    }
+
+    fn default_max_retries() -> u32 {
+        3
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            extra_ca_certs: Vec::new(),
+            timeout: Self::default_timeout(),
+            max_retries: Self::default_max_retries(),
+            rate_limits: HashMap::new(),
+        }
+    }
+}
+
+/// An S3-compatible bucket, such as AWS S3 or MinIO, that chat archives are uploaded to.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ObjectStorageConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// The URL of the storage service, ex. `https://s3.eu-north-1.amazonaws.com`.
+    pub endpoint: String,
+    #[serde(default = "ObjectStorageConfig::default_region")]
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+
+    /// Address buckets as `endpoint/bucket` instead of `bucket.endpoint`, which MinIO needs.
+    #[serde(default)]
+    pub path_style: bool,
+
+    /// The URL objects are linked with, ex. a CDN in front of the bucket.
+    /// Defaults to the address of the bucket.
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+impl ObjectStorageConfig {
+    fn default_region() -> String {
+        "us-east-1".to_owned()
+    }
+}
+
+/// Mirrors the talent icons and stream thumbnails used in embeds to the object storage,
+/// so that the embeds keep their images when the originals stop being served.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ImageCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How many times an image has to be used before it's mirrored.
+    #[serde(default = "ImageCacheConfig::default_min_uses")]
+    pub min_uses: u32,
+
+    /// How long an image is assumed to still be available after checking that it is.
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "ImageCacheConfig::default_check_interval")]
+    pub check_interval: Duration,
+}
+
+impl ImageCacheConfig {
+    fn default_min_uses() -> u32 {
+        3
+    }
+
+    fn default_check_interval() -> Duration {
+        Duration::minutes(10)
+    }
+}
+
+impl Default for ImageCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_uses: Self::default_min_uses(),
+            check_interval: Self::default_check_interval(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct LoggingConfig {
+    /// Log filter using the same syntax as `RUST_LOG`, ex. `"info,apis::holo_api=trace"`.
+    /// It is applied on startup and whenever the bot receives SIGHUP.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HostRateLimit {
+    pub requests: u32,
+    /// The period in seconds over which `requests` are allowed.
+    pub period: u64,
+}
+
+/// Embed colours, for the embeds that don't have a talent colour to use.
+/// Event colours left at 0 fall back to `default`, or `error` for moderation logs.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ThemeConfig {
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default = "ThemeConfig::default_colour")]
+    pub default: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default = "ThemeConfig::default_error_colour")]
+    pub error: u32,
+
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub tweets: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub live_alerts: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub schedule_updates: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub schedule_digests: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub birthdays: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub stream_chat: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub moderation: u32,
+    #[serde(with = "SerHex::<CompactPfx>")]
+    #[serde(default)]
+    pub support: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbedEvent {
+    Tweet,
+    LiveAlert,
+    ScheduleUpdate,
+    ScheduleDigest,
+    Birthday,
+    /// Archival notices, VOD summaries and slowmode announcements.
+    StreamChat,
+    /// Content filtering and temp mute logs.
+    Moderation,
+    /// `/donate` and thanks for donations.
+    Support,
+}
+
+impl ThemeConfig {
+    #[must_use]
+    pub fn colour(&self, event: EmbedEvent) -> u32 {
+        let colour = match event {
+            EmbedEvent::Tweet => self.tweets,
+            EmbedEvent::LiveAlert => self.live_alerts,
+            EmbedEvent::ScheduleUpdate => self.schedule_updates,
+            EmbedEvent::ScheduleDigest => self.schedule_digests,
+            EmbedEvent::Birthday => self.birthdays,
+            EmbedEvent::StreamChat => self.stream_chat,
+            EmbedEvent::Moderation => self.moderation,
+            EmbedEvent::Support => self.support,
+        };
+
+        match (colour, event) {
+            (0, EmbedEvent::Moderation) => self.error,
+            (0, _) => self.default,
+            (colour, _) => colour,
+        }
+    }
+
+    pub(crate) fn default_colour() -> u32 {
+        0x5F_DD_EF
+    }
+
+    fn default_error_colour() -> u32 {
+        Colour::RED.0
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            default: Self::default_colour(),
+            error: Self::default_error_colour(),
+            tweets: 0,
+            live_alerts: 0,
+            schedule_updates: 0,
+            schedule_digests: 0,
+            birthdays: 0,
+            stream_chat: 0,
+            moderation: 0,
+            support: 0,
+        }
+    }
+}
@@ -1,5 +1,7 @@
+mod retry;
 mod segmented_message;
+mod snapshot;
 mod traits;
 mod types;
 
-pub use self::{segmented_message::*, traits::*, types::*};
+pub use self::{retry::*, segmented_message::*, snapshot::*, traits::*, types::*};
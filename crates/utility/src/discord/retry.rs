@@ -0,0 +1,196 @@
+use std::{future::Future, time::Duration};
+
+use backoff::ExponentialBackoff;
+use serenity::http::error::Error as HttpError;
+use tracing::warn;
+
+/// How long a request is retried for before giving up.
+const MAX_RETRY_TIME: Duration = Duration::from_secs(5 * 60);
+/// How long an interaction response is retried for, leaving time for it to arrive.
+const MAX_INTERACTION_RETRY_TIME: Duration = Duration::from_millis(2500);
+
+/// How a Discord request can safely be retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    /// Reads, edits and deletes, which can be sent again when it's unknown whether they went
+    /// through, such as when Discord fails with a server error or the connection drops.
+    Idempotent,
+    /// Requests that create something, such as sending a message. They're only retried when
+    /// Discord rate limited them, since they might otherwise end up being done twice.
+    NotIdempotent,
+    /// Responses to an interaction, which Discord only accepts within three seconds of it.
+    /// They're only retried when rate limited, and only while there's still time.
+    InteractionResponse,
+}
+
+impl RequestKind {
+    /// Whether the request can be sent again after failing with the error.
+    #[must_use]
+    pub fn can_retry(self, error: &serenity::Error) -> bool {
+        self.retries(Failure::of(error))
+    }
+
+    fn retries(self, failure: Failure) -> bool {
+        match failure {
+            Failure::RateLimited => true,
+            Failure::Temporary => self == Self::Idempotent,
+            Failure::Permanent => false,
+        }
+    }
+}
+
+/// How a request failed, as far as sending it again is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// Discord refused the request because of a rate limit, so it wasn't done.
+    RateLimited,
+    /// Discord failed with a server error or the connection dropped, so it might have been done.
+    Temporary,
+    Permanent,
+}
+
+impl Failure {
+    fn of(error: &serenity::Error) -> Self {
+        if is_rate_limited(error) {
+            Self::RateLimited
+        } else if is_temporary(error) {
+            Self::Temporary
+        } else {
+            Self::Permanent
+        }
+    }
+}
+
+/// Sends a Discord request built by `request`, retrying it with exponential backoff
+/// for as long as `kind` allows.
+pub async fn retry_discord_request<T, F, Fut>(
+    kind: RequestKind,
+    mut request: F,
+) -> serenity::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = serenity::Result<T>>,
+{
+    let config = match kind {
+        RequestKind::InteractionResponse => ExponentialBackoff {
+            initial_interval: Duration::from_millis(250),
+            max_interval: Duration::from_secs(1),
+            max_elapsed_time: Some(MAX_INTERACTION_RETRY_TIME),
+            ..ExponentialBackoff::default()
+        },
+        RequestKind::Idempotent | RequestKind::NotIdempotent => ExponentialBackoff {
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(60),
+            max_elapsed_time: Some(MAX_RETRY_TIME),
+            ..ExponentialBackoff::default()
+        },
+    };
+
+    backoff::future::retry_notify(
+        config,
+        || {
+            let response = request();
+
+            async move {
+                response.await.map_err(|e| {
+                    if kind.can_retry(&e) {
+                        backoff::Error::transient(e)
+                    } else {
+                        backoff::Error::permanent(e)
+                    }
+                })
+            }
+        },
+        |e, delay: Duration| {
+            warn!(
+                ?kind,
+                delay = delay.as_secs_f32(),
+                "Discord request failed, retrying: {:?}",
+                e
+            );
+        },
+    )
+    .await
+}
+
+/// Whether Discord refused the request because of a rate limit, so that it wasn't done.
+#[must_use]
+pub fn is_rate_limited(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(e) => match e.as_ref() {
+            HttpError::UnsuccessfulRequest(r) => r.status_code.as_u16() == 429,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether the request failed in a way that might be temporary,
+/// but might still have been done by Discord.
+fn is_temporary(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(e) => match e.as_ref() {
+            HttpError::UnsuccessfulRequest(r) => (500..=599).contains(&r.status_code.as_u16()),
+            HttpError::Request(_) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::http::{error::ErrorResponse, StatusCode};
+
+    use super::*;
+
+    fn unsuccessful(status: u16) -> serenity::Error {
+        HttpError::UnsuccessfulRequest(ErrorResponse {
+            status_code: StatusCode::from_u16(status).unwrap(),
+            url: "https://discord.com/api/v10/channels/1/messages"
+                .parse()
+                .unwrap(),
+            error: serde_json::from_value(serde_json::json!({
+                "code": 0,
+                "message": "Failed",
+            }))
+            .unwrap(),
+        })
+        .into()
+    }
+
+    #[test]
+    fn failures_are_classified_by_status() {
+        assert_eq!(Failure::of(&unsuccessful(429)), Failure::RateLimited);
+        assert_eq!(Failure::of(&unsuccessful(500)), Failure::Temporary);
+        assert_eq!(Failure::of(&unsuccessful(503)), Failure::Temporary);
+        assert_eq!(Failure::of(&unsuccessful(403)), Failure::Permanent);
+        assert_eq!(Failure::of(&unsuccessful(404)), Failure::Permanent);
+    }
+
+    #[test]
+    fn only_idempotent_requests_retry_temporary_failures() {
+        // Server errors and dropped connections are both temporary failures.
+        assert!(RequestKind::Idempotent.retries(Failure::Temporary));
+        assert!(!RequestKind::NotIdempotent.retries(Failure::Temporary));
+        assert!(!RequestKind::InteractionResponse.retries(Failure::Temporary));
+
+        assert!(RequestKind::Idempotent.can_retry(&unsuccessful(502)));
+        assert!(!RequestKind::NotIdempotent.can_retry(&unsuccessful(502)));
+        assert!(!RequestKind::InteractionResponse.can_retry(&unsuccessful(502)));
+    }
+
+    #[test]
+    fn every_request_retries_rate_limits_but_not_permanent_failures() {
+        for kind in [
+            RequestKind::Idempotent,
+            RequestKind::NotIdempotent,
+            RequestKind::InteractionResponse,
+        ] {
+            assert!(kind.retries(Failure::RateLimited), "{kind:?}");
+            assert!(kind.can_retry(&unsuccessful(429)), "{kind:?}");
+            assert!(!kind.retries(Failure::Permanent), "{kind:?}");
+            assert!(!kind.can_retry(&unsuccessful(400)), "{kind:?}");
+        }
+    }
+}
@@ -1,6 +1,5 @@
-use std::{fmt::Display, sync::Arc};
+use std::{fmt::Display, ops::Range, sync::Arc};
 
-use anyhow::Context as _;
 use either::Either;
 use itertools::{EitherOrBoth, Itertools};
 use num::Integer;
@@ -11,12 +10,14 @@ use serenity::{
 };
 use tokio::sync::Mutex;
 
-use crate::here;
+use super::{retry_discord_request, RequestKind};
+use crate::config::ThemeConfig;
 
 pub type EmbedFormatter<Arg> = Box<dyn Fn(&mut CreateEmbed, usize, &[Arg]) + Send + Sync>;
 
 pub type SegmentLinkFn<Arg> = Box<dyn Fn(usize, &Message, &[Arg]) -> String + Send + Sync>;
 pub type ElementFormatter<D, Arg> = Box<dyn Fn(&D, &[Arg]) -> String + Send + Sync>;
+pub type FailureHandler = Box<dyn Fn(&SegmentFailure) + Send + Sync>;
 
 pub struct SegmentedMessage<D, Arg = String> {
     data: Vec<D>,
@@ -32,6 +33,45 @@ pub struct SegmentedMessage<D, Arg = String> {
     element_formatter: ElementFormatter<D, Arg>,
 
     args: Vec<Arg>,
+
+    failure_handler: Option<FailureHandler>,
+    progress: SegmentProgress,
+}
+
+/// What an unfinished [`SegmentedMessage::create`] has posted so far.
+#[derive(Debug)]
+struct SegmentProgress<M = Message> {
+    index_pages: Vec<M>,
+    segments: Vec<M>,
+    /// How many of the index pages have been filled in with links.
+    indexed: usize,
+}
+
+impl<M> SegmentProgress<M> {
+    /// The index pages that still have to be reserved, out of the `needed` ones.
+    fn index_pages_to_reserve(&self, needed: usize) -> Range<usize> {
+        self.index_pages.len()..needed
+    }
+
+    /// The segments that still have to be posted, out of the `needed` ones.
+    fn segments_to_post(&self, needed: usize) -> Range<usize> {
+        self.segments.len()..needed
+    }
+
+    /// The reserved index pages that still have to be filled in with links.
+    fn index_pages_to_fill(&self) -> Range<usize> {
+        self.indexed..self.index_pages.len()
+    }
+}
+
+impl<M> Default for SegmentProgress<M> {
+    fn default() -> Self {
+        Self {
+            index_pages: Vec::new(),
+            segments: Vec::new(),
+            indexed: 0,
+        }
+    }
 }
 
 impl<D, Arg> SegmentedMessage<D, Arg>
@@ -97,7 +137,48 @@ where
         self
     }
 
-    pub async fn create(&mut self, ctx: &Context, ch: Arc<Mutex<ChannelId>>) -> anyhow::Result<()> {
+    /// Called with the part that failed when posting the segments fails.
+    pub fn on_failure(&'_ mut self, handler: FailureHandler) -> &'_ mut Self {
+        self.failure_handler = Some(handler);
+        self
+    }
+
+    /// How many segments have been posted so far by an unfinished [`create`](Self::create).
+    #[must_use]
+    pub fn segments_posted(&self) -> usize {
+        self.progress.segments.len()
+    }
+
+    /// Posts the segments, returning the first message, which links to the rest.
+    ///
+    /// Requests that are rate limited are retried. If posting still fails, the error is a
+    /// [`SegmentFailure`] saying which part failed, and calling this again resumes from there
+    /// instead of posting the segments that were already posted again.
+    pub async fn create(
+        &mut self,
+        ctx: &Context,
+        ch: Arc<Mutex<ChannelId>>,
+    ) -> anyhow::Result<Message> {
+        let log_ch = ch.lock().await;
+        let result = self.post(ctx, *log_ch).await;
+        drop(log_ch);
+
+        match result {
+            Ok(message) => {
+                self.progress = SegmentProgress::default();
+                Ok(message)
+            }
+            Err(failure) => {
+                if let Some(handler) = &self.failure_handler {
+                    handler(&failure);
+                }
+
+                Err(failure.into())
+            }
+        }
+    }
+
+    async fn post(&mut self, ctx: &Context, channel: ChannelId) -> Result<Message, SegmentFailure> {
         let data_iter = match self.order {
             DataOrder::Normal => Either::Left(self.data.iter()),
             DataOrder::Reverse => Either::Right(self.data.iter().rev()),
@@ -119,52 +200,62 @@ where
             })
             .collect::<Vec<String>>();
 
-        let log_ch = ch.lock().await;
-
         let max_chunks_per_message = match &self.position {
             SegmentDataPosition::Description => 1,
             SegmentDataPosition::Fields => Self::MAX_TOTAL_BYTES / Self::MAX_FIELD_SIZE,
         };
 
         if chunks.len() <= max_chunks_per_message {
-            self.create_segment(ctx, *log_ch, 0, &chunks, &self.index_fmt)
-                .await?;
-
-            return Ok(());
+            return self
+                .create_segment(ctx, channel, 0, &chunks, &self.index_fmt)
+                .await
+                .map_err(|e| SegmentFailure::new(SegmentPart::Segment(0), 1, e));
         }
 
-        let approx_segments_needed =
-            <usize as Integer>::div_ceil(&chunks.len(), &max_chunks_per_message);
+        let segments_needed = <usize as Integer>::div_ceil(&chunks.len(), &max_chunks_per_message);
         let index_pages_needed =
-            <usize as Integer>::div_ceil(&approx_segments_needed, &Self::LINKS_PER_INDEX_PAGE);
-
-        let mut index_pages = Vec::with_capacity(index_pages_needed);
-
-        for i in 0..index_pages_needed {
-            index_pages.push(
-                log_ch
-                    .send_message(&ctx.http, |m| {
-                        m.content(format!("Reserved index page {}", i + 1))
-                    })
-                    .await?,
-            );
-        }
+            <usize as Integer>::div_ceil(&segments_needed, &Self::LINKS_PER_INDEX_PAGE);
 
-        let mut log_message_links = Vec::with_capacity(approx_segments_needed);
+        for i in self.progress.index_pages_to_reserve(index_pages_needed) {
+            let page = retry_discord_request(RequestKind::NotIdempotent, || {
+                channel.send_message(&ctx.http, |m| {
+                    m.content(format!("Reserved index page {}", i + 1))
+                })
+            })
+            .await
+            .map_err(|e| SegmentFailure::new(SegmentPart::IndexPage(i), segments_needed, e))?;
 
-        for (i, chunk) in chunks.chunks(max_chunks_per_message).enumerate() {
-            log_message_links.push(
-                self.create_segment(ctx, *log_ch, i, chunk, &self.segment_fmt)
-                    .await?,
-            );
+            self.progress.index_pages.push(page);
         }
 
-        drop(log_ch);
+        let first_message = self
+            .progress
+            .index_pages
+            .first()
+            .cloned()
+            .expect("At least one index page is needed for several segments.");
 
-        let indices = log_message_links
-            .into_iter()
+        let to_post = self.progress.segments_to_post(segments_needed);
+        let remaining = chunks
+            .chunks(max_chunks_per_message)
             .enumerate()
-            .map(|(i, msg)| (self.index_link_fn)(i, &msg, &self.args))
+            .filter(|(i, _)| to_post.contains(i));
+
+        for (i, chunk) in remaining {
+            let segment = self
+                .create_segment(ctx, channel, i, chunk, &self.segment_fmt)
+                .await
+                .map_err(|e| SegmentFailure::new(SegmentPart::Segment(i), segments_needed, e))?;
+
+            self.progress.segments.push(segment);
+        }
+
+        let indices = self
+            .progress
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, msg)| (self.index_link_fn)(i, msg, &self.args))
             .coalesce(|a, b| {
                 if a.len() + b.len() <= Self::MAX_DESCRIPTION_SIZE {
                     Ok([a, b].concat())
@@ -174,33 +265,52 @@ where
             })
             .collect::<Vec<String>>();
 
-        assert!(indices.len() <= index_pages.len());
+        assert!(indices.len() <= self.progress.index_pages.len());
 
-        let indices = index_pages
+        let to_fill = self.progress.index_pages_to_fill();
+        let indices = self
+            .progress
+            .index_pages
+            .clone()
             .into_iter()
             .zip_longest(indices.into_iter())
             .enumerate()
+            .filter(|(i, _)| to_fill.contains(i))
             .collect::<Vec<(_, _)>>();
 
         let prev_position = self.position;
         self.position = SegmentDataPosition::Description;
 
+        let mut result = Ok(());
+
         for (i, index) in indices {
-            match index {
+            let posted = match index {
                 // If all the links fit in previous pages, delete this one.
-                EitherOrBoth::Left(msg) => msg.delete(&ctx).await.context(here!()),
+                EitherOrBoth::Left(msg) => {
+                    retry_discord_request(RequestKind::Idempotent, || msg.delete(ctx)).await
+                }
                 EitherOrBoth::Right(_) => unreachable!(),
-                EitherOrBoth::Both(mut msg, link) => self
-                    .edit_segment(ctx, &mut msg, i, &[link], &self.index_fmt)
-                    .await
-                    .context(here!())
-                    .map(|_| ()),
-            }?;
+                EitherOrBoth::Both(msg, link) => {
+                    self.edit_segment(ctx, &msg, i, &[link], &self.index_fmt)
+                        .await
+                }
+            };
+
+            if let Err(e) = posted {
+                result = Err(SegmentFailure::new(
+                    SegmentPart::IndexPage(i),
+                    segments_needed,
+                    e,
+                ));
+                break;
+            }
+
+            self.progress.indexed += 1;
         }
 
         self.position = prev_position;
 
-        Ok(())
+        result.map(|_| first_message)
     }
 
     #[allow(clippy::manual_async_fn)]
@@ -212,28 +322,31 @@ where
         i: usize,
         data: &[String],
         formatter: &Option<EmbedFormatter<Arg>>,
-    ) -> anyhow::Result<Message> {
-        ch.send_message(&ctx.http, |m| {
-            m.embed(|e| self.format_segment_embed(e, i, data, formatter))
+    ) -> serenity::Result<Message> {
+        retry_discord_request(RequestKind::NotIdempotent, || {
+            ch.send_message(&ctx.http, |m| {
+                m.embed(|e| self.format_segment_embed(e, i, data, formatter))
+            })
         })
         .await
-        .context(here!())
     }
 
     async fn edit_segment(
         &self,
         ctx: &Context,
-        msg: &mut Message,
+        msg: &Message,
         i: usize,
         data: &[String],
         formatter: &Option<EmbedFormatter<Arg>>,
-    ) -> anyhow::Result<()> {
-        msg.edit(ctx, |m| {
-            m.content(String::new())
-                .embed(|e| self.format_segment_embed(e, i, data, formatter))
+    ) -> serenity::Result<()> {
+        retry_discord_request(RequestKind::Idempotent, || {
+            msg.channel_id.edit_message(&ctx.http, msg.id, |m| {
+                m.content(String::new())
+                    .embed(|e| self.format_segment_embed(e, i, data, formatter))
+            })
         })
         .await
-        .context(here!())
+        .map(|_| ())
     }
 
     fn format_segment_embed<'a>(
@@ -264,7 +377,7 @@ impl<D: Display, Arg> Default for SegmentedMessage<D, Arg> {
             data: Vec::new(),
             args: Vec::new(),
 
-            colour: 6_282_735,
+            colour: ThemeConfig::default_colour(),
 
             order: DataOrder::Normal,
             position: SegmentDataPosition::Fields,
@@ -274,10 +387,64 @@ impl<D: Display, Arg> Default for SegmentedMessage<D, Arg> {
 
             index_link_fn: Box::new(|i, msg, _| format!("[Segment {}]({})\n", i + 1, msg.link())),
             element_formatter: Box::new(|d, _| d.to_string()),
+
+            failure_handler: None,
+            progress: SegmentProgress::default(),
+        }
+    }
+}
+
+/// The part of a segmented message that couldn't be posted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SegmentPart {
+    /// An index page, which links to the segments.
+    IndexPage(usize),
+    Segment(usize),
+}
+
+impl Display for SegmentPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IndexPage(i) => write!(f, "index page {}", i + 1),
+            Self::Segment(i) => write!(f, "segment {}", i + 1),
         }
     }
 }
 
+/// Why [`SegmentedMessage::create`] failed.
+#[derive(Debug)]
+pub struct SegmentFailure {
+    pub part: SegmentPart,
+    pub total_segments: usize,
+    pub source: serenity::Error,
+}
+
+impl SegmentFailure {
+    fn new(part: SegmentPart, total_segments: usize, source: serenity::Error) -> Self {
+        Self {
+            part,
+            total_segments,
+            source,
+        }
+    }
+}
+
+impl Display for SegmentFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to post {} of a message with {} segments.",
+            self.part, self.total_segments
+        )
+    }
+}
+
+impl std::error::Error for SegmentFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum SegmentDataPosition {
     Description,
@@ -289,3 +456,44 @@ pub enum DataOrder {
     Normal,
     Reverse,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(index_pages: usize, segments: usize, indexed: usize) -> SegmentProgress<usize> {
+        SegmentProgress {
+            index_pages: (0..index_pages).collect(),
+            segments: (0..segments).collect(),
+            indexed,
+        }
+    }
+
+    #[test]
+    fn new_messages_post_everything() {
+        let progress = SegmentProgress::<usize>::default();
+
+        assert_eq!(progress.index_pages_to_reserve(2), 0..2);
+        assert_eq!(progress.segments_to_post(40), 0..40);
+        assert!(progress.index_pages_to_fill().is_empty());
+    }
+
+    #[test]
+    fn failed_messages_resume_where_they_stopped() {
+        // Failed while posting the fourth segment.
+        let progress = stored(2, 3, 0);
+        assert!(progress.index_pages_to_reserve(2).is_empty());
+        assert_eq!(progress.segments_to_post(40), 3..40);
+        assert_eq!(progress.index_pages_to_fill(), 0..2);
+
+        // Failed while reserving the second index page.
+        let progress = stored(1, 0, 0);
+        assert_eq!(progress.index_pages_to_reserve(2), 1..2);
+        assert_eq!(progress.segments_to_post(40), 0..40);
+
+        // Failed while filling in the second index page.
+        let progress = stored(2, 40, 1);
+        assert!(progress.segments_to_post(40).is_empty());
+        assert_eq!(progress.index_pages_to_fill(), 1..2);
+    }
+}
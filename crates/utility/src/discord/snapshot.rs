@@ -0,0 +1,78 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use serenity::builder::CreateEmbed;
+
+/// Set to overwrite golden files with the current output instead of comparing against them.
+pub const UPDATE_GOLDEN_VAR: &str = "UPDATE_GOLDEN";
+
+/// Serializes the embed with every object's keys sorted, so the same embed always gives the same text.
+#[must_use]
+pub fn canonical_embed(embed: &CreateEmbed) -> String {
+    let map = embed
+        .0
+        .iter()
+        .map(|(k, v)| ((*k).to_owned(), v.clone()))
+        .collect();
+
+    canonical_json(&Value::Object(map))
+}
+
+#[must_use]
+pub fn canonical_json(value: &Value) -> String {
+    let mut json =
+        serde_json::to_string_pretty(&sort_keys(value)).expect("JSON values always serialize");
+
+    json.push('\n');
+    json
+}
+
+/// Compares the value against the golden file, or rewrites the file if [`UPDATE_GOLDEN_VAR`] is set.
+///
+/// # Panics
+///
+/// Panics if the file is missing or differs from the value, so it can be used in tests.
+pub fn assert_golden(path: impl AsRef<Path>, value: &Value) {
+    let path = path.as_ref();
+    let actual = canonical_json(value);
+
+    if std::env::var_os(UPDATE_GOLDEN_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create golden file directory.");
+        }
+
+        fs::write(path, actual).expect("Failed to write golden file.");
+        return;
+    }
+
+    let expected = fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read golden file {}: {e}. Run with {UPDATE_GOLDEN_VAR}=1 to create it.",
+            path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "Output differs from golden file {}. Run with {UPDATE_GOLDEN_VAR}=1 to update it.\n\nExpected:\n{expected}\nActual:\n{actual}",
+        path.display()
+    );
+}
+
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_unstable_by_key(|(k, _)| *k);
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sort_keys(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.iter().map(sort_keys).collect()),
+        value => value.clone(),
+    }
+}
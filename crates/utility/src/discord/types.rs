@@ -1,9 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
 use anyhow::Context;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
 use holodex::model::id::VideoId;
 use rusqlite::ToSql;
-use serenity::model::id::{EmojiId, StickerId};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{EmojiId, GuildId, RoleId, StickerId, UserId};
 use tokio::sync::oneshot;
 
 use crate::{
@@ -91,3 +94,197 @@ impl DatabaseOperations<'_, VideoId> for HashSet<VideoId> {
             .map(|s| s.parse().context(here!()))?
     }
 }
+
+impl DatabaseOperations<'_, (UserId, Tz)> for HashMap<UserId, Tz> {
+    type LoadItemContainer = Self;
+
+    const TABLE_NAME: &'static str = "UserTimezones";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("user_id", "INTEGER", Some("PRIMARY KEY")),
+        ("timezone", "TEXT", Some("NOT NULL")),
+    ];
+
+    fn into_row((user, timezone): (UserId, Tz)) -> Vec<Box<dyn ToSql>> {
+        vec![Box::new(*user.as_u64()), Box::new(timezone.name())]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<(UserId, Tz)> {
+        Ok((
+            UserId(row.get("user_id").context(here!())?),
+            row.get::<_, String>("timezone")
+                .context(here!())?
+                .parse()
+                .map_err(|e| anyhow::anyhow!("{}", e))
+                .context(here!())?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How many times a command can be used by a member within a period.
+pub struct CommandRateLimit {
+    pub uses: u32,
+    pub period: Duration,
+}
+
+impl DatabaseOperations<'_, ((GuildId, String), CommandRateLimit)>
+    for HashMap<(GuildId, String), CommandRateLimit>
+{
+    type LoadItemContainer = Self;
+
+    const TRUNCATE_TABLE: bool = true;
+    const TABLE_NAME: &'static str = "CommandRateLimits";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("guild_id", "INTEGER", Some("NOT NULL")),
+        ("command", "TEXT", Some("NOT NULL")),
+        ("uses", "INTEGER", Some("NOT NULL")),
+        ("period", "INTEGER", Some("NOT NULL")),
+    ];
+
+    fn into_row(
+        ((guild, command), limit): ((GuildId, String), CommandRateLimit),
+    ) -> Vec<Box<dyn ToSql>> {
+        vec![
+            Box::new(*guild.as_u64()),
+            Box::new(command),
+            Box::new(limit.uses),
+            Box::new(limit.period.num_seconds()),
+        ]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<((GuildId, String), CommandRateLimit)> {
+        Ok((
+            (
+                GuildId(row.get("guild_id").context(here!())?),
+                row.get("command").context(here!())?,
+            ),
+            CommandRateLimit {
+                uses: row.get("uses").context(here!())?,
+                period: Duration::seconds(row.get("period").context(here!())?),
+            },
+        ))
+    }
+}
+
+impl DatabaseOperations<'_, (GuildId, RoleId)> for HashMap<GuildId, RoleId> {
+    type LoadItemContainer = Self;
+
+    const TRUNCATE_TABLE: bool = true;
+    const TABLE_NAME: &'static str = "RateLimitBypassRoles";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("guild_id", "INTEGER", Some("PRIMARY KEY")),
+        ("role_id", "INTEGER", Some("NOT NULL")),
+    ];
+
+    fn into_row((guild, role): (GuildId, RoleId)) -> Vec<Box<dyn ToSql>> {
+        vec![Box::new(*guild.as_u64()), Box::new(*role.as_u64())]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<(GuildId, RoleId)> {
+        Ok((
+            GuildId(row.get("guild_id").context(here!())?),
+            RoleId(row.get("role_id").context(here!())?),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandUse {
+    pub guild: GuildId,
+    pub user: UserId,
+    pub command: String,
+    pub used_at: DateTime<Utc>,
+}
+
+impl DatabaseOperations<'_, CommandUse> for Vec<CommandUse> {
+    type LoadItemContainer = Self;
+
+    const TRUNCATE_TABLE: bool = true;
+    const TABLE_NAME: &'static str = "CommandUsage";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("guild_id", "INTEGER", Some("NOT NULL")),
+        ("user_id", "INTEGER", Some("NOT NULL")),
+        ("command", "TEXT", Some("NOT NULL")),
+        ("used_at", "INTEGER", Some("NOT NULL")),
+    ];
+
+    fn into_row(usage: CommandUse) -> Vec<Box<dyn ToSql>> {
+        vec![
+            Box::new(*usage.guild.as_u64()),
+            Box::new(*usage.user.as_u64()),
+            Box::new(usage.command),
+            Box::new(usage.used_at.timestamp()),
+        ]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<CommandUse> {
+        Ok(CommandUse {
+            guild: GuildId(row.get("guild_id").context(here!())?),
+            user: UserId(row.get("user_id").context(here!())?),
+            command: row.get("command").context(here!())?,
+            used_at: Utc
+                .timestamp_opt(row.get("used_at").context(here!())?, 0)
+                .single()
+                .context(here!())?,
+        })
+    }
+}
+
+/// A reply an easter egg can be answered with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum EasterEggResponse {
+    Text(String),
+    Image(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A fun reply to messages matching a trigger, picked at random from the responses.
+pub struct EasterEgg {
+    pub responses: Vec<EasterEggResponse>,
+    pub cooldown: Duration,
+    pub enabled: bool,
+}
+
+impl DatabaseOperations<'_, ((GuildId, String), EasterEgg)>
+    for HashMap<(GuildId, String), EasterEgg>
+{
+    type LoadItemContainer = Self;
+
+    const TRUNCATE_TABLE: bool = true;
+    const TABLE_NAME: &'static str = "EasterEggs";
+    const COLUMNS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("guild_id", "INTEGER", Some("NOT NULL")),
+        ("trigger", "TEXT", Some("NOT NULL")),
+        ("responses", "TEXT", Some("NOT NULL")),
+        ("cooldown", "INTEGER", Some("NOT NULL")),
+        ("enabled", "INTEGER", Some("NOT NULL")),
+    ];
+
+    fn into_row(((guild, trigger), egg): ((GuildId, String), EasterEgg)) -> Vec<Box<dyn ToSql>> {
+        vec![
+            Box::new(*guild.as_u64()),
+            Box::new(trigger),
+            Box::new(serde_json::to_string(&egg.responses).unwrap_or_else(|_| "[]".to_owned())),
+            Box::new(egg.cooldown.num_seconds()),
+            Box::new(egg.enabled),
+        ]
+    }
+
+    fn from_row(row: &rusqlite::Row) -> anyhow::Result<((GuildId, String), EasterEgg)> {
+        Ok((
+            (
+                GuildId(row.get("guild_id").context(here!())?),
+                row.get("trigger").context(here!())?,
+            ),
+            EasterEgg {
+                responses: serde_json::from_str(
+                    &row.get::<_, String>("responses").context(here!())?,
+                )
+                .context(here!())?,
+                cooldown: Duration::seconds(row.get("cooldown").context(here!())?),
+                enabled: row.get("enabled").context(here!())?,
+            },
+        ))
+    }
+}
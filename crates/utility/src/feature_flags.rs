@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    sync::{Mutex, RwLock},
+};
+
+use anyhow::{anyhow, Context};
+use once_cell::sync::OnceCell;
+use poise::ChoiceParameter;
+use serenity::model::id::GuildId;
+use strum::{Display, EnumIter, EnumString};
+use tracing::error;
+
+use crate::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+static FEATURE_FLAGS: OnceCell<FeatureFlags> = OnceCell::new();
+
+/// Features that can be turned off per guild. Flags can only turn off features that are
+/// enabled in the config, they can't turn on ones that are disabled there.
+#[derive(
+    Debug, Clone, Copy, Hash, PartialEq, Eq, Display, EnumString, EnumIter, ChoiceParameter,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Feature {
+    #[name = "Live alerts"]
+    LiveAlerts,
+    #[name = "Schedule updates"]
+    ScheduleUpdates,
+    #[name = "Birthday alerts"]
+    Birthdays,
+    #[name = "Tweets"]
+    Tweets,
+    #[name = "Quotes"]
+    Quotes,
+    #[name = "Emotes"]
+    Emotes,
+    #[name = "Memes"]
+    Memes,
+    #[name = "Polls"]
+    Polls,
+    #[name = "Giveaways"]
+    Giveaways,
+    #[name = "Announcements"]
+    Announcements,
+}
+
+/// Loads the flags set for each guild, features are enabled everywhere before this.
+pub fn init(database: &Database) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+
+    FEATURE_FLAGS
+        .set(FeatureFlags::load(handle).context(here!())?)
+        .map_err(|_| anyhow!("Feature flags have already been initialized!"))
+}
+
+pub fn is_enabled(guild: GuildId, feature: Feature) -> bool {
+    FEATURE_FLAGS
+        .get()
+        .map_or(true, |flags| flags.is_enabled(guild, feature))
+}
+
+pub fn set_enabled(guild: GuildId, feature: Feature, enabled: bool) -> anyhow::Result<()> {
+    FEATURE_FLAGS
+        .get()
+        .ok_or_else(|| anyhow!("Feature flags have not been initialized!"))?
+        .set_enabled(guild, feature, enabled)
+}
+
+/// The features that have been turned off in the guild.
+pub fn disabled(guild: GuildId) -> Vec<Feature> {
+    FEATURE_FLAGS
+        .get()
+        .map(|flags| flags.disabled(guild))
+        .unwrap_or_default()
+}
+
+struct FeatureFlags {
+    database: Mutex<DatabaseHandle>,
+    disabled: RwLock<HashSet<(GuildId, Feature)>>,
+}
+
+impl FeatureFlags {
+    const TABLE_NAME: &'static str = "FeatureFlags";
+
+    fn load(handle: DatabaseHandle) -> anyhow::Result<Self> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("guild_id", "INTEGER", Some("NOT NULL")),
+                    ("feature", "TEXT", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        let mut disabled = HashSet::new();
+
+        match &handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!(
+                        "SELECT guild_id, feature FROM {}",
+                        Self::TABLE_NAME
+                    ))
+                    .context(here!())?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((GuildId(row.get(0)?), row.get::<_, String>(1)?))
+                    })
+                    .context(here!())?;
+
+                for row in rows {
+                    let (guild, feature) = row.context(here!())?;
+
+                    // Features that have since been removed are ignored.
+                    match feature.parse() {
+                        Ok(feature) => {
+                            disabled.insert((guild, feature));
+                        }
+                        Err(_) => error!(%feature, "Unknown feature flag!"),
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            database: Mutex::new(handle),
+            disabled: RwLock::new(disabled),
+        })
+    }
+
+    fn is_enabled(&self, guild: GuildId, feature: Feature) -> bool {
+        self.disabled
+            .read()
+            .map_or(true, |d| !d.contains(&(guild, feature)))
+    }
+
+    fn disabled(&self, guild: GuildId) -> Vec<Feature> {
+        self.disabled
+            .read()
+            .map(|d| {
+                d.iter()
+                    .filter(|(g, _)| *g == guild)
+                    .map(|(_, f)| *f)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_enabled(&self, guild: GuildId, feature: Feature, enabled: bool) -> anyhow::Result<()> {
+        let handle = self
+            .database
+            .lock()
+            .map_err(|_| anyhow!("Feature flag database lock was poisoned!"))?;
+
+        match &*handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE guild_id = ? AND feature = ?",
+                        Self::TABLE_NAME
+                    ),
+                    rusqlite::params![guild.0, feature.to_string()],
+                )
+                .context(here!())?;
+
+                if !enabled {
+                    h.execute(
+                        &format!(
+                            "INSERT INTO {} (guild_id, feature) VALUES (?, ?)",
+                            Self::TABLE_NAME
+                        ),
+                        rusqlite::params![guild.0, feature.to_string()],
+                    )
+                    .context(here!())?;
+                }
+            }
+        }
+
+        let mut disabled = self
+            .disabled
+            .write()
+            .map_err(|_| anyhow!("Feature flag lock was poisoned!"))?;
+
+        if enabled {
+            disabled.remove(&(guild, feature));
+        } else {
+            disabled.insert((guild, feature));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::TempDatabase;
+
+    use super::*;
+
+    #[test]
+    fn flags_are_per_guild_and_persisted() {
+        let database = TempDatabase::new("feature_flags");
+
+        let flags = FeatureFlags::load(database.open()).unwrap();
+        flags
+            .set_enabled(GuildId(1), Feature::LiveAlerts, false)
+            .unwrap();
+
+        assert!(!flags.is_enabled(GuildId(1), Feature::LiveAlerts));
+        assert!(flags.is_enabled(GuildId(1), Feature::Tweets));
+        assert!(flags.is_enabled(GuildId(2), Feature::LiveAlerts));
+
+        let reloaded = FeatureFlags::load(database.open()).unwrap();
+        assert_eq!(reloaded.disabled(GuildId(1)), vec![Feature::LiveAlerts]);
+
+        reloaded
+            .set_enabled(GuildId(1), Feature::LiveAlerts, true)
+            .unwrap();
+        assert!(reloaded.is_enabled(GuildId(1), Feature::LiveAlerts));
+    }
+}
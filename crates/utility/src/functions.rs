@@ -285,6 +285,67 @@ pub fn try_parse_written_time_with_tz(time: &str, timezone: &Tz) -> anyhow::Resu
     Ok(time.with_timezone(&Utc))
 }
 
+/// Parses durations written like `1d 12h`, `90m` or `2w`.
+pub fn parse_duration(duration: &str) -> anyhow::Result<Duration> {
+    let mut total = 0;
+    let mut number = String::new();
+
+    for c in duration.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(anyhow!("Unknown duration unit '{c}' in \"{duration}\".")),
+        };
+
+        let amount: u64 = number
+            .parse()
+            .map_err(|_| anyhow!("Missing number before '{c}' in \"{duration}\"."))?;
+
+        total = amount.saturating_mul(unit).saturating_add(total);
+        number.clear();
+    }
+
+    if !number.is_empty() || total == 0 {
+        return Err(anyhow!(
+            "Invalid duration \"{duration}\", write it like `1d 12h` or `30m`."
+        ));
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Writes out a duration like `1d 12h` or `20m`, the same way [`parse_duration`] reads them.
+/// Seconds are only shown for durations shorter than a minute.
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    if secs < 60 {
+        return format!("{secs}s");
+    }
+
+    let parts = [
+        (secs / (24 * 60 * 60), 'd'),
+        (secs / (60 * 60) % 24, 'h'),
+        (secs / 60 % 60, 'm'),
+    ];
+
+    parts
+        .iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub fn is_default<T: Default + PartialEq>(t: &T) -> bool {
     t == &T::default()
 }
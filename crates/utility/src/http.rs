@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap, fs::File, io::BufReader, path::PathBuf, sync::Arc, time::Duration,
+};
+
+use anyhow::{anyhow, Context};
+use once_cell::sync::OnceCell;
+use tokio::time::sleep;
+use tracing::{debug_span, warn, Instrument};
+
+use crate::{config::NetworkConfig, here, rate_limiter::RateLimiter};
+
+static CLIENT: OnceCell<HttpClient> = OnceCell::new();
+
+/// Sets up the shared client from the config, this has to happen before it is first used.
+///
+/// The proxy is also exported through the standard proxy environment variables,
+/// so that clients which aren't built on the shared agent pick it up as well.
+pub fn init(config: &NetworkConfig) -> anyhow::Result<()> {
+    if let Some(proxy) = &config.proxy_url {
+        for var in ["HTTPS_PROXY", "HTTP_PROXY", "ALL_PROXY"] {
+            std::env::set_var(var, proxy);
+        }
+    }
+
+    CLIENT
+        .set(HttpClient::new(config)?)
+        .map_err(|_| anyhow!("HTTP client has already been initialized!"))
+}
+
+/// The client shared by all API integrations.
+pub fn client() -> &'static HttpClient {
+    CLIENT.get_or_init(HttpClient::default)
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    agent: ureq::Agent,
+    max_retries: u32,
+    limiters: Arc<HashMap<String, RateLimiter>>,
+}
+
+impl HttpClient {
+    const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+    pub fn new(config: &NetworkConfig) -> anyhow::Result<Self> {
+        let mut builder = Self::agent_builder(Duration::from_secs(config.timeout));
+
+        if let Some(proxy) = &config.proxy_url {
+            builder = builder.proxy(ureq::Proxy::new(proxy).context(here!())?);
+        }
+
+        if !config.extra_ca_certs.is_empty() {
+            builder = builder.tls_config(Arc::new(tls_config(&config.extra_ca_certs)?));
+        }
+
+        let limiters = config
+            .rate_limits
+            .iter()
+            .map(|(host, limit)| {
+                (
+                    host.clone(),
+                    RateLimiter::new(limit.requests, Duration::from_secs(limit.period)),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            agent: builder.build(),
+            max_retries: config.max_retries,
+            limiters: Arc::new(limiters),
+        })
+    }
+
+    /// The underlying agent, for clients that manage their own requests.
+    #[must_use]
+    pub fn agent(&self) -> &ureq::Agent {
+        &self.agent
+    }
+
+    /// Sends a request built by `send`, waiting for the rate limit of the host
+    /// and retrying if the request fails in a way that might be temporary.
+    ///
+    /// The request is sent on the blocking thread pool, since ureq blocks until the
+    /// response headers have arrived. Reading the body blocks as well.
+    pub async fn call<F>(&self, method: &str, url: &str, send: F) -> anyhow::Result<ureq::Response>
+    where
+        F: Fn(ureq::Request) -> Result<ureq::Response, ureq::Error> + Send + Sync + 'static,
+    {
+        let request = self.agent.request(method, url);
+
+        let (host, path, limiter) = self.route(&request)?;
+        let limiter = limiter.cloned();
+        let send = Arc::new(send);
+
+        let span = debug_span!("http_request", %method, %host, %path);
+
+        async move {
+            let mut attempt = 0;
+
+            loop {
+                if let Some(limiter) = &limiter {
+                    limiter.acquire().await;
+                }
+
+                let response = {
+                    let send = Arc::clone(&send);
+                    let request = request.clone();
+
+                    tokio::task::spawn_blocking(move || send(request))
+                        .await
+                        .context(here!())?
+                };
+
+                let retry_after = match response {
+                    Ok(response) => return Ok(response),
+                    Err(ureq::Error::Status(status, response))
+                        if Self::is_retryable(status) && attempt < self.max_retries =>
+                    {
+                        warn!(%status, attempt, "Request failed, retrying.");
+
+                        response
+                            .header("Retry-After")
+                            .and_then(|s| s.parse().ok())
+                            .map(Duration::from_secs)
+                    }
+                    Err(ureq::Error::Transport(e)) if attempt < self.max_retries => {
+                        warn!(error = %e, attempt, "Request failed, retrying.");
+                        None
+                    }
+                    Err(e) => return Err(e).context(here!()),
+                };
+
+                let backoff = Self::INITIAL_RETRY_DELAY * 2_u32.pow(attempt.min(6));
+                sleep(retry_after.unwrap_or(backoff)).await;
+                attempt += 1;
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// The host and path the request is sent to, and the rate limit of the host if it has one.
+    fn route(
+        &self,
+        request: &ureq::Request,
+    ) -> anyhow::Result<(String, String, Option<&RateLimiter>)> {
+        let url = request.request_url().context(here!())?;
+        let limiter = self.limiters.get(url.host());
+
+        Ok((url.host().to_owned(), url.path().to_owned(), limiter))
+    }
+
+    fn is_retryable(status: u16) -> bool {
+        status == 429 || (500..=599).contains(&status)
+    }
+
+    fn agent_builder(timeout: Duration) -> ureq::AgentBuilder {
+        ureq::builder().timeout(timeout).user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION"),
+        ))
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        let config = NetworkConfig::default();
+
+        Self {
+            agent: Self::agent_builder(Duration::from_secs(config.timeout)).build(),
+            max_retries: config.max_retries,
+            limiters: Arc::default(),
+        }
+    }
+}
+
+/// Reads every certificate from the given PEM files, in DER format.
+pub fn load_ca_certs(paths: &[PathBuf]) -> anyhow::Result<Vec<Vec<u8>>> {
+    let mut certs = Vec::new();
+
+    for path in paths {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open certificate file {}.", path.display()))?;
+
+        certs.extend(rustls_pemfile::certs(&mut BufReader::new(file)).context(here!())?);
+    }
+
+    Ok(certs)
+}
+
+/// TLS settings trusting both the native root certificates and the extra ones.
+fn tls_config(extra_ca_certs: &[PathBuf]) -> anyhow::Result<rustls::ClientConfig> {
+    let mut roots = rustls::RootCertStore::empty();
+
+    for cert in rustls_native_certs::load_native_certs().context(here!())? {
+        // Some systems ship certificates rustls can't parse, those are skipped.
+        let _ = roots.add(&rustls::Certificate(cert.0));
+    }
+
+    for cert in load_ca_certs(extra_ca_certs)? {
+        roots.add(&rustls::Certificate(cert)).context(here!())?;
+    }
+
+    Ok(rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::HostRateLimit;
+
+    use super::*;
+
+    #[test]
+    fn rate_limits_and_server_errors_are_retried() {
+        assert!(HttpClient::is_retryable(429));
+        assert!(HttpClient::is_retryable(500));
+        assert!(HttpClient::is_retryable(503));
+        assert!(HttpClient::is_retryable(599));
+
+        assert!(!HttpClient::is_retryable(200));
+        assert!(!HttpClient::is_retryable(400));
+        assert!(!HttpClient::is_retryable(404));
+        assert!(!HttpClient::is_retryable(600));
+    }
+
+    #[test]
+    fn requests_use_the_rate_limit_of_their_host() {
+        let limit = || HostRateLimit {
+            requests: 10,
+            period: 60,
+        };
+
+        let client = HttpClient::new(&NetworkConfig {
+            rate_limits: HashMap::from([
+                ("api.example.com".to_owned(), limit()),
+                ("example.com".to_owned(), limit()),
+            ]),
+            ..NetworkConfig::default()
+        })
+        .unwrap();
+
+        let route = |url: &str| client.route(&client.agent.get(url)).unwrap();
+
+        let (host, path, limiter) = route("https://api.example.com:8443/v1/memes?page=2");
+        assert_eq!(host, "api.example.com");
+        assert_eq!(path, "/v1/memes");
+        assert!(std::ptr::eq(
+            limiter.unwrap(),
+            &client.limiters["api.example.com"]
+        ));
+
+        let (_, _, limiter) = route("https://example.com/");
+        assert!(std::ptr::eq(
+            limiter.unwrap(),
+            &client.limiters["example.com"]
+        ));
+
+        let (_, _, limiter) = route("https://cdn.example.com/");
+        assert!(limiter.is_none());
+    }
+}
@@ -1,11 +1,21 @@
 #[macro_use]
 extern crate fix_hidden_lifetime_bug;
 
+pub mod audit_log;
+pub mod birthdays;
+pub mod clock;
 pub mod config;
 pub mod discord;
 pub mod extensions;
+pub mod feature_flags;
 pub mod functions;
+pub mod http;
+pub mod logging;
 pub mod macros;
+pub mod rate_limiter;
 pub mod serializers;
 pub mod streams;
+#[cfg(test)]
+mod test_support;
+pub mod tweet_subscriptions;
 pub mod types;
@@ -0,0 +1,119 @@
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use once_cell::sync::OnceCell;
+
+type ReloadFn = Box<dyn Fn(&str) -> anyhow::Result<()> + Send + Sync>;
+
+static FILTER: OnceCell<LogFilter> = OnceCell::new();
+
+/// Lets the log filter be changed at runtime, by whichever subscriber the binary installed.
+struct LogFilter {
+    reload: ReloadFn,
+    default: String,
+    directives: Mutex<String>,
+}
+
+/// Registers how the installed subscriber applies new filter directives,
+/// this has to happen before the filter can be changed.
+pub fn init<F>(directives: String, reload: F) -> anyhow::Result<()>
+where
+    F: Fn(&str) -> anyhow::Result<()> + Send + Sync + 'static,
+{
+    FILTER
+        .set(LogFilter {
+            reload: Box::new(reload),
+            default: directives.clone(),
+            directives: Mutex::new(directives),
+        })
+        .map_err(|_| anyhow!("Log filter has already been initialized!"))
+}
+
+/// Replaces the log filter, using the same syntax as `RUST_LOG`.
+pub fn set_filter(directives: &str) -> anyhow::Result<()> {
+    let filter = FILTER
+        .get()
+        .ok_or_else(|| anyhow!("Log filter can't be changed at runtime."))?;
+
+    (filter.reload)(directives)?;
+
+    if let Ok(mut current) = filter.directives.lock() {
+        *current = directives.to_owned();
+    }
+
+    Ok(())
+}
+
+/// Changes the level of a single target, such as `apis::holo_api`, keeping the rest of the filter.
+pub fn set_target_level(target: &str, level: &str) -> anyhow::Result<String> {
+    let current =
+        current_filter().ok_or_else(|| anyhow!("Log filter can't be changed at runtime."))?;
+    let directives = with_target_level(&current, target, level);
+
+    set_filter(&directives)?;
+    Ok(directives)
+}
+
+/// Goes back to the filter the subscriber was installed with.
+pub fn reset_filter() -> anyhow::Result<String> {
+    let default = FILTER
+        .get()
+        .map(|f| f.default.clone())
+        .ok_or_else(|| anyhow!("Log filter can't be changed at runtime."))?;
+
+    set_filter(&default)?;
+    Ok(default)
+}
+
+/// The directives of the current log filter, if it can be changed at runtime.
+pub fn current_filter() -> Option<String> {
+    FILTER
+        .get()
+        .and_then(|f| f.directives.lock().ok().map(|d| d.clone()))
+}
+
+/// Replaces any directive for the target with one at the given level.
+fn with_target_level(directives: &str, target: &str, level: &str) -> String {
+    let mut directives = directives
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty() && d.split_once('=').map_or(true, |(t, _)| t != target))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+
+    directives.push(format!("{target}={level}"));
+    directives.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_level_is_appended() {
+        assert_eq!(
+            with_target_level("info,serenity=warn", "apis::holo_api", "trace"),
+            "info,serenity=warn,apis::holo_api=trace"
+        );
+    }
+
+    #[test]
+    fn target_level_replaces_existing_directive() {
+        assert_eq!(
+            with_target_level(
+                "info,apis::holo_api=debug,serenity=warn",
+                "apis::holo_api",
+                "trace"
+            ),
+            "info,serenity=warn,apis::holo_api=trace"
+        );
+    }
+
+    #[test]
+    fn global_level_is_kept() {
+        assert_eq!(
+            with_target_level("debug", "hyper", "info"),
+            "debug,hyper=info"
+        );
+    }
+}
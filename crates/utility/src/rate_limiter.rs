@@ -0,0 +1,62 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::Mutex,
+    time::{sleep, Instant},
+};
+
+/// A token bucket that can be cloned and shared between tasks using the same API.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Allows bursts of up to `requests`, refilling at `requests` per `period`.
+    #[must_use]
+    pub fn new(requests: u32, period: Duration) -> Self {
+        let capacity = f64::from(requests.max(1));
+
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                capacity,
+                tokens: capacity,
+                refill_per_second: capacity / period.as_secs_f64(),
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// Waits until a request is allowed to be made.
+    pub async fn acquire(&self) {
+        // Holding the lock while sleeping makes waiters line up in order.
+        let mut bucket = self.bucket.lock().await;
+        bucket.refill();
+
+        if bucket.tokens < 1.0 {
+            let missing = 1.0 - bucket.tokens;
+            sleep(Duration::from_secs_f64(missing / bucket.refill_per_second)).await;
+            bucket.refill();
+        }
+
+        bucket.tokens -= 1.0;
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = (now - self.last_refill).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+    }
+}
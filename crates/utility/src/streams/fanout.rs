@@ -0,0 +1,273 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use holodex::model::{id::VideoId, VideoStatus};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, warn};
+
+use super::{Livestream, StreamUpdate};
+use crate::config::UpdateQueueConfig;
+
+/// Sends stream updates to every subscriber through a bounded queue of its own, so that
+/// a slow subscriber can't make the others miss updates. A subscriber whose queue fills up
+/// is told to resync from the stream index instead, since updates were dropped.
+#[derive(Debug, Clone)]
+pub struct StreamUpdateFanout {
+    config: UpdateQueueConfig,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    name: &'static str,
+    sender: mpsc::Sender<StreamUpdate>,
+    lagged: Arc<AtomicBool>,
+}
+
+impl StreamUpdateFanout {
+    #[must_use]
+    pub fn new(config: UpdateQueueConfig) -> Self {
+        Self {
+            config,
+            subscribers: Arc::default(),
+        }
+    }
+
+    /// Subscribes to the updates, with the queue capacity configured for `name`.
+    pub fn subscribe(&self, name: &'static str) -> UpdateSubscription {
+        let capacity = self.config.capacity(name);
+        let (sender, receiver) = mpsc::channel(capacity);
+        let lagged = Arc::new(AtomicBool::new(false));
+
+        self.subscribers().push(Subscriber {
+            name,
+            sender,
+            lagged: Arc::clone(&lagged),
+        });
+
+        debug!(subscriber = name, capacity, "Subscribed to stream updates.");
+
+        UpdateSubscription {
+            name,
+            receiver,
+            lagged,
+        }
+    }
+
+    #[must_use]
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers().len()
+    }
+
+    /// Queues the update for every subscriber, without waiting for the ones that are behind.
+    pub fn send(&self, update: StreamUpdate) {
+        self.subscribers().retain(
+            |subscriber| match subscriber.sender.try_send(update.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    if !subscriber.lagged.swap(true, Ordering::AcqRel) {
+                        warn!(
+                            subscriber = subscriber.name,
+                            "Stream update queue is full, dropping updates until it resyncs."
+                        );
+                    }
+
+                    true
+                }
+                Err(TrySendError::Closed(_)) => {
+                    debug!(
+                        subscriber = subscriber.name,
+                        "Stream update subscriber closed."
+                    );
+                    false
+                }
+            },
+        );
+    }
+
+    fn subscribers(&self) -> MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Received {
+    Update(StreamUpdate),
+    /// Updates were dropped while the subscriber was behind, so whatever it keeps track of
+    /// has to be rebuilt from the stream index.
+    Resync,
+}
+
+#[derive(Debug)]
+pub struct UpdateSubscription {
+    name: &'static str,
+    receiver: mpsc::Receiver<StreamUpdate>,
+    lagged: Arc<AtomicBool>,
+}
+
+impl UpdateSubscription {
+    /// Waits for the next update, returning `None` once the fan-out has been dropped.
+    pub async fn recv(&mut self) -> Option<Received> {
+        if self.take_lag() {
+            return Some(Received::Resync);
+        }
+
+        let update = self.receiver.recv().await?;
+
+        // The queue might have filled up while this update was waiting in it.
+        if self.take_lag() {
+            return Some(Received::Resync);
+        }
+
+        Some(Received::Update(update))
+    }
+
+    fn take_lag(&mut self) -> bool {
+        if !self.lagged.swap(false, Ordering::AcqRel) {
+            return false;
+        }
+
+        // Everything still queued is older than the index that's resynced from.
+        let mut dropped = 0;
+
+        while self.receiver.try_recv().is_ok() {
+            dropped += 1;
+        }
+
+        warn!(
+            subscriber = self.name,
+            dropped, "Stream updates lagged, resyncing from the index."
+        );
+
+        true
+    }
+}
+
+/// The updates that bring a subscriber up to date with the index, given the streams it knows
+/// about and the status it last saw them in. Renames and reschedules can't be recovered,
+/// since only the statuses are compared.
+pub fn resync_updates<'a>(
+    index: &HashMap<VideoId, Livestream>,
+    known: impl IntoIterator<Item = (&'a VideoId, VideoStatus)>,
+) -> Vec<StreamUpdate> {
+    let known = known.into_iter().collect::<HashMap<_, _>>();
+    let mut updates = Vec::new();
+
+    for (&id, &status) in &known {
+        let stream = index.get(id);
+
+        match (status, stream.map(|s| s.state)) {
+            (VideoStatus::Upcoming, Some(VideoStatus::Upcoming))
+            | (VideoStatus::Live, Some(VideoStatus::Live)) => (),
+            (VideoStatus::Upcoming, Some(VideoStatus::Live)) => {
+                updates.extend(stream.cloned().map(StreamUpdate::Started));
+            }
+            // The whole stream happened while the subscriber was behind.
+            (VideoStatus::Upcoming, Some(VideoStatus::Past)) => {
+                updates.extend(stream.cloned().map(StreamUpdate::Started));
+                updates.push(StreamUpdate::Ended(id.clone()));
+            }
+            (VideoStatus::Upcoming, _) => updates.push(StreamUpdate::Unscheduled(id.clone())),
+            (VideoStatus::Live, _) => updates.push(StreamUpdate::Ended(id.clone())),
+            _ => (),
+        }
+    }
+
+    for stream in index.values().filter(|s| !known.contains_key(&s.id)) {
+        match stream.state {
+            VideoStatus::Upcoming => updates.push(StreamUpdate::Scheduled(stream.clone())),
+            VideoStatus::Live => updates.push(StreamUpdate::Started(stream.clone())),
+            _ => (),
+        }
+    }
+
+    updates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn fanout(capacity: usize) -> StreamUpdateFanout {
+        StreamUpdateFanout::new(UpdateQueueConfig {
+            default_capacity: capacity,
+            capacities: HashMap::new(),
+        })
+    }
+
+    /// Video IDs are 11 characters long.
+    fn ended(n: u32) -> StreamUpdate {
+        StreamUpdate::Ended(format!("video{n:06}").parse().unwrap())
+    }
+
+    #[test]
+    fn every_subscriber_gets_every_update() {
+        let fanout = fanout(4);
+        let mut first = fanout.subscribe("first");
+        let mut second = fanout.subscribe("second");
+
+        fanout.send(ended(1));
+        fanout.send(ended(2));
+
+        for subscription in [&mut first, &mut second] {
+            for expected in ["video000001", "video000002"] {
+                match block_on(subscription.recv()) {
+                    Some(Received::Update(StreamUpdate::Ended(id))) => {
+                        assert_eq!(id.to_string(), expected);
+                    }
+                    other => panic!("Unexpected update: {other:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_queues_resync_without_affecting_others() {
+        let fanout = fanout(2);
+        let mut slow = fanout.subscribe("slow");
+        let mut fast = fanout.subscribe("fast");
+
+        for n in 1..=3 {
+            fanout.send(ended(n));
+            assert!(matches!(block_on(fast.recv()), Some(Received::Update(_))));
+        }
+
+        assert!(matches!(block_on(slow.recv()), Some(Received::Resync)));
+
+        // The dropped updates aren't delivered after the resync.
+        fanout.send(ended(4));
+
+        match block_on(slow.recv()) {
+            Some(Received::Update(StreamUpdate::Ended(id))) => {
+                assert_eq!(id.to_string(), "video000004");
+            }
+            other => panic!("Unexpected update: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn closed_subscribers_are_dropped() {
+        let fanout = fanout(2);
+        let subscription = fanout.subscribe("closed");
+        let _open = fanout.subscribe("open");
+
+        drop(subscription);
+        fanout.send(ended(1));
+
+        assert_eq!(fanout.subscriber_count(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use holodex::model::id::VideoId;
+use once_cell::sync::Lazy;
+use strum::Display;
+use tracing::{error, info};
+
+/// Latencies of every stream going live since the bot started, shared between the
+/// stream indexer, the alert poster and the `/debug latency` command.
+pub static STREAM_LATENCY: Lazy<LatencyTracker> = Lazy::new(LatencyTracker::default);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum LatencyKind {
+    /// From a stream actually going live until Holodex reported it as live.
+    #[strum(serialize = "Live detection")]
+    Detection,
+    /// From the scheduled start of a stream until its alert was posted.
+    #[strum(serialize = "Alert posting")]
+    Alert,
+}
+
+#[derive(Debug, Clone)]
+pub struct LatencySample {
+    pub stream: VideoId,
+    pub latency: Duration,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p90: Duration,
+    pub max: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    samples: Mutex<HashMap<LatencyKind, VecDeque<LatencySample>>>,
+}
+
+impl LatencyTracker {
+    const MAX_SAMPLES: usize = 100;
+
+    /// Records how long after `expected_at` the bot reacted to a stream going live.
+    /// Only the first reaction to each stream is recorded.
+    pub fn record(
+        &self,
+        kind: LatencyKind,
+        stream: &VideoId,
+        expected_at: DateTime<Utc>,
+        reacted_at: DateTime<Utc>,
+    ) {
+        let mut samples = match self.samples.lock() {
+            Ok(samples) => samples,
+            Err(e) => {
+                error!("Latency samples lock was poisoned: {:?}", e);
+                return;
+            }
+        };
+
+        let samples = samples.entry(kind).or_default();
+
+        if samples.iter().any(|s| s.stream == *stream) {
+            return;
+        }
+
+        let latency = (reacted_at - expected_at).max(Duration::zero());
+
+        info!(
+            target: "metrics",
+            %kind,
+            %stream,
+            latency_ms = latency.num_milliseconds(),
+            "Stream latency recorded."
+        );
+
+        samples.push_back(LatencySample {
+            stream: stream.clone(),
+            latency,
+            recorded_at: reacted_at,
+        });
+
+        while samples.len() > Self::MAX_SAMPLES {
+            samples.pop_front();
+        }
+    }
+
+    pub fn contains(&self, kind: LatencyKind, stream: &VideoId) -> bool {
+        self.samples
+            .lock()
+            .ok()
+            .and_then(|s| s.get(&kind).map(|s| s.iter().any(|s| s.stream == *stream)))
+            .unwrap_or(false)
+    }
+
+    /// The most recent samples, newest first.
+    pub fn recent(&self, kind: LatencyKind, count: usize) -> Vec<LatencySample> {
+        self.samples
+            .lock()
+            .ok()
+            .and_then(|s| {
+                s.get(&kind)
+                    .map(|s| s.iter().rev().take(count).cloned().collect())
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn summary(&self, kind: LatencyKind) -> Option<LatencySummary> {
+        let mut latencies = self
+            .samples
+            .lock()
+            .ok()?
+            .get(&kind)?
+            .iter()
+            .map(|s| s.latency)
+            .collect::<Vec<_>>();
+
+        LatencySummary::from_latencies(&mut latencies)
+    }
+}
+
+impl LatencySummary {
+    fn from_latencies(latencies: &mut [Duration]) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+
+        latencies.sort_unstable();
+
+        let samples = latencies.len();
+        let total = latencies.iter().fold(Duration::zero(), |acc, l| acc + *l);
+        let percentile = |p: usize| latencies[((samples - 1) * p) / 100];
+
+        Some(Self {
+            samples,
+            mean: total / samples as i32,
+            median: percentile(50),
+            p90: percentile(90),
+            max: latencies[samples - 1],
+        })
+    }
+}
@@ -1,3 +1,7 @@
+mod fanout;
+mod latency;
 mod types;
 
+pub use fanout::*;
+pub use latency::*;
 pub use types::*;
@@ -12,12 +12,20 @@ pub struct Livestream {
     pub thumbnail: String,
     pub url: String,
     pub streamer: Talent,
+    /// Other tracked talents appearing in the stream, if it's a collab.
+    pub collaborators: Vec<Talent>,
 
     pub created_at: DateTime<Utc>,
     pub start_at: DateTime<Utc>,
 
     pub duration: Option<Duration>,
     pub state: VideoStatus,
+    /// The Holodex topic of the stream, such as `singing` or `Minecraft`.
+    pub topic: Option<String>,
+    /// Only members of the channel can watch the stream.
+    pub members_only: bool,
+
+    pub viewers: ViewerStats,
 }
 
 impl Livestream {
@@ -25,6 +33,7 @@ impl Livestream {
         let id = video.id.clone();
         let thumbnail = format!("https://i3.ytimg.com/vi/{}/maxresdefault.jpg", &video.id);
         let url = format!("https://youtube.com/watch?v={}", &video.id);
+        let members_only = is_members_only(video.topic.as_deref(), &video.title);
 
         Livestream {
             id,
@@ -39,12 +48,83 @@ impl Livestream {
                 .duration
                 .and_then(|d| if d.is_zero() { None } else { Some(d) }),
             streamer: talent.clone(),
+            collaborators: Vec::new(),
             state: video.status,
+            topic: video.topic,
+            members_only,
             url,
+            viewers: ViewerStats::default(),
         }
     }
 }
 
+impl Livestream {
+    /// Renames the stream, checking the new title for member-only labels.
+    pub fn set_title(&mut self, title: String) {
+        self.members_only = is_members_only(self.topic.as_deref(), &title);
+        self.title = title;
+    }
+
+    /// Changes the topic of the stream, which can mark it as member-only.
+    pub fn set_topic(&mut self, topic: Option<String>) {
+        self.members_only = is_members_only(topic.as_deref(), &self.title);
+        self.topic = topic;
+    }
+
+    pub fn is_collab(&self) -> bool {
+        !self.collaborators.is_empty()
+    }
+
+    /// Every tracked talent participating in the stream, starting with the host.
+    pub fn participants(&self) -> impl Iterator<Item = &Talent> {
+        std::iter::once(&self.streamer).chain(self.collaborators.iter())
+    }
+
+    /// Whether the stream is on the topic, ignoring case.
+    pub fn has_topic(&self, topic: &str) -> bool {
+        self.topic
+            .as_deref()
+            .map_or(false, |t| t.eq_ignore_ascii_case(topic))
+    }
+
+    /// The topic of the stream as it's shown to users, such as `Singing` for `singing`.
+    pub fn topic_name(&self) -> Option<String> {
+        topic_name(self.topic.as_deref()?)
+    }
+}
+
+fn topic_name(topic: &str) -> Option<String> {
+    let name = topic
+        .split('_')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map_or_else(String::new, |c| {
+                c.to_uppercase().chain(chars).collect::<String>()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Holodex tags member-only streams with a topic, but the topic is often set late,
+/// so the title is checked for the usual labels as well.
+fn is_members_only(topic: Option<&str>, title: &str) -> bool {
+    const TITLE_LABELS: [&str; 5] = [
+        "members only",
+        "member only",
+        "members-only",
+        "メン限",
+        "メンバー限定",
+    ];
+
+    let title = title.to_lowercase();
+
+    topic == Some("membersonly") || TITLE_LABELS.iter().any(|l| title.contains(l))
+}
+
 impl Display for Livestream {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -61,7 +141,41 @@ impl PartialEq for Livestream {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Concurrent viewer counts sampled while a stream is live.
+#[derive(Debug, Clone, Default)]
+pub struct ViewerStats {
+    samples: Vec<(DateTime<Utc>, u32)>,
+}
+
+impl ViewerStats {
+    pub fn record(&mut self, at: DateTime<Utc>, viewers: u32) {
+        self.samples.push((at, viewers));
+    }
+
+    pub fn samples(&self) -> &[(DateTime<Utc>, u32)] {
+        &self.samples
+    }
+
+    /// The most recent sample, and when it was taken.
+    pub fn latest(&self) -> Option<(DateTime<Utc>, u32)> {
+        self.samples.last().copied()
+    }
+
+    pub fn peak(&self) -> Option<u32> {
+        self.samples.iter().map(|(_, v)| *v).max()
+    }
+
+    pub fn average(&self) -> Option<u32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let total: u64 = self.samples.iter().map(|(_, v)| u64::from(*v)).sum();
+        u32::try_from(total / self.samples.len() as u64).ok()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
 pub enum StreamState {
     Scheduled,
     Live,
@@ -77,3 +191,61 @@ pub enum StreamUpdate {
     Renamed(VideoId, String),
     Rescheduled(VideoId, DateTime<Utc>),
 }
+
+/// Manual interventions in the stream index, for when Holodex data is stuck.
+#[derive(Debug, Clone)]
+pub enum IndexCommand {
+    /// Poll Holodex right away instead of waiting for the next update.
+    Refresh,
+    /// Move a stream into a state, as if Holodex had reported it.
+    SetState(VideoId, StreamState),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members_only_streams_are_detected() {
+        assert!(is_members_only(Some("membersonly"), "Chatting"));
+        assert!(is_members_only(None, "【Members Only】Karaoke"));
+        assert!(is_members_only(None, "【メン限】雑談"));
+        assert!(!is_members_only(Some("singing"), "Karaoke with chat"));
+    }
+
+    #[test]
+    fn member_only_labels_added_later_are_picked_up() {
+        let mut stream = Livestream {
+            id: "dQw4w9WgXcQ".parse().unwrap(),
+            title: "Karaoke".to_owned(),
+            thumbnail: String::new(),
+            url: String::new(),
+            streamer: Talent::from(crate::config::TalentConfigData::default()),
+            collaborators: Vec::new(),
+            created_at: Utc::now(),
+            start_at: Utc::now(),
+            duration: None,
+            state: VideoStatus::Upcoming,
+            topic: None,
+            members_only: false,
+            viewers: ViewerStats::default(),
+        };
+
+        stream.set_topic(Some("membersonly".to_owned()));
+        assert!(stream.members_only);
+
+        stream.set_topic(Some("singing".to_owned()));
+        assert!(!stream.members_only);
+
+        stream.set_title("【Members Only】Karaoke".to_owned());
+        assert!(stream.members_only);
+    }
+
+    #[test]
+    fn topic_names_are_capitalized() {
+        assert_eq!(topic_name("singing").as_deref(), Some("Singing"));
+        assert_eq!(topic_name("Minecraft").as_deref(), Some("Minecraft"));
+        assert_eq!(topic_name("3D_Stream").as_deref(), Some("3D Stream"));
+        assert_eq!(topic_name("_").as_deref(), None);
+    }
+}
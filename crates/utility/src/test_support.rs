@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+
+use crate::config::DatabaseHandle;
+
+/// A database file that is removed when the test is done with it, to test that stores
+/// keep their data when they're loaded again.
+pub(crate) struct TempDatabase {
+    path: PathBuf,
+}
+
+impl TempDatabase {
+    /// Starts from an empty file, since a test that failed before may have left one behind.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{name}_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        Self { path }
+    }
+
+    pub fn open(&self) -> DatabaseHandle {
+        DatabaseHandle::SQLite(Connection::open(&self.path).unwrap())
+    }
+}
+
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, RwLock},
+};
+
+use anyhow::{anyhow, Context};
+use once_cell::sync::OnceCell;
+use serenity::model::id::UserId;
+
+use crate::{
+    config::{Database, DatabaseHandle},
+    here,
+};
+
+static SUBSCRIPTIONS: OnceCell<TweetSubscriptions> = OnceCell::new();
+
+pub fn init(database: &Database) -> anyhow::Result<()> {
+    let handle = database.get_handle().context(here!())?;
+
+    SUBSCRIPTIONS
+        .set(TweetSubscriptions::load(handle).context(here!())?)
+        .map_err(|_| anyhow!("Tweet subscriptions have already been initialized!"))
+}
+
+pub fn store() -> anyhow::Result<&'static TweetSubscriptions> {
+    SUBSCRIPTIONS
+        .get()
+        .ok_or_else(|| anyhow!("Tweet subscriptions have not been initialized!"))
+}
+
+/// The members who want the tweets of a talent sent to them by DM, by talent name.
+pub struct TweetSubscriptions {
+    database: Mutex<DatabaseHandle>,
+    subscribers: RwLock<HashMap<String, HashSet<UserId>>>,
+}
+
+impl TweetSubscriptions {
+    const TABLE_NAME: &'static str = "TweetSubscriptions";
+
+    pub fn load(handle: DatabaseHandle) -> anyhow::Result<Self> {
+        handle
+            .create_table(
+                Self::TABLE_NAME,
+                &[
+                    ("user_id", "INTEGER", Some("NOT NULL")),
+                    ("talent", "TEXT", Some("NOT NULL")),
+                ],
+            )
+            .context(here!())?;
+
+        let mut subscribers: HashMap<String, HashSet<UserId>> = HashMap::new();
+
+        match &handle {
+            DatabaseHandle::SQLite(h) => {
+                let mut stmt = h
+                    .prepare(&format!("SELECT user_id, talent FROM {}", Self::TABLE_NAME))
+                    .context(here!())?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, u64>(0)?, row.get::<_, String>(1)?))
+                    })
+                    .context(here!())?;
+
+                for row in rows {
+                    let (user, talent) = row.context(here!())?;
+                    subscribers.entry(talent).or_default().insert(UserId(user));
+                }
+            }
+        }
+
+        Ok(Self {
+            database: Mutex::new(handle),
+            subscribers: RwLock::new(subscribers),
+        })
+    }
+
+    #[must_use]
+    pub fn subscribers(&self, talent: &str) -> Vec<UserId> {
+        self.subscribers
+            .read()
+            .ok()
+            .and_then(|s| s.get(talent).map(|users| users.iter().copied().collect()))
+            .unwrap_or_default()
+    }
+
+    /// The talents the user is subscribed to, sorted by name.
+    #[must_use]
+    pub fn subscriptions(&self, user: UserId) -> Vec<String> {
+        let mut talents = self
+            .subscribers
+            .read()
+            .map(|s| {
+                s.iter()
+                    .filter(|(_, users)| users.contains(&user))
+                    .map(|(talent, _)| talent.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        talents.sort_unstable();
+        talents
+    }
+
+    /// Subscribes the user to the talent, returning whether they weren't already.
+    pub fn subscribe(&self, user: UserId, talent: &str) -> anyhow::Result<bool> {
+        let mut subscribers = self
+            .subscribers
+            .write()
+            .map_err(|_| anyhow!("Tweet subscription lock was poisoned!"))?;
+
+        if subscribers
+            .get(talent)
+            .map_or(false, |users| users.contains(&user))
+        {
+            return Ok(false);
+        }
+
+        self.execute(
+            &format!(
+                "INSERT INTO {} (user_id, talent) VALUES (?, ?)",
+                Self::TABLE_NAME
+            ),
+            user,
+            talent,
+        )?;
+
+        subscribers
+            .entry(talent.to_owned())
+            .or_default()
+            .insert(user);
+
+        Ok(true)
+    }
+
+    /// Unsubscribes the user from the talent, returning whether they were subscribed.
+    pub fn unsubscribe(&self, user: UserId, talent: &str) -> anyhow::Result<bool> {
+        let mut subscribers = self
+            .subscribers
+            .write()
+            .map_err(|_| anyhow!("Tweet subscription lock was poisoned!"))?;
+
+        let removed = subscribers
+            .get_mut(talent)
+            .map_or(false, |users| users.remove(&user));
+
+        if removed {
+            self.execute(
+                &format!(
+                    "DELETE FROM {} WHERE user_id = ? AND talent = ?",
+                    Self::TABLE_NAME
+                ),
+                user,
+                talent,
+            )?;
+        }
+
+        Ok(removed)
+    }
+
+    fn execute(&self, query: &str, user: UserId, talent: &str) -> anyhow::Result<()> {
+        let handle = self
+            .database
+            .lock()
+            .map_err(|_| anyhow!("Tweet subscription database lock was poisoned!"))?;
+
+        match &*handle {
+            DatabaseHandle::SQLite(h) => {
+                h.execute(query, rusqlite::params![user.0, talent])
+                    .context(here!())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::TempDatabase;
+
+    use super::*;
+
+    #[test]
+    fn subscriptions_are_persisted() {
+        let database = TempDatabase::new("tweet_subscriptions");
+
+        let store = TweetSubscriptions::load(database.open()).unwrap();
+
+        assert!(store.subscribe(UserId(1), "Pekora").unwrap());
+        assert!(!store.subscribe(UserId(1), "Pekora").unwrap());
+        assert!(store.subscribe(UserId(1), "Miko").unwrap());
+        assert!(store.subscribe(UserId(2), "Pekora").unwrap());
+
+        let reloaded = TweetSubscriptions::load(database.open()).unwrap();
+
+        assert_eq!(reloaded.subscriptions(UserId(1)), vec!["Miko", "Pekora"]);
+
+        let mut subscribers = reloaded.subscribers("Pekora");
+        subscribers.sort_unstable();
+        assert_eq!(subscribers, vec![UserId(1), UserId(2)]);
+
+        assert!(reloaded.unsubscribe(UserId(1), "Pekora").unwrap());
+        assert!(!reloaded.unsubscribe(UserId(1), "Pekora").unwrap());
+
+        assert_eq!(
+            TweetSubscriptions::load(database.open())
+                .unwrap()
+                .subscribers("Pekora"),
+            vec![UserId(2)]
+        );
+    }
+}
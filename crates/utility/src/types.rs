@@ -30,3 +30,13 @@ pub enum Service {
     #[name = "Twitter Feed"]
     TwitterFeed,
 }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Cache {
+    #[name = "Tweet replies"]
+    TweetReplies,
+    #[name = "Webhooks"]
+    Webhooks,
+    #[name = "Meme templates"]
+    MemeTemplates,
+}